@@ -0,0 +1,43 @@
+// cargo bench --bench compressed_audio_benchmark
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use screenpipe_audio::pcm_decode::pcm_decode;
+use screenpipe_audio::{encode_single_audio, read_audio_file, AudioFormat};
+use std::path::PathBuf;
+
+/// Read latency of a chunk stored as plain wav and as wav+zstd (`--compress-audio`).
+fn benchmark_compressed_audio(c: &mut Criterion) {
+    let source = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("test_data")
+        .join("accuracy1.wav");
+    let (samples, sample_rate) = pcm_decode(&source).unwrap();
+    let data: Vec<u8> = samples.iter().flat_map(|s| s.to_le_bytes()).collect();
+
+    let dir = tempfile::tempdir().unwrap();
+    let compressed = dir.path().join("chunk.wav.zst");
+    encode_single_audio(&data, sample_rate, 1, &compressed, AudioFormat::WavZstd).unwrap();
+    let wav = dir.path().join("chunk.wav");
+    std::fs::write(&wav, read_audio_file(&compressed).unwrap()).unwrap();
+    println!(
+        "wav {} bytes, wav+zstd {} bytes",
+        std::fs::metadata(&wav).unwrap().len(),
+        std::fs::metadata(&compressed).unwrap().len()
+    );
+
+    let mut group = c.benchmark_group("audio_read");
+    group.bench_function("read_wav", |b| {
+        b.iter(|| read_audio_file(black_box(&wav)).unwrap())
+    });
+    group.bench_function("read_wav_zstd", |b| {
+        b.iter(|| read_audio_file(black_box(&compressed)).unwrap())
+    });
+    group.bench_function("decode_wav", |b| {
+        b.iter(|| pcm_decode(black_box(&wav)).unwrap())
+    });
+    group.bench_function("decode_wav_zstd", |b| {
+        b.iter(|| pcm_decode(black_box(&compressed)).unwrap())
+    });
+    group.finish();
+}
+
+criterion_group!(benches, benchmark_compressed_audio);
+criterion_main!(benches);
@@ -24,6 +24,11 @@ async fn setup_test() -> (
         None,
         &output_path,
         VadSensitivity::High,
+        false,
+        false,
+        screenpipe_audio::AudioFormat::Mp4,
+        Duration::ZERO,
+        false,
     )
     .await
     .unwrap();
@@ -0,0 +1,277 @@
+//! Audio of a single app on macos 14 and later, `--capture-audio-from-app`: ScreenCaptureKit
+//! streams what one app plays, without the rest of the system audio a loopback device records.
+
+use crate::core::{AudioDevice, DeviceType};
+
+#[cfg(target_os = "macos")]
+pub use capture::{app_running, record_app_audio};
+
+/// Rate ScreenCaptureKit is asked to deliver app audio at
+pub const APP_AUDIO_SAMPLE_RATE: u32 = 48000;
+
+/// First macos release whose ScreenCaptureKit filters audio by app
+pub const MIN_APP_AUDIO_MACOS: u32 = 14;
+
+const APP_AUDIO_PREFIX: &str = "app audio ";
+
+/// The name carries the bundle id, e.g. `app audio com.spotify.client (output)`.
+pub fn app_audio_device(bundle_id: &str) -> AudioDevice {
+    AudioDevice::new(
+        format!("{}{}", APP_AUDIO_PREFIX, bundle_id),
+        DeviceType::Output,
+    )
+}
+
+/// Bundle id of a device made by [`app_audio_device`], `None` for real devices.
+pub fn app_bundle_id(audio_device: &AudioDevice) -> Option<&str> {
+    audio_device
+        .name
+        .strip_prefix(APP_AUDIO_PREFIX)
+        .filter(|bundle_id| !bundle_id.is_empty())
+}
+
+/// Major version of a `sw_vers -productVersion` output such as `14.2.1`.
+pub fn parse_macos_major_version(version: &str) -> Option<u32> {
+    version.trim().split('.').next()?.parse().ok()
+}
+
+/// Whether this machine can record the audio of a single app.
+pub fn app_audio_supported() -> bool {
+    #[cfg(target_os = "macos")]
+    {
+        std::process::Command::new("sw_vers")
+            .arg("-productVersion")
+            .output()
+            .ok()
+            .and_then(|output| parse_macos_major_version(&String::from_utf8_lossy(&output.stdout)))
+            .is_some_and(|major| major >= MIN_APP_AUDIO_MACOS)
+    }
+    #[cfg(not(target_os = "macos"))]
+    {
+        false
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod capture {
+    use super::{app_bundle_id, APP_AUDIO_SAMPLE_RATE};
+    use crate::core::AudioDevice;
+    use crate::AudioInput;
+    use anyhow::{anyhow, Result};
+    use lazy_static::lazy_static;
+    use log::{error, info};
+    use screencapturekit::{
+        cm_sample_buffer::CMSampleBuffer,
+        sc_content_filter::{InitParams, SCContentFilter},
+        sc_error_handler::StreamErrorHandler,
+        sc_output_handler::{SCStreamOutputType, StreamOutput},
+        sc_shareable_content::SCShareableContent,
+        sc_stream::SCStream,
+        sc_stream_configuration::SCStreamConfiguration,
+    };
+    use std::collections::HashMap;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::{Arc, Mutex};
+    use std::time::{Duration, Instant, SystemTime};
+
+    struct SampleCollector {
+        samples: Arc<Mutex<Vec<f32>>>,
+    }
+
+    impl StreamOutput for SampleCollector {
+        fn did_output_sample_buffer(&self, sample: CMSampleBuffer, of_type: SCStreamOutputType) {
+            if !matches!(of_type, SCStreamOutputType::Audio) {
+                return;
+            }
+            // the stream is mono, its one buffer holds f32 samples
+            let Some(buffer) = sample.sys_ref.get_av_audio_buffer_list().into_iter().next() else {
+                return;
+            };
+            self.samples.lock().unwrap().extend(
+                buffer
+                    .data
+                    .chunks_exact(4)
+                    .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]])),
+            );
+        }
+    }
+
+    struct ErrorLogger {
+        failed: Arc<AtomicBool>,
+    }
+
+    impl StreamErrorHandler for ErrorLogger {
+        fn on_error(&self) {
+            error!("app audio capture stopped with an error");
+            self.failed.store(true, Ordering::Relaxed);
+        }
+    }
+
+    /// Capture of one app kept running across chunks, the `SCStream` lives on its own thread.
+    struct AppStream {
+        samples: Arc<Mutex<Vec<f32>>>,
+        stop: Arc<AtomicBool>,
+        failed: Arc<AtomicBool>,
+    }
+
+    lazy_static! {
+        static ref STREAMS: Mutex<HashMap<String, Arc<AppStream>>> = Mutex::new(HashMap::new());
+    }
+
+    /// Whether `bundle_id` is among the apps ScreenCaptureKit can capture right now, blocking.
+    pub fn app_running(bundle_id: &str) -> bool {
+        SCShareableContent::current()
+            .applications
+            .iter()
+            .any(|app| app.bundle_identifier.as_deref() == Some(bundle_id))
+    }
+
+    fn start_capture(bundle_id: &str, stream: &AppStream) -> Result<SCStream> {
+        let content = SCShareableContent::current();
+        let app = content
+            .applications
+            .into_iter()
+            .find(|app| app.bundle_identifier.as_deref() == Some(bundle_id))
+            .ok_or_else(|| anyhow!("app {} is not running", bundle_id))?;
+        // audio filters hang off a display, the video of the stream is not used
+        let display = content
+            .displays
+            .into_iter()
+            .next()
+            .ok_or_else(|| anyhow!("no display to capture the audio of {} on", bundle_id))?;
+        let filter =
+            SCContentFilter::new(InitParams::DisplayIncludingApplicationsExceptingWindows(
+                display,
+                vec![app],
+                Vec::new(),
+            ));
+        let config = SCStreamConfiguration {
+            width: 2,
+            height: 2,
+            captures_audio: true,
+            sample_rate: APP_AUDIO_SAMPLE_RATE,
+            channel_count: 1,
+            ..Default::default()
+        };
+
+        let mut sc_stream = SCStream::new(
+            filter,
+            config,
+            ErrorLogger {
+                failed: stream.failed.clone(),
+            },
+        );
+        sc_stream.add_output(
+            SampleCollector {
+                samples: stream.samples.clone(),
+            },
+            SCStreamOutputType::Audio,
+        );
+        sc_stream
+            .start_capture()
+            .map_err(|e| anyhow!("failed to start audio capture of {}: {:?}", bundle_id, e))?;
+        Ok(sc_stream)
+    }
+
+    /// Starts capturing `bundle_id` on a thread that keeps the stream until `stop` is set,
+    /// blocking until the capture started.
+    fn spawn_capture(bundle_id: &str) -> Result<Arc<AppStream>> {
+        let stream = Arc::new(AppStream {
+            samples: Arc::new(Mutex::new(Vec::new())),
+            stop: Arc::new(AtomicBool::new(false)),
+            failed: Arc::new(AtomicBool::new(false)),
+        });
+        let (started_tx, started_rx) = std::sync::mpsc::channel();
+        let thread_stream = Arc::clone(&stream);
+        let thread_bundle_id = bundle_id.to_string();
+        std::thread::spawn(move || {
+            let sc_stream = match start_capture(&thread_bundle_id, &thread_stream) {
+                Ok(sc_stream) => {
+                    let _ = started_tx.send(Ok(()));
+                    sc_stream
+                }
+                Err(e) => {
+                    let _ = started_tx.send(Err(e));
+                    return;
+                }
+            };
+            while !thread_stream.stop.load(Ordering::Relaxed) {
+                std::thread::sleep(Duration::from_millis(100));
+            }
+            if let Err(e) = sc_stream.stop_capture() {
+                error!(
+                    "failed to stop audio capture of {}: {:?}",
+                    thread_bundle_id, e
+                );
+            }
+        });
+        started_rx
+            .recv()
+            .map_err(|_| anyhow!("audio capture of {} exited before starting", bundle_id))??;
+        Ok(stream)
+    }
+
+    /// The capture of `bundle_id`, started on first use and again after it failed, blocking.
+    fn app_stream(bundle_id: &str) -> Result<Arc<AppStream>> {
+        let mut streams = STREAMS.lock().unwrap();
+        if let Some(stream) = streams.get(bundle_id) {
+            if !stream.failed.load(Ordering::Relaxed) {
+                return Ok(Arc::clone(stream));
+            }
+            stream.stop.store(true, Ordering::Relaxed);
+            streams.remove(bundle_id);
+        }
+        let stream = spawn_capture(bundle_id)?;
+        streams.insert(bundle_id.to_string(), Arc::clone(&stream));
+        Ok(stream)
+    }
+
+    fn stop_app_stream(bundle_id: &str) {
+        if let Some(stream) = STREAMS.lock().unwrap().remove(bundle_id) {
+            stream.stop.store(true, Ordering::Relaxed);
+        }
+    }
+
+    /// Same contract as `record_and_transcribe`, for a device made by `app_audio_device`.
+    pub async fn record_app_audio(
+        audio_device: Arc<AudioDevice>,
+        duration: Duration,
+        whisper_sender: crossbeam::channel::Sender<AudioInput>,
+        is_running: Arc<AtomicBool>,
+    ) -> Result<()> {
+        let bundle_id = app_bundle_id(&audio_device)
+            .ok_or_else(|| anyhow!("{} is not an app audio device", audio_device))?
+            .to_string();
+        info!(
+            "Recording audio of {} for {} seconds",
+            bundle_id,
+            duration.as_secs()
+        );
+        let stream_bundle_id = bundle_id.clone();
+        let stream = tokio::task::spawn_blocking(move || app_stream(&stream_bundle_id)).await??;
+        let deadline = Instant::now() + duration;
+        while is_running.load(Ordering::Relaxed)
+            && !stream.failed.load(Ordering::Relaxed)
+            && Instant::now() < deadline
+        {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+        // what the app played since the previous chunk, the stream keeps running for the next
+        let data = std::mem::take(&mut *stream.samples.lock().unwrap());
+        if !is_running.load(Ordering::Relaxed) || stream.failed.load(Ordering::Relaxed) {
+            stop_app_stream(&bundle_id);
+        }
+        is_running.store(false, Ordering::Relaxed);
+
+        if let Err(e) = whisper_sender.send(AudioInput {
+            data: Arc::new(data),
+            device: audio_device,
+            sample_rate: APP_AUDIO_SAMPLE_RATE,
+            channels: 1,
+            end_time: SystemTime::now(),
+        }) {
+            error!("failed to send audio to audio model: {}", e);
+        }
+        Ok(())
+    }
+}
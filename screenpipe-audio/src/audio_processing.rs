@@ -1,3 +1,5 @@
+use std::time::SystemTime;
+
 pub fn normalize_v2(audio: &[f32]) -> Vec<f32> {
     let rms = (audio.iter().map(|&x| x * x).sum::<f32>() / audio.len() as f32).sqrt();
     let peak = audio
@@ -17,3 +19,80 @@ pub fn normalize_v2(audio: &[f32]) -> Vec<f32> {
         .map(|&sample| sample * scaling_factor)
         .collect()
 }
+
+/// Loudness that `normalize_rms` targets when `--normalize-audio` is enabled.
+pub const TARGET_RMS_DBFS: f32 = -18.0;
+
+/// Converts a linear sample amplitude to dBFS (0.0 dBFS = full scale).
+pub fn amplitude_to_dbfs(amplitude: f32) -> f32 {
+    if amplitude <= 0.0 {
+        f32::NEG_INFINITY
+    } else {
+        20.0 * amplitude.log10()
+    }
+}
+
+pub fn peak_dbfs(audio: &[f32]) -> f32 {
+    let peak = audio
+        .iter()
+        .fold(0.0f32, |max, &sample| max.max(sample.abs()));
+    amplitude_to_dbfs(peak)
+}
+
+/// Scales the buffer so its RMS level matches `target_dbfs`.
+/// Silent buffers are returned untouched and samples are clamped to [-1.0, 1.0] to avoid wrapping.
+pub fn normalize_rms(audio: &[f32], target_dbfs: f32) -> Vec<f32> {
+    if audio.is_empty() {
+        return Vec::new();
+    }
+
+    let rms = (audio.iter().map(|&x| x * x).sum::<f32>() / audio.len() as f32).sqrt();
+    if rms <= f32::EPSILON {
+        return audio.to_vec();
+    }
+
+    let target_rms = 10f32.powf(target_dbfs / 20.0);
+    let gain = target_rms / rms;
+
+    audio
+        .iter()
+        .map(|&sample| (sample * gain).clamp(-1.0, 1.0))
+        .collect()
+}
+
+/// Averages interleaved channels into a single mono channel.
+pub fn downmix_to_mono(audio: &[f32], channels: u16) -> Vec<f32> {
+    if channels <= 1 {
+        return audio.to_vec();
+    }
+    audio
+        .chunks(channels as usize)
+        .map(|frame| frame.iter().sum::<f32>() / frame.len() as f32)
+        .collect()
+}
+
+/// `len` mono samples from `start`, taken from the `(start time, samples)` chunks covering them
+/// and silent where none does.
+pub fn align_reference(
+    chunks: &[(SystemTime, &[f32])],
+    start: SystemTime,
+    len: usize,
+    sample_rate: u32,
+) -> Vec<f32> {
+    let mut aligned = vec![0.0; len];
+    for (chunk_start, samples) in chunks {
+        // samples from `start` to the start of the chunk, negative when it started before
+        let offset_secs = match chunk_start.duration_since(start) {
+            Ok(after) => after.as_secs_f64(),
+            Err(before) => -before.duration().as_secs_f64(),
+        };
+        let offset = (offset_secs * sample_rate as f64).round() as i64;
+        let from = offset.clamp(0, len as i64) as usize;
+        let to = (offset + samples.len() as i64).clamp(0, len as i64) as usize;
+        if from < to {
+            let skip = (from as i64 - offset) as usize;
+            aligned[from..to].copy_from_slice(&samples[skip..skip + to - from]);
+        }
+    }
+    aligned
+}
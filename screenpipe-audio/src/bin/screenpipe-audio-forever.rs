@@ -9,6 +9,7 @@ use screenpipe_audio::parse_audio_device;
 use screenpipe_audio::record_and_transcribe;
 use screenpipe_audio::vad_engine::VadSensitivity;
 use screenpipe_audio::AudioDevice;
+use screenpipe_audio::AudioFormat;
 use screenpipe_audio::AudioTranscriptionEngine;
 use screenpipe_audio::VadEngineEnum;
 use std::path::PathBuf;
@@ -87,6 +88,11 @@ async fn main() -> Result<()> {
         args.deepgram_api_key,
         &PathBuf::from("output.mp4"),
         VadSensitivity::Medium,
+        false,
+        false,
+        AudioFormat::Mp4,
+        Duration::ZERO,
+        true,
     )
     .await?;
     // Spawn threads for each device
@@ -9,6 +9,7 @@ use screenpipe_audio::parse_audio_device;
 use screenpipe_audio::record_and_transcribe;
 use screenpipe_audio::vad_engine::VadSensitivity;
 use screenpipe_audio::AudioDevice;
+use screenpipe_audio::AudioFormat;
 use screenpipe_audio::AudioTranscriptionEngine;
 use screenpipe_audio::VadEngineEnum;
 use std::path::PathBuf;
@@ -91,6 +92,11 @@ async fn main() -> Result<()> {
         deepgram_api_key,
         &output_path,
         VadSensitivity::Medium,
+        false,
+        false,
+        AudioFormat::Mp4,
+        Duration::ZERO,
+        true,
     )
     .await?;
     // Spawn threads for each device
@@ -0,0 +1,158 @@
+//! `--audio-chunk-overlap-secs`: a chunk starts with the end of the previous chunk of its device,
+//! so a word cut at the boundary is heard whole in the next chunk, and the words both chunks
+//! transcribed are dropped from the second transcript before it is stored.
+
+use crate::core::AudioDevice;
+use crate::stt::{AudioInput, TranscriptionResult};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Most words looked at on each side of a boundary, more than a second of speech holds.
+const MAX_OVERLAP_WORDS: usize = 12;
+
+struct DeviceOverlap {
+    tail: Vec<f32>,
+    sample_rate: u32,
+    channels: u16,
+    /// Audio of the previous chunk the last chunk sent started with
+    prepended: Duration,
+    /// The same in samples of all channels
+    prepended_samples: usize,
+    transcript: String,
+}
+
+/// Tails and last transcripts of each device, kept by the transcription loop which sees the
+/// chunks of a device in order.
+pub struct ChunkOverlap {
+    overlap: Duration,
+    devices: HashMap<String, DeviceOverlap>,
+}
+
+impl ChunkOverlap {
+    pub fn new(overlap: Duration) -> Self {
+        ChunkOverlap {
+            overlap,
+            devices: HashMap::new(),
+        }
+    }
+
+    /// Prepends the end of the previous chunk of the device to `input` and keeps the end of this
+    /// one for the next.
+    pub fn extend(&mut self, input: AudioInput) -> AudioInput {
+        if self.overlap.is_zero() {
+            return input;
+        }
+        let channels = input.channels.max(1) as usize;
+        let tail_len = (self.overlap.as_secs_f64() * input.sample_rate as f64) as usize * channels;
+        let tail = input.data[input.data.len().saturating_sub(tail_len)..].to_vec();
+
+        let device = self
+            .devices
+            .entry(input.device.to_string())
+            .or_insert_with(|| DeviceOverlap {
+                tail: Vec::new(),
+                sample_rate: input.sample_rate,
+                channels: input.channels,
+                prepended: Duration::ZERO,
+                prepended_samples: 0,
+                transcript: String::new(),
+            });
+        // a device reopened with another config starts over
+        let previous = std::mem::replace(&mut device.tail, tail);
+        let same_format =
+            device.sample_rate == input.sample_rate && device.channels == input.channels;
+        device.sample_rate = input.sample_rate;
+        device.channels = input.channels;
+        if previous.is_empty() || !same_format {
+            device.prepended = Duration::ZERO;
+            device.prepended_samples = 0;
+            return input;
+        }
+        device.prepended_samples = previous.len();
+
+        device.prepended =
+            Duration::from_secs_f64((previous.len() / channels) as f64 / input.sample_rate as f64);
+        let mut data = previous;
+        data.extend_from_slice(&input.data);
+        AudioInput {
+            data: Arc::new(data),
+            ..input
+        }
+    }
+
+    /// Samples of the last chunk `extend` gave for `device` that come from the chunk before it,
+    /// the stored audio file leaves them out.
+    pub fn prepended_samples(&self, device: &AudioDevice) -> usize {
+        self.devices
+            .get(&device.to_string())
+            .map_or(0, |device| device.prepended_samples)
+    }
+
+    /// Drops the words `result` repeats from the previous transcript of its device, and the word
+    /// timestamps lying wholly in the prepended audio. The others are moved to the time of the
+    /// stored file, which starts after the prepended audio.
+    pub fn merge(&mut self, mut result: TranscriptionResult) -> TranscriptionResult {
+        let Some(device) = self.devices.get_mut(&result.input.device.to_string()) else {
+            return result;
+        };
+        let Some(transcription) = result.transcription.take() else {
+            return result;
+        };
+        let transcription = if device.prepended.is_zero() {
+            transcription
+        } else {
+            let prepended_ms = device.prepended.as_millis() as u64;
+            result
+                .segments
+                .retain(|segment| segment.end_ms > prepended_ms);
+            for segment in &mut result.segments {
+                segment.start_ms = segment.start_ms.saturating_sub(prepended_ms);
+                segment.end_ms -= prepended_ms;
+            }
+            merge_overlapping(&device.transcript, &transcription)
+        };
+        device.transcript = transcription.clone();
+        result.transcription = Some(transcription);
+        result
+    }
+}
+
+fn normalize(word: &str) -> String {
+    word.chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// `current` without the words it starts with that `previous` ended with. The last word of
+/// `previous` may have been cut by the boundary, it matches a word it is the start of, which is
+/// kept as the whole word is only in `current`.
+pub fn merge_overlapping(previous: &str, current: &str) -> String {
+    let previous: Vec<String> = previous.split_whitespace().map(normalize).collect();
+    let words: Vec<&str> = current.split_whitespace().collect();
+    let normalized: Vec<String> = words.iter().map(|word| normalize(word)).collect();
+    let max = previous.len().min(words.len()).min(MAX_OVERLAP_WORDS);
+
+    for len in (1..=max).rev() {
+        let suffix = &previous[previous.len() - len..];
+        let prefix = &normalized[..len];
+        let (last, head) = suffix.split_last().expect("len is at least 1");
+        let (cut, prefix_head) = prefix.split_last().expect("len is at least 1");
+        if last.is_empty()
+            || head
+                .iter()
+                .zip(prefix_head)
+                .any(|(a, b)| a.is_empty() || a != b)
+        {
+            continue;
+        }
+        if last == cut {
+            return words[len..].join(" ");
+        }
+        if cut.starts_with(last.as_str()) {
+            return words[len - 1..].join(" ");
+        }
+    }
+    current.to_string()
+}
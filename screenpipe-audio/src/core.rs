@@ -7,7 +7,7 @@ use log::{debug, error, info, warn};
 use serde::{Deserialize, Serialize};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, SystemTime};
 use std::{fmt, thread}; // Note: We're using parking_lot for better performance
 
 #[derive(Clone, Debug, PartialEq)]
@@ -101,6 +101,70 @@ pub fn parse_audio_device(name: &str) -> Result<AudioDevice> {
     AudioDevice::from_name(name)
 }
 
+/// `name` without a trailing `{...}` of hex digits, dots and dashes, bare or in parentheses, as
+/// windows appends endpoint ids.
+fn strip_guid_suffix(name: &str) -> Option<&str> {
+    let (inner_end, open) = if let Some(rest) = name.strip_suffix("})") {
+        (rest.len(), "({")
+    } else if let Some(rest) = name.strip_suffix('}') {
+        (rest.len(), "{")
+    } else {
+        return None;
+    };
+    let start = name[..inner_end].rfind(open)?;
+    let inner = &name[start + open.len()..inner_end];
+    let is_guid = !inner.is_empty()
+        && inner
+            .chars()
+            .all(|c| c.is_ascii_hexdigit() || c == '-' || c == '.');
+    is_guid.then(|| name[..start].trim_end().trim_end_matches('.').trim_end())
+}
+
+fn strip_suffix_ignore_case<'a>(name: &'a str, suffix: &str) -> Option<&'a str> {
+    let start = name.len().checked_sub(suffix.len())?;
+    (name.is_char_boundary(start) && name[start..].eq_ignore_ascii_case(suffix))
+        .then(|| &name[..start])
+}
+
+/// Device name as people call it: without the ` (Built-in)` of macos or the guids of windows,
+/// with whitespace collapsed. A name that is nothing but such suffixes is kept as is.
+pub fn normalize_device_name(raw: &str) -> String {
+    let mut name = raw.trim();
+    loop {
+        if let Some(stripped) = strip_guid_suffix(name) {
+            name = stripped;
+        } else if let Some(stripped) = strip_suffix_ignore_case(name, "(built-in)") {
+            name = stripped.trim_end();
+        } else {
+            break;
+        }
+    }
+    if name.is_empty() {
+        name = raw.trim();
+    }
+    name.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// The device of `devices` that `requested` names, by its full name first then by
+/// [`normalize_device_name`] ignoring case, so `MacBook Microphone (input)` finds
+/// `MacBook Microphone (Built-in)`.
+pub fn find_audio_device<'a>(
+    devices: &'a [AudioDevice],
+    requested: &AudioDevice,
+) -> Option<&'a AudioDevice> {
+    let of_type = || {
+        devices
+            .iter()
+            .filter(|device| device.device_type == requested.device_type)
+    };
+    of_type()
+        .find(|device| device.name == requested.name)
+        .or_else(|| {
+            let wanted = normalize_device_name(&requested.name).to_lowercase();
+            of_type().find(|device| normalize_device_name(&device.name).to_lowercase() == wanted)
+        })
+}
+
 async fn get_device_and_config(
     audio_device: &AudioDevice,
 ) -> Result<(cpal::Device, cpal::SupportedStreamConfig)> {
@@ -161,6 +225,44 @@ pub async fn record_and_transcribe(
     whisper_sender: crossbeam::channel::Sender<AudioInput>,
     is_running: Arc<AtomicBool>,
 ) -> Result<()> {
+    if crate::fake_device::fake_device_frequency(&audio_device).is_some() {
+        return crate::fake_device::record_fake_device(
+            audio_device,
+            duration,
+            whisper_sender,
+            is_running,
+        )
+        .await;
+    }
+
+    #[cfg(target_os = "macos")]
+    if crate::app_audio::app_bundle_id(&audio_device).is_some() {
+        return crate::app_audio::record_app_audio(
+            audio_device,
+            duration,
+            whisper_sender,
+            is_running,
+        )
+        .await;
+    }
+
+    #[cfg(target_os = "linux")]
+    if crate::pulseaudio::is_monitor_source(&audio_device) {
+        return crate::pulseaudio::record_monitor_source(
+            audio_device,
+            duration,
+            whisper_sender,
+            is_running,
+        )
+        .await;
+    }
+
+    #[cfg(target_os = "windows")]
+    if crate::wasapi::is_loopback_device(&audio_device) {
+        return crate::wasapi::record_loopback(audio_device, duration, whisper_sender, is_running)
+            .await;
+    }
+
     let (cpal_audio_device, config) = get_device_and_config(&audio_device).await?;
     let sample_rate = config.sample_rate().0;
     let channels = config.channels() as u16;
@@ -181,7 +283,9 @@ pub async fn record_and_transcribe(
     // Define the error callback function
     let error_callback = move |err: StreamError| {
         error!("An error occurred on the audio stream: {}", err);
-        if err.to_string().contains("device is no longer valid") {
+        if matches!(err, StreamError::DeviceNotAvailable)
+            || err.to_string().contains("device is no longer valid")
+        {
             warn!("Audio device disconnected. Stopping recording.");
             if let Some(arc) = is_running_weak_2.upgrade() {
                 arc.store(false, Ordering::Relaxed);
@@ -286,11 +390,18 @@ pub async fn record_and_transcribe(
             }
             tokio::time::sleep(Duration::from_millis(10)).await;
         }
+        // pick up what the stream pushed between the last pop and the stop
+        while let Some(chunk) = audio_queue.pop() {
+            collected_audio.extend(chunk);
+        }
         collected_audio
     });
 
-    // Wait for the duration
-    tokio::time::sleep(duration).await;
+    // Wait for the duration, a stop from the caller cuts the chunk short but it is still sent
+    let deadline = tokio::time::Instant::now() + duration;
+    while is_running.load(Ordering::Relaxed) && tokio::time::Instant::now() < deadline {
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
 
     // Signal the recording to stop
     is_running.store(false, Ordering::Relaxed);
@@ -312,6 +423,7 @@ pub async fn record_and_transcribe(
         device: audio_device.clone(),
         sample_rate,
         channels,
+        end_time: SystemTime::now(),
     }) {
         error!("failed to send audio to audio model: {}", e);
     }
@@ -320,6 +432,30 @@ pub async fn record_and_transcribe(
     Ok(())
 }
 
+/// Whether the device can be opened right now, false once e.g. a usb headset is unplugged.
+pub async fn audio_device_available(audio_device: &AudioDevice) -> bool {
+    if crate::fake_device::fake_device_frequency(audio_device).is_some() {
+        return true;
+    }
+    #[cfg(target_os = "macos")]
+    if let Some(bundle_id) = crate::app_audio::app_bundle_id(audio_device) {
+        // an app that quit comes back like an unplugged device
+        let bundle_id = bundle_id.to_string();
+        return tokio::task::spawn_blocking(move || crate::app_audio::app_running(&bundle_id))
+            .await
+            .unwrap_or(false);
+    }
+    #[cfg(target_os = "linux")]
+    if crate::pulseaudio::is_monitor_source(audio_device) {
+        return crate::pulseaudio::monitor_source_present(audio_device);
+    }
+    #[cfg(target_os = "windows")]
+    if crate::wasapi::is_loopback_device(audio_device) {
+        return crate::wasapi::loopback_device_present(audio_device);
+    }
+    get_device_and_config(audio_device).await.is_ok()
+}
+
 pub async fn list_audio_devices() -> Result<Vec<AudioDevice>> {
     let host = cpal::default_host();
     let mut devices = Vec::new();
@@ -367,6 +503,12 @@ pub async fn list_audio_devices() -> Result<Vec<AudioDevice>> {
         }
     }
 
+    // cpal only knows alsa devices, loopback goes through the pulseaudio monitor sources
+    #[cfg(target_os = "linux")]
+    for source in crate::pulseaudio::list_monitor_sources() {
+        devices.push(AudioDevice::new(source.name, DeviceType::Output));
+    }
+
     // last, add devices that are listed in .devices() which are not already in the devices vector
     let other_devices = host.devices().unwrap();
     for device in other_devices {
@@ -407,6 +549,16 @@ pub fn default_output_device() -> Result<AudioDevice> {
 
     #[cfg(not(target_os = "macos"))]
     {
+        // the alsa default output cannot be recorded, the monitor of the default sink can
+        #[cfg(target_os = "linux")]
+        if let Some(source) = crate::pulseaudio::default_monitor_source() {
+            return Ok(AudioDevice::new(source.name, DeviceType::Output));
+        }
+        // recorded in loopback mode, see `wasapi`
+        #[cfg(target_os = "windows")]
+        if let Ok(name) = crate::wasapi::default_loopback_device() {
+            return Ok(AudioDevice::new(name, DeviceType::Output));
+        }
         let host = cpal::default_host();
         let device = host
             .default_output_device()
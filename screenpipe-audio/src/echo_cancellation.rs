@@ -0,0 +1,208 @@
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+use anyhow::{anyhow, Result};
+use log::{debug, warn};
+use webrtc_audio_processing::{
+    Config, EchoCancellation, EchoCancellationSuppressionLevel, InitializationConfig, Processor,
+    NUM_SAMPLES_PER_FRAME,
+};
+
+use crate::audio_processing::{align_reference, downmix_to_mono};
+use crate::{stt::resample, AudioInput, DeviceType};
+
+// the webrtc processor only works on 10ms frames at 48kHz
+const APM_SAMPLE_RATE: u32 = 48000;
+// echo suppression above which we consider the adaptive filter settled
+const CONVERGED_ERLE_DB: f64 = 10.0;
+// output audio kept for microphone chunks that come in late
+const REFERENCE_HISTORY: Duration = Duration::from_secs(120);
+// a microphone chunk ending this much after the output recorded so far doesn't wait for more
+const REFERENCE_SLACK: Duration = Duration::from_secs(1);
+
+/// Output audio in the processor's format, from `start` on.
+struct ReferenceChunk {
+    start: SystemTime,
+    end: SystemTime,
+    samples: Vec<f32>,
+}
+
+/// Removes speaker playback picked up by the microphone using the WebRTC audio processing module.
+/// The far-end reference of a microphone chunk is the output audio recorded over the same time.
+pub struct EchoCanceller {
+    processor: Processor,
+    references: VecDeque<ReferenceChunk>,
+    /// Microphone chunks waiting for the output chunk recorded at the same time, at most one per
+    /// device
+    pending: Vec<AudioInput>,
+}
+
+/// When the first sample of `input` was recorded.
+fn start_time(input: &AudioInput) -> SystemTime {
+    let frames = input.data.len() / input.channels.max(1) as usize;
+    input.end_time - Duration::from_secs_f64(frames as f64 / input.sample_rate.max(1) as f64)
+}
+
+impl EchoCanceller {
+    pub fn new() -> Result<Self> {
+        let mut processor = Processor::new(&InitializationConfig {
+            num_capture_channels: 1,
+            num_render_channels: 1,
+            ..Default::default()
+        })
+        .map_err(|e| anyhow!("failed to initialize echo canceller: {:?}", e))?;
+
+        processor.set_config(Config {
+            echo_cancellation: Some(EchoCancellation {
+                suppression_level: EchoCancellationSuppressionLevel::High,
+                enable_extended_filter: true,
+                enable_delay_agnostic: true,
+                stream_delay_ms: None,
+            }),
+            ..Default::default()
+        });
+
+        Ok(EchoCanceller {
+            processor,
+            references: VecDeque::new(),
+            pending: Vec::new(),
+        })
+    }
+
+    /// Output chunks are kept as the reference and returned as is, input chunks are echo cancelled
+    /// once the output audio recorded at the same time is there. Returns the chunks ready to be
+    /// transcribed, in the order each device recorded them.
+    pub fn process(&mut self, input: AudioInput) -> Vec<AudioInput> {
+        if input.device.device_type == DeviceType::Output {
+            self.add_reference(&input);
+            let covered_until = self.covered_until();
+            let (ready, pending): (Vec<_>, Vec<_>) = std::mem::take(&mut self.pending)
+                .into_iter()
+                .partition(|pending| Some(pending.end_time) <= covered_until);
+            self.pending = pending;
+            let mut ready: Vec<_> = ready.into_iter().map(|ready| self.cancel(ready)).collect();
+            ready.push(input);
+            return ready;
+        }
+
+        let Some(covered_until) = self.covered_until() else {
+            debug!(
+                "device: {}, no output audio recorded yet, skipping echo cancellation",
+                input.device
+            );
+            return vec![input];
+        };
+        if input.end_time <= covered_until {
+            return vec![self.cancel(input)];
+        }
+        // output recording stopped, the held chunk goes with the output audio there is
+        let mut ready = Vec::new();
+        if let Some(index) = self
+            .pending
+            .iter()
+            .position(|pending| pending.device == input.device)
+        {
+            let held = self.pending.remove(index);
+            ready.push(self.cancel(held));
+        }
+        self.pending.push(input);
+        ready
+    }
+
+    fn add_reference(&mut self, input: &AudioInput) {
+        match to_apm_format(input) {
+            Ok(samples) => self.references.push_back(ReferenceChunk {
+                start: start_time(input),
+                end: input.end_time,
+                samples,
+            }),
+            Err(e) => warn!(
+                "device: {}, failed to keep output audio: {}",
+                input.device, e
+            ),
+        }
+        let Some(keep_from) = input.end_time.checked_sub(REFERENCE_HISTORY) else {
+            return;
+        };
+        self.references
+            .retain(|reference| reference.end >= keep_from);
+    }
+
+    /// Microphone chunks ending before this are covered by the output audio recorded so far.
+    fn covered_until(&self) -> Option<SystemTime> {
+        self.references
+            .iter()
+            .map(|reference| reference.end + REFERENCE_SLACK)
+            .max()
+    }
+
+    fn cancel(&mut self, input: AudioInput) -> AudioInput {
+        match self.cancel_echo(&input) {
+            Ok(cancelled) => cancelled,
+            Err(e) => {
+                warn!("device: {}, echo cancellation failed: {}", input.device, e);
+                input
+            }
+        }
+    }
+
+    fn cancel_echo(&mut self, input: &AudioInput) -> Result<AudioInput> {
+        let mut capture = to_apm_format(input)?;
+        let chunks: Vec<_> = self
+            .references
+            .iter()
+            .map(|reference| (reference.start, reference.samples.as_slice()))
+            .collect();
+        // every capture frame gets the render frame played at the same time
+        let mut render =
+            align_reference(&chunks, start_time(input), capture.len(), APM_SAMPLE_RATE);
+
+        let frame_size = NUM_SAMPLES_PER_FRAME as usize;
+        for (capture_frame, render_frame) in capture
+            .chunks_exact_mut(frame_size)
+            .zip(render.chunks_exact_mut(frame_size))
+        {
+            self.processor
+                .process_render_frame(render_frame)
+                .map_err(|e| anyhow!("failed to process reference frame: {:?}", e))?;
+            self.processor
+                .process_capture_frame(capture_frame)
+                .map_err(|e| anyhow!("failed to process capture frame: {:?}", e))?;
+        }
+
+        let stats = self.processor.get_stats();
+        debug!(
+            "device: {}, aec {}: echo return loss enhancement {:?} dB, echo return loss {:?} dB, delay median {:?} ms",
+            input.device,
+            if stats
+                .echo_return_loss_enhancement
+                .map_or(false, |erle| erle >= CONVERGED_ERLE_DB)
+            {
+                "converged"
+            } else {
+                "converging"
+            },
+            stats.echo_return_loss_enhancement,
+            stats.echo_return_loss,
+            stats.delay_median_ms
+        );
+
+        Ok(AudioInput {
+            data: Arc::new(capture),
+            sample_rate: APM_SAMPLE_RATE,
+            channels: 1,
+            device: input.device.clone(),
+            end_time: input.end_time,
+        })
+    }
+}
+
+fn to_apm_format(input: &AudioInput) -> Result<Vec<f32>> {
+    let mono = downmix_to_mono(&input.data, input.channels);
+    if input.sample_rate == APM_SAMPLE_RATE {
+        Ok(mono)
+    } else {
+        resample(&mono, input.sample_rate, APM_SAMPLE_RATE)
+    }
+}
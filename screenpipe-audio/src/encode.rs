@@ -1,42 +1,179 @@
+use hound::{WavSpec, WavWriter};
 use screenpipe_core::find_ffmpeg_path;
-use std::io::Write;
+use std::io::{Cursor, Write};
 use std::{
-    path::PathBuf,
+    fmt,
+    fs::File,
+    path::{Path, PathBuf},
     process::{Command, Stdio},
 };
 use tracing::{debug, error};
 
+/// Container/codec used to store audio chunks.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum AudioFormat {
+    /// AAC in mp4
+    #[default]
+    Mp4,
+    Wav,
+    Flac,
+    Opus,
+    /// 16 bit wav compressed with zstd, `--compress-audio`
+    WavZstd,
+}
+
+/// zstd level of compressed chunks, low enough to keep up with recording.
+const ZSTD_LEVEL: i32 = 3;
+
+impl AudioFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            AudioFormat::Mp4 => "mp4",
+            AudioFormat::Wav => "wav",
+            AudioFormat::Flac => "flac",
+            AudioFormat::Opus => "opus",
+            AudioFormat::WavZstd => "wav.zst",
+        }
+    }
+
+    /// Value of the `format` column of `audio_chunks`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            AudioFormat::WavZstd => "wav+zstd",
+            _ => self.extension(),
+        }
+    }
+
+    pub fn is_zstd(&self) -> bool {
+        *self == AudioFormat::WavZstd
+    }
+
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            AudioFormat::Mp4 => "audio/mp4",
+            AudioFormat::Wav => "audio/wav",
+            AudioFormat::Flac => "audio/flac",
+            AudioFormat::Opus => "audio/ogg",
+            // served decompressed
+            AudioFormat::WavZstd => "audio/wav",
+        }
+    }
+
+    /// Detects the format of an existing chunk from its file extension.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()?.to_lowercase().as_str() {
+            "mp4" => Some(AudioFormat::Mp4),
+            "wav" => Some(AudioFormat::Wav),
+            "flac" => Some(AudioFormat::Flac),
+            "opus" => Some(AudioFormat::Opus),
+            "zst" if path.file_stem()?.to_str()?.to_lowercase().ends_with(".wav") => {
+                Some(AudioFormat::WavZstd)
+            }
+            _ => None,
+        }
+    }
+
+    fn ffmpeg_args(&self) -> &'static [&'static str] {
+        match self {
+            AudioFormat::Mp4 => &[
+                "-c:a",
+                "aac",
+                "-b:a",
+                "64k", // Reduced bitrate for higher compression
+                "-profile:a",
+                "aac_low", // Use AAC-LC profile for better compatibility
+                "-movflags",
+                "+faststart", // Optimize for web streaming
+                "-f",
+                "mp4",
+            ],
+            AudioFormat::Wav | AudioFormat::WavZstd => &["-c:a", "pcm_s16le", "-f", "wav"],
+            AudioFormat::Flac => &["-c:a", "flac", "-f", "flac"],
+            AudioFormat::Opus => &[
+                "-c:a", "libopus", "-b:a", "32k", "-ar",
+                "48000", // libopus does not accept 44.1k
+                "-f", "ogg",
+            ],
+        }
+    }
+}
+
+impl fmt::Display for AudioFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// Contents of an audio chunk, decompressed when it was stored with `--compress-audio`.
+pub fn read_audio_file(path: &Path) -> std::io::Result<Vec<u8>> {
+    if AudioFormat::from_path(path).is_some_and(|format| format.is_zstd()) {
+        zstd::decode_all(File::open(path)?)
+    } else {
+        std::fs::read(path)
+    }
+}
+
+/// Writes the f32le samples in `data` as a 16 bit wav through a zstd encoder, ffmpeg can't be
+/// used as its wav header is only complete when it can seek back in the output.
+fn encode_wav_zstd(
+    data: &[u8],
+    sample_rate: u32,
+    channels: u16,
+    output_path: &Path,
+) -> anyhow::Result<()> {
+    let spec = WavSpec {
+        channels,
+        sample_rate,
+        bits_per_sample: 16,
+        sample_format: hound::SampleFormat::Int,
+    };
+    let mut wav = Cursor::new(Vec::with_capacity(data.len() / 2 + 44));
+    {
+        let mut writer = WavWriter::new(&mut wav, spec)?;
+        for sample in data.chunks_exact(4) {
+            let sample = f32::from_le_bytes([sample[0], sample[1], sample[2], sample[3]]);
+            writer.write_sample((sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16)?;
+        }
+        writer.finalize()?;
+    }
+
+    let mut encoder = zstd::Encoder::new(File::create(output_path)?, ZSTD_LEVEL)?;
+    encoder.write_all(wav.get_ref())?;
+    encoder.finish()?.sync_all()?;
+    Ok(())
+}
+
 pub fn encode_single_audio(
     data: &[u8],
     sample_rate: u32,
     channels: u16,
     output_path: &PathBuf,
+    format: AudioFormat,
 ) -> anyhow::Result<()> {
+    if format.is_zstd() {
+        debug!("writing zstd compressed wav to {:?}", output_path);
+        return encode_wav_zstd(data, sample_rate, channels, output_path);
+    }
     debug!("Starting FFmpeg process");
 
+    let sample_rate = sample_rate.to_string();
+    let channels = channels.to_string();
+    let mut args = vec![
+        "-f",
+        "f32le",
+        "-ar",
+        &sample_rate,
+        "-ac",
+        &channels,
+        "-i",
+        "pipe:0",
+    ];
+    args.extend_from_slice(format.ffmpeg_args());
+    args.push(output_path.to_str().unwrap());
+
     let mut command = Command::new(find_ffmpeg_path().unwrap());
     command
-        .args(&[
-            "-f",
-            "f32le",
-            "-ar",
-            &sample_rate.to_string(),
-            "-ac",
-            &channels.to_string(),
-            "-i",
-            "pipe:0",
-            "-c:a",
-            "aac",
-            "-b:a",
-            "64k", // Reduced bitrate for higher compression
-            "-profile:a",
-            "aac_low", // Use AAC-LC profile for better compatibility
-            "-movflags",
-            "+faststart", // Optimize for web streaming
-            "-f",
-            "mp4",
-            output_path.to_str().unwrap(),
-        ])
+        .args(&args)
         .stdin(Stdio::piped())
         .stdout(Stdio::piped())
         .stderr(Stdio::piped());
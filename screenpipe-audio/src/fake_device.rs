@@ -0,0 +1,95 @@
+//! Synthetic input device for machines without audio hardware such as CI runners: it plays a
+//! sine wave and hands its samples to the transcription channel on the schedule a microphone
+//! would, so the recording pipeline runs end to end.
+
+use crate::core::{AudioDevice, DeviceType};
+use crate::AudioInput;
+use anyhow::{anyhow, Result};
+use log::{error, info};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime};
+
+pub const FAKE_DEVICE_SAMPLE_RATE: u32 = 16000;
+
+pub const DEFAULT_FAKE_FREQUENCY_HZ: f32 = 440.0;
+
+const FAKE_DEVICE_PREFIX: &str = "screenpipe fake sine ";
+
+const AMPLITUDE: f64 = 0.5;
+
+/// How often samples are generated while recording.
+const TICK: Duration = Duration::from_millis(100);
+
+/// The name carries the frequency, so the device needs no state of its own.
+pub fn fake_audio_device(frequency_hz: f32) -> AudioDevice {
+    AudioDevice::new(
+        format!("{}{} Hz", FAKE_DEVICE_PREFIX, frequency_hz),
+        DeviceType::Input,
+    )
+}
+
+/// Frequency of a device made by [`fake_audio_device`], `None` for real devices.
+pub fn fake_device_frequency(audio_device: &AudioDevice) -> Option<f32> {
+    audio_device
+        .name
+        .strip_prefix(FAKE_DEVICE_PREFIX)?
+        .strip_suffix(" Hz")?
+        .parse()
+        .ok()
+}
+
+/// `len` samples of the wave starting at sample `first_sample`.
+pub fn sine_wave(frequency_hz: f32, sample_rate: u32, first_sample: u64, len: usize) -> Vec<f32> {
+    (first_sample..first_sample + len as u64)
+        .map(|i| {
+            let t = i as f64 / sample_rate as f64;
+            (AMPLITUDE * (2.0 * std::f64::consts::PI * frequency_hz as f64 * t).sin()) as f32
+        })
+        .collect()
+}
+
+/// Same contract as `record_and_transcribe`, samples are produced as time passes.
+pub async fn record_fake_device(
+    audio_device: Arc<AudioDevice>,
+    duration: Duration,
+    whisper_sender: crossbeam::channel::Sender<AudioInput>,
+    is_running: Arc<AtomicBool>,
+) -> Result<()> {
+    let frequency = fake_device_frequency(&audio_device)
+        .ok_or_else(|| anyhow!("{} is not a fake device", audio_device))?;
+    info!(
+        "Recording {} for {} seconds",
+        audio_device.to_string(),
+        duration.as_secs()
+    );
+
+    let start = Instant::now();
+    let mut data = Vec::new();
+    let mut tick = tokio::time::interval(TICK);
+    while is_running.load(Ordering::Relaxed) && start.elapsed() < duration {
+        tick.tick().await;
+        // as many samples as a real device would have delivered by now
+        let due =
+            (start.elapsed().min(duration).as_secs_f64() * FAKE_DEVICE_SAMPLE_RATE as f64) as usize;
+        let missing = due.saturating_sub(data.len());
+        data.extend(sine_wave(
+            frequency,
+            FAKE_DEVICE_SAMPLE_RATE,
+            data.len() as u64,
+            missing,
+        ));
+    }
+    is_running.store(false, Ordering::Relaxed);
+
+    if let Err(e) = whisper_sender.send(AudioInput {
+        data: Arc::new(data),
+        device: audio_device,
+        sample_rate: FAKE_DEVICE_SAMPLE_RATE,
+        channels: 1,
+        end_time: SystemTime::now(),
+    }) {
+        error!("failed to send audio to audio model: {}", e);
+    }
+    Ok(())
+}
@@ -1,16 +1,29 @@
+pub mod app_audio;
 pub mod audio_processing;
+pub mod chunk_overlap;
 mod core;
+#[cfg(feature = "echo-cancellation")]
+pub mod echo_cancellation;
 pub mod encode;
+pub mod fake_device;
 mod multilingual;
 pub mod pcm_decode;
+pub mod pulseaudio;
+pub mod streaming;
 pub mod stt;
 pub mod vad_engine;
+#[cfg(target_os = "windows")]
+pub mod wasapi;
 pub mod whisper;
 pub use core::{
-    default_input_device, default_output_device, list_audio_devices, parse_audio_device,
-    record_and_transcribe, AudioDevice, AudioTranscriptionEngine, DeviceControl, DeviceType,
+    audio_device_available, default_input_device, default_output_device, find_audio_device,
+    list_audio_devices, normalize_device_name, parse_audio_device, record_and_transcribe,
+    AudioDevice, AudioTranscriptionEngine, DeviceControl, DeviceType,
 };
-pub use encode::encode_single_audio;
+pub use encode::{encode_single_audio, read_audio_file, AudioFormat};
 pub use pcm_decode::pcm_decode;
-pub use stt::{create_whisper_channel, stt, AudioInput, TranscriptionResult};
+pub use streaming::{LiveTranscriber, TranscriptEvent};
+pub use stt::{
+    create_whisper_channel, stt, AudioInput, TranscriptionResult, TranscriptionSegment,
+};
 pub use vad_engine::VadEngineEnum;
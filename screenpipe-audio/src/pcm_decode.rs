@@ -1,3 +1,4 @@
+use crate::encode::{read_audio_file, AudioFormat};
 use log::debug;
 use symphonia::core::audio::{AudioBufferRef, Signal};
 use symphonia::core::codecs::{DecoderOptions, CODEC_TYPE_NULL};
@@ -13,11 +14,16 @@ where
 
 pub fn pcm_decode<P: AsRef<std::path::Path>>(path: P) -> anyhow::Result<(Vec<f32>, u32)> {
     debug!("Starting PCM decoding for {:?}", path.as_ref());
-    // Open the media source.
-    let src = std::fs::File::open(path)?;
+    // Open the media source, a compressed chunk is decompressed in memory as symphonia seeks.
+    let src: Box<dyn symphonia::core::io::MediaSource> =
+        if AudioFormat::from_path(path.as_ref()).is_some_and(|format| format.is_zstd()) {
+            Box::new(std::io::Cursor::new(read_audio_file(path.as_ref())?))
+        } else {
+            Box::new(std::fs::File::open(path)?)
+        };
 
     // Create the media source stream.
-    let mss = symphonia::core::io::MediaSourceStream::new(Box::new(src), Default::default());
+    let mss = symphonia::core::io::MediaSourceStream::new(src, Default::default());
 
     // Create a probe hint using the file's extension. [Optional]
     let hint = symphonia::core::probe::Hint::new();
@@ -43,9 +49,7 @@ pub fn pcm_decode<P: AsRef<std::path::Path>>(path: P) -> anyhow::Result<(Vec<f32
     let dec_opts: DecoderOptions = Default::default();
 
     // Create a decoder for the track.
-    let mut decoder = symphonia::default::get_codecs()
-        .make(&track.codec_params, &dec_opts)
-        .expect("unsupported codec");
+    let mut decoder = symphonia::default::get_codecs().make(&track.codec_params, &dec_opts)?;
     let track_id = track.id;
     let sample_rate = track.codec_params.sample_rate.unwrap_or(0);
     let mut pcm_data = Vec::new();
@@ -0,0 +1,194 @@
+//! Loopback recording on linux from PulseAudio (or pipewire-pulse) monitor sources, which
+//! cpal does not list: they are found with `pactl` and recorded with `parec`.
+
+use crate::core::{AudioDevice, DeviceType};
+use crate::AudioInput;
+use anyhow::{anyhow, Result};
+use lazy_static::lazy_static;
+use log::{debug, error, info};
+use std::collections::HashMap;
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime};
+use tokio::io::AsyncReadExt;
+
+/// Rate `parec` is asked to resample monitor sources to.
+const MONITOR_SAMPLE_RATE: u32 = 16000;
+
+lazy_static! {
+    /// Whether each output device is a monitor source, asked before every chunk is recorded.
+    static ref MONITOR_SOURCES: Mutex<HashMap<String, bool>> = Mutex::new(HashMap::new());
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PulseSource {
+    pub name: String,
+    pub description: String,
+    /// Sink this source is the monitor of, `None` for microphones and other capture sources
+    pub monitor_of_sink: Option<String>,
+}
+
+/// Reads the output of `pactl list sources`, run with `LC_ALL=C` so the field names are english.
+pub fn parse_pactl_sources(output: &str) -> Vec<PulseSource> {
+    let mut sources = Vec::new();
+    for block in output.split("Source #").skip(1) {
+        let field = |key: &str| {
+            block
+                .lines()
+                .find_map(|line| line.trim().strip_prefix(key))
+                .map(|value| value.trim().to_string())
+        };
+        let Some(name) = field("Name:") else {
+            continue;
+        };
+        sources.push(PulseSource {
+            name,
+            description: field("Description:").unwrap_or_default(),
+            monitor_of_sink: field("Monitor of Sink:").filter(|sink| sink != "n/a"),
+        });
+    }
+    sources
+}
+
+/// Reads the `Default Sink:` line of `pactl info`.
+pub fn parse_default_sink(pactl_info: &str) -> Option<String> {
+    pactl_info
+        .lines()
+        .find_map(|line| line.trim().strip_prefix("Default Sink:"))
+        .map(|sink| sink.trim().to_string())
+        .filter(|sink| !sink.is_empty())
+}
+
+/// Monitor of `default_sink`, or the first monitor when there is none for it.
+pub fn select_monitor_source(
+    sources: &[PulseSource],
+    default_sink: Option<&str>,
+) -> Option<PulseSource> {
+    let mut monitors = sources.iter().filter(|s| s.monitor_of_sink.is_some());
+    default_sink
+        .and_then(|sink| {
+            monitors
+                .clone()
+                .find(|s| s.monitor_of_sink.as_deref() == Some(sink))
+        })
+        .or_else(|| monitors.next())
+        .cloned()
+}
+
+fn pactl(args: &[&str]) -> Option<String> {
+    let output = Command::new("pactl")
+        .args(args)
+        .env("LC_ALL", "C")
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Empty when PulseAudio is not running or `pactl` is not installed.
+pub fn list_monitor_sources() -> Vec<PulseSource> {
+    pactl(&["list", "sources"])
+        .map(|output| parse_pactl_sources(&output))
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|source| source.monitor_of_sink.is_some())
+        .collect()
+}
+
+/// Monitor source of the default sink, i.e. what is currently playing.
+pub fn default_monitor_source() -> Option<PulseSource> {
+    let sources = list_monitor_sources();
+    let default_sink = pactl(&["info"]).and_then(|info| parse_default_sink(&info));
+    select_monitor_source(&sources, default_sink.as_deref())
+}
+
+fn lists_monitor_source(pactl_sources: &str, name: &str) -> bool {
+    parse_pactl_sources(pactl_sources)
+        .iter()
+        .any(|source| source.monitor_of_sink.is_some() && source.name == name)
+}
+
+/// Looked up with `pactl` once per device, not cached while PulseAudio can't be reached.
+pub fn is_monitor_source(audio_device: &AudioDevice) -> bool {
+    if audio_device.device_type != DeviceType::Output {
+        return false;
+    }
+    let mut cache = MONITOR_SOURCES.lock().unwrap();
+    if let Some(&monitor) = cache.get(&audio_device.name) {
+        return monitor;
+    }
+    let Some(output) = pactl(&["list", "sources"]) else {
+        return false;
+    };
+    let monitor = lists_monitor_source(&output, &audio_device.name);
+    cache.insert(audio_device.name.clone(), monitor);
+    monitor
+}
+
+/// Whether the monitor source is listed right now, false once e.g. its sink was removed.
+pub fn monitor_source_present(audio_device: &AudioDevice) -> bool {
+    pactl(&["list", "sources"])
+        .is_some_and(|output| lists_monitor_source(&output, &audio_device.name))
+}
+
+/// Same contract as `record_and_transcribe`, reading mono f32 samples from `parec`.
+pub async fn record_monitor_source(
+    audio_device: Arc<AudioDevice>,
+    duration: Duration,
+    whisper_sender: crossbeam::channel::Sender<AudioInput>,
+    is_running: Arc<AtomicBool>,
+) -> Result<()> {
+    let mut child = tokio::process::Command::new("parec")
+        .arg(format!("--device={}", audio_device.name))
+        .args(["--format=float32le", "--channels=1", "--raw"])
+        .arg(format!("--rate={}", MONITOR_SAMPLE_RATE))
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .kill_on_drop(true)
+        .spawn()?;
+    let mut stdout = child
+        .stdout
+        .take()
+        .ok_or_else(|| anyhow!("failed to open parec stdout"))?;
+
+    info!(
+        "Recording {} for {} seconds",
+        audio_device.to_string(),
+        duration.as_secs()
+    );
+    let deadline = Instant::now() + duration;
+    let mut bytes = Vec::new();
+    let mut buf = [0u8; 8192];
+    while is_running.load(Ordering::Relaxed) && Instant::now() < deadline {
+        // short reads so a stop request is noticed even when nothing is playing
+        match tokio::time::timeout(Duration::from_millis(100), stdout.read(&mut buf)).await {
+            Ok(Ok(0)) => break,
+            Ok(Ok(n)) => bytes.extend_from_slice(&buf[..n]),
+            Ok(Err(e)) => return Err(e.into()),
+            Err(_) => {}
+        }
+    }
+    is_running.store(false, Ordering::Relaxed);
+    if let Err(e) = child.kill().await {
+        debug!("failed to kill parec: {}", e);
+    }
+
+    let data = bytes
+        .chunks_exact(4)
+        .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+        .collect();
+    if let Err(e) = whisper_sender.send(AudioInput {
+        data: Arc::new(data),
+        device: audio_device,
+        sample_rate: MONITOR_SAMPLE_RATE,
+        channels: 1,
+        end_time: SystemTime::now(),
+    }) {
+        error!("failed to send audio to audio model: {}", e);
+    }
+    Ok(())
+}
@@ -0,0 +1,219 @@
+//! Live transcription of an audio stream, for captions: the utterance being spoken is transcribed
+//! again every second as a partial, and a last time as final once the speaker pauses.
+
+use crate::stt::{load_mel_filters, resample, transcribe_with_whisper};
+use crate::vad_engine::{VadEngine, WebRtcVad};
+use crate::whisper::WhisperModel;
+use anyhow::Result;
+use candle_transformers::models::whisper as m;
+use log::debug;
+use serde::{Deserialize, Serialize};
+
+/// Samples in the 100 ms frames the vad looks at
+pub const STREAM_FRAME_LEN: usize = m::SAMPLE_RATE / 10;
+/// A partial every second of new audio
+const PARTIAL_FRAMES: usize = 10;
+/// Silence that ends an utterance
+const SILENCE_FRAMES: usize = 6;
+/// Longer utterances are cut, whisper slows down with the length of its input
+const MAX_UTTERANCE_FRAMES: usize = 150;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StreamStep {
+    /// The utterance so far may change as it goes on
+    Partial,
+    /// The utterance ended, its transcript won't change
+    Final,
+}
+
+/// The utterance being spoken, in 16 kHz samples. Silence before it is dropped.
+#[derive(Debug, Default)]
+pub struct Utterance {
+    samples: Vec<f32>,
+    /// Ms into the stream the utterance starts at
+    start_ms: u64,
+    frames_since_partial: usize,
+    silent_frames: usize,
+}
+
+impl Utterance {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds 100 ms of audio, returns whether the utterance is to be transcribed now.
+    pub fn push_frame(&mut self, frame: &[f32], is_voice: bool) -> Option<StreamStep> {
+        if self.samples.is_empty() && !is_voice {
+            self.start_ms += samples_ms(frame.len());
+            return None;
+        }
+        self.samples.extend_from_slice(frame);
+        self.frames_since_partial += 1;
+        self.silent_frames = if is_voice { 0 } else { self.silent_frames + 1 };
+
+        if self.silent_frames >= SILENCE_FRAMES
+            || self.samples.len() >= MAX_UTTERANCE_FRAMES * STREAM_FRAME_LEN
+        {
+            Some(StreamStep::Final)
+        } else if self.frames_since_partial >= PARTIAL_FRAMES {
+            self.frames_since_partial = 0;
+            Some(StreamStep::Partial)
+        } else {
+            None
+        }
+    }
+
+    pub fn samples(&self) -> &[f32] {
+        &self.samples
+    }
+
+    pub fn start_ms(&self) -> u64 {
+        self.start_ms
+    }
+
+    pub fn end_ms(&self) -> u64 {
+        self.start_ms + samples_ms(self.samples.len())
+    }
+
+    /// Starts the next utterance where this one ends.
+    pub fn finish(&mut self) {
+        *self = Utterance {
+            start_ms: self.end_ms(),
+            ..Utterance::default()
+        };
+    }
+}
+
+fn samples_ms(samples: usize) -> u64 {
+    (samples * 1000 / m::SAMPLE_RATE) as u64
+}
+
+/// Sent to the client for each transcription, times are ms into the stream.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TranscriptEvent {
+    Partial {
+        text: String,
+        start_ms: u64,
+        end_ms: u64,
+    },
+    Final {
+        text: String,
+        start_ms: u64,
+        end_ms: u64,
+    },
+}
+
+/// Transcribes a stream of mono audio as it comes. Whisper runs on the thread calling
+/// [`LiveTranscriber::push`], keep it off the async runtime.
+pub struct LiveTranscriber {
+    whisper_model: WhisperModel,
+    mel_filters: Vec<f32>,
+    vad: Box<dyn VadEngine + Send>,
+    sample_rate: u32,
+    /// Audio at `sample_rate` not resampled yet, it is resampled 100 ms at a time
+    pending_input: Vec<f32>,
+    /// Resampled audio short of a frame
+    pending_frame: Vec<f32>,
+    utterance: Utterance,
+}
+
+impl LiveTranscriber {
+    pub fn new(whisper_model: WhisperModel, sample_rate: u32) -> Result<Self> {
+        if sample_rate == 0 {
+            anyhow::bail!("sample rate must be above 0");
+        }
+        Ok(LiveTranscriber {
+            mel_filters: load_mel_filters(&whisper_model)?,
+            whisper_model,
+            vad: Box::new(WebRtcVad::new()),
+            sample_rate,
+            pending_input: Vec::new(),
+            pending_frame: Vec::new(),
+            utterance: Utterance::new(),
+        })
+    }
+
+    /// Adds audio to the stream and returns the transcripts it completes. When it holds several
+    /// partials only the last is transcribed, so a slow model doesn't fall further behind.
+    pub fn push(&mut self, samples: &[f32]) -> Result<Vec<TranscriptEvent>> {
+        self.pending_input.extend_from_slice(samples);
+        let block = (self.sample_rate / 10).max(1) as usize;
+        if self.pending_input.len() < block {
+            return Ok(Vec::new());
+        }
+        let input_len = self.pending_input.len() - self.pending_input.len() % block;
+        let input: Vec<f32> = self.pending_input.drain(..input_len).collect();
+        if self.sample_rate == m::SAMPLE_RATE as u32 {
+            self.pending_frame.extend(input);
+        } else {
+            self.pending_frame
+                .extend(resample(&input, self.sample_rate, m::SAMPLE_RATE as u32)?);
+        }
+
+        let mut events = Vec::new();
+        let mut partial = false;
+        let frames = self.pending_frame.len() / STREAM_FRAME_LEN;
+        let audio: Vec<f32> = self
+            .pending_frame
+            .drain(..frames * STREAM_FRAME_LEN)
+            .collect();
+        for frame in audio.chunks(STREAM_FRAME_LEN) {
+            let is_voice = self.vad.is_voice_segment(frame).unwrap_or_else(|e| {
+                debug!("vad failed on live audio: {}", e);
+                false
+            });
+            match self.utterance.push_frame(frame, is_voice) {
+                Some(StreamStep::Final) => {
+                    partial = false;
+                    events.extend(self.transcribe(StreamStep::Final)?);
+                }
+                Some(StreamStep::Partial) => partial = true,
+                None => {}
+            }
+        }
+        if partial {
+            events.extend(self.transcribe(StreamStep::Partial)?);
+        }
+        Ok(events)
+    }
+
+    /// Final transcript of the utterance left when the stream ends.
+    pub fn finish(&mut self) -> Result<Option<TranscriptEvent>> {
+        self.pending_frame.clear();
+        self.pending_input.clear();
+        self.transcribe(StreamStep::Final)
+    }
+
+    fn transcribe(&mut self, step: StreamStep) -> Result<Option<TranscriptEvent>> {
+        if self.utterance.samples().is_empty() {
+            return Ok(None);
+        }
+        let (text, _) = transcribe_with_whisper(
+            &self.whisper_model,
+            &self.mel_filters,
+            self.utterance.samples(),
+            "live",
+        )?;
+        let text = text.trim().to_string();
+        let (start_ms, end_ms) = (self.utterance.start_ms(), self.utterance.end_ms());
+        if step == StreamStep::Final {
+            self.utterance.finish();
+        }
+        if text.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(match step {
+            StreamStep::Partial => TranscriptEvent::Partial {
+                text,
+                start_ms,
+                end_ms,
+            },
+            StreamStep::Final => TranscriptEvent::Final {
+                text,
+                start_ms,
+                end_ms,
+            },
+        }))
+    }
+}
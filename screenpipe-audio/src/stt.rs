@@ -1,13 +1,13 @@
 use std::{
     path::PathBuf,
     sync::{Arc, Mutex},
-    time::{SystemTime, UNIX_EPOCH},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
 };
 
 use anyhow::Result;
 use candle::Tensor;
 use chrono::Utc;
-use log::{debug, error, info};
+use log::{debug, error, info, warn};
 #[cfg(target_os = "macos")]
 use objc::rc::autoreleasepool;
 
@@ -16,20 +16,33 @@ use rubato::{
     Resampler, SincFixedIn, SincInterpolationParameters, SincInterpolationType, WindowFunction,
 };
 
+#[cfg(feature = "echo-cancellation")]
+use crate::echo_cancellation::EchoCanceller;
 use crate::{
-    audio_processing::normalize_v2,
+    audio_processing::{normalize_rms, normalize_v2, peak_dbfs, TARGET_RMS_DBFS},
+    chunk_overlap::ChunkOverlap,
     encode_single_audio, multilingual,
     vad_engine::{SileroVad, VadEngine, VadEngineEnum, VadSensitivity, WebRtcVad},
-    whisper::{Decoder, WhisperModel},
-    AudioDevice, AudioTranscriptionEngine, DeviceType,
+    whisper::{Decoder, Segment, WhisperModel},
+    AudioDevice, AudioFormat, AudioTranscriptionEngine, DeviceType,
 };
 
 use hound::{WavSpec, WavWriter};
 use std::io::Cursor;
 
 use reqwest::Client;
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
+/// Part of a transcription with its position in the audio chunk: a single word when the
+/// engine times words (deepgram), a whisper segment otherwise.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TranscriptionSegment {
+    pub text: String,
+    pub start_ms: u64,
+    pub end_ms: u64,
+}
+
 // Replace the get_deepgram_api_key function with this:
 fn get_deepgram_api_key() -> String {
     "7ed2a159a094337b01fd8178b914b7ae0e77822d".to_string()
@@ -40,7 +53,7 @@ async fn transcribe_with_deepgram(
     audio_data: &[f32],
     device: &str,
     sample_rate: u32,
-) -> Result<String> {
+) -> Result<(String, Vec<TranscriptionSegment>)> {
     debug!("starting deepgram transcription");
     let client = Client::new();
 
@@ -86,10 +99,26 @@ async fn transcribe_with_deepgram(
                         );
                         return Err(anyhow::anyhow!("Deepgram API error: {:?}", result));
                     }
-                    let transcription = result["results"]["channels"][0]["alternatives"][0]
-                        ["transcript"]
-                        .as_str()
-                        .unwrap_or("");
+                    let alternative = &result["results"]["channels"][0]["alternatives"][0];
+                    let transcription = alternative["transcript"].as_str().unwrap_or("");
+                    let words = alternative["words"]
+                        .as_array()
+                        .map(|words| {
+                            words
+                                .iter()
+                                .filter_map(|word| {
+                                    let text = word["punctuated_word"]
+                                        .as_str()
+                                        .or_else(|| word["word"].as_str())?;
+                                    Some(TranscriptionSegment {
+                                        text: text.to_string(),
+                                        start_ms: (word["start"].as_f64()? * 1000.0) as u64,
+                                        end_ms: (word["end"].as_f64()? * 1000.0) as u64,
+                                    })
+                                })
+                                .collect()
+                        })
+                        .unwrap_or_default();
 
                     if transcription.is_empty() {
                         info!(
@@ -104,7 +133,7 @@ async fn transcribe_with_deepgram(
                         );
                     }
 
-                    Ok(transcription.to_string())
+                    Ok((transcription.to_string(), words))
                 }
                 Err(e) => {
                     error!("Failed to parse JSON response: {:?}", e);
@@ -129,7 +158,10 @@ pub fn stt_sync(
     vad_engine: Arc<Mutex<Box<dyn VadEngine + Send>>>, // Changed type here
     deepgram_api_key: Option<String>,
     output_path: &PathBuf,
-) -> Result<(String, String)> {
+    normalize_audio: bool,
+    audio_format: AudioFormat,
+    stored_from: usize,
+) -> Result<(String, String, Vec<TranscriptionSegment>)> {
     let audio_input = audio_input.clone();
     let whisper_model = whisper_model.clone();
     let output_path = output_path.clone();
@@ -147,12 +179,17 @@ pub fn stt_sync(
             deepgram_api_key,
             &output_path,
             false,
+            normalize_audio,
+            audio_format,
+            stored_from,
         ))
     });
 
     handle.join().unwrap()
 }
 
+/// The stored file leaves out the first `stored_from` samples, audio of the previous chunk that
+/// `ChunkOverlap` prepended for the transcription only.
 pub async fn stt(
     audio_input: &AudioInput,
     whisper_model: &WhisperModel,
@@ -161,19 +198,25 @@ pub async fn stt(
     deepgram_api_key: Option<String>,
     output_path: &PathBuf,
     skip_encoding: bool,
-) -> Result<(String, String)> {
-    let model = &whisper_model.model;
-    let tokenizer = &whisper_model.tokenizer;
-    let device = &whisper_model.device;
-
-    debug!("Loading mel filters");
-    let mel_bytes = match model.config().num_mel_bins {
-        80 => include_bytes!("../models/whisper/melfilters.bytes").as_slice(),
-        128 => include_bytes!("../models/whisper/melfilters128.bytes").as_slice(),
-        nmel => anyhow::bail!("unexpected num_mel_bins {nmel}"),
+    normalize_audio: bool,
+    audio_format: AudioFormat,
+    stored_from: usize,
+) -> Result<(String, String, Vec<TranscriptionSegment>)> {
+    let mel_filters = load_mel_filters(whisper_model)?;
+
+    // normalize the raw chunk once so both the stored file and the transcription get the same gain
+    let samples = if normalize_audio {
+        let normalized = normalize_rms(&audio_input.data, TARGET_RMS_DBFS);
+        debug!(
+            "device: {}, normalized audio peak from {:.1} dBFS to {:.1} dBFS",
+            audio_input.device,
+            peak_dbfs(&audio_input.data),
+            peak_dbfs(&normalized)
+        );
+        Arc::new(normalized)
+    } else {
+        audio_input.data.clone()
     };
-    let mut mel_filters = vec![0f32; mel_bytes.len() / 4];
-    <byteorder::LittleEndian as byteorder::ByteOrder>::read_f32_into(mel_bytes, &mut mel_filters);
 
     let audio_data = if audio_input.sample_rate != m::SAMPLE_RATE as u32 {
         info!(
@@ -183,12 +226,12 @@ pub async fn stt(
             m::SAMPLE_RATE
         );
         resample(
-            audio_input.data.as_ref(),
+            samples.as_ref(),
             audio_input.sample_rate,
             m::SAMPLE_RATE as u32,
         )?
     } else {
-        audio_input.data.as_ref().to_vec()
+        samples.as_ref().to_vec()
     };
 
     let audio_data = if audio_input.device.device_type == DeviceType::Input {
@@ -201,6 +244,8 @@ pub async fn stt(
     let mut speech_frames = Vec::new();
     let mut total_frames = 0;
     let mut speech_frame_count = 0;
+    // start (ms) in the chunk of each frame kept in speech_frames, to time transcriptions
+    let mut speech_frame_starts = Vec::new();
 
     for chunk in audio_data.chunks(frame_size) {
         total_frames += 1;
@@ -208,6 +253,7 @@ pub async fn stt(
             Ok(is_voice) => {
                 if is_voice {
                     speech_frames.extend_from_slice(chunk);
+                    speech_frame_starts.push((total_frames - 1) * 100);
                     speech_frame_count += 1;
                 }
             }
@@ -239,10 +285,10 @@ pub async fn stt(
             speech_ratio,
             min_speech_ratio
         );
-        return Ok(("".to_string(), "".to_string()));
+        return Ok(("".to_string(), "".to_string(), vec![]));
     }
 
-    let transcription: Result<String> =
+    let transcription: Result<(String, Vec<TranscriptionSegment>)> =
         if audio_transcription_engine == AudioTranscriptionEngine::Deepgram.into() {
             // Deepgram implementation
             //check if key is set or empty or no chars in it
@@ -255,9 +301,8 @@ pub async fn stt(
                 get_deepgram_api_key()
             };
             info!(
-                "device: {}, using deepgram api key: {}...",
-                audio_input.device,
-                &api_key[..8]
+                "device: {}, using deepgram api key: ***",
+                audio_input.device
             );
             match transcribe_with_deepgram(
                 &api_key,
@@ -273,110 +318,31 @@ pub async fn stt(
                         "device: {}, deepgram transcription failed, falling back to Whisper: {:?}",
                         audio_input.device, e
                     );
-                    // Existing Whisper implementation
-                    debug!(
-                        "device: {}, converting pcm to mel spectrogram",
-                        audio_input.device
-                    );
-                    let mel = audio::pcm_to_mel(&model.config(), &speech_frames, &mel_filters);
-                    let mel_len = mel.len();
-                    debug!(
-                        "device: {}, creating tensor from mel spectrogram",
-                        audio_input.device
-                    );
-                    let mel = Tensor::from_vec(
-                        mel,
-                        (
-                            1,
-                            model.config().num_mel_bins,
-                            mel_len / model.config().num_mel_bins,
-                        ),
-                        &device,
-                    )?;
-
-                    debug!("device: {}, detecting language", audio_input.device);
-                    let language_token = Some(multilingual::detect_language(
-                        &mut model.clone(),
-                        &tokenizer,
-                        &mel,
-                    )?);
-                    let mut model = model.clone();
-                    debug!("device: {}, initializing decoder", audio_input.device);
-                    let mut dc = Decoder::new(
-                        &mut model,
-                        tokenizer,
-                        42,
-                        &device,
-                        language_token,
-                        true,
-                        false,
-                    )?;
-                    debug!("device: {}, starting decoding process", audio_input.device);
-                    let segments = dc.run(&mel)?;
-                    debug!("device: {}, decoding complete", audio_input.device);
-                    Ok(segments
-                        .iter()
-                        .map(|s| s.dr.text.clone())
-                        .collect::<Vec<String>>()
-                        .join("\n"))
+                    transcribe_with_whisper(
+                        whisper_model,
+                        &mel_filters,
+                        &speech_frames,
+                        &audio_input.device.to_string(),
+                    )
                 }
             }
         } else {
-            // Existing Whisper implementation
-            debug!(
-                "device: {}, starting whisper transcription",
-                audio_input.device
-            );
-            debug!(
-                "device: {}, converting pcm to mel spectrogram",
-                audio_input.device
-            );
-            let mel = audio::pcm_to_mel(&model.config(), &speech_frames, &mel_filters);
-            let mel_len = mel.len();
-            debug!(
-                "device: {}, creating tensor from mel spectrogram",
-                audio_input.device
-            );
-            let mel = Tensor::from_vec(
-                mel,
-                (
-                    1,
-                    model.config().num_mel_bins,
-                    mel_len / model.config().num_mel_bins,
-                ),
-                &device,
-            )?;
-
-            debug!("device: {}, detecting language", audio_input.device);
-            let language_token = Some(multilingual::detect_language(
-                &mut model.clone(),
-                &tokenizer,
-                &mel,
-            )?);
-            let mut model = model.clone();
-            debug!("device: {}, initializing decoder", audio_input.device);
-            let mut dc = Decoder::new(
-                &mut model,
-                tokenizer,
-                42,
-                &device,
-                language_token,
-                true,
-                false,
-            )?;
-            debug!("device: {}, starting decoding process", audio_input.device);
-            let segments = dc.run(&mel)?;
-            debug!("device: {}, decoding complete", audio_input.device);
-            Ok(segments
-                .iter()
-                .map(|s| s.dr.text.clone())
-                .collect::<Vec<String>>()
-                .join("\n"))
+            transcribe_with_whisper(
+                whisper_model,
+                &mel_filters,
+                &speech_frames,
+                &audio_input.device.to_string(),
+            )
         };
     let new_file_name = Utc::now().format("%Y-%m-%d_%H-%M-%S").to_string();
     let sanitized_device_name = audio_input.device.to_string().replace(['/', '\\'], "_");
     let file_path = PathBuf::from(output_path)
-        .join(format!("{}_{}.mp4", sanitized_device_name, new_file_name))
+        .join(format!(
+            "{}_{}.{}",
+            sanitized_device_name,
+            new_file_name,
+            audio_format.extension()
+        ))
         .to_str()
         .expect("Failed to create valid path")
         .to_string();
@@ -384,17 +350,133 @@ pub async fn stt(
     // Run FFmpeg in a separate task
     if !skip_encoding {
         encode_single_audio(
-            bytemuck::cast_slice(&audio_input.data),
+            bytemuck::cast_slice(&samples[stored_from.min(samples.len())..]),
             audio_input.sample_rate,
             audio_input.channels,
             &file_path.into(),
+            audio_format,
         )?;
     }
 
-    Ok((transcription?, file_path_clone))
+    let (transcription, segments) = transcription?;
+    let segments = segments
+        .into_iter()
+        .map(|segment| TranscriptionSegment {
+            start_ms: speech_to_chunk_ms(segment.start_ms, &speech_frame_starts),
+            // an end on a frame boundary belongs to the frame before it
+            end_ms: speech_to_chunk_ms(segment.end_ms.saturating_sub(1), &speech_frame_starts) + 1,
+            text: segment.text,
+        })
+        .collect();
+
+    Ok((transcription, file_path_clone, segments))
 }
 
-fn resample(input: &[f32], from_sample_rate: u32, to_sample_rate: u32) -> Result<Vec<f32>> {
+pub(crate) fn load_mel_filters(whisper_model: &WhisperModel) -> Result<Vec<f32>> {
+    debug!("Loading mel filters");
+    let mel_bytes = match whisper_model.model.config().num_mel_bins {
+        80 => include_bytes!("../models/whisper/melfilters.bytes").as_slice(),
+        128 => include_bytes!("../models/whisper/melfilters128.bytes").as_slice(),
+        nmel => anyhow::bail!("unexpected num_mel_bins {nmel}"),
+    };
+    let mut mel_filters = vec![0f32; mel_bytes.len() / 4];
+    <byteorder::LittleEndian as byteorder::ByteOrder>::read_f32_into(mel_bytes, &mut mel_filters);
+    Ok(mel_filters)
+}
+
+/// Whisper on 16 kHz `speech`, its language detected first. `device` only labels the logs.
+pub(crate) fn transcribe_with_whisper(
+    whisper_model: &WhisperModel,
+    mel_filters: &[f32],
+    speech: &[f32],
+    device: &str,
+) -> Result<(String, Vec<TranscriptionSegment>)> {
+    let model = &whisper_model.model;
+    debug!("device: {}, converting pcm to mel spectrogram", device);
+    let mel = audio::pcm_to_mel(&model.config(), speech, mel_filters);
+    let mel_len = mel.len();
+    debug!("device: {}, creating tensor from mel spectrogram", device);
+    let mel = Tensor::from_vec(
+        mel,
+        (
+            1,
+            model.config().num_mel_bins,
+            mel_len / model.config().num_mel_bins,
+        ),
+        &whisper_model.device,
+    )?;
+
+    debug!("device: {}, detecting language", device);
+    let language_token = Some(multilingual::detect_language(
+        &mut model.clone(),
+        &whisper_model.tokenizer,
+        &mel,
+    )?);
+    let mut model = model.clone();
+    debug!("device: {}, initializing decoder", device);
+    let mut dc = Decoder::new(
+        &mut model,
+        &whisper_model.tokenizer,
+        42,
+        &whisper_model.device,
+        language_token,
+        true,
+        false,
+    )?;
+    debug!("device: {}, starting decoding process", device);
+    let segments = dc.run(&mel)?;
+    debug!("device: {}, decoding complete", device);
+    Ok(whisper_transcription(&segments))
+}
+
+/// Runs whisper once on 100 ms of silence, the first chunk is otherwise slowed down by the
+/// weights and kernels loaded on first use. Returns how long it took.
+pub fn warm_up_whisper(whisper_model: &WhisperModel) -> Result<Duration> {
+    let start = Instant::now();
+    let silence = vec![0.0; m::SAMPLE_RATE / 10];
+    transcribe_with_whisper(
+        whisper_model,
+        &load_mel_filters(whisper_model)?,
+        &silence,
+        "warm-up",
+    )?;
+    Ok(start.elapsed())
+}
+
+fn whisper_transcription(segments: &[Segment]) -> (String, Vec<TranscriptionSegment>) {
+    let text = segments
+        .iter()
+        .map(|s| s.dr.text.clone())
+        .collect::<Vec<String>>()
+        .join("\n");
+    let timed = segments
+        .iter()
+        .flat_map(|s| s.timed_texts.iter())
+        .map(|(start, end, text)| TranscriptionSegment {
+            text: text.trim().to_string(),
+            start_ms: (start * 1000.0) as u64,
+            end_ms: (end * 1000.0) as u64,
+        })
+        .collect();
+    (text, timed)
+}
+
+/// Transcriptions are timed against the vad-filtered speech only, map a time back onto the
+/// chunk using the start of each 100ms frame that was kept.
+fn speech_to_chunk_ms(speech_ms: u64, speech_frame_starts: &[u64]) -> u64 {
+    match speech_frame_starts.get((speech_ms / 100) as usize) {
+        Some(frame_start) => frame_start + speech_ms % 100,
+        None => speech_frame_starts
+            .last()
+            .map_or(speech_ms, |last| last + 100),
+    }
+}
+
+pub(crate) fn resample(
+    input: &[f32],
+    from_sample_rate: u32,
+    to_sample_rate: u32,
+) -> Result<Vec<f32>> {
     debug!("Resampling audio");
     let params = SincInterpolationParameters {
         sinc_len: 256,
@@ -425,6 +507,8 @@ pub struct AudioInput {
     pub sample_rate: u32,
     pub channels: u16,
     pub device: Arc<AudioDevice>,
+    /// When the last sample was recorded, the first one is the chunk's length earlier
+    pub end_time: SystemTime,
 }
 
 #[derive(Debug, Clone)]
@@ -432,6 +516,7 @@ pub struct TranscriptionResult {
     pub path: String,
     pub input: AudioInput,
     pub transcription: Option<String>,
+    pub segments: Vec<TranscriptionSegment>,
     pub timestamp: u64,
     pub error: Option<String>,
 }
@@ -443,6 +528,11 @@ pub async fn create_whisper_channel(
     deepgram_api_key: Option<String>,
     output_path: &PathBuf,
     vad_sensitivity: VadSensitivity,
+    normalize_audio: bool,
+    echo_cancellation: bool,
+    audio_format: AudioFormat,
+    chunk_overlap: Duration,
+    warm_up: bool,
 ) -> Result<(
     crossbeam::channel::Sender<AudioInput>,
     crossbeam::channel::Receiver<TranscriptionResult>,
@@ -467,8 +557,28 @@ pub async fn create_whisper_channel(
     let shutdown_flag_clone = shutdown_flag.clone();
     let output_path = output_path.clone();
 
+    #[cfg(feature = "echo-cancellation")]
+    let mut echo_canceller = if echo_cancellation {
+        Some(EchoCanceller::new()?)
+    } else {
+        None
+    };
+    #[cfg(not(feature = "echo-cancellation"))]
+    if echo_cancellation {
+        log::warn!("echo cancellation requested but screenpipe was built without the echo-cancellation feature, ignoring");
+    }
+
+    let mut chunk_overlap = ChunkOverlap::new(chunk_overlap);
+
     tokio::spawn(async move {
-        loop {
+        // in the background, the first chunk is only sent once it is recorded
+        if warm_up {
+            match warm_up_whisper(&whisper_model) {
+                Ok(latency) => info!("whisper warmed up in {:?}", latency),
+                Err(e) => warn!("whisper warm-up failed: {}", e),
+            }
+        }
+        'transcription: loop {
             if shutdown_flag_clone.load(Ordering::Relaxed) {
                 info!("Whisper channel shutting down");
                 break;
@@ -480,64 +590,80 @@ pub async fn create_whisper_channel(
                     match input_result {
                         Ok(input) => {
                             debug!("Received input from input_receiver");
-                            let timestamp = SystemTime::now()
-                                .duration_since(UNIX_EPOCH)
-                                .expect("Time went backwards")
-                                .as_secs();
-
-                            let transcription_result = if cfg!(target_os = "macos") {
-                                #[cfg(target_os = "macos")]
-                                {
-                                    autoreleasepool(|| {
-                                        match stt_sync(&input, &whisper_model, audio_transcription_engine.clone(), vad_engine.clone(), deepgram_api_key.clone(), &output_path) {
-                                            Ok((transcription, path)) => TranscriptionResult {
-                                                input: input.clone(),
-                                                transcription: Some(transcription),
-                                                path,
-                                                timestamp,
-                                                error: None,
-                                            },
-                                            Err(e) => {
-                                                error!("STT error for input {}: {:?}", input.device, e);
-                                                TranscriptionResult {
+                            #[cfg(feature = "echo-cancellation")]
+                            let inputs = match echo_canceller.as_mut() {
+                                Some(echo_canceller) => echo_canceller.process(input),
+                                None => vec![input],
+                            };
+                            #[cfg(not(feature = "echo-cancellation"))]
+                            let inputs = [input];
+                            for input in inputs {
+                                let input = chunk_overlap.extend(input);
+                                let stored_from = chunk_overlap.prepended_samples(&input.device);
+                                let timestamp = SystemTime::now()
+                                    .duration_since(UNIX_EPOCH)
+                                    .expect("Time went backwards")
+                                    .as_secs();
+
+                                let transcription_result = if cfg!(target_os = "macos") {
+                                    #[cfg(target_os = "macos")]
+                                    {
+                                        autoreleasepool(|| {
+                                            match stt_sync(&input, &whisper_model, audio_transcription_engine.clone(), vad_engine.clone(), deepgram_api_key.clone(), &output_path, normalize_audio, audio_format, stored_from) {
+                                                Ok((transcription, path, segments)) => TranscriptionResult {
                                                     input: input.clone(),
-                                                    transcription: None,
-                                                    path: "".to_string(),
+                                                    transcription: Some(transcription),
+                                                    segments,
+                                                    path,
                                                     timestamp,
-                                                    error: Some(e.to_string()),
-                                                }
-                                            },
-                                        }
-                                    })
-                                }
-                                #[cfg(not(target_os = "macos"))]
-                                {
-                                    unreachable!("This code should not be reached on non-macOS platforms")
-                                }
-                            } else {
-                                match stt_sync(&input, &whisper_model, audio_transcription_engine.clone(), vad_engine.clone(), deepgram_api_key.clone(), &output_path) {
-                                    Ok((transcription, path)) => TranscriptionResult {
-                                        input: input.clone(),
-                                        transcription: Some(transcription),
-                                        path,
-                                        timestamp,
-                                        error: None,
-                                    },
-                                    Err(e) => {
-                                        error!("STT error for input {}: {:?}", input.device, e);
-                                        TranscriptionResult {
+                                                    error: None,
+                                                },
+                                                Err(e) => {
+                                                    error!("STT error for input {}: {:?}", input.device, e);
+                                                    TranscriptionResult {
+                                                        input: input.clone(),
+                                                        transcription: None,
+                                                        segments: vec![],
+                                                        path: "".to_string(),
+                                                        timestamp,
+                                                        error: Some(e.to_string()),
+                                                    }
+                                                },
+                                            }
+                                        })
+                                    }
+                                    #[cfg(not(target_os = "macos"))]
+                                    {
+                                        unreachable!("This code should not be reached on non-macOS platforms")
+                                    }
+                                } else {
+                                    match stt_sync(&input, &whisper_model, audio_transcription_engine.clone(), vad_engine.clone(), deepgram_api_key.clone(), &output_path, normalize_audio, audio_format, stored_from) {
+                                        Ok((transcription, path, segments)) => TranscriptionResult {
                                             input: input.clone(),
-                                            transcription: None,
-                                            path: "".to_string(),
+                                            transcription: Some(transcription),
+                                            segments,
+                                            path,
                                             timestamp,
-                                            error: Some(e.to_string()),
-                                        }
-                                    },
+                                            error: None,
+                                        },
+                                        Err(e) => {
+                                            error!("STT error for input {}: {:?}", input.device, e);
+                                            TranscriptionResult {
+                                                input: input.clone(),
+                                                transcription: None,
+                                                segments: vec![],
+                                                path: "".to_string(),
+                                                timestamp,
+                                                error: Some(e.to_string()),
+                                            }
+                                        },
+                                    }
+                                };
+
+                                let transcription_result = chunk_overlap.merge(transcription_result);
+                                if output_sender.send(transcription_result).is_err() {
+                                    break 'transcription;
                                 }
-                            };
-
-                            if output_sender.send(transcription_result).is_err() {
-                                break;
                             }
                         },
                         Err(e) => {
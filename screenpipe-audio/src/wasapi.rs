@@ -0,0 +1,231 @@
+//! Loopback recording on windows: WASAPI can capture what any playback device renders when
+//! it is opened in shared mode with `AUDCLNT_STREAMFLAGS_LOOPBACK`, no virtual audio cable needed.
+
+use crate::core::{AudioDevice, DeviceType};
+use crate::AudioInput;
+use anyhow::{anyhow, bail, Result};
+use lazy_static::lazy_static;
+use log::{error, info};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime};
+use windows::Win32::Devices::FunctionDiscovery::PKEY_Device_FriendlyName;
+use windows::Win32::Media::Audio::{
+    eConsole, eRender, IAudioCaptureClient, IAudioClient, IMMDevice, IMMDeviceEnumerator,
+    MMDeviceEnumerator, AUDCLNT_BUFFERFLAGS_SILENT, AUDCLNT_SHAREMODE_SHARED,
+    AUDCLNT_STREAMFLAGS_LOOPBACK, DEVICE_STATE_ACTIVE,
+};
+use windows::Win32::System::Com::StructuredStorage::PropVariantToStringAlloc;
+use windows::Win32::System::Com::{
+    CoCreateInstance, CoInitializeEx, CoTaskMemFree, CLSCTX_ALL, COINIT_MULTITHREADED, STGM_READ,
+};
+
+/// Buffer asked of WASAPI, in 100ns units.
+const BUFFER_DURATION: i64 = 10_000_000;
+
+/// WASAPI sends no packets while nothing plays, audio this late is taken for silence.
+const SILENCE_AFTER: Duration = Duration::from_millis(50);
+
+lazy_static! {
+    /// Whether each output device is a playback device, asked before every chunk is recorded.
+    static ref LOOPBACK_DEVICES: Mutex<HashMap<String, bool>> = Mutex::new(HashMap::new());
+}
+
+fn enumerator() -> Result<IMMDeviceEnumerator> {
+    unsafe {
+        // S_FALSE or RPC_E_CHANGED_MODE when the thread already initialized com, both usable
+        let _ = CoInitializeEx(None, COINIT_MULTITHREADED);
+        Ok(CoCreateInstance(&MMDeviceEnumerator, None, CLSCTX_ALL)?)
+    }
+}
+
+/// Name shown in the sound settings, which is also the name cpal lists the device under.
+fn friendly_name(device: &IMMDevice) -> Result<String> {
+    unsafe {
+        let value = device
+            .OpenPropertyStore(STGM_READ)?
+            .GetValue(&PKEY_Device_FriendlyName)?;
+        let name = PropVariantToStringAlloc(&value)?;
+        let result = name.to_string();
+        CoTaskMemFree(Some(name.0 as *const _));
+        Ok(result?)
+    }
+}
+
+fn render_endpoints() -> Result<Vec<(String, IMMDevice)>> {
+    unsafe {
+        let collection = enumerator()?.EnumAudioEndpoints(eRender, DEVICE_STATE_ACTIVE)?;
+        let mut endpoints = Vec::new();
+        for i in 0..collection.GetCount()? {
+            let device = collection.Item(i)?;
+            endpoints.push((friendly_name(&device)?, device));
+        }
+        Ok(endpoints)
+    }
+}
+
+fn default_endpoint() -> Result<IMMDevice> {
+    unsafe { Ok(enumerator()?.GetDefaultAudioEndpoint(eRender, eConsole)?) }
+}
+
+/// Active playback devices, empty when WASAPI cannot be reached.
+pub fn list_loopback_devices() -> Vec<String> {
+    render_endpoints()
+        .map(|endpoints| endpoints.into_iter().map(|(name, _)| name).collect())
+        .unwrap_or_default()
+}
+
+/// Playback device sounds currently go to.
+pub fn default_loopback_device() -> Result<String> {
+    friendly_name(&default_endpoint()?)
+}
+
+/// Looked up once per device, not cached while WASAPI can't be reached.
+pub fn is_loopback_device(audio_device: &AudioDevice) -> bool {
+    if audio_device.device_type != DeviceType::Output {
+        return false;
+    }
+    if audio_device.name == "default" {
+        return true;
+    }
+    let mut cache = LOOPBACK_DEVICES.lock().unwrap();
+    if let Some(&loopback) = cache.get(&audio_device.name) {
+        return loopback;
+    }
+    let Ok(endpoints) = render_endpoints() else {
+        return false;
+    };
+    let loopback = endpoints.iter().any(|(name, _)| *name == audio_device.name);
+    cache.insert(audio_device.name.clone(), loopback);
+    loopback
+}
+
+/// Whether the playback device is active right now, false once e.g. a usb headset is unplugged.
+pub fn loopback_device_present(audio_device: &AudioDevice) -> bool {
+    if audio_device.name == "default" {
+        return default_endpoint().is_ok();
+    }
+    list_loopback_devices().contains(&audio_device.name)
+}
+
+/// Interleaved sample count of `elapsed` of audio.
+fn samples_for(elapsed: Duration, sample_rate: u32, channels: u16) -> usize {
+    (elapsed.as_secs_f64() * sample_rate as f64) as usize * channels as usize
+}
+
+fn find_endpoint(name: &str) -> Result<IMMDevice> {
+    if name == "default" {
+        return default_endpoint();
+    }
+    render_endpoints()?
+        .into_iter()
+        .find(|(endpoint_name, _)| endpoint_name == name)
+        .map(|(_, device)| device)
+        .ok_or_else(|| anyhow!("playback device {} not found", name))
+}
+
+/// Interleaved samples of the device's mix format with its sample rate and channel count.
+fn capture_loopback(
+    name: &str,
+    duration: Duration,
+    is_running: &AtomicBool,
+) -> Result<(Vec<f32>, u32, u16)> {
+    unsafe {
+        let client: IAudioClient = find_endpoint(name)?.Activate(CLSCTX_ALL, None)?;
+        let format = client.GetMixFormat()?;
+        let (sample_rate, channels, bits) = (
+            (*format).nSamplesPerSec,
+            (*format).nChannels,
+            (*format).wBitsPerSample,
+        );
+        let initialized = client.Initialize(
+            AUDCLNT_SHAREMODE_SHARED,
+            AUDCLNT_STREAMFLAGS_LOOPBACK,
+            BUFFER_DURATION,
+            0,
+            format,
+            None,
+        );
+        CoTaskMemFree(Some(format as *const _));
+        initialized?;
+        // the shared mode mix format is 32 bit float on every windows version supported
+        if bits != 32 {
+            bail!("unsupported {} bit mix format", bits);
+        }
+        let capture: IAudioCaptureClient = client.GetService()?;
+        client.Start()?;
+
+        let start = Instant::now();
+        let deadline = start + duration;
+        let mut samples = Vec::new();
+        while is_running.load(Ordering::Relaxed) && Instant::now() < deadline {
+            let mut received = false;
+            while capture.GetNextPacketSize()? > 0 {
+                received = true;
+                let mut data = std::ptr::null_mut();
+                let mut frames = 0;
+                let mut flags = 0;
+                capture.GetBuffer(&mut data, &mut frames, &mut flags, None, None)?;
+                let len = frames as usize * channels as usize;
+                if flags & AUDCLNT_BUFFERFLAGS_SILENT.0 as u32 != 0 {
+                    samples.resize(samples.len() + len, 0.0);
+                } else {
+                    samples.extend_from_slice(std::slice::from_raw_parts(data as *const f32, len));
+                }
+                capture.ReleaseBuffer(frames)?;
+            }
+            if !received {
+                // nothing plays, the gap is filled so what plays next keeps its place in the chunk
+                let silent_until = samples_for(
+                    start.elapsed().saturating_sub(SILENCE_AFTER),
+                    sample_rate,
+                    channels,
+                );
+                if samples.len() < silent_until {
+                    samples.resize(silent_until, 0.0);
+                }
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        client.Stop()?;
+        // a chunk recorded while nothing played is as long as the others, not empty
+        let total = samples_for(start.elapsed().min(duration), sample_rate, channels);
+        if samples.len() < total {
+            samples.resize(total, 0.0);
+        }
+        Ok((samples, sample_rate, channels))
+    }
+}
+
+/// Same contract as `record_and_transcribe`, capturing a playback device in loopback mode.
+pub async fn record_loopback(
+    audio_device: Arc<AudioDevice>,
+    duration: Duration,
+    whisper_sender: crossbeam::channel::Sender<AudioInput>,
+    is_running: Arc<AtomicBool>,
+) -> Result<()> {
+    info!(
+        "Recording {} for {} seconds",
+        audio_device.to_string(),
+        duration.as_secs()
+    );
+    let name = audio_device.name.clone();
+    let running = Arc::clone(&is_running);
+    // com objects stay on the thread that created them
+    let captured =
+        tokio::task::spawn_blocking(move || capture_loopback(&name, duration, &running)).await?;
+    is_running.store(false, Ordering::Relaxed);
+    let (data, sample_rate, channels) = captured?;
+
+    if let Err(e) = whisper_sender.send(AudioInput {
+        data: Arc::new(data),
+        device: audio_device,
+        sample_rate,
+        channels,
+        end_time: SystemTime::now(),
+    }) {
+        error!("failed to send audio to audio model: {}", e);
+    }
+    Ok(())
+}
@@ -127,6 +127,8 @@ pub struct Segment {
     start: f64,
     duration: f64,
     pub dr: DecodingResult,
+    /// (start seconds, end seconds, text) between timestamp tokens, empty without timestamps
+    pub timed_texts: Vec<(f64, f64, String)>,
 }
 
 pub struct Decoder<'a> {
@@ -326,10 +328,11 @@ impl<'a> Decoder<'a> {
                 info!("no speech detected, skipping {seek} {dr:?}");
                 continue;
             }
-            let segment = Segment {
+            let mut segment = Segment {
                 start: time_offset,
                 duration: segment_duration,
                 dr,
+                timed_texts: vec![],
             };
             if self.timestamps {
                 info!(
@@ -339,6 +342,7 @@ impl<'a> Decoder<'a> {
                 );
                 let mut tokens_to_decode = vec![];
                 let mut prev_timestamp_s = 0f32;
+                let mut timed_texts = vec![];
                 for &token in segment.dr.tokens.iter() {
                     if token == self.sot_token || token == self.eot_token {
                         continue;
@@ -351,6 +355,11 @@ impl<'a> Decoder<'a> {
                                 .decode(&tokens_to_decode, true)
                                 .map_err(E::msg)?;
                             info!("  {:.1}s-{:.1}s: {}", prev_timestamp_s, timestamp_s, text);
+                            timed_texts.push((
+                                segment.start + prev_timestamp_s as f64,
+                                segment.start + timestamp_s as f64,
+                                text,
+                            ));
                             tokens_to_decode.clear()
                         }
                         prev_timestamp_s = timestamp_s;
@@ -365,9 +374,15 @@ impl<'a> Decoder<'a> {
                         .map_err(E::msg)?;
                     if !text.is_empty() {
                         info!("  {:.1}s-...: {}", prev_timestamp_s, text);
+                        timed_texts.push((
+                            segment.start + prev_timestamp_s as f64,
+                            segment.start + segment.duration,
+                            text,
+                        ));
                     }
                     tokens_to_decode.clear()
                 }
+                segment.timed_texts = timed_texts;
             } else {
                 info!(
                     "{:.1}s -- {:.1}s: {}",
@@ -2,9 +2,10 @@ use futures::future::join_all;
 use screenpipe_audio::stt::stt;
 use screenpipe_audio::vad_engine::{SileroVad, VadEngine};
 use screenpipe_audio::whisper::WhisperModel;
-use screenpipe_audio::{AudioInput, AudioTranscriptionEngine};
+use screenpipe_audio::{AudioFormat, AudioInput, AudioTranscriptionEngine};
 use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::SystemTime;
 use strsim::levenshtein;
 use tokio::sync::Mutex;
 use tracing::debug;
@@ -83,10 +84,11 @@ So it's a little bit, you know"#,
                 sample_rate: 44100, // hardcoded based on test data sample rate
                 channels: 1,
                 device: Arc::new(screenpipe_audio::default_input_device().unwrap()),
+                end_time: SystemTime::now(),
             };
 
             let mut vad_engine_guard = vad_engine.lock().await;
-            let (transcription, _) = stt(
+            let (transcription, _, _) = stt(
                 &audio_input,
                 &whisper_model,
                 Arc::new(AudioTranscriptionEngine::WhisperLargeV3Turbo),
@@ -94,6 +96,9 @@ So it's a little bit, you know"#,
                 None,
                 &output_path,
                 true,
+                false,
+                AudioFormat::Mp4,
+                0,
             )
             .await
             .unwrap();
@@ -0,0 +1,20 @@
+use screenpipe_audio::app_audio::{app_audio_device, app_bundle_id, parse_macos_major_version};
+use screenpipe_audio::{AudioDevice, DeviceType};
+
+#[test]
+fn test_app_audio_device() {
+    let device = app_audio_device("com.spotify.client");
+    assert_eq!(device.device_type, DeviceType::Output);
+    assert_eq!(device.to_string(), "app audio com.spotify.client (output)");
+    assert_eq!(app_bundle_id(&device), Some("com.spotify.client"));
+    let real = AudioDevice::new("MacBook Pro Speakers".to_string(), DeviceType::Output);
+    assert_eq!(app_bundle_id(&real), None);
+}
+
+#[test]
+fn test_parse_macos_major_version() {
+    assert_eq!(parse_macos_major_version("14.2.1\n"), Some(14));
+    assert_eq!(parse_macos_major_version("13.6"), Some(13));
+    assert_eq!(parse_macos_major_version("15"), Some(15));
+    assert_eq!(parse_macos_major_version(""), None);
+}
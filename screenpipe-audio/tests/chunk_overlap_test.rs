@@ -0,0 +1,100 @@
+use screenpipe_audio::chunk_overlap::{merge_overlapping, ChunkOverlap};
+use screenpipe_audio::stt::{AudioInput, TranscriptionResult, TranscriptionSegment};
+use screenpipe_audio::{AudioDevice, DeviceType};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+fn input(device: &Arc<AudioDevice>, data: Vec<f32>) -> AudioInput {
+    AudioInput {
+        data: Arc::new(data),
+        sample_rate: 10,
+        channels: 1,
+        device: Arc::clone(device),
+        end_time: SystemTime::now(),
+    }
+}
+
+fn result(input: AudioInput, transcription: &str, segments: &[(u64, u64)]) -> TranscriptionResult {
+    TranscriptionResult {
+        path: "chunk.mp4".to_string(),
+        input,
+        transcription: Some(transcription.to_string()),
+        segments: segments
+            .iter()
+            .map(|&(start_ms, end_ms)| TranscriptionSegment {
+                text: String::new(),
+                start_ms,
+                end_ms,
+            })
+            .collect(),
+        timestamp: 0,
+        error: None,
+    }
+}
+
+#[test]
+fn test_merge_overlapping() {
+    assert_eq!(
+        merge_overlapping(
+            "we should ship it on friday",
+            "on Friday, then the release notes"
+        ),
+        "then the release notes"
+    );
+    // the end of "release" was cut from the previous chunk
+    assert_eq!(
+        merge_overlapping("then the rel", "the release notes"),
+        "release notes"
+    );
+    assert_eq!(
+        merge_overlapping("hello there", "general kenobi"),
+        "general kenobi"
+    );
+    assert_eq!(merge_overlapping("", "general kenobi"), "general kenobi");
+    assert_eq!(merge_overlapping("it is done", "done"), "");
+}
+
+#[test]
+fn test_chunk_overlap_extend_and_merge() {
+    let mic = Arc::new(AudioDevice::new("mic".to_string(), DeviceType::Input));
+    let speaker = Arc::new(AudioDevice::new("speaker".to_string(), DeviceType::Output));
+    let mut overlap = ChunkOverlap::new(Duration::from_millis(500));
+
+    let first = overlap.extend(input(&mic, (0..20).map(|i| i as f32).collect()));
+    assert_eq!(first.data.len(), 20);
+    let first = overlap.merge(result(first, "see you on monday", &[(0, 2000)]));
+    assert_eq!(first.transcription.as_deref(), Some("see you on monday"));
+
+    // the other device has no tail yet
+    assert_eq!(
+        overlap.extend(input(&speaker, vec![0.0; 20])).data.len(),
+        20
+    );
+
+    let second = overlap.extend(input(&mic, (20..40).map(|i| i as f32).collect()));
+    assert_eq!(second.data.len(), 25);
+    assert_eq!(second.data[..6], [15.0, 16.0, 17.0, 18.0, 19.0, 20.0]);
+    assert_eq!(overlap.prepended_samples(&mic), 5);
+    assert_eq!(overlap.prepended_samples(&speaker), 0);
+    let second = overlap.merge(result(
+        second,
+        "on monday morning",
+        &[(0, 400), (300, 2500)],
+    ));
+    assert_eq!(second.transcription.as_deref(), Some("morning"));
+    // timed from the start of the stored chunk, after the 500 ms of the previous one
+    assert_eq!(second.segments.len(), 1);
+    assert_eq!(second.segments[0].start_ms, 0);
+    assert_eq!(second.segments[0].end_ms, 2000);
+}
+
+#[test]
+fn test_chunk_overlap_disabled() {
+    let mic = Arc::new(AudioDevice::new("mic".to_string(), DeviceType::Input));
+    let mut overlap = ChunkOverlap::new(Duration::ZERO);
+    overlap.extend(input(&mic, vec![0.0; 20]));
+    let second = overlap.extend(input(&mic, vec![0.0; 20]));
+    assert_eq!(second.data.len(), 20);
+    let second = overlap.merge(result(second, "monday monday", &[]));
+    assert_eq!(second.transcription.as_deref(), Some("monday monday"));
+}
@@ -0,0 +1,33 @@
+use screenpipe_audio::pcm_decode::pcm_decode;
+use screenpipe_audio::{encode_single_audio, read_audio_file, AudioFormat};
+use std::path::Path;
+
+#[test]
+fn test_wav_zstd_round_trip() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("chunk.wav.zst");
+    let samples: Vec<f32> = (0..8000).map(|i| (i as f32 / 50.0).sin() * 0.5).collect();
+    let data: Vec<u8> = samples.iter().flat_map(|s| s.to_le_bytes()).collect();
+    encode_single_audio(&data, 16000, 1, &path, AudioFormat::WavZstd).unwrap();
+
+    assert_eq!(AudioFormat::from_path(&path), Some(AudioFormat::WavZstd));
+    assert_eq!(AudioFormat::WavZstd.to_string(), "wav+zstd");
+    assert_eq!(&read_audio_file(&path).unwrap()[..4], b"RIFF");
+    assert_ne!(&std::fs::read(&path).unwrap()[..4], b"RIFF");
+
+    let (decoded, sample_rate) = pcm_decode(&path).unwrap();
+    assert_eq!(sample_rate, 16000);
+    assert_eq!(decoded.len(), samples.len());
+    for (decoded, sample) in decoded.iter().zip(&samples) {
+        assert!((decoded - sample).abs() < 1e-3);
+    }
+}
+
+#[test]
+fn test_audio_format_from_path() {
+    let format = |path: &str| AudioFormat::from_path(Path::new(path));
+    assert_eq!(format("a/chunk.WAV.zst"), Some(AudioFormat::WavZstd));
+    assert_eq!(format("chunk.wav"), Some(AudioFormat::Wav));
+    assert_eq!(format("chunk.flac.zst"), None);
+    assert_eq!(AudioFormat::Mp4.to_string(), "mp4");
+}
@@ -6,15 +6,19 @@ mod tests {
     use screenpipe_audio::vad_engine::{SileroVad, VadEngine, VadEngineEnum, VadSensitivity};
     use screenpipe_audio::whisper::WhisperModel;
     use screenpipe_audio::{
-        default_output_device, list_audio_devices, pcm_decode, AudioInput, AudioTranscriptionEngine,
+        default_output_device, list_audio_devices, pcm_decode, AudioFormat, AudioInput,
+        AudioTranscriptionEngine,
+    };
+    use screenpipe_audio::{
+        find_audio_device, normalize_device_name, parse_audio_device, record_and_transcribe,
+        AudioDevice, DeviceType,
     };
-    use screenpipe_audio::{parse_audio_device, record_and_transcribe};
     use std::path::PathBuf;
     use std::process::Command;
     use std::str::FromStr;
     use std::sync::atomic::{AtomicBool, Ordering};
     use std::sync::{Arc, Mutex};
-    use std::time::{Duration, Instant};
+    use std::time::{Duration, Instant, SystemTime};
 
     fn setup() {
         // Initialize the logger with an info level filter
@@ -42,6 +46,111 @@ mod tests {
         assert_eq!(spec.to_string(), "Test Device (input)");
     }
 
+    #[test]
+    fn test_normalize_device_name() {
+        for (raw, normalized) in [
+            ("MacBook Microphone (Built-in)", "MacBook Microphone"),
+            ("MacBook Microphone", "MacBook Microphone"),
+            (
+                "Speakers (Realtek) {0.0.0.00000000}.{5f0a1b2c-1111-2222-3333-444455556666}",
+                "Speakers (Realtek)",
+            ),
+            (
+                "Headset  Mic ({A1B2C3D4-1111-2222-3333-444455556666})",
+                "Headset Mic",
+            ),
+            ("Studio {Left}", "Studio {Left}"),
+            (
+                "{a1b2c3d4-1111-2222-3333-444455556666}",
+                "{a1b2c3d4-1111-2222-3333-444455556666}",
+            ),
+        ] {
+            assert_eq!(normalize_device_name(raw), normalized, "{}", raw);
+        }
+    }
+
+    #[test]
+    fn test_find_audio_device() {
+        let devices = [
+            AudioDevice::new(
+                "MacBook Microphone (Built-in)".to_string(),
+                DeviceType::Input,
+            ),
+            AudioDevice::new("MacBook Microphone".to_string(), DeviceType::Output),
+            AudioDevice::new("USB Mic {0.0.1.00000000}".to_string(), DeviceType::Input),
+        ];
+        let find = |name: &str| {
+            let requested = parse_audio_device(name).unwrap();
+            find_audio_device(&devices, &requested).map(|device| device.name.clone())
+        };
+        assert_eq!(
+            find("macbook microphone (input)").as_deref(),
+            Some("MacBook Microphone (Built-in)")
+        );
+        // full names still match as they are
+        assert_eq!(
+            find("MacBook Microphone (Built-in) (input)").as_deref(),
+            Some("MacBook Microphone (Built-in)")
+        );
+        assert_eq!(
+            find("MacBook Microphone (output)").as_deref(),
+            Some("MacBook Microphone")
+        );
+        assert_eq!(
+            find("USB Mic (input)").as_deref(),
+            Some("USB Mic {0.0.1.00000000}")
+        );
+        assert_eq!(find("USB Mic (output)"), None);
+    }
+
+    #[test]
+    fn test_normalize_rms_targets_dbfs() {
+        use screenpipe_audio::audio_processing::{normalize_rms, TARGET_RMS_DBFS};
+
+        let quiet: Vec<f32> = (0..16000).map(|i| 0.01 * (i as f32 * 0.05).sin()).collect();
+        let normalized = normalize_rms(&quiet, TARGET_RMS_DBFS);
+
+        let rms = (normalized.iter().map(|x| x * x).sum::<f32>() / normalized.len() as f32).sqrt();
+        let rms_dbfs = 20.0 * rms.log10();
+        assert!((rms_dbfs - TARGET_RMS_DBFS).abs() < 0.1);
+
+        // silence must not be amplified into noise
+        let silence = vec![0.0f32; 1600];
+        assert_eq!(normalize_rms(&silence, TARGET_RMS_DBFS), silence);
+    }
+
+    #[test]
+    fn test_downmix_to_mono() {
+        use screenpipe_audio::audio_processing::downmix_to_mono;
+
+        let stereo = vec![0.2, 0.4, -1.0, 1.0, 0.5, 0.5];
+        assert_eq!(downmix_to_mono(&stereo, 2), vec![0.3, 0.0, 0.5]);
+        assert_eq!(downmix_to_mono(&stereo, 1), stereo);
+    }
+
+    #[test]
+    fn test_align_reference() {
+        use screenpipe_audio::audio_processing::align_reference;
+
+        let start = SystemTime::UNIX_EPOCH + Duration::from_secs(100);
+        let earlier = [1.0, 2.0, 3.0, 4.0];
+        let later = [5.0, 6.0];
+        // 10 samples a second: one chunk starting 0.2s before the microphone, one 0.5s after
+        let chunks = [
+            (start - Duration::from_millis(200), &earlier[..]),
+            (start + Duration::from_millis(500), &later[..]),
+        ];
+        assert_eq!(
+            align_reference(&chunks, start, 8, 10),
+            vec![3.0, 4.0, 0.0, 0.0, 0.0, 5.0, 6.0, 0.0]
+        );
+        // output recorded before the microphone chunk is not used for it
+        assert_eq!(
+            align_reference(&chunks[..1], start + Duration::from_secs(1), 3, 10),
+            vec![0.0; 3]
+        );
+    }
+
     #[tokio::test]
     #[ignore] // Add this if you want to skip this test in regular test runs
     async fn test_record_and_transcribe() {
@@ -201,6 +310,11 @@ mod tests {
             None,
             &output_path_2.clone(),
             VadSensitivity::High,
+            false,
+            false,
+            AudioFormat::Mp4,
+            Duration::ZERO,
+            false,
         )
         .await
         .unwrap();
@@ -285,6 +399,7 @@ mod tests {
             sample_rate: 16000, // Adjust this based on your test audio
             channels: 1,
             device: Arc::new(default_output_device().unwrap()),
+            end_time: SystemTime::now(),
         };
 
         // Initialize the WhisperModel
@@ -306,6 +421,9 @@ mod tests {
             None,
             &output_path,
             true,
+            false,
+            AudioFormat::Mp4,
+            0,
         )
         .await;
 
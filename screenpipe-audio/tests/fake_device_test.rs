@@ -0,0 +1,53 @@
+use screenpipe_audio::fake_device::{
+    fake_audio_device, fake_device_frequency, sine_wave, FAKE_DEVICE_SAMPLE_RATE,
+};
+use screenpipe_audio::{audio_device_available, record_and_transcribe, AudioDevice, DeviceType};
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+use std::time::Duration;
+
+#[test]
+fn test_fake_device_frequency() {
+    let device = fake_audio_device(440.0);
+    assert_eq!(device.device_type, DeviceType::Input);
+    assert_eq!(fake_device_frequency(&device), Some(440.0));
+    let real = AudioDevice::new("MacBook Pro Microphone".to_string(), DeviceType::Input);
+    assert_eq!(fake_device_frequency(&real), None);
+}
+
+#[test]
+fn test_sine_wave() {
+    // a 1000 Hz wave at 16 kHz repeats every 16 samples
+    let wave = sine_wave(1000.0, 16000, 0, 32);
+    assert!(wave[0].abs() < 1e-6);
+    assert!((wave[4] - 0.5).abs() < 1e-6);
+    assert!((wave[12] + 0.5).abs() < 1e-6);
+    assert!((wave[20] - wave[4]).abs() < 1e-6);
+    // continues where the previous chunk stopped
+    assert_eq!(sine_wave(1000.0, 16000, 16, 16), wave[16..].to_vec());
+}
+
+#[tokio::test]
+async fn test_record_fake_device() {
+    let device = Arc::new(fake_audio_device(440.0));
+    assert!(audio_device_available(&device).await);
+
+    let (sender, receiver) = crossbeam::channel::unbounded();
+    record_and_transcribe(
+        Arc::clone(&device),
+        Duration::from_millis(500),
+        sender,
+        Arc::new(AtomicBool::new(true)),
+    )
+    .await
+    .unwrap();
+
+    let input = receiver.try_recv().unwrap();
+    assert_eq!(input.device, device);
+    assert_eq!(input.sample_rate, FAKE_DEVICE_SAMPLE_RATE);
+    assert_eq!(input.channels, 1);
+    // half a second of samples, give or take a tick
+    let expected = FAKE_DEVICE_SAMPLE_RATE as usize / 2;
+    assert!(input.data.len() >= expected - 1600 && input.data.len() <= expected);
+    assert!(input.data.iter().all(|s| s.abs() <= 0.5));
+}
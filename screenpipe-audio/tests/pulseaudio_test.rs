@@ -0,0 +1,70 @@
+use screenpipe_audio::pulseaudio::{
+    parse_default_sink, parse_pactl_sources, select_monitor_source, PulseSource,
+};
+
+const PACTL_SOURCES: &str = "Source #0
+	State: SUSPENDED
+	Name: alsa_output.pci-0000_00_1f.3.analog-stereo.monitor
+	Description: Monitor of Built-in Audio Analog Stereo
+	Driver: module-alsa-card.c
+	Monitor of Sink: alsa_output.pci-0000_00_1f.3.analog-stereo
+	Latency: 0 usec, configured 0 usec
+
+Source #1
+	State: RUNNING
+	Name: alsa_input.pci-0000_00_1f.3.analog-stereo
+	Description: Built-in Audio Analog Stereo
+	Driver: module-alsa-card.c
+	Monitor of Sink: n/a
+
+Source #2
+	State: IDLE
+	Name: bluez_output.00_1B_66_AA_BB_CC.1.monitor
+	Description: Monitor of Headphones
+	Monitor of Sink: bluez_output.00_1B_66_AA_BB_CC.1
+";
+
+#[test]
+fn test_parse_pactl_sources() {
+    let sources = parse_pactl_sources(PACTL_SOURCES);
+    assert_eq!(sources.len(), 3);
+    assert_eq!(
+        sources[0],
+        PulseSource {
+            name: "alsa_output.pci-0000_00_1f.3.analog-stereo.monitor".to_string(),
+            description: "Monitor of Built-in Audio Analog Stereo".to_string(),
+            monitor_of_sink: Some("alsa_output.pci-0000_00_1f.3.analog-stereo".to_string()),
+        }
+    );
+    assert_eq!(sources[1].monitor_of_sink, None);
+    assert!(parse_pactl_sources("").is_empty());
+}
+
+#[test]
+fn test_parse_default_sink() {
+    let info = "Server Name: PulseAudio (on PipeWire 1.0.5)\nDefault Sink: bluez_output.00_1B_66_AA_BB_CC.1\nDefault Source: alsa_input.pci-0000_00_1f.3.analog-stereo\n";
+    assert_eq!(
+        parse_default_sink(info).as_deref(),
+        Some("bluez_output.00_1B_66_AA_BB_CC.1")
+    );
+    assert_eq!(parse_default_sink("Default Sink: \n"), None);
+}
+
+#[test]
+fn test_select_monitor_source() {
+    let sources = parse_pactl_sources(PACTL_SOURCES);
+    let selected = |sink| select_monitor_source(&sources, sink).map(|source| source.name);
+
+    assert_eq!(
+        selected(Some("bluez_output.00_1B_66_AA_BB_CC.1")).as_deref(),
+        Some("bluez_output.00_1B_66_AA_BB_CC.1.monitor")
+    );
+    // unknown or no default sink falls back to the first monitor
+    for sink in [Some("gone"), None] {
+        assert_eq!(
+            selected(sink).as_deref(),
+            Some("alsa_output.pci-0000_00_1f.3.analog-stereo.monitor")
+        );
+    }
+    assert_eq!(select_monitor_source(&sources[1..2], None), None);
+}
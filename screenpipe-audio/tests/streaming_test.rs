@@ -0,0 +1,58 @@
+use screenpipe_audio::streaming::{StreamStep, Utterance, STREAM_FRAME_LEN};
+use screenpipe_audio::TranscriptEvent;
+
+fn push(utterance: &mut Utterance, frames: usize, is_voice: bool) -> Vec<StreamStep> {
+    let frame = vec![0.1; STREAM_FRAME_LEN];
+    (0..frames)
+        .filter_map(|_| utterance.push_frame(&frame, is_voice))
+        .collect()
+}
+
+#[test]
+fn test_leading_silence_is_dropped() {
+    let mut utterance = Utterance::new();
+    assert!(push(&mut utterance, 5, false).is_empty());
+    assert!(utterance.samples().is_empty());
+    assert_eq!(utterance.start_ms(), 500);
+    assert_eq!(utterance.end_ms(), 500);
+}
+
+#[test]
+fn test_partials_then_final_on_pause() {
+    let mut utterance = Utterance::new();
+    assert_eq!(push(&mut utterance, 25, true), vec![StreamStep::Partial; 2]);
+    assert_eq!(
+        push(&mut utterance, 6, false),
+        vec![StreamStep::Partial, StreamStep::Final]
+    );
+    assert_eq!(utterance.end_ms(), 3100);
+
+    utterance.finish();
+    assert!(utterance.samples().is_empty());
+    assert_eq!(utterance.start_ms(), 3100);
+    // a short pause doesn't end the next utterance
+    push(&mut utterance, 3, true);
+    assert!(push(&mut utterance, 5, false).is_empty());
+    assert!(push(&mut utterance, 1, true).is_empty());
+}
+
+#[test]
+fn test_long_utterance_is_cut() {
+    let mut utterance = Utterance::new();
+    let steps = push(&mut utterance, 150, true);
+    assert_eq!(steps.last(), Some(&StreamStep::Final));
+    assert_eq!(utterance.end_ms(), 15_000);
+}
+
+#[test]
+fn test_transcript_event_json() {
+    let event = TranscriptEvent::Partial {
+        text: "hello".to_string(),
+        start_ms: 0,
+        end_ms: 1000,
+    };
+    assert_eq!(
+        serde_json::to_value(&event).unwrap(),
+        serde_json::json!({"type": "partial", "text": "hello", "start_ms": 0, "end_ms": 1000})
+    );
+}
@@ -0,0 +1,29 @@
+#![cfg(target_os = "windows")]
+
+use screenpipe_audio::wasapi::{default_loopback_device, list_loopback_devices};
+use screenpipe_audio::{default_output_device, list_audio_devices, DeviceType};
+
+#[tokio::test]
+async fn test_loopback_devices_are_listed() {
+    let devices = list_audio_devices().await.unwrap();
+    let loopback = list_loopback_devices();
+    // ci runners may have no playback device at all
+    if loopback.is_empty() {
+        return;
+    }
+
+    // cpal and wasapi name the playback devices the same way
+    for name in &loopback {
+        assert!(
+            devices
+                .iter()
+                .any(|d| d.device_type == DeviceType::Output && &d.name == name),
+            "{} missing from list_audio_devices",
+            name
+        );
+    }
+    let default = default_output_device().unwrap();
+    assert_eq!(default.device_type, DeviceType::Output);
+    assert_eq!(default.name, default_loopback_device().unwrap());
+    assert!(loopback.contains(&default.name));
+}
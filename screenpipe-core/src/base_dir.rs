@@ -0,0 +1,63 @@
+use anyhow::{anyhow, Result};
+use std::fs;
+use std::path::PathBuf;
+
+/// Where screenpipe keeps its files. Recordings go to `<base_dir>/data` unless `--frames-dir` or
+/// `--audio-dir` put them elsewhere, e.g. video on a fast ssd and audio on a larger disk.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StoragePaths {
+    /// Database, logs, pipes and summaries
+    pub base_dir: PathBuf,
+    /// Video chunks and frame thumbnails
+    pub frames_dir: PathBuf,
+    /// Audio chunks
+    pub audio_dir: PathBuf,
+}
+
+impl StoragePaths {
+    /// Both kinds of recordings in `<base_dir>/data`.
+    pub fn new(base_dir: PathBuf) -> Self {
+        let data_dir = base_dir.join("data");
+        StoragePaths {
+            frames_dir: data_dir.clone(),
+            audio_dir: data_dir,
+            base_dir,
+        }
+    }
+
+    /// The recording directories, once each when they are the same.
+    pub fn media_dirs(&self) -> Vec<&PathBuf> {
+        let mut dirs = vec![&self.frames_dir];
+        if self.audio_dir != self.frames_dir {
+            dirs.push(&self.audio_dir);
+        }
+        dirs
+    }
+}
+
+/// The screenpipe directories: `custom_path` when given, `~/.screenpipe` otherwise, with
+/// recordings in `frames_dir` and `audio_dir` when given. Missing directories are created.
+pub fn get_base_dir(
+    custom_path: Option<String>,
+    frames_dir: Option<String>,
+    audio_dir: Option<String>,
+) -> Result<StoragePaths> {
+    let default_path = dirs::home_dir()
+        .ok_or_else(|| anyhow!("failed to get home directory"))?
+        .join(".screenpipe");
+
+    let base_dir = custom_path.map(PathBuf::from).unwrap_or(default_path);
+    let mut paths = StoragePaths::new(base_dir);
+    if let Some(frames_dir) = frames_dir {
+        paths.frames_dir = PathBuf::from(frames_dir);
+    }
+    if let Some(audio_dir) = audio_dir {
+        paths.audio_dir = PathBuf::from(audio_dir);
+    }
+
+    fs::create_dir_all(&paths.base_dir)?;
+    for dir in paths.media_dirs() {
+        fs::create_dir_all(dir)?;
+    }
+    Ok(paths)
+}
@@ -1,5 +1,11 @@
+pub mod base_dir;
+pub use base_dir::{get_base_dir, StoragePaths};
 pub mod ffmpeg;
 pub use ffmpeg::find_ffmpeg_path;
+pub mod multi_writer;
+pub use multi_writer::{MultiWriter, MultiWriterError, WriterId};
+pub mod retry;
+pub use retry::retry;
 #[cfg(feature = "llm")]
 pub mod llm;
 #[cfg(feature = "llm")]
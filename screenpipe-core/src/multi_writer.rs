@@ -0,0 +1,99 @@
+//! A `Write` duplicating its output to several targets, e.g. the console and a log file.
+
+use std::io::Write;
+
+/// Handle of a target added to a [`MultiWriter`], to remove it again.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct WriterId(u64);
+
+/// Writes everything to each of its targets. A failing target does not keep the data from the
+/// others, its error is returned once all of them were written, as a [`MultiWriterError`].
+pub struct MultiWriter {
+    writers: Vec<(WriterId, Box<dyn Write + Send>)>,
+    next_id: u64,
+}
+
+/// Errors of the targets that failed, found with `io::Error::get_ref` and `downcast_ref`.
+#[derive(Debug)]
+pub struct MultiWriterError {
+    pub errors: Vec<(WriterId, std::io::Error)>,
+}
+
+impl std::fmt::Display for MultiWriterError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} log writer(s) failed:", self.errors.len())?;
+        for (id, e) in &self.errors {
+            write!(f, " [{}] {}", id.0, e)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for MultiWriterError {}
+
+impl MultiWriter {
+    pub fn new(writers: Vec<Box<dyn Write + Send>>) -> Self {
+        let mut multi_writer = MultiWriter {
+            writers: Vec::with_capacity(writers.len()),
+            next_id: 0,
+        };
+        for writer in writers {
+            multi_writer.add_writer(writer);
+        }
+        multi_writer
+    }
+
+    /// Ids of the targets in the order they are written to.
+    pub fn writer_ids(&self) -> Vec<WriterId> {
+        self.writers.iter().map(|(id, _)| *id).collect()
+    }
+
+    pub fn add_writer(&mut self, writer: Box<dyn Write + Send>) -> WriterId {
+        let id = WriterId(self.next_id);
+        self.next_id += 1;
+        self.writers.push((id, writer));
+        id
+    }
+
+    /// Removes a target, e.g. the log file being rotated, after flushing it. `None` when it was
+    /// already removed.
+    pub fn remove_writer(&mut self, id: WriterId) -> Option<Box<dyn Write + Send>> {
+        let index = self
+            .writers
+            .iter()
+            .position(|(writer_id, _)| *writer_id == id)?;
+        let (_, mut writer) = self.writers.remove(index);
+        let _ = writer.flush();
+        Some(writer)
+    }
+
+    fn for_each_writer(
+        &mut self,
+        mut f: impl FnMut(&mut dyn Write) -> std::io::Result<()>,
+    ) -> std::io::Result<()> {
+        let errors: Vec<_> = self
+            .writers
+            .iter_mut()
+            .filter_map(|(id, writer)| f(writer.as_mut()).err().map(|e| (*id, e)))
+            .collect();
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(std::io::Error::new(
+                std::io::ErrorKind::Other,
+                MultiWriterError { errors },
+            ))
+        }
+    }
+}
+
+impl Write for MultiWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.for_each_writer(|writer| writer.write_all(buf))?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.for_each_writer(|writer| writer.flush())
+    }
+}
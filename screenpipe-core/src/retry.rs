@@ -0,0 +1,26 @@
+use log::debug;
+use std::fmt::Display;
+use std::future::Future;
+use std::time::Duration;
+
+/// Runs `f` until it succeeds, at most `attempts` times with `delay` between two of them, and
+/// returns the error of the last attempt. `f` gets the attempt number, starting at 1.
+pub async fn retry<T, E, F, Fut>(attempts: u32, delay: Duration, mut f: F) -> Result<T, E>
+where
+    E: Display,
+    F: FnMut(u32) -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+{
+    let mut attempt = 1;
+    loop {
+        match f(attempt).await {
+            Ok(value) => return Ok(value),
+            Err(e) if attempt >= attempts => return Err(e),
+            Err(e) => {
+                debug!("attempt {}/{} failed: {}", attempt, attempts, e);
+                attempt += 1;
+                tokio::time::sleep(delay).await;
+            }
+        }
+    }
+}
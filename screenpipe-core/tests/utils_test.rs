@@ -0,0 +1,125 @@
+use screenpipe_core::{get_base_dir, retry, MultiWriter, MultiWriterError, StoragePaths};
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+#[test]
+fn test_get_base_dir() {
+    let dir = tempfile::tempdir().unwrap();
+    let custom = dir.path().join("screenpipe");
+    let paths = get_base_dir(Some(custom.to_string_lossy().into_owned()), None, None).unwrap();
+    assert_eq!(paths, StoragePaths::new(custom.clone()));
+    assert_eq!(paths.frames_dir, custom.join("data"));
+    assert!(custom.join("data").is_dir());
+    assert_eq!(paths.media_dirs().len(), 1);
+    // already created is fine
+    assert_eq!(
+        get_base_dir(Some(custom.to_string_lossy().into_owned()), None, None).unwrap(),
+        paths
+    );
+}
+
+#[test]
+fn test_get_base_dir_split_storage() {
+    let dir = tempfile::tempdir().unwrap();
+    let custom = dir.path().join("screenpipe");
+    let frames = dir.path().join("nvme").join("frames");
+    let audio = dir.path().join("hdd").join("audio");
+    let paths = get_base_dir(
+        Some(custom.to_string_lossy().into_owned()),
+        Some(frames.to_string_lossy().into_owned()),
+        Some(audio.to_string_lossy().into_owned()),
+    )
+    .unwrap();
+    assert_eq!(paths.base_dir, custom);
+    assert_eq!(paths.frames_dir, frames);
+    assert_eq!(paths.audio_dir, audio);
+    assert!(frames.is_dir() && audio.is_dir());
+    assert!(!custom.join("data").exists());
+    assert_eq!(paths.media_dirs(), vec![&frames, &audio]);
+}
+
+#[tokio::test]
+async fn test_retry() {
+    let calls = Mutex::new(Vec::new());
+    let result: Result<u32, String> = retry(5, Duration::from_millis(1), |attempt| {
+        calls.lock().unwrap().push(attempt);
+        async move {
+            if attempt < 3 {
+                Err(format!("attempt {}", attempt))
+            } else {
+                Ok(attempt)
+            }
+        }
+    })
+    .await;
+    assert_eq!(result, Ok(3));
+    assert_eq!(*calls.lock().unwrap(), vec![1, 2, 3]);
+
+    // the last error is returned once the attempts run out
+    let result: Result<(), String> = retry(2, Duration::from_millis(1), |attempt| async move {
+        Err(format!("attempt {}", attempt))
+    })
+    .await;
+    assert_eq!(result, Err("attempt 2".to_string()));
+}
+
+#[derive(Clone, Default)]
+struct Captured(Arc<Mutex<Vec<u8>>>);
+
+impl Write for Captured {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+struct Broken;
+
+impl Write for Broken {
+    fn write(&mut self, _buf: &[u8]) -> std::io::Result<usize> {
+        Err(std::io::Error::new(std::io::ErrorKind::BrokenPipe, "gone"))
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Err(std::io::Error::new(std::io::ErrorKind::BrokenPipe, "gone"))
+    }
+}
+
+#[test]
+fn test_multi_writer_errors_and_swap() {
+    let first = Captured::default();
+    let second = Captured::default();
+    let mut writer = MultiWriter::new(vec![Box::new(first.clone()), Box::new(Broken)]);
+    let broken_id = writer.writer_ids()[1];
+    let second_id = writer.add_writer(Box::new(second.clone()));
+
+    // the broken target is reported, the others still get the line
+    let e = writer.write_all(b"line 1\n").unwrap_err();
+    let errors = &e
+        .get_ref()
+        .and_then(|e| e.downcast_ref::<MultiWriterError>())
+        .unwrap()
+        .errors;
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].0, broken_id);
+    assert!(writer.flush().is_err());
+    assert_eq!(&first.0.lock().unwrap()[..], b"line 1\n");
+    assert_eq!(&second.0.lock().unwrap()[..], b"line 1\n");
+
+    assert!(writer.remove_writer(broken_id).is_some());
+    assert!(writer.remove_writer(broken_id).is_none());
+    // rotation: the new file target replaces the old one
+    let rotated = Captured::default();
+    writer.add_writer(Box::new(rotated.clone()));
+    writer.remove_writer(second_id);
+    writer.write_all(b"line 2\n").unwrap();
+    writer.flush().unwrap();
+    assert_eq!(&first.0.lock().unwrap()[..], b"line 1\nline 2\n");
+    assert_eq!(&second.0.lock().unwrap()[..], b"line 1\n");
+    assert_eq!(&rotated.0.lock().unwrap()[..], b"line 2\n");
+}
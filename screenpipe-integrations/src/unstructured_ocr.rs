@@ -1,6 +1,7 @@
 use anyhow::{anyhow, Result};
 use image::{codecs::png::PngEncoder, DynamicImage, ImageEncoder};
-use log::error;
+use log::{error, warn};
+use rand::Rng;
 use reqwest::multipart::{Form, Part};
 use reqwest::Client;
 use serde_json;
@@ -11,8 +12,31 @@ use std::io::Cursor;
 use std::io::Read;
 use std::io::Write;
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
 use tempfile::NamedTempFile;
-use tokio::time::{timeout, Duration};
+use tokio::time::Duration;
+
+pub const DEFAULT_CLOUD_OCR_TIMEOUT_MS: u64 = 10_000;
+const CLOUD_OCR_ATTEMPTS: u32 = 3;
+const CLOUD_OCR_RETRY_DELAY_MS: u64 = 500;
+
+static CLOUD_OCR_TIMEOUT_MS: AtomicU64 = AtomicU64::new(DEFAULT_CLOUD_OCR_TIMEOUT_MS);
+
+/// Time a single cloud ocr request may take, connection included, before it is retried.
+pub fn set_cloud_ocr_timeout(timeout: Duration) {
+    CLOUD_OCR_TIMEOUT_MS.store(timeout.as_millis() as u64, Ordering::Relaxed);
+}
+
+pub fn cloud_ocr_timeout() -> Duration {
+    Duration::from_millis(CLOUD_OCR_TIMEOUT_MS.load(Ordering::Relaxed))
+}
+
+/// Wait before attempt `attempt + 1`: growing with each attempt, jittered so that the
+/// monitors recording in parallel do not retry in lockstep.
+fn retry_delay(attempt: u32) -> Duration {
+    let base = CLOUD_OCR_RETRY_DELAY_MS * 2u64.pow(attempt - 1);
+    Duration::from_millis(base + rand::thread_rng().gen_range(0..=base))
+}
 
 pub async fn perform_ocr_cloud(image: &DynamicImage) -> Result<(String, String, Option<f64>)> {
     let api_key = match env::var("UNSTRUCTURED_API_KEY") {
@@ -35,37 +59,52 @@ pub async fn perform_ocr_cloud(image: &DynamicImage) -> Result<(String, String,
         )
         .unwrap();
 
-    let part = Part::bytes(buffer)
-        .file_name("image.png".to_string())
-        .mime_str("image/png")
-        .unwrap();
-
-    let form = Form::new()
-        .part("files", part)
-        .text("strategy", "auto")
-        .text("coordinates", "true");
-
-    let client = reqwest::Client::new();
-    let response = match timeout(
-        Duration::from_secs(180),
-        client
+    let client = Client::builder().timeout(cloud_ocr_timeout()).build()?;
+    let mut attempt = 1;
+    let response_text = loop {
+        let part = Part::bytes(buffer.clone())
+            .file_name("image.png".to_string())
+            .mime_str("image/png")
+            .unwrap();
+        let form = Form::new()
+            .part("files", part)
+            .text("strategy", "auto")
+            .text("coordinates", "true");
+
+        let result = client
             .post(&api_url)
             .header("accept", "application/json")
             .header("unstructured-api-key", &api_key)
             .multipart(form)
-            .send(),
-    )
-    .await
-    {
-        Ok(Ok(response)) => response,
-        Ok(Err(e)) => return Err(anyhow!("Request error: {}", e)),
-        Err(_) => return Err(anyhow!("Request timed out")),
-    };
-
-    let response_text = if response.status().is_success() {
-        response.text().await?
-    } else {
-        return Err(anyhow!("Error: {}", response.status()));
+            .send()
+            .await;
+        let error = match result {
+            Ok(response) if response.status().is_success() => break response.text().await?,
+            // other client errors, e.g. a bad api key, fail the same way on every attempt
+            Ok(response)
+                if response.status().is_client_error()
+                    && response.status() != reqwest::StatusCode::TOO_MANY_REQUESTS =>
+            {
+                return Err(anyhow!("Error: {}", response.status()));
+            }
+            Ok(response) => anyhow!("Error: {}", response.status()),
+            Err(e) if e.is_timeout() => anyhow!("Request timed out"),
+            Err(e) => anyhow!("Request error: {}", e),
+        };
+        if attempt == CLOUD_OCR_ATTEMPTS {
+            return Err(anyhow!(
+                "{} (gave up after {} attempts)",
+                error,
+                CLOUD_OCR_ATTEMPTS
+            ));
+        }
+        let delay = retry_delay(attempt);
+        warn!(
+            "cloud ocr attempt {} failed: {}, retrying in {:?}",
+            attempt, error, delay
+        );
+        tokio::time::sleep(delay).await;
+        attempt += 1;
     };
 
     let json_output = response_text.clone();
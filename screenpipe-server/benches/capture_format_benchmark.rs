@@ -0,0 +1,76 @@
+// cargo bench --bench capture_format_benchmark
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use image::{DynamicImage, Rgb, RgbImage};
+use rand::Rng;
+use screenpipe_server::CaptureFormat;
+
+const WIDTH: u32 = 1920;
+const HEIGHT: u32 = 1080;
+
+/// Dark text lines on a light background, like an editor or a web page.
+fn text_screen() -> DynamicImage {
+    let mut rng = rand::thread_rng();
+    let mut image = RgbImage::from_pixel(WIDTH, HEIGHT, Rgb([250, 250, 250]));
+    for line in (20..HEIGHT - 20).step_by(24) {
+        let mut x = 20;
+        while x < WIDTH - 40 {
+            let word = rng.gen_range(3..12) * 9;
+            for dx in 0..word.min(WIDTH - 20 - x) {
+                for dy in 0..14 {
+                    if rng.gen_bool(0.4) {
+                        image.put_pixel(x + dx, line + dy, Rgb([30, 30, 30]));
+                    }
+                }
+            }
+            x += word + 9;
+        }
+    }
+    DynamicImage::ImageRgb8(image)
+}
+
+/// Flat panels and gradients, like a desktop with a few windows.
+fn ui_screen() -> DynamicImage {
+    DynamicImage::ImageRgb8(RgbImage::from_fn(WIDTH, HEIGHT, |x, y| {
+        match (x / 480, y / 270) {
+            (0, _) => Rgb([40, 44, 52]),
+            (_, 0) => Rgb([230, 230, 235]),
+            _ => Rgb([(x % 256) as u8, (y % 256) as u8, 128]),
+        }
+    }))
+}
+
+/// Noise, the worst case, e.g. a photo or a video playing.
+fn photo_screen() -> DynamicImage {
+    let mut rng = rand::thread_rng();
+    DynamicImage::ImageRgb8(RgbImage::from_fn(WIDTH, HEIGHT, |_, _| {
+        Rgb([rng.gen(), rng.gen(), rng.gen()])
+    }))
+}
+
+fn bench_capture_formats(c: &mut Criterion) {
+    let screens = [
+        ("text", text_screen()),
+        ("ui", ui_screen()),
+        ("photo", photo_screen()),
+    ];
+    let formats = [CaptureFormat::Png, CaptureFormat::WebpLossless];
+
+    let mut group = c.benchmark_group("capture_format_encode");
+    group.sample_size(10);
+    for (content, image) in &screens {
+        for format in formats {
+            let size = format.encode(image).unwrap().len();
+            println!("{} {:?}: {} KB", content, format, size / 1024);
+            group.bench_with_input(
+                BenchmarkId::new(format!("{:?}", format), content),
+                image,
+                |b, image| b.iter(|| format.encode(image).unwrap()),
+            );
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_capture_formats);
+criterion_main!(benches);
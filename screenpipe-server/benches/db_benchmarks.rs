@@ -3,7 +3,7 @@
 use criterion::{criterion_group, criterion_main, Criterion};
 use rand::Rng;
 use screenpipe_audio::AudioDevice;
-use screenpipe_server::{ContentType, DatabaseManager};
+use screenpipe_server::{ContentType, DatabaseManager, SearchOrder};
 use screenpipe_vision::OcrEngine;
 use std::sync::Arc;
 use tokio::runtime::Runtime;
@@ -78,6 +78,9 @@ fn bench_search(c: &mut Criterion) {
                                 None,
                                 None,
                                 None,
+                                None,
+                                None,
+                                &SearchOrder::default(),
                             )
                             .await
                             .unwrap()
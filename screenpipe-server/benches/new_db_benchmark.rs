@@ -1,6 +1,8 @@
+use chrono::Utc;
 use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
-use screenpipe_server::DatabaseManager;
+use screenpipe_server::{DatabaseManager, FrameData};
 use screenpipe_vision::OcrEngine;
+use std::sync::Arc;
 use std::time::Duration;
 use tokio::runtime::Runtime;
 
@@ -59,5 +61,73 @@ fn bench_insert_ocr_text(c: &mut Criterion) {
     group.finish();
 }
 
-criterion_group!(benches, bench_insert_ocr_text);
+// 50 frames written one transaction pair per frame vs in batches, as the recording loop does.
+// On disk like a recording, commits cost nothing on an in-memory database.
+fn bench_insert_frames(c: &mut Criterion) {
+    const FRAMES: usize = 50;
+    let dir = tempfile::tempdir().unwrap();
+    let rt = Runtime::new().unwrap();
+    let db = rt
+        .block_on(DatabaseManager::new(
+            &dir.path().join("db.sqlite").to_string_lossy(),
+        ))
+        .expect("Failed to create database");
+    rt.block_on(db.insert_video_chunk("bench.mp4")).unwrap();
+
+    let mut group = c.benchmark_group("Database Insert Frames");
+    group.sample_size(20);
+    group.measurement_time(Duration::from_secs(10));
+
+    let frame = FrameData {
+        timestamp: Utc::now(),
+        text: "fn main() {\n    println!(\"Hello, world!\");\n}".to_string(),
+        text_json: "[]".to_string(),
+        app_name: "BenchmarkApp".to_string(),
+        window_name: "BenchmarkWindow".to_string(),
+        ocr_engine: Arc::new(OcrEngine::AppleNative),
+        focused: true,
+        ocr_failed: false,
+        cursor: None,
+        window_bounds: None,
+    };
+
+    group.bench_function(BenchmarkId::new("One by one", FRAMES), |b| {
+        b.to_async(&rt).iter(|| async {
+            for _ in 0..FRAMES {
+                let frame_id = db.insert_frame().await.unwrap();
+                db.insert_ocr_text(
+                    frame_id,
+                    &frame.text,
+                    &frame.text_json,
+                    &frame.app_name,
+                    &frame.window_name,
+                    Arc::clone(&frame.ocr_engine),
+                    frame.focused,
+                )
+                .await
+                .unwrap();
+            }
+        })
+    });
+
+    for batch_size in [10, 50] {
+        group.bench_with_input(
+            BenchmarkId::new("Batched", batch_size),
+            &batch_size,
+            |b, &batch_size| {
+                b.to_async(&rt).iter(|| async {
+                    for _ in 0..FRAMES / batch_size {
+                        db.bulk_insert_frames(black_box(vec![frame.clone(); batch_size]))
+                            .await
+                            .unwrap();
+                    }
+                })
+            },
+        );
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_insert_ocr_text, bench_insert_frames);
 criterion_main!(benches);
@@ -0,0 +1,196 @@
+//! The whole server, middleware included, on an ephemeral port in front of a temp database,
+//! driven over http like a client would. Tests needing audio hardware are skipped with `CI=true`.
+
+use crossbeam::queue::SegQueue;
+use screenpipe_audio::{AudioDevice, DeviceType};
+use screenpipe_core::StoragePaths;
+use screenpipe_server::{
+    ContentItem, DatabaseManager, ListDeviceResponse, PaginatedResponse, PipeManager,
+    RecordingControl, Server,
+};
+use screenpipe_vision::{CaptureConfig, OcrEngine, OcrPreprocess, DEFAULT_DEDUP_THRESHOLD};
+use std::collections::HashMap;
+use std::sync::atomic::AtomicBool;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use tempfile::TempDir;
+
+struct TestServer {
+    base_url: String,
+    db: Arc<DatabaseManager>,
+    // removed with the server
+    _dir: TempDir,
+}
+
+/// Starts a server and waits until it accepts connections.
+async fn spawn_server() -> TestServer {
+    let dir = tempfile::tempdir().unwrap();
+    let storage = StoragePaths::new(dir.path().to_path_buf());
+    let db = Arc::new(
+        DatabaseManager::new(&dir.path().join("db.sqlite").to_string_lossy())
+            .await
+            .unwrap(),
+    );
+    let server = Server::new(
+        db.clone(),
+        "127.0.0.1:0".parse().unwrap(),
+        Arc::new(AtomicBool::new(false)),
+        Arc::new(SegQueue::new()),
+        storage.clone(),
+        Arc::new(PipeManager::new(storage.base_dir.clone())),
+        false,
+        false,
+        Duration::from_secs(30),
+        Arc::new(RwLock::new(CaptureConfig {
+            fps: 1.0,
+            ocr_engine: OcrEngine::Tesseract,
+            dedup_threshold: DEFAULT_DEDUP_THRESHOLD,
+            idle_pause: None,
+            capture_cursor: false,
+            ocr_skip_frames: 1,
+            disable_ocr: false,
+            ocr_preprocess: OcrPreprocess::default(),
+        })),
+        Arc::new(RecordingControl::new(true)),
+        true,
+        false,
+        None,
+        #[cfg(feature = "llm")]
+        false,
+        #[cfg(feature = "llm")]
+        None,
+    );
+    let (ready_tx, ready_rx) = tokio::sync::oneshot::channel();
+    let server = server.notify_ready(ready_tx);
+    tokio::spawn(async move { server.start(HashMap::new(), |_| {}).await.unwrap() });
+    let port = tokio::time::timeout(Duration::from_secs(10), ready_rx)
+        .await
+        .expect("server didn't start in time")
+        .unwrap();
+
+    TestServer {
+        base_url: format!("http://127.0.0.1:{}", port),
+        db,
+        _dir: dir,
+    }
+}
+
+async fn search(server: &TestServer, query: &[(&str, &str)]) -> PaginatedResponse<ContentItem> {
+    let response = reqwest::Client::new()
+        .get(format!("{}/search", server.base_url))
+        .query(query)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), reqwest::StatusCode::OK);
+    response.json().await.unwrap()
+}
+
+#[tokio::test]
+async fn test_search_frames() {
+    let server = spawn_server().await;
+    server
+        .db
+        .insert_video_chunk("monitor_1_2024-10-14_10-00-00.mp4")
+        .await
+        .unwrap();
+    for (app_name, text) in [("billing", "quarterly invoice"), ("terminal", "cargo test")] {
+        let frame_id = server.db.insert_frame().await.unwrap();
+        server
+            .db
+            .insert_ocr_text(
+                frame_id,
+                text,
+                "",
+                app_name,
+                "",
+                Arc::new(OcrEngine::Tesseract),
+                true,
+            )
+            .await
+            .unwrap();
+    }
+
+    let results = search(&server, &[("q", "invoice"), ("content_type", "ocr")]).await;
+    assert_eq!(results.pagination.total, 1);
+    match &results.data[..] {
+        [ContentItem::OCR(ocr)] => {
+            assert_eq!(ocr.text, "quarterly invoice");
+            assert_eq!(ocr.app_name, "billing");
+        }
+        other => panic!("expected one ocr result, got {:?}", other),
+    }
+
+    let all = search(&server, &[("content_type", "ocr")]).await;
+    assert_eq!(all.data.len(), 2);
+}
+
+#[tokio::test]
+async fn test_search_audio() {
+    let server = spawn_server().await;
+    let audio_chunk_id = server
+        .db
+        .insert_audio_chunk("microphone_2024-10-14_10-00-00.mp4")
+        .await
+        .unwrap();
+    server
+        .db
+        .insert_audio_transcription(
+            audio_chunk_id,
+            "let's ship it on friday",
+            0,
+            "",
+            &AudioDevice::new("microphone".to_string(), DeviceType::Input),
+        )
+        .await
+        .unwrap();
+
+    let results = search(&server, &[("q", "friday"), ("content_type", "audio")]).await;
+    match &results.data[..] {
+        [ContentItem::Audio(audio)] => {
+            assert_eq!(audio.chunk_id, audio_chunk_id);
+            assert_eq!(audio.transcription, "let's ship it on friday");
+            assert_eq!(audio.device_name, "microphone");
+        }
+        other => panic!("expected one audio result, got {:?}", other),
+    }
+    assert!(
+        search(&server, &[("q", "monday"), ("content_type", "audio")])
+            .await
+            .data
+            .is_empty()
+    );
+    assert!(search(&server, &[("q", "friday"), ("content_type", "ocr")])
+        .await
+        .data
+        .is_empty());
+}
+
+#[tokio::test]
+async fn test_search_rejects_bad_query() {
+    let server = spawn_server().await;
+    let response = reqwest::get(format!("{}/search?limit=many", server.base_url))
+        .await
+        .unwrap();
+    assert!(response.status().is_client_error());
+    assert_eq!(
+        response.headers()["x-screenpipe-version"],
+        env!("CARGO_PKG_VERSION")
+    );
+}
+
+#[tokio::test]
+async fn test_list_audio_devices() {
+    // ci runners have no sound card
+    if std::env::var("CI").is_ok_and(|ci| ci == "true") {
+        eprintln!("CI=true, skipping the test needing audio hardware");
+        return;
+    }
+    let server = spawn_server().await;
+    let response = reqwest::get(format!("{}/audio/list", server.base_url))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), reqwest::StatusCode::OK);
+    let devices: Vec<ListDeviceResponse> = response.json().await.unwrap();
+    assert!(devices.iter().any(|device| device.is_default));
+}
@@ -0,0 +1,67 @@
+use crate::DatabaseManager;
+use anyhow::Result;
+use log::{error, info, warn};
+use std::sync::Arc;
+use std::time::Duration;
+
+const AUDIO_INTEGRITY_INTERVAL: Duration = Duration::from_secs(24 * 60 * 60);
+
+const AUDIO_INTEGRITY_BATCH: u32 = 1000;
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct AudioIntegrityReport {
+    pub checked: usize,
+    /// Chunks whose file is gone or empty, including ones already flagged before
+    pub missing: usize,
+}
+
+/// An audio chunk's file is usable when it exists and is not empty.
+pub async fn audio_file_present(file_path: &str) -> bool {
+    tokio::fs::metadata(file_path)
+        .await
+        .is_ok_and(|metadata| metadata.is_file() && metadata.len() > 0)
+}
+
+/// Sets `file_missing` on every audio chunk whose file is gone or empty, and clears it on the
+/// ones whose file came back.
+pub async fn check_audio_chunks(db: &DatabaseManager) -> Result<AudioIntegrityReport> {
+    let mut report = AudioIntegrityReport::default();
+    let mut after_id = 0;
+    loop {
+        let chunks = db
+            .get_audio_chunk_files(after_id, AUDIO_INTEGRITY_BATCH)
+            .await?;
+        let Some((last_id, _, _)) = chunks.last() else {
+            break;
+        };
+        after_id = *last_id;
+        for (id, file_path, was_missing) in chunks {
+            let missing = !audio_file_present(&file_path).await;
+            if missing != was_missing {
+                db.set_audio_chunk_file_missing(id, missing).await?;
+            }
+            report.checked += 1;
+            if missing {
+                report.missing += 1;
+            }
+        }
+    }
+    Ok(report)
+}
+
+/// Runs [`check_audio_chunks`] now and then once a day.
+pub fn start_audio_integrity_check(db: Arc<DatabaseManager>) {
+    tokio::spawn(async move {
+        loop {
+            match check_audio_chunks(&db).await {
+                Ok(report) if report.missing > 0 => warn!(
+                    "{} of {} audio chunks have a missing or empty file",
+                    report.missing, report.checked
+                ),
+                Ok(report) => info!("checked {} audio chunks, all files present", report.checked),
+                Err(e) => error!("audio integrity check failed: {}", e),
+            }
+            tokio::time::sleep(AUDIO_INTEGRITY_INTERVAL).await;
+        }
+    });
+}
@@ -0,0 +1,57 @@
+use screenpipe_audio::{AudioDevice, DeviceControl};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// How often an unplugged audio device is looked for again.
+pub const RECONNECT_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Recorded audio devices that went away and are waited on to come back, by name.
+static DISCONNECTED_DEVICES: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+pub fn set_disconnected(device: &str, disconnected: bool) {
+    let mut devices = DISCONNECTED_DEVICES.lock().unwrap();
+    devices.retain(|d| d != device);
+    if disconnected {
+        devices.push(device.to_string());
+    }
+}
+
+pub fn disconnected_devices() -> Vec<String> {
+    DISCONNECTED_DEVICES.lock().unwrap().clone()
+}
+
+/// Brings the known devices in line with `discovered`, a fresh enumeration: new devices are added
+/// stopped like those found at startup, known ones missing from it are kept and marked
+/// disconnected. Returns the names of the devices added and of those newly gone.
+pub fn merge_discovered_devices(
+    devices: &mut HashMap<AudioDevice, DeviceControl>,
+    discovered: Vec<AudioDevice>,
+) -> (Vec<String>, Vec<String>) {
+    let disconnected = disconnected_devices();
+    let removed: Vec<String> = devices
+        .keys()
+        .filter(|device| !discovered.contains(device))
+        .map(|device| device.to_string())
+        .filter(|name| !disconnected.contains(name))
+        .collect();
+    for name in &removed {
+        set_disconnected(name, true);
+    }
+
+    let mut added = Vec::new();
+    for device in discovered {
+        set_disconnected(&device.to_string(), false);
+        if !devices.contains_key(&device) {
+            added.push(device.to_string());
+            devices.insert(
+                device,
+                DeviceControl {
+                    is_running: false,
+                    is_paused: false,
+                },
+            );
+        }
+    }
+    (added, removed)
+}
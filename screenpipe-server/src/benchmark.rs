@@ -0,0 +1,139 @@
+use crate::runtime_config::ocr_engine_name;
+use crate::{DatabaseManager, FrameData};
+use anyhow::{anyhow, Result};
+use chrono::Utc;
+use image::DynamicImage;
+use screenpipe_vision::{monitor::list_monitors, utils::capture_screenshot, OcrEngine};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Frames captured once and handed to every engine, so they all read the same screens.
+pub const BENCHMARK_FRAMES: usize = 10;
+const CAPTURE_INTERVAL: Duration = Duration::from_millis(500);
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EngineBenchmark {
+    pub engine: String,
+    pub frames: usize,
+    pub median_ms: f64,
+    pub p99_ms: f64,
+    /// Average characters recognized per frame
+    pub characters: usize,
+    /// Why the engine stopped before all frames, e.g. a missing api key
+    pub error: Option<String>,
+}
+
+/// Nearest-rank percentile of `sorted`, `p` between 0 and 100.
+pub fn percentile(sorted: &[Duration], p: f64) -> Duration {
+    if sorted.is_empty() {
+        return Duration::ZERO;
+    }
+    let rank = (p / 100.0 * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+async fn capture_frames(count: usize) -> Result<Vec<DynamicImage>> {
+    let monitor = list_monitors()
+        .await
+        .into_iter()
+        .next()
+        .ok_or_else(|| anyhow!("no monitor found"))?;
+    let mut frames = Vec::with_capacity(count);
+    for _ in 0..count {
+        let (image, _, _, _) = capture_screenshot(&monitor, &[], &[])
+            .await
+            .map_err(|e| anyhow!("failed to capture screen: {}", e))?;
+        frames.push(image);
+        tokio::time::sleep(CAPTURE_INTERVAL).await;
+    }
+    Ok(frames)
+}
+
+/// Runs `engine` on every frame, storing the text in `db` the way recording does. Only the ocr
+/// itself is timed.
+async fn bench_engine(
+    db: &DatabaseManager,
+    engine: OcrEngine,
+    frames: &[DynamicImage],
+) -> EngineBenchmark {
+    let mut latencies = Vec::with_capacity(frames.len());
+    let mut characters = 0;
+    let mut error = None;
+    let ocr_engine = Arc::new(engine);
+    for image in frames {
+        let start = Instant::now();
+        let result = engine.perform_ocr(image).await;
+        let elapsed = start.elapsed();
+        let (text, text_json) = match result {
+            Ok((text, text_json, _)) => (text, text_json),
+            Err(e) => {
+                error = Some(e.to_string());
+                break;
+            }
+        };
+        latencies.push(elapsed);
+        characters += text.chars().count();
+        let frame = FrameData {
+            timestamp: Utc::now(),
+            text,
+            text_json,
+            app_name: "screenpipe benchmark".to_string(),
+            window_name: String::new(),
+            ocr_engine: Arc::clone(&ocr_engine),
+            focused: true,
+            ocr_failed: false,
+            cursor: None,
+            window_bounds: None,
+        };
+        if let Err(e) = db.bulk_insert_frames(vec![frame]).await {
+            error = Some(format!("failed to store ocr result: {}", e));
+            break;
+        }
+    }
+    latencies.sort();
+    EngineBenchmark {
+        engine: ocr_engine_name(&engine).to_string(),
+        frames: latencies.len(),
+        median_ms: percentile(&latencies, 50.0).as_secs_f64() * 1000.0,
+        p99_ms: percentile(&latencies, 99.0).as_secs_f64() * 1000.0,
+        characters: characters / latencies.len().max(1),
+        error,
+    }
+}
+
+/// Captures [`BENCHMARK_FRAMES`] frames of the first monitor and times each engine on them.
+/// Results go to an in-memory database, the recording database is never opened.
+pub async fn run_benchmark(engines: Vec<OcrEngine>) -> Result<Vec<EngineBenchmark>> {
+    let frames = capture_frames(BENCHMARK_FRAMES).await?;
+    let db = DatabaseManager::new("sqlite::memory:").await?;
+    db.insert_video_chunk("benchmark.mp4").await?;
+    let mut results = Vec::with_capacity(engines.len());
+    for engine in engines {
+        results.push(bench_engine(&db, engine, &frames).await);
+    }
+    Ok(results)
+}
+
+pub fn print_benchmark(results: &[EngineBenchmark]) {
+    println!(
+        "{:<16} {:>6} {:>12} {:>12} {:>10}",
+        "engine", "frames", "median (ms)", "p99 (ms)", "chars"
+    );
+    for result in results {
+        println!(
+            "{:<16} {:>6} {:>12.1} {:>12.1} {:>10}",
+            result.engine, result.frames, result.median_ms, result.p99_ms, result.characters
+        );
+        if let Some(error) = &result.error {
+            println!("  {} failed: {}", result.engine, error);
+        }
+    }
+    if let Some(fastest) = results
+        .iter()
+        .filter(|result| result.error.is_none() && result.frames > 0)
+        .min_by(|a, b| a.median_ms.total_cmp(&b.median_ms))
+    {
+        println!("fastest: {}", fastest.engine);
+    }
+}
@@ -1,32 +1,43 @@
 use std::{
-    collections::HashMap, fs, io, net::SocketAddr, ops::Deref, path::PathBuf, sync::{atomic::AtomicBool, Arc}, time::Duration, env
+    collections::HashMap, fs, io, num::NonZeroU32, ops::Deref, sync::{atomic::AtomicBool, Arc}, time::Duration, env
 };
 use std::io::Write;
 
-use clap::Parser;
+use clap::ValueEnum;
 #[allow(unused_imports)]
 use colored::Colorize;
 use crossbeam::queue::SegQueue;
-use dirs::home_dir;
 use futures::{pin_mut, stream::FuturesUnordered, StreamExt};
 use highlightio::Highlight;
-use log::{debug, error, info};
+use log::{debug, error, info, warn};
+use screenpipe_audio::app_audio::{app_audio_device, app_audio_supported, MIN_APP_AUDIO_MACOS};
+use screenpipe_audio::fake_device::fake_audio_device;
 use screenpipe_audio::{
-    default_input_device, default_output_device, list_audio_devices, parse_audio_device,
-    AudioDevice, DeviceControl,
+    create_whisper_channel, default_input_device, default_output_device, find_audio_device,
+    list_audio_devices, parse_audio_device, AudioDevice, AudioFormat, DeviceControl, DeviceType,
 };
-use screenpipe_core::find_ffmpeg_path;
+use screenpipe_core::{find_ffmpeg_path, get_base_dir, StoragePaths};
+use screenpipe_integrations::unstructured_ocr::set_cloud_ocr_timeout;
 use screenpipe_server::{
-    cli::{Cli, CliAudioTranscriptionEngine, CliOcrEngine, Command, PipeCommand}, logs::SingleFileRollingWriter, start_continuous_recording, watch_pid, DatabaseManager, PipeManager, ResourceMonitor, Server
+    benchmark::{print_benchmark, run_benchmark}, cli::{Cli, CliAudioTranscriptionEngine, ConfigSource, ConfigSources, CliLogFormat, CliOcrEngine, Command, PipeCommand}, filtering::select_prioritized_device, import_file, logs::{JsonLogFormat, SingleFileRollingWriter}, self_test::{print_report, run_self_test}, start_audio_integrity_check, start_continuous_recording, start_daily_summaries, watch_pid, bind_listener, listen_addr, AlertThresholds, BodyLimits, DatabaseManager, FrameBuffer, HealBackoff, ImportAudio, ImportOptions, MediaSigner, OcrScript, PipeCmd, PipeManager, RecordingControl, RemoteSync, ResourceMonitor, RateLimit, Secrets, Server, TaskLimiter, spawn_startup_script, TesseractLangManager, secrets_path
+};
+use screenpipe_vision::{
+    monitor::{is_virtual_monitor, list_monitors},
+    set_tesseract_langs, CaptureConfig, OcrFallback, OcrPreprocess,
 };
-use screenpipe_vision::monitor::list_monitors;
 use serde_json::{json, Value};
-use tokio::{runtime::Runtime, signal, sync::broadcast};
+use tokio::{runtime::Runtime, signal};
+use tokio_util::sync::CancellationToken;
+use tracing::{info_span, Instrument};
 use tracing_subscriber::prelude::__tracing_subscriber_SubscriberExt;
 use tracing_subscriber::util::SubscriberInitExt;
 use tracing_subscriber::Layer;
+use tracing_subscriber::fmt::format::JsonFields;
 use tracing_subscriber::{fmt, EnvFilter};
 
+/// How long recording gets to finish its current chunks once a shutdown is requested.
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(10);
+
 fn print_devices(devices: &[AudioDevice]) {
     println!("available audio devices:");
     for device in devices.iter() {
@@ -47,26 +58,86 @@ const DISPLAY: &str = r"
 
 ";
 
-fn get_base_dir(custom_path: Option<String>) -> anyhow::Result<PathBuf> {
-    let default_path = home_dir()
-        .ok_or_else(|| anyhow::anyhow!("failed to get home directory"))?
-        .join(".screenpipe");
-
-    let base_dir = custom_path.map(PathBuf::from).unwrap_or(default_path);
-    let data_dir = base_dir.join("data");
-
-    fs::create_dir_all(&data_dir)?;
-    Ok(base_dir)
+fn main() -> anyhow::Result<()> {
+    let (cli, config_sources) = Cli::parse_with_env();
+    // the environment is only safe to change while no other thread can read it, so before the
+    // runtime starts its workers
+    #[cfg(target_os = "linux")]
+    if let Some(display) = &cli.display {
+        // xcap captures the X server DISPLAY names, also from a wayland session
+        std::env::set_var("DISPLAY", display);
+        std::env::remove_var("WAYLAND_DISPLAY");
+        std::env::set_var("XDG_SESSION_TYPE", "x11");
+    }
+    let storage = get_base_dir(cli.data_dir.clone(), cli.frames_dir.clone(), cli.audio_dir.clone())?;
+    let tessdata = if cli.ocr_hint_language.is_empty() {
+        None
+    } else {
+        let tessdata = TesseractLangManager::new(storage.base_dir.join("tessdata"))?;
+        match tessdata.prepare(&cli.ocr_hint_language) {
+            Ok(true) => {
+                // the tesseract processes only find our packs through the environment
+                std::env::set_var("TESSDATA_PREFIX", tessdata.dir());
+                Some(tessdata)
+            }
+            Ok(false) => None,
+            Err(e) => {
+                eprintln!("failed to set up tesseract languages: {}", e);
+                None
+            }
+        }
+    };
+    tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()?
+        .block_on(run(cli, config_sources, storage, tessdata))
 }
 
-
-
-
-#[tokio::main]
-async fn main() -> anyhow::Result<()> {
+async fn run(
+    mut cli: Cli,
+    mut config_sources: ConfigSources,
+    storage: StoragePaths,
+    tessdata: Option<TesseractLangManager>,
+) -> anyhow::Result<()> {
     debug!("starting screenpipe server");
-    let cli = Cli::parse();
-    let local_data_dir = get_base_dir(cli.data_dir)?;
+    let local_data_dir = storage.base_dir.clone();
+
+    // flags on the command line and their env vars win over the secrets file
+    let secrets = Secrets::load(&secrets_path(&local_data_dir))?;
+    for (id, flag, secret) in [
+        ("deepgram_api_key", &cli.deepgram_api_key, &secrets.deepgram_api_key),
+        ("remote_sync_secret", &cli.remote_sync_secret, &secrets.remote_sync_secret),
+    ] {
+        if flag.is_none() && secret.is_some() {
+            config_sources.insert(id.to_string(), ConfigSource::Config);
+        }
+    }
+    cli.deepgram_api_key = cli.deepgram_api_key.or(secrets.deepgram_api_key.clone());
+    cli.remote_sync_secret = cli.remote_sync_secret.or(secrets.remote_sync_secret.clone());
+    if cli.remote_sync_url.is_some() && cli.remote_sync_secret.is_none() {
+        return Err(anyhow::anyhow!("--remote-sync-url needs --remote-sync-secret"));
+    }
+    if cli.audio_chunk_overlap_secs >= cli.audio_chunk_duration as f64 {
+        return Err(anyhow::anyhow!(
+            "--audio-chunk-overlap-secs must be shorter than --audio-chunk-duration"
+        ));
+    }
+    if cli.records_nothing() {
+        warn!("audio and screen text are both disabled, screenpipe will record no searchable data");
+        if !cli.force {
+            return Err(anyhow::anyhow!(
+                "nothing to record with --disable-audio and --disable-ocr or --disable-vision, \
+                 pass --force to start anyway"
+            ));
+        }
+    }
+    secrets.export_env();
+    let audio_format = if cli.compress_audio {
+        AudioFormat::WavZstd
+    } else {
+        cli.audio_format.clone().into()
+    };
+    set_cloud_ocr_timeout(Duration::from_millis(cli.cloud_ocr_timeout_ms));
     let local_data_dir_clone = local_data_dir.clone();
 
     let pipe_manager = Arc::new(PipeManager::new(local_data_dir_clone.clone()));
@@ -77,6 +148,96 @@ async fn main() -> anyhow::Result<()> {
                 handle_pipe_command(subcommand, &pipe_manager).await?;
                 return Ok(());
             }
+            Command::SelfTest => {
+                let results = run_self_test(cli.ocr_engine.clone().into()).await;
+                print_report(&results);
+                if results.iter().any(|r| !r.passed) {
+                    std::process::exit(1);
+                }
+                return Ok(());
+            }
+            Command::Benchmark {
+                ocr_engine,
+                output_json,
+            } => {
+                let engines = if ocr_engine.is_empty() {
+                    CliOcrEngine::value_variants()
+                        .iter()
+                        .filter(|engine| {
+                            **engine != CliOcrEngine::Unstructured
+                                || env::var("UNSTRUCTURED_API_KEY").is_ok()
+                        })
+                        .cloned()
+                        .collect()
+                } else {
+                    ocr_engine
+                };
+                let results =
+                    run_benchmark(engines.into_iter().map(Into::into).collect()).await?;
+                print_benchmark(&results);
+                if let Some(path) = output_json {
+                    fs::write(&path, serde_json::to_string_pretty(&results)?)?;
+                    println!("results written to {}", path.display());
+                }
+                return Ok(());
+            }
+            Command::Import {
+                path,
+                fps,
+                start_time,
+                no_audio,
+            } => {
+                let db = DatabaseManager::new_with_pool_size(
+                    &format!("{}/db.sqlite", local_data_dir.to_string_lossy()),
+                    cli.db_pool_size,
+                )
+                .await?;
+                let audio = if no_audio {
+                    None
+                } else {
+                    let transcription_engine =
+                        Arc::new(cli.audio_transcription_engine.clone().into());
+                    let (whisper_sender, whisper_receiver, _) = create_whisper_channel(
+                        Arc::clone(&transcription_engine),
+                        cli.vad_engine.clone().into(),
+                        cli.deepgram_api_key.clone(),
+                        &storage.audio_dir,
+                        cli.vad_sensitivity.clone().into(),
+                        cli.normalize_audio,
+                        false,
+                        audio_format,
+                        Duration::from_secs_f64(cli.audio_chunk_overlap_secs),
+                        !cli.skip_warmup,
+                    )
+                    .await?;
+                    Some(ImportAudio {
+                        whisper_sender,
+                        whisper_receiver,
+                        transcription_engine,
+                        chunk_duration: Duration::from_secs(cli.audio_chunk_duration),
+                    })
+                };
+                let summary = import_file(
+                    &db,
+                    &storage.frames_dir,
+                    &path,
+                    ImportOptions {
+                        fps,
+                        start_time,
+                        ocr_engine: Arc::new(cli.ocr_engine.clone().into()),
+                        normalize_ocr: !cli.ocr_no_normalize,
+                        audio,
+                    },
+                )
+                .await?;
+                println!(
+                    "imported {} frames and {} audio chunks from {}",
+                    summary.frames,
+                    summary.audio_chunks,
+                    path.display()
+                );
+                return Ok(());
+            }
         }
     }
 
@@ -85,20 +246,60 @@ async fn main() -> anyhow::Result<()> {
         std::process::exit(1);
     }
 
+    if cli.ipv4_only && cli.bind_address.is_ipv6() && !cli.bind_address.is_unspecified() {
+        eprintln!("--bind-address {} is an ipv6 address, remove --ipv4-only to use it.", cli.bind_address);
+        std::process::exit(1);
+    }
+    let server_addr = listen_addr(cli.bind_address, cli.port, cli.ipv4_only);
+    if let Err(e) = bind_listener(server_addr) {
+        eprintln!(
+            "cannot listen on {}: {}. check --bind-address is an address of this machine and --port is not already in use.",
+            server_addr, e
+        );
+        std::process::exit(1);
+    }
+    let read_only_addr = cli
+        .read_only_port
+        .map(|port| listen_addr(cli.bind_address, port, cli.ipv4_only));
+    if let Some(addr) = read_only_addr {
+        if let Err(e) = bind_listener(addr) {
+            eprintln!("cannot listen on {}: {}. check --read-only-port is not already in use.", addr, e);
+            std::process::exit(1);
+        }
+    }
+
     // Set up file appender
     let log_file_path = local_data_dir.join("screenpipe.log");
     let file_writer = SingleFileRollingWriter::new(log_file_path)?;
 
     // Create a custom layer for file logging
-    let file_layer = fmt::layer()
-        .with_writer(file_writer)
-        .with_ansi(false)
-        .with_filter(EnvFilter::new("info"));
+    let file_layer = match cli.log_format {
+        CliLogFormat::Text => fmt::layer()
+            .with_writer(file_writer)
+            .with_ansi(false)
+            .with_filter(EnvFilter::new("info"))
+            .boxed(),
+        CliLogFormat::Json => fmt::layer()
+            .event_format(JsonLogFormat)
+            .fmt_fields(JsonFields::new())
+            .with_writer(file_writer)
+            .with_filter(EnvFilter::new("info"))
+            .boxed(),
+    };
 
     // Create a custom layer for console logging
-    let console_layer = fmt::layer()
-        .with_writer(std::io::stdout)
-        .with_filter(EnvFilter::new("debug"));
+    let console_layer = match cli.log_format {
+        CliLogFormat::Text => fmt::layer()
+            .with_writer(std::io::stdout)
+            .with_filter(EnvFilter::new("debug"))
+            .boxed(),
+        CliLogFormat::Json => fmt::layer()
+            .event_format(JsonLogFormat)
+            .fmt_fields(JsonFields::new())
+            .with_writer(std::io::stdout)
+            .with_filter(EnvFilter::new("debug"))
+            .boxed(),
+    };
 
     // Build the EnvFilter
     let env_filter = EnvFilter::from_default_env()
@@ -148,6 +349,9 @@ async fn main() -> anyhow::Result<()> {
         .with(file_layer)
         .with(console_layer)
         .init();
+    for (id, source) in &config_sources {
+        debug!("setting {} from {}", id, source);
+    }
 
 
     let all_audio_devices = list_audio_devices().await?;
@@ -156,11 +360,23 @@ async fn main() -> anyhow::Result<()> {
         print_devices(&all_audio_devices);
         return Ok(());
     }
+    if let Some(display) = &cli.display {
+        if cfg!(target_os = "linux") {
+            info!("capturing X11 display {}", display);
+        } else {
+            warn!("--display only applies to X11 on linux, ignored");
+        }
+    }
     let all_monitors = list_monitors().await;
     if cli.list_monitors {
         println!("available monitors:");
         for monitor in all_monitors.iter() {
-            println!("  {}. {:?}", monitor.id(), monitor);
+            let kind = if is_virtual_monitor(monitor) {
+                "virtual"
+            } else {
+                "physical"
+            };
+            println!("  {}. {:?} ({})", monitor.id(), monitor, kind);
         }
         return Ok(());
     }
@@ -181,30 +397,62 @@ async fn main() -> anyhow::Result<()> {
     }
 
     if !cli.disable_audio {
-        if cli.audio_device.is_empty() {
-            // Use default devices
-            if let Ok(input_device) = default_input_device() {
-                audio_devices.push(Arc::new(input_device.clone()));
-                let device_control = DeviceControl {
-                    is_running: true,
-                    is_paused: false,
+        if let Some(frequency) = cli.fake_audio_device {
+            let device = fake_audio_device(frequency);
+            info!("recording fake audio device {}", device);
+            audio_devices.push(Arc::new(device.clone()));
+            let device_control = DeviceControl {
+                is_running: true,
+                is_paused: false,
+            };
+            devices_status.insert(device, device_control);
+        } else if cli.audio_device.is_empty() {
+            // Use prioritized devices, falling back to the default ones
+            let defaults = [
+                (DeviceType::Input, default_input_device()),
+                // audio output only on macos <15.0 atm ?
+                // see https://github.com/mediar-ai/screenpipe/pull/106
+                (DeviceType::Output, default_output_device()),
+            ];
+            for (device_type, default_device) in defaults {
+                let device = match select_prioritized_device(
+                    &all_audio_devices,
+                    &device_type,
+                    &cli.audio_device_priority,
+                ) {
+                    Some((device, pattern)) => {
+                        info!(
+                            "selected audio device {} (matched priority rule \"{}\")",
+                            device, pattern
+                        );
+                        device.clone()
+                    }
+                    None => match default_device {
+                        Ok(device) => {
+                            info!("selected default audio device {}", device);
+                            device
+                        }
+                        Err(_) => continue,
+                    },
                 };
-                devices_status.insert(input_device, device_control);
-            }
-            // audio output only on macos <15.0 atm ?
-            // see https://github.com/mediar-ai/screenpipe/pull/106
-            if let Ok(output_device) = default_output_device() {
-                audio_devices.push(Arc::new(output_device.clone()));
+                audio_devices.push(Arc::new(device.clone()));
                 let device_control = DeviceControl {
                     is_running: true,
                     is_paused: false,
                 };
-                devices_status.insert(output_device, device_control);
+                devices_status.insert(device, device_control);
             }
         } else {
             // Use specified devices
             for d in &cli.audio_device {
                 let device = parse_audio_device(d).expect("failed to parse audio device");
+                let device = match find_audio_device(&all_audio_devices, &device) {
+                    Some(found) if found.name != device.name => {
+                        info!("audio device \"{}\" is {}", d, found);
+                        found.clone()
+                    }
+                    _ => device,
+                };
                 audio_devices.push(Arc::new(device.clone()));
                 let device_control = DeviceControl {
                     is_running: true,
@@ -214,6 +462,36 @@ async fn main() -> anyhow::Result<()> {
             }
         }
 
+        let app_audio = !cli.capture_audio_from_app.is_empty() && app_audio_supported();
+        for bundle_id in &cli.capture_audio_from_app {
+            let device = if app_audio {
+                app_audio_device(bundle_id)
+            } else {
+                warn!(
+                    "recording the audio of {} needs macos {}, falling back to the system output. \
+                     this fallback is deprecated and will be removed",
+                    bundle_id, MIN_APP_AUDIO_MACOS
+                );
+                match default_output_device() {
+                    Ok(device) => device,
+                    Err(e) => {
+                        warn!("no output device to record {} from: {}", bundle_id, e);
+                        continue;
+                    }
+                }
+            };
+            if audio_devices.iter().any(|d| **d == device) {
+                continue;
+            }
+            info!("recording audio of {} from {}", bundle_id, device);
+            audio_devices.push(Arc::new(device.clone()));
+            let device_control = DeviceControl {
+                is_running: true,
+                is_paused: false,
+            };
+            devices_status.insert(device, device_control);
+        }
+
         if audio_devices.is_empty() {
             eprintln!("no audio devices available. audio recording will be disabled.");
         } else {
@@ -235,21 +513,44 @@ async fn main() -> anyhow::Result<()> {
         }
     }
 
-    let resource_monitor = ResourceMonitor::new();
+    let resource_monitor = ResourceMonitor::new(AlertThresholds {
+        data_dir: local_data_dir.clone(),
+        disk_pct: cli.alert_disk_pct,
+        cpu_pct: cli.alert_cpu_pct,
+        max_memory_mb: cli.max_memory_mb,
+    });
     resource_monitor.start_monitoring(Duration::from_secs(10));
 
     let db = Arc::new(
-        DatabaseManager::new(&format!("{}/db.sqlite", local_data_dir.to_string_lossy()))
-            .await
-            .map_err(|e| {
-                eprintln!("failed to initialize database: {:?}", e);
-                e
-            })?,
+        DatabaseManager::new_with_pool_size(
+            &format!("{}/db.sqlite", local_data_dir.to_string_lossy()),
+            cli.db_pool_size,
+        )
+        .await
+        .map_err(|e| {
+            eprintln!("failed to initialize database: {:?}", e);
+            e
+        })?,
     );
     info!(
         "database initialized, will store files in {}",
         local_data_dir.to_string_lossy()
     );
+    start_audio_integrity_check(db.clone());
+    start_daily_summaries(db.clone(), local_data_dir.clone(), cli.summary_time);
+    if let (Some(url), Some(secret)) = (&cli.remote_sync_url, &cli.remote_sync_secret) {
+        RemoteSync::new(db.clone(), url.clone(), secret.clone())?.start();
+    }
+    if let Some(buffer_dir) = &cli.frame_buffer_dir {
+        fs::create_dir_all(buffer_dir)?;
+        let frame_buffer = FrameBuffer {
+            buffer_dir: buffer_dir.clone(),
+            frames_dir: storage.frames_dir.clone(),
+        };
+        if let Err(e) = frame_buffer.flush_leftovers(&db).await {
+            warn!("failed to move chunks left in the frame buffer: {}", e);
+        }
+    }
     let db_server = db.clone();
 
     // Channel for controlling the recorder ! TODO RENAME SHIT
@@ -269,10 +570,11 @@ async fn main() -> anyhow::Result<()> {
     };
 
     let ocr_engine_clone = cli.ocr_engine.clone();
+    let ocr_engine_fallback_clone = cli.ocr_engine_fallback.clone();
     let vad_engine = cli.vad_engine.clone();
     let vad_engine_clone = vad_engine.clone();
     let vad_sensitivity_clone = cli.vad_sensitivity.clone();
-    let (shutdown_tx, _) = broadcast::channel::<()>(1);
+    let shutdown = CancellationToken::new();
 
     let audio_runtime = Runtime::new().unwrap();
     let vision_runtime = Runtime::new().unwrap();
@@ -281,33 +583,97 @@ async fn main() -> anyhow::Result<()> {
     let vision_handle = vision_runtime.handle().clone();
 
     let db_clone = Arc::clone(&db);
-    let output_path_clone = Arc::new(local_data_dir.join("data").to_string_lossy().into_owned());
     let vision_control_clone = Arc::clone(&vision_control);
-    let shutdown_tx_clone = shutdown_tx.clone();
+    let shutdown_clone = shutdown.clone();
+    let pipe_cmd = cli.pipe_cmd.clone().map(|cmd| {
+        Arc::new(PipeCmd::new(
+            cmd,
+            cli.pipe_cmd_concurrency as usize,
+            Arc::clone(&db),
+        ))
+    });
+    if let Some(tessdata) = &tessdata {
+        tessdata.ensure_langs(&cli.ocr_hint_language).await;
+    }
+    set_tesseract_langs(&cli.ocr_hint_language);
+    let ocr_script = match &cli.ocr_postprocess_script {
+        Some(path) => Some(Arc::new(OcrScript::load(path)?)),
+        None => None,
+    };
+    let task_limiter = Arc::new(TaskLimiter::new(
+        cli.max_concurrent_tasks as usize,
+        cli.task_queue_depth as usize,
+    ));
     let friend_wearable_uid_clone: Option<String> = friend_wearable_uid.clone(); // Clone here
     let monitor_ids_clone = monitor_ids.clone();
     let ignored_windows_clone = cli.ignored_windows.clone();
     let included_windows_clone = cli.included_windows.clone();
-
-    let fps = if cli.fps.is_finite() && cli.fps > 0.0 {
-        cli.fps
-    } else {
-        eprintln!("invalid fps value: {}. using default of 1.0", cli.fps);
-        1.0
-    };
+    let ignore_window_clone = cli.ignore_window.clone();
+    let audio_device_priority_clone = cli.audio_device_priority.clone();
 
     let audio_chunk_duration = Duration::from_secs(cli.audio_chunk_duration);
 
+    // shared with the api server, PATCH /config applies from the next capture cycle
+    let capture_config = Arc::new(std::sync::RwLock::new(CaptureConfig {
+        fps: cli.fps,
+        ocr_engine: cli.ocr_engine.clone().into(),
+        dedup_threshold: cli.dedup_threshold,
+        idle_pause: cli.idle_pause_secs.map(Duration::from_secs),
+        capture_cursor: cli.capture_cursor,
+        ocr_skip_frames: cli.ocr_skip_frames,
+        disable_ocr: cli.disable_ocr,
+        ocr_preprocess: OcrPreprocess {
+            upscale: cli.ocr_preprocess_upscale,
+            binarize: cli.ocr_preprocess_binarize,
+            deskew: cli.ocr_preprocess_deskew,
+        },
+    }));
+    let capture_config_server = Arc::clone(&capture_config);
+
+    let ocr_fallback = cli.ocr_engine_fallback.clone().map(|engine| OcrFallback {
+        engine: engine.into(),
+        confidence_threshold: cli.ocr_fallback_threshold,
+    });
+
+    let recording_control = Arc::new(RecordingControl::new(!cli.manual_start));
+    let recording_control_server = Arc::clone(&recording_control);
+
+    let resource_monitor_clone = Arc::clone(&resource_monitor);
+    let storage_clone = storage.clone();
     let handle = {
         let runtime = &tokio::runtime::Handle::current();
         runtime.spawn(async move {
+            let mut backoff = HealBackoff::new(
+                Duration::from_secs(cli.heal_initial_delay_secs),
+                Duration::from_secs(cli.heal_max_delay_secs),
+            );
             loop {
+                if !recording_control.is_running() {
+                    info!("recording stopped, waiting for POST /recording/start");
+                    tokio::select! {
+                        _ = recording_control.started() => info!("starting recording"),
+                        _ = shutdown_clone.cancelled() => {
+                            info!("recording stopped");
+                            break;
+                        }
+                    }
+                }
                 let vad_engine_clone = vad_engine.clone(); // Clone it here for each iteration
-                let mut shutdown_rx = shutdown_tx_clone.subscribe();
+                let started_at = std::time::Instant::now();
+                // every start/restart of the recorder is a new session
+                let session_id = match db_clone.start_session().await {
+                    Ok(id) => Some(id),
+                    Err(e) => {
+                        error!("failed to start recording session: {}", e);
+                        None
+                    }
+                };
+                // cancelled alone to restart the recorder, e.g. over --max-memory-mb
+                let run = shutdown_clone.child_token();
                 let recording_future = start_continuous_recording(
                     db_clone.clone(),
-                    output_path_clone.clone(),
-                    fps,
+                    &storage_clone,
+                    capture_config.clone(),
                     audio_chunk_duration, // use the new setting
                     Duration::from_secs(cli.video_chunk_duration),
                     vision_control_clone.clone(),
@@ -315,39 +681,97 @@ async fn main() -> anyhow::Result<()> {
                     cli.disable_audio,
                     cli.save_text_files,
                     Arc::new(cli.audio_transcription_engine.clone().into()),
-                    Arc::new(cli.ocr_engine.clone().into()),
+                    ocr_fallback,
                     friend_wearable_uid_clone.clone(),
                     monitor_ids_clone.clone(),
                     cli.use_pii_removal,
+                    !cli.ocr_no_normalize,
+                    cli.ocr_workers as usize,
+                    cli.max_frame_size_kb,
+                    cli.capture_format.clone().into(),
                     cli.disable_vision,
                     vad_engine_clone,
                     &vision_handle,
                     &audio_handle,
                     &cli.ignored_windows,
                     &cli.included_windows,
+                    &cli.ignore_window,
                     cli.deepgram_api_key.clone(),
                     cli.vad_sensitivity.clone(),
-                );
-
+                    cli.normalize_audio,
+                    cli.echo_cancellation,
+                    audio_format,
+                    Duration::from_secs_f64(cli.audio_chunk_overlap_secs),
+                    !cli.skip_warmup,
+                    cli.frame_batch_size as usize,
+                    pipe_cmd.clone(),
+                    ocr_script.clone(),
+                    Arc::clone(&task_limiter),
+                    cli.frame_buffer_dir.clone(),
+                    run.clone(),
+                )
+                .instrument(info_span!(
+                    "recording",
+                    session_id = session_id.as_deref().unwrap_or_default()
+                ));
+                pin_mut!(recording_future);
+
+                // on shutdown the recorder finishes its current chunks and returns
                 let result = tokio::select! {
-                    result = recording_future => result,
-                    _ = shutdown_rx.recv() => {
-                        info!("received shutdown signal for recording");
-                        break;
+                    result = &mut recording_future => result,
+                    _ = resource_monitor_clone.restart_requested() => {
+                        run.cancel();
+                        recording_future.await
+                    }
+                    // POST /recording/stop, the loop then waits for the next start
+                    _ = recording_control.stopped() => {
+                        run.cancel();
+                        recording_future.await
                     }
                 };
 
-                if let Err(e) = result {
-                    error!("continuous recording error: {:?}", e);
+                if let Some(session_id) = &session_id {
+                    if let Err(e) = db_clone.end_session(session_id).await {
+                        error!("failed to end recording session: {}", e);
+                    }
+                }
+
+                if shutdown_clone.is_cancelled() {
+                    if let Err(e) = result {
+                        error!("continuous recording error: {:?}", e);
+                    }
+                    info!("recording stopped");
+                    break;
+                }
+                // a run that outlasted the longest backoff counts as healthy
+                if started_at.elapsed() >= backoff.max_delay() {
+                    backoff.reset();
+                }
+                match result {
+                    Ok(_) => backoff.reset(),
+                    Err(e) => {
+                        let delay = backoff.next_delay();
+                        error!(
+                            "continuous recording error: {:?}, restarting in {}s",
+                            e,
+                            delay.as_secs()
+                        );
+                        tokio::select! {
+                            _ = tokio::time::sleep(delay) => {}
+                            _ = shutdown_clone.cancelled() => {
+                                info!("recording stopped");
+                                break;
+                            }
+                        }
+                    }
                 }
             }
 
-            drop(vision_runtime);
-            drop(audio_runtime);
+            vision_runtime.shutdown_background();
+            audio_runtime.shutdown_background();
         })
     };
 
-    let local_data_dir_clone_2 = local_data_dir_clone.clone();
     #[cfg(feature = "llm")]
     debug!("LLM initializing");
 
@@ -367,21 +791,90 @@ async fn main() -> anyhow::Result<()> {
             // Track search requests
         }
     };
+    let read_only_server = match read_only_addr {
+        Some(addr) => Some(Server::new(
+            Arc::new(
+                DatabaseManager::new_read_only(
+                    &format!("{}/db.sqlite", local_data_dir.to_string_lossy()),
+                    cli.db_pool_size,
+                )
+                .await?,
+            ),
+            addr,
+            vision_control_server_clone.clone(),
+            audio_devices_control_server.clone(),
+            storage.clone(),
+            pipe_manager.clone(),
+            cli.disable_vision,
+            cli.disable_audio,
+            audio_chunk_duration,
+            Arc::clone(&capture_config_server),
+            Arc::clone(&recording_control_server),
+            !cli.disable_security_headers,
+            true,
+            cli.remote_sync_secret.clone(),
+            #[cfg(feature = "llm")]
+            false,
+            #[cfg(feature = "llm")]
+            None,
+        )),
+        None => None,
+    };
     let server = Server::new(
         db_server,
-        SocketAddr::from(([127, 0, 0, 1], cli.port)),
+        server_addr,
         vision_control_server_clone,
         audio_devices_control_server,
-        local_data_dir_clone_2,
+        storage.clone(),
         pipe_manager.clone(),
         cli.disable_vision,
         cli.disable_audio,
+        audio_chunk_duration,
+        capture_config_server,
+        recording_control_server,
+        !cli.disable_security_headers,
+        false,
+        cli.remote_sync_secret.clone(),
         #[cfg(feature = "llm")]
         cli.enable_llm,
         #[cfg(feature = "llm")]
         llm,
 
     );
+    let rate_limit = cli.rate_limit_rps.and_then(NonZeroU32::new).map(|rps| RateLimit {
+        requests_per_second: rps,
+        burst: cli.rate_limit_burst.and_then(NonZeroU32::new).unwrap_or(rps),
+        exempt_key: cli.rate_limit_exempt_key.clone(),
+    });
+    // one key for both servers, so a media url works on either port
+    let media_signer = Arc::new(MediaSigner::random(Duration::from_secs(
+        cli.signed_url_ttl_secs,
+    )));
+    let body_limits = BodyLimits {
+        max_body_size: cli.max_body_size_kb.saturating_mul(1024),
+        max_import_body_size: cli.max_import_body_size_kb.saturating_mul(1024),
+    };
+    let read_only_server = read_only_server.map(|server| {
+        server
+            .rate_limit(rate_limit.clone())
+            .media_signer(Arc::clone(&media_signer))
+            .body_limits(body_limits)
+            .normalize_ocr(!cli.ocr_no_normalize)
+    });
+    let server = server
+        .rate_limit(rate_limit)
+        .media_signer(media_signer)
+        .body_limits(body_limits)
+        .normalize_ocr(!cli.ocr_no_normalize)
+        .live_transcription(Arc::new(cli.audio_transcription_engine.clone().into()));
+    let server = match &cli.startup_script {
+        Some(script) => {
+            let (ready_tx, ready_rx) = tokio::sync::oneshot::channel();
+            spawn_startup_script(script.clone(), local_data_dir.clone(), ready_rx);
+            server.notify_ready(ready_tx)
+        }
+        None => server,
+    };
 
     let mut pipe_futures = FuturesUnordered::new();
 
@@ -406,13 +899,86 @@ async fn main() -> anyhow::Result<()> {
         "│ audio chunk duration│ {:<34} │",
         format!("{} seconds", cli.audio_chunk_duration)
     );
+    println!(
+        "│ audio chunk overlap │ {:<34} │",
+        format!("{} seconds", cli.audio_chunk_overlap_secs)
+    );
     println!(
         "│ video chunk duration│ {:<34} │",
         format!("{} seconds", cli.video_chunk_duration)
     );
     println!("│ port                │ {:<34} │", cli.port);
+    println!(
+        "│ rate limit          │ {:<34} │",
+        cli.rate_limit_rps.map_or("disabled".to_string(), |rps| format!(
+            "{} req/s, burst {}",
+            rps,
+            cli.rate_limit_burst.unwrap_or(rps)
+        ))
+    );
+    println!(
+        "│ max body size       │ {:<34} │",
+        format!(
+            "{} KB, import {} KB",
+            cli.max_body_size_kb, cli.max_import_body_size_kb
+        )
+    );
+    println!(
+        "│ signed url ttl      │ {:<34} │",
+        format!("{} seconds", cli.signed_url_ttl_secs)
+    );
+    println!("│ bind address        │ {:<34} │", server_addr.ip());
+    println!(
+        "│ read-only port      │ {:<34} │",
+        cli.read_only_port
+            .map_or("disabled".to_string(), |port| port.to_string())
+    );
+    println!("│ security headers    │ {:<34} │", !cli.disable_security_headers);
+    println!("│ db pool size        │ {:<34} │", cli.db_pool_size);
+    println!("│ frame batch size    │ {:<34} │", cli.frame_batch_size);
+    println!(
+        "│ concurrent tasks    │ {:<34} │",
+        format!("{} ({} queued)", cli.max_concurrent_tasks, cli.task_queue_depth)
+    );
+    println!(
+        "│ restart backoff     │ {:<34} │",
+        format!("{}s to {}s", cli.heal_initial_delay_secs, cli.heal_max_delay_secs)
+    );
+    println!(
+        "│ pipe cmd            │ {:<34} │",
+        format_cell(
+            &cli.pipe_cmd
+                .as_ref()
+                .map(|cmd| format!("{} (x{})", cmd, cli.pipe_cmd_concurrency))
+                .unwrap_or_else(|| "disabled".to_string()),
+            VALUE_WIDTH
+        )
+    );
+    println!(
+        "│ alert thresholds    │ {:<34} │",
+        format!("disk {}%, cpu {}%", cli.alert_disk_pct, cli.alert_cpu_pct)
+    );
+    println!(
+        "│ max memory          │ {:<34} │",
+        cli.max_memory_mb
+            .map_or("not set".to_string(), |mb| format!("{} MB", mb))
+    );
     println!("│ audio disabled      │ {:<34} │", cli.disable_audio);
+    if !cli.capture_audio_from_app.is_empty() {
+        println!(
+            "│ app audio           │ {:<34} │",
+            cli.capture_audio_from_app.join(", ")
+        );
+    }
+    println!("│ normalize audio     │ {:<34} │", cli.normalize_audio);
+    println!("│ echo cancellation   │ {:<34} │", cli.echo_cancellation);
+    println!(
+        "│ audio format        │ {:<34} │",
+        format!("{:?}", audio_format)
+    );
     println!("│ vision disabled     │ {:<34} │", cli.disable_vision);
+    println!("│ ocr disabled        │ {:<34} │", cli.disable_ocr);
+    println!("│ manual start        │ {:<34} │", cli.manual_start);
     println!("│ save text files     │ {:<34} │", cli.save_text_files);
     println!(
         "│ audio engine        │ {:<34} │",
@@ -422,6 +988,50 @@ async fn main() -> anyhow::Result<()> {
         "│ ocr engine          │ {:<34} │",
         format!("{:?}", ocr_engine_clone)
     );
+    println!(
+        "│ ocr fallback        │ {:<34} │",
+        match &ocr_engine_fallback_clone {
+            Some(engine) => format!("{:?} (< {})", engine, cli.ocr_fallback_threshold),
+            None => "not set".to_string(),
+        }
+    );
+    println!("│ ocr workers         │ {:<34} │", cli.ocr_workers);
+    println!(
+        "│ ocr skip frames     │ {:<34} │",
+        if cli.ocr_skip_frames > 1 {
+            format!("ocr 1 in {} frames", cli.ocr_skip_frames)
+        } else {
+            "disabled".to_string()
+        }
+    );
+    println!(
+        "│ ocr preprocessing   │ {:<34} │",
+        [
+            cli.ocr_preprocess_upscale
+                .map(|factor| format!("upscale {}x", factor)),
+            cli.ocr_preprocess_deskew.then(|| "deskew".to_string()),
+            cli.ocr_preprocess_binarize.then(|| "binarize".to_string()),
+        ]
+        .into_iter()
+        .flatten()
+        .reduce(|steps, step| format!("{}, {}", steps, step))
+        .unwrap_or_else(|| "disabled".to_string())
+    );
+    println!("│ summary time        │ {:<34} │", cli.summary_time.format("%H:%M").to_string());
+    println!("│ cloud ocr timeout   │ {:<34} │", format!("{} ms", cli.cloud_ocr_timeout_ms));
+    println!("│ max frame size      │ {:<34} │", format!("{} KB", cli.max_frame_size_kb));
+    println!("│ capture format      │ {:<34} │", format!("{:?}", cli.capture_format));
+    println!("│ dedup threshold     │ {:<34} │", cli.dedup_threshold);
+    println!(
+        "│ remote sync         │ {:<34} │",
+        cli.remote_sync_url.as_deref().unwrap_or("disabled")
+    );
+    println!(
+        "│ idle pause          │ {:<34} │",
+        cli.idle_pause_secs
+            .map_or("disabled".to_string(), |secs| format!("after {}s", secs))
+    );
+    println!("│ capture cursor      │ {:<34} │", cli.capture_cursor);
     println!(
         "│ vad engine          │ {:<34} │",
         format!("{:?}", vad_engine_clone)
@@ -434,11 +1044,70 @@ async fn main() -> anyhow::Result<()> {
         "│ data directory      │ {:<34} │",
         local_data_dir_clone.display()
     );
+    println!(
+        "│ frames directory    │ {:<34} │",
+        format_cell(&storage.frames_dir.display().to_string(), VALUE_WIDTH)
+    );
+    println!(
+        "│ audio directory     │ {:<34} │",
+        format_cell(&storage.audio_dir.display().to_string(), VALUE_WIDTH)
+    );
+    println!(
+        "│ frame buffer        │ {:<34} │",
+        cli.frame_buffer_dir.as_ref().map_or("disabled".to_string(), |dir| {
+            format_cell(&dir.display().to_string(), VALUE_WIDTH)
+        })
+    );
     println!("│ debug mode          │ {:<34} │", cli.debug);
+    println!(
+        "│ log format          │ {:<34} │",
+        format!("{:?}", cli.log_format).to_lowercase()
+    );
     println!("│ telemetry           │ {:<34} │", !cli.disable_telemetry);
     println!("│ local llm           │ {:<34} │", cli.enable_llm);
 
     println!("│ use pii removal     │ {:<34} │", cli.use_pii_removal);
+    println!("│ normalize ocr       │ {:<34} │", !cli.ocr_no_normalize);
+    println!(
+        "│ ocr script          │ {:<34} │",
+        format_cell(
+            &cli.ocr_postprocess_script
+                .as_ref()
+                .map(|path| path.display().to_string())
+                .unwrap_or_else(|| "disabled".to_string()),
+            VALUE_WIDTH
+        )
+    );
+    println!(
+        "│ ocr languages       │ {:<34} │",
+        format_cell(
+            &if cli.ocr_hint_language.is_empty() {
+                "auto".to_string()
+            } else {
+                cli.ocr_hint_language.join(", ")
+            },
+            VALUE_WIDTH
+        )
+    );
+    println!(
+        "│ startup script      │ {:<34} │",
+        format_cell(
+            &cli.startup_script
+                .as_ref()
+                .map(|path| path.display().to_string())
+                .unwrap_or_else(|| "disabled".to_string()),
+            VALUE_WIDTH
+        )
+    );
+    let secret_names = secrets.names();
+    println!(
+        "│ secrets             │ {:<34} │",
+        if secret_names.is_empty() {
+            "not set".to_string()
+        } else {
+            format_cell(&secret_names.join(", "), VALUE_WIDTH)
+        }
+    );
     println!(
         "│ ignored windows     │ {:<34} │",
         format_cell(&format!("{:?}", &ignored_windows_clone), VALUE_WIDTH)
@@ -447,6 +1116,14 @@ async fn main() -> anyhow::Result<()> {
         "│ included windows    │ {:<34} │",
         format_cell(&format!("{:?}", &included_windows_clone), VALUE_WIDTH)
     );
+    println!(
+        "│ ignore window       │ {:<34} │",
+        format_cell(&format!("{:?}", &ignore_window_clone), VALUE_WIDTH)
+    );
+    println!(
+        "│ audio priority      │ {:<34} │",
+        format_cell(&format!("{:?}", &audio_device_priority_clone), VALUE_WIDTH)
+    );
     println!(
         "│ friend wearable uid │ {:<34} │",
         cli.friend_wearable_uid.as_deref().unwrap_or("not set")
@@ -588,6 +1265,14 @@ async fn main() -> anyhow::Result<()> {
         }
     }
 
+    if let Some(read_only_server) = read_only_server {
+        let devices_status = devices_status.clone();
+        tokio::spawn(async move {
+            if let Err(e) = read_only_server.start(devices_status, api_plugin).await {
+                error!("read-only server stopped with error: {:?}", e);
+            }
+        });
+    }
     let server_future = server.start(devices_status, api_plugin);
     pin_mut!(server_future);
 
@@ -605,44 +1290,98 @@ async fn main() -> anyhow::Result<()> {
     // Add auto-destruct watcher
     if let Some(pid) = cli.auto_destruct_pid {
         info!("watching pid {} for auto-destruction", pid);
-        let shutdown_tx_clone = shutdown_tx.clone();
+        let shutdown_clone = shutdown.clone();
         tokio::spawn(async move {
             if watch_pid(pid).await {
                 info!("watched pid {} has stopped, initiating shutdown", pid);
-                let _ = shutdown_tx_clone.send(());
+                shutdown_clone.cancel();
             }
         });
     }
 
-    let ctrl_c_future = signal::ctrl_c();
-    pin_mut!(ctrl_c_future);
+    let shutdown_signal = shutdown_signal();
+    pin_mut!(shutdown_signal);
+    let mut handle = handle;
 
-    tokio::select! {
-        _ = handle => info!("recording completed"),
+    let mut server_failed = false;
+    let recording_stopped = tokio::select! {
+        _ = &mut handle => {
+            info!("recording completed");
+            true
+        }
         result = &mut server_future => {
             match result {
                 Ok(_) => info!("server stopped normally"),
-                Err(e) => error!("server stopped with error: {:?}", e),
+                Err(e) => {
+                    error!("server stopped with error: {:?}", e);
+                    eprintln!("{}", e);
+                    server_failed = true;
+                }
             }
+            false
         }
         _ = &mut pipes_future => {
             info!("all pipes completed, but server is still running");
+            false
         }
-        _ = ctrl_c_future => {
-            info!("received ctrl+c, initiating shutdown");
-            let _ = shutdown_tx.send(());
+        _ = &mut shutdown_signal => {
+            info!("initiating shutdown");
+            false
+        }
+        _ = shutdown.cancelled() => false,
+    };
+
+    shutdown.cancel();
+    if !recording_stopped {
+        match tokio::time::timeout(SHUTDOWN_TIMEOUT, &mut handle).await {
+            Ok(Err(e)) => error!("recording task failed during shutdown: {:?}", e),
+            Ok(Ok(())) => {}
+            Err(_) => warn!(
+                "recording did not stop within {}s, exiting anyway",
+                SHUTDOWN_TIMEOUT.as_secs()
+            ),
         }
     }
+    db.pool.close().await;
 
     info!("shutdown complete");
 
     if let Some(h) = h {
         h.shutdown();
     }
+    if server_failed {
+        std::process::exit(1);
+    }
 
     Ok(())
 }
 
+/// Resolves on SIGINT or SIGTERM, on ctrl+c where unix signals are not available.
+async fn shutdown_signal() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal as unix_signal, SignalKind};
+        match unix_signal(SignalKind::terminate()) {
+            Ok(mut sigterm) => {
+                tokio::select! {
+                    _ = signal::ctrl_c() => info!("received SIGINT"),
+                    _ = sigterm.recv() => info!("received SIGTERM"),
+                }
+            }
+            Err(e) => {
+                error!("failed to listen for SIGTERM: {}", e);
+                let _ = signal::ctrl_c().await;
+                info!("received SIGINT");
+            }
+        }
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = signal::ctrl_c().await;
+        info!("received ctrl+c");
+    }
+}
+
 async fn handle_pipe_command(pipe: PipeCommand, pipe_manager: &PipeManager) -> anyhow::Result<()> {
     // Handle pipe subcommands
     match pipe {
@@ -0,0 +1,36 @@
+use socket2::{Domain, Protocol, Socket, Type};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, TcpListener};
+
+/// Whether an ipv6 socket can be opened here, false e.g. with ipv6 disabled in the kernel.
+pub fn ipv6_available() -> bool {
+    TcpListener::bind((Ipv6Addr::LOCALHOST, 0)).is_ok()
+}
+
+/// Where to listen for `--bind-address`: all interfaces (`0.0.0.0` or `::`) means the
+/// dual-stack `[::]` when ipv6 is available and `0.0.0.0` otherwise or with `--ipv4-only`,
+/// any other address is used as given.
+pub fn listen_addr(bind_address: IpAddr, port: u16, ipv4_only: bool) -> SocketAddr {
+    if !bind_address.is_unspecified() {
+        return SocketAddr::new(bind_address, port);
+    }
+    if !ipv4_only && ipv6_available() {
+        SocketAddr::new(IpAddr::V6(Ipv6Addr::UNSPECIFIED), port)
+    } else {
+        SocketAddr::new(IpAddr::V4(Ipv4Addr::UNSPECIFIED), port)
+    }
+}
+
+/// Like `TcpListener::bind`, but `[::]` also accepts ipv4 clients, which is not the default on
+/// every platform (windows, some bsds).
+pub fn bind_listener(addr: SocketAddr) -> std::io::Result<TcpListener> {
+    let socket = Socket::new(Domain::for_address(addr), Type::STREAM, Some(Protocol::TCP))?;
+    if let IpAddr::V6(ip) = addr.ip() {
+        socket.set_only_v6(!ip.is_unspecified())?;
+    }
+    #[cfg(not(windows))]
+    socket.set_reuse_address(true)?;
+    socket.bind(&addr.into())?;
+    socket.listen(1024)?;
+    socket.set_nonblocking(true)?;
+    Ok(socket.into())
+}
@@ -0,0 +1,65 @@
+//! `--max-body-size-kb`: requests with larger bodies are answered `413 Payload Too Large` before
+//! they are read, so a client can't run the server out of memory with one upload.
+
+use axum::extract::{DefaultBodyLimit, State};
+use axum::http::header::CONTENT_TYPE;
+use axum::http::StatusCode;
+use axum::middleware;
+use axum::response::{IntoResponse, Json, Response};
+use axum::Router;
+use serde_json::json;
+use tower_http::limit::RequestBodyLimitLayer;
+
+pub const DEFAULT_MAX_BODY_SIZE_KB: usize = 10 * 1024;
+/// `POST /import` takes batches of frames and transcriptions from another machine
+pub const DEFAULT_MAX_IMPORT_BODY_SIZE_KB: usize = 32 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BodyLimits {
+    /// Bytes, every route but `POST /import`
+    pub max_body_size: usize,
+    /// Bytes, `POST /import`
+    pub max_import_body_size: usize,
+}
+
+impl Default for BodyLimits {
+    fn default() -> Self {
+        BodyLimits {
+            max_body_size: DEFAULT_MAX_BODY_SIZE_KB * 1024,
+            max_import_body_size: DEFAULT_MAX_IMPORT_BODY_SIZE_KB * 1024,
+        }
+    }
+}
+
+// bodies cut short while streaming are refused by the extractor with a plain text 413
+async fn json_payload_too_large(State(limit): State<usize>, response: Response) -> Response {
+    let is_json = response
+        .headers()
+        .get(CONTENT_TYPE)
+        .is_some_and(|value| value.as_bytes().starts_with(b"application/json"));
+    if response.status() != StatusCode::PAYLOAD_TOO_LARGE || is_json {
+        return response;
+    }
+    (
+        StatusCode::PAYLOAD_TOO_LARGE,
+        Json(json!({
+            "error": format!("request body is larger than {} KB", limit / 1024)
+        })),
+    )
+        .into_response()
+}
+
+/// Refuses bodies of more than `limit` bytes on the routes of `router`, in place of axum's own
+/// 2 MB limit on extractors.
+pub fn with_body_limit<S>(router: Router<S>, limit: usize) -> Router<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    router
+        .layer(DefaultBodyLimit::disable())
+        .layer(RequestBodyLimitLayer::new(limit))
+        .layer(middleware::map_response_with_state(
+            limit,
+            json_payload_too_large,
+        ))
+}
@@ -1,8 +1,20 @@
-use clap::{Parser, Subcommand};
+use clap::parser::ValueSource;
+use clap::{CommandFactory, FromArgMatches, Parser, Subcommand};
 use screenpipe_audio::{vad_engine::VadSensitivity, AudioTranscriptionEngine as CoreAudioTranscriptionEngine};
 use screenpipe_vision::utils::OcrEngine as CoreOcrEngine;
+use screenpipe_vision::{default_ocr_workers, DEFAULT_DEDUP_THRESHOLD, MAX_FPS, MIN_FPS};
+use std::collections::BTreeMap;
+use std::ffi::OsString;
+use std::fmt;
+use std::net::{IpAddr, Ipv4Addr};
+use std::path::PathBuf;
+use chrono::{DateTime, NaiveTime, Utc};
 use clap::ValueEnum;
 use screenpipe_audio::vad_engine::VadEngineEnum;
+use screenpipe_audio::AudioFormat;
+use screenpipe_integrations::unstructured_ocr::DEFAULT_CLOUD_OCR_TIMEOUT_MS;
+use crate::frame_format::CaptureFormat;
+use crate::body_limit::{DEFAULT_MAX_BODY_SIZE_KB, DEFAULT_MAX_IMPORT_BODY_SIZE_KB};
 
 #[derive(Clone, Debug, ValueEnum, PartialEq)]
 pub enum CliAudioTranscriptionEngine {
@@ -89,6 +101,98 @@ impl From<CliVadSensitivity> for VadSensitivity {
     }
 }
 
+#[derive(Clone, Debug, ValueEnum, PartialEq)]
+pub enum CliAudioFormat {
+    Mp4,
+    Wav,
+    Flac,
+    Opus,
+}
+
+impl From<CliAudioFormat> for AudioFormat {
+    fn from(cli_format: CliAudioFormat) -> Self {
+        match cli_format {
+            CliAudioFormat::Mp4 => AudioFormat::Mp4,
+            CliAudioFormat::Wav => AudioFormat::Wav,
+            CliAudioFormat::Flac => AudioFormat::Flac,
+            CliAudioFormat::Opus => AudioFormat::Opus,
+        }
+    }
+}
+
+#[derive(Clone, Debug, ValueEnum, PartialEq)]
+pub enum CliCaptureFormat {
+    Png,
+    /// Lossless like png, faster to encode
+    WebpLossless,
+}
+
+impl From<CliCaptureFormat> for CaptureFormat {
+    fn from(cli_format: CliCaptureFormat) -> Self {
+        match cli_format {
+            CliCaptureFormat::Png => CaptureFormat::Png,
+            CliCaptureFormat::WebpLossless => CaptureFormat::WebpLossless,
+        }
+    }
+}
+
+#[derive(Clone, Debug, ValueEnum, PartialEq)]
+pub enum CliLogFormat {
+    Text,
+    /// One JSON object per line, for Loki, Datadog or Elasticsearch
+    Json,
+}
+
+/// `--fps` values outside `MIN_FPS..=MAX_FPS` are rejected at startup.
+pub fn parse_fps(value: &str) -> Result<f64, String> {
+    let fps: f64 = value
+        .parse()
+        .map_err(|_| format!("'{}' is not a number", value))?;
+    if !(MIN_FPS..=MAX_FPS).contains(&fps) {
+        return Err(format!("fps must be between {} and {}", MIN_FPS, MAX_FPS));
+    }
+    Ok(fps)
+}
+
+/// `--dedup-threshold` is between 0 and 1, like the one PATCH /config takes.
+pub fn parse_dedup_threshold(value: &str) -> Result<f64, String> {
+    let threshold: f64 = value
+        .parse()
+        .map_err(|_| format!("'{}' is not a number", value))?;
+    if !(0.0..=1.0).contains(&threshold) {
+        return Err("dedup threshold must be between 0 and 1".to_string());
+    }
+    Ok(threshold)
+}
+
+/// `--audio-chunk-overlap-secs` is a number of seconds of at least 0.
+pub fn parse_audio_chunk_overlap(value: &str) -> Result<f64, String> {
+    let secs: f64 = value
+        .parse()
+        .map_err(|_| format!("'{}' is not a number", value))?;
+    if !secs.is_finite() || secs < 0.0 {
+        return Err("audio chunk overlap must be a number of seconds of at least 0".to_string());
+    }
+    Ok(secs)
+}
+
+/// `--ocr-hint-language` values are tessdata pack names like `deu` or `chi_sim`.
+pub fn parse_ocr_hint_language(value: &str) -> Result<String, String> {
+    if !crate::tessdata::is_lang_code(value) {
+        return Err(format!(
+            "'{}' is not a tesseract language, they are lowercase letters and '_'",
+            value
+        ));
+    }
+    Ok(value.to_string())
+}
+
+/// `--summary-time` is a 24h `HH:MM`.
+pub fn parse_summary_time(value: &str) -> Result<NaiveTime, String> {
+    NaiveTime::parse_from_str(value, "%H:%M")
+        .map_err(|_| format!("'{}' is not a time like 23:30", value))
+}
+
 #[derive(Parser)]
 #[command(
     author, 
@@ -98,31 +202,172 @@ impl From<CliVadSensitivity> for VadSensitivity {
     name = "screenpipe"
 )]
 pub struct Cli {
-    /// FPS for continuous recording
-    /// 1 FPS = 30 GB / month
+    /// FPS for continuous recording, between 0.001 and 60. Below 1 means one frame every
+    /// 1 / fps seconds, e.g. 0.0167 for one frame a minute.
     /// 5 FPS = 150 GB / month
+    /// 1 FPS = 30 GB / month
+    /// 0.2 FPS = 6 GB / month
+    /// 0.1 FPS = 3 GB / month
+    /// 0.0167 FPS = 0.5 GB / month
     /// Optimise based on your needs.
     /// Your screen rarely change more than 1 times within a second, right?
-    #[cfg_attr(not(target_os = "macos"), arg(short, long, default_value_t = 1.0))]
-    #[cfg_attr(target_os = "macos", arg(short, long, default_value_t = 0.2))] 
+    #[cfg_attr(not(target_os = "macos"), arg(short, long, default_value_t = 1.0, value_parser = parse_fps))]
+    #[cfg_attr(target_os = "macos", arg(short, long, default_value_t = 0.2, value_parser = parse_fps))] 
     pub fps: f64, // ! not crazy about this (inconsistent behaviour across platforms) see https://github.com/mediar-ai/screenpipe/issues/173
     
     /// Audio chunk duration in seconds
     #[arg(short = 'd', long, default_value_t = 30)]
     pub audio_chunk_duration: u64,
 
+    /// Seconds of the end of each audio chunk also transcribed at the start of the next one, so
+    /// words cut at a boundary are kept, the words both chunks transcribed are stored once
+    /// (0 turns it off)
+    #[arg(long, default_value_t = 0.5, value_parser = parse_audio_chunk_overlap)]
+    pub audio_chunk_overlap_secs: f64,
+
+    /// Don't warm up the transcription model on 100 ms of silence at startup, which saves a burst
+    /// of cpu on constrained machines but makes the first audio chunk slower to transcribe
+    #[arg(long, default_value_t = false)]
+    pub skip_warmup: bool,
+
+    /// Normalize the loudness of each audio chunk to -18 dBFS (RMS) before storage and transcription
+    #[arg(long, default_value_t = false)]
+    pub normalize_audio: bool,
+
+    /// Remove speaker echo from microphone audio, using recorded output audio as the reference
+    /// (requires building with the echo-cancellation feature)
+    #[arg(long, default_value_t = false)]
+    pub echo_cancellation: bool,
+
+    /// Format used to store audio chunks: mp4 (aac), wav, flac (lossless) or opus (smallest)
+    #[arg(long, value_enum, default_value_t = CliAudioFormat::Mp4)]
+    pub audio_format: CliAudioFormat,
+
+    /// Store audio chunks as wav compressed with zstd (.wav.zst) instead of --audio-format, the
+    /// api serves them decompressed
+    #[arg(long, default_value_t = false, conflicts_with = "audio_format")]
+    pub compress_audio: bool,
+
+    /// Maximum number of pooled SQLite connections shared by the recorder and the API server
+    #[arg(long, default_value_t = 4, value_parser = clap::value_parser!(u32).range(1..))]
+    pub db_pool_size: u32,
+
+    /// Number of captured frames written to the database per transaction, higher values cut
+    /// write overhead at high fps at the cost of frames showing up in search later
+    #[arg(long, default_value_t = 1, value_parser = clap::value_parser!(u64).range(1..))]
+    pub frame_batch_size: u64,
+
+    /// Maximum number of ocr jobs, frame batch writes and transcription inserts running at once
+    #[arg(long, default_value_t = 16, value_parser = clap::value_parser!(u64).range(1..))]
+    pub max_concurrent_tasks: u64,
+
+    /// Transcription inserts waiting for one of the --max-concurrent-tasks slots, new ones are
+    /// dropped with a warning once that many wait. Frame writes and ocr wait without a limit, a
+    /// dropped frame would shift the video offsets of the frames after it
+    #[arg(long, default_value_t = 64)]
+    pub task_queue_depth: u64,
+
+    /// Executable run for each stored frame: gets {timestamp, ocr_text, app_name, frame_path}
+    /// as JSON on stdin and answers {tags: [], notes: ""} on stdout, which is saved with the frame
+    #[arg(long)]
+    pub pipe_cmd: Option<String>,
+
+    /// Maximum number of --pipe-cmd processes running at once, frames beyond that are skipped
+    #[arg(long, default_value_t = 4, value_parser = clap::value_parser!(u64).range(1..))]
+    pub pipe_cmd_concurrency: u64,
+
+    /// Seconds to wait before restarting recording after it failed, doubled on each consecutive
+    /// failure
+    #[arg(long, default_value_t = 5, value_parser = clap::value_parser!(u64).range(1..))]
+    pub heal_initial_delay_secs: u64,
+
+    /// Upper bound of the wait between recording restarts, in seconds
+    #[arg(long, default_value_t = 300, value_parser = clap::value_parser!(u64).range(1..))]
+    pub heal_max_delay_secs: u64,
+
+    /// Send a desktop notification when the disk holding the data directory is fuller than this (%)
+    #[arg(long, default_value_t = 90.0)]
+    pub alert_disk_pct: f32,
+
+    /// Send a desktop notification when CPU usage stays above this (%) for 60 seconds
+    #[arg(long, default_value_t = 95.0)]
+    pub alert_cpu_pct: f32,
+
+    /// Restart recording when screenpipe uses more memory than this (MB), releasing what leaked
+    /// without exiting the process
+    #[arg(long, value_parser = clap::value_parser!(u64).range(1..))]
+    pub max_memory_mb: Option<u64>,
+
     /// Port to run the server on
     #[arg(short = 'p', long, default_value_t = 3030)]
     pub port: u16,
 
+    /// Also serve the GET endpoints on this port from separate read-only database connections,
+    /// so analytics tools can run heavy searches without slowing down recording
+    #[arg(long)]
+    pub read_only_port: Option<u16>,
+
+    /// Requests per second each client ip may send to the api, more get 429 Too Many Requests
+    /// (no limit by default)
+    #[arg(long, value_parser = clap::value_parser!(u32).range(1..))]
+    pub rate_limit_rps: Option<u32>,
+
+    /// Requests a client may send at once above --rate-limit-rps, defaults to --rate-limit-rps
+    #[arg(long, requires = "rate_limit_rps", value_parser = clap::value_parser!(u32).range(1..))]
+    pub rate_limit_burst: Option<u32>,
+
+    /// Requests carrying `Authorization: Bearer <key>` are not rate limited
+    #[arg(long, requires = "rate_limit_rps")]
+    pub rate_limit_exempt_key: Option<String>,
+
+    /// Seconds the `media_url` links of search results to frame images and audio files stay
+    /// valid
+    #[arg(long, default_value_t = 3600, value_parser = clap::value_parser!(u64).range(1..))]
+    pub signed_url_ttl_secs: u64,
+
+    /// Largest request body the api reads, in kilobytes, larger ones get 413 Payload Too Large
+    #[arg(long, default_value_t = DEFAULT_MAX_BODY_SIZE_KB, value_parser = clap::value_parser!(u64).range(1..).map(|kb| kb as usize))]
+    pub max_body_size_kb: usize,
+
+    /// Largest body of `POST /import`, in kilobytes, it receives batches of recordings from
+    /// --remote-sync-url
+    #[arg(long, default_value_t = DEFAULT_MAX_IMPORT_BODY_SIZE_KB, value_parser = clap::value_parser!(u64).range(1..).map(|kb| kb as usize))]
+    pub max_import_body_size_kb: usize,
+
+    /// Address the api server listens on, 127.0.0.1 for local-only access, 0.0.0.0 for all
+    /// interfaces
+    #[arg(long, default_value_t = IpAddr::V4(Ipv4Addr::LOCALHOST))]
+    pub bind_address: IpAddr,
+
+    /// Listen on 0.0.0.0 rather than the dual-stack [::] when --bind-address is all interfaces
+    #[arg(long)]
+    pub ipv4_only: bool,
+
     /// Disable audio recording
     #[arg(long, default_value_t = false)]
     pub disable_audio: bool,
 
-    /// Audio devices to use (can be specified multiple times)
+    /// Audio devices to use (can be specified multiple times), e.g. "MacBook Microphone (input)".
+    /// Suffixes such as " (Built-in)" or windows guids can be left off the name
     #[arg(short = 'i', long)]
     pub audio_device: Vec<String>,
 
+    /// Ordered glob patterns (case-insensitive) for preferred audio devices, used when no
+    /// --audio-device is given, before falling back to the system defaults, example:
+    /// --audio-device-priority "*Headset*" --audio-device-priority "*USB*"
+    #[arg(long)]
+    pub audio_device_priority: Vec<String>,
+
+    /// Record a synthetic input device playing a sine wave at this frequency (440 Hz when no
+    /// value is given) instead of the system devices, for machines without audio hardware
+    #[arg(long, value_name = "HZ", num_args = 0..=1, default_missing_value = "440")]
+    pub fake_audio_device: Option<f32>,
+
+    /// Record the audio this app plays, by bundle id (can be specified multiple times), e.g.
+    /// "com.spotify.client". Needs macos 14, older versions record the system output instead
+    #[arg(long, value_name = "BUNDLE_ID")]
+    pub capture_audio_from_app: Vec<String>,
+
     /// List available audio devices
     #[arg(long)]
     pub list_audio_devices: bool,
@@ -131,10 +376,27 @@ pub struct Cli {
     #[arg(long)]
     pub data_dir: Option<String>,
 
+    /// Directory for video chunks and thumbnails, instead of the data dir's data folder
+    #[arg(long)]
+    pub frames_dir: Option<String>,
+
+    /// Directory for audio chunks, instead of the data dir's data folder
+    #[arg(long)]
+    pub audio_dir: Option<String>,
+
+    /// Record video chunks in this directory, e.g. a tmpfs mount, and move them to the frames
+    /// directory once finished. Chunks left there by a crash are moved on startup
+    #[arg(long)]
+    pub frame_buffer_dir: Option<PathBuf>,
+
     /// Enable debug logging for screenpipe modules
     #[arg(long)]
     pub debug: bool,
 
+    /// Format of the console and screenpipe.log output
+    #[arg(long, value_enum, default_value_t = CliLogFormat::Text)]
+    pub log_format: CliLogFormat,
+
     /// Save text files
     #[arg(long, default_value_t = false)]
     pub save_text_files: bool,
@@ -166,6 +428,35 @@ pub struct Cli {
     )]
     pub ocr_engine: CliOcrEngine,
 
+    /// OCR engine to re-run a window with when the primary engine's confidence is below --ocr-fallback-threshold,
+    /// example: -o tesseract --ocr-engine-fallback unstructured
+    #[arg(long, value_enum)]
+    pub ocr_engine_fallback: Option<CliOcrEngine>,
+
+    /// Confidence (0.0 to 1.0) below which the fallback OCR engine is used
+    #[arg(long, default_value_t = 0.5)]
+    pub ocr_fallback_threshold: f64,
+
+    /// Timeout of each unstructured cloud OCR request, failed requests are tried up to 3 times
+    /// before the frame is stored without text
+    #[arg(long, default_value_t = DEFAULT_CLOUD_OCR_TIMEOUT_MS, value_parser = clap::value_parser!(u64).range(1..))]
+    pub cloud_ocr_timeout_ms: u64,
+
+    /// Frames differing from the previous one by less than this (0.0 to 1.0) are skipped,
+    /// can be changed while running with PATCH /config
+    #[arg(long, default_value_t = DEFAULT_DEDUP_THRESHOLD, value_parser = parse_dedup_threshold)]
+    pub dedup_threshold: f64,
+
+    /// Pause recording of the screen once no frame got past --dedup-threshold for this many
+    /// seconds (screensaver, locked screen), it resumes with the next change
+    #[arg(long, value_parser = clap::value_parser!(u64).range(1..))]
+    pub idle_pause_secs: Option<u64>,
+
+    /// Draw the mouse cursor into captured frames, its position is stored with each frame either
+    /// way
+    #[arg(long, default_value_t = false)]
+    pub capture_cursor: bool,
+
     /// UID key for sending data to friend wearable (if not provided, data won't be sent)
     #[arg(long)]
     pub friend_wearable_uid: Option<String>,
@@ -174,6 +465,16 @@ pub struct Cli {
     #[arg(long)]
     pub list_monitors: bool,
 
+    /// Script or executable run once the api accepts connections, with SCREENPIPE_PORT and
+    /// SCREENPIPE_DATA_DIR set. A failing script is logged, the server keeps running
+    #[arg(long)]
+    pub startup_script: Option<PathBuf>,
+
+    /// X11 display to capture on Linux, e.g. `:1` for a VNC session, instead of the one in
+    /// DISPLAY. Ignored on Windows and macOS, which record the displays the OS lists as active
+    #[arg(long)]
+    pub display: Option<String>,
+
     /// Monitor IDs to use, these will be used to select the monitors to record
     #[arg(short = 'm', long)]
     pub monitor_id: Vec<u32>,
@@ -182,10 +483,78 @@ pub struct Cli {
     #[arg(long, default_value_t = false)]
     pub use_pii_removal: bool,
 
-    /// Disable vision recording
+    /// Store OCR text as the engine returned it, without expanding ligatures, applying unicode NFC,
+    /// stripping control characters and collapsing whitespace
     #[arg(long, default_value_t = false)]
+    pub ocr_no_normalize: bool,
+
+    /// Rhai script rewriting the OCR text of each window before it is stored, e.g. to drop
+    /// watermarks. It gets `text` and `confidence` and returns the new text, a run taking more
+    /// than 100 ms is stopped and the text kept as is
+    #[arg(long)]
+    pub ocr_postprocess_script: Option<PathBuf>,
+
+    /// Tesseract languages to read frames with, e.g. `--ocr-hint-language deu`. Packs tesseract
+    /// doesn't have are downloaded to ~/.screenpipe/tessdata on startup
+    #[arg(long, value_parser = parse_ocr_hint_language)]
+    pub ocr_hint_language: Vec<String>,
+
+    /// Frames OCR'd at the same time, they are still stored in capture order. Defaults to the
+    /// number of physical cores minus one
+    #[arg(long, default_value_t = default_ocr_workers() as u32, value_parser = clap::value_parser!(u32).range(1..))]
+    pub ocr_workers: u32,
+
+    /// Run OCR on one in this many frames to save cpu at high --fps, the others are stored
+    /// without text. 1 runs it on every frame
+    #[arg(long, default_value_t = 1, value_parser = clap::value_parser!(u32).range(1..))]
+    pub ocr_skip_frames: u32,
+
+    /// Upscale window images by this factor (bicubic) before OCR, small text reads better
+    #[arg(long, value_parser = clap::value_parser!(u32).range(2..=4))]
+    pub ocr_preprocess_upscale: Option<u32>,
+
+    /// Threshold window images to black and white (otsu) before OCR
+    #[arg(long)]
+    pub ocr_preprocess_binarize: bool,
+
+    /// Straighten tilted text in window images before OCR
+    #[arg(long)]
+    pub ocr_preprocess_deskew: bool,
+
+    /// Frames whose --capture-format encoding is larger than this are dropped instead of stored,
+    /// such frames usually come from a capture error
+    #[arg(long, default_value_t = 5000, value_parser = clap::value_parser!(u64).range(1..))]
+    pub max_frame_size_kb: u64,
+
+    /// Encoding of captured frames on their way to the video encoder: png or webp-lossless,
+    /// which is as lossless and usually faster
+    #[arg(long, value_enum, default_value_t = CliCaptureFormat::Png)]
+    pub capture_format: CliCaptureFormat,
+
+    /// Disable vision recording, the screen isn't captured at all (also --disable-frames)
+    #[arg(long, alias = "disable-frames", default_value_t = false)]
     pub disable_vision: bool,
 
+    /// Store captured frames without running OCR on them, e.g. to only search transcriptions
+    /// with the screen kept as context
+    #[arg(long, default_value_t = false)]
+    pub disable_ocr: bool,
+
+    /// Start even when --disable-audio with --disable-ocr or --disable-vision leaves nothing to
+    /// search
+    #[arg(long, default_value_t = false)]
+    pub force: bool,
+
+    /// Don't record until POST /recording/start is called, e.g. when an app embedding screenpipe
+    /// decides when to record
+    #[arg(long, default_value_t = false)]
+    pub manual_start: bool,
+
+    /// Local time (HH:MM) at which the day's markdown summary is written to
+    /// <data-dir>/summaries/YYYY-MM-DD.md
+    #[arg(long, default_value = "23:59", value_parser = parse_summary_time)]
+    pub summary_time: NaiveTime,
+
     /// VAD engine to use for speech detection
     #[arg(long, value_enum, default_value_t = CliVadEngine::Silero)] // Silero or WebRtc
     pub vad_engine: CliVadEngine,
@@ -202,14 +571,30 @@ pub struct Cli {
     #[arg(long)]
     pub included_windows: Vec<String>,
 
+    /// Glob patterns (case-insensitive) for focused window titles whose frames are discarded entirely,
+    /// neither stored in the video nor the database, example:
+    /// --ignore-window "*Private*" --ignore-window "*- Incognito"
+    #[arg(long)]
+    pub ignore_window: Vec<String>,
+
     /// Video chunk duration in seconds
     #[arg(long, default_value_t = 60)]
     pub video_chunk_duration: u64,
 
-    /// Deepgram API Key for audio transcription
+    /// Deepgram API Key for audio transcription, otherwise `deepgram_api_key` in ~/.screenpipe/secrets.toml
     #[arg(long = "deepgram-api-key")]
     pub deepgram_api_key: Option<String>,
 
+    /// Base url of another screenpipe instance, e.g. http://desktop:3030, whose POST /import gets
+    /// the frames and transcriptions recorded here as they are committed
+    #[arg(long)]
+    pub remote_sync_url: Option<String>,
+
+    /// Secret the rows pushed to --remote-sync-url are signed with, POST /import only accepts
+    /// rows signed with it. Otherwise `remote_sync_secret` in ~/.screenpipe/secrets.toml
+    #[arg(long)]
+    pub remote_sync_secret: Option<String>,
+
     /// PID to watch for auto-destruction. If provided, screenpipe will stop when this PID is no longer running.
     #[arg(long)]
     pub auto_destruct_pid: Option<u32>,
@@ -222,6 +607,11 @@ pub struct Cli {
     #[arg(long, default_value_t = false)]
     pub disable_telemetry: bool,
 
+    /// Don't add the X-Content-Type-Options, X-Frame-Options, Content-Security-Policy and
+    /// Referrer-Policy headers to api responses, e.g. when a framework embedding the api sets its own
+    #[arg(long, default_value_t = false)]
+    pub disable_security_headers: bool,
+
     /// Enable Local LLM API
     #[arg(long, default_value_t = false)]
     pub enable_llm: bool,
@@ -231,6 +621,81 @@ pub struct Cli {
 
 }
 
+/// Where a setting came from, a flag on the command line wins over its environment variable,
+/// which wins over the secrets file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigSource {
+    Cli,
+    Env,
+    Config,
+    Default,
+}
+
+impl fmt::Display for ConfigSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ConfigSource::Cli => "cli",
+            ConfigSource::Env => "env",
+            ConfigSource::Config => "config",
+            ConfigSource::Default => "default",
+        })
+    }
+}
+
+/// Source of each setting, by field name of [`Cli`].
+pub type ConfigSources = BTreeMap<String, ConfigSource>;
+
+/// Variable a flag is read from when left off the command line, `--audio-chunk-duration` is
+/// `SCREENPIPE_AUDIO_CHUNK_DURATION`.
+pub fn env_var_name(long: &str) -> String {
+    format!("SCREENPIPE_{}", long.replace('-', "_").to_uppercase())
+}
+
+impl Cli {
+    /// [`Cli::parse`] that reads the flags left off the command line from their
+    /// [`env_var_name`] variables.
+    pub fn parse_with_env() -> (Self, ConfigSources) {
+        Self::try_parse_with_env(std::env::args_os()).unwrap_or_else(|e| e.exit())
+    }
+
+    pub fn try_parse_with_env<I, T>(args: I) -> Result<(Self, ConfigSources), clap::Error>
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<OsString> + Clone,
+    {
+        let mut command = Self::command().mut_args(|arg| {
+            let name = arg.get_long().map(env_var_name);
+            match name {
+                Some(name) => arg.env(name),
+                None => arg,
+            }
+        });
+        let matches = command.try_get_matches_from_mut(args)?;
+        let sources = command
+            .get_arguments()
+            .filter(|arg| {
+                arg.get_long()
+                    .is_some_and(|long| long != "help" && long != "version")
+            })
+            .map(|arg| {
+                let id = arg.get_id().as_str();
+                let source = match matches.value_source(id) {
+                    Some(ValueSource::CommandLine) => ConfigSource::Cli,
+                    Some(ValueSource::EnvVariable) => ConfigSource::Env,
+                    _ => ConfigSource::Default,
+                };
+                (id.to_string(), source)
+            })
+            .collect();
+        Ok((Self::from_arg_matches(&matches)?, sources))
+    }
+
+    /// Neither audio nor screen text would be recorded.
+    pub fn records_nothing(&self) -> bool {
+        self.disable_audio && (self.disable_vision || self.disable_ocr)
+    }
+}
+
 #[derive(Subcommand)]
 pub enum Command {
     /// Pipe management commands
@@ -238,6 +703,33 @@ pub enum Command {
         #[command(subcommand)]
         subcommand: PipeCommand,
     },
+    /// Check that ffmpeg, screen capture with ocr, audio recording and the database work
+    SelfTest,
+    /// Time the ocr engines on the same captured frames to pick the fastest for this machine
+    Benchmark {
+        /// Engines to compare, defaults to every engine available on this platform
+        /// (unstructured only when UNSTRUCTURED_API_KEY is set)
+        #[arg(long, value_enum)]
+        ocr_engine: Vec<CliOcrEngine>,
+        /// Also write the results to this file as JSON
+        #[arg(long)]
+        output_json: Option<PathBuf>,
+    },
+    /// Import a pre-recorded video: ocr its frames and transcribe its audio into the database.
+    /// Running it again on the same file resumes where it stopped
+    Import {
+        /// Video file to import
+        path: PathBuf,
+        /// Frames per second taken from the video
+        #[arg(long, default_value_t = 1.0, value_parser = parse_fps)]
+        fps: f64,
+        /// When the recording started (RFC 3339), defaults to the file's modification time minus its duration
+        #[arg(long)]
+        start_time: Option<DateTime<Utc>>,
+        /// Only import the frames
+        #[arg(long, default_value_t = false)]
+        no_audio: bool,
+    },
     // ... (other top-level commands if any)
 }
 
@@ -0,0 +1,171 @@
+//! Typed client for the api of a running screenpipe, the request and response types are the
+//! ones the server uses.
+//!
+//! ```no_run
+//! use screenpipe_server::client::{Client, SearchParams};
+//! use screenpipe_server::{ContentItem, ContentType, RuntimeConfigUpdate};
+//!
+//! # async fn run() -> anyhow::Result<()> {
+//! let client = Client::new("http://localhost:3030")?;
+//! println!("screenpipe is {}", client.get_health().await?.status);
+//!
+//! let results = client
+//!     .search(&SearchParams {
+//!         q: Some("invoice".to_string()),
+//!         content_type: Some(ContentType::OCR),
+//!         limit: Some(5),
+//!         ..Default::default()
+//!     })
+//!     .await?;
+//! for item in results.data {
+//!     if let ContentItem::OCR(ocr) = item {
+//!         println!("{} {}: {}", ocr.timestamp, ocr.app_name, ocr.text);
+//!     }
+//! }
+//!
+//! // record less often for a while
+//! client
+//!     .update_config(&RuntimeConfigUpdate {
+//!         fps: Some(0.2),
+//!         ..Default::default()
+//!     })
+//!     .await?;
+//! client.pause_recording().await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::search_rank::{SearchSort, SortOrder};
+use crate::server::{
+    ContentItem, FrameSearchItem, HealthCheckResponse, ListDeviceResponse, MonitorInfo,
+    PaginatedResponse, RecordingStatusResponse,
+};
+use crate::{ContentType, RecordingStats, RuntimeConfigResponse, RuntimeConfigUpdate};
+use anyhow::{bail, Result};
+use chrono::{DateTime, Utc};
+use reqwest::{Method, RequestBuilder};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::time::Duration;
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Query of `GET /search`, fields left `None` take the server's default.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SearchParams {
+    pub q: Option<String>,
+    pub content_type: Option<ContentType>,
+    pub limit: Option<u32>,
+    pub offset: Option<u32>,
+    pub start_time: Option<DateTime<Utc>>,
+    pub end_time: Option<DateTime<Utc>>,
+    pub app_name: Option<String>,
+    pub window_name: Option<String>,
+    pub include_frames: Option<bool>,
+    pub min_length: Option<usize>,
+    pub max_length: Option<usize>,
+    pub session_id: Option<String>,
+    /// `next_cursor` of the previous page
+    pub cursor: Option<String>,
+    pub fuzzy: Option<bool>,
+    pub sort_by: Option<SearchSort>,
+    pub order: Option<SortOrder>,
+}
+
+/// Query of `GET /frames/search`.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct FrameSearchParams {
+    pub q: Option<String>,
+    pub limit: Option<u32>,
+    pub offset: Option<u32>,
+    pub start_time: Option<DateTime<Utc>>,
+    pub end_time: Option<DateTime<Utc>>,
+    pub app_name: Option<String>,
+    pub window_name: Option<String>,
+    /// Returns the ocr text of each frame with it
+    pub inline_text: Option<bool>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Client {
+    http: reqwest::Client,
+    base_url: String,
+}
+
+impl Client {
+    /// `base_url` is where the api listens, e.g. `http://localhost:3030`.
+    pub fn new(base_url: impl Into<String>) -> Result<Self> {
+        let http = reqwest::Client::builder()
+            .timeout(REQUEST_TIMEOUT)
+            .build()?;
+        Ok(Self::with_http_client(base_url, http))
+    }
+
+    /// For a client with its own timeouts, proxy or tls settings.
+    pub fn with_http_client(base_url: impl Into<String>, http: reqwest::Client) -> Self {
+        Client {
+            http,
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+        }
+    }
+
+    fn request(&self, method: Method, path: &str) -> RequestBuilder {
+        self.http
+            .request(method, format!("{}{}", self.base_url, path))
+    }
+
+    /// Sends the request, an answer other than 2xx is an error carrying the server's message.
+    async fn send<T: DeserializeOwned>(request: RequestBuilder) -> Result<T> {
+        let response = request.send().await?;
+        let status = response.status();
+        if !status.is_success() {
+            let url = response.url().clone();
+            bail!("{} answered {}: {}", url, status, response.text().await?);
+        }
+        Ok(response.json().await?)
+    }
+
+    pub async fn get_health(&self) -> Result<HealthCheckResponse> {
+        Self::send(self.request(Method::GET, "/health")).await
+    }
+
+    pub async fn search(&self, params: &SearchParams) -> Result<PaginatedResponse<ContentItem>> {
+        Self::send(self.request(Method::GET, "/search").query(params)).await
+    }
+
+    pub async fn search_frames(&self, params: &FrameSearchParams) -> Result<Vec<FrameSearchItem>> {
+        Self::send(self.request(Method::GET, "/frames/search").query(params)).await
+    }
+
+    pub async fn get_stats(&self) -> Result<RecordingStats> {
+        Self::send(self.request(Method::GET, "/stats")).await
+    }
+
+    /// Stops recording at the end of the current chunks, like `POST /recording/stop`.
+    pub async fn pause_recording(&self) -> Result<RecordingStatusResponse> {
+        Self::send(self.request(Method::POST, "/recording/stop")).await
+    }
+
+    pub async fn resume_recording(&self) -> Result<RecordingStatusResponse> {
+        Self::send(self.request(Method::POST, "/recording/start")).await
+    }
+
+    pub async fn get_config(&self) -> Result<RuntimeConfigResponse> {
+        Self::send(self.request(Method::GET, "/config")).await
+    }
+
+    pub async fn update_config(
+        &self,
+        update: &RuntimeConfigUpdate,
+    ) -> Result<RuntimeConfigResponse> {
+        Self::send(self.request(Method::PATCH, "/config").json(update)).await
+    }
+
+    pub async fn list_audio_devices(&self) -> Result<Vec<ListDeviceResponse>> {
+        Self::send(self.request(Method::GET, "/audio/list")).await
+    }
+
+    pub async fn list_monitors(&self) -> Result<Vec<MonitorInfo>> {
+        Self::send(self.request(Method::GET, "/monitors")).await
+    }
+}
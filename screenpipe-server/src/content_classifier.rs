@@ -0,0 +1,217 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// What a window shows, inferred after OCR and stored in `ocr_text.content_type`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ScreenContentType {
+    Code,
+    Browser,
+    Spreadsheet,
+    Terminal,
+    Video,
+}
+
+impl ScreenContentType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ScreenContentType::Code => "code",
+            ScreenContentType::Browser => "browser",
+            ScreenContentType::Spreadsheet => "spreadsheet",
+            ScreenContentType::Terminal => "terminal",
+            ScreenContentType::Video => "video",
+        }
+    }
+}
+
+impl fmt::Display for ScreenContentType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Infers the content type of a window, `None` when nothing matches.
+/// Implement this to plug in a model-based classifier instead of [`RuleBasedClassifier`].
+pub trait ContentClassifier: Send + Sync {
+    fn classify(&self, text: &str, app_name: &str, window_name: &str) -> Option<ScreenContentType>;
+}
+
+/// Deterministic classifier working offline from app / window name patterns and keyword lists.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RuleBasedClassifier;
+
+// checked against the window name first, so a youtube tab counts as video rather than browser
+const WINDOW_RULES: &[(ScreenContentType, &[&str])] = &[
+    (
+        ScreenContentType::Video,
+        &[
+            "youtube",
+            "netflix",
+            "twitch",
+            "vimeo",
+            "prime video",
+            "disney+",
+        ],
+    ),
+    (
+        ScreenContentType::Spreadsheet,
+        &["google sheets", ".xlsx", ".xls", ".csv", ".ods", ".numbers"],
+    ),
+    (
+        ScreenContentType::Code,
+        &[
+            ".rs", ".py", ".ts", ".tsx", ".js", ".jsx", ".go", ".java", ".kt", ".swift", ".c",
+            ".cpp", ".h", ".rb", ".php", ".cs", ".sql", ".toml",
+        ],
+    ),
+];
+
+const APP_RULES: &[(ScreenContentType, &[&str])] = &[
+    (
+        ScreenContentType::Terminal,
+        &[
+            "terminal",
+            "iterm",
+            "iterm2",
+            "alacritty",
+            "kitty",
+            "wezterm",
+            "warp",
+            "konsole",
+            "powershell",
+            "cmd",
+            "hyper",
+            "ghostty",
+        ],
+    ),
+    (
+        ScreenContentType::Code,
+        &[
+            "code",
+            "visual studio",
+            "cursor",
+            "zed",
+            "xcode",
+            "intellij",
+            "pycharm",
+            "webstorm",
+            "goland",
+            "clion",
+            "rustrover",
+            "android studio",
+            "sublime text",
+            "vim",
+            "nvim",
+            "emacs",
+        ],
+    ),
+    (
+        ScreenContentType::Spreadsheet,
+        &["excel", "numbers", "libreoffice calc", "calc"],
+    ),
+    (
+        ScreenContentType::Video,
+        &["vlc", "quicktime player", "iina", "mpv", "tv"],
+    ),
+    (
+        ScreenContentType::Browser,
+        &[
+            "chrome", "chromium", "firefox", "safari", "edge", "arc", "brave", "opera", "vivaldi",
+        ],
+    ),
+];
+
+const CODE_KEYWORDS: &[&str] = &[
+    "fn ",
+    "def ",
+    "function ",
+    "import ",
+    "return ",
+    "const ",
+    "class ",
+    "=>",
+    "};",
+    "#include",
+    "pub ",
+    "let ",
+    "async ",
+    "await ",
+    "elif ",
+    "println!",
+    "console.log",
+];
+
+const TERMINAL_KEYWORDS: &[&str] = &[
+    "$ sudo",
+    "$ cd ",
+    "$ ls",
+    "$ git ",
+    "$ cargo ",
+    "$ npm ",
+    "~ %",
+    "command not found",
+];
+
+/// Number of distinct keywords the ocr text needs before it is classified on text alone.
+const MIN_KEYWORD_MATCHES: usize = 3;
+
+impl ContentClassifier for RuleBasedClassifier {
+    fn classify(&self, text: &str, app_name: &str, window_name: &str) -> Option<ScreenContentType> {
+        let window_name = window_name.to_lowercase();
+        let app_name = app_name.to_lowercase();
+
+        for (content_type, patterns) in WINDOW_RULES {
+            if patterns
+                .iter()
+                .any(|p| window_name_matches(&window_name, p))
+            {
+                return Some(*content_type);
+            }
+        }
+        for (content_type, patterns) in APP_RULES {
+            if patterns.iter().any(|p| contains_word(&app_name, p)) {
+                return Some(*content_type);
+            }
+        }
+
+        let text = text.to_lowercase();
+        let matches = |keywords: &[&str]| keywords.iter().filter(|k| text.contains(*k)).count();
+        if matches(TERMINAL_KEYWORDS) >= MIN_KEYWORD_MATCHES {
+            Some(ScreenContentType::Terminal)
+        } else if matches(CODE_KEYWORDS) >= MIN_KEYWORD_MATCHES {
+            Some(ScreenContentType::Code)
+        } else {
+            None
+        }
+    }
+}
+
+/// Classifies with the default rule set.
+pub fn classify_screen_content(
+    text: &str,
+    app_name: &str,
+    window_name: &str,
+) -> Option<ScreenContentType> {
+    RuleBasedClassifier.classify(text, app_name, window_name)
+}
+
+fn window_name_matches(window_name: &str, pattern: &str) -> bool {
+    if pattern.starts_with('.') {
+        // file extension, e.g. "main.rs — screenpipe"
+        window_name
+            .split(|c: char| c.is_whitespace() || c == '/' || c == '\\')
+            .any(|word| word.ends_with(pattern) && word.len() > pattern.len())
+    } else {
+        contains_word(window_name, pattern)
+    }
+}
+
+/// `needle` appears in `haystack` not surrounded by other letters or digits, so "arc" does not
+/// match "search".
+fn contains_word(haystack: &str, needle: &str) -> bool {
+    haystack.match_indices(needle).any(|(start, _)| {
+        let before = haystack[..start].chars().next_back();
+        let after = haystack[start + needle.len()..].chars().next();
+        !before.is_some_and(char::is_alphanumeric) && !after.is_some_and(char::is_alphanumeric)
+    })
+}
@@ -1,29 +1,50 @@
+use crate::audio_status::{self, RECONNECT_INTERVAL};
 use crate::cli::{CliVadEngine, CliVadSensitivity};
-use crate::{DatabaseManager, VideoCapture};
+use crate::stats::{
+    CAPTURE_LATENCY, OCR_BINARIZE_LATENCY, OCR_DESKEW_LATENCY, OCR_SKIPPED_FRAMES,
+    OCR_UPSCALE_LATENCY,
+};
+use crate::thumbnails::{encode_thumbnail, store_thumbnail, thumbnails_dir};
+use crate::{
+    CaptureFormat, DatabaseManager, FrameBuffer, FrameData, OcrScript, PipeCmd, PipeCmdInput,
+    TaskLimiter, VideoCapture,
+};
 use anyhow::Result;
+use chrono::{DateTime, Utc};
 use crossbeam::queue::SegQueue;
 use futures::future::join_all;
+use futures::FutureExt;
 use log::{debug, error, info, warn};
+use screenpipe_audio::app_audio::app_bundle_id;
 use screenpipe_audio::vad_engine::VadSensitivity;
 use screenpipe_audio::{
-    create_whisper_channel, record_and_transcribe, vad_engine::VadEngineEnum, AudioDevice,
-    AudioInput, AudioTranscriptionEngine, DeviceControl, TranscriptionResult,
+    audio_device_available, create_whisper_channel, record_and_transcribe,
+    vad_engine::VadEngineEnum, AudioDevice, AudioFormat, AudioInput, AudioTranscriptionEngine,
+    DeviceControl, TranscriptionResult,
 };
 use screenpipe_core::pii_removal::remove_pii;
+use screenpipe_core::StoragePaths;
 use screenpipe_integrations::friend_wearable::initialize_friend_wearable_loop;
-use screenpipe_vision::OcrEngine;
+use screenpipe_vision::{
+    capture_interval, normalize_ocr_text, CaptureResult, OcrFallback, SharedCaptureConfig,
+};
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::runtime::Handle;
 use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+use tracing::{info_span, Instrument};
+
+/// Frame batches of a monitor waiting for its writer, capture waits beyond that.
+const FRAME_BATCH_QUEUE: usize = 16;
 
 pub async fn start_continuous_recording(
     db: Arc<DatabaseManager>,
-    output_path: Arc<String>,
-    fps: f64,
+    storage: &StoragePaths,
+    capture_config: SharedCaptureConfig,
     audio_chunk_duration: Duration,
     video_chunk_duration: Duration,
     vision_control: Arc<AtomicBool>,
@@ -31,18 +52,34 @@ pub async fn start_continuous_recording(
     audio_disabled: bool,
     save_text_files: bool,
     audio_transcription_engine: Arc<AudioTranscriptionEngine>,
-    ocr_engine: Arc<OcrEngine>,
+    ocr_fallback: Option<OcrFallback>,
     friend_wearable_uid: Option<String>,
     monitor_ids: Vec<u32>,
     use_pii_removal: bool,
+    normalize_ocr: bool,
+    ocr_workers: usize,
+    max_frame_size_kb: u64,
+    capture_format: CaptureFormat,
     vision_disabled: bool,
     vad_engine: CliVadEngine,
     vision_handle: &Handle,
     audio_handle: &Handle,
     ignored_windows: &[String],
     include_windows: &[String],
+    ignore_window_patterns: &[String],
     deepgram_api_key: Option<String>,
     vad_sensitivity: CliVadSensitivity,
+    normalize_audio: bool,
+    echo_cancellation: bool,
+    audio_format: AudioFormat,
+    audio_chunk_overlap: Duration,
+    warm_up_audio: bool,
+    frame_batch_size: usize,
+    pipe_cmd: Option<Arc<PipeCmd>>,
+    ocr_script: Option<Arc<OcrScript>>,
+    task_limiter: Arc<TaskLimiter>,
+    frame_buffer_dir: Option<PathBuf>,
+    shutdown: CancellationToken,
 ) -> Result<()> {
     let (whisper_sender, whisper_receiver, whisper_shutdown_flag) = if audio_disabled {
         // Create a dummy channel if no audio devices are available, e.g. audio disabled
@@ -64,12 +101,16 @@ pub async fn start_continuous_recording(
             audio_transcription_engine.clone(),
             VadEngineEnum::from(vad_engine),
             deepgram_api_key,
-            &PathBuf::from(output_path.as_ref()),
+            &storage.audio_dir,
             VadSensitivity::from(vad_sensitivity),
+            normalize_audio,
+            echo_cancellation,
+            audio_format,
+            audio_chunk_overlap,
+            warm_up_audio,
         )
         .await?
     };
-    let whisper_sender_clone = whisper_sender.clone();
     let db_manager_audio = Arc::clone(&db);
     // Initialize friend wearable loop
     if let Some(uid) = &friend_wearable_uid {
@@ -80,6 +121,11 @@ pub async fn start_continuous_recording(
     }
 
     debug!("Starting video recording for monitor {:?}", monitor_ids);
+    let output_path = Arc::new(storage.frames_dir.to_string_lossy().into_owned());
+    let frame_buffer = frame_buffer_dir.map(|buffer_dir| FrameBuffer {
+        buffer_dir,
+        frames_dir: storage.frames_dir.clone(),
+    });
     let video_tasks = if !vision_disabled {
         monitor_ids
             .iter()
@@ -87,54 +133,81 @@ pub async fn start_continuous_recording(
                 let db_manager_video = Arc::clone(&db);
                 let output_path_video = Arc::clone(&output_path);
                 let is_running_video = Arc::clone(&vision_control);
-                let ocr_engine = Arc::clone(&ocr_engine);
+                let capture_config = Arc::clone(&capture_config);
                 let friend_wearable_uid_video = friend_wearable_uid.clone();
                 let ignored_windows_video = ignored_windows.to_vec();
                 let include_windows_video = include_windows.to_vec();
+                let ignore_window_patterns_video = ignore_window_patterns.to_vec();
+                let shutdown_video = shutdown.clone();
+                let pipe_cmd = pipe_cmd.clone();
+                let ocr_script = ocr_script.clone();
+                let task_limiter = Arc::clone(&task_limiter);
+                let frame_buffer = frame_buffer.clone();
 
                 debug!("Starting video recording for monitor {}", monitor_id);
-                vision_handle.spawn(async move {
-                    record_video(
-                        db_manager_video,
-                        output_path_video,
-                        fps,
-                        is_running_video,
-                        save_text_files,
-                        ocr_engine,
-                        friend_wearable_uid_video,
-                        monitor_id,
-                        use_pii_removal,
-                        &ignored_windows_video,
-                        &include_windows_video,
-                        video_chunk_duration,
-                    )
-                    .await
-                })
+                vision_handle.spawn(
+                    async move {
+                        record_video(
+                            db_manager_video,
+                            output_path_video,
+                            capture_config,
+                            is_running_video,
+                            save_text_files,
+                            ocr_fallback,
+                            friend_wearable_uid_video,
+                            monitor_id,
+                            use_pii_removal,
+                            normalize_ocr,
+                            ocr_workers,
+                            max_frame_size_kb,
+                            capture_format,
+                            &ignored_windows_video,
+                            &include_windows_video,
+                            &ignore_window_patterns_video,
+                            video_chunk_duration,
+                            frame_batch_size,
+                            pipe_cmd,
+                            ocr_script,
+                            task_limiter,
+                            frame_buffer,
+                            shutdown_video,
+                        )
+                        .await
+                    }
+                    .instrument(info_span!("vision", monitor_id)),
+                )
             })
             .collect::<Vec<_>>()
     } else {
+        let shutdown_video = shutdown.clone();
         vec![vision_handle.spawn(async move {
-            tokio::time::sleep(Duration::from_secs(60)).await;
+            let _ = tokio::time::timeout(Duration::from_secs(60), shutdown_video.cancelled()).await;
             Ok(())
         })]
     };
 
+    let pending_tasks = Arc::clone(&task_limiter);
     let audio_task = if !audio_disabled {
-        audio_handle.spawn(async move {
-            record_audio(
-                db_manager_audio,
-                audio_chunk_duration,
-                whisper_sender,
-                whisper_receiver,
-                audio_devices_control,
-                friend_wearable_uid,
-                audio_transcription_engine,
-            )
-            .await
-        })
+        audio_handle.spawn(
+            async move {
+                record_audio(
+                    db_manager_audio,
+                    audio_chunk_duration,
+                    whisper_sender,
+                    whisper_receiver,
+                    audio_devices_control,
+                    friend_wearable_uid,
+                    audio_transcription_engine,
+                    task_limiter,
+                    shutdown,
+                )
+                .await
+            }
+            .in_current_span(),
+        )
     } else {
         audio_handle.spawn(async move {
-            tokio::time::sleep(Duration::from_secs(60)).await;
+            let _ = tokio::time::timeout(Duration::from_secs(60), shutdown.cancelled()).await;
             Ok(())
         })
     };
@@ -151,14 +224,11 @@ pub async fn start_continuous_recording(
     if let Err(e) = audio_task.await {
         error!("Audio recording error: {:?}", e);
     }
+    // transcriptions still queued or being stored, record_audio already drained whisper
+    pending_tasks.drain().await;
 
     // Shutdown the whisper channel
     whisper_shutdown_flag.store(true, Ordering::Relaxed);
-    drop(whisper_sender_clone); // Close the sender channel
-
-    // TODO: process any remaining audio chunks
-    // TODO: wait a bit for whisper to finish processing
-    // TODO: any additional cleanup like device controls to release
 
     info!("Stopped recording");
     Ok(())
@@ -167,16 +237,27 @@ pub async fn start_continuous_recording(
 async fn record_video(
     db: Arc<DatabaseManager>,
     output_path: Arc<String>,
-    fps: f64,
+    capture_config: SharedCaptureConfig,
     is_running: Arc<AtomicBool>,
     save_text_files: bool,
-    ocr_engine: Arc<OcrEngine>,
+    ocr_fallback: Option<OcrFallback>,
     _friend_wearable_uid: Option<String>,
     monitor_id: u32,
     use_pii_removal: bool,
+    normalize_ocr: bool,
+    ocr_workers: usize,
+    max_frame_size_kb: u64,
+    capture_format: CaptureFormat,
     ignored_windows: &[String],
     include_windows: &[String],
+    ignore_window_patterns: &[String],
     video_chunk_duration: Duration,
+    frame_batch_size: usize,
+    pipe_cmd: Option<Arc<PipeCmd>>,
+    ocr_script: Option<Arc<OcrScript>>,
+    task_limiter: Arc<TaskLimiter>,
+    frame_buffer: Option<FrameBuffer>,
+    shutdown: CancellationToken,
 ) -> Result<()> {
     debug!("record_video: Starting");
     let db_chunk_callback = Arc::clone(&db);
@@ -192,64 +273,247 @@ async fn record_video(
         });
     };
 
+    let db_moved_callback = Arc::clone(&db);
+    // awaited by the move, so a stop doesn't return before the chunk points at its new path
+    let chunk_moved_callback = move |old_path: &str, new_path: &str| {
+        let db_moved_callback = Arc::clone(&db_moved_callback);
+        let (old_path, new_path) = (old_path.to_string(), new_path.to_string());
+        async move {
+            if let Err(e) = db_moved_callback
+                .update_video_chunk_path(&old_path, &new_path)
+                .await
+            {
+                error!("Failed to update moved video chunk {}: {}", new_path, e);
+            }
+        }
+        .boxed()
+    };
+
     let video_capture = VideoCapture::new(
         &output_path,
-        fps,
+        frame_buffer,
+        Arc::clone(&capture_config),
         video_chunk_duration,
         new_chunk_callback,
+        chunk_moved_callback,
         save_text_files,
-        Arc::clone(&ocr_engine),
+        ocr_fallback,
+        ocr_workers,
+        max_frame_size_kb,
+        capture_format,
         monitor_id,
         ignored_windows,
         include_windows,
+        ignore_window_patterns,
+        Some(task_limiter.permits()),
     );
+    let thumbnails_dir = thumbnails_dir(Path::new(output_path.as_str()));
+
+    // one writer per monitor: the offset of a frame in its video chunk is given when its batch
+    // is committed, so batches are written in capture order and never dropped
+    let (batch_sender, mut batch_receiver) =
+        tokio::sync::mpsc::channel::<Vec<(Arc<CaptureResult>, Vec<FrameData>)>>(FRAME_BATCH_QUEUE);
+    let writer = {
+        let db = Arc::clone(&db);
+        let thumbnails_dir = thumbnails_dir.clone();
+        let pipe_cmd = pipe_cmd.clone();
+        let task_limiter = Arc::clone(&task_limiter);
+        tokio::spawn(
+            async move {
+                while let Some(batch) = batch_receiver.recv().await {
+                    let _permit = task_limiter.acquire().await;
+                    flush_frames(&db, &thumbnails_dir, pipe_cmd.as_ref(), batch).await;
+                }
+            }
+            .in_current_span(),
+        )
+    };
+
+    // captured frames waiting to be written, flushed every `frame_batch_size` frames
+    let mut pending: Vec<(Arc<CaptureResult>, Vec<FrameData>)> = Vec::new();
 
-    while is_running.load(Ordering::SeqCst) {
+    while is_running.load(Ordering::SeqCst) && !shutdown.is_cancelled() {
+        let (fps, ocr_engine) = {
+            let config = capture_config.read().unwrap();
+            (config.fps, Arc::new(config.ocr_engine))
+        };
         if let Some(frame) = video_capture.ocr_frame_queue.pop() {
-            for window_result in &frame.window_ocr_results {
-                match db.insert_frame().await {
-                    Ok(frame_id) => {
-                        let text_json =
-                            serde_json::to_string(&window_result.text_json).unwrap_or_default();
-
-                        let text = if use_pii_removal {
-                            &remove_pii(&window_result.text)
+            let timestamp = Utc::now();
+            if frame.ocr_skipped {
+                OCR_SKIPPED_FRAMES.fetch_add(1, Ordering::Relaxed);
+            }
+            let preprocess = frame.preprocess_timings;
+            for (latency, elapsed) in [
+                (&OCR_UPSCALE_LATENCY, preprocess.upscale),
+                (&OCR_BINARIZE_LATENCY, preprocess.binarize),
+                (&OCR_DESKEW_LATENCY, preprocess.deskew),
+            ] {
+                if let Some(elapsed) = elapsed {
+                    latency.record(elapsed);
+                }
+            }
+            let windows = frame
+                .window_ocr_results
+                .iter()
+                .map(|window_result| {
+                    // stored without an ocr_text row, not as an empty text
+                    let no_text = window_result.ocr_failed || frame.ocr_skipped;
+                    let text = if normalize_ocr {
+                        normalize_ocr_text(&window_result.text)
+                    } else {
+                        window_result.text.clone()
+                    };
+                    let text = match &ocr_script {
+                        Some(script) if !no_text => script.run(&text, window_result.confidence),
+                        _ => text,
+                    };
+                    FrameData {
+                        timestamp,
+                        text: if use_pii_removal {
+                            remove_pii(&text)
                         } else {
-                            &window_result.text
-                        };
-                        if let Err(e) = db
-                            .insert_ocr_text(
-                                frame_id,
-                                text,
-                                &text_json,
-                                &window_result.app_name,
-                                &window_result.window_name,
-                                Arc::clone(&ocr_engine),
-                                window_result.focused, // Add this line
-                            )
-                            .await
-                        {
-                            error!(
-                                "Failed to insert OCR text: {}, skipping window {} of frame {}",
-                                e, window_result.window_name, frame_id
-                            );
-                            continue;
-                        }
-                    }
-                    Err(e) => {
-                        warn!("Failed to insert frame: {}", e);
-                        tokio::time::sleep(Duration::from_millis(100)).await;
-                        continue;
+                            text
+                        },
+                        text_json: serde_json::to_string(&window_result.text_json)
+                            .unwrap_or_default(),
+                        app_name: window_result.app_name.clone(),
+                        window_name: window_result.window_name.clone(),
+                        ocr_engine: Arc::clone(&ocr_engine),
+                        focused: window_result.focused,
+                        ocr_failed: no_text,
+                        cursor: frame.cursor,
+                        window_bounds: window_result.bounds,
                     }
-                }
+                })
+                .collect();
+            pending.push((frame, windows));
+            // waits while the writer is behind
+            if pending.len() >= frame_batch_size
+                && batch_sender
+                    .send(std::mem::take(&mut pending))
+                    .await
+                    .is_err()
+            {
+                error!("frame writer of monitor {} stopped", monitor_id);
+                break;
             }
         }
-        tokio::time::sleep(Duration::from_secs_f64(1.0 / fps)).await;
+        // at a low fps the sleep alone would hold a shutdown for seconds
+        tokio::select! {
+            _ = shutdown.cancelled() => break,
+            _ = tokio::time::sleep(capture_interval(fps)) => {}
+        }
+    }
+    // awaited, so frames captured before a shutdown are not dropped with the runtime
+    if !pending.is_empty() {
+        let _ = batch_sender.send(pending).await;
     }
+    drop(batch_sender);
+    if let Err(e) = writer.await {
+        error!("frame writer of monitor {} failed: {}", monitor_id, e);
+    }
+    video_capture.stop().await;
 
     Ok(())
 }
 
+async fn flush_frames(
+    db: &Arc<DatabaseManager>,
+    thumbnails_dir: &Path,
+    pipe_cmd: Option<&Arc<PipeCmd>>,
+    pending: Vec<(Arc<CaptureResult>, Vec<FrameData>)>,
+) {
+    if pending.is_empty() {
+        return;
+    }
+    let window_counts: Vec<usize> = pending.iter().map(|(_, windows)| windows.len()).collect();
+    let (frames, windows): (Vec<_>, Vec<_>) = pending.into_iter().unzip();
+    // what the pipe cmd gets for each window, in frame id order
+    let mut pipe_inputs = pipe_cmd
+        .is_some()
+        .then(|| {
+            windows
+                .iter()
+                .flatten()
+                .map(|window| {
+                    (
+                        window.timestamp,
+                        window.text.clone(),
+                        window.app_name.clone(),
+                    )
+                })
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default()
+        .into_iter();
+    let mut frame_ids = match db
+        .bulk_insert_frames(windows.into_iter().flatten().collect())
+        .await
+    {
+        Ok(frame_ids) => frame_ids.into_iter(),
+        Err(e) => {
+            error!("Failed to insert {} frames: {}", frames.len(), e);
+            return;
+        }
+    };
+
+    for (frame, window_count) in frames.into_iter().zip(window_counts) {
+        let frame_ids: Vec<i64> = frame_ids.by_ref().take(window_count).collect();
+        let inputs: Vec<_> = pipe_inputs.by_ref().take(window_count).collect();
+        if frame_ids.is_empty() {
+            continue;
+        }
+        CAPTURE_LATENCY.record(frame.timestamp.elapsed());
+        store_frame_thumbnail(db, thumbnails_dir, pipe_cmd, frame, frame_ids, inputs).await;
+    }
+}
+
+/// The thumbnail of a stored frame, then the pipe cmd of each of its windows.
+async fn store_frame_thumbnail(
+    db: &DatabaseManager,
+    thumbnails_dir: &Path,
+    pipe_cmd: Option<&Arc<PipeCmd>>,
+    frame: Arc<CaptureResult>,
+    frame_ids: Vec<i64>,
+    inputs: Vec<(DateTime<Utc>, String, String)>,
+) {
+    let jpeg = match tokio::task::spawn_blocking(move || encode_thumbnail(&frame.image)).await {
+        Ok(Ok(jpeg)) => jpeg,
+        Ok(Err(e)) => {
+            error!("Failed to encode thumbnail: {}", e);
+            return;
+        }
+        Err(e) => {
+            error!("Thumbnail encoding task failed: {}", e);
+            return;
+        }
+    };
+    let thumbnail = match store_thumbnail(db, thumbnails_dir, &frame_ids, &jpeg).await {
+        Ok(thumbnail) => thumbnail,
+        Err(e) => {
+            error!(
+                "Failed to write thumbnail for frames {:?}: {}",
+                frame_ids, e
+            );
+            return;
+        }
+    };
+    // after the thumbnail, which is the frame_path handed to the pipe cmd
+    if let Some(pipe_cmd) = pipe_cmd {
+        for (frame_id, (timestamp, ocr_text, app_name)) in frame_ids.into_iter().zip(inputs) {
+            pipe_cmd.submit(
+                frame_id,
+                PipeCmdInput {
+                    timestamp,
+                    ocr_text,
+                    app_name,
+                    frame_path: thumbnail.clone(),
+                },
+            );
+        }
+    }
+}
+
 async fn record_audio(
     db: Arc<DatabaseManager>,
     chunk_duration: Duration,
@@ -258,8 +522,11 @@ async fn record_audio(
     audio_devices_control: Arc<SegQueue<(AudioDevice, DeviceControl)>>,
     friend_wearable_uid: Option<String>,
     audio_transcription_engine: Arc<AudioTranscriptionEngine>,
+    task_limiter: Arc<TaskLimiter>,
+    shutdown: CancellationToken,
 ) -> Result<()> {
-    let mut handles: HashMap<String, JoinHandle<()>> = HashMap::new();
+    // each device gets its own stop token, a child of shutdown so both cut its chunk short
+    let mut handles: HashMap<String, (JoinHandle<()>, CancellationToken)> = HashMap::new();
 
     loop {
         while let Some((audio_device, device_control)) = audio_devices_control.pop() {
@@ -268,10 +535,12 @@ async fn record_audio(
 
             if !device_control.is_running {
                 info!("Device control signaled stop for device {}", &audio_device);
-                if let Some(handle) = handles.remove(&device_id) {
-                    handle.abort();
+                if let Some((_, stop)) = handles.remove(&device_id) {
+                    // the thread sends what it recorded so far to whisper, then exits
+                    stop.cancel();
                     info!("Stopped thread for device {}", &audio_device);
                 }
+                audio_status::set_disconnected(&device_id, false);
                 continue;
             }
 
@@ -280,6 +549,9 @@ async fn record_audio(
             let audio_device = Arc::new(audio_device);
             let device_control = Arc::new(device_control);
 
+            let stop = shutdown.child_token();
+            let shutdown = stop.clone();
+            let span = info_span!("audio", device = %audio_device);
             let handle = tokio::spawn(async move {
                 let audio_device_clone = Arc::clone(&audio_device);
                 let device_control_clone = Arc::clone(&device_control);
@@ -289,7 +561,7 @@ async fn record_audio(
                 );
 
                 let mut iteration = 0;
-                loop {
+                'recording: loop {
                     iteration += 1;
                     debug!(
                         "Starting iteration {} for device {}",
@@ -305,13 +577,22 @@ async fn record_audio(
                         "Starting record_and_transcribe for device {} (iteration {})",
                         audio_device_clone, iteration
                     );
-                    let result = record_and_transcribe(
+                    let is_running = Arc::new(AtomicBool::new(device_control_clone.is_running));
+                    let recording = record_and_transcribe(
                         audio_device_clone,
                         chunk_duration,
                         whisper_sender,
-                        Arc::new(AtomicBool::new(device_control_clone.is_running)),
-                    )
-                    .await;
+                        Arc::clone(&is_running),
+                    );
+                    tokio::pin!(recording);
+                    let result = tokio::select! {
+                        result = &mut recording => result,
+                        _ = shutdown.cancelled() => {
+                            // cut the chunk short, what was recorded so far still goes to whisper
+                            is_running.store(false, Ordering::Relaxed);
+                            recording.await
+                        }
+                    };
                     info!(
                         "Finished record_and_transcribe for device {} (iteration {})",
                         audio_device_clone_2, iteration
@@ -325,11 +606,32 @@ async fn record_audio(
                             );
                         }
                         Err(e) => {
-                            error!(
-                                "Error in record_and_transcribe for device {} (iteration {}): {}, stopping thread",
-                                audio_device, iteration, e
+                            if audio_device_available(&audio_device).await {
+                                error!(
+                                    "Error in record_and_transcribe for device {} (iteration {}): {}, stopping thread",
+                                    audio_device, iteration, e
+                                );
+                                break;
+                            }
+                            // unplugged, wait for it to come back instead of giving up on it
+                            warn!(
+                                "audio device {} disconnected ({}), checking every {}s for it to come back",
+                                audio_device,
+                                e,
+                                RECONNECT_INTERVAL.as_secs()
                             );
-                            break;
+                            let device_id = audio_device.to_string();
+                            audio_status::set_disconnected(&device_id, true);
+                            while !audio_device_available(&audio_device).await {
+                                if tokio::time::timeout(RECONNECT_INTERVAL, shutdown.cancelled())
+                                    .await
+                                    .is_ok()
+                                {
+                                    break 'recording;
+                                }
+                            }
+                            audio_status::set_disconnected(&device_id, false);
+                            info!("audio device {} reconnected", audio_device);
                         }
                     }
 
@@ -337,15 +639,18 @@ async fn record_audio(
                         "Finished iteration {} for device {}",
                         iteration, &audio_device
                     );
+                    if shutdown.is_cancelled() {
+                        break;
+                    }
                 }
 
                 info!("Exiting audio capture thread for device: {}", &audio_device);
-            });
+            }.instrument(span));
 
-            handles.insert(device_id, handle);
+            handles.insert(device_id, (handle, stop));
         }
 
-        handles.retain(|device_id, handle| {
+        handles.retain(|device_id, (handle, _)| {
             if handle.is_finished() {
                 info!("Handle for device {} has finished", device_id);
                 false
@@ -354,26 +659,80 @@ async fn record_audio(
             }
         });
 
+        let stopping = shutdown.is_cancelled();
+        if stopping {
+            // the devices send their cut short chunks to whisper before exiting
+            for (device_id, (handle, _)) in handles.drain() {
+                if let Err(e) = handle.await {
+                    error!("audio thread for device {} failed: {:?}", device_id, e);
+                }
+                info!("Stopped thread for device {}", device_id);
+            }
+            // whisper exits once its input is disconnected and empty, closing whisper_receiver
+            drop(whisper_sender);
+            loop {
+                match whisper_receiver.try_recv() {
+                    Ok(transcription) => {
+                        store_transcription(
+                            Arc::clone(&db),
+                            transcription,
+                            friend_wearable_uid.clone(),
+                            Arc::clone(&audio_transcription_engine),
+                        )
+                        .await
+                    }
+                    Err(crossbeam::channel::TryRecvError::Empty) => {
+                        tokio::time::sleep(Duration::from_millis(100)).await
+                    }
+                    Err(crossbeam::channel::TryRecvError::Disconnected) => return Ok(()),
+                }
+            }
+        }
+
         while let Ok(transcription) = whisper_receiver.try_recv() {
-            info!(
-                "device {} received transcription {:?}",
-                transcription.input.device, transcription.transcription
-            );
-            // avoiding crashing the audio processing if one fails
-            if let Err(e) = process_audio_result(
-                &db,
+            let device = transcription.input.device.to_string();
+            let task = store_transcription(
+                Arc::clone(&db),
                 transcription,
-                friend_wearable_uid.as_deref(),
-                audio_transcription_engine.clone(),
-            )
-            .await
-            {
-                error!("Error processing audio result: {}", e);
-                // Optionally, you can add more specific error handling here
+                friend_wearable_uid.clone(),
+                Arc::clone(&audio_transcription_engine),
+            );
+            if !task_limiter.spawn(task) {
+                warn!(
+                    "task queue full ({} waiting), dropping transcription of device {}",
+                    task_limiter.queued(),
+                    device
+                );
             }
         }
 
-        tokio::time::sleep(Duration::from_millis(100)).await;
+        tokio::select! {
+            _ = shutdown.cancelled() => {}
+            _ = tokio::time::sleep(Duration::from_millis(100)) => {}
+        }
+    }
+}
+
+async fn store_transcription(
+    db: Arc<DatabaseManager>,
+    transcription: TranscriptionResult,
+    friend_wearable_uid: Option<String>,
+    audio_transcription_engine: Arc<AudioTranscriptionEngine>,
+) {
+    info!(
+        "device {} received transcription {:?}",
+        transcription.input.device, transcription.transcription
+    );
+    // avoiding crashing the audio processing if one fails
+    if let Err(e) = process_audio_result(
+        &db,
+        transcription,
+        friend_wearable_uid.as_deref(),
+        audio_transcription_engine,
+    )
+    .await
+    {
+        error!("Error processing audio result: {}", e);
     }
 }
 
@@ -399,6 +758,17 @@ async fn process_audio_result(
     );
     match db.insert_audio_chunk(&result.path).await {
         Ok(audio_chunk_id) => {
+            if let Some(bundle_id) = app_bundle_id(&result.input.device) {
+                if let Err(e) = db
+                    .set_audio_chunk_app_bundle_id(audio_chunk_id, bundle_id)
+                    .await
+                {
+                    error!(
+                        "Failed to store the app of audio chunk {}: {}",
+                        audio_chunk_id, e
+                    );
+                }
+            }
             if transcription.is_empty() {
                 return Ok(());
             }
@@ -424,6 +794,18 @@ async fn process_audio_result(
                     audio_chunk_id, result.input.device, transcription_engine
                 );
             }
+
+            if !result.segments.is_empty() {
+                if let Err(e) = db
+                    .set_audio_chunk_word_timestamps(audio_chunk_id, &result.segments)
+                    .await
+                {
+                    error!(
+                        "Failed to insert word timestamps for chunk {}: {}",
+                        audio_chunk_id, e
+                    );
+                }
+            }
         }
         Err(e) => error!(
             "Failed to insert audio chunk for device {}: {}",
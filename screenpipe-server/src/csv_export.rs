@@ -0,0 +1,152 @@
+use crate::db::FrameExportRow;
+use crate::DatabaseManager;
+use axum::body::Body;
+use chrono::{DateTime, Utc};
+use futures::StreamExt;
+use std::borrow::Cow;
+use std::sync::Arc;
+
+/// Frames read from the database per chunk of the response.
+const EXPORT_PAGE: u32 = 500;
+
+/// Columns `GET /frames/export/csv` can write, by their `columns=` name.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CsvColumn {
+    FrameId,
+    Timestamp,
+    AppName,
+    WindowTitle,
+    OcrText,
+    Focused,
+    FilePath,
+    OffsetIndex,
+}
+
+pub const DEFAULT_CSV_COLUMNS: &[CsvColumn] = &[
+    CsvColumn::Timestamp,
+    CsvColumn::AppName,
+    CsvColumn::WindowTitle,
+    CsvColumn::OcrText,
+];
+
+impl CsvColumn {
+    pub const ALL: [CsvColumn; 8] = [
+        CsvColumn::FrameId,
+        CsvColumn::Timestamp,
+        CsvColumn::AppName,
+        CsvColumn::WindowTitle,
+        CsvColumn::OcrText,
+        CsvColumn::Focused,
+        CsvColumn::FilePath,
+        CsvColumn::OffsetIndex,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            CsvColumn::FrameId => "frame_id",
+            CsvColumn::Timestamp => "timestamp",
+            CsvColumn::AppName => "app_name",
+            CsvColumn::WindowTitle => "window_title",
+            CsvColumn::OcrText => "ocr_text",
+            CsvColumn::Focused => "focused",
+            CsvColumn::FilePath => "file_path",
+            CsvColumn::OffsetIndex => "offset_index",
+        }
+    }
+
+    fn value(self, row: &FrameExportRow) -> String {
+        match self {
+            CsvColumn::FrameId => row.frame_id.to_string(),
+            CsvColumn::Timestamp => row.timestamp.to_rfc3339(),
+            CsvColumn::AppName => row.app_name.clone().unwrap_or_default(),
+            CsvColumn::WindowTitle => row.window_name.clone().unwrap_or_default(),
+            CsvColumn::OcrText => row.text.clone().unwrap_or_default(),
+            CsvColumn::Focused => row.focused.map(|f| f.to_string()).unwrap_or_default(),
+            CsvColumn::FilePath => row.file_path.clone(),
+            CsvColumn::OffsetIndex => row.offset_index.to_string(),
+        }
+    }
+}
+
+/// Reads a comma separated `columns=` list, unknown names are rejected.
+pub fn parse_csv_columns(value: &str) -> Result<Vec<CsvColumn>, String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .map(|name| {
+            CsvColumn::ALL
+                .into_iter()
+                .find(|column| column.name() == name)
+                .ok_or_else(|| {
+                    let allowed: Vec<_> = CsvColumn::ALL.iter().map(|c| c.name()).collect();
+                    format!(
+                        "unknown column '{}', expected one of {}",
+                        name,
+                        allowed.join(", ")
+                    )
+                })
+        })
+        .collect()
+}
+
+/// Quotes a field holding a separator, quote or line break, RFC 4180 style.
+pub fn escape_csv_field(value: &str) -> Cow<'_, str> {
+    if value.contains([',', '"', '\n', '\r']) {
+        Cow::Owned(format!("\"{}\"", value.replace('"', "\"\"")))
+    } else {
+        Cow::Borrowed(value)
+    }
+}
+
+pub fn csv_filename(from: DateTime<Utc>, to: DateTime<Utc>) -> String {
+    const FORMAT: &str = "%Y%m%dT%H%M%SZ";
+    format!("frames_{}_{}.csv", from.format(FORMAT), to.format(FORMAT))
+}
+
+fn csv_line<S: AsRef<str>>(fields: impl IntoIterator<Item = S>) -> String {
+    let fields: Vec<_> = fields
+        .into_iter()
+        .map(|field| escape_csv_field(field.as_ref()).into_owned())
+        .collect();
+    fields.join(",") + "\r\n"
+}
+
+pub fn csv_header(columns: &[CsvColumn]) -> String {
+    csv_line(columns.iter().map(|column| column.name()))
+}
+
+pub fn csv_rows(columns: &[CsvColumn], rows: &[FrameExportRow]) -> String {
+    rows.iter()
+        .map(|row| csv_line(columns.iter().map(|column| column.value(row))))
+        .collect()
+}
+
+/// The header then the frames a page at a time, so an export of months never sits in memory.
+pub fn stream_frames_csv(
+    db: Arc<DatabaseManager>,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+    columns: Vec<CsvColumn>,
+) -> Body {
+    let header = csv_header(&columns);
+    let pages = futures::stream::unfold(Some(0), move |after_id| {
+        let db = Arc::clone(&db);
+        let columns = columns.clone();
+        async move {
+            let after_id = after_id?;
+            match db
+                .get_frames_for_export(from, to, after_id, EXPORT_PAGE)
+                .await
+            {
+                Ok(rows) => {
+                    let last_id = rows.last()?.frame_id;
+                    let next = (rows.len() as u32 == EXPORT_PAGE).then_some(last_id);
+                    Some((Ok(csv_rows(&columns, &rows)), next))
+                }
+                Err(e) => Some((Err(std::io::Error::new(std::io::ErrorKind::Other, e)), None)),
+            }
+        }
+    });
+    let header = futures::stream::once(async move { Ok::<_, std::io::Error>(header) });
+    Body::from_stream(header.chain(pages))
+}
@@ -1,10 +1,24 @@
+use crate::content_classifier::{classify_screen_content, ScreenContentType};
 use crate::filtering::filter_texts;
+use crate::frame_diff::{diff_text, FrameDiff};
+use crate::fuzzy::{
+    match_score, substring_edit_distance, trigram_match_query, MAX_EDIT_DISTANCE,
+    MAX_FUZZY_CANDIDATES,
+};
+use crate::remote_sync::{ImportedRows, SyncBatch, SyncedFrame, SyncedTranscription};
+use crate::search_cursor::{CursorPosition, SearchCursor};
+use crate::search_rank::SearchOrder;
+use crate::subtitles::AudioTranscript;
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
+use futures::{Stream, StreamExt};
 use log::{debug, error, info, warn};
-use screenpipe_audio::{AudioDevice, DeviceType};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use screenpipe_audio::{AudioDevice, AudioFormat, DeviceType, TranscriptionSegment};
+use screenpipe_core::retry;
 use screenpipe_integrations::friend_wearable::FriendWearableDatabase;
-use screenpipe_vision::OcrEngine;
+use screenpipe_vision::{MousePosition, OcrEngine, WindowBounds};
 use serde::{Deserialize, Serialize};
 use sqlx::migrate::MigrateDatabase;
 use sqlx::Column;
@@ -13,16 +27,23 @@ use sqlx::Row;
 use sqlx::TypeInfo;
 use sqlx::ValueRef;
 use sqlx::{
-    sqlite::{SqlitePool, SqlitePoolOptions},
+    sqlite::{
+        SqliteConnectOptions, SqliteJournalMode, SqlitePool, SqlitePoolOptions, SqliteSynchronous,
+    },
     FromRow,
 };
 
+use std::collections::HashMap;
 use std::error::Error as StdError;
 use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
 use std::sync::Arc;
 use std::time::Duration;
 
+use tokio::sync::Notify;
 use tokio::time::{timeout, Duration as TokioDuration};
+use uuid::Uuid;
 #[derive(Debug)]
 pub struct DatabaseError(String);
 
@@ -82,6 +103,8 @@ struct OCRResultRaw {
     ocr_engine: String,
     window_name: String,
     tags: Option<String>,
+    #[sqlx(default)]
+    rank: f64,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -96,41 +119,548 @@ pub struct OCRResult {
     pub ocr_engine: String,
     pub window_name: String,
     pub tags: Vec<String>,
+    /// Relevance to the query and recency together, see [`SearchOrder::rank_sql`]
+    pub rank: f64,
 }
 
-#[derive(Debug, Deserialize, PartialEq, Default, Clone, Copy)]
+impl From<OCRResultRaw> for OCRResult {
+    fn from(raw: OCRResultRaw) -> Self {
+        OCRResult {
+            frame_id: raw.frame_id,
+            ocr_text: raw.ocr_text,
+            text_json: raw.text_json,
+            timestamp: raw.timestamp,
+            file_path: raw.file_path,
+            offset_index: raw.offset_index,
+            app_name: raw.app_name,
+            ocr_engine: raw.ocr_engine,
+            window_name: raw.window_name,
+            tags: raw
+                .tags
+                .map(|s| s.split(',').map(String::from).collect())
+                .unwrap_or_default(),
+            rank: raw.rank,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Default, Clone, Copy)]
 #[serde(rename_all = "lowercase")]
 pub enum ContentType {
     #[default]
     All,
     OCR, // TODO replace by vision and make this deprecated
     Audio,
+    /// OCR results classified as this kind of screen content, e.g. `content_type=code`
+    #[serde(untagged)]
+    Screen(ScreenContentType),
+}
+
+impl ContentType {
+    fn screen_content_type(&self) -> Option<ScreenContentType> {
+        match self {
+            ContentType::Screen(screen_content_type) => Some(*screen_content_type),
+            _ => None,
+        }
+    }
 }
 
 #[derive(FromRow)]
 struct AudioResultRaw {
+    transcription_id: i64,
     audio_chunk_id: i64,
     transcription: String,
     timestamp: DateTime<Utc>,
     file_path: String,
+    format: String,
     offset_index: i64,
     transcription_engine: String,
     tags: Option<String>,
     device_name: String,
     is_input_device: bool,
+    word_timestamps: Option<String>,
+    source: String,
+    speaker: Option<String>,
+    corrected: bool,
+    rank: f64,
+}
+
+/// Where a transcription comes from, stored in `audio_transcriptions.source`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TranscriptSource {
+    /// Transcribed while recording
+    #[default]
+    Automated,
+    /// Imported through `POST /import/transcript`
+    Manual,
+    /// Corrected through `PATCH /audio/chunks/:id/transcript`, the other rows of its chunk are
+    /// kept but no longer read
+    Corrected,
+}
+
+impl TranscriptSource {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TranscriptSource::Automated => "automated",
+            TranscriptSource::Manual => "manual",
+            TranscriptSource::Corrected => "corrected",
+        }
+    }
+
+    fn from_column(source: &str) -> Self {
+        match source {
+            "manual" => TranscriptSource::Manual,
+            "corrected" => TranscriptSource::Corrected,
+            _ => TranscriptSource::Automated,
+        }
+    }
+}
+
+/// A row of `transcript_corrections`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TranscriptCorrection {
+    pub id: i64,
+    pub audio_chunk_id: i64,
+    /// Transcriptions of the chunk before, joined with spaces
+    pub original_transcript: String,
+    pub corrected_transcript: String,
+    pub user_agent: Option<String>,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// A segment of a transcript made outside of screenpipe, offsets from the chunk start.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ManualTranscriptSegment {
+    pub start_ms: u64,
+    pub end_ms: u64,
+    pub text: String,
+    #[serde(default)]
+    pub speaker: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct AudioResult {
+    pub transcription_id: i64,
     pub audio_chunk_id: i64,
     pub transcription: String,
     pub timestamp: DateTime<Utc>,
     pub file_path: String,
+    pub format: String,
     pub offset_index: i64,
     pub transcription_engine: String,
     pub tags: Vec<String>,
     pub device_name: String,
     pub device_type: DeviceType,
+    pub word_timestamps: Vec<TranscriptionSegment>,
+    pub source: TranscriptSource,
+    pub speaker: Option<String>,
+    /// The transcript was corrected through `PATCH /audio/chunks/:id/transcript`
+    pub corrected: bool,
+    /// Relevance to the query and recency together, see [`SearchOrder::rank_sql`]
+    pub rank: f64,
+}
+
+impl From<AudioResultRaw> for AudioResult {
+    fn from(raw: AudioResultRaw) -> Self {
+        AudioResult {
+            transcription_id: raw.transcription_id,
+            audio_chunk_id: raw.audio_chunk_id,
+            transcription: raw.transcription,
+            timestamp: raw.timestamp,
+            file_path: raw.file_path,
+            format: raw.format,
+            offset_index: raw.offset_index,
+            transcription_engine: raw.transcription_engine,
+            tags: raw
+                .tags
+                .map(|s| s.split(',').map(String::from).collect())
+                .unwrap_or_default(),
+            device_name: raw.device_name,
+            device_type: if raw.is_input_device {
+                DeviceType::Input
+            } else {
+                DeviceType::Output
+            },
+            word_timestamps: raw
+                .word_timestamps
+                .and_then(|json| serde_json::from_str(&json).ok())
+                .unwrap_or_default(),
+            source: TranscriptSource::from_column(&raw.source),
+            speaker: raw.speaker,
+            corrected: raw.corrected,
+            rank: raw.rank,
+        }
+    }
+}
+
+#[derive(FromRow, Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ContentAggregates {
+    pub total_frames: i64,
+    pub total_audio_chunks: i64,
+    pub total_ocr_entries: i64,
+    pub total_ocr_text_chars: i64,
+}
+
+/// Bucket size of `GET /timeline`.
+#[derive(Debug, Deserialize, PartialEq, Default, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum TimelineResolution {
+    Second,
+    #[default]
+    Minute,
+    Hour,
+}
+
+impl TimelineResolution {
+    pub fn bucket_secs(&self) -> i64 {
+        match self {
+            TimelineResolution::Second => 1,
+            TimelineResolution::Minute => 60,
+            TimelineResolution::Hour => 3600,
+        }
+    }
+
+    /// `strftime` format truncating a timestamp to the start of its bucket.
+    fn strftime_format(&self) -> &'static str {
+        match self {
+            TimelineResolution::Second => "%Y-%m-%dT%H:%M:%SZ",
+            TimelineResolution::Minute => "%Y-%m-%dT%H:%M:00Z",
+            TimelineResolution::Hour => "%Y-%m-%dT%H:00:00Z",
+        }
+    }
+}
+
+/// Longest `text_snippet` of a timeline bucket, in characters.
+pub const TIMELINE_SNIPPET_CHARS: i64 = 100;
+
+/// Activity in one bucket of `GET /timeline`, buckets without frames or audio are left out.
+#[derive(FromRow, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TimelineBucket {
+    pub bucket_ts: DateTime<Utc>,
+    pub frame_count: i64,
+    pub audio_duration_sec: f64,
+    /// App seen in the most ocr results of the bucket
+    pub dominant_app: Option<String>,
+    /// Start of the longest ocr text of the bucket
+    pub text_snippet: Option<String>,
+}
+
+/// Time spent in an app, from the gaps between its focused frames.
+#[derive(FromRow, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AppScreenTime {
+    pub app_name: String,
+    pub seconds: f64,
+}
+
+/// A frame with its window and ocr text, as `GET /frames/export/csv` writes it.
+#[derive(FromRow, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FrameExportRow {
+    pub frame_id: i64,
+    pub timestamp: DateTime<Utc>,
+    pub file_path: String,
+    pub offset_index: i64,
+    pub app_name: Option<String>,
+    pub window_name: Option<String>,
+    pub text: Option<String>,
+    pub focused: Option<bool>,
+}
+
+/// A frame with the ocr text of its windows, one per line, for `GET /share/<token>`.
+#[derive(FromRow, Debug, Clone, PartialEq)]
+pub struct FrameText {
+    pub frame_id: i64,
+    pub timestamp: DateTime<Utc>,
+    pub file_path: String,
+    pub offset_index: i64,
+    pub text: String,
+}
+
+/// Columns of [`FrameText`], to be followed by a `WHERE` on `frames` and `GROUP BY frames.id`
+const FRAME_TEXT_SELECT: &str = r#"
+    SELECT
+        frames.id AS frame_id,
+        frames.timestamp,
+        video_chunks.file_path,
+        frames.offset_index,
+        COALESCE(GROUP_CONCAT(ocr_text.text, char(10)), '') AS text
+    FROM frames
+    JOIN video_chunks ON video_chunks.id = frames.video_chunk_id
+    LEFT JOIN ocr_text ON ocr_text.frame_id = frames.id
+"#;
+
+/// State of a share token, see [`DatabaseManager::find_share`] and
+/// [`DatabaseManager::claim_share`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShareClaim {
+    /// The frame it links to, the token is still valid or was just used up by the claim
+    Frame(i64),
+    Used,
+    Expired,
+}
+
+/// Chunk live frames go to, imported files get their own chunk which is never appended to.
+const LATEST_RECORDED_CHUNK: &str = "SELECT id FROM video_chunks WHERE id NOT IN (SELECT video_chunk_id FROM imports) ORDER BY id DESC LIMIT 1";
+
+/// Diffs the ocr text of `frame_id` against the previous frame of the same window and stores it
+/// in `frame_diffs`, replacing the diff it had.
+async fn store_frame_diff(
+    conn: &mut sqlx::SqliteConnection,
+    frame_id: i64,
+    app_name: &str,
+    window_name: &str,
+    text: &str,
+) -> Result<(), sqlx::Error> {
+    let previous: Option<(i64, String)> = sqlx::query_as(
+        "SELECT frame_id, text FROM ocr_text WHERE app_name = ?1 AND window_name = ?2 AND frame_id < ?3 ORDER BY frame_id DESC LIMIT 1",
+    )
+    .bind(app_name)
+    .bind(window_name)
+    .bind(frame_id)
+    .fetch_optional(&mut *conn)
+    .await?;
+    let (diff, lines_added, lines_removed) =
+        diff_text(previous.as_ref().map_or("", |(_, text)| text), text);
+    sqlx::query(
+        "INSERT OR REPLACE INTO frame_diffs (frame_id, previous_frame_id, diff, lines_added, lines_removed) VALUES (?1, ?2, ?3, ?4, ?5)",
+    )
+    .bind(frame_id)
+    .bind(previous.map(|(id, _)| id))
+    .bind(diff)
+    .bind(lines_added)
+    .bind(lines_removed)
+    .execute(&mut *conn)
+    .await?;
+    Ok(())
+}
+
+/// Transcription filter on `?1`: the trigram index of `audio_fts` for queries it can match (3
+/// characters and up, quoted as a phrase), a substring scan for shorter ones.
+const AUDIO_TEXT_MATCH: &str = r#"(?1 = ''
+            OR (LENGTH(?1) < 3 AND audio_transcriptions.transcription LIKE '%' || ?1 || '%' COLLATE NOCASE)
+            OR (LENGTH(?1) >= 3 AND audio_transcriptions.id IN (
+                SELECT rowid FROM audio_fts WHERE audio_fts MATCH '"' || REPLACE(?1, '"', '""') || '"'
+            )))"#;
+
+/// The transcription row is read, those of a corrected chunk give way to its correction.
+const CURRENT_TRANSCRIPTION: &str = r#"(audio_transcriptions.source = 'corrected' OR NOT EXISTS (
+            SELECT 1 FROM audio_transcriptions AS correction
+            WHERE correction.audio_chunk_id = audio_transcriptions.audio_chunk_id
+                AND correction.source = 'corrected'
+            ))"#;
+
+/// Rows `search_stream` reads ahead of its consumer.
+pub const SEARCH_STREAM_BUFFER: usize = 64;
+
+/// Ocr search bound as in `search_ocr`, `?8` is the limit, -1 for none.
+fn ocr_search_sql(order: &SearchOrder) -> String {
+    let rank = order.rank_sql("ocr_match.bm25", "frames.timestamp");
+    let mut sql = format!(
+        r#"
+        SELECT 
+            ocr_text.frame_id,
+            ocr_text.text as ocr_text,
+            ocr_text.text_json,
+            frames.timestamp,
+            video_chunks.file_path,
+            frames.offset_index,
+            ocr_text.app_name,
+            ocr_text.ocr_engine,
+            ocr_text.window_name,
+            GROUP_CONCAT(tags.name, ',') as tags,
+            {rank} as rank
+        FROM 
+            ocr_text
+        JOIN 
+            frames ON ocr_text.frame_id = frames.id
+        JOIN 
+            video_chunks ON frames.video_chunk_id = video_chunks.id
+        LEFT JOIN (
+            SELECT rowid, bm25(ocr_text_fts) AS bm25 FROM ocr_text_fts
+            WHERE ocr_text_fts MATCH '"' || REPLACE(?1, '"', '""') || '"'
+        ) AS ocr_match ON ocr_match.rowid = ocr_text.frame_id
+        LEFT JOIN
+            vision_tags ON frames.id = vision_tags.vision_id
+        LEFT JOIN
+            tags ON vision_tags.tag_id = tags.id
+        WHERE 
+            (?1 = '' OR ocr_text.text LIKE '%' || ?1 || '%' COLLATE NOCASE)
+            AND ocr_text.text != 'No text found'
+            AND (?2 IS NULL OR frames.timestamp >= ?2)
+            AND (?3 IS NULL OR frames.timestamp <= ?3)
+            AND (?4 IS NULL OR LENGTH(ocr_text.text) >= ?4)
+            AND (?5 IS NULL OR LENGTH(ocr_text.text) <= ?5)
+            AND (?6 IS NULL OR ocr_text.app_name LIKE '%' || ?6 || '%' COLLATE NOCASE)
+            AND (?7 IS NULL OR ocr_text.window_name LIKE '%' || ?7 || '%' COLLATE NOCASE)
+            AND (?10 IS NULL OR frames.session_id = ?10)
+            AND (?11 IS NULL OR (frames.timestamp, ocr_text.frame_id) < (?11, ?12))
+            AND (?13 IS NULL OR ocr_text.content_type = ?13)
+    "#,
+    );
+
+    sql.push_str(&format!(
+        r#"
+        GROUP BY 
+            ocr_text.frame_id
+        ORDER BY 
+            {}
+        LIMIT ?8 OFFSET ?9
+        "#,
+        order.order_by_sql("ocr_text.app_name", "frames.timestamp", "ocr_text.frame_id")
+    ));
+    sql
+}
+
+/// Transcription search bound as in `search_audio`, `?6` is the limit, -1 for none.
+fn audio_search_sql(order: &SearchOrder) -> String {
+    let rank = order.rank_sql("audio_match.bm25", "audio_transcriptions.timestamp");
+    let mut sql = format!(
+        r#"
+    SELECT 
+        audio_transcriptions.id as transcription_id,
+        audio_transcriptions.audio_chunk_id,
+        audio_transcriptions.transcription,
+        audio_transcriptions.timestamp,
+        audio_chunks.file_path,
+        audio_chunks.format,
+        audio_transcriptions.offset_index,
+        audio_transcriptions.transcription_engine,
+        GROUP_CONCAT(tags.name, ',') as tags,
+        audio_transcriptions.device as device_name,
+        audio_transcriptions.is_input_device,
+        CASE WHEN audio_transcriptions.source = 'corrected' THEN NULL
+            ELSE audio_chunks.word_timestamps END AS word_timestamps,
+        audio_transcriptions.source,
+        audio_transcriptions.speaker,
+        EXISTS (
+            SELECT 1 FROM transcript_corrections
+            WHERE transcript_corrections.audio_chunk_id = audio_transcriptions.audio_chunk_id
+        ) as corrected,
+        {rank} as rank
+    FROM 
+        audio_transcriptions
+    JOIN 
+        audio_chunks ON audio_transcriptions.audio_chunk_id = audio_chunks.id
+    LEFT JOIN (
+        SELECT rowid, bm25(audio_fts) AS bm25 FROM audio_fts
+        WHERE audio_fts MATCH '"' || REPLACE(?1, '"', '""') || '"'
+    ) AS audio_match ON audio_match.rowid = audio_transcriptions.id
+    LEFT JOIN
+        audio_tags ON audio_chunks.id = audio_tags.audio_chunk_id
+    LEFT JOIN
+        tags ON audio_tags.tag_id = tags.id
+    WHERE 
+        {AUDIO_TEXT_MATCH}
+        AND {CURRENT_TRANSCRIPTION}
+        AND (?2 IS NULL OR audio_transcriptions.timestamp >= ?2)
+        AND (?3 IS NULL OR audio_transcriptions.timestamp <= ?3)
+        AND (?4 IS NULL OR LENGTH(audio_transcriptions.transcription) >= ?4)
+        AND (?5 IS NULL OR LENGTH(audio_transcriptions.transcription) <= ?5)
+        AND (?8 IS NULL OR audio_chunks.session_id = ?8)
+        AND (?9 IS NULL OR (audio_transcriptions.timestamp, audio_transcriptions.id) < (?9, ?10))
+    "#,
+    );
+
+    // transcriptions have no app, they sort as an empty name
+    sql.push_str(&format!(
+        r#"
+    GROUP BY
+        audio_transcriptions.id,
+        audio_transcriptions.audio_chunk_id,
+        audio_transcriptions.transcription,
+        audio_transcriptions.timestamp,
+        audio_transcriptions.offset_index
+    ORDER BY 
+        {}
+    LIMIT ?6 OFFSET ?7
+    "#,
+        order.order_by_sql(
+            "''",
+            "audio_transcriptions.timestamp",
+            "audio_transcriptions.id"
+        )
+    ));
+    sql
+}
+
+/// How far `screenpipe import` got with a file, offsets are milliseconds into it.
+#[derive(FromRow, Debug, Clone, PartialEq)]
+pub struct ImportState {
+    pub video_chunk_id: i64,
+    pub start_time: DateTime<Utc>,
+    pub last_frame_ms: Option<i64>,
+    pub last_audio_ms: Option<i64>,
+}
+
+/// Rows removed by `delete_frames` / `delete_audio`, files are left to the caller.
+#[derive(Debug, Default)]
+pub struct DeletedContent {
+    pub ids: Vec<i64>,
+    pub file_paths: Vec<String>,
+}
+
+#[derive(FromRow, Debug, Clone, Serialize, Deserialize)]
+pub struct RequestLogEntry {
+    pub path: String,
+    pub method: String,
+    pub status: u16,
+    pub duration_ms: f64,
+    pub timestamp: DateTime<Utc>,
+}
+
+#[derive(FromRow, Debug, Clone, Serialize, Deserialize)]
+pub struct Session {
+    pub id: String,
+    pub name: Option<String>,
+    pub start_time: DateTime<Utc>,
+    pub end_time: Option<DateTime<Utc>>,
+}
+
+/// One window of a captured frame, written by [`DatabaseManager::bulk_insert_frames`].
+#[derive(Debug, Clone)]
+pub struct FrameData {
+    pub timestamp: DateTime<Utc>,
+    pub text: String,
+    pub text_json: String,
+    pub app_name: String,
+    pub window_name: String,
+    pub ocr_engine: Arc<OcrEngine>,
+    pub focused: bool,
+    /// Ocr failed or was skipped, the frame is stored without an `ocr_text` row and its text
+    /// reads as NULL
+    pub ocr_failed: bool,
+    pub cursor: Option<MousePosition>,
+    /// Part of the monitor frame the window covered, `None` when the frame is all of it
+    pub window_bounds: Option<WindowBounds>,
+}
+
+/// Window a frame was recorded of, see [`DatabaseManager::get_frame_window`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct FrameWindow {
+    pub app_name: String,
+    pub window_name: String,
+    pub focused: bool,
+    pub bounds: Option<WindowBounds>,
+}
+
+/// Ocr text of a frame replaced by [`DatabaseManager::replace_ocr_text`].
+#[derive(FromRow, Debug, Clone, Serialize, Deserialize)]
+pub struct OcrHistoryEntry {
+    pub text: String,
+    pub text_json: Option<String>,
+    pub ocr_engine: String,
+    pub confidence: Option<f64>,
+    pub replaced_at: DateTime<Utc>,
+}
+
+#[derive(FromRow, Debug, Clone, Serialize, Deserialize)]
+pub struct FrameInfo {
+    pub frame_id: i64,
+    pub timestamp: DateTime<Utc>,
+    pub file_path: String,
+    pub offset_index: i64,
+    pub cursor_x: Option<i64>,
+    pub cursor_y: Option<i64>,
 }
 
 #[derive(Debug, Deserialize, PartialEq)]
@@ -142,13 +672,51 @@ pub enum TagContentType {
 
 pub struct DatabaseManager {
     pub pool: SqlitePool,
+    /// Notified after frames or transcriptions are committed, see [`crate::RemoteSync`]
+    pub new_rows: Arc<Notify>,
+    read_only: bool,
+}
+
+pub const DEFAULT_DB_POOL_SIZE: u32 = 4;
+
+const DB_PROBE_ATTEMPTS: u32 = 10;
+const DB_PROBE_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Takes the write lock and lets it go until sqlite grants it, so migrations don't start on a
+/// database another instance is writing to. A `SELECT` succeeds on a locked database.
+async fn wait_for_write_lock(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    retry(DB_PROBE_ATTEMPTS, DB_PROBE_INTERVAL, |_| async {
+        let mut connection = pool.acquire().await?;
+        sqlx::query("BEGIN IMMEDIATE")
+            .execute(&mut *connection)
+            .await?;
+        sqlx::query("ROLLBACK").execute(&mut *connection).await?;
+        Ok::<_, sqlx::Error>(())
+    })
+    .await
+    .map_err(|e| {
+        sqlx::Error::Io(std::io::Error::new(
+            std::io::ErrorKind::Other,
+            format!(
+                "Database locked — is another screenpipe instance running? ({})",
+                e
+            ),
+        ))
+    })
 }
 
 impl DatabaseManager {
     pub async fn new(database_path: &str) -> Result<Self, sqlx::Error> {
+        Self::new_with_pool_size(database_path, DEFAULT_DB_POOL_SIZE).await
+    }
+
+    pub async fn new_with_pool_size(
+        database_path: &str,
+        pool_size: u32,
+    ) -> Result<Self, sqlx::Error> {
         debug!(
-            "Initializing DatabaseManager with database path: {}",
-            database_path
+            "Initializing DatabaseManager with database path: {}, pool size: {}",
+            database_path, pool_size
         );
         let connection_string = format!("sqlite:{}", database_path);
 
@@ -157,29 +725,29 @@ impl DatabaseManager {
             sqlx::Sqlite::create_database(&connection_string).await?;
         }
 
+        // Pragmas are set on every pooled connection, not just the first one
+        // cache_size = -2000 -> 2MB cache, temp_store = MEMORY -> temporary tables and indices in memory
+        let connect_options = SqliteConnectOptions::from_str(&connection_string)?
+            .journal_mode(SqliteJournalMode::Wal)
+            .synchronous(SqliteSynchronous::Normal)
+            .pragma("cache_size", "-2000")
+            .pragma("temp_store", "MEMORY");
+
+        let pool_size = pool_size.max(1);
         let pool = SqlitePoolOptions::new()
-            .max_connections(10)
-            .min_connections(3) // Minimum number of idle connections
+            .max_connections(pool_size)
+            .min_connections(pool_size.min(3)) // Minimum number of idle connections
             .acquire_timeout(Duration::from_secs(10))
-            .connect(&connection_string)
-            .await?;
-
-        // Enable WAL mode
-        sqlx::query("PRAGMA journal_mode = WAL;")
-            .execute(&pool)
+            .connect_with(connect_options)
             .await?;
 
-        // Enable SQLite's query result caching
-        // PRAGMA cache_size = -2000; -- Set cache size to 2MB
-        // PRAGMA temp_store = MEMORY; -- Store temporary tables and indices in memory
-        sqlx::query("PRAGMA cache_size = -2000;")
-            .execute(&pool)
-            .await?;
-        sqlx::query("PRAGMA temp_store = MEMORY;")
-            .execute(&pool)
-            .await?;
+        let db_manager = DatabaseManager {
+            pool,
+            new_rows: Arc::new(Notify::new()),
+            read_only: false,
+        };
 
-        let db_manager = DatabaseManager { pool };
+        wait_for_write_lock(&db_manager.pool).await?;
 
         // Run migrations after establishing the connection
         if let Err(e) = Self::run_migrations(&db_manager.pool).await {
@@ -191,6 +759,32 @@ impl DatabaseManager {
         Ok(db_manager)
     }
 
+    /// Separate pool on an existing database whose connections refuse writes
+    /// (`PRAGMA query_only`), so heavy reads don't compete with recording for connections.
+    pub async fn new_read_only(database_path: &str, pool_size: u32) -> Result<Self, sqlx::Error> {
+        let connect_options = SqliteConnectOptions::from_str(&format!("sqlite:{}", database_path))?
+            .pragma("cache_size", "-2000")
+            .pragma("temp_store", "MEMORY")
+            .pragma("query_only", "1");
+        let pool_size = pool_size.max(1);
+        let pool = SqlitePoolOptions::new()
+            .max_connections(pool_size)
+            .min_connections(pool_size.min(3))
+            .acquire_timeout(Duration::from_secs(10))
+            .connect_with(connect_options)
+            .await?;
+        Ok(DatabaseManager {
+            pool,
+            new_rows: Arc::new(Notify::new()),
+            read_only: true,
+        })
+    }
+
+    /// Opened with [`DatabaseManager::new_read_only`], writes fail on this pool.
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
     async fn run_migrations(pool: &SqlitePool) -> Result<(), sqlx::Error> {
         sqlx::migrate!("./src/migrations").run(pool).await?;
         Ok(())
@@ -198,16 +792,47 @@ impl DatabaseManager {
 
     pub async fn insert_audio_chunk(&self, file_path: &str) -> Result<i64, sqlx::Error> {
         let mut tx = self.pool.begin().await?;
-        let id = sqlx::query("INSERT INTO audio_chunks (file_path, timestamp) VALUES (?1, ?2)")
-            .bind(file_path)
-            .bind(Utc::now())
-            .execute(&mut *tx)
-            .await?
-            .last_insert_rowid();
+        let format = AudioFormat::from_path(Path::new(file_path)).unwrap_or_default();
+        let id = sqlx::query(
+            "INSERT INTO audio_chunks (file_path, timestamp, format, session_id) VALUES (?1, ?2, ?3, (SELECT id FROM sessions WHERE end_time IS NULL ORDER BY start_time DESC LIMIT 1))",
+        )
+        .bind(file_path)
+        .bind(Utc::now())
+        .bind(format.to_string())
+        .execute(&mut *tx)
+        .await?
+        .last_insert_rowid();
         tx.commit().await?;
         Ok(id)
     }
 
+    pub async fn set_audio_chunk_word_timestamps(
+        &self,
+        audio_chunk_id: i64,
+        word_timestamps: &[TranscriptionSegment],
+    ) -> Result<(), sqlx::Error> {
+        let json = serde_json::to_string(word_timestamps).unwrap_or_default();
+        sqlx::query("UPDATE audio_chunks SET word_timestamps = ?1 WHERE id = ?2")
+            .bind(json)
+            .bind(audio_chunk_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn set_audio_chunk_app_bundle_id(
+        &self,
+        audio_chunk_id: i64,
+        app_bundle_id: &str,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query("UPDATE audio_chunks SET app_bundle_id = ?1 WHERE id = ?2")
+            .bind(app_bundle_id)
+            .bind(audio_chunk_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
     pub async fn insert_audio_transcription(
         &self,
         audio_chunk_id: i64,
@@ -235,82 +860,738 @@ impl DatabaseManager {
 
         // Commit the transaction for the full transcription
         tx.commit().await?;
+        self.new_rows.notify_one();
 
         Ok(id)
     }
 
-    pub async fn insert_video_chunk(&self, file_path: &str) -> Result<i64, sqlx::Error> {
+    /// Replaces the transcriptions of an audio chunk by `segments`, one row each timestamped from
+    /// the start of the chunk, and its word timestamps by their offsets. `None` when there is no
+    /// such chunk.
+    pub async fn replace_audio_transcript(
+        &self,
+        audio_chunk_id: i64,
+        segments: &[ManualTranscriptSegment],
+    ) -> Result<Option<usize>, sqlx::Error> {
         let mut tx = self.pool.begin().await?;
-        let id = sqlx::query("INSERT INTO video_chunks (file_path) VALUES (?1)")
-            .bind(file_path)
+        let chunk_start: Option<DateTime<Utc>> =
+            sqlx::query_scalar("SELECT timestamp FROM audio_chunks WHERE id = ?1")
+                .bind(audio_chunk_id)
+                .fetch_optional(&mut *tx)
+                .await?;
+        let Some(chunk_start) = chunk_start else {
+            return Ok(None);
+        };
+        // the imported rows keep the device of the transcriptions they replace
+        let (device, is_input_device): (String, bool) = sqlx::query_as(
+            "SELECT device, is_input_device FROM audio_transcriptions WHERE audio_chunk_id = ?1 LIMIT 1",
+        )
+        .bind(audio_chunk_id)
+        .fetch_optional(&mut *tx)
+        .await?
+        .unwrap_or_else(|| (String::new(), true));
+
+        sqlx::query("DELETE FROM audio_transcriptions WHERE audio_chunk_id = ?1")
+            .bind(audio_chunk_id)
             .execute(&mut *tx)
-            .await?
-            .last_insert_rowid();
+            .await?;
+        for (index, segment) in segments.iter().enumerate() {
+            sqlx::query(
+                "INSERT INTO audio_transcriptions (audio_chunk_id, transcription, offset_index, timestamp, transcription_engine, device, is_input_device, source, speaker) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            )
+            .bind(audio_chunk_id)
+            .bind(&segment.text)
+            .bind(index as i64)
+            .bind(chunk_start + chrono::Duration::milliseconds(segment.start_ms as i64))
+            .bind(TranscriptSource::Manual.as_str())
+            .bind(&device)
+            .bind(is_input_device)
+            .bind(TranscriptSource::Manual.as_str())
+            .bind(&segment.speaker)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        let word_timestamps: Vec<_> = segments
+            .iter()
+            .map(|segment| TranscriptionSegment {
+                text: segment.text.clone(),
+                start_ms: segment.start_ms,
+                end_ms: segment.end_ms,
+            })
+            .collect();
+        sqlx::query("UPDATE audio_chunks SET word_timestamps = ?1 WHERE id = ?2")
+            .bind(serde_json::to_string(&word_timestamps).unwrap_or_default())
+            .bind(audio_chunk_id)
+            .execute(&mut *tx)
+            .await?;
         tx.commit().await?;
-        Ok(id)
+        self.new_rows.notify_one();
+        Ok(Some(segments.len()))
     }
 
-    pub async fn insert_frame(&self) -> Result<i64, sqlx::Error> {
+    /// Corrects the transcript of an audio chunk to `transcript`, stored in a `corrected` row
+    /// that search and summaries read instead of the other rows of the chunk, and records the
+    /// correction. The transcribed rows and the word timestamps of the chunk are kept, the
+    /// corrected row has no word timestamps. `None` when the chunk has no transcript.
+    pub async fn correct_audio_transcript(
+        &self,
+        audio_chunk_id: i64,
+        transcript: &str,
+        user_agent: Option<&str>,
+    ) -> Result<Option<TranscriptCorrection>, sqlx::Error> {
         let mut tx = self.pool.begin().await?;
-        debug!("insert_frame Transaction started");
-
-        // Get the most recent video_chunk_id
-        let video_chunk_id: Option<i64> =
-            sqlx::query_scalar("SELECT id FROM video_chunks ORDER BY id DESC LIMIT 1")
-                .fetch_optional(&mut *tx)
-                .await?;
-        debug!("Fetched most recent video_chunk_id: {:?}", video_chunk_id);
+        let rows: Vec<(i64, String, String)> = sqlx::query_as(
+            "SELECT id, transcription, source FROM audio_transcriptions WHERE audio_chunk_id = ?1 ORDER BY offset_index, id",
+        )
+        .bind(audio_chunk_id)
+        .fetch_all(&mut *tx)
+        .await?;
+        if rows.is_empty() {
+            return Ok(None);
+        }
+        let corrected = TranscriptSource::Corrected.as_str();
+        let correction = rows.iter().find(|(_, _, source)| source == corrected);
+        let original_transcript = match correction {
+            Some((_, transcription, _)) => transcription.clone(),
+            None => rows
+                .iter()
+                .map(|(_, transcription, _)| transcription.trim())
+                .collect::<Vec<_>>()
+                .join(" "),
+        };
 
-        // If no video chunk is found, return 0
-        let video_chunk_id = match video_chunk_id {
-            Some(id) => id,
+        // the fts triggers index the corrected row, the other rows stay indexed but are no
+        // longer read
+        match correction {
+            Some((id, _, _)) => {
+                sqlx::query("UPDATE audio_transcriptions SET transcription = ?1 WHERE id = ?2")
+                    .bind(transcript)
+                    .bind(id)
+                    .execute(&mut *tx)
+                    .await?;
+            }
             None => {
-                debug!("No video chunk found, rolling back transaction");
-                tx.rollback().await?;
-                return Ok(0);
+                sqlx::query(
+                    r#"
+                    INSERT INTO audio_transcriptions (audio_chunk_id, transcription, offset_index, timestamp, transcription_engine, device, is_input_device, source)
+                    SELECT audio_chunk_id, ?2, offset_index, timestamp, ?3, device, is_input_device, ?3
+                    FROM audio_transcriptions
+                    WHERE id = ?1
+                    "#,
+                )
+                .bind(rows[0].0)
+                .bind(transcript)
+                .bind(corrected)
+                .execute(&mut *tx)
+                .await?;
             }
-        };
-
-        // Calculate the offset_index
-        let offset_index: i64 = sqlx::query_scalar(
-            "SELECT COALESCE(MAX(offset_index), -1) + 1 FROM frames WHERE video_chunk_id = ?1",
-        )
-        .bind(video_chunk_id)
-        .fetch_one(&mut *tx)
-        .await?;
-        debug!("insert_frame Calculated offset_index: {}", offset_index);
+        }
 
-        // Insert the new frame
+        let corrected_at = Utc::now();
         let id = sqlx::query(
-            "INSERT INTO frames (video_chunk_id, offset_index, timestamp) VALUES (?1, ?2, ?3)",
+            "INSERT INTO transcript_corrections (audio_chunk_id, original_transcript, corrected_transcript, user_agent, timestamp) VALUES (?1, ?2, ?3, ?4, ?5)",
         )
-        .bind(video_chunk_id)
-        .bind(offset_index)
-        .bind(Utc::now())
+        .bind(audio_chunk_id)
+        .bind(&original_transcript)
+        .bind(transcript)
+        .bind(user_agent)
+        .bind(corrected_at)
         .execute(&mut *tx)
         .await?
         .last_insert_rowid();
-        debug!("insert_frame Inserted new frame with id: {}", id);
-
-        // Commit the transaction
         tx.commit().await?;
-        // debug!("insert_frame Transaction committed");
+        Ok(Some(TranscriptCorrection {
+            id,
+            audio_chunk_id,
+            original_transcript,
+            corrected_transcript: transcript.to_string(),
+            user_agent: user_agent.map(String::from),
+            timestamp: corrected_at,
+        }))
+    }
 
-        Ok(id)
+    /// Last frame and transcription ids pushed to `url`.
+    pub async fn get_remote_sync_state(&self, url: &str) -> Result<(i64, i64), sqlx::Error> {
+        let state = sqlx::query_as(
+            "SELECT last_frame_id, last_transcription_id FROM remote_sync_state WHERE url = ?1",
+        )
+        .bind(url)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(state.unwrap_or((0, 0)))
     }
 
-    pub async fn insert_ocr_text(
+    pub async fn set_remote_sync_state(
         &self,
-        frame_id: i64,
-        text: &str,
-        text_json: &str,
-        app_name: &str,
-        window_name: &str,
-        ocr_engine: Arc<OcrEngine>,
-        focused: bool,
+        url: &str,
+        last_frame_id: i64,
+        last_transcription_id: i64,
     ) -> Result<(), sqlx::Error> {
-        const MAX_RETRIES: u32 = 3;
-        const TIMEOUT_DURATION: TokioDuration = TokioDuration::from_secs(10);
+        sqlx::query(
+            r#"
+            INSERT INTO remote_sync_state (url, last_frame_id, last_transcription_id)
+            VALUES (?1, ?2, ?3)
+            ON CONFLICT(url) DO UPDATE SET
+                last_frame_id = excluded.last_frame_id,
+                last_transcription_id = excluded.last_transcription_id
+            "#,
+        )
+        .bind(url)
+        .bind(last_frame_id)
+        .bind(last_transcription_id)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn get_frames_to_sync(
+        &self,
+        after_id: i64,
+        limit: u32,
+    ) -> Result<Vec<SyncedFrame>, sqlx::Error> {
+        sqlx::query_as(
+            r#"
+            SELECT
+                frames.id,
+                frames.timestamp,
+                ocr_text.text,
+                COALESCE(ocr_text.text_json, '') AS text_json,
+                COALESCE(ocr_text.app_name, '') AS app_name,
+                COALESCE(ocr_text.window_name, '') AS window_name,
+                COALESCE(ocr_text.ocr_engine, '') AS ocr_engine,
+                COALESCE(ocr_text.focused, FALSE) AS focused
+            FROM frames
+            LEFT JOIN ocr_text ON ocr_text.frame_id = frames.id
+            WHERE frames.id > ?1
+            ORDER BY frames.id
+            LIMIT ?2
+            "#,
+        )
+        .bind(after_id)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    pub async fn get_transcriptions_to_sync(
+        &self,
+        after_id: i64,
+        limit: u32,
+    ) -> Result<Vec<SyncedTranscription>, sqlx::Error> {
+        sqlx::query_as(
+            r#"
+            SELECT id, timestamp, transcription, transcription_engine, device, is_input_device
+            FROM audio_transcriptions
+            WHERE id > ?1
+            ORDER BY id
+            LIMIT ?2
+            "#,
+        )
+        .bind(after_id)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    /// Stores the rows pushed by another instance under placeholder `remote://<source>` chunks,
+    /// skipping those of an earlier batch.
+    pub async fn import_sync_batch(&self, batch: &SyncBatch) -> Result<ImportedRows, sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+        let source: Option<(i64, i64, i64, i64)> = sqlx::query_as(
+            "SELECT video_chunk_id, audio_chunk_id, last_frame_id, last_transcription_id FROM remote_sync_sources WHERE source = ?1",
+        )
+        .bind(&batch.source)
+        .fetch_optional(&mut *tx)
+        .await?;
+        let (video_chunk_id, audio_chunk_id, last_frame_id, last_transcription_id) = match source {
+            Some(source) => source,
+            None => {
+                let file_path = format!("remote://{}", batch.source);
+                let video_chunk_id =
+                    sqlx::query("INSERT INTO video_chunks (file_path) VALUES (?1)")
+                        .bind(&file_path)
+                        .execute(&mut *tx)
+                        .await?
+                        .last_insert_rowid();
+                let audio_chunk_id =
+                    sqlx::query("INSERT INTO audio_chunks (file_path, timestamp) VALUES (?1, ?2)")
+                        .bind(&file_path)
+                        .bind(Utc::now())
+                        .execute(&mut *tx)
+                        .await?
+                        .last_insert_rowid();
+                sqlx::query(
+                    "INSERT INTO remote_sync_sources (source, video_chunk_id, audio_chunk_id) VALUES (?1, ?2, ?3)",
+                )
+                .bind(&batch.source)
+                .bind(video_chunk_id)
+                .bind(audio_chunk_id)
+                .execute(&mut *tx)
+                .await?;
+                (video_chunk_id, audio_chunk_id, 0, 0)
+            }
+        };
+
+        // the remote ids keep the rows of a source in recording order
+        let frames: Vec<_> = batch
+            .frames
+            .iter()
+            .filter(|f| f.id > last_frame_id)
+            .collect();
+        for frame in &frames {
+            let id = sqlx::query(
+                "INSERT INTO frames (video_chunk_id, offset_index, timestamp) VALUES (?1, ?2, ?3)",
+            )
+            .bind(video_chunk_id)
+            .bind(frame.id)
+            .bind(frame.timestamp)
+            .execute(&mut *tx)
+            .await?
+            .last_insert_rowid();
+            let Some(text) = &frame.text else {
+                continue;
+            };
+            let content_type = classify_screen_content(text, &frame.app_name, &frame.window_name);
+            sqlx::query("INSERT INTO ocr_text (frame_id, text, text_json, app_name, ocr_engine, window_name, focused, content_type) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)")
+                .bind(id)
+                .bind(text)
+                .bind(&frame.text_json)
+                .bind(&frame.app_name)
+                .bind(&frame.ocr_engine)
+                .bind(&frame.window_name)
+                .bind(frame.focused)
+                .bind(content_type.map(|c| c.as_str()))
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        let transcriptions: Vec<_> = batch
+            .transcriptions
+            .iter()
+            .filter(|t| t.id > last_transcription_id)
+            .collect();
+        for transcription in &transcriptions {
+            sqlx::query(
+                "INSERT INTO audio_transcriptions (audio_chunk_id, transcription, offset_index, timestamp, transcription_engine, device, is_input_device) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            )
+            .bind(audio_chunk_id)
+            .bind(&transcription.transcription)
+            .bind(transcription.id)
+            .bind(transcription.timestamp)
+            .bind(&transcription.transcription_engine)
+            .bind(&transcription.device)
+            .bind(transcription.is_input_device)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        sqlx::query(
+            "UPDATE remote_sync_sources SET last_frame_id = ?2, last_transcription_id = ?3 WHERE source = ?1",
+        )
+        .bind(&batch.source)
+        .bind(frames.last().map_or(last_frame_id, |f| f.id))
+        .bind(transcriptions.last().map_or(last_transcription_id, |t| t.id))
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+        Ok(ImportedRows {
+            frames: frames.len(),
+            transcriptions: transcriptions.len(),
+        })
+    }
+
+    pub async fn insert_video_chunk(&self, file_path: &str) -> Result<i64, sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+        let id = sqlx::query("INSERT INTO video_chunks (file_path) VALUES (?1)")
+            .bind(file_path)
+            .execute(&mut *tx)
+            .await?
+            .last_insert_rowid();
+        tx.commit().await?;
+        Ok(id)
+    }
+
+    /// Points the chunk recorded at `old_path` and its frames to the file's new place.
+    pub async fn update_video_chunk_path(
+        &self,
+        old_path: &str,
+        new_path: &str,
+    ) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query("UPDATE video_chunks SET file_path = ?2 WHERE file_path = ?1")
+            .bind(old_path)
+            .bind(new_path)
+            .execute(&self.pool)
+            .await?;
+        Ok(result.rows_affected())
+    }
+
+    /// Writes a row and reads it back in a transaction that is rolled back, for
+    /// `GET /health/deep`.
+    pub async fn check_read_write(&self) -> Result<(), sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+        let id = sqlx::query("INSERT INTO video_chunks (file_path) VALUES ('health-check')")
+            .execute(&mut *tx)
+            .await?
+            .last_insert_rowid();
+        let file_path: String =
+            sqlx::query_scalar("SELECT file_path FROM video_chunks WHERE id = ?1")
+                .bind(id)
+                .fetch_one(&mut *tx)
+                .await?;
+        tx.rollback().await?;
+        if file_path != "health-check" {
+            return Err(sqlx::Error::RowNotFound);
+        }
+        Ok(())
+    }
+
+    /// Reads a row for `GET /health/deep` on a read-only pool, which can't run
+    /// [`DatabaseManager::check_read_write`].
+    pub async fn check_read(&self) -> Result<(), sqlx::Error> {
+        sqlx::query("SELECT id FROM video_chunks LIMIT 1")
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn get_import(&self, file_path: &str) -> Result<Option<ImportState>, sqlx::Error> {
+        sqlx::query_as(
+            "SELECT video_chunk_id, start_time, last_frame_ms, last_audio_ms FROM imports WHERE file_path = ?1",
+        )
+        .bind(file_path)
+        .fetch_optional(&self.pool)
+        .await
+    }
+
+    /// Starts importing `file_path`, which becomes the video chunk of its frames.
+    pub async fn insert_import(
+        &self,
+        file_path: &str,
+        start_time: DateTime<Utc>,
+    ) -> Result<ImportState, sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+        let video_chunk_id = sqlx::query("INSERT INTO video_chunks (file_path) VALUES (?1)")
+            .bind(file_path)
+            .execute(&mut *tx)
+            .await?
+            .last_insert_rowid();
+        sqlx::query(
+            "INSERT INTO imports (file_path, video_chunk_id, start_time) VALUES (?1, ?2, ?3)",
+        )
+        .bind(file_path)
+        .bind(video_chunk_id)
+        .bind(start_time)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+        Ok(ImportState {
+            video_chunk_id,
+            start_time,
+            last_frame_ms: None,
+            last_audio_ms: None,
+        })
+    }
+
+    /// Inserts a frame of an imported file with its ocr text and records it as imported, in one
+    /// transaction so a resumed import neither skips nor repeats it.
+    pub async fn insert_imported_frame(
+        &self,
+        file_path: &str,
+        offset_ms: i64,
+        frame: &FrameData,
+    ) -> Result<i64, sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+        let id = sqlx::query(
+            r#"
+            INSERT INTO frames (video_chunk_id, offset_index, timestamp)
+            SELECT video_chunk_id, ?2, ?3 FROM imports WHERE file_path = ?1
+            "#,
+        )
+        .bind(file_path)
+        .bind(offset_ms)
+        .bind(frame.timestamp)
+        .execute(&mut *tx)
+        .await?
+        .last_insert_rowid();
+
+        let content_type =
+            classify_screen_content(&frame.text, &frame.app_name, &frame.window_name);
+        sqlx::query("INSERT INTO ocr_text (frame_id, text, text_json, app_name, ocr_engine, window_name, focused, content_type) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)")
+            .bind(id)
+            .bind(&frame.text)
+            .bind(&frame.text_json)
+            .bind(&frame.app_name)
+            .bind(format!("{:?}", *frame.ocr_engine))
+            .bind(&frame.window_name)
+            .bind(frame.focused)
+            .bind(content_type.map(|c| c.as_str()))
+            .execute(&mut *tx)
+            .await?;
+
+        sqlx::query("UPDATE imports SET last_frame_ms = ?2 WHERE file_path = ?1")
+            .bind(file_path)
+            .bind(offset_ms)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+        Ok(id)
+    }
+
+    pub async fn set_import_audio_progress(
+        &self,
+        file_path: &str,
+        last_audio_ms: i64,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query("UPDATE imports SET last_audio_ms = ?2 WHERE file_path = ?1")
+            .bind(file_path)
+            .bind(last_audio_ms)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Moves an audio chunk and its transcriptions to when the audio was recorded.
+    pub async fn set_audio_chunk_timestamp(
+        &self,
+        audio_chunk_id: i64,
+        timestamp: DateTime<Utc>,
+    ) -> Result<(), sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+        for sql in [
+            "UPDATE audio_chunks SET timestamp = ?2 WHERE id = ?1",
+            "UPDATE audio_transcriptions SET timestamp = ?2 WHERE audio_chunk_id = ?1",
+        ] {
+            sqlx::query(sql)
+                .bind(audio_chunk_id)
+                .bind(timestamp)
+                .execute(&mut *tx)
+                .await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    pub async fn set_frame_notes(&self, frame_id: i64, notes: &str) -> Result<(), sqlx::Error> {
+        sqlx::query("UPDATE frames SET notes = ?1 WHERE id = ?2")
+            .bind(notes)
+            .bind(frame_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn get_frame_notes(&self, frame_id: i64) -> Result<Option<String>, sqlx::Error> {
+        sqlx::query_scalar("SELECT notes FROM frames WHERE id = ?1")
+            .bind(frame_id)
+            .fetch_optional(&self.pool)
+            .await
+            .map(Option::flatten)
+    }
+
+    pub async fn get_frame_hash_path(&self, md5: &str) -> Result<Option<String>, sqlx::Error> {
+        sqlx::query_scalar("SELECT file_path FROM frame_hashes WHERE md5 = ?1")
+            .bind(md5)
+            .fetch_optional(&self.pool)
+            .await
+    }
+
+    pub async fn insert_frame_hash(&self, md5: &str, file_path: &str) -> Result<(), sqlx::Error> {
+        sqlx::query("INSERT OR REPLACE INTO frame_hashes (md5, file_path) VALUES (?1, ?2)")
+            .bind(md5)
+            .bind(file_path)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn set_frame_thumbnail_hash(
+        &self,
+        frame_ids: &[i64],
+        md5: &str,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "UPDATE frames SET thumbnail_md5 = ?1 WHERE id IN (SELECT value FROM json_each(?2))",
+        )
+        .bind(md5)
+        .bind(serde_json::to_string(frame_ids).unwrap_or_default())
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn get_frame_thumbnail_path(
+        &self,
+        frame_id: i64,
+    ) -> Result<Option<String>, sqlx::Error> {
+        sqlx::query_scalar(
+            r#"
+            SELECT frame_hashes.file_path
+            FROM frames
+            JOIN frame_hashes ON frame_hashes.md5 = frames.thumbnail_md5
+            WHERE frames.id = ?1
+            "#,
+        )
+        .bind(frame_id)
+        .fetch_optional(&self.pool)
+        .await
+    }
+
+    pub async fn insert_frame(&self) -> Result<i64, sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+        debug!("insert_frame Transaction started");
+
+        // Get the most recent video_chunk_id
+        let video_chunk_id: Option<i64> = sqlx::query_scalar(LATEST_RECORDED_CHUNK)
+            .fetch_optional(&mut *tx)
+            .await?;
+        debug!("Fetched most recent video_chunk_id: {:?}", video_chunk_id);
+
+        // If no video chunk is found, return 0
+        let video_chunk_id = match video_chunk_id {
+            Some(id) => id,
+            None => {
+                debug!("No video chunk found, rolling back transaction");
+                tx.rollback().await?;
+                return Ok(0);
+            }
+        };
+
+        // Calculate the offset_index
+        let offset_index: i64 = sqlx::query_scalar(
+            "SELECT COALESCE(MAX(offset_index), -1) + 1 FROM frames WHERE video_chunk_id = ?1",
+        )
+        .bind(video_chunk_id)
+        .fetch_one(&mut *tx)
+        .await?;
+        debug!("insert_frame Calculated offset_index: {}", offset_index);
+
+        // Insert the new frame
+        let id = sqlx::query(
+            "INSERT INTO frames (video_chunk_id, offset_index, timestamp, session_id) VALUES (?1, ?2, ?3, (SELECT id FROM sessions WHERE end_time IS NULL ORDER BY start_time DESC LIMIT 1))",
+        )
+        .bind(video_chunk_id)
+        .bind(offset_index)
+        .bind(Utc::now())
+        .execute(&mut *tx)
+        .await?
+        .last_insert_rowid();
+        debug!("insert_frame Inserted new frame with id: {}", id);
+
+        // Commit the transaction
+        tx.commit().await?;
+        // debug!("insert_frame Transaction committed");
+
+        Ok(id)
+    }
+
+    /// Inserts the frames and their ocr text in a single transaction, returning the frame ids in
+    /// order. Like [`Self::insert_frame`] frames go to the latest video chunk, nothing is
+    /// written when there is none yet.
+    pub async fn bulk_insert_frames(
+        &self,
+        frames: Vec<FrameData>,
+    ) -> Result<Vec<i64>, sqlx::Error> {
+        if frames.is_empty() {
+            return Ok(vec![]);
+        }
+        let mut tx = self.pool.begin().await?;
+
+        let video_chunk_id: Option<i64> = sqlx::query_scalar(LATEST_RECORDED_CHUNK)
+            .fetch_optional(&mut *tx)
+            .await?;
+        let Some(video_chunk_id) = video_chunk_id else {
+            debug!("No video chunk found, dropping {} frames", frames.len());
+            tx.rollback().await?;
+            return Ok(vec![]);
+        };
+        let mut offset_index: i64 = sqlx::query_scalar(
+            "SELECT COALESCE(MAX(offset_index), -1) + 1 FROM frames WHERE video_chunk_id = ?1",
+        )
+        .bind(video_chunk_id)
+        .fetch_one(&mut *tx)
+        .await?;
+        let session_id: Option<String> = sqlx::query_scalar(
+            "SELECT id FROM sessions WHERE end_time IS NULL ORDER BY start_time DESC LIMIT 1",
+        )
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        let mut ids = Vec::with_capacity(frames.len());
+        for frame in &frames {
+            let id = sqlx::query(
+                "INSERT INTO frames (video_chunk_id, offset_index, timestamp, session_id, cursor_x, cursor_y) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            )
+            .bind(video_chunk_id)
+            .bind(offset_index)
+            .bind(frame.timestamp)
+            .bind(&session_id)
+            .bind(frame.cursor.map(|cursor| cursor.x))
+            .bind(frame.cursor.map(|cursor| cursor.y))
+            .execute(&mut *tx)
+            .await?
+            .last_insert_rowid();
+            offset_index += 1;
+            let bounds = frame.window_bounds;
+            sqlx::query("INSERT INTO frame_windows (frame_id, app_name, window_name, focused, x, y, width, height) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)")
+                .bind(id)
+                .bind(&frame.app_name)
+                .bind(&frame.window_name)
+                .bind(frame.focused)
+                .bind(bounds.map(|b| b.x))
+                .bind(bounds.map(|b| b.y))
+                .bind(bounds.map(|b| b.width))
+                .bind(bounds.map(|b| b.height))
+                .execute(&mut *tx)
+                .await?;
+            if frame.ocr_failed {
+                warn!("ocr failed for frame {}, stored without text", id);
+                ids.push(id);
+                continue;
+            }
+
+            let content_type =
+                classify_screen_content(&frame.text, &frame.app_name, &frame.window_name);
+            sqlx::query("INSERT INTO ocr_text (frame_id, text, text_json, app_name, ocr_engine, window_name, focused, content_type) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)")
+                .bind(id)
+                .bind(&frame.text)
+                .bind(&frame.text_json)
+                .bind(&frame.app_name)
+                .bind(format!("{:?}", *frame.ocr_engine))
+                .bind(&frame.window_name)
+                .bind(frame.focused)
+                .bind(content_type.map(|c| c.as_str()))
+                .execute(&mut *tx)
+                .await?;
+            store_frame_diff(
+                &mut *tx,
+                id,
+                &frame.app_name,
+                &frame.window_name,
+                &frame.text,
+            )
+            .await?;
+            ids.push(id);
+        }
+
+        tx.commit().await?;
+        self.new_rows.notify_one();
+        debug!("Inserted {} frames in one transaction", ids.len());
+        Ok(ids)
+    }
+
+    pub async fn insert_ocr_text(
+        &self,
+        frame_id: i64,
+        text: &str,
+        text_json: &str,
+        app_name: &str,
+        window_name: &str,
+        ocr_engine: Arc<OcrEngine>,
+        focused: bool,
+    ) -> Result<(), sqlx::Error> {
+        const MAX_RETRIES: u32 = 3;
+        const TIMEOUT_DURATION: TokioDuration = TokioDuration::from_secs(10);
 
         for attempt in 1..=MAX_RETRIES {
             match timeout(
@@ -357,301 +1638,988 @@ impl DatabaseManager {
             }
         }
 
-        error!(
-            "Exiting insert_ocr_text for frame_id: {} with PoolTimedOut error",
-            frame_id
+        error!(
+            "Exiting insert_ocr_text for frame_id: {} with PoolTimedOut error",
+            frame_id
+        );
+        Err(sqlx::Error::PoolTimedOut)
+    }
+
+    async fn insert_ocr_text_old(
+        &self,
+        frame_id: i64,
+        text: &str,
+        text_json: &str,
+        app_name: &str,
+        window_name: &str,
+        ocr_engine: Arc<OcrEngine>,
+        focused: bool,
+    ) -> Result<(), sqlx::Error> {
+        let display_window_name = if window_name.chars().count() > 20 {
+            format!("{}...", window_name.chars().take(20).collect::<String>())
+        } else {
+            window_name.to_string()
+        };
+
+        debug!(
+            "Inserting OCR: frame_id {}, app {}, window {}, focused {}, text {}{}",
+            frame_id,
+            app_name,
+            display_window_name,
+            focused,
+            text.replace('\n', " ").chars().take(60).collect::<String>(),
+            if text.len() > 60 { "..." } else { "" },
+        );
+
+        let content_type = classify_screen_content(text, app_name, window_name);
+
+        let mut tx = self.pool.begin().await?;
+        sqlx::query("INSERT INTO ocr_text (frame_id, text, text_json, app_name, ocr_engine, window_name, focused, content_type) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)")
+            .bind(frame_id)
+            .bind(text)
+            .bind(text_json)
+            .bind(app_name)
+            .bind(format!("{:?}", *ocr_engine))
+            .bind(window_name)
+            .bind(focused)
+            .bind(content_type.map(|c| c.as_str()))
+            .execute(&mut *tx)
+            .await?;
+        store_frame_diff(&mut *tx, frame_id, app_name, window_name, text).await?;
+
+        tx.commit().await?;
+        debug!("OCR text inserted into db successfully");
+        Ok(())
+    }
+
+    pub async fn search(
+        &self,
+        query: &str,
+        content_type: ContentType,
+        limit: u32,
+        offset: u32,
+        start_time: Option<DateTime<Utc>>,
+        end_time: Option<DateTime<Utc>>,
+        app_name: Option<&str>,
+        window_name: Option<&str>,
+        min_length: Option<usize>,
+        max_length: Option<usize>,
+        session_id: Option<&str>,
+        cursor: Option<&SearchCursor>,
+        order: &SearchOrder,
+    ) -> Result<Vec<SearchResult>, sqlx::Error> {
+        let mut results = Vec::new();
+        // keyset pagination replaces the offset
+        let offset = if cursor.is_some() { 0 } else { offset };
+        let cursor = cursor.cloned().unwrap_or_default();
+
+        if matches!(
+            content_type,
+            ContentType::All | ContentType::OCR | ContentType::Screen(_)
+        ) && cursor.ocr != CursorPosition::Done
+        {
+            let ocr_results = self
+                .search_ocr(
+                    query,
+                    limit,
+                    offset,
+                    start_time,
+                    end_time,
+                    app_name,
+                    window_name,
+                    min_length,
+                    max_length,
+                    session_id,
+                    cursor.ocr.after(),
+                    content_type.screen_content_type(),
+                    order,
+                )
+                .await?;
+            results.extend(ocr_results.into_iter().map(SearchResult::OCR));
+        }
+
+        if (content_type == ContentType::All || content_type == ContentType::Audio)
+            && app_name.is_none()
+            && window_name.is_none()
+            && cursor.audio != CursorPosition::Done
+        {
+            let audio_results = self
+                .search_audio(
+                    query,
+                    limit,
+                    offset,
+                    start_time,
+                    end_time,
+                    min_length,
+                    max_length,
+                    session_id,
+                    cursor.audio.after(),
+                    order,
+                )
+                .await?;
+            results.extend(audio_results.into_iter().map(SearchResult::Audio));
+        }
+
+        Ok(results)
+    }
+
+    /// Every result of a search, fetched row by row as the stream is read rather than collected
+    /// first: the ocr results and then the audio ones, each in `order`. Rows are read ahead by at
+    /// most [`SEARCH_STREAM_BUFFER`], the query stops when the stream is dropped.
+    pub fn search_stream(
+        &self,
+        query: &str,
+        content_type: ContentType,
+        start_time: Option<DateTime<Utc>>,
+        end_time: Option<DateTime<Utc>>,
+        app_name: Option<&str>,
+        window_name: Option<&str>,
+        min_length: Option<usize>,
+        max_length: Option<usize>,
+        session_id: Option<&str>,
+        order: &SearchOrder,
+    ) -> impl Stream<Item = Result<SearchResult, sqlx::Error>> + Send + 'static {
+        let search_ocr = matches!(
+            content_type,
+            ContentType::All | ContentType::OCR | ContentType::Screen(_)
+        );
+        let search_audio = (content_type == ContentType::All || content_type == ContentType::Audio)
+            && app_name.is_none()
+            && window_name.is_none();
+        let screen_content_type = content_type.screen_content_type();
+        let (query, app_name, window_name, session_id) = (
+            query.to_string(),
+            app_name.map(String::from),
+            window_name.map(String::from),
+            session_id.map(String::from),
+        );
+        let (min_length, max_length) = (min_length.map(|l| l as i64), max_length.map(|l| l as i64));
+        let order = *order;
+        let pool = self.pool.clone();
+        let (sender, receiver) = tokio::sync::mpsc::channel(SEARCH_STREAM_BUFFER);
+
+        tokio::spawn(async move {
+            if search_ocr {
+                let sql = ocr_search_sql(&order);
+                let mut rows = sqlx::query_as::<_, OCRResultRaw>(&sql)
+                    .bind(query.trim())
+                    .bind(start_time)
+                    .bind(end_time)
+                    .bind(min_length)
+                    .bind(max_length)
+                    .bind(app_name.as_deref())
+                    .bind(window_name.as_deref())
+                    .bind(-1)
+                    .bind(0)
+                    .bind(session_id.as_deref())
+                    .bind(None::<DateTime<Utc>>)
+                    .bind(None::<i64>)
+                    .bind(screen_content_type.map(|c| c.as_str()))
+                    .fetch(&pool);
+                while let Some(row) = rows.next().await {
+                    let failed = row.is_err();
+                    let row = row.map(|raw| SearchResult::OCR(raw.into()));
+                    // a send fails once the stream is dropped
+                    if sender.send(row).await.is_err() || failed {
+                        return;
+                    }
+                }
+            }
+            if search_audio {
+                let sql = audio_search_sql(&order);
+                let mut rows = sqlx::query_as::<_, AudioResultRaw>(&sql)
+                    .bind(query.as_str())
+                    .bind(start_time)
+                    .bind(end_time)
+                    .bind(min_length)
+                    .bind(max_length)
+                    .bind(-1)
+                    .bind(0)
+                    .bind(session_id.as_deref())
+                    .bind(None::<DateTime<Utc>>)
+                    .bind(None::<i64>)
+                    .fetch(&pool);
+                while let Some(row) = rows.next().await {
+                    let failed = row.is_err();
+                    let row = row.map(|raw| SearchResult::Audio(raw.into()));
+                    if sender.send(row).await.is_err() || failed {
+                        return;
+                    }
+                }
+            }
+        });
+
+        futures::stream::unfold(receiver, |mut receiver| async move {
+            receiver.recv().await.map(|row| (row, receiver))
+        })
+    }
+
+    async fn search_ocr(
+        &self,
+        query: &str,
+        limit: u32,
+        offset: u32,
+        start_time: Option<DateTime<Utc>>,
+        end_time: Option<DateTime<Utc>>,
+        app_name: Option<&str>,
+        window_name: Option<&str>,
+        min_length: Option<usize>,
+        max_length: Option<usize>,
+        session_id: Option<&str>,
+        after: Option<(DateTime<Utc>, i64)>,
+        screen_content_type: Option<ScreenContentType>,
+        order: &SearchOrder,
+    ) -> Result<Vec<OCRResult>, sqlx::Error> {
+        let sql = ocr_search_sql(order);
+        let query = sqlx::query_as::<_, OCRResultRaw>(&sql)
+            .bind(query.trim()) // Trim the query to handle empty strings properly
+            .bind(start_time)
+            .bind(end_time)
+            .bind(min_length.map(|l| l as i64))
+            .bind(max_length.map(|l| l as i64))
+            .bind(app_name)
+            .bind(window_name)
+            .bind(limit)
+            .bind(offset)
+            .bind(session_id)
+            .bind(after.map(|(timestamp, _)| timestamp))
+            .bind(after.map(|(_, id)| id))
+            .bind(screen_content_type.map(|c| c.as_str()));
+
+        let ocr_results_raw = query.fetch_all(&self.pool).await?;
+
+        let ocr_results = ocr_results_raw.into_iter().map(OCRResult::from).collect();
+
+        Ok(ocr_results)
+    }
+
+    /// OCR results within [`MAX_EDIT_DISTANCE`] edits of `query`, best `match_score` first, and
+    /// the total number of matches. Candidates come from the `frame_trigrams` index, so a match
+    /// shares at least one trigram with the query; empty when the query is too short for one.
+    pub async fn fuzzy_search_ocr(
+        &self,
+        query: &str,
+        limit: u32,
+        offset: u32,
+        start_time: Option<DateTime<Utc>>,
+        end_time: Option<DateTime<Utc>>,
+        app_name: Option<&str>,
+        window_name: Option<&str>,
+        session_id: Option<&str>,
+    ) -> Result<(Vec<(OCRResult, f32)>, usize), sqlx::Error> {
+        let Some(match_query) = trigram_match_query(query) else {
+            return Ok((Vec::new(), 0));
+        };
+
+        let candidates = sqlx::query_as::<_, OCRResultRaw>(
+            r#"
+            SELECT
+                ocr_text.frame_id,
+                ocr_text.text as ocr_text,
+                ocr_text.text_json,
+                frames.timestamp,
+                video_chunks.file_path,
+                frames.offset_index,
+                ocr_text.app_name,
+                ocr_text.ocr_engine,
+                ocr_text.window_name,
+                GROUP_CONCAT(tags.name, ',') as tags
+            FROM
+                (SELECT rowid, rank FROM frame_trigrams WHERE frame_trigrams MATCH ?1) AS candidates
+            JOIN
+                ocr_text ON ocr_text.id = candidates.rowid
+            JOIN
+                frames ON ocr_text.frame_id = frames.id
+            JOIN
+                video_chunks ON frames.video_chunk_id = video_chunks.id
+            LEFT JOIN
+                vision_tags ON frames.id = vision_tags.vision_id
+            LEFT JOIN
+                tags ON vision_tags.tag_id = tags.id
+            WHERE
+                ocr_text.text != 'No text found'
+                AND (?2 IS NULL OR frames.timestamp >= ?2)
+                AND (?3 IS NULL OR frames.timestamp <= ?3)
+                AND (?4 IS NULL OR ocr_text.app_name LIKE '%' || ?4 || '%' COLLATE NOCASE)
+                AND (?5 IS NULL OR ocr_text.window_name LIKE '%' || ?5 || '%' COLLATE NOCASE)
+                AND (?6 IS NULL OR frames.session_id = ?6)
+            GROUP BY
+                ocr_text.id
+            ORDER BY
+                candidates.rank
+            LIMIT ?7
+            "#,
+        )
+        .bind(match_query)
+        .bind(start_time)
+        .bind(end_time)
+        .bind(app_name)
+        .bind(window_name)
+        .bind(session_id)
+        .bind(MAX_FUZZY_CANDIDATES)
+        .fetch_all(&self.pool)
+        .await?;
+
+        // a frame has one ocr_text row per window, keep its best matching one
+        let mut best: HashMap<i64, (OCRResultRaw, f32)> = HashMap::new();
+        for raw in candidates {
+            let distance = substring_edit_distance(query, &raw.ocr_text);
+            if distance > MAX_EDIT_DISTANCE {
+                continue;
+            }
+            let score = match_score(query, distance);
+            if best
+                .get(&raw.frame_id)
+                .map_or(true, |(_, best_score)| score > *best_score)
+            {
+                best.insert(raw.frame_id, (raw, score));
+            }
+        }
+
+        let mut matches: Vec<(OCRResultRaw, f32)> = best.into_values().collect();
+        matches.sort_by(|(a, a_score), (b, b_score)| {
+            b_score
+                .total_cmp(a_score)
+                .then(b.timestamp.cmp(&a.timestamp))
+                .then(b.frame_id.cmp(&a.frame_id))
+        });
+        let total = matches.len();
+
+        let results = matches
+            .into_iter()
+            .skip(offset as usize)
+            .take(limit as usize)
+            .map(|(raw, score)| (OCRResult::from(raw), score))
+            .collect();
+
+        Ok((results, total))
+    }
+
+    pub async fn search_audio(
+        &self,
+        query: &str,
+        limit: u32,
+        offset: u32,
+        start_time: Option<DateTime<Utc>>,
+        end_time: Option<DateTime<Utc>>,
+        min_length: Option<usize>,
+        max_length: Option<usize>,
+        session_id: Option<&str>,
+        after: Option<(DateTime<Utc>, i64)>,
+        order: &SearchOrder,
+    ) -> Result<Vec<AudioResult>, sqlx::Error> {
+        let sql = audio_search_sql(order);
+        let query = sqlx::query_as::<_, AudioResultRaw>(&sql)
+            .bind(query)
+            .bind(start_time)
+            .bind(end_time)
+            .bind(min_length.map(|l| l as i64))
+            .bind(max_length.map(|l| l as i64))
+            .bind(limit)
+            .bind(offset)
+            .bind(session_id)
+            .bind(after.map(|(timestamp, _)| timestamp))
+            .bind(after.map(|(_, id)| id));
+
+        let audio_results_raw = query.fetch_all(&self.pool).await?;
+
+        let audio_results = audio_results_raw
+            .into_iter()
+            .map(AudioResult::from)
+            .collect();
+
+        Ok(audio_results)
+    }
+
+    /// Transcriptions of the audio chunks that started between `start` and `end`, oldest first.
+    pub async fn get_audio_transcripts(
+        &self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<AudioTranscript>, sqlx::Error> {
+        let sql = format!(
+            r#"
+            SELECT audio_chunks.timestamp, audio_transcriptions.transcription,
+                   CASE WHEN audio_transcriptions.source = 'corrected' THEN NULL
+                       ELSE audio_chunks.word_timestamps END
+            FROM audio_transcriptions
+            JOIN audio_chunks ON audio_transcriptions.audio_chunk_id = audio_chunks.id
+            WHERE audio_chunks.timestamp >= ?1 AND audio_chunks.timestamp <= ?2
+                AND {CURRENT_TRANSCRIPTION}
+            ORDER BY audio_chunks.timestamp, audio_transcriptions.id
+            "#
         );
-        Err(sqlx::Error::PoolTimedOut)
+        let rows = sqlx::query_as::<_, (DateTime<Utc>, String, Option<String>)>(&sql)
+            .bind(start)
+            .bind(end)
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(rows
+            .into_iter()
+            .map(|(start, transcription, word_timestamps)| AudioTranscript {
+                start,
+                transcription,
+                word_timestamps: word_timestamps
+                    .and_then(|json| serde_json::from_str(&json).ok())
+                    .unwrap_or_default(),
+            })
+            .collect())
     }
 
-    async fn insert_ocr_text_old(
+    pub async fn get_frame(&self, frame_id: i64) -> Result<Option<(String, i64)>, sqlx::Error> {
+        sqlx::query_as::<_, (String, i64)>(
+            r#"
+            SELECT 
+                video_chunks.file_path,
+                frames.offset_index
+            FROM 
+                frames
+            JOIN 
+                video_chunks ON frames.video_chunk_id = video_chunks.id
+            WHERE 
+                frames.id = ?1
+            "#,
+        )
+        .bind(frame_id)
+        .fetch_optional(&self.pool)
+        .await
+    }
+
+    /// `None` for frames recorded before windows were stored, or not recorded but inserted alone.
+    pub async fn get_frame_window(
+        &self,
+        frame_id: i64,
+    ) -> Result<Option<FrameWindow>, sqlx::Error> {
+        let row = sqlx::query(
+            "SELECT app_name, window_name, focused, x, y, width, height FROM frame_windows WHERE frame_id = ?1",
+        )
+        .bind(frame_id)
+        .fetch_optional(&self.pool)
+        .await?;
+        let Some(row) = row else {
+            return Ok(None);
+        };
+        let bounds = match (
+            row.try_get::<Option<u32>, _>("x")?,
+            row.try_get::<Option<u32>, _>("y")?,
+            row.try_get::<Option<u32>, _>("width")?,
+            row.try_get::<Option<u32>, _>("height")?,
+        ) {
+            (Some(x), Some(y), Some(width), Some(height)) => Some(WindowBounds {
+                x,
+                y,
+                width,
+                height,
+            }),
+            _ => None,
+        };
+        Ok(Some(FrameWindow {
+            app_name: row.try_get("app_name")?,
+            window_name: row.try_get("window_name")?,
+            focused: row.try_get("focused")?,
+            bounds,
+        }))
+    }
+
+    /// Replaces the ocr text of a frame, archiving the previous one in `frame_ocr_history`, and
+    /// classifies it again. A frame stored without text, e.g. after a failed ocr, gets its first
+    /// `ocr_text` row, under the window it was recorded of.
+    pub async fn replace_ocr_text(
         &self,
         frame_id: i64,
         text: &str,
         text_json: &str,
-        app_name: &str,
-        window_name: &str,
-        ocr_engine: Arc<OcrEngine>,
-        focused: bool,
+        ocr_engine: &OcrEngine,
+        confidence: Option<f64>,
     ) -> Result<(), sqlx::Error> {
-        let display_window_name = if window_name.chars().count() > 20 {
-            format!("{}...", window_name.chars().take(20).collect::<String>())
-        } else {
-            window_name.to_string()
-        };
-
-        debug!(
-            "Inserting OCR: frame_id {}, app {}, window {}, focused {}, text {}{}",
-            frame_id,
-            app_name,
-            display_window_name,
-            focused,
-            text.replace('\n', " ").chars().take(60).collect::<String>(),
-            if text.len() > 60 { "..." } else { "" },
-        );
-
         let mut tx = self.pool.begin().await?;
-        sqlx::query("INSERT INTO ocr_text (frame_id, text, text_json, app_name, ocr_engine, window_name, focused) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)")
-            .bind(frame_id)
-            .bind(text)
-            .bind(text_json)
-            .bind(app_name)
-            .bind(format!("{:?}", *ocr_engine))
-            .bind(window_name)
-            .bind(focused)
-            .execute(&mut *tx)
-            .await?;
+        sqlx::query(
+            r#"
+            INSERT INTO frame_ocr_history (frame_id, text, text_json, ocr_engine, confidence, replaced_at)
+            SELECT frame_id, text, text_json, ocr_engine, confidence, ?2
+            FROM ocr_text
+            WHERE frame_id = ?1
+            "#,
+        )
+        .bind(frame_id)
+        .bind(Utc::now())
+        .execute(&mut *tx)
+        .await?;
 
+        let existing: Option<(String, String)> =
+            sqlx::query_as("SELECT app_name, window_name FROM ocr_text WHERE frame_id = ?1")
+                .bind(frame_id)
+                .fetch_optional(&mut *tx)
+                .await?;
+        let (app_name, window_name) = match existing {
+            Some((app_name, window_name)) => {
+                let content_type = classify_screen_content(text, &app_name, &window_name);
+                sqlx::query(
+                    "UPDATE ocr_text SET text = ?2, text_json = ?3, ocr_engine = ?4, confidence = ?5, content_type = ?6 WHERE frame_id = ?1",
+                )
+                .bind(frame_id)
+                .bind(text)
+                .bind(text_json)
+                .bind(format!("{:?}", ocr_engine))
+                .bind(confidence)
+                .bind(content_type.map(|c| c.as_str()))
+                .execute(&mut *tx)
+                .await?;
+                (app_name, window_name)
+            }
+            None => {
+                let (app_name, window_name, focused): (String, String, bool) = sqlx::query_as(
+                    "SELECT app_name, window_name, focused FROM frame_windows WHERE frame_id = ?1",
+                )
+                .bind(frame_id)
+                .fetch_optional(&mut *tx)
+                .await?
+                .unwrap_or_default();
+                let content_type = classify_screen_content(text, &app_name, &window_name);
+                sqlx::query("INSERT INTO ocr_text (frame_id, text, text_json, app_name, ocr_engine, window_name, focused, confidence, content_type) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)")
+                    .bind(frame_id)
+                    .bind(text)
+                    .bind(text_json)
+                    .bind(&app_name)
+                    .bind(format!("{:?}", ocr_engine))
+                    .bind(&window_name)
+                    .bind(focused)
+                    .bind(confidence)
+                    .bind(content_type.map(|c| c.as_str()))
+                    .execute(&mut *tx)
+                    .await?;
+                (app_name, window_name)
+            }
+        };
+        store_frame_diff(&mut *tx, frame_id, &app_name, &window_name, text).await?;
         tx.commit().await?;
-        debug!("OCR text inserted into db successfully");
         Ok(())
     }
 
-    pub async fn search(
+    /// Stores a share of `frame_id` under `token`, false when there is no such frame.
+    pub async fn create_share(
         &self,
-        query: &str,
-        content_type: ContentType,
-        limit: u32,
-        offset: u32,
-        start_time: Option<DateTime<Utc>>,
-        end_time: Option<DateTime<Utc>>,
-        app_name: Option<&str>,
-        window_name: Option<&str>,
-        min_length: Option<usize>,
-        max_length: Option<usize>,
-    ) -> Result<Vec<SearchResult>, sqlx::Error> {
-        let mut results = Vec::new();
+        token: &str,
+        frame_id: i64,
+        expires_at: DateTime<Utc>,
+    ) -> Result<bool, sqlx::Error> {
+        let inserted = sqlx::query(
+            "INSERT INTO shares (token, frame_id, created_at, expires_at) SELECT ?1, id, ?3, ?4 FROM frames WHERE id = ?2",
+        )
+        .bind(token)
+        .bind(frame_id)
+        .bind(Utc::now())
+        .bind(expires_at)
+        .execute(&self.pool)
+        .await?
+        .rows_affected();
+        Ok(inserted > 0)
+    }
 
-        if content_type == ContentType::All || content_type == ContentType::OCR {
-            let ocr_results = self
-                .search_ocr(
-                    query,
-                    limit,
-                    offset,
-                    start_time,
-                    end_time,
-                    app_name,
-                    window_name,
-                    min_length,
-                    max_length,
-                )
-                .await?;
-            results.extend(ocr_results.into_iter().map(SearchResult::OCR));
+    /// Marks `token` used if it is still valid at `now`, a token is claimed once however many
+    /// requests race for it. `None` when there is no such token.
+    pub async fn claim_share(
+        &self,
+        token: &str,
+        now: DateTime<Utc>,
+    ) -> Result<Option<ShareClaim>, sqlx::Error> {
+        let claimed: Option<i64> = sqlx::query_scalar(
+            "UPDATE shares SET used_at = ?2 WHERE token = ?1 AND used_at IS NULL AND expires_at > ?2 RETURNING frame_id",
+        )
+        .bind(token)
+        .bind(now)
+        .fetch_optional(&self.pool)
+        .await?;
+        if let Some(frame_id) = claimed {
+            return Ok(Some(ShareClaim::Frame(frame_id)));
         }
+        self.find_share(token, now).await
+    }
 
-        if (content_type == ContentType::All || content_type == ContentType::Audio)
-            && app_name.is_none()
-            && window_name.is_none()
-        {
-            let audio_results = self
-                .search_audio(
-                    query, limit, offset, start_time, end_time, min_length, max_length,
-                )
+    /// Like [`DatabaseManager::claim_share`] without using the token up.
+    pub async fn find_share(
+        &self,
+        token: &str,
+        now: DateTime<Utc>,
+    ) -> Result<Option<ShareClaim>, sqlx::Error> {
+        let share: Option<(i64, Option<DateTime<Utc>>, DateTime<Utc>)> =
+            sqlx::query_as("SELECT frame_id, used_at, expires_at FROM shares WHERE token = ?1")
+                .bind(token)
+                .fetch_optional(&self.pool)
                 .await?;
-            results.extend(audio_results.into_iter().map(SearchResult::Audio));
-        }
+        Ok(share.map(|(frame_id, used_at, expires_at)| {
+            if used_at.is_some() {
+                ShareClaim::Used
+            } else if expires_at <= now {
+                ShareClaim::Expired
+            } else {
+                ShareClaim::Frame(frame_id)
+            }
+        }))
+    }
 
-        Ok(results)
+    pub async fn get_frame_text(&self, frame_id: i64) -> Result<Option<FrameText>, sqlx::Error> {
+        sqlx::query_as(&format!(
+            "{FRAME_TEXT_SELECT} WHERE frames.id = ?1 GROUP BY frames.id"
+        ))
+        .bind(frame_id)
+        .fetch_optional(&self.pool)
+        .await
     }
 
-    async fn search_ocr(
+    /// `n` frames picked at random between `start` and `end` by `ORDER BY RANDOM()`, oldest
+    /// first. With a `seed` the same frames come back for as long as the range holds the same
+    /// frames.
+    pub async fn get_random_frames(
         &self,
-        query: &str,
+        start: Option<DateTime<Utc>>,
+        end: Option<DateTime<Utc>>,
+        n: u32,
+        seed: Option<u64>,
+    ) -> Result<Vec<FrameText>, sqlx::Error> {
+        const IN_RANGE: &str =
+            "(?1 IS NULL OR frames.timestamp >= ?1) AND (?2 IS NULL OR frames.timestamp <= ?2)";
+        let Some(seed) = seed else {
+            return sqlx::query_as(&format!(
+                "{FRAME_TEXT_SELECT} WHERE frames.id IN (SELECT id FROM frames WHERE {IN_RANGE} ORDER BY RANDOM() LIMIT ?3) GROUP BY frames.id ORDER BY frames.timestamp, frames.id"
+            ))
+            .bind(start)
+            .bind(end)
+            .bind(n)
+            .fetch_all(&self.pool)
+            .await;
+        };
+
+        // sqlite's RANDOM() can't be seeded, the positions of the picked frames among those of
+        // the range are drawn here instead
+        let count: i64 =
+            sqlx::query_scalar(&format!("SELECT COUNT(*) FROM frames WHERE {IN_RANGE}"))
+                .bind(start)
+                .bind(end)
+                .fetch_one(&self.pool)
+                .await?;
+        let count = count.max(0) as usize;
+        let mut rng = StdRng::seed_from_u64(seed);
+        let positions =
+            rand::seq::index::sample(&mut rng, count, (n as usize).min(count)).into_vec();
+        sqlx::query_as(&format!(
+            r#"
+            WITH ranked AS (
+                SELECT id, ROW_NUMBER() OVER (ORDER BY id) - 1 AS position
+                FROM frames
+                WHERE {IN_RANGE}
+            )
+            {FRAME_TEXT_SELECT}
+            WHERE frames.id IN (
+                SELECT id FROM ranked WHERE position IN (SELECT value FROM json_each(?3))
+            )
+            GROUP BY frames.id
+            ORDER BY frames.timestamp, frames.id
+            "#
+        ))
+        .bind(start)
+        .bind(end)
+        .bind(serde_json::to_string(&positions).unwrap_or_default())
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    pub async fn get_frame_diff(&self, frame_id: i64) -> Result<Option<FrameDiff>, sqlx::Error> {
+        let row: Option<(i64, Option<i64>, String, i64, i64)> = sqlx::query_as(
+            "SELECT frame_id, previous_frame_id, diff, lines_added, lines_removed FROM frame_diffs WHERE frame_id = ?1",
+        )
+        .bind(frame_id)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(row.map(
+            |(frame_id, previous_frame_id, diff, lines_added, lines_removed)| FrameDiff {
+                frame_id,
+                previous_frame_id,
+                diff,
+                lines_added,
+                lines_removed,
+            },
+        ))
+    }
+
+    /// Replaced ocr texts of a frame, oldest first.
+    pub async fn get_ocr_history(
+        &self,
+        frame_id: i64,
+    ) -> Result<Vec<OcrHistoryEntry>, sqlx::Error> {
+        sqlx::query_as(
+            "SELECT text, text_json, ocr_engine, confidence, replaced_at FROM frame_ocr_history WHERE frame_id = ?1 ORDER BY id",
+        )
+        .bind(frame_id)
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    pub async fn get_audio_chunk_path(
+        &self,
+        audio_chunk_id: i64,
+    ) -> Result<Option<String>, sqlx::Error> {
+        sqlx::query_scalar("SELECT file_path FROM audio_chunks WHERE id = ?1")
+            .bind(audio_chunk_id)
+            .fetch_optional(&self.pool)
+            .await
+    }
+
+    /// Path of an audio chunk and whether the integrity check found its file missing.
+    pub async fn get_audio_chunk_file(
+        &self,
+        audio_chunk_id: i64,
+    ) -> Result<Option<(String, bool)>, sqlx::Error> {
+        sqlx::query_as("SELECT file_path, file_missing FROM audio_chunks WHERE id = ?1")
+            .bind(audio_chunk_id)
+            .fetch_optional(&self.pool)
+            .await
+    }
+
+    /// Up to `limit` audio chunks with an id above `after_id`, by id: id, path and `file_missing`.
+    pub async fn get_audio_chunk_files(
+        &self,
+        after_id: i64,
         limit: u32,
-        offset: u32,
+    ) -> Result<Vec<(i64, String, bool)>, sqlx::Error> {
+        sqlx::query_as(
+            "SELECT id, file_path, file_missing FROM audio_chunks WHERE id > ?1 ORDER BY id LIMIT ?2",
+        )
+        .bind(after_id)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    pub async fn set_audio_chunk_file_missing(
+        &self,
+        audio_chunk_id: i64,
+        file_missing: bool,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query("UPDATE audio_chunks SET file_missing = ?2 WHERE id = ?1")
+            .bind(audio_chunk_id)
+            .bind(file_missing)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn get_frames(
+        &self,
         start_time: Option<DateTime<Utc>>,
         end_time: Option<DateTime<Utc>>,
-        app_name: Option<&str>,
-        window_name: Option<&str>,
-        min_length: Option<usize>,
-        max_length: Option<usize>,
-    ) -> Result<Vec<OCRResult>, sqlx::Error> {
-        let mut sql = format!(
-            r#"
-            SELECT 
-                ocr_text.frame_id,
-                ocr_text.text as ocr_text,
-                ocr_text.text_json,
+        limit: u32,
+        offset: u32,
+    ) -> Result<Vec<FrameInfo>, sqlx::Error> {
+        sqlx::query_as::<_, FrameInfo>(
+            r#"
+            SELECT
+                frames.id as frame_id,
                 frames.timestamp,
                 video_chunks.file_path,
                 frames.offset_index,
-                ocr_text.app_name,
-                ocr_text.ocr_engine,
-                ocr_text.window_name,
-                GROUP_CONCAT(tags.name, ',') as tags
-            FROM 
-                ocr_text
-            JOIN 
-                frames ON ocr_text.frame_id = frames.id
-            JOIN 
+                frames.cursor_x,
+                frames.cursor_y
+            FROM
+                frames
+            JOIN
                 video_chunks ON frames.video_chunk_id = video_chunks.id
-            LEFT JOIN
-                vision_tags ON frames.id = vision_tags.vision_id
-            LEFT JOIN
-                tags ON vision_tags.tag_id = tags.id
-            WHERE 
-                (?1 = '' OR ocr_text.text LIKE '%' || ?1 || '%' COLLATE NOCASE)
-                AND ocr_text.text != 'No text found'
-                AND (?2 IS NULL OR frames.timestamp >= ?2)
-                AND (?3 IS NULL OR frames.timestamp <= ?3)
-                AND (?4 IS NULL OR LENGTH(ocr_text.text) >= ?4)
-                AND (?5 IS NULL OR LENGTH(ocr_text.text) <= ?5)
-                AND (?6 IS NULL OR ocr_text.app_name LIKE '%' || ?6 || '%' COLLATE NOCASE)
-                AND (?7 IS NULL OR ocr_text.window_name LIKE '%' || ?7 || '%' COLLATE NOCASE)
-        "#,
-        );
+            WHERE
+                (?1 IS NULL OR frames.timestamp >= ?1)
+                AND (?2 IS NULL OR frames.timestamp <= ?2)
+            ORDER BY
+                frames.timestamp ASC, frames.id ASC
+            LIMIT ?3 OFFSET ?4
+            "#,
+        )
+        .bind(start_time)
+        .bind(end_time)
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(&self.pool)
+        .await
+    }
 
-        sql.push_str(
+    pub async fn get_monitor_frames(
+        &self,
+        monitor_id: u32,
+        start_time: DateTime<Utc>,
+        end_time: DateTime<Utc>,
+    ) -> Result<Vec<FrameInfo>, sqlx::Error> {
+        sqlx::query_as::<_, FrameInfo>(
             r#"
-            GROUP BY 
-                ocr_text.frame_id
-            ORDER BY 
-                frames.timestamp DESC
-            LIMIT ?8 OFFSET ?9
+            SELECT
+                frames.id as frame_id,
+                frames.timestamp,
+                video_chunks.file_path,
+                frames.offset_index,
+                frames.cursor_x,
+                frames.cursor_y
+            FROM
+                frames
+            JOIN
+                video_chunks ON frames.video_chunk_id = video_chunks.id
+            WHERE
+                frames.timestamp >= ?1
+                AND frames.timestamp <= ?2
+                AND video_chunks.file_path LIKE ?3
+            ORDER BY
+                frames.timestamp ASC, frames.id ASC
             "#,
-        );
-
-        let query = sqlx::query_as::<_, OCRResultRaw>(&sql)
-            .bind(query.trim()) // Trim the query to handle empty strings properly
-            .bind(start_time)
-            .bind(end_time)
-            .bind(min_length.map(|l| l as i64))
-            .bind(max_length.map(|l| l as i64))
-            .bind(app_name)
-            .bind(window_name)
-            .bind(limit)
-            .bind(offset);
-
-        let ocr_results_raw = query.fetch_all(&self.pool).await?;
-
-        let ocr_results: Vec<OCRResult> = ocr_results_raw
-            .into_iter()
-            .map(|raw| OCRResult {
-                frame_id: raw.frame_id,
-                ocr_text: raw.ocr_text,
-                text_json: raw.text_json,
-                timestamp: raw.timestamp,
-                file_path: raw.file_path,
-                offset_index: raw.offset_index,
-                app_name: raw.app_name,
-                ocr_engine: raw.ocr_engine,
-                window_name: raw.window_name,
-                tags: raw
-                    .tags
-                    .map(|s| s.split(',').map(String::from).collect())
-                    .unwrap_or_default(),
-            })
-            .collect();
-
-        Ok(ocr_results)
+        )
+        .bind(start_time)
+        .bind(end_time)
+        // chunks are named monitor_<id>_<time>.mp4
+        .bind(format!("%monitor_{}_%", monitor_id))
+        .fetch_all(&self.pool)
+        .await
     }
 
-    pub async fn search_audio(
+    /// Deletes matching frames with their ocr text and tags, along with the video chunks that
+    /// have no frames left. The chunk currently being recorded is never removed.
+    pub async fn delete_frames(
         &self,
-        query: &str,
-        limit: u32,
-        offset: u32,
         start_time: Option<DateTime<Utc>>,
         end_time: Option<DateTime<Utc>>,
-        min_length: Option<usize>,
-        max_length: Option<usize>,
-    ) -> Result<Vec<AudioResult>, sqlx::Error> {
-        let mut sql = format!(
+        app_name: Option<&str>,
+    ) -> Result<DeletedContent, sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+
+        let frames: Vec<(i64, i64)> = sqlx::query_as(
             r#"
-        SELECT 
-            audio_transcriptions.audio_chunk_id,
-            audio_transcriptions.transcription,
-            audio_transcriptions.timestamp,
-            audio_chunks.file_path,
-            audio_transcriptions.offset_index,
-            audio_transcriptions.transcription_engine,
-            GROUP_CONCAT(tags.name, ',') as tags,
-            audio_transcriptions.device as device_name,
-            audio_transcriptions.is_input_device
-        FROM 
-            audio_transcriptions
-        JOIN 
-            audio_chunks ON audio_transcriptions.audio_chunk_id = audio_chunks.id
-        LEFT JOIN
-            audio_tags ON audio_chunks.id = audio_tags.audio_chunk_id
-        LEFT JOIN
-            tags ON audio_tags.tag_id = tags.id
-        WHERE 
-            (?1 = '' OR audio_transcriptions.transcription LIKE '%' || ?1 || '%' COLLATE NOCASE)
-            AND (?2 IS NULL OR audio_transcriptions.timestamp >= ?2)
-            AND (?3 IS NULL OR audio_transcriptions.timestamp <= ?3)
-            AND (?4 IS NULL OR LENGTH(audio_transcriptions.transcription) >= ?4)
-            AND (?5 IS NULL OR LENGTH(audio_transcriptions.transcription) <= ?5)
-        "#,
-        );
+            SELECT id, video_chunk_id
+            FROM frames
+            WHERE
+                (?1 IS NULL OR timestamp >= ?1)
+                AND (?2 IS NULL OR timestamp <= ?2)
+                AND (?3 IS NULL OR EXISTS (
+                    SELECT 1 FROM ocr_text
+                    WHERE ocr_text.frame_id = frames.id AND ocr_text.app_name = ?3 COLLATE NOCASE
+                ))
+            "#,
+        )
+        .bind(start_time)
+        .bind(end_time)
+        .bind(app_name)
+        .fetch_all(&mut *tx)
+        .await?;
 
-        sql.push_str(
+        let ids: Vec<i64> = frames.iter().map(|(id, _)| *id).collect();
+        let mut chunk_ids: Vec<i64> = frames.iter().map(|(_, chunk_id)| *chunk_id).collect();
+        chunk_ids.sort_unstable();
+        chunk_ids.dedup();
+        let ids_json = serde_json::to_string(&ids).unwrap_or_default();
+
+        let hashes: Vec<String> = sqlx::query_scalar(
             r#"
-        GROUP BY
-            audio_transcriptions.audio_chunk_id,
-            audio_transcriptions.transcription,
-            audio_transcriptions.timestamp,
-            audio_transcriptions.offset_index
-        ORDER BY 
-            audio_transcriptions.timestamp DESC
-        LIMIT ?6 OFFSET ?7
-        "#,
-        );
+            SELECT DISTINCT thumbnail_md5
+            FROM frames
+            WHERE id IN (SELECT value FROM json_each(?1)) AND thumbnail_md5 IS NOT NULL
+            "#,
+        )
+        .bind(&ids_json)
+        .fetch_all(&mut *tx)
+        .await?;
 
-        let query = sqlx::query_as::<_, AudioResultRaw>(&sql)
-            .bind(query)
-            .bind(start_time)
-            .bind(end_time)
-            .bind(min_length.map(|l| l as i64))
-            .bind(max_length.map(|l| l as i64))
-            .bind(limit)
-            .bind(offset);
+        for sql in [
+            "DELETE FROM ocr_text WHERE frame_id IN (SELECT value FROM json_each(?1))",
+            "DELETE FROM frame_diffs WHERE frame_id IN (SELECT value FROM json_each(?1))",
+            "DELETE FROM frame_windows WHERE frame_id IN (SELECT value FROM json_each(?1))",
+            "UPDATE frame_diffs SET previous_frame_id = NULL WHERE previous_frame_id IN (SELECT value FROM json_each(?1))",
+            "DELETE FROM vision_tags WHERE vision_id IN (SELECT value FROM json_each(?1))",
+            "DELETE FROM chunked_text_entries WHERE frame_id IN (SELECT value FROM json_each(?1))",
+            "DELETE FROM shares WHERE frame_id IN (SELECT value FROM json_each(?1))",
+            "DELETE FROM frames WHERE id IN (SELECT value FROM json_each(?1))",
+        ] {
+            sqlx::query(sql).bind(&ids_json).execute(&mut *tx).await?;
+        }
 
-        let audio_results_raw = query.fetch_all(&self.pool).await?;
+        let empty_chunks: Vec<(i64, String)> = sqlx::query_as(
+            r#"
+            SELECT id, file_path
+            FROM video_chunks
+            WHERE
+                id IN (SELECT value FROM json_each(?1))
+                AND id != (SELECT MAX(id) FROM video_chunks)
+                AND id NOT IN (SELECT video_chunk_id FROM imports)
+                AND NOT EXISTS (SELECT 1 FROM frames WHERE frames.video_chunk_id = video_chunks.id)
+            "#,
+        )
+        .bind(serde_json::to_string(&chunk_ids).unwrap_or_default())
+        .fetch_all(&mut *tx)
+        .await?;
 
-        // Parse the tags string into a Vec<String>
-        let audio_results = audio_results_raw
-            .into_iter()
-            .map(|raw| AudioResult {
-                audio_chunk_id: raw.audio_chunk_id,
-                transcription: raw.transcription,
-                timestamp: raw.timestamp,
-                file_path: raw.file_path,
-                offset_index: raw.offset_index,
-                transcription_engine: raw.transcription_engine,
-                tags: raw
-                    .tags
-                    .map(|s| s.split(',').map(String::from).collect())
-                    .unwrap_or_default(),
-                device_name: raw.device_name,
-                device_type: if raw.is_input_device {
-                    DeviceType::Input
-                } else {
-                    DeviceType::Output
-                },
-            })
-            .collect();
+        let empty_chunk_ids: Vec<i64> = empty_chunks.iter().map(|(id, _)| *id).collect();
+        sqlx::query("DELETE FROM video_chunks WHERE id IN (SELECT value FROM json_each(?1))")
+            .bind(serde_json::to_string(&empty_chunk_ids).unwrap_or_default())
+            .execute(&mut *tx)
+            .await?;
 
-        Ok(audio_results)
+        // thumbnails no remaining frame shares
+        let unused_thumbnails: Vec<String> = sqlx::query_scalar(
+            r#"
+            DELETE FROM frame_hashes
+            WHERE
+                md5 IN (SELECT value FROM json_each(?1))
+                AND NOT EXISTS (SELECT 1 FROM frames WHERE frames.thumbnail_md5 = frame_hashes.md5)
+            RETURNING file_path
+            "#,
+        )
+        .bind(serde_json::to_string(&hashes).unwrap_or_default())
+        .fetch_all(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(DeletedContent {
+            ids,
+            file_paths: empty_chunks
+                .into_iter()
+                .map(|(_, path)| path)
+                .chain(unused_thumbnails)
+                .collect(),
+        })
     }
 
-    pub async fn get_frame(&self, frame_id: i64) -> Result<Option<(String, i64)>, sqlx::Error> {
-        sqlx::query_as::<_, (String, i64)>(
+    /// Deletes matching audio chunks with their transcriptions and tags.
+    pub async fn delete_audio(
+        &self,
+        start_time: Option<DateTime<Utc>>,
+        end_time: Option<DateTime<Utc>>,
+    ) -> Result<DeletedContent, sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+
+        let chunks: Vec<(i64, String)> = sqlx::query_as(
             r#"
-            SELECT 
-                video_chunks.file_path,
-                frames.offset_index
-            FROM 
-                frames
-            JOIN 
-                video_chunks ON frames.video_chunk_id = video_chunks.id
-            WHERE 
-                frames.id = ?1
+            SELECT id, file_path
+            FROM audio_chunks
+            WHERE
+                (?1 IS NULL OR timestamp >= ?1)
+                AND (?2 IS NULL OR timestamp <= ?2)
             "#,
         )
-        .bind(frame_id)
-        .fetch_optional(&self.pool)
-        .await
+        .bind(start_time)
+        .bind(end_time)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        let ids: Vec<i64> = chunks.iter().map(|(id, _)| *id).collect();
+        let ids_json = serde_json::to_string(&ids).unwrap_or_default();
+
+        for sql in [
+            "DELETE FROM audio_transcriptions WHERE audio_chunk_id IN (SELECT value FROM json_each(?1))",
+            "DELETE FROM audio_tags WHERE audio_chunk_id IN (SELECT value FROM json_each(?1))",
+            "DELETE FROM chunked_text_entries WHERE audio_chunk_id IN (SELECT value FROM json_each(?1))",
+            "DELETE FROM transcript_corrections WHERE audio_chunk_id IN (SELECT value FROM json_each(?1))",
+            "DELETE FROM audio_chunks WHERE id IN (SELECT value FROM json_each(?1))",
+        ] {
+            sqlx::query(sql).bind(&ids_json).execute(&mut *tx).await?;
+        }
+
+        tx.commit().await?;
+        Ok(DeletedContent {
+            ids,
+            file_paths: chunks.into_iter().map(|(_, path)| path).collect(),
+        })
     }
 
     pub async fn count_search_results(
@@ -664,11 +2632,15 @@ impl DatabaseManager {
         window_name: Option<&str>,
         min_length: Option<usize>,
         max_length: Option<usize>,
+        session_id: Option<&str>,
     ) -> Result<usize, sqlx::Error> {
         let mut total_count = 0;
 
-        // If app_name or window_name is specified, only count OCR results
-        if app_name.is_some() || window_name.is_some() {
+        // If app_name, window_name or a screen content type is specified, only count OCR results
+        if app_name.is_some()
+            || window_name.is_some()
+            || content_type.screen_content_type().is_some()
+        {
             let ocr_count = self
                 .count_ocr_results(
                     query,
@@ -678,6 +2650,8 @@ impl DatabaseManager {
                     window_name,
                     min_length,
                     max_length,
+                    session_id,
+                    content_type.screen_content_type(),
                 )
                 .await?;
             total_count += ocr_count;
@@ -687,6 +2661,7 @@ impl DatabaseManager {
                 let ocr_count = self
                     .count_ocr_results(
                         query, start_time, end_time, None, None, min_length, max_length,
+                        session_id, None,
                     )
                     .await?;
                 total_count += ocr_count;
@@ -694,7 +2669,9 @@ impl DatabaseManager {
 
             if content_type == ContentType::All || content_type == ContentType::Audio {
                 let audio_count = self
-                    .count_audio_results(query, start_time, end_time, min_length, max_length)
+                    .count_audio_results(
+                        query, start_time, end_time, min_length, max_length, session_id,
+                    )
                     .await?;
                 total_count += audio_count;
             }
@@ -712,6 +2689,8 @@ impl DatabaseManager {
         window_name: Option<&str>,
         min_length: Option<usize>,
         max_length: Option<usize>,
+        session_id: Option<&str>,
+        screen_content_type: Option<ScreenContentType>,
     ) -> Result<usize, sqlx::Error> {
         let sql = r#"
             SELECT COUNT(*)
@@ -726,6 +2705,8 @@ impl DatabaseManager {
                 AND (?5 IS NULL OR LENGTH(ocr_text.text) <= ?5)
                 AND (?6 IS NULL OR ocr_text.app_name LIKE '%' || ?6 || '%' COLLATE NOCASE)
                 AND (?7 IS NULL OR ocr_text.window_name LIKE '%' || ?7 || '%' COLLATE NOCASE)
+                AND (?8 IS NULL OR frames.session_id = ?8)
+                AND (?9 IS NULL OR ocr_text.content_type = ?9)
         "#
         .to_string();
 
@@ -736,7 +2717,9 @@ impl DatabaseManager {
             .bind(min_length.map(|l| l as i64))
             .bind(max_length.map(|l| l as i64))
             .bind(app_name)
-            .bind(window_name);
+            .bind(window_name)
+            .bind(session_id)
+            .bind(screen_content_type.map(|c| c.as_str()));
 
         let (count,) = query.fetch_one(&self.pool).await?;
         Ok(count as usize)
@@ -748,29 +2731,316 @@ impl DatabaseManager {
         end_time: Option<DateTime<Utc>>,
         min_length: Option<usize>,
         max_length: Option<usize>,
+        session_id: Option<&str>,
     ) -> Result<usize, sqlx::Error> {
-        let sql = r#"
+        let sql = format!(
+            r#"
             SELECT COUNT(*)
             FROM audio_transcriptions
             JOIN audio_chunks ON audio_transcriptions.audio_chunk_id = audio_chunks.id
             WHERE 
-                (?1 = '' OR audio_transcriptions.transcription LIKE '%' || ?1 || '%' COLLATE NOCASE)
+                {AUDIO_TEXT_MATCH}
+                AND {CURRENT_TRANSCRIPTION}
                 AND (?2 IS NULL OR audio_transcriptions.timestamp >= ?2)
                 AND (?3 IS NULL OR audio_transcriptions.timestamp <= ?3)
                 AND (?4 IS NULL OR LENGTH(audio_transcriptions.transcription) >= ?4)
                 AND (?5 IS NULL OR LENGTH(audio_transcriptions.transcription) <= ?5)
-        "#;
+                AND (?6 IS NULL OR audio_chunks.session_id = ?6)
+        "#
+        );
 
-        let query = sqlx::query_as::<_, (i64,)>(sql)
+        let query = sqlx::query_as::<_, (i64,)>(&sql)
             .bind(query)
             .bind(start_time)
             .bind(end_time)
             .bind(min_length.map(|l| l as i64))
-            .bind(max_length.map(|l| l as i64));
+            .bind(max_length.map(|l| l as i64))
+            .bind(session_id);
 
         let (count,) = query.fetch_one(&self.pool).await?;
         Ok(count as usize)
     }
+    /// Closes any session left open (e.g. after a crash) and opens a new one.
+    pub async fn start_session(&self) -> Result<String, sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+        let now = Utc::now();
+        sqlx::query("UPDATE sessions SET end_time = ?1 WHERE end_time IS NULL")
+            .bind(now)
+            .execute(&mut *tx)
+            .await?;
+        let id = Uuid::new_v4().to_string();
+        sqlx::query("INSERT INTO sessions (id, start_time) VALUES (?1, ?2)")
+            .bind(&id)
+            .bind(now)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+        Ok(id)
+    }
+
+    pub async fn end_session(&self, session_id: &str) -> Result<(), sqlx::Error> {
+        sqlx::query("UPDATE sessions SET end_time = ?1 WHERE id = ?2 AND end_time IS NULL")
+            .bind(Utc::now())
+            .bind(session_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Frames, audio and the dominant app per `resolution` bucket between `start` and `end`,
+    /// `audio_chunk_duration` is counted for each audio chunk starting in a bucket.
+    pub async fn get_timeline(
+        &self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        resolution: TimelineResolution,
+        audio_chunk_duration: Duration,
+    ) -> Result<Vec<TimelineBucket>, sqlx::Error> {
+        sqlx::query_as::<_, TimelineBucket>(
+            r#"
+            WITH
+            ocr AS (
+                SELECT strftime(?3, frames.timestamp) AS bucket, ocr_text.app_name, ocr_text.text
+                FROM frames
+                JOIN ocr_text ON ocr_text.frame_id = frames.id
+                WHERE frames.timestamp >= ?1 AND frames.timestamp <= ?2
+            ),
+            frame_counts AS (
+                SELECT strftime(?3, timestamp) AS bucket, COUNT(*) AS frame_count
+                FROM frames
+                WHERE timestamp >= ?1 AND timestamp <= ?2
+                GROUP BY bucket
+            ),
+            audio_counts AS (
+                SELECT strftime(?3, timestamp) AS bucket, COUNT(*) AS audio_chunks
+                FROM audio_chunks
+                WHERE timestamp >= ?1 AND timestamp <= ?2
+                GROUP BY bucket
+            ),
+            apps AS (
+                SELECT
+                    bucket,
+                    app_name,
+                    ROW_NUMBER() OVER (PARTITION BY bucket ORDER BY COUNT(*) DESC, app_name) AS rank
+                FROM ocr
+                WHERE app_name != ''
+                GROUP BY bucket, app_name
+            ),
+            snippets AS (
+                SELECT
+                    bucket,
+                    substr(text, 1, ?4) AS text_snippet,
+                    ROW_NUMBER() OVER (PARTITION BY bucket ORDER BY length(text) DESC) AS rank
+                FROM ocr
+                WHERE text != ''
+            ),
+            buckets AS (
+                SELECT bucket FROM frame_counts UNION SELECT bucket FROM audio_counts
+            )
+            SELECT
+                buckets.bucket AS bucket_ts,
+                COALESCE(frame_counts.frame_count, 0) AS frame_count,
+                COALESCE(audio_counts.audio_chunks, 0) * ?5 AS audio_duration_sec,
+                apps.app_name AS dominant_app,
+                snippets.text_snippet
+            FROM buckets
+            LEFT JOIN frame_counts ON frame_counts.bucket = buckets.bucket
+            LEFT JOIN audio_counts ON audio_counts.bucket = buckets.bucket
+            LEFT JOIN apps ON apps.bucket = buckets.bucket AND apps.rank = 1
+            LEFT JOIN snippets ON snippets.bucket = buckets.bucket AND snippets.rank = 1
+            ORDER BY buckets.bucket
+            "#,
+        )
+        .bind(start)
+        .bind(end)
+        .bind(resolution.strftime_format())
+        .bind(TIMELINE_SNIPPET_CHARS)
+        .bind(audio_chunk_duration.as_secs_f64())
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    /// Apps by time focused between `start` and `end`, most used first. Every focused frame counts
+    /// until the next one, up to `max_gap` so that idle time or a stopped recorder is not.
+    pub async fn get_app_screen_time(
+        &self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        max_gap: Duration,
+        limit: u32,
+    ) -> Result<Vec<AppScreenTime>, sqlx::Error> {
+        sqlx::query_as::<_, AppScreenTime>(
+            r#"
+            WITH focused AS (
+                SELECT
+                    ocr_text.app_name,
+                    (julianday(LEAD(frames.timestamp) OVER (ORDER BY frames.timestamp, frames.id))
+                        - julianday(frames.timestamp)) * 86400 AS gap
+                FROM frames
+                JOIN ocr_text ON ocr_text.frame_id = frames.id
+                WHERE ocr_text.focused = 1 AND frames.timestamp >= ?1 AND frames.timestamp < ?2
+            )
+            SELECT app_name, SUM(MIN(COALESCE(gap, 0), ?3)) AS seconds
+            FROM focused
+            WHERE app_name != ''
+            GROUP BY app_name
+            ORDER BY seconds DESC, app_name
+            LIMIT ?4
+            "#,
+        )
+        .bind(start)
+        .bind(end)
+        .bind(max_gap.as_secs_f64())
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    /// Ocr texts of the frames from `start` up to `end`, read as the stream is polled.
+    pub fn stream_ocr_texts(
+        &self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> impl Stream<Item = Result<String, sqlx::Error>> + '_ {
+        sqlx::query_scalar(
+            r#"
+            SELECT ocr_text.text
+            FROM ocr_text
+            JOIN frames ON frames.id = ocr_text.frame_id
+            WHERE frames.timestamp >= ?1 AND frames.timestamp < ?2
+            "#,
+        )
+        .bind(start)
+        .bind(end)
+        .fetch(&self.pool)
+    }
+
+    /// Up to `limit` frames between `start` and `end` (both included) with an id above
+    /// `after_id`, by id.
+    pub async fn get_frames_for_export(
+        &self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        after_id: i64,
+        limit: u32,
+    ) -> Result<Vec<FrameExportRow>, sqlx::Error> {
+        sqlx::query_as(
+            r#"
+            SELECT
+                frames.id AS frame_id,
+                frames.timestamp,
+                video_chunks.file_path,
+                frames.offset_index,
+                ocr_text.app_name,
+                ocr_text.window_name,
+                ocr_text.text,
+                ocr_text.focused
+            FROM frames
+            JOIN video_chunks ON video_chunks.id = frames.video_chunk_id
+            LEFT JOIN ocr_text ON ocr_text.frame_id = frames.id
+            WHERE frames.id > ?3 AND frames.timestamp >= ?1 AND frames.timestamp <= ?2
+            ORDER BY frames.id
+            LIMIT ?4
+            "#,
+        )
+        .bind(start)
+        .bind(end)
+        .bind(after_id)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    /// Frames recorded between `start` and `end`.
+    pub async fn count_frames(
+        &self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<i64, sqlx::Error> {
+        sqlx::query_scalar("SELECT COUNT(*) FROM frames WHERE timestamp >= ?1 AND timestamp < ?2")
+            .bind(start)
+            .bind(end)
+            .fetch_one(&self.pool)
+            .await
+    }
+
+    pub async fn get_sessions(&self, limit: u32, offset: u32) -> Result<Vec<Session>, sqlx::Error> {
+        sqlx::query_as::<_, Session>(
+            "SELECT id, name, start_time, end_time FROM sessions ORDER BY start_time DESC LIMIT ?1 OFFSET ?2",
+        )
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    /// Returns `None` if no session has this id.
+    pub async fn rename_session(
+        &self,
+        session_id: &str,
+        name: Option<&str>,
+    ) -> Result<Option<Session>, sqlx::Error> {
+        sqlx::query("UPDATE sessions SET name = ?1 WHERE id = ?2")
+            .bind(name)
+            .bind(session_id)
+            .execute(&self.pool)
+            .await?;
+        sqlx::query_as::<_, Session>(
+            "SELECT id, name, start_time, end_time FROM sessions WHERE id = ?1",
+        )
+        .bind(session_id)
+        .fetch_optional(&self.pool)
+        .await
+    }
+
+    /// Stores a request duration, dropping entries older than a week.
+    pub async fn insert_request_log(
+        &self,
+        path: &str,
+        method: &str,
+        status: u16,
+        duration_ms: f64,
+    ) -> Result<(), sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+        let now = Utc::now();
+        sqlx::query(
+            "INSERT INTO request_log (path, method, status, duration_ms, timestamp) VALUES (?1, ?2, ?3, ?4, ?5)",
+        )
+        .bind(path)
+        .bind(method)
+        .bind(status)
+        .bind(duration_ms)
+        .bind(now)
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query("DELETE FROM request_log WHERE timestamp < ?1")
+            .bind(now - chrono::Duration::days(7))
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    pub async fn get_slow_requests(
+        &self,
+        since: DateTime<Utc>,
+        limit: u32,
+    ) -> Result<Vec<RequestLogEntry>, sqlx::Error> {
+        sqlx::query_as::<_, RequestLogEntry>(
+            r#"
+            SELECT path, method, status, duration_ms, timestamp
+            FROM request_log
+            WHERE timestamp >= ?1
+            ORDER BY duration_ms DESC
+            LIMIT ?2
+            "#,
+        )
+        .bind(since)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await
+    }
+
     pub async fn get_latest_timestamps(
         &self,
     ) -> Result<(Option<DateTime<Utc>>, Option<DateTime<Utc>>), sqlx::Error> {
@@ -787,6 +3057,20 @@ impl DatabaseManager {
         Ok((latest_frame.map(|f| f.0), latest_audio.map(|a| a.0)))
     }
 
+    pub async fn get_content_aggregates(&self) -> Result<ContentAggregates, sqlx::Error> {
+        sqlx::query_as::<_, ContentAggregates>(
+            r#"
+            SELECT
+                (SELECT COUNT(*) FROM frames) as total_frames,
+                (SELECT COUNT(*) FROM audio_chunks) as total_audio_chunks,
+                (SELECT COUNT(*) FROM ocr_text) as total_ocr_entries,
+                (SELECT COALESCE(SUM(LENGTH(text)), 0) FROM ocr_text) as total_ocr_text_chars
+            "#,
+        )
+        .fetch_one(&self.pool)
+        .await
+    }
+
     // Modify the insert_chunked_text method to handle both OCR and audio transcriptions
     pub async fn insert_chunked_text(
         &self,
@@ -1192,6 +3476,8 @@ impl Clone for DatabaseManager {
     fn clone(&self) -> Self {
         DatabaseManager {
             pool: self.pool.clone(),
+            new_rows: Arc::clone(&self.new_rows),
+            read_only: self.read_only,
         }
     }
 }
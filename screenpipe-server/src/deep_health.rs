@@ -0,0 +1,180 @@
+//! `GET /health/deep`: unlike `/health`, which reports what was recorded lately, each subsystem
+//! is actively exercised. Checks run concurrently and each has its own timeout, so the answer
+//! comes back within [`DEEP_HEALTH_TIMEOUT`] for liveness probes.
+
+use crate::DatabaseManager;
+use image::DynamicImage;
+use screenpipe_audio::default_input_device;
+use screenpipe_vision::{monitor::list_monitors, utils::capture_screenshot, OcrEngine};
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+pub const DEEP_HEALTH_TIMEOUT: Duration = Duration::from_secs(5);
+const DATABASE_TIMEOUT: Duration = Duration::from_secs(2);
+const CAPTURE_TIMEOUT: Duration = Duration::from_secs(2);
+// capture and ocr run one after the other, together under DEEP_HEALTH_TIMEOUT
+const OCR_TIMEOUT: Duration = Duration::from_millis(2500);
+const AUDIO_TIMEOUT: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubsystemHealth {
+    pub ok: bool,
+    pub latency_ms: u64,
+    pub error: Option<String>,
+    /// What was not checked and why, e.g. writes on the `--read-only-port` server
+    pub skipped: Option<String>,
+}
+
+/// A subsystem turned off with `--disable-vision` or `--disable-audio` is `null` and doesn't
+/// count against `healthy`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeepHealthResponse {
+    pub healthy: bool,
+    pub database: SubsystemHealth,
+    pub capture: Option<SubsystemHealth>,
+    pub ocr: Option<SubsystemHealth>,
+    pub audio: Option<SubsystemHealth>,
+}
+
+/// Runs `check` in a task of its own, a panic (capture apis panic on missing permissions) or a
+/// timeout is a failure. A check that times out is aborted rather than left running.
+async fn timed<T, F>(timeout: Duration, check: F) -> (SubsystemHealth, Option<T>)
+where
+    T: Send + 'static,
+    F: Future<Output = Result<T, String>> + Send + 'static,
+{
+    let start = Instant::now();
+    let mut handle = tokio::spawn(check);
+    let result = match tokio::time::timeout(timeout, &mut handle).await {
+        Ok(Ok(result)) => result,
+        Ok(Err(e)) => Err(format!("check panicked: {}", e)),
+        Err(_) => {
+            handle.abort();
+            Err(format!("no answer within {:?}", timeout))
+        }
+    };
+    let latency_ms = start.elapsed().as_millis() as u64;
+    match result {
+        Ok(value) => (
+            SubsystemHealth {
+                ok: true,
+                latency_ms,
+                error: None,
+                skipped: None,
+            },
+            Some(value),
+        ),
+        Err(e) => (
+            SubsystemHealth {
+                ok: false,
+                latency_ms,
+                error: Some(e),
+                skipped: None,
+            },
+            None,
+        ),
+    }
+}
+
+async fn capture_frame() -> Result<DynamicImage, String> {
+    let monitor = list_monitors()
+        .await
+        .into_iter()
+        .next()
+        .ok_or("no monitor found")?;
+    let (image, _, _, _) = capture_screenshot(&monitor, &[], &[])
+        .await
+        .map_err(|e| format!("failed to capture screen: {}", e))?;
+    Ok(image)
+}
+
+async fn check_vision(ocr_engine: OcrEngine) -> (SubsystemHealth, SubsystemHealth) {
+    let (capture, image) = timed(CAPTURE_TIMEOUT, capture_frame()).await;
+    let ocr = match image {
+        Some(image) => {
+            timed(OCR_TIMEOUT, async move {
+                ocr_engine
+                    .perform_ocr(&image)
+                    .await
+                    .map_err(|e| format!("{:?} ocr failed: {}", ocr_engine, e))
+            })
+            .await
+            .0
+        }
+        None => SubsystemHealth {
+            ok: false,
+            latency_ms: 0,
+            error: Some("no frame captured to run ocr on".to_string()),
+            skipped: None,
+        },
+    };
+    (capture, ocr)
+}
+
+async fn check_audio() -> Result<(), String> {
+    default_input_device()
+        .map(|_| ())
+        .map_err(|e| format!("no input device: {}", e))
+}
+
+pub async fn check_deep_health(
+    db: Arc<DatabaseManager>,
+    ocr_engine: OcrEngine,
+    vision_disabled: bool,
+    audio_disabled: bool,
+) -> DeepHealthResponse {
+    let database = async move {
+        if db.is_read_only() {
+            let mut health = timed(DATABASE_TIMEOUT, async move {
+                db.check_read()
+                    .await
+                    .map_err(|e| format!("database check failed: {}", e))
+            })
+            .await
+            .0;
+            health.skipped = Some("write check, this server has a read-only pool".to_string());
+            return health;
+        }
+        timed(DATABASE_TIMEOUT, async move {
+            db.check_read_write()
+                .await
+                .map_err(|e| format!("database check failed: {}", e))
+        })
+        .await
+        .0
+    };
+    let vision = async move {
+        if vision_disabled {
+            None
+        } else {
+            Some(check_vision(ocr_engine).await)
+        }
+    };
+    let audio = async move {
+        if audio_disabled {
+            None
+        } else {
+            Some(timed(AUDIO_TIMEOUT, check_audio()).await.0)
+        }
+    };
+    let (database, vision, audio) = tokio::join!(database, vision, audio);
+    let (capture, ocr) = match vision {
+        Some((capture, ocr)) => (Some(capture), Some(ocr)),
+        None => (None, None),
+    };
+
+    let healthy = database.ok
+        && [&capture, &ocr, &audio]
+            .into_iter()
+            .flatten()
+            .all(|subsystem| subsystem.ok);
+    DeepHealthResponse {
+        healthy,
+        database,
+        capture,
+        ocr,
+        audio,
+    }
+}
@@ -0,0 +1,304 @@
+use crate::db::FrameInfo;
+use crate::frame_format::CaptureFormat;
+use crate::request_id::spawn_in_current_span;
+use crate::video::{start_ffmpeg_process, MAX_FPS};
+use crate::video_utils::extract_frame_png;
+use anyhow::Result;
+use axum::body::Body;
+use chrono::{DateTime, Utc};
+use log::{debug, error, info};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::process::{Child, ChildStderr, ChildStdin};
+use tokio::sync::RwLock;
+use uuid::Uuid;
+
+/// Exports with more frames than this run as a background job instead of being streamed.
+pub const MAX_STREAMED_FRAMES: usize = 300;
+/// Keyframe interval of exports longer than `MAX_STREAMED_FRAMES` when the request has none,
+/// shorter ones get a keyframe on every frame so any frame can be seeked to exactly.
+pub const DEFAULT_KEYFRAME_INTERVAL: u32 = 30;
+const EXPORT_TTL: Duration = Duration::from_secs(60 * 60);
+const CLEANUP_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+fn default_export_fps() -> f64 {
+    1.0
+}
+
+#[derive(Deserialize)]
+pub struct ExportVideoRequest {
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+    #[serde(default = "default_export_fps")]
+    pub fps: f64,
+    pub monitor_id: u32,
+    /// Frames between keyframes of long exports, see `keyframe_interval`
+    #[serde(default)]
+    pub keyframe_interval: Option<u32>,
+}
+
+/// `-g` of an export of `frame_count` frames: every frame of a short clip is a keyframe, long
+/// ones trade seek accuracy for size with `requested` or `DEFAULT_KEYFRAME_INTERVAL`.
+pub fn keyframe_interval(frame_count: usize, requested: Option<u32>) -> u32 {
+    if frame_count <= MAX_STREAMED_FRAMES {
+        1
+    } else {
+        requested.unwrap_or(DEFAULT_KEYFRAME_INTERVAL).max(1)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct FrameTimestamp {
+    pub frame_id: i64,
+    /// Seconds from the start of the mp4
+    pub pts: f64,
+}
+
+/// The `.json` written next to an exported mp4, mapping its frames back to the database.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ExportIndex {
+    pub fps: f64,
+    pub keyframe_interval: u32,
+    pub frames: Vec<FrameTimestamp>,
+}
+
+impl ExportIndex {
+    /// `frame_ids` in the order they were encoded, ffmpeg giving the nth frame `n / fps`.
+    pub fn new(frame_ids: &[i64], fps: f64, keyframe_interval: u32) -> Self {
+        // ffmpeg encodes at most MAX_FPS
+        let fps = fps.min(MAX_FPS);
+        ExportIndex {
+            fps,
+            keyframe_interval,
+            frames: frame_ids
+                .iter()
+                .enumerate()
+                .map(|(n, &frame_id)| FrameTimestamp {
+                    frame_id,
+                    pts: n as f64 / fps,
+                })
+                .collect(),
+        }
+    }
+}
+
+pub fn index_path(mp4_path: &Path) -> PathBuf {
+    mp4_path.with_extension("json")
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportStatus {
+    Running,
+    Completed,
+    Failed,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ExportJob {
+    pub id: String,
+    pub status: ExportStatus,
+    pub frame_count: usize,
+    pub file_path: Option<String>,
+    /// `ExportIndex` of the mp4
+    pub index_path: Option<String>,
+    pub error: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Background video exports, removed together with their mp4 and index after an hour.
+pub struct ExportJobs {
+    export_dir: PathBuf,
+    jobs: RwLock<HashMap<String, ExportJob>>,
+}
+
+impl ExportJobs {
+    pub fn new(export_dir: PathBuf) -> Self {
+        ExportJobs {
+            export_dir,
+            jobs: RwLock::new(HashMap::new()),
+        }
+    }
+
+    pub fn start_cleanup(self: &Arc<Self>) {
+        let jobs = Arc::clone(self);
+        tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(CLEANUP_INTERVAL).await;
+                jobs.cleanup().await;
+            }
+        });
+    }
+
+    pub async fn get(&self, id: &str) -> Option<ExportJob> {
+        self.jobs.read().await.get(id).cloned()
+    }
+
+    pub async fn spawn(
+        self: &Arc<Self>,
+        frames: Vec<FrameInfo>,
+        fps: f64,
+        keyframe_interval: u32,
+    ) -> Result<ExportJob> {
+        tokio::fs::create_dir_all(&self.export_dir).await?;
+
+        let id = Uuid::new_v4().to_string();
+        let output_path = self.export_dir.join(format!("export_{}.mp4", id));
+        let job = ExportJob {
+            id: id.clone(),
+            status: ExportStatus::Running,
+            frame_count: frames.len(),
+            file_path: None,
+            index_path: None,
+            error: None,
+            created_at: Utc::now(),
+        };
+        self.jobs.write().await.insert(id.clone(), job.clone());
+
+        let jobs = Arc::clone(self);
+        spawn_in_current_span(async move {
+            let result = export_to_file(frames, fps, keyframe_interval, &output_path).await;
+            if let Some(job) = jobs.jobs.write().await.get_mut(&id) {
+                match result {
+                    Ok(index_path) => {
+                        info!("export job {} completed: {}", id, output_path.display());
+                        job.status = ExportStatus::Completed;
+                        job.file_path = Some(output_path.to_string_lossy().into_owned());
+                        job.index_path = Some(index_path.to_string_lossy().into_owned());
+                    }
+                    Err(e) => {
+                        error!("export job {} failed: {}", id, e);
+                        job.status = ExportStatus::Failed;
+                        job.error = Some(e.to_string());
+                    }
+                }
+            }
+        });
+
+        Ok(job)
+    }
+
+    async fn cleanup(&self) {
+        let now = Utc::now();
+        let mut jobs = self.jobs.write().await;
+        let expired: Vec<String> = jobs
+            .values()
+            .filter(|job| {
+                job.status != ExportStatus::Running
+                    && (now - job.created_at).to_std().unwrap_or_default() > EXPORT_TTL
+            })
+            .map(|job| job.id.clone())
+            .collect();
+
+        for id in expired {
+            let Some(job) = jobs.remove(&id) else {
+                continue;
+            };
+            for path in job.file_path.iter().chain(&job.index_path) {
+                if let Err(e) = tokio::fs::remove_file(path).await {
+                    error!("failed to remove expired export {}: {}", path, e);
+                }
+            }
+            debug!("removed expired export job {}", id);
+        }
+    }
+}
+
+/// Encodes the frames on the fly and returns the fragmented mp4 as a chunked body.
+pub async fn stream_export(
+    frames: Vec<FrameInfo>,
+    fps: f64,
+    keyframe_interval: u32,
+) -> Result<Body> {
+    let mut child =
+        start_ffmpeg_process("-", fps, CaptureFormat::Png, Some(keyframe_interval)).await?;
+    let stdout = child.stdout.take().expect("failed to open stdout");
+
+    spawn_in_current_span(async move {
+        if let Err(e) = feed_frames(&mut child, frames).await {
+            error!("video export failed: {}", e);
+        }
+    });
+
+    let stream = futures::stream::unfold(Some(stdout), |stdout| async move {
+        let mut stdout = stdout?;
+        let mut buffer = vec![0u8; 64 * 1024];
+        match stdout.read(&mut buffer).await {
+            Ok(0) => None,
+            Ok(n) => {
+                buffer.truncate(n);
+                Some((Ok::<_, std::io::Error>(buffer), Some(stdout)))
+            }
+            Err(e) => Some((Err(e), None)),
+        }
+    });
+
+    Ok(Body::from_stream(stream))
+}
+
+/// Writes the mp4 and its `ExportIndex`, returns the path of the index.
+async fn export_to_file(
+    frames: Vec<FrameInfo>,
+    fps: f64,
+    keyframe_interval: u32,
+    output_path: &Path,
+) -> Result<PathBuf> {
+    let mut child = start_ffmpeg_process(
+        &output_path.to_string_lossy(),
+        fps,
+        CaptureFormat::Png,
+        Some(keyframe_interval),
+    )
+    .await?;
+    // output goes to the file, nothing to read
+    drop(child.stdout.take());
+    let frame_ids = feed_frames(&mut child, frames).await?;
+
+    let index = ExportIndex::new(&frame_ids, fps, keyframe_interval);
+    let index_path = index_path(output_path);
+    tokio::fs::write(&index_path, serde_json::to_vec(&index)?).await?;
+    Ok(index_path)
+}
+
+/// Returns the ids of the frames encoded, in order.
+async fn feed_frames(child: &mut Child, frames: Vec<FrameInfo>) -> Result<Vec<i64>> {
+    let stdin = child.stdin.take().expect("failed to open stdin");
+    let stderr = child.stderr.take().expect("failed to open stderr");
+    spawn_in_current_span(log_ffmpeg_stderr(stderr));
+
+    let frame_ids = write_frames(stdin, frames).await?;
+
+    let status = child.wait().await?;
+    if !status.success() {
+        return Err(anyhow::anyhow!("ffmpeg exited with {}", status));
+    }
+    Ok(frame_ids)
+}
+
+async fn write_frames(mut stdin: ChildStdin, frames: Vec<FrameInfo>) -> Result<Vec<i64>> {
+    let mut frame_ids = Vec::with_capacity(frames.len());
+    for frame in frames {
+        match extract_frame_png(&frame.file_path, frame.offset_index).await {
+            Ok(png) => {
+                stdin.write_all(&png).await?;
+                frame_ids.push(frame.frame_id);
+            }
+            // the chunk may still be recording or was deleted, keep going
+            Err(e) => debug!("skipping frame {} in export: {}", frame.frame_id, e),
+        }
+    }
+    // closing stdin lets ffmpeg finish the file
+    drop(stdin);
+    Ok(frame_ids)
+}
+
+async fn log_ffmpeg_stderr(stderr: ChildStderr) {
+    let mut lines = BufReader::new(stderr).lines();
+    while let Ok(Some(line)) = lines.next_line().await {
+        debug!("FFmpeg: {}", line);
+    }
+}
@@ -4,6 +4,7 @@ use rust_stemmers::{Algorithm, Stemmer};
 use std::collections::{HashMap, HashSet};
 use std::error::Error;
 use log::debug;
+use screenpipe_audio::{AudioDevice, DeviceType};
 
 fn keep_least_similar(chunks: &[String], percentage: f64) -> Vec<usize> {
     if chunks.is_empty() {
@@ -100,4 +101,46 @@ pub async fn filter_texts(timestamp: &str, memory_source: &str, pool: &SqlitePoo
     let output = kept_texts.iter().map(|s| s.as_str()).collect::<Vec<&str>>().join("\n");
 
     Ok(output)
-}
\ No newline at end of file
+}
+
+/// Case-insensitive glob match supporting `*` (any sequence) and `?` (any single character).
+pub fn window_title_matches(title: &str, pattern: &str) -> bool {
+    let title: Vec<char> = title.to_lowercase().chars().collect();
+    let pattern: Vec<char> = pattern.to_lowercase().chars().collect();
+
+    let (mut t, mut p) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+    while t < title.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == title[t]) {
+            t += 1;
+            p += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            backtrack = Some((p, t));
+            p += 1;
+        } else if let Some((star_p, star_t)) = backtrack {
+            // let the last `*` swallow one more character
+            backtrack = Some((star_p, star_t + 1));
+            p = star_p + 1;
+            t = star_t + 1;
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// Returns the first device of `device_type` whose name matches the earliest possible pattern
+/// of `priorities` (same glob syntax as [`window_title_matches`]), with the pattern that matched.
+pub fn select_prioritized_device<'a>(
+    devices: &'a [AudioDevice],
+    device_type: &DeviceType,
+    priorities: &'a [String],
+) -> Option<(&'a AudioDevice, &'a str)> {
+    priorities.iter().find_map(|pattern| {
+        devices
+            .iter()
+            .filter(|device| &device.device_type == device_type)
+            .find(|device| window_title_matches(&device.name, pattern))
+            .map(|device| (device, pattern.as_str()))
+    })
+}
@@ -0,0 +1,66 @@
+//! `--frame-buffer-dir`: video chunks are recorded on a faster disk, e.g. a tmpfs mount, and
+//! moved to the frames dir once finished, keeping slow storage out of the way of capture.
+
+use crate::DatabaseManager;
+use anyhow::Result;
+use log::{info, warn};
+use std::io;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone)]
+pub struct FrameBuffer {
+    /// Where the chunk being recorded is written
+    pub buffer_dir: PathBuf,
+    /// Where finished chunks are kept, see `--frames-dir`
+    pub frames_dir: PathBuf,
+}
+
+/// Renames `from` to `to`, copying then deleting it when they are on different filesystems.
+pub async fn move_file(from: &Path, to: &Path) -> io::Result<()> {
+    if tokio::fs::rename(from, to).await.is_ok() {
+        return Ok(());
+    }
+    tokio::fs::copy(from, to).await?;
+    tokio::fs::remove_file(from).await
+}
+
+impl FrameBuffer {
+    /// Moves a finished chunk to the frames dir and returns its new path.
+    pub async fn flush(&self, chunk: &Path) -> io::Result<PathBuf> {
+        let file_name = chunk.file_name().ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidInput, "chunk path has no file name")
+        })?;
+        let target = self.frames_dir.join(file_name);
+        move_file(chunk, &target).await?;
+        Ok(target)
+    }
+
+    /// Moves the chunks a crash left in the buffer to the frames dir, with their paths in the
+    /// database. Returns how many were moved.
+    pub async fn flush_leftovers(&self, db: &DatabaseManager) -> Result<usize> {
+        let mut entries = tokio::fs::read_dir(&self.buffer_dir).await?;
+        let mut moved = 0;
+        while let Some(entry) = entries.next_entry().await? {
+            let chunk = entry.path();
+            if !chunk.extension().is_some_and(|ext| ext == "mp4") {
+                continue;
+            }
+            match self.flush(&chunk).await {
+                Ok(target) => {
+                    db.update_video_chunk_path(&chunk.to_string_lossy(), &target.to_string_lossy())
+                        .await?;
+                    moved += 1;
+                }
+                Err(e) => warn!(
+                    "failed to move {} out of the frame buffer: {}",
+                    chunk.display(),
+                    e
+                ),
+            }
+        }
+        if moved > 0 {
+            info!("moved {} video chunks left in the frame buffer", moved);
+        }
+        Ok(moved)
+    }
+}
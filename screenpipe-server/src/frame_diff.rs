@@ -0,0 +1,37 @@
+use serde::{Deserialize, Serialize};
+use similar::{ChangeTag, TextDiff};
+
+/// Lines of context around each change of a diff.
+const CONTEXT_LINES: usize = 3;
+
+/// `GET /frames/:frame_id/diff`: what changed in the ocr text of a frame since the previous frame
+/// of the same window, as a unified diff.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FrameDiff {
+    pub frame_id: i64,
+    /// `None` for the first frame of a window, diffed against no text
+    pub previous_frame_id: Option<i64>,
+    pub diff: String,
+    pub lines_added: i64,
+    pub lines_removed: i64,
+}
+
+/// Unified diff from `previous` to `current` with the number of lines added and removed, the
+/// diff is empty when the texts are the same.
+pub fn diff_text(previous: &str, current: &str) -> (String, i64, i64) {
+    let diff = TextDiff::from_lines(previous, current);
+    let (mut added, mut removed) = (0, 0);
+    for change in diff.iter_all_changes() {
+        match change.tag() {
+            ChangeTag::Insert => added += 1,
+            ChangeTag::Delete => removed += 1,
+            ChangeTag::Equal => {}
+        }
+    }
+    let unified = diff
+        .unified_diff()
+        .context_radius(CONTEXT_LINES)
+        .header("previous", "current")
+        .to_string();
+    (unified, added, removed)
+}
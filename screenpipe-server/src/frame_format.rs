@@ -0,0 +1,48 @@
+use anyhow::{anyhow, Result};
+use image::{DynamicImage, ImageFormat};
+
+/// How captured frames are encoded on their way to ffmpeg, see `--capture-format`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CaptureFormat {
+    #[default]
+    Png,
+    /// Lossless too, about as small as png but faster to encode
+    WebpLossless,
+}
+
+impl CaptureFormat {
+    pub fn encode(self, image: &DynamicImage) -> Result<Vec<u8>> {
+        match self {
+            CaptureFormat::Png => {
+                let mut buffer = Vec::new();
+                image.write_to(&mut std::io::Cursor::new(&mut buffer), ImageFormat::Png)?;
+                Ok(buffer)
+            }
+            CaptureFormat::WebpLossless => encode_webp_lossless(image),
+        }
+    }
+
+    /// The `-vcodec` ffmpeg reads the piped frames with.
+    pub fn ffmpeg_codec(self) -> &'static str {
+        match self {
+            CaptureFormat::Png => "png",
+            CaptureFormat::WebpLossless => "webp",
+        }
+    }
+}
+
+fn encode_webp_lossless(image: &DynamicImage) -> Result<Vec<u8>> {
+    // the encoder takes 8 bit rgb and rgba only
+    let encoder = match image {
+        DynamicImage::ImageRgb8(_) | DynamicImage::ImageRgba8(_) => {
+            webp::Encoder::from_image(image)
+                .map_err(|e| anyhow!("webp encoding failed: {}", e))?
+                .encode_lossless()
+        }
+        _ => {
+            let rgba = image.to_rgba8();
+            webp::Encoder::from_rgba(&rgba, rgba.width(), rgba.height()).encode_lossless()
+        }
+    };
+    Ok(encoder.to_vec())
+}
@@ -0,0 +1,53 @@
+/// Largest edit distance between the query and some part of the text still counted as a match.
+pub const MAX_EDIT_DISTANCE: usize = 2;
+
+/// Trigram candidates looked at per fuzzy search, best bm25 rank first.
+pub const MAX_FUZZY_CANDIDATES: u32 = 1000;
+
+/// Queries shorter than this have no trigram to look up.
+pub const MIN_FUZZY_QUERY_LEN: usize = 3;
+
+/// FTS5 query matching rows of the trigram index that share at least one trigram with `query`,
+/// `None` when the query is too short to have one.
+pub fn trigram_match_query(query: &str) -> Option<String> {
+    let chars: Vec<char> = query.trim().to_lowercase().chars().collect();
+    if chars.len() < MIN_FUZZY_QUERY_LEN {
+        return None;
+    }
+    let mut trigrams: Vec<String> = chars.windows(3).map(|w| w.iter().collect()).collect();
+    trigrams.sort();
+    trigrams.dedup();
+    Some(
+        trigrams
+            .iter()
+            .map(|t| format!("\"{}\"", t.replace('"', "\"\"")))
+            .collect::<Vec<_>>()
+            .join(" OR "),
+    )
+}
+
+/// Smallest edit distance between `query` and any substring of `text`, ignoring case.
+pub fn substring_edit_distance(query: &str, text: &str) -> usize {
+    let query: Vec<char> = query.trim().to_lowercase().chars().collect();
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+
+    // levenshtein where the match may start anywhere in the text for free (sellers)
+    let mut previous: Vec<usize> = (0..=query.len()).collect();
+    let mut best = query.len();
+    for &c in &text {
+        let mut current = vec![0; query.len() + 1];
+        for (i, &q) in query.iter().enumerate() {
+            let substitution = previous[i] + usize::from(q != c);
+            current[i + 1] = substitution.min(previous[i + 1] + 1).min(current[i] + 1);
+        }
+        best = best.min(current[query.len()]);
+        previous = current;
+    }
+    best
+}
+
+/// `match_score` of a fuzzy result, 1.0 for an exact match down to 0.0.
+pub fn match_score(query: &str, distance: usize) -> f32 {
+    let len = query.trim().chars().count().max(1);
+    (1.0 - distance as f32 / len as f32).max(0.0)
+}
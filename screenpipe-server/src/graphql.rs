@@ -0,0 +1,255 @@
+use crate::db::{AudioResult, OCRResult};
+use crate::search_rank::SearchOrder;
+use crate::server::{health_check, AppState};
+use crate::{ContentType, SearchResult as DbSearchResult};
+use async_graphql::{Context, EmptySubscription, Object, Result, Schema, SimpleObject, Union};
+use async_graphql_axum::{GraphQLRequest, GraphQLResponse};
+use axum::extract::State;
+use chrono::{DateTime, Utc};
+use screenpipe_audio::DeviceControl;
+use std::sync::atomic::Ordering;
+use std::sync::{Arc, OnceLock};
+
+const DEFAULT_LIMIT: u32 = 20;
+
+/// A frame whose ocr text matched, as in the `OCR` results of `GET /search`.
+#[derive(SimpleObject)]
+pub struct Frame {
+    pub frame_id: i64,
+    pub text: String,
+    pub timestamp: DateTime<Utc>,
+    pub file_path: String,
+    pub offset_index: i64,
+    pub app_name: String,
+    pub window_name: String,
+    pub tags: Vec<String>,
+}
+
+/// An audio transcription that matched, as in the `Audio` results of `GET /search`.
+#[derive(SimpleObject)]
+pub struct AudioChunk {
+    pub audio_chunk_id: i64,
+    pub transcription: String,
+    pub timestamp: DateTime<Utc>,
+    pub file_path: String,
+    pub offset_index: i64,
+    pub device_name: String,
+    pub tags: Vec<String>,
+}
+
+#[derive(Union)]
+pub enum SearchResult {
+    Frame(Frame),
+    AudioChunk(AudioChunk),
+}
+
+#[derive(SimpleObject)]
+pub struct Health {
+    pub status: String,
+    pub last_frame_timestamp: Option<DateTime<Utc>>,
+    pub last_audio_timestamp: Option<DateTime<Utc>>,
+    pub frame_status: String,
+    pub audio_status: String,
+    pub message: String,
+}
+
+impl From<OCRResult> for Frame {
+    fn from(ocr: OCRResult) -> Self {
+        Frame {
+            frame_id: ocr.frame_id,
+            text: ocr.ocr_text,
+            timestamp: ocr.timestamp,
+            file_path: ocr.file_path,
+            offset_index: ocr.offset_index,
+            app_name: ocr.app_name,
+            window_name: ocr.window_name,
+            tags: ocr.tags,
+        }
+    }
+}
+
+impl From<AudioResult> for AudioChunk {
+    fn from(audio: AudioResult) -> Self {
+        AudioChunk {
+            audio_chunk_id: audio.audio_chunk_id,
+            transcription: audio.transcription,
+            timestamp: audio.timestamp,
+            file_path: audio.file_path,
+            offset_index: audio.offset_index,
+            device_name: audio.device_name,
+            tags: audio.tags,
+        }
+    }
+}
+
+impl SearchResult {
+    /// Full text search results have no graphql counterpart yet.
+    fn from_db(result: DbSearchResult) -> Option<Self> {
+        match result {
+            DbSearchResult::OCR(ocr) => Some(SearchResult::Frame(ocr.into())),
+            DbSearchResult::Audio(audio) => Some(SearchResult::AudioChunk(audio.into())),
+            DbSearchResult::FTS(_) => None,
+        }
+    }
+}
+
+async fn search(
+    ctx: &Context<'_>,
+    query: &str,
+    content_type: ContentType,
+    from: Option<DateTime<Utc>>,
+    to: Option<DateTime<Utc>>,
+    limit: u32,
+) -> Result<Vec<SearchResult>> {
+    let state = ctx.data::<Arc<AppState>>()?;
+    let results = state
+        .db
+        .search(
+            query,
+            content_type,
+            limit,
+            0,
+            from,
+            to,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            &SearchOrder::default(),
+        )
+        .await?;
+    Ok(results
+        .into_iter()
+        .filter_map(SearchResult::from_db)
+        .collect())
+}
+
+pub struct QueryRoot;
+
+#[Object]
+impl QueryRoot {
+    async fn search_frames(
+        &self,
+        ctx: &Context<'_>,
+        query: String,
+        from: Option<DateTime<Utc>>,
+        to: Option<DateTime<Utc>>,
+        limit: Option<u32>,
+    ) -> Result<Vec<Frame>> {
+        let results = search(
+            ctx,
+            &query,
+            ContentType::OCR,
+            from,
+            to,
+            limit.unwrap_or(DEFAULT_LIMIT),
+        )
+        .await?;
+        Ok(results
+            .into_iter()
+            .filter_map(|result| match result {
+                SearchResult::Frame(frame) => Some(frame),
+                SearchResult::AudioChunk(_) => None,
+            })
+            .collect())
+    }
+
+    async fn search_audio(
+        &self,
+        ctx: &Context<'_>,
+        query: String,
+        from: Option<DateTime<Utc>>,
+        to: Option<DateTime<Utc>>,
+        limit: Option<u32>,
+    ) -> Result<Vec<AudioChunk>> {
+        let results = search(
+            ctx,
+            &query,
+            ContentType::Audio,
+            from,
+            to,
+            limit.unwrap_or(DEFAULT_LIMIT),
+        )
+        .await?;
+        Ok(results
+            .into_iter()
+            .filter_map(|result| match result {
+                SearchResult::AudioChunk(audio) => Some(audio),
+                SearchResult::Frame(_) => None,
+            })
+            .collect())
+    }
+
+    /// Frames and audio together, like `GET /search` without a content type.
+    async fn search(
+        &self,
+        ctx: &Context<'_>,
+        query: String,
+        from: Option<DateTime<Utc>>,
+        to: Option<DateTime<Utc>>,
+        limit: Option<u32>,
+    ) -> Result<Vec<SearchResult>> {
+        search(
+            ctx,
+            &query,
+            ContentType::All,
+            from,
+            to,
+            limit.unwrap_or(DEFAULT_LIMIT),
+        )
+        .await
+    }
+
+    async fn health(&self, ctx: &Context<'_>) -> Result<Health> {
+        let state = ctx.data::<Arc<AppState>>()?;
+        let health = health_check(State(Arc::clone(state))).await.0;
+        Ok(Health {
+            status: health.status,
+            last_frame_timestamp: health.last_frame_timestamp,
+            last_audio_timestamp: health.last_audio_timestamp,
+            frame_status: health.frame_status,
+            audio_status: health.audio_status,
+            message: health.message,
+        })
+    }
+}
+
+pub struct MutationRoot;
+
+#[Object]
+impl MutationRoot {
+    /// Stops screen capture and every audio device until screenpipe is restarted.
+    async fn pause_recording(&self, ctx: &Context<'_>) -> Result<bool> {
+        let state = ctx.data::<Arc<AppState>>()?;
+        state.vision_control.store(false, Ordering::SeqCst);
+        for device in state.devices_status.read().unwrap().keys() {
+            state.audio_devices_control.push((
+                device.clone(),
+                DeviceControl {
+                    is_running: false,
+                    is_paused: true,
+                },
+            ));
+        }
+        Ok(true)
+    }
+}
+
+pub type GraphQLSchema = Schema<QueryRoot, MutationRoot, EmptySubscription>;
+
+pub fn graphql_schema() -> &'static GraphQLSchema {
+    static SCHEMA: OnceLock<GraphQLSchema> = OnceLock::new();
+    SCHEMA.get_or_init(|| Schema::build(QueryRoot, MutationRoot, EmptySubscription).finish())
+}
+
+pub(crate) async fn graphql_handler(
+    State(state): State<Arc<AppState>>,
+    request: GraphQLRequest,
+) -> GraphQLResponse {
+    graphql_schema()
+        .execute(request.into_inner().data(state))
+        .await
+        .into()
+}
@@ -0,0 +1,81 @@
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+use std::time::Duration;
+
+/// Where the recorder restart loop stands, reported by `GET /health`.
+pub struct HealStatus {
+    retries: AtomicU32,
+    delay_ms: AtomicU64,
+}
+
+impl HealStatus {
+    pub const fn new() -> Self {
+        HealStatus {
+            retries: AtomicU32::new(0),
+            delay_ms: AtomicU64::new(0),
+        }
+    }
+
+    pub fn snapshot(&self) -> HealSnapshot {
+        HealSnapshot {
+            retries: self.retries.load(Ordering::Relaxed),
+            delay_secs: self.delay_ms.load(Ordering::Relaxed) as f64 / 1000.0,
+        }
+    }
+
+    fn set(&self, retries: u32, delay: Duration) {
+        self.retries.store(retries, Ordering::Relaxed);
+        self.delay_ms
+            .store(delay.as_millis() as u64, Ordering::Relaxed);
+    }
+}
+
+pub static HEAL_STATUS: HealStatus = HealStatus::new();
+
+/// Restarts since recording last ran fine, and the wait before the latest one (0 when healthy).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct HealSnapshot {
+    pub retries: u32,
+    pub delay_secs: f64,
+}
+
+const BACKOFF_FACTOR: u32 = 2;
+
+/// Exponential backoff between restarts of a failing recorder: `initial`, doubling each
+/// consecutive failure up to `max`.
+pub struct HealBackoff {
+    initial: Duration,
+    max: Duration,
+    retries: u32,
+}
+
+impl HealBackoff {
+    pub fn new(initial: Duration, max: Duration) -> Self {
+        HealBackoff {
+            initial,
+            max: max.max(initial),
+            retries: 0,
+        }
+    }
+
+    /// Counts a failure and returns how long to wait before restarting.
+    pub fn next_delay(&mut self) -> Duration {
+        let delay = BACKOFF_FACTOR
+            .checked_pow(self.retries)
+            .and_then(|factor| self.initial.checked_mul(factor))
+            .map_or(self.max, |delay| delay.min(self.max));
+        self.retries = self.retries.saturating_add(1);
+        HEAL_STATUS.set(self.retries, delay);
+        delay
+    }
+
+    /// Recording is healthy again, the next failure starts over at `initial`.
+    pub fn reset(&mut self) {
+        self.retries = 0;
+        HEAL_STATUS.set(0, Duration::ZERO);
+    }
+
+    pub fn max_delay(&self) -> Duration {
+        self.max
+    }
+}
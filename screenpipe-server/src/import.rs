@@ -0,0 +1,267 @@
+use crate::thumbnails::{encode_thumbnail, store_thumbnail, thumbnails_dir};
+use crate::video_utils::extract_frame_png;
+use crate::{DatabaseManager, FrameData};
+use anyhow::{anyhow, bail, Result};
+use chrono::{DateTime, Utc};
+use indicatif::{ProgressBar, ProgressStyle};
+use log::warn;
+use screenpipe_audio::{
+    AudioDevice, AudioInput, AudioTranscriptionEngine, DeviceType, TranscriptionResult,
+};
+use screenpipe_core::find_ffmpeg_path;
+use screenpipe_vision::{normalize_ocr_text, OcrEngine};
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use tokio::process::Command;
+
+/// `app_name` of imported frames.
+pub const IMPORT_APP_NAME: &str = "screenpipe import";
+
+const IMPORT_SAMPLE_RATE: u32 = 16000;
+
+pub struct ImportOptions {
+    pub fps: f64,
+    /// When the recording started, defaults to the file's modification time minus its duration
+    pub start_time: Option<DateTime<Utc>>,
+    pub ocr_engine: Arc<OcrEngine>,
+    /// Runs [`normalize_ocr_text`] on the ocr output, as recording does by default
+    pub normalize_ocr: bool,
+    /// `None` skips the audio track
+    pub audio: Option<ImportAudio>,
+}
+
+pub struct ImportAudio {
+    pub whisper_sender: crossbeam::channel::Sender<AudioInput>,
+    pub whisper_receiver: crossbeam::channel::Receiver<TranscriptionResult>,
+    pub transcription_engine: Arc<AudioTranscriptionEngine>,
+    pub chunk_duration: Duration,
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ImportSummary {
+    pub frames: usize,
+    pub audio_chunks: usize,
+}
+
+/// What ffmpeg reports about an input file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MediaInfo {
+    pub duration_secs: f64,
+    pub has_audio: bool,
+}
+
+/// Reads the `Duration:` and stream lines of `ffmpeg -i`, `None` when there is no duration.
+pub fn parse_media_info(ffmpeg_output: &str) -> Option<MediaInfo> {
+    let duration = ffmpeg_output
+        .split("Duration: ")
+        .nth(1)?
+        .split(',')
+        .next()?;
+    let mut duration_secs = 0.0;
+    for part in duration.trim().split(':') {
+        duration_secs = duration_secs * 60.0 + part.parse::<f64>().ok()?;
+    }
+    Some(MediaInfo {
+        duration_secs,
+        has_audio: ffmpeg_output
+            .lines()
+            .any(|line| line.trim_start().starts_with("Stream") && line.contains("Audio:")),
+    })
+}
+
+async fn probe(file_path: &str) -> Result<MediaInfo> {
+    let ffmpeg_path = find_ffmpeg_path().ok_or_else(|| anyhow!("ffmpeg not found"))?;
+    // without an output ffmpeg exits with an error after printing the input details
+    let output = Command::new(ffmpeg_path)
+        .args(["-hide_banner", "-i", file_path])
+        .output()
+        .await?;
+    parse_media_info(&String::from_utf8_lossy(&output.stderr))
+        .ok_or_else(|| anyhow!("{} is not a video file ffmpeg can read", file_path))
+}
+
+/// Mono samples at `IMPORT_SAMPLE_RATE` of `duration` from `offset_ms` on.
+async fn extract_audio(file_path: &str, offset_ms: i64, duration: Duration) -> Result<Vec<f32>> {
+    let ffmpeg_path = find_ffmpeg_path().ok_or_else(|| anyhow!("ffmpeg not found"))?;
+    let output = Command::new(ffmpeg_path)
+        .args(["-ss", &format!("{:.3}", offset_ms as f64 / 1000.0)])
+        .args(["-t", &format!("{:.3}", duration.as_secs_f64())])
+        .args(["-i", file_path, "-vn", "-ac", "1"])
+        .args(["-ar", &IMPORT_SAMPLE_RATE.to_string()])
+        .args(["-f", "f32le", "-"])
+        .output()
+        .await?;
+    if !output.status.success() {
+        bail!(
+            "ffmpeg failed to extract audio: {}",
+            String::from_utf8_lossy(&output.stderr)
+                .lines()
+                .last()
+                .unwrap_or_default()
+        );
+    }
+    Ok(output
+        .stdout
+        .chunks_exact(4)
+        .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+        .collect())
+}
+
+fn progress_bar(len: u64, position: u64, message: &'static str) -> ProgressBar {
+    let bar = ProgressBar::new(len).with_message(message);
+    bar.set_style(
+        ProgressStyle::with_template("{msg:>8} [{bar:40}] {pos}/{len} ({eta} left)")
+            .unwrap()
+            .progress_chars("=> "),
+    );
+    bar.set_position(position);
+    bar
+}
+
+/// Ingests a pre-recorded video as if it had been recorded live: frames every `1 / fps` seconds
+/// are ocr'd and stored, the audio track is transcribed in `chunk_duration` pieces. Running it
+/// again on the same file resumes after the last frame / audio chunk stored.
+pub async fn import_file(
+    db: &DatabaseManager,
+    data_dir: &Path,
+    path: &Path,
+    options: ImportOptions,
+) -> Result<ImportSummary> {
+    if !options.fps.is_finite() || options.fps <= 0.0 {
+        bail!("fps must be above 0");
+    }
+    let path = path.canonicalize()?;
+    let file_path = path.to_string_lossy().into_owned();
+    let info = probe(&file_path).await?;
+    let duration_ms = (info.duration_secs * 1000.0) as i64;
+
+    let state = match db.get_import(&file_path).await? {
+        Some(state) => state,
+        None => {
+            let start_time = match options.start_time {
+                Some(start_time) => start_time,
+                None => {
+                    let modified: DateTime<Utc> = std::fs::metadata(&path)?.modified()?.into();
+                    modified - chrono::Duration::milliseconds(duration_ms)
+                }
+            };
+            db.insert_import(&file_path, start_time).await?
+        }
+    };
+    let mut summary = ImportSummary::default();
+
+    let step_ms = ((1000.0 / options.fps).round() as i64).max(1);
+    let first_ms = state.last_frame_ms.map_or(0, |ms| ms + step_ms);
+    let window_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let thumbnails_dir = thumbnails_dir(data_dir);
+    let bar = progress_bar(
+        (duration_ms + step_ms - 1) as u64 / step_ms as u64,
+        (first_ms + step_ms - 1) as u64 / step_ms as u64,
+        "frames",
+    );
+    for offset_ms in (first_ms..duration_ms).step_by(step_ms as usize) {
+        // e.g. a timestamp past the last frame, which estimating from the duration can give
+        let image = match extract_frame_png(&file_path, offset_ms)
+            .await
+            .and_then(|png| Ok(image::load_from_memory(&png)?))
+        {
+            Ok(image) => image,
+            Err(e) => {
+                warn!("skipping frame at {}ms: {}", offset_ms, e);
+                bar.inc(1);
+                continue;
+            }
+        };
+        let (text, text_json, _) = options.ocr_engine.perform_ocr(&image).await?;
+        let text = if options.normalize_ocr {
+            normalize_ocr_text(&text)
+        } else {
+            text
+        };
+        let frame = FrameData {
+            timestamp: state.start_time + chrono::Duration::milliseconds(offset_ms),
+            text,
+            text_json,
+            app_name: IMPORT_APP_NAME.to_string(),
+            window_name: window_name.clone(),
+            ocr_engine: Arc::clone(&options.ocr_engine),
+            focused: true,
+            ocr_failed: false,
+            cursor: None,
+            window_bounds: None,
+        };
+        let frame_id = db
+            .insert_imported_frame(&file_path, offset_ms, &frame)
+            .await?;
+        if let Err(e) =
+            store_thumbnail(db, &thumbnails_dir, &[frame_id], &encode_thumbnail(&image)?).await
+        {
+            warn!("failed to write thumbnail for frame {}: {}", frame_id, e);
+        }
+        summary.frames += 1;
+        bar.inc(1);
+    }
+    bar.finish();
+
+    let Some(audio) = options.audio else {
+        return Ok(summary);
+    };
+    if !info.has_audio {
+        return Ok(summary);
+    }
+    let chunk_ms = (audio.chunk_duration.as_millis() as i64).max(1);
+    let first_ms = state.last_audio_ms.map_or(0, |ms| ms + chunk_ms);
+    let device = Arc::new(AudioDevice::new(window_name, DeviceType::Input));
+    let bar = progress_bar(
+        (duration_ms + chunk_ms - 1) as u64 / chunk_ms as u64,
+        (first_ms + chunk_ms - 1) as u64 / chunk_ms as u64,
+        "audio",
+    );
+    for offset_ms in (first_ms..duration_ms).step_by(chunk_ms as usize) {
+        let data = extract_audio(&file_path, offset_ms, audio.chunk_duration).await?;
+        audio.whisper_sender.send(AudioInput {
+            data: Arc::new(data),
+            sample_rate: IMPORT_SAMPLE_RATE,
+            channels: 1,
+            device: Arc::clone(&device),
+            end_time: SystemTime::now(),
+        })?;
+        let receiver = audio.whisper_receiver.clone();
+        let result = tokio::task::spawn_blocking(move || receiver.recv()).await??;
+        let transcription = match (result.transcription, result.error) {
+            (Some(transcription), None) => transcription,
+            (_, error) => bail!(
+                "transcription failed at {}s: {}",
+                offset_ms / 1000,
+                error.unwrap_or_default()
+            ),
+        };
+
+        if !transcription.is_empty() {
+            let audio_chunk_id = db.insert_audio_chunk(&result.path).await?;
+            db.insert_audio_transcription(
+                audio_chunk_id,
+                &transcription,
+                0,
+                &audio.transcription_engine.to_string(),
+                &device,
+            )
+            .await?;
+            db.set_audio_chunk_timestamp(
+                audio_chunk_id,
+                state.start_time + chrono::Duration::milliseconds(offset_ms),
+            )
+            .await?;
+            summary.audio_chunks += 1;
+        }
+        db.set_import_audio_progress(&file_path, offset_ms).await?;
+        bar.inc(1);
+    }
+    bar.finish();
+
+    Ok(summary)
+}
@@ -1,29 +1,152 @@
+mod audio_integrity;
+mod audio_status;
 mod auto_destruct;
+pub mod benchmark;
+mod bind;
+mod body_limit;
 pub mod chunking;
 pub mod cli;
+pub mod client;
+pub mod content_classifier;
 pub mod core;
+mod csv_export;
 mod db;
+mod deep_health;
+mod export;
 pub mod filtering;
+mod frame_buffer;
+mod frame_diff;
+mod frame_format;
+pub mod fuzzy;
+mod graphql;
+mod heal;
+mod import;
+mod live_transcription;
 pub mod logs;
+mod ocr_script;
+mod pipe_cmd;
 mod pipe_manager;
 mod plugin;
+mod rate_limit;
+mod recording_control;
+mod remote_sync;
+mod request_id;
+mod request_log;
 mod resource_monitor;
+mod runtime_config;
+mod search_cursor;
+mod search_rank;
+mod secrets;
+mod security_headers;
+pub mod self_test;
 mod server;
+mod share;
+mod signed_url;
+mod startup_script;
+mod stats;
+mod subtitles;
+mod summary;
+mod task_limit;
+mod tessdata;
+mod thumbnails;
+mod version;
 mod video;
 mod video_db;
 mod video_utils;
+mod waveform;
+pub use audio_integrity::{
+    audio_file_present, check_audio_chunks, start_audio_integrity_check, AudioIntegrityReport,
+};
+pub use audio_status::merge_discovered_devices;
 pub use auto_destruct::watch_pid;
-pub use cli::Cli;
+pub use bind::{bind_listener, ipv6_available, listen_addr};
+pub use body_limit::{
+    with_body_limit, BodyLimits, DEFAULT_MAX_BODY_SIZE_KB, DEFAULT_MAX_IMPORT_BODY_SIZE_KB,
+};
+pub use cli::{
+    env_var_name, parse_dedup_threshold, parse_fps, parse_ocr_hint_language, Cli, ConfigSource,
+    ConfigSources,
+};
+pub use content_classifier::ScreenContentType;
 pub use core::start_continuous_recording;
-pub use db::{ContentSource, ContentType, DatabaseManager, SearchResult};
-pub use logs::MultiWriter;
+pub use csv_export::{
+    csv_filename, csv_header, csv_rows, escape_csv_field, parse_csv_columns, CsvColumn,
+    DEFAULT_CSV_COLUMNS,
+};
+pub use db::{
+    AppScreenTime, ContentSource, ContentType, DatabaseManager, FrameData, FrameExportRow,
+    FrameText, FrameWindow, ImportState, ManualTranscriptSegment, SearchResult, ShareClaim,
+    TimelineBucket, TimelineResolution, TranscriptCorrection, TranscriptSource,
+};
+pub use deep_health::{
+    check_deep_health, DeepHealthResponse, SubsystemHealth, DEEP_HEALTH_TIMEOUT,
+};
+pub use export::{
+    index_path, keyframe_interval, ExportIndex, ExportJob, ExportJobs, ExportStatus,
+    FrameTimestamp, DEFAULT_KEYFRAME_INTERVAL,
+};
+pub use frame_buffer::{move_file, FrameBuffer};
+pub use frame_diff::{diff_text, FrameDiff};
+pub use frame_format::CaptureFormat;
+pub use heal::{HealBackoff, HealSnapshot};
+pub use import::{
+    import_file, parse_media_info, ImportAudio, ImportOptions, ImportSummary, MediaInfo,
+    IMPORT_APP_NAME,
+};
+pub use live_transcription::{decode_pcm, LiveTranscription, PcmEncoding};
+pub use logs::JsonLogFormat;
+pub use ocr_script::{OcrScript, OCR_SCRIPT_TIMEOUT};
+pub use pipe_cmd::{run_pipe_cmd, PipeCmd, PipeCmdInput, PipeCmdOutput};
 pub use pipe_manager::PipeManager;
-pub use resource_monitor::{ResourceMonitor, RestartSignal};
+pub use rate_limit::{with_rate_limit, RateLimit};
+pub use recording_control::RecordingControl;
+pub use remote_sync::{
+    sign_payload, verify_signature, ImportedRows, RemoteSync, SyncBatch, SyncedFrame,
+    SyncedTranscription, SIGNATURE_HEADER,
+};
+pub use request_id::{
+    spawn_blocking_in_current_span, spawn_in_current_span, with_request_tracing, RequestSpan,
+    REQUEST_ID_HEADER,
+};
+pub use request_log::is_logged_path;
+pub use resource_monitor::{
+    send_desktop_notification, AlertThresholds, DiskUsage, ResourceMonitor, RestartSignal,
+    DISK_USAGE, MEMORY_USAGE_BYTES,
+};
+pub use runtime_config::{RuntimeConfigResponse, RuntimeConfigUpdate};
+pub use search_cursor::{CursorPosition, SearchCursor};
+pub use search_rank::{RankWeights, SearchOrder, SearchSort, SharedRankWeights, SortOrder};
+pub use secrets::{secrets_path, Secrets, REDACTED};
+pub use security_headers::with_security_headers;
 pub use server::create_router;
+pub use server::create_router_with_limits;
 pub use server::health_check;
+pub use server::reject_writes;
 pub use server::AppState;
 pub use server::ContentItem;
 pub use server::HealthCheckResponse;
 pub use server::PaginatedResponse;
 pub use server::Server;
+pub use server::{
+    DeviceStatus, FrameSearchItem, ListDeviceResponse, MonitorInfo, RandomFrameItem,
+    RecordingStatusResponse,
+};
+pub use share::{new_share_token, ShareResponse, SharedFrame, DEFAULT_SHARE_TTL};
+pub use signed_url::{MediaKind, MediaSigner, MediaToken, MediaTokenError, DEFAULT_SIGNED_URL_TTL};
+pub use startup_script::{run_startup_script, spawn_startup_script};
+pub use stats::{RecordingStats, StatsCache};
+pub use subtitles::{build_cues, render_subtitles, AudioTranscript, Cue, SubtitleFormat};
+pub use summary::{
+    render_markdown, start_daily_summaries, summarize_day, summary_path, top_terms,
+    write_daily_summary, DailySummary, TermCounts,
+};
+pub use task_limit::TaskLimiter;
+pub use tessdata::{
+    git_blob_sha1, installed_langs, is_lang_code, parse_tessdata_dir, TesseractLangManager,
+    TESSDATA_RELEASE,
+};
+pub use version::{
+    with_version_headers, VersionResponse, API_VERSION, SERVER_VERSION, X_SCREENPIPE_API_VERSION,
+    X_SCREENPIPE_VERSION,
+};
 pub use video::VideoCapture;
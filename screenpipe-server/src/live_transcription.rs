@@ -0,0 +1,188 @@
+//! `GET /stream/transcription`: a websocket the client sends raw mono pcm to, answered with
+//! partial and final transcripts as json, for live captions. A text message ends the stream, the
+//! server then sends the final transcript of what is left and closes.
+
+use crate::request_id::spawn_blocking_in_current_span;
+use crate::server::AppState;
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::extract::{Query, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Json, Response};
+use log::{debug, error, info};
+use screenpipe_audio::whisper::WhisperModel;
+use screenpipe_audio::{AudioTranscriptionEngine, LiveTranscriber};
+use serde::Deserialize;
+use serde_json::json;
+use std::sync::Arc;
+use tokio::sync::OnceCell;
+
+/// Whisper model of live transcription, loaded on the first stream.
+pub struct LiveTranscription {
+    engine: Arc<AudioTranscriptionEngine>,
+    model: OnceCell<WhisperModel>,
+}
+
+impl LiveTranscription {
+    /// Deepgram streams are transcribed with whisper tiny.
+    pub fn new(engine: Arc<AudioTranscriptionEngine>) -> Self {
+        LiveTranscription {
+            engine,
+            model: OnceCell::new(),
+        }
+    }
+
+    async fn model(&self) -> anyhow::Result<WhisperModel> {
+        let model = self
+            .model
+            .get_or_try_init(|| async {
+                let engine = self.engine.clone();
+                spawn_blocking_in_current_span(move || WhisperModel::new(&engine)).await?
+            })
+            .await?;
+        Ok(model.clone())
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PcmEncoding {
+    /// 16 bit little endian integers
+    #[default]
+    S16le,
+    /// 32 bit little endian floats
+    F32le,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct StreamTranscriptionQuery {
+    #[serde(default = "default_sample_rate")]
+    sample_rate: u32,
+    #[serde(default)]
+    encoding: PcmEncoding,
+}
+
+fn default_sample_rate() -> u32 {
+    16000
+}
+
+/// Samples of a binary message, a trailing partial sample is dropped.
+pub fn decode_pcm(bytes: &[u8], encoding: PcmEncoding) -> Vec<f32> {
+    match encoding {
+        PcmEncoding::S16le => bytes
+            .chunks_exact(2)
+            .map(|b| i16::from_le_bytes([b[0], b[1]]) as f32 / 32768.0)
+            .collect(),
+        PcmEncoding::F32le => bytes
+            .chunks_exact(4)
+            .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+            .collect(),
+    }
+}
+
+pub(crate) async fn stream_transcription(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<StreamTranscriptionQuery>,
+    ws: WebSocketUpgrade,
+) -> Response {
+    let Some(live_transcription) = state.live_transcription.clone() else {
+        return (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(json!({"error": "live transcription is not available on this server"})),
+        )
+            .into_response();
+    };
+    if query.sample_rate == 0 {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(json!({"error": "sample_rate must be above 0"})),
+        )
+            .into_response();
+    }
+    ws.on_upgrade(move |socket| transcribe_socket(socket, live_transcription, query))
+}
+
+async fn send_json(socket: &mut WebSocket, value: impl serde::Serialize) -> bool {
+    match serde_json::to_string(&value) {
+        Ok(text) => socket.send(Message::Text(text)).await.is_ok(),
+        Err(_) => false,
+    }
+}
+
+async fn transcribe_socket(
+    mut socket: WebSocket,
+    live_transcription: Arc<LiveTranscription>,
+    query: StreamTranscriptionQuery,
+) {
+    let mut transcriber = match live_transcription
+        .model()
+        .await
+        .and_then(|model| LiveTranscriber::new(model, query.sample_rate))
+    {
+        Ok(transcriber) => transcriber,
+        Err(e) => {
+            error!("failed to start live transcription: {}", e);
+            send_json(
+                &mut socket,
+                json!({"type": "error", "error": e.to_string()}),
+            )
+            .await;
+            return;
+        }
+    };
+    info!(
+        "live transcription started, {} Hz {:?}",
+        query.sample_rate, query.encoding
+    );
+
+    while let Some(message) = socket.recv().await {
+        let samples = match message {
+            Ok(Message::Binary(bytes)) => decode_pcm(&bytes, query.encoding),
+            Ok(Message::Text(_)) => break,
+            Ok(Message::Close(_)) => return,
+            Ok(_) => continue,
+            Err(e) => {
+                debug!("live transcription socket failed: {}", e);
+                return;
+            }
+        };
+        let (returned, events) = match spawn_blocking_in_current_span(move || {
+            let events = transcriber.push(&samples);
+            (transcriber, events)
+        })
+        .await
+        {
+            Ok(result) => result,
+            Err(e) => {
+                error!("live transcription panicked: {}", e);
+                return;
+            }
+        };
+        transcriber = returned;
+        match events {
+            Ok(events) => {
+                for event in events {
+                    if !send_json(&mut socket, event).await {
+                        return;
+                    }
+                }
+            }
+            Err(e) => {
+                error!("live transcription failed: {}", e);
+                send_json(
+                    &mut socket,
+                    json!({"type": "error", "error": e.to_string()}),
+                )
+                .await;
+                return;
+            }
+        }
+    }
+
+    // the client ended the stream, what was said last is still worth sending
+    if let Ok(Ok(Some(event))) = spawn_blocking_in_current_span(move || transcriber.finish()).await
+    {
+        send_json(&mut socket, event).await;
+    }
+    let _ = socket.close().await;
+    info!("live transcription stopped");
+}
@@ -1,33 +1,14 @@
-use std::io::Write;
-pub struct MultiWriter {
-    writers: Vec<Box<dyn Write + Send>>,
-}
-
-impl MultiWriter {
-    pub fn new(writers: Vec<Box<dyn Write + Send>>) -> Self {
-        MultiWriter { writers }
-    }
-}
-
-impl Write for MultiWriter {
-    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
-        for writer in &mut self.writers {
-            writer.write_all(buf)?;
-        }
-        Ok(buf.len())
-    }
-
-    fn flush(&mut self) -> std::io::Result<()> {
-        for writer in &mut self.writers {
-            writer.flush()?;
-        }
-        Ok(())
-    }
-}
-
+use serde_json::{Map, Value};
+use std::fmt::Write as _;
 use std::fs::{File, OpenOptions};
-use std::io::{self, Seek, SeekFrom};
+use std::io::{self, Seek, SeekFrom, Write};
+use tracing::field::{Field, Visit};
+use tracing::{Event, Subscriber};
+use tracing_log::NormalizeEvent;
+use tracing_subscriber::fmt::format::{JsonFields, Writer};
 use tracing_subscriber::fmt::writer::MakeWriter;
+use tracing_subscriber::fmt::{FmtContext, FormatEvent, FormattedFields};
+use tracing_subscriber::registry::LookupSpan;
 
 const MAX_LOG_SIZE: u64 = 100 * 1024 * 1024; // 100 MB
 
@@ -84,3 +65,96 @@ impl<'a> MakeWriter<'a> for SingleFileRollingWriter {
         SingleFileRollingWriter::new(&self.path).expect("Failed to create writer")
     }
 }
+
+/// `--log-format json`: one object per line such as
+/// `{"ts":"...","level":"INFO","module":"screenpipe_server::core","thread":"...","msg":"..."}`,
+/// with the fields of the event and of its spans, e.g. the recording `session_id` or the audio
+/// `device`. Spans are recorded with [`JsonFields`], set it as the layer's `fmt_fields`.
+pub struct JsonLogFormat;
+
+struct JsonVisitor<'a>(&'a mut Map<String, Value>);
+
+impl Visit for JsonVisitor<'_> {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.insert(field, Value::from(value));
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.insert(field, Value::from(value));
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.insert(field, Value::from(value));
+    }
+
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.insert(field, Value::from(value));
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.insert(field, Value::from(value));
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        self.insert(field, Value::from(format!("{:?}", value)));
+    }
+}
+
+impl JsonVisitor<'_> {
+    fn insert(&mut self, field: &Field, value: Value) {
+        match field.name() {
+            "message" => {
+                self.0.insert("msg".to_string(), value);
+            }
+            // already in `module`, added by the `log` crate bridge
+            name if name.starts_with("log.") => {}
+            name => {
+                self.0.insert(name.to_string(), value);
+            }
+        }
+    }
+}
+
+impl<S> FormatEvent<S, JsonFields> for JsonLogFormat
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn format_event(
+        &self,
+        ctx: &FmtContext<'_, S, JsonFields>,
+        mut writer: Writer<'_>,
+        event: &Event<'_>,
+    ) -> std::fmt::Result {
+        let normalized = event.normalized_metadata();
+        let metadata = normalized.as_ref().unwrap_or_else(|| event.metadata());
+
+        let mut fields = Map::new();
+        // outermost first, so inner spans and the event win on duplicate names
+        if let Some(scope) = ctx.event_scope() {
+            for span in scope.from_root() {
+                let extensions = span.extensions();
+                let Some(span_fields) = extensions.get::<FormattedFields<JsonFields>>() else {
+                    continue;
+                };
+                if let Ok(Value::Object(span_fields)) = serde_json::from_str(span_fields) {
+                    fields.extend(span_fields);
+                }
+            }
+        }
+        event.record(&mut JsonVisitor(&mut fields));
+
+        fields.insert(
+            "ts".to_string(),
+            Value::from(chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Micros, true)),
+        );
+        fields.insert("level".to_string(), Value::from(metadata.level().as_str()));
+        fields.insert(
+            "module".to_string(),
+            Value::from(metadata.module_path().unwrap_or(metadata.target())),
+        );
+        if let Some(thread) = std::thread::current().name() {
+            fields.insert("thread".to_string(), Value::from(thread));
+        }
+        writeln!(writer, "{}", Value::Object(fields))
+    }
+}
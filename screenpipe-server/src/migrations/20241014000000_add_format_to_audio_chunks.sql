@@ -0,0 +1,2 @@
+-- Add format column to audio_chunks, existing chunks were all encoded as aac/mp4
+ALTER TABLE audio_chunks ADD COLUMN format TEXT NOT NULL DEFAULT 'mp4';
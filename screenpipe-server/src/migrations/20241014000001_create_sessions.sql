@@ -0,0 +1,13 @@
+-- Recording sessions, from start to stop/restart of the recorder
+CREATE TABLE IF NOT EXISTS sessions (
+    id TEXT PRIMARY KEY,
+    name TEXT,
+    start_time TIMESTAMP NOT NULL,
+    end_time TIMESTAMP
+);
+
+ALTER TABLE frames ADD COLUMN session_id TEXT REFERENCES sessions(id);
+ALTER TABLE audio_chunks ADD COLUMN session_id TEXT REFERENCES sessions(id);
+
+CREATE INDEX IF NOT EXISTS idx_frames_session_id ON frames(session_id);
+CREATE INDEX IF NOT EXISTS idx_audio_chunks_session_id ON audio_chunks(session_id);
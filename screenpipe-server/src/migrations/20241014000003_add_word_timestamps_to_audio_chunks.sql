@@ -0,0 +1,2 @@
+-- JSON array of {"text", "start_ms", "end_ms"} for the chunk's transcription, offsets from the chunk start
+ALTER TABLE audio_chunks ADD COLUMN word_timestamps TEXT;
@@ -0,0 +1,4 @@
+-- Screen content category inferred from the ocr text and window (code, browser, spreadsheet, terminal, video)
+ALTER TABLE ocr_text ADD COLUMN content_type TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_ocr_text_content_type ON ocr_text(content_type);
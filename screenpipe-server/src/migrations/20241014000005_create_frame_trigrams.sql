@@ -0,0 +1,18 @@
+-- Trigram index over the ocr text for fuzzy search, the text itself stays in ocr_text
+CREATE VIRTUAL TABLE IF NOT EXISTS frame_trigrams USING fts5(text, content='ocr_text', content_rowid='rowid', tokenize='trigram');
+
+-- Index existing ocr text
+INSERT INTO frame_trigrams(frame_trigrams) VALUES ('rebuild');
+
+CREATE TRIGGER IF NOT EXISTS frame_trigrams_ai AFTER INSERT ON ocr_text BEGIN
+  INSERT INTO frame_trigrams(rowid, text) VALUES (new.rowid, new.text);
+END;
+
+CREATE TRIGGER IF NOT EXISTS frame_trigrams_ad AFTER DELETE ON ocr_text BEGIN
+  INSERT INTO frame_trigrams(frame_trigrams, rowid, text) VALUES ('delete', old.rowid, old.text);
+END;
+
+CREATE TRIGGER IF NOT EXISTS frame_trigrams_au AFTER UPDATE OF text ON ocr_text BEGIN
+  INSERT INTO frame_trigrams(frame_trigrams, rowid, text) VALUES ('delete', old.rowid, old.text);
+  INSERT INTO frame_trigrams(rowid, text) VALUES (new.rowid, new.text);
+END;
@@ -0,0 +1,2 @@
+-- Notes returned by the --pipe-cmd executable for a frame
+ALTER TABLE frames ADD COLUMN notes TEXT;
@@ -0,0 +1,9 @@
+-- Thumbnails stored once per distinct content, frames point at theirs by md5
+CREATE TABLE IF NOT EXISTS frame_hashes (
+    md5 TEXT PRIMARY KEY,
+    file_path TEXT NOT NULL
+);
+
+ALTER TABLE frames ADD COLUMN thumbnail_md5 TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_frames_thumbnail_md5 ON frames(thumbnail_md5);
@@ -0,0 +1,9 @@
+-- Files ingested with `screenpipe import`, with how far each got so an interrupted import resumes
+CREATE TABLE IF NOT EXISTS imports (
+    file_path TEXT PRIMARY KEY,
+    video_chunk_id INTEGER NOT NULL,
+    start_time TIMESTAMP NOT NULL,
+    last_frame_ms INTEGER,
+    last_audio_ms INTEGER,
+    FOREIGN KEY (video_chunk_id) REFERENCES video_chunks(id)
+);
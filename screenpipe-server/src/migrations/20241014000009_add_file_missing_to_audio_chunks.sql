@@ -0,0 +1,2 @@
+-- Set by the audio integrity check when the chunk's file was deleted or is empty
+ALTER TABLE audio_chunks ADD COLUMN file_missing BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,18 @@
+-- Trigram index over the transcriptions for audio search, the text itself stays in audio_transcriptions
+CREATE VIRTUAL TABLE IF NOT EXISTS audio_fts USING fts5(transcription, content='audio_transcriptions', content_rowid='id', tokenize='trigram');
+
+-- Index existing transcriptions
+INSERT INTO audio_fts(audio_fts) VALUES ('rebuild');
+
+CREATE TRIGGER IF NOT EXISTS audio_fts_ai AFTER INSERT ON audio_transcriptions BEGIN
+  INSERT INTO audio_fts(rowid, transcription) VALUES (new.id, new.transcription);
+END;
+
+CREATE TRIGGER IF NOT EXISTS audio_fts_ad AFTER DELETE ON audio_transcriptions BEGIN
+  INSERT INTO audio_fts(audio_fts, rowid, transcription) VALUES ('delete', old.id, old.transcription);
+END;
+
+CREATE TRIGGER IF NOT EXISTS audio_fts_au AFTER UPDATE OF transcription ON audio_transcriptions BEGIN
+  INSERT INTO audio_fts(audio_fts, rowid, transcription) VALUES ('delete', old.id, old.transcription);
+  INSERT INTO audio_fts(rowid, transcription) VALUES (new.id, new.transcription);
+END;
@@ -0,0 +1,16 @@
+-- Confidence of the ocr engine, set when a frame is re-ocr'd
+ALTER TABLE ocr_text ADD COLUMN confidence REAL;
+
+-- Ocr results replaced by POST /ocr/reprocess/:frame_id
+CREATE TABLE IF NOT EXISTS frame_ocr_history (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    frame_id INTEGER NOT NULL,
+    text TEXT NOT NULL,
+    text_json TEXT,
+    ocr_engine TEXT NOT NULL,
+    confidence REAL,
+    replaced_at TIMESTAMP NOT NULL,
+    FOREIGN KEY (frame_id) REFERENCES frames(id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_frame_ocr_history_frame_id ON frame_ocr_history(frame_id);
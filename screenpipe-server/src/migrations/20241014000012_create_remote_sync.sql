@@ -0,0 +1,17 @@
+-- Rows already pushed to each --remote-sync-url
+CREATE TABLE IF NOT EXISTS remote_sync_state (
+    url TEXT PRIMARY KEY,
+    last_frame_id INTEGER NOT NULL DEFAULT 0,
+    last_transcription_id INTEGER NOT NULL DEFAULT 0
+);
+
+-- Machines pushing to POST /import, their rows are stored under placeholder chunks
+CREATE TABLE IF NOT EXISTS remote_sync_sources (
+    source TEXT PRIMARY KEY,
+    video_chunk_id INTEGER NOT NULL,
+    audio_chunk_id INTEGER NOT NULL,
+    last_frame_id INTEGER NOT NULL DEFAULT 0,
+    last_transcription_id INTEGER NOT NULL DEFAULT 0,
+    FOREIGN KEY (video_chunk_id) REFERENCES video_chunks(id),
+    FOREIGN KEY (audio_chunk_id) REFERENCES audio_chunks(id)
+);
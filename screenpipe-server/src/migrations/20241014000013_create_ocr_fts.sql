@@ -0,0 +1,18 @@
+-- Word index over the ocr text, search ranks frames by the bm25 score of their match
+CREATE VIRTUAL TABLE IF NOT EXISTS ocr_text_fts USING fts5(text, content='ocr_text', content_rowid='frame_id');
+
+-- Index existing text
+INSERT INTO ocr_text_fts(ocr_text_fts) VALUES ('rebuild');
+
+CREATE TRIGGER IF NOT EXISTS ocr_text_fts_ai AFTER INSERT ON ocr_text BEGIN
+  INSERT INTO ocr_text_fts(rowid, text) VALUES (new.frame_id, new.text);
+END;
+
+CREATE TRIGGER IF NOT EXISTS ocr_text_fts_ad AFTER DELETE ON ocr_text BEGIN
+  INSERT INTO ocr_text_fts(ocr_text_fts, rowid, text) VALUES ('delete', old.frame_id, old.text);
+END;
+
+CREATE TRIGGER IF NOT EXISTS ocr_text_fts_au AFTER UPDATE OF text ON ocr_text BEGIN
+  INSERT INTO ocr_text_fts(ocr_text_fts, rowid, text) VALUES ('delete', old.frame_id, old.text);
+  INSERT INTO ocr_text_fts(rowid, text) VALUES (new.frame_id, new.text);
+END;
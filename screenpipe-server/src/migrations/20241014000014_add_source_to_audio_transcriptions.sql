@@ -0,0 +1,4 @@
+-- 'automated' for transcriptions of the recorder, 'manual' for the ones of POST /import/transcript
+ALTER TABLE audio_transcriptions ADD COLUMN source TEXT NOT NULL DEFAULT 'automated';
+-- Speaker of an imported segment
+ALTER TABLE audio_transcriptions ADD COLUMN speaker TEXT;
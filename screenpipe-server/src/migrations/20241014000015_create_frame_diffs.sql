@@ -0,0 +1,12 @@
+-- Unified diff of the ocr text of a frame against the previous frame of the same window
+CREATE TABLE IF NOT EXISTS frame_diffs (
+    frame_id INTEGER PRIMARY KEY,
+    previous_frame_id INTEGER,
+    diff TEXT NOT NULL,
+    lines_added INTEGER NOT NULL,
+    lines_removed INTEGER NOT NULL,
+    FOREIGN KEY (frame_id) REFERENCES frames(id)
+);
+
+-- Finds the previous frame of a window
+CREATE INDEX IF NOT EXISTS idx_ocr_text_app_window_frame_id ON ocr_text(app_name, window_name, frame_id);
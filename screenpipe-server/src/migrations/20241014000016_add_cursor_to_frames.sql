@@ -0,0 +1,3 @@
+-- Mouse position on the frame when it was captured, NULL when it was on another monitor
+ALTER TABLE frames ADD COLUMN cursor_x INTEGER;
+ALTER TABLE frames ADD COLUMN cursor_y INTEGER;
@@ -0,0 +1,12 @@
+-- Transcripts of audio chunks corrected through PATCH /audio/chunks/:id/transcript
+CREATE TABLE IF NOT EXISTS transcript_corrections (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    audio_chunk_id INTEGER NOT NULL,
+    original_transcript TEXT NOT NULL,
+    corrected_transcript TEXT NOT NULL,
+    user_agent TEXT,
+    timestamp TIMESTAMP NOT NULL,
+    FOREIGN KEY (audio_chunk_id) REFERENCES audio_chunks(id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_transcript_corrections_audio_chunk_id ON transcript_corrections(audio_chunk_id);
@@ -0,0 +1,10 @@
+-- One-time links to a frame made with POST /share/frame/:id
+CREATE TABLE IF NOT EXISTS shares (
+    token TEXT PRIMARY KEY,
+    frame_id INTEGER NOT NULL,
+    created_at TIMESTAMP NOT NULL,
+    expires_at TIMESTAMP NOT NULL,
+    -- Set when GET /share/<token> served it, the link is dead from then on
+    used_at TIMESTAMP,
+    FOREIGN KEY (frame_id) REFERENCES frames(id)
+);
@@ -0,0 +1,2 @@
+-- App a chunk recorded with --capture-audio-from-app was captured from, NULL for audio devices
+ALTER TABLE audio_chunks ADD COLUMN app_bundle_id TEXT;
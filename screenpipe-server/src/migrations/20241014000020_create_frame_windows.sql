@@ -0,0 +1,13 @@
+-- Window each recorded frame is of, also for frames stored without ocr text, and the part of
+-- the monitor frame it covered, NULL when unknown, e.g. for imported videos
+CREATE TABLE IF NOT EXISTS frame_windows (
+    frame_id INTEGER PRIMARY KEY,
+    app_name TEXT NOT NULL,
+    window_name TEXT NOT NULL,
+    focused BOOLEAN NOT NULL DEFAULT FALSE,
+    x INTEGER,
+    y INTEGER,
+    width INTEGER,
+    height INTEGER,
+    FOREIGN KEY (frame_id) REFERENCES frames(id)
+);
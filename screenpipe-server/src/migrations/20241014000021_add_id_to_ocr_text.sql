@@ -0,0 +1,61 @@
+-- ocr_text had no INTEGER PRIMARY KEY, so its implicit rowid, which frame_trigrams is keyed on,
+-- could be renumbered by a VACUUM. The rows keep their rowid as the new id.
+PRAGMA foreign_keys=off;
+
+CREATE TABLE IF NOT EXISTS ocr_text_new (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    frame_id INTEGER NOT NULL,
+    text TEXT NOT NULL,
+    text_json TEXT,
+    app_name TEXT NOT NULL DEFAULT '',
+    ocr_engine TEXT NOT NULL DEFAULT 'unknown',
+    window_name TEXT,
+    focused BOOLEAN DEFAULT FALSE,
+    content_type TEXT,
+    confidence REAL
+);
+
+INSERT INTO ocr_text_new (id, frame_id, text, text_json, app_name, ocr_engine, window_name, focused, content_type, confidence)
+SELECT rowid, frame_id, text, text_json, app_name, ocr_engine, window_name, focused, content_type, confidence
+FROM ocr_text;
+
+-- also drops the triggers keeping ocr_text_fts and frame_trigrams up to date
+DROP TABLE ocr_text;
+
+ALTER TABLE ocr_text_new RENAME TO ocr_text;
+
+CREATE INDEX IF NOT EXISTS idx_ocr_text_content_type ON ocr_text(content_type);
+CREATE INDEX IF NOT EXISTS idx_ocr_text_app_window_frame_id ON ocr_text(app_name, window_name, frame_id);
+
+CREATE TRIGGER IF NOT EXISTS ocr_text_fts_ai AFTER INSERT ON ocr_text BEGIN
+  INSERT INTO ocr_text_fts(rowid, text) VALUES (new.frame_id, new.text);
+END;
+
+CREATE TRIGGER IF NOT EXISTS ocr_text_fts_ad AFTER DELETE ON ocr_text BEGIN
+  INSERT INTO ocr_text_fts(ocr_text_fts, rowid, text) VALUES ('delete', old.frame_id, old.text);
+END;
+
+CREATE TRIGGER IF NOT EXISTS ocr_text_fts_au AFTER UPDATE OF text ON ocr_text BEGIN
+  INSERT INTO ocr_text_fts(ocr_text_fts, rowid, text) VALUES ('delete', old.frame_id, old.text);
+  INSERT INTO ocr_text_fts(rowid, text) VALUES (new.frame_id, new.text);
+END;
+
+-- the trigram index is keyed on the new id
+DROP TABLE IF EXISTS frame_trigrams;
+CREATE VIRTUAL TABLE IF NOT EXISTS frame_trigrams USING fts5(text, content='ocr_text', content_rowid='id', tokenize='trigram');
+INSERT INTO frame_trigrams(frame_trigrams) VALUES ('rebuild');
+
+CREATE TRIGGER IF NOT EXISTS frame_trigrams_ai AFTER INSERT ON ocr_text BEGIN
+  INSERT INTO frame_trigrams(rowid, text) VALUES (new.id, new.text);
+END;
+
+CREATE TRIGGER IF NOT EXISTS frame_trigrams_ad AFTER DELETE ON ocr_text BEGIN
+  INSERT INTO frame_trigrams(frame_trigrams, rowid, text) VALUES ('delete', old.id, old.text);
+END;
+
+CREATE TRIGGER IF NOT EXISTS frame_trigrams_au AFTER UPDATE OF text ON ocr_text BEGIN
+  INSERT INTO frame_trigrams(frame_trigrams, rowid, text) VALUES ('delete', old.id, old.text);
+  INSERT INTO frame_trigrams(rowid, text) VALUES (new.id, new.text);
+END;
+
+PRAGMA foreign_keys=on;
@@ -0,0 +1,74 @@
+use anyhow::{anyhow, Result};
+use log::warn;
+use rhai::{Dynamic, Engine, EvalAltResult, Scope, AST};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// A script still running after this long is stopped and the text kept as it was.
+pub const OCR_SCRIPT_TIMEOUT: Duration = Duration::from_millis(100);
+
+/// `--ocr-postprocess-script`: a Rhai script rewriting the ocr text of each window before it is
+/// stored. It sees `text` and the engine's `confidence` (0.0 to 1.0) and returns the new text,
+/// e.g. `text.replace("CONFIDENTIAL", ""); text`.
+pub struct OcrScript {
+    ast: AST,
+    timeout: Duration,
+}
+
+impl OcrScript {
+    /// Compiles the script, syntax errors are reported at startup rather than on each frame.
+    pub fn load(path: &Path) -> Result<Self> {
+        let ast = Engine::new()
+            .compile_file(path.to_path_buf())
+            .map_err(|e| anyhow!("failed to compile {}: {}", path.display(), e))?;
+        Ok(OcrScript {
+            ast,
+            timeout: OCR_SCRIPT_TIMEOUT,
+        })
+    }
+
+    pub fn from_source(source: &str) -> Result<Self> {
+        let ast = Engine::new()
+            .compile(source)
+            .map_err(|e| anyhow!("failed to compile ocr script: {}", e))?;
+        Ok(OcrScript {
+            ast,
+            timeout: OCR_SCRIPT_TIMEOUT,
+        })
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    fn eval(&self, text: &str, confidence: f64) -> Result<String, Box<EvalAltResult>> {
+        // an engine per run, so concurrent monitors each get their own deadline
+        let mut engine = Engine::new();
+        let deadline = Instant::now() + self.timeout;
+        engine.on_progress(move |_| (Instant::now() > deadline).then_some(Dynamic::UNIT));
+
+        let mut scope = Scope::new();
+        scope.push("text", text.to_string());
+        scope.push("confidence", confidence);
+        engine.eval_ast_with_scope::<String>(&mut scope, &self.ast)
+    }
+
+    /// The rewritten text, or `text` itself when the script fails or times out.
+    pub fn run(&self, text: &str, confidence: f64) -> String {
+        match self.eval(text, confidence) {
+            Ok(text) => text,
+            Err(e) if matches!(*e, EvalAltResult::ErrorTerminated(..)) => {
+                warn!(
+                    "ocr postprocess script stopped after {:?}, text kept as is",
+                    self.timeout
+                );
+                text.to_string()
+            }
+            Err(e) => {
+                warn!("ocr postprocess script failed, text kept as is: {}", e);
+                text.to_string()
+            }
+        }
+    }
+}
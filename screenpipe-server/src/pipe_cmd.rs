@@ -0,0 +1,110 @@
+use crate::db::TagContentType;
+use crate::DatabaseManager;
+use anyhow::{anyhow, bail, Result};
+use chrono::{DateTime, Utc};
+use log::{debug, warn};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::process::Stdio;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+use tokio::sync::Semaphore;
+use tokio::time::timeout;
+
+/// The executable is killed when it has not answered within this time.
+const PIPE_CMD_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Written as JSON to the stdin of `--pipe-cmd` for each stored frame.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PipeCmdInput {
+    pub timestamp: DateTime<Utc>,
+    pub ocr_text: String,
+    pub app_name: String,
+    /// jpeg thumbnail of the frame
+    pub frame_path: PathBuf,
+}
+
+/// Expected as JSON on the stdout of `--pipe-cmd`, both fields may be left out.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PipeCmdOutput {
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub notes: String,
+}
+
+/// Runs an external executable on stored frames and saves the tags / notes it returns.
+/// At most `concurrency` runs at once, frames arriving while all are busy are skipped so a slow
+/// executable never holds up recording.
+pub struct PipeCmd {
+    cmd: String,
+    db: Arc<DatabaseManager>,
+    permits: Arc<Semaphore>,
+}
+
+impl PipeCmd {
+    pub fn new(cmd: String, concurrency: usize, db: Arc<DatabaseManager>) -> Self {
+        Self {
+            cmd,
+            db,
+            permits: Arc::new(Semaphore::new(concurrency)),
+        }
+    }
+
+    pub fn submit(self: &Arc<Self>, frame_id: i64, input: PipeCmdInput) {
+        let Ok(permit) = Arc::clone(&self.permits).try_acquire_owned() else {
+            debug!("pipe cmd busy, skipping frame {}", frame_id);
+            return;
+        };
+        let pipe_cmd = Arc::clone(self);
+        tokio::spawn(async move {
+            let _permit = permit;
+            if let Err(e) = pipe_cmd.process(frame_id, &input).await {
+                warn!("pipe cmd failed for frame {}: {}", frame_id, e);
+            }
+        });
+    }
+
+    async fn process(&self, frame_id: i64, input: &PipeCmdInput) -> Result<()> {
+        let output = run_pipe_cmd(&self.cmd, input).await?;
+        if !output.tags.is_empty() {
+            self.db
+                .add_tags(frame_id, TagContentType::Vision, output.tags)
+                .await?;
+        }
+        if !output.notes.is_empty() {
+            self.db.set_frame_notes(frame_id, &output.notes).await?;
+        }
+        Ok(())
+    }
+}
+
+pub async fn run_pipe_cmd(cmd: &str, input: &PipeCmdInput) -> Result<PipeCmdOutput> {
+    let mut child = Command::new(cmd)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()?;
+
+    let mut stdin = child
+        .stdin
+        .take()
+        .ok_or_else(|| anyhow!("failed to open stdin"))?;
+    stdin.write_all(&serde_json::to_vec(input)?).await?;
+    drop(stdin);
+
+    let output = timeout(PIPE_CMD_TIMEOUT, child.wait_with_output())
+        .await
+        .map_err(|_| anyhow!("timed out after {}s", PIPE_CMD_TIMEOUT.as_secs()))??;
+    if !output.status.success() {
+        bail!(
+            "exited with {}: {}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(serde_json::from_slice(&output.stdout)?)
+}
@@ -0,0 +1,103 @@
+//! `--rate-limit-rps`: a token bucket per client ip in front of the api, so one misbehaving
+//! client can't starve the database for everyone else.
+
+use axum::body::Body;
+use axum::extract::{ConnectInfo, Request, State};
+use axum::http::header::{AUTHORIZATION, RETRY_AFTER};
+use axum::http::StatusCode;
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Json, Response};
+use axum::Router;
+use governor::clock::Clock;
+use governor::{DefaultKeyedRateLimiter, Quota, RateLimiter};
+use serde_json::json;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::num::NonZeroU32;
+use std::sync::Arc;
+use std::time::Duration;
+use subtle::ConstantTimeEq;
+
+/// How often the buckets of clients that went quiet are dropped.
+const CLEANUP_INTERVAL: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone)]
+pub struct RateLimit {
+    pub requests_per_second: NonZeroU32,
+    /// Requests a client may send at once before being held to `requests_per_second`
+    pub burst: NonZeroU32,
+    /// Requests with `Authorization: Bearer <exempt_key>` are never limited
+    pub exempt_key: Option<String>,
+}
+
+struct Limiter {
+    buckets: DefaultKeyedRateLimiter<IpAddr>,
+    exempt_key: Option<String>,
+}
+
+/// Compares the key in constant time, so response times don't tell how much of a guess is right.
+fn is_exempt(request: &Request, exempt_key: Option<&str>) -> bool {
+    let Some(exempt_key) = exempt_key else {
+        return false;
+    };
+    request
+        .headers()
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .is_some_and(|key| key.trim().as_bytes().ct_eq(exempt_key.as_bytes()).into())
+}
+
+async fn limit_rate(
+    State(limiter): State<Arc<Limiter>>,
+    request: Request,
+    next: Next,
+) -> Response<Body> {
+    if is_exempt(&request, limiter.exempt_key.as_deref()) {
+        return next.run(request).await;
+    }
+    // requests handed to the router without a socket, e.g. in tests, share one bucket
+    let ip = request
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map_or(IpAddr::V4(Ipv4Addr::UNSPECIFIED), |info| info.0.ip());
+    match limiter.buckets.check_key(&ip) {
+        Ok(()) => next.run(request).await,
+        Err(not_until) => {
+            let wait = not_until.wait_time_from(limiter.buckets.clock().now());
+            let retry_after = (wait.as_secs() + u64::from(wait.subsec_nanos() > 0)).max(1);
+            (
+                StatusCode::TOO_MANY_REQUESTS,
+                [(RETRY_AFTER, retry_after.to_string())],
+                Json(json!({
+                    "error": format!("too many requests, retry in {} seconds", retry_after)
+                })),
+            )
+                .into_response()
+        }
+    }
+}
+
+/// Answers `429 Too Many Requests` with a `Retry-After` to clients over `rate_limit`. Needs a
+/// tokio runtime, buckets of idle clients are dropped in the background.
+pub fn with_rate_limit<S>(router: Router<S>, rate_limit: RateLimit) -> Router<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    let quota = Quota::per_second(rate_limit.requests_per_second).allow_burst(rate_limit.burst);
+    let limiter = Arc::new(Limiter {
+        buckets: RateLimiter::keyed(quota),
+        exempt_key: rate_limit.exempt_key,
+    });
+    let cleanup = Arc::downgrade(&limiter);
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(CLEANUP_INTERVAL);
+        loop {
+            interval.tick().await;
+            let Some(limiter) = cleanup.upgrade() else {
+                break;
+            };
+            limiter.buckets.retain_recent();
+        }
+    });
+    router.layer(middleware::from_fn_with_state(limiter, limit_rate))
+}
@@ -0,0 +1,39 @@
+use tokio::sync::watch;
+
+/// Whether the recorder should run, switched by `POST /recording/start` and
+/// `POST /recording/stop`. With `--manual-start` it starts out stopped.
+pub struct RecordingControl {
+    running: watch::Sender<bool>,
+}
+
+impl RecordingControl {
+    pub fn new(running: bool) -> Self {
+        RecordingControl {
+            running: watch::Sender::new(running),
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        *self.running.borrow()
+    }
+
+    /// Returns `false` when recording was already running.
+    pub fn start(&self) -> bool {
+        self.running
+            .send_if_modified(|running| !std::mem::replace(running, true))
+    }
+
+    /// Returns `false` when recording was already stopped.
+    pub fn stop(&self) -> bool {
+        self.running
+            .send_if_modified(|running| std::mem::replace(running, false))
+    }
+
+    pub async fn started(&self) {
+        let _ = self.running.subscribe().wait_for(|running| *running).await;
+    }
+
+    pub async fn stopped(&self) {
+        let _ = self.running.subscribe().wait_for(|running| !*running).await;
+    }
+}
@@ -0,0 +1,184 @@
+//! `--remote-sync-url`: pushes the frames and transcriptions recorded here to the `POST /import`
+//! of another screenpipe instance, signed with the `--remote-sync-secret` both of them share.
+//! Media files stay on the machine that recorded them, only the rows are sent.
+
+use crate::DatabaseManager;
+use anyhow::{bail, Result};
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use log::{debug, warn};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use sqlx::FromRow;
+use std::sync::Arc;
+use std::time::Duration;
+use sysinfo::{System, SystemExt};
+
+/// Hex HMAC-SHA256 of the request body.
+pub const SIGNATURE_HEADER: &str = "x-screenpipe-signature";
+
+const SYNC_BATCH: u32 = 100;
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+const RETRY_DELAY: Duration = Duration::from_secs(30);
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// A frame with its ocr text, `text` is `None` for frames stored without one.
+#[derive(FromRow, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SyncedFrame {
+    pub id: i64,
+    pub timestamp: DateTime<Utc>,
+    pub text: Option<String>,
+    pub text_json: String,
+    pub app_name: String,
+    pub window_name: String,
+    pub ocr_engine: String,
+    pub focused: bool,
+}
+
+#[derive(FromRow, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SyncedTranscription {
+    pub id: i64,
+    pub timestamp: DateTime<Utc>,
+    pub transcription: String,
+    pub transcription_engine: String,
+    pub device: String,
+    pub is_input_device: bool,
+}
+
+/// Body of `POST /import`, ids are those of the sending machine and only grow, so a batch sent
+/// again after a lost response is not imported twice.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SyncBatch {
+    /// Host name of the sending machine
+    pub source: String,
+    pub frames: Vec<SyncedFrame>,
+    pub transcriptions: Vec<SyncedTranscription>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ImportedRows {
+    pub frames: usize,
+    pub transcriptions: usize,
+}
+
+fn hmac(secret: &str, payload: &[u8]) -> HmacSha256 {
+    let mut mac =
+        HmacSha256::new_from_slice(secret.as_bytes()).expect("hmac takes keys of any size");
+    mac.update(payload);
+    mac
+}
+
+pub fn sign_payload(secret: &str, payload: &[u8]) -> String {
+    hex::encode(hmac(secret, payload).finalize().into_bytes())
+}
+
+/// Checks the [`SIGNATURE_HEADER`] of a request in constant time.
+pub fn verify_signature(secret: &str, payload: &[u8], signature: &str) -> bool {
+    match hex::decode(signature.trim()) {
+        Ok(signature) => hmac(secret, payload).verify_slice(&signature).is_ok(),
+        Err(_) => false,
+    }
+}
+
+pub struct RemoteSync {
+    db: Arc<DatabaseManager>,
+    client: reqwest::Client,
+    url: String,
+    secret: String,
+    source: String,
+}
+
+impl RemoteSync {
+    /// `url` is the base url of the remote instance, rows are sent to its `/import`.
+    pub fn new(db: Arc<DatabaseManager>, url: String, secret: String) -> Result<Self> {
+        let client = reqwest::Client::builder()
+            .timeout(REQUEST_TIMEOUT)
+            .build()?;
+        let source = System::new()
+            .host_name()
+            .unwrap_or_else(|| "unknown".to_string());
+        Ok(RemoteSync {
+            db,
+            client,
+            url: url.trim_end_matches('/').to_string(),
+            secret,
+            source,
+        })
+    }
+
+    /// Pushes every row committed since the last sync, returns how many were sent. Progress is
+    /// saved after each accepted batch, a restart resumes where it stopped.
+    pub async fn sync_once(&self) -> Result<usize> {
+        let (mut last_frame_id, mut last_transcription_id) =
+            self.db.get_remote_sync_state(&self.url).await?;
+        let mut sent = 0;
+        loop {
+            let frames = self
+                .db
+                .get_frames_to_sync(last_frame_id, SYNC_BATCH)
+                .await?;
+            let transcriptions = self
+                .db
+                .get_transcriptions_to_sync(last_transcription_id, SYNC_BATCH)
+                .await?;
+            if frames.is_empty() && transcriptions.is_empty() {
+                return Ok(sent);
+            }
+            let batch = SyncBatch {
+                source: self.source.clone(),
+                frames,
+                transcriptions,
+            };
+            let payload = serde_json::to_vec(&batch)?;
+            let response = self
+                .client
+                .post(format!("{}/import", self.url))
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .header(SIGNATURE_HEADER, sign_payload(&self.secret, &payload))
+                .body(payload)
+                .send()
+                .await?;
+            let status = response.status();
+            if !status.is_success() {
+                bail!(
+                    "{} answered {}: {}",
+                    self.url,
+                    status,
+                    response.text().await?
+                );
+            }
+
+            if let Some(frame) = batch.frames.last() {
+                last_frame_id = frame.id;
+            }
+            if let Some(transcription) = batch.transcriptions.last() {
+                last_transcription_id = transcription.id;
+            }
+            self.db
+                .set_remote_sync_state(&self.url, last_frame_id, last_transcription_id)
+                .await?;
+            sent += batch.frames.len() + batch.transcriptions.len();
+        }
+    }
+
+    /// Syncs once at startup, then each time the recorder commits new rows.
+    pub fn start(self) {
+        tokio::spawn(async move {
+            loop {
+                match self.sync_once().await {
+                    Ok(0) => {}
+                    Ok(sent) => debug!("synced {} rows to {}", sent, self.url),
+                    Err(e) => {
+                        warn!("remote sync to {} failed: {}", self.url, e);
+                        tokio::time::sleep(RETRY_DELAY).await;
+                        continue;
+                    }
+                }
+                self.db.new_rows.notified().await;
+            }
+        });
+    }
+}
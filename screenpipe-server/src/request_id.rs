@@ -0,0 +1,71 @@
+//! `X-Request-Id` on every api response, generated when the client sent none. A request is
+//! handled inside a span carrying its id, and the work it hands to other tasks is spawned in that
+//! same span, so its logs can be followed from the handler to the database and back.
+
+use axum::http::Request;
+use axum::Router;
+use std::future::Future;
+use tokio::task::JoinHandle;
+use tower_http::request_id::{MakeRequestUuid, PropagateRequestIdLayer, SetRequestIdLayer};
+use tower_http::trace::{DefaultOnResponse, MakeSpan, TraceLayer};
+use tower_http::LatencyUnit;
+use tracing::{Instrument, Span};
+
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// Span of a request, its `request_id` field shows up on every log line written while handling
+/// it, in the json logs as a field of its own. Headers are left out, they carry the api key.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RequestSpan;
+
+impl<B> MakeSpan<B> for RequestSpan {
+    fn make_span(&mut self, request: &Request<B>) -> Span {
+        let request_id = request
+            .headers()
+            .get(REQUEST_ID_HEADER)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or_default();
+        tracing::info_span!(
+            "request",
+            request_id = %request_id,
+            method = %request.method(),
+            uri = %request.uri(),
+        )
+    }
+}
+
+/// Gives each request of `router` an id, sent back as `X-Request-Id`, and handles it in a
+/// [`RequestSpan`].
+pub fn with_request_tracing<S>(router: Router<S>) -> Router<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    // the last layer sees the request first, the id is set before the span is made
+    router
+        .layer(
+            TraceLayer::new_for_http()
+                .make_span_with(RequestSpan)
+                .on_response(DefaultOnResponse::new().latency_unit(LatencyUnit::Millis)),
+        )
+        .layer(PropagateRequestIdLayer::x_request_id())
+        .layer(SetRequestIdLayer::x_request_id(MakeRequestUuid))
+}
+
+/// `tokio::spawn` in the span of the caller, so the task logs with the caller's request id.
+pub fn spawn_in_current_span<F>(future: F) -> JoinHandle<F::Output>
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    tokio::spawn(future.in_current_span())
+}
+
+/// `spawn_blocking` in the span of the caller.
+pub fn spawn_blocking_in_current_span<F, R>(f: F) -> JoinHandle<R>
+where
+    F: FnOnce() -> R + Send + 'static,
+    R: Send + 'static,
+{
+    let span = Span::current();
+    tokio::task::spawn_blocking(move || span.in_scope(f))
+}
@@ -0,0 +1,67 @@
+use crate::request_id::spawn_in_current_span;
+use crate::server::AppState;
+use axum::{
+    extract::{MatchedPath, Request, State},
+    middleware::Next,
+    response::Response,
+};
+use log::{debug, error, warn};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+pub const SLOW_REQUEST_THRESHOLD: Duration = Duration::from_millis(500);
+
+/// Polled by monitoring every few seconds, a row for each would crowd out the requests of users.
+pub const UNLOGGED_PATHS: &[&str] = &["/health", "/health/deep", "/stats"];
+
+/// Whether requests to the route `path` get a row in `request_log`.
+pub fn is_logged_path(path: &str) -> bool {
+    !UNLOGGED_PATHS.contains(&path)
+}
+
+/// Records the duration of every request but the health and stats polls in the `request_log`
+/// table, warning about slow ones.
+pub(crate) async fn log_request_duration(
+    State(state): State<Arc<AppState>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let method = request.method().to_string();
+    // use the route template so e.g. /frames/:frame_id/thumbnail is grouped
+    let path = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_string())
+        .unwrap_or_else(|| request.uri().path().to_string());
+
+    let start = Instant::now();
+    let response = next.run(request).await;
+    let duration = start.elapsed();
+    let status = response.status().as_u16();
+
+    if duration > SLOW_REQUEST_THRESHOLD {
+        warn!(
+            "slow request: {} {} returned {} in {:?}",
+            method, path, status, duration
+        );
+    } else {
+        debug!("{} {} returned {} in {:?}", method, path, status, duration);
+    }
+    if !is_logged_path(&path) {
+        return response;
+    }
+
+    // don't make the client wait for the insert
+    let db = Arc::clone(&state.db);
+    spawn_in_current_span(async move {
+        let duration_ms = duration.as_secs_f64() * 1000.0;
+        if let Err(e) = db
+            .insert_request_log(&path, &method, status, duration_ms)
+            .await
+        {
+            error!("failed to record request duration: {}", e);
+        }
+    });
+
+    response
+}
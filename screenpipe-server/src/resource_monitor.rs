@@ -1,4 +1,5 @@
 use chrono::Local;
+use serde::{Deserialize, Serialize};
 use serde_json::json;
 use serde_json::Value;
 use std::env;
@@ -8,15 +9,111 @@ use std::io::Read;
 use std::io::Seek;
 use std::io::SeekFrom;
 use std::io::Write;
+use std::path::PathBuf;
 use std::process::Command;
-use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
-use sysinfo::{PidExt, ProcessExt, System, SystemExt};
-use tracing::{error, info};
+use sysinfo::{CpuExt, DiskExt, PidExt, ProcessExt, System, SystemExt};
+use tokio::sync::Notify;
+use tracing::{debug, error, info, warn};
+
+/// How long cpu usage has to stay above the threshold before alerting.
+const CPU_ALERT_DURATION: Duration = Duration::from_secs(60);
+
+const MEMORY_LOG_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Least time between two `--max-memory-mb` restarts, memory held outside the recording tasks
+/// would otherwise restart them over and over.
+const MEMORY_RESTART_COOLDOWN: Duration = Duration::from_secs(300);
+
+/// Resident memory of screenpipe and its child processes in bytes, as of the last check.
+pub static MEMORY_USAGE_BYTES: AtomicU64 = AtomicU64::new(0);
+
+/// `GET /health` reports `degraded` when the data disk is fuller than this.
+pub const DISK_DEGRADED_PCT: f32 = 95.0;
+
+/// Usage of the disk holding the data dir, as of the last check.
+pub static DISK_USAGE: DiskUsageStatus = DiskUsageStatus::new();
+
+pub struct DiskUsageStatus {
+    used_bytes: AtomicU64,
+    available_bytes: AtomicU64,
+}
+
+impl DiskUsageStatus {
+    pub const fn new() -> Self {
+        DiskUsageStatus {
+            used_bytes: AtomicU64::new(0),
+            available_bytes: AtomicU64::new(0),
+        }
+    }
+
+    /// `None` until the monitor found the disk.
+    pub fn snapshot(&self) -> Option<DiskUsage> {
+        let used = self.used_bytes.load(Ordering::Relaxed);
+        let available = self.available_bytes.load(Ordering::Relaxed);
+        (used + available > 0).then(|| DiskUsage::new(used, available))
+    }
+
+    fn set(&self, usage: &DiskUsage) {
+        self.used_bytes.store(usage.used_bytes, Ordering::Relaxed);
+        self.available_bytes
+            .store(usage.available_bytes, Ordering::Relaxed);
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct DiskUsage {
+    pub used_bytes: u64,
+    pub available_bytes: u64,
+    pub usage_pct: f32,
+}
+
+impl DiskUsage {
+    pub fn new(used_bytes: u64, available_bytes: u64) -> Self {
+        let total = used_bytes + available_bytes;
+        DiskUsage {
+            used_bytes,
+            available_bytes,
+            usage_pct: if total == 0 {
+                0.0
+            } else {
+                used_bytes as f32 / total as f32 * 100.0
+            },
+        }
+    }
+
+    pub fn is_degraded(&self) -> bool {
+        self.usage_pct > DISK_DEGRADED_PCT
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct AlertThresholds {
+    /// Disk whose usage is watched, the one holding the screenpipe data.
+    pub data_dir: PathBuf,
+    pub disk_pct: f32,
+    pub cpu_pct: f32,
+    /// Recording is restarted when memory goes above this
+    pub max_memory_mb: Option<u64>,
+}
+
+#[derive(Default)]
+struct AlertState {
+    cpu_high_since: Option<Instant>,
+    cpu_alerted: bool,
+    disk_alerted: bool,
+    memory_logged_at: Option<Instant>,
+    memory_restarted_at: Option<Instant>,
+}
 
 pub struct ResourceMonitor {
     start_time: Instant,
     resource_log_file: Option<String>, // analyse output here: https://colab.research.google.com/drive/1zELlGdzGdjChWKikSqZTHekm5XRxY-1r?usp=sharing
+    alert_thresholds: AlertThresholds,
+    alert_state: Mutex<AlertState>,
+    restart: Notify,
 }
 
 pub enum RestartSignal {
@@ -24,7 +121,7 @@ pub enum RestartSignal {
 }
 
 impl ResourceMonitor {
-    pub fn new() -> Arc<Self> {
+    pub fn new(alert_thresholds: AlertThresholds) -> Arc<Self> {
         let resource_log_file = if env::var("SAVE_RESOURCE_USAGE").is_ok() {
             let now = Local::now();
             let filename = format!("resource_usage_{}.json", now.format("%Y%m%d_%H%M%S"));
@@ -47,9 +144,120 @@ impl ResourceMonitor {
         Arc::new(Self {
             start_time: Instant::now(),
             resource_log_file,
+            alert_thresholds,
+            alert_state: Mutex::new(AlertState::default()),
+            restart: Notify::new(),
         })
     }
 
+    /// Resolves once the recording tasks should be restarted to release memory.
+    pub async fn restart_requested(&self) -> RestartSignal {
+        self.restart.notified().await;
+        RestartSignal::RecordingTasks
+    }
+
+    fn check_memory(&self, sys: &System) {
+        let pid = sysinfo::Pid::from_u32(std::process::id());
+        let Some(process) = sys.process(pid) else {
+            return;
+        };
+        let memory = process.memory()
+            + sys
+                .processes()
+                .values()
+                .filter(|child| child.parent() == Some(pid))
+                .map(|child| child.memory())
+                .sum::<u64>();
+        MEMORY_USAGE_BYTES.store(memory, Ordering::Relaxed);
+        let memory_mb = memory / 1024 / 1024;
+
+        let mut state = self.alert_state.lock().unwrap();
+        if state
+            .memory_logged_at
+            .map_or(true, |at| at.elapsed() >= MEMORY_LOG_INTERVAL)
+        {
+            state.memory_logged_at = Some(Instant::now());
+            debug!("memory usage: {} MB", memory_mb);
+        }
+
+        let Some(max_memory_mb) = self.alert_thresholds.max_memory_mb else {
+            return;
+        };
+        let cooled_down = state
+            .memory_restarted_at
+            .map_or(true, |at| at.elapsed() >= MEMORY_RESTART_COOLDOWN);
+        if memory_mb > max_memory_mb && cooled_down {
+            state.memory_restarted_at = Some(Instant::now());
+            warn!(
+                "memory usage {} MB is above --max-memory-mb {}, restarting recording",
+                memory_mb, max_memory_mb
+            );
+            self.restart.notify_one();
+        }
+    }
+
+    /// Notifies once per episode, an alert is re-armed when usage drops back under the threshold.
+    fn check_alerts(&self, sys: &System) {
+        let mut state = self.alert_state.lock().unwrap();
+
+        let cpu_usage = sys.global_cpu_info().cpu_usage();
+        if cpu_usage > self.alert_thresholds.cpu_pct {
+            let high_since = *state.cpu_high_since.get_or_insert_with(Instant::now);
+            if !state.cpu_alerted && high_since.elapsed() >= CPU_ALERT_DURATION {
+                state.cpu_alerted = true;
+                alert(
+                    "screenpipe: high cpu usage",
+                    &format!(
+                        "CPU usage has been above {:.0}% for over {}s (currently {:.0}%)",
+                        self.alert_thresholds.cpu_pct,
+                        CPU_ALERT_DURATION.as_secs(),
+                        cpu_usage
+                    ),
+                );
+            }
+        } else {
+            state.cpu_high_since = None;
+            state.cpu_alerted = false;
+        }
+
+        if let Some(disk_usage) = self.data_disk_usage(sys) {
+            DISK_USAGE.set(&disk_usage);
+            if disk_usage.usage_pct > self.alert_thresholds.disk_pct {
+                if !state.disk_alerted {
+                    state.disk_alerted = true;
+                    alert(
+                        "screenpipe: disk almost full",
+                        &format!(
+                            "Disk holding {} is {:.0}% full",
+                            self.alert_thresholds.data_dir.display(),
+                            disk_usage.usage_pct
+                        ),
+                    );
+                }
+            } else {
+                state.disk_alerted = false;
+            }
+        }
+    }
+
+    /// Usage of the disk with the longest mount point containing the data dir.
+    fn data_disk_usage(&self, sys: &System) -> Option<DiskUsage> {
+        let disk = sys
+            .disks()
+            .iter()
+            .filter(|disk| {
+                self.alert_thresholds
+                    .data_dir
+                    .starts_with(disk.mount_point())
+            })
+            .max_by_key(|disk| disk.mount_point().as_os_str().len())?;
+        if disk.total_space() == 0 {
+            return None;
+        }
+        let used = disk.total_space().saturating_sub(disk.available_space());
+        Some(DiskUsage::new(used, disk.available_space()))
+    }
+
     fn log_status(&self, sys: &System) {
         let pid = std::process::id();
         let main_process = sys.process(sysinfo::Pid::from_u32(pid));
@@ -150,6 +358,8 @@ impl ResourceMonitor {
                     _ = tokio::time::sleep(interval) => {
                         sys.refresh_all();
                         monitor.log_status(&sys);
+                        monitor.check_alerts(&sys);
+                        monitor.check_memory(&sys);
                     }
                 }
             }
@@ -187,3 +397,19 @@ impl ResourceMonitor {
         None
     }
 }
+
+fn alert(summary: &str, body: &str) {
+    warn!("{}: {}", summary, body);
+    if let Err(e) = send_desktop_notification(summary, body) {
+        error!("failed to send desktop notification: {}", e);
+    }
+}
+
+pub fn send_desktop_notification(summary: &str, body: &str) -> anyhow::Result<()> {
+    notify_rust::Notification::new()
+        .appname("screenpipe")
+        .summary(summary)
+        .body(body)
+        .show()?;
+    Ok(())
+}
@@ -0,0 +1,111 @@
+use crate::cli::CliOcrEngine;
+use crate::search_rank::{validate_rank_weight, RankWeights};
+use clap::ValueEnum;
+use screenpipe_vision::{CaptureConfig, OcrEngine, MIN_FPS};
+use serde::{Deserialize, Serialize};
+
+pub const MAX_RUNTIME_FPS: f64 = 30.0;
+
+/// Body of `PATCH /config`, fields left out keep their current value.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RuntimeConfigUpdate {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fps: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dedup_threshold: Option<f64>,
+    /// Same names as `--ocr-engine`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ocr_engine: Option<String>,
+    /// Share of the full text relevance in the search `rank`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rank_relevance_weight: Option<f64>,
+    /// Share of the recency in the search `rank`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rank_recency_weight: Option<f64>,
+}
+
+/// `GET /config`: the capture settings in effect, the cli values unless updated since.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RuntimeConfigResponse {
+    pub fps: f64,
+    pub dedup_threshold: f64,
+    pub ocr_engine: String,
+    pub rank_relevance_weight: f64,
+    pub rank_recency_weight: f64,
+}
+
+impl RuntimeConfigResponse {
+    pub fn new(config: &CaptureConfig, rank_weights: &RankWeights) -> Self {
+        RuntimeConfigResponse {
+            fps: config.fps,
+            dedup_threshold: config.dedup_threshold,
+            ocr_engine: ocr_engine_name(&config.ocr_engine).to_string(),
+            rank_relevance_weight: rank_weights.relevance,
+            rank_recency_weight: rank_weights.recency,
+        }
+    }
+}
+
+impl RuntimeConfigUpdate {
+    /// Validates every field before changing any, so a bad update leaves the config untouched.
+    pub fn apply(
+        &self,
+        config: &mut CaptureConfig,
+        rank_weights: &mut RankWeights,
+    ) -> Result<(), String> {
+        if let Some(fps) = self.fps {
+            if !(MIN_FPS..=MAX_RUNTIME_FPS).contains(&fps) {
+                return Err(format!(
+                    "fps must be between {} and {}",
+                    MIN_FPS, MAX_RUNTIME_FPS
+                ));
+            }
+        }
+        if let Some(threshold) = self.dedup_threshold {
+            if !(0.0..=1.0).contains(&threshold) {
+                return Err("dedup_threshold must be between 0 and 1".to_string());
+            }
+        }
+        if let Some(weight) = self.rank_relevance_weight {
+            validate_rank_weight("rank_relevance_weight", weight)?;
+        }
+        if let Some(weight) = self.rank_recency_weight {
+            validate_rank_weight("rank_recency_weight", weight)?;
+        }
+        let ocr_engine = match &self.ocr_engine {
+            Some(name) => Some(
+                CliOcrEngine::from_str(name, true)
+                    .map(OcrEngine::from)
+                    .map_err(|_| format!("unsupported ocr_engine '{}'", name))?,
+            ),
+            None => None,
+        };
+
+        if let Some(fps) = self.fps {
+            config.fps = fps;
+        }
+        if let Some(threshold) = self.dedup_threshold {
+            config.dedup_threshold = threshold;
+        }
+        if let Some(ocr_engine) = ocr_engine {
+            config.ocr_engine = ocr_engine;
+        }
+        if let Some(weight) = self.rank_relevance_weight {
+            rank_weights.relevance = weight;
+        }
+        if let Some(weight) = self.rank_recency_weight {
+            rank_weights.recency = weight;
+        }
+        Ok(())
+    }
+}
+
+pub(crate) fn ocr_engine_name(engine: &OcrEngine) -> &'static str {
+    match engine {
+        OcrEngine::Unstructured => "unstructured",
+        OcrEngine::Tesseract => "tesseract",
+        OcrEngine::WindowsNative => "windows-native",
+        OcrEngine::AppleNative => "apple-native",
+    }
+}
@@ -0,0 +1,62 @@
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Where a search stopped for one content type. Results are ordered by (timestamp, id)
+/// descending, so the next page starts strictly before the last returned row.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CursorPosition {
+    #[default]
+    Start,
+    After {
+        timestamp: DateTime<Utc>,
+        id: i64,
+    },
+    Done,
+}
+
+impl CursorPosition {
+    pub fn after(&self) -> Option<(DateTime<Utc>, i64)> {
+        match self {
+            CursorPosition::After { timestamp, id } => Some((*timestamp, *id)),
+            _ => None,
+        }
+    }
+
+    /// Position following a page of `returned` results, `last` being the oldest one.
+    pub fn next(returned: usize, limit: u32, last: Option<(DateTime<Utc>, i64)>) -> Self {
+        match last {
+            Some((timestamp, id)) if returned >= limit as usize => {
+                CursorPosition::After { timestamp, id }
+            }
+            _ => CursorPosition::Done,
+        }
+    }
+}
+
+/// The `cursor` / `next_cursor` token of `GET /search`: this struct as JSON, encoded as
+/// URL-safe base64 without padding, e.g. before encoding:
+/// `{"ocr":{"after":{"timestamp":"2024-10-14T09:00:00Z","id":42}},"audio":"done"}`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SearchCursor {
+    #[serde(default)]
+    pub ocr: CursorPosition,
+    #[serde(default)]
+    pub audio: CursorPosition,
+}
+
+impl SearchCursor {
+    pub fn encode(&self) -> String {
+        URL_SAFE_NO_PAD.encode(serde_json::to_vec(self).unwrap_or_default())
+    }
+
+    pub fn decode(token: &str) -> Option<Self> {
+        let json = URL_SAFE_NO_PAD.decode(token).ok()?;
+        serde_json::from_slice(&json).ok()
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.ocr == CursorPosition::Done && self.audio == CursorPosition::Done
+    }
+}
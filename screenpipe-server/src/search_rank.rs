@@ -0,0 +1,98 @@
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, RwLock};
+
+/// Share of the full text relevance and of the recency in the `rank` of a search result,
+/// changed through `PATCH /config`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct RankWeights {
+    pub relevance: f64,
+    pub recency: f64,
+}
+
+impl Default for RankWeights {
+    fn default() -> Self {
+        RankWeights {
+            relevance: 0.6,
+            recency: 0.4,
+        }
+    }
+}
+
+pub type SharedRankWeights = Arc<RwLock<RankWeights>>;
+
+/// `?sort_by=` of `GET /search`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SearchSort {
+    Rank,
+    #[default]
+    Timestamp,
+    AppName,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+    Asc,
+    #[default]
+    Desc,
+}
+
+impl SortOrder {
+    fn sql(self) -> &'static str {
+        match self {
+            SortOrder::Asc => "ASC",
+            SortOrder::Desc => "DESC",
+        }
+    }
+}
+
+/// How the results of a search are ranked and sorted, newest first by default.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct SearchOrder {
+    pub sort_by: SearchSort,
+    pub order: SortOrder,
+    pub weights: RankWeights,
+}
+
+impl SearchOrder {
+    /// Keyset cursors only page through results in the default order.
+    pub fn is_default(&self) -> bool {
+        self.sort_by == SearchSort::Timestamp && self.order == SortOrder::Desc
+    }
+
+    /// SQL for the rank of a row from the `bm25()` of its full text match, NULL when the query
+    /// matched none of its words, and its capture time. Both parts lie between 0 and 1, bm25
+    /// being negative with the best matches lowest, and recency being the inverse log of the
+    /// seconds since capture.
+    pub(crate) fn rank_sql(&self, bm25: &str, timestamp: &str) -> String {
+        format!(
+            "({relevance} * COALESCE(-({bm25}) / (1.0 - ({bm25})), 0.0) \
+             + {recency} / (1.0 + ln(1.0 + MAX(0.0, \
+             (julianday('now') - julianday({timestamp})) * 86400.0))))",
+            relevance = self.weights.relevance,
+            recency = self.weights.recency,
+        )
+    }
+
+    /// `ORDER BY` terms, ties broken newest first so pages don't overlap.
+    pub(crate) fn order_by_sql(&self, app_name: &str, timestamp: &str, id: &str) -> String {
+        let order = self.order.sql();
+        match self.sort_by {
+            SearchSort::Timestamp => format!("{timestamp} {order}, {id} {order}"),
+            SearchSort::Rank => format!("rank {order}, {timestamp} DESC, {id} DESC"),
+            SearchSort::AppName => {
+                format!("{app_name} COLLATE NOCASE {order}, {timestamp} DESC, {id} DESC")
+            }
+        }
+    }
+}
+
+/// Checks the weights given to `PATCH /config`.
+pub fn validate_rank_weight(name: &str, weight: f64) -> Result<(), String> {
+    if weight.is_finite() && weight >= 0.0 {
+        Ok(())
+    } else {
+        Err(format!("{} must be a number of at least 0", name))
+    }
+}
@@ -0,0 +1,91 @@
+use anyhow::{anyhow, Context, Result};
+use serde::Deserialize;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// Shown in place of a secret value in logs and the startup table.
+pub const REDACTED: &str = "***";
+
+/// Cloud api keys and the remote sync secret read from `secrets.toml` in the screenpipe
+/// directory, kept out of the command line and shell history. Flags given on the command line win
+/// over the file.
+#[derive(Default, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Secrets {
+    pub unstructured_api_key: Option<String>,
+    pub openai_api_key: Option<String>,
+    pub deepgram_api_key: Option<String>,
+    pub remote_sync_secret: Option<String>,
+}
+
+impl fmt::Debug for Secrets {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let redact = |key: &Option<String>| key.as_ref().map(|_| REDACTED);
+        f.debug_struct("Secrets")
+            .field("unstructured_api_key", &redact(&self.unstructured_api_key))
+            .field("openai_api_key", &redact(&self.openai_api_key))
+            .field("deepgram_api_key", &redact(&self.deepgram_api_key))
+            .field("remote_sync_secret", &redact(&self.remote_sync_secret))
+            .finish()
+    }
+}
+
+pub fn secrets_path(base_dir: &Path) -> PathBuf {
+    base_dir.join("secrets.toml")
+}
+
+impl Secrets {
+    /// Reads the secrets file, empty when it does not exist. On unix the file must be mode 0600,
+    /// one readable by other users is rejected.
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Secrets::default());
+        }
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(path)?.permissions().mode() & 0o777;
+            if mode & 0o077 != 0 {
+                return Err(anyhow!(
+                    "{} has mode {:o}, it must only be readable by you: chmod 600 {}",
+                    path.display(),
+                    mode,
+                    path.display()
+                ));
+            }
+        }
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        // the toml error quotes the offending line, which may hold a key
+        toml::from_str(&content).map_err(|_| anyhow!("{} is not valid toml", path.display()))
+    }
+
+    /// Names of the keys that are set, for the startup table.
+    pub fn names(&self) -> Vec<&'static str> {
+        [
+            ("unstructured_api_key", &self.unstructured_api_key),
+            ("openai_api_key", &self.openai_api_key),
+            ("deepgram_api_key", &self.deepgram_api_key),
+            ("remote_sync_secret", &self.remote_sync_secret),
+        ]
+        .into_iter()
+        .filter(|(_, key)| key.is_some())
+        .map(|(name, _)| name)
+        .collect()
+    }
+
+    /// Keys read from the environment by the ocr / llm clients and the pipes, only set where the
+    /// environment does not already have them.
+    pub fn export_env(&self) {
+        for (var, key) in [
+            ("UNSTRUCTURED_API_KEY", &self.unstructured_api_key),
+            ("OPENAI_API_KEY", &self.openai_api_key),
+        ] {
+            if let Some(key) = key {
+                if std::env::var_os(var).is_none() {
+                    std::env::set_var(var, key);
+                }
+            }
+        }
+    }
+}
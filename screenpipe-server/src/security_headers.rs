@@ -0,0 +1,50 @@
+use axum::http::header::{
+    HeaderName, CONTENT_SECURITY_POLICY, CONTENT_TYPE, REFERRER_POLICY, X_CONTENT_TYPE_OPTIONS,
+    X_FRAME_OPTIONS,
+};
+use axum::http::{HeaderValue, Response};
+use axum::Router;
+use tower_http::set_header::SetResponseHeaderLayer;
+
+/// Headers added to every response that is not an image, video, audio or raw bytes.
+const SECURITY_HEADERS: [(HeaderName, &str); 4] = [
+    // browsers must not guess html or javascript out of a json or text body
+    (X_CONTENT_TYPE_OPTIONS, "nosniff"),
+    // nothing the api returns is meant to be framed, so no page can overlay it to trick clicks
+    (X_FRAME_OPTIONS, "DENY"),
+    // responses are data, should one be rendered anyway it may not load or run anything
+    (CONTENT_SECURITY_POLICY, "default-src 'none'"),
+    // urls carry search queries and recorded text, keep them from leaking to linked sites
+    (REFERRER_POLICY, "no-referrer"),
+];
+
+fn is_binary<B>(response: &Response<B>) -> bool {
+    response
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|content_type| {
+            ["image/", "video/", "audio/", "application/octet-stream"]
+                .iter()
+                .any(|prefix| content_type.starts_with(prefix))
+        })
+}
+
+/// Adds [`SECURITY_HEADERS`] to the responses of `router`, headers a handler set itself are
+/// kept. Turned off with `--disable-security-headers` when embedding the api in another app.
+pub fn with_security_headers<S>(router: Router<S>) -> Router<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    SECURITY_HEADERS
+        .into_iter()
+        .fold(router, |router, (name, value)| {
+            let value = HeaderValue::from_static(value);
+            router.layer(SetResponseHeaderLayer::if_not_present(
+                name,
+                move |response: &Response<axum::body::Body>| {
+                    (!is_binary(response)).then(|| value.clone())
+                },
+            ))
+        })
+}
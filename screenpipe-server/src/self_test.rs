@@ -0,0 +1,167 @@
+use crate::DatabaseManager;
+use screenpipe_audio::{default_input_device, record_and_transcribe};
+use screenpipe_core::find_ffmpeg_path;
+use screenpipe_vision::{monitor::list_monitors, utils::capture_screenshot, OcrEngine};
+use std::future::Future;
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+use std::time::Duration;
+use uuid::Uuid;
+
+#[derive(Debug, Clone)]
+pub struct CheckResult {
+    pub name: &'static str,
+    pub passed: bool,
+    pub detail: String,
+}
+
+/// Runs every check in order, a failing (or panicking) check does not stop the next ones.
+pub async fn run_self_test(ocr_engine: OcrEngine) -> Vec<CheckResult> {
+    vec![
+        run_check("ffmpeg", check_ffmpeg()).await,
+        run_check("screen capture + ocr", check_capture_and_ocr(ocr_engine)).await,
+        run_check("audio recording", check_audio()).await,
+        run_check("database", check_database()).await,
+    ]
+}
+
+pub fn print_report(results: &[CheckResult]) {
+    println!("screenpipe self-test:");
+    for result in results {
+        println!(
+            "  [{}] {:<22} {}",
+            if result.passed { " OK " } else { "FAIL" },
+            result.name,
+            result.detail
+        );
+    }
+    let failed = results.iter().filter(|r| !r.passed).count();
+    if failed == 0 {
+        println!("all checks passed");
+    } else {
+        println!("{} of {} checks failed", failed, results.len());
+    }
+}
+
+async fn run_check<F>(name: &'static str, check: F) -> CheckResult
+where
+    F: Future<Output = Result<String, String>> + Send + 'static,
+{
+    // capture apis can panic on missing permissions, report it as a failure
+    let result = tokio::spawn(check)
+        .await
+        .unwrap_or_else(|e| Err(format!("check panicked: {}", e)));
+    CheckResult {
+        name,
+        passed: result.is_ok(),
+        detail: result.unwrap_or_else(|e| e),
+    }
+}
+
+async fn check_ffmpeg() -> Result<String, String> {
+    let path = find_ffmpeg_path().ok_or("ffmpeg not found, install it and add it to your path")?;
+    let output = tokio::process::Command::new(&path)
+        .arg("-version")
+        .output()
+        .await
+        .map_err(|e| format!("failed to run {}: {}", path.display(), e))?;
+    if !output.status.success() {
+        return Err(format!("{} exited with {}", path.display(), output.status));
+    }
+    let version = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .next()
+        .unwrap_or_default()
+        .to_string();
+    Ok(version)
+}
+
+async fn check_capture_and_ocr(ocr_engine: OcrEngine) -> Result<String, String> {
+    let monitor = list_monitors()
+        .await
+        .into_iter()
+        .next()
+        .ok_or("no monitor found")?;
+    let (image, _, _, capture_duration) =
+        capture_screenshot(&monitor, &[], &[]).await.map_err(|e| {
+            format!(
+                "failed to capture screen, check screen recording permissions: {}",
+                e
+            )
+        })?;
+    let (text, _, _) = ocr_engine
+        .perform_ocr(&image)
+        .await
+        .map_err(|e| format!("{:?} ocr failed: {}", ocr_engine, e))?;
+    Ok(format!(
+        "{}x{} frame captured in {:?}, {:?} recognized {} characters",
+        image.width(),
+        image.height(),
+        capture_duration,
+        ocr_engine,
+        text.chars().count()
+    ))
+}
+
+async fn check_audio() -> Result<String, String> {
+    let device = default_input_device().map_err(|e| format!("no input device: {}", e))?;
+    let (sender, receiver) = crossbeam::channel::bounded(1);
+    record_and_transcribe(
+        Arc::new(device.clone()),
+        Duration::from_secs(1),
+        sender,
+        Arc::new(AtomicBool::new(true)),
+    )
+    .await
+    .map_err(|e| format!("failed to record from {}: {}", device, e))?;
+
+    let audio = receiver
+        .try_recv()
+        .map_err(|_| format!("no audio received from {}", device))?;
+    if audio.data.iter().all(|&sample| sample == 0.0) {
+        // this is what a denied microphone permission looks like on macos
+        return Err(format!(
+            "only silence recorded from {}, check microphone permissions",
+            device
+        ));
+    }
+    Ok(format!(
+        "{} samples recorded from {} at {} Hz",
+        audio.data.len(),
+        device,
+        audio.sample_rate
+    ))
+}
+
+async fn check_database() -> Result<String, String> {
+    let path = std::env::temp_dir().join(format!("screenpipe-self-test-{}.sqlite", Uuid::new_v4()));
+    let result: Result<String, String> = async {
+        let db = DatabaseManager::new(&path.to_string_lossy())
+            .await
+            .map_err(|e| format!("failed to open database: {}", e))?;
+        db.insert_video_chunk("self-test.mp4")
+            .await
+            .map_err(|e| format!("failed to write: {}", e))?;
+        let frame_id = db
+            .insert_frame()
+            .await
+            .map_err(|e| format!("failed to write: {}", e))?;
+        let frames = db
+            .get_frames(None, None, 1, 0)
+            .await
+            .map_err(|e| format!("failed to read: {}", e))?;
+        db.pool.close().await;
+        match frames.first() {
+            Some(frame) if frame.frame_id == frame_id => {
+                Ok(format!("wrote and read back a frame in {}", path.display()))
+            }
+            _ => Err("frame written but not read back".to_string()),
+        }
+    }
+    .await;
+
+    for suffix in ["", "-wal", "-shm"] {
+        let _ = std::fs::remove_file(format!("{}{}", path.display(), suffix));
+    }
+    result
+}
@@ -1,30 +1,81 @@
 use axum::{
+    body::{Body, Bytes},
     extract::{Path, Query, State},
-    http::StatusCode,
-    response::Json as JsonResponse,
-    routing::{get, post},
+    http::{header, HeaderMap, Method, StatusCode},
+    middleware,
+    response::{IntoResponse, Json as JsonResponse, Response},
+    routing::{delete, get, patch, post, put},
     serve, Router,
 };
 use crossbeam::queue::SegQueue;
-use futures::future::{try_join, try_join_all};
+use futures::future::{join_all, try_join};
+use futures::StreamExt;
+use screenpipe_core::StoragePaths;
 #[cfg(feature = "llm")]
 use screenpipe_core::LLM;
 #[cfg(feature = "llm")]
 use screenpipe_core::{ChatRequest, ChatResponse};
-use screenpipe_vision::monitor::list_monitors;
+use screenpipe_vision::{
+    monitor::{is_virtual_monitor, list_monitors},
+    normalize_ocr_text, OcrEngine, SharedCaptureConfig, IDLE_STATUS,
+};
 
 use crate::{
-    db::TagContentType,
+    audio_integrity::audio_file_present,
+    audio_status,
+    bind::bind_listener,
+    body_limit::{with_body_limit, BodyLimits},
+    cli::CliOcrEngine,
+    csv_export::{csv_filename, parse_csv_columns, stream_frames_csv, DEFAULT_CSV_COLUMNS},
+    db::{
+        ManualTranscriptSegment, RequestLogEntry, Session, TagContentType, TimelineBucket,
+        TimelineResolution, TranscriptCorrection, TranscriptSource,
+    },
+    deep_health::{check_deep_health, DeepHealthResponse},
+    export::{
+        keyframe_interval, stream_export, ExportJob, ExportJobs, ExportVideoRequest,
+        MAX_STREAMED_FRAMES,
+    },
+    frame_diff::FrameDiff,
+    fuzzy::MIN_FUZZY_QUERY_LEN,
+    graphql::graphql_handler,
+    heal::{HealSnapshot, HEAL_STATUS},
+    live_transcription::{stream_transcription, LiveTranscription},
     pipe_manager::{PipeInfo, PipeManager},
+    stats::{RecordingStats, StatsCache},
+    subtitles::{build_cues, render_subtitles, SubtitleFormat},
+    summary::summary_path,
+    thumbnails::{
+        encode_thumbnail, find_thumbnail, spawn_thumbnail_generation, store_thumbnail,
+        thumbnail_path, thumbnails_dir,
+    },
+    version::{get_version, with_version_headers},
     video_utils::{merge_videos, MergeVideosRequest, MergeVideosResponse},
-    ContentType, DatabaseManager, SearchResult,
+    waveform::{cache_waveform, cached_waveform, compute_waveform, MAX_WAVEFORM_SAMPLES},
+    ContentType, CursorPosition, DatabaseManager, SearchCursor, SearchResult,
+};
+use crate::{
+    plugin::ApiPluginLayer,
+    rate_limit::{with_rate_limit, RateLimit},
+    recording_control::RecordingControl,
+    remote_sync::{verify_signature, ImportedRows, SyncBatch, SIGNATURE_HEADER},
+    request_id::{spawn_blocking_in_current_span, with_request_tracing},
+    request_log::log_request_duration,
+    resource_monitor::{send_desktop_notification, DiskUsage, DISK_USAGE, MEMORY_USAGE_BYTES},
+    runtime_config::{RuntimeConfigResponse, RuntimeConfigUpdate},
+    search_rank::{RankWeights, SearchOrder, SearchSort, SharedRankWeights, SortOrder},
+    security_headers::with_security_headers,
+    share::{create_share, get_share},
+    signed_url::{MediaKind, MediaSigner, DEFAULT_SIGNED_URL_TTL},
+    video_utils::{extract_frame, extract_frame_png},
 };
-use crate::{plugin::ApiPluginLayer, video_utils::extract_frame};
-use chrono::{DateTime, Utc};
-use log::{debug, error, info};
+use chrono::{DateTime, NaiveDate, Utc};
+use clap::ValueEnum;
+use log::{debug, error, info, warn};
 use screenpipe_audio::{
-    default_input_device, default_output_device, list_audio_devices, AudioDevice, DeviceControl,
-    DeviceType,
+    default_input_device, default_output_device, list_audio_devices, pcm_decode, read_audio_file,
+    AudioDevice, AudioFormat, AudioTranscriptionEngine, DeviceControl, DeviceType,
+    TranscriptionSegment,
 };
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
@@ -32,24 +83,46 @@ use std::{
     collections::HashMap,
     net::SocketAddr,
     path::PathBuf,
-    sync::{atomic::AtomicBool, Arc},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, RwLock,
+    },
     time::Duration,
 };
 
 use tokio::net::TcpListener;
-use tower_http::trace::TraceLayer;
-use tower_http::{cors::CorsLayer, trace::DefaultMakeSpan};
+use tokio::sync::oneshot;
+use tokio_util::io::ReaderStream;
+use tower_http::cors::CorsLayer;
 
 pub struct AppState {
     pub db: Arc<DatabaseManager>,
     pub vision_control: Arc<AtomicBool>,
     pub audio_devices_control: Arc<SegQueue<(AudioDevice, DeviceControl)>>,
-    pub devices_status: HashMap<AudioDevice, DeviceControl>,
+    /// Every known audio device, updated by `POST /devices/audio/discover`
+    pub devices_status: Arc<RwLock<HashMap<AudioDevice, DeviceControl>>>,
     pub app_start_time: DateTime<Utc>,
     pub screenpipe_dir: PathBuf,
+    /// Where video chunks and thumbnails are written, see `--frames-dir`
+    pub frames_dir: PathBuf,
     pub pipe_manager: Arc<PipeManager>,
     pub vision_disabled: bool,
     pub audio_disabled: bool,
+    pub stats_cache: Arc<StatsCache>,
+    pub export_jobs: Arc<ExportJobs>,
+    /// Settings the recorder reads each capture cycle, changed through `PATCH /config`
+    pub capture_config: SharedCaptureConfig,
+    /// Weights of the search `rank`, changed through `PATCH /config`
+    pub rank_weights: SharedRankWeights,
+    pub recording: Arc<RecordingControl>,
+    /// `--remote-sync-secret`, `POST /import` is refused without it
+    pub remote_sync_secret: Option<String>,
+    /// Signs the `media_url` of search results, see `--signed-url-ttl-secs`
+    pub media_signer: Arc<MediaSigner>,
+    /// `None` when `GET /stream/transcription` is turned off, e.g. on the read-only server
+    pub live_transcription: Option<Arc<LiveTranscription>>,
+    /// `false` with `--ocr-no-normalize`, re-ocr'd text is normalized like recorded text
+    pub normalize_ocr: bool,
     #[cfg(feature = "llm")]
     pub llm_enabled: bool,
     #[cfg(feature = "llm")]
@@ -78,6 +151,31 @@ pub(crate) struct SearchQuery {
     min_length: Option<usize>,
     #[serde(default)]
     max_length: Option<usize>,
+    #[serde(default)]
+    session_id: Option<String>,
+    /// `next_cursor` of the previous page, see [`SearchCursor`]
+    #[serde(default)]
+    cursor: Option<String>,
+    #[serde(default)]
+    align: Option<SearchAlign>,
+    /// Approximate matching of `q` against the ocr text, tolerating a few typos
+    #[serde(default)]
+    fuzzy: bool,
+    /// Sorts ocr and audio results together, by default each comes newest first on its own
+    #[serde(default)]
+    sort_by: Option<SearchSort>,
+    #[serde(default)]
+    order: SortOrder,
+    /// Adds the local path of the video or audio file to ocr and audio results
+    #[serde(default)]
+    include_file_paths: bool,
+}
+
+/// `?align=word` adds the timed words (or whisper segments) to audio results.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum SearchAlign {
+    Word,
 }
 
 #[derive(Deserialize)]
@@ -110,6 +208,9 @@ pub struct PaginationInfo {
     pub limit: u32,
     pub offset: u32,
     pub total: i64,
+    /// Only set by `GET /search`, `None` on the last page
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -125,12 +226,23 @@ pub struct OCRContent {
     pub frame_id: i64,
     pub text: String,
     pub timestamp: DateTime<Utc>,
-    pub file_path: String,
+    /// Only with `?include_file_paths=true`, `media_url` serves the frame without it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file_path: Option<String>,
     pub offset_index: i64,
     pub app_name: String,
     pub window_name: String,
     pub tags: Vec<String>,
     pub frame: Option<String>,
+    /// `GET /media/<token>` serving the frame as png until the url expires
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub media_url: Option<String>,
+    /// How close a `?fuzzy=true` match is, 1.0 being exact
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub match_score: Option<f32>,
+    /// Relevance to `q` and recency together, higher is better
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rank: Option<f64>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -138,11 +250,28 @@ pub struct AudioContent {
     pub chunk_id: i64,
     pub transcription: String,
     pub timestamp: DateTime<Utc>,
-    pub file_path: String,
+    /// Only with `?include_file_paths=true`, `media_url` serves the file without it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file_path: Option<String>,
+    pub format: String,
     pub offset_index: i64,
     pub tags: Vec<String>,
     pub device_name: String,
     pub device_type: DeviceType,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub word_timestamps: Option<Vec<TranscriptionSegment>>,
+    #[serde(default)]
+    pub source: TranscriptSource,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub speaker: Option<String>,
+    /// `GET /media/<token>` serving the audio file until the url expires
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub media_url: Option<String>,
+    /// The transcript was corrected by hand, see `PATCH /audio/chunks/:id/transcript`
+    #[serde(default)]
+    pub corrected: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rank: Option<f64>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -158,19 +287,30 @@ pub struct FTSContent {
     pub tags: Vec<String>,
 }
 
-#[derive(Serialize)]
-pub(crate) struct ListDeviceResponse {
-    name: String,
-    is_default: bool,
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ListDeviceResponse {
+    pub name: String,
+    pub is_default: bool,
+    pub status: DeviceStatus,
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum DeviceStatus {
+    Connected,
+    /// Being recorded but unplugged, recording resumes when it comes back
+    Disconnected,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct MonitorInfo {
-    id: u32,
-    name: String,
-    width: u32,
-    height: u32,
-    is_default: bool,
+    pub id: u32,
+    pub name: String,
+    pub width: u32,
+    pub height: u32,
+    pub is_default: bool,
+    /// A VNC or RDP session, a dummy display driver, ... rather than a physical screen
+    pub is_virtual: bool,
 }
 
 #[derive(Deserialize)]
@@ -207,6 +347,18 @@ pub struct HealthCheckResponse {
     pub audio_status: String,
     pub message: String,
     pub verbose_instructions: Option<String>,
+    /// Recording restarts since it last ran fine, with the current backoff delay
+    #[serde(default)]
+    pub restarts: HealSnapshot,
+    /// Resident memory of screenpipe and its child processes
+    #[serde(default)]
+    pub memory_usage_bytes: u64,
+    /// Disk holding the data dir, `None` until the resource monitor checked it
+    #[serde(default)]
+    pub disk: Option<DiskUsage>,
+    /// Seconds screen recording has been paused by `--idle-pause-secs`, `None` while recording
+    #[serde(default)]
+    pub idle_secs: Option<f64>,
 }
 
 // Update the search function
@@ -218,7 +370,7 @@ pub(crate) async fn search(
     (StatusCode, JsonResponse<serde_json::Value>),
 > {
     info!(
-        "received search request: query='{}', content_type={:?}, limit={}, offset={}, start_time={:?}, end_time={:?}, app_name={:?}, window_name={:?}, min_length={:?}, max_length={:?}",
+        "received search request: query='{}', content_type={:?}, limit={}, offset={}, start_time={:?}, end_time={:?}, app_name={:?}, window_name={:?}, min_length={:?}, max_length={:?}, session_id={:?}",
         query.q.as_deref().unwrap_or(""),
         query.content_type,
         query.pagination.limit,
@@ -228,13 +380,41 @@ pub(crate) async fn search(
         query.app_name,
         query.window_name,
         query.min_length,
-        query.max_length
+        query.max_length,
+        query.session_id
     );
 
     let query_str = query.q.as_deref().unwrap_or("");
 
+    if query.fuzzy {
+        return fuzzy_search(&query, &state).await;
+    }
+
+    let cursor = match query.cursor.as_deref() {
+        Some(token) => Some(SearchCursor::decode(token).ok_or_else(|| {
+            (
+                StatusCode::BAD_REQUEST,
+                JsonResponse(json!({"error": "invalid cursor"})),
+            )
+        })?),
+        None => None,
+    };
+    let order = SearchOrder {
+        sort_by: query.sort_by.unwrap_or_default(),
+        order: query.order,
+        weights: *state.rank_weights.read().unwrap(),
+    };
+    if cursor.is_some() && !order.is_default() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            JsonResponse(json!({"error": "cursor only pages results sorted by timestamp desc"})),
+        ));
+    }
+
     // If app_name or window_name is specified, force content_type to OCR
-    let content_type = if query.app_name.is_some() || query.window_name.is_some() {
+    let content_type = if (query.app_name.is_some() || query.window_name.is_some())
+        && !matches!(query.content_type, ContentType::Screen(_))
+    {
         ContentType::OCR
     } else {
         query.content_type
@@ -252,6 +432,9 @@ pub(crate) async fn search(
             query.window_name.as_deref(),
             query.min_length,
             query.max_length,
+            query.session_id.as_deref(),
+            cursor.as_ref(),
+            &order,
         ),
         state.db.count_search_results(
             query_str,
@@ -262,6 +445,7 @@ pub(crate) async fn search(
             query.window_name.as_deref(),
             query.min_length,
             query.max_length,
+            query.session_id.as_deref(),
         ),
     )
     .await
@@ -275,78 +459,366 @@ pub(crate) async fn search(
 
     let mut content_items: Vec<ContentItem> = results
         .iter()
-        .map(|result| match result {
-            SearchResult::OCR(ocr) => ContentItem::OCR(OCRContent {
+        .map(|result| content_item(result, &state, query.align, query.include_file_paths))
+        .collect();
+    if query.sort_by.is_some() {
+        sort_content_items(&mut content_items, &order);
+    }
+
+    if query.include_frames {
+        attach_frames(&mut content_items, &state.db).await;
+    }
+
+    let next_cursor = next_search_cursor(
+        &results,
+        cursor.unwrap_or_default(),
+        content_type,
+        query.pagination.limit,
+    );
+
+    info!("search completed: found {} results", total);
+    Ok(JsonResponse(PaginatedResponse {
+        data: content_items,
+        pagination: PaginationInfo {
+            limit: query.pagination.limit,
+            offset: query.pagination.offset,
+            total: total as i64,
+            next_cursor: (!next_cursor.is_done() && order.is_default())
+                .then(|| next_cursor.encode()),
+        },
+    }))
+}
+
+fn content_item(
+    result: &SearchResult,
+    state: &AppState,
+    align: Option<SearchAlign>,
+    include_file_paths: bool,
+) -> ContentItem {
+    match result {
+        SearchResult::OCR(ocr) => ContentItem::OCR(OCRContent {
+            frame_id: ocr.frame_id,
+            text: ocr.ocr_text.clone(),
+            timestamp: ocr.timestamp,
+            file_path: include_file_paths.then(|| ocr.file_path.clone()),
+            offset_index: ocr.offset_index,
+            app_name: ocr.app_name.clone(),
+            window_name: ocr.window_name.clone(),
+            tags: ocr.tags.clone(),
+            frame: None,
+            media_url: Some(state.media_signer.url(MediaKind::Frame, ocr.frame_id)),
+            match_score: None,
+            rank: Some(ocr.rank),
+        }),
+        SearchResult::Audio(audio) => ContentItem::Audio(AudioContent {
+            chunk_id: audio.audio_chunk_id,
+            transcription: audio.transcription.clone(),
+            timestamp: audio.timestamp,
+            file_path: include_file_paths.then(|| audio.file_path.clone()),
+            format: audio.format.clone(),
+            offset_index: audio.offset_index,
+            tags: audio.tags.clone(),
+            device_name: audio.device_name.clone(),
+            device_type: audio.device_type.clone(),
+            word_timestamps: (align == Some(SearchAlign::Word))
+                .then(|| audio.word_timestamps.clone()),
+            source: audio.source,
+            speaker: audio.speaker.clone(),
+            media_url: Some(
+                state
+                    .media_signer
+                    .url(MediaKind::Audio, audio.audio_chunk_id),
+            ),
+            corrected: audio.corrected,
+            rank: Some(audio.rank),
+        }),
+        SearchResult::FTS(fts) => ContentItem::FTS(FTSContent {
+            text_id: fts.text_id,
+            matched_text: fts.matched_text.clone(),
+            frame_id: fts.frame_id,
+            timestamp: fts.frame_timestamp,
+            app_name: fts.app_name.clone(),
+            window_name: fts.window_name.clone(),
+            file_path: fts.video_file_path.clone(),
+            original_frame_text: fts.original_frame_text.clone(),
+            tags: fts.tags.clone(),
+        }),
+    }
+}
+
+#[derive(Deserialize)]
+pub(crate) struct StreamSearchQuery {
+    q: Option<String>,
+    #[serde(default)]
+    content_type: ContentType,
+    #[serde(default)]
+    start_time: Option<DateTime<Utc>>,
+    #[serde(default)]
+    end_time: Option<DateTime<Utc>>,
+    #[serde(default)]
+    app_name: Option<String>,
+    #[serde(default)]
+    window_name: Option<String>,
+    #[serde(default)]
+    min_length: Option<usize>,
+    #[serde(default)]
+    max_length: Option<usize>,
+    #[serde(default)]
+    session_id: Option<String>,
+    #[serde(default)]
+    align: Option<SearchAlign>,
+    #[serde(default)]
+    sort_by: Option<SearchSort>,
+    #[serde(default)]
+    order: SortOrder,
+    /// Adds the local path of the video or audio file to ocr and audio results
+    #[serde(default)]
+    include_file_paths: bool,
+}
+
+/// `GET /search/stream`: every result of a search as newline delimited json, one `ContentItem`
+/// a line, read from the database while the response is sent so exports of millions of rows
+/// don't sit in memory. Ocr results come first, then audio ones, each sorted on its own.
+pub(crate) async fn stream_search(
+    Query(query): Query<StreamSearchQuery>,
+    State(state): State<Arc<AppState>>,
+) -> Response {
+    info!(
+        "received search stream request: query='{}', content_type={:?}, start_time={:?}, end_time={:?}, app_name={:?}, window_name={:?}",
+        query.q.as_deref().unwrap_or(""),
+        query.content_type,
+        query.start_time,
+        query.end_time,
+        query.app_name,
+        query.window_name
+    );
+    let order = SearchOrder {
+        sort_by: query.sort_by.unwrap_or_default(),
+        order: query.order,
+        weights: *state.rank_weights.read().unwrap(),
+    };
+    // as in `search`, app and window filters only apply to ocr results
+    let content_type = if (query.app_name.is_some() || query.window_name.is_some())
+        && !matches!(query.content_type, ContentType::Screen(_))
+    {
+        ContentType::OCR
+    } else {
+        query.content_type
+    };
+
+    let results = state.db.search_stream(
+        query.q.as_deref().unwrap_or(""),
+        content_type,
+        query.start_time,
+        query.end_time,
+        query.app_name.as_deref(),
+        query.window_name.as_deref(),
+        query.min_length,
+        query.max_length,
+        query.session_id.as_deref(),
+        &order,
+    );
+    let align = query.align;
+    let include_file_paths = query.include_file_paths;
+    let lines = results.map(move |result| -> std::io::Result<Vec<u8>> {
+        // the status is sent already, a failure can only cut the body short
+        let result = result.map_err(|e| {
+            error!("search stream failed: {}", e);
+            std::io::Error::new(std::io::ErrorKind::Other, e)
+        })?;
+        let mut line =
+            serde_json::to_vec(&content_item(&result, &state, align, include_file_paths))?;
+        line.push(b'\n');
+        Ok(line)
+    });
+    (
+        [(header::CONTENT_TYPE, "application/x-ndjson")],
+        Body::from_stream(lines),
+    )
+        .into_response()
+}
+
+/// Merges the ocr and audio results of a page in `order`, as the database sorted each of them.
+fn sort_content_items(items: &mut [ContentItem], order: &SearchOrder) {
+    let key = |item: &ContentItem| match item {
+        ContentItem::OCR(ocr) => (ocr.timestamp, ocr.rank, ocr.app_name.to_lowercase()),
+        ContentItem::Audio(audio) => (audio.timestamp, audio.rank, String::new()),
+        ContentItem::FTS(fts) => (fts.timestamp, None, fts.app_name.to_lowercase()),
+    };
+    items.sort_by(|a, b| {
+        let (a_timestamp, a_rank, a_app) = key(a);
+        let (b_timestamp, b_rank, b_app) = key(b);
+        let ordering = match order.sort_by {
+            SearchSort::Timestamp => a_timestamp.cmp(&b_timestamp),
+            SearchSort::Rank => a_rank.unwrap_or(0.0).total_cmp(&b_rank.unwrap_or(0.0)),
+            SearchSort::AppName => a_app.cmp(&b_app),
+        };
+        let ordering = match order.order {
+            SortOrder::Asc => ordering,
+            SortOrder::Desc => ordering.reverse(),
+        };
+        ordering.then(b_timestamp.cmp(&a_timestamp))
+    });
+}
+
+/// `GET /search?fuzzy=true`: ocr text within a couple of edits of `q`, best match first.
+/// Paged with limit / offset only, there is no `next_cursor`.
+async fn fuzzy_search(
+    query: &SearchQuery,
+    state: &AppState,
+) -> Result<
+    JsonResponse<PaginatedResponse<ContentItem>>,
+    (StatusCode, JsonResponse<serde_json::Value>),
+> {
+    let query_str = query.q.as_deref().unwrap_or("").trim();
+    if query_str.chars().count() < MIN_FUZZY_QUERY_LEN {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            JsonResponse(json!({
+                "error": format!("fuzzy search needs a query of at least {} characters", MIN_FUZZY_QUERY_LEN)
+            })),
+        ));
+    }
+    if !matches!(query.content_type, ContentType::All | ContentType::OCR) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            JsonResponse(json!({"error": "fuzzy search only covers ocr text"})),
+        ));
+    }
+
+    let (results, total) = state
+        .db
+        .fuzzy_search_ocr(
+            query_str,
+            query.pagination.limit,
+            query.pagination.offset,
+            query.start_time,
+            query.end_time,
+            query.app_name.as_deref(),
+            query.window_name.as_deref(),
+            query.session_id.as_deref(),
+        )
+        .await
+        .map_err(|e| {
+            error!("failed to perform fuzzy search: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                JsonResponse(json!({"error": format!("failed to perform fuzzy search: {}", e)})),
+            )
+        })?;
+
+    let mut content_items: Vec<ContentItem> = results
+        .into_iter()
+        .map(|(ocr, score)| {
+            ContentItem::OCR(OCRContent {
                 frame_id: ocr.frame_id,
-                text: ocr.ocr_text.clone(),
+                text: ocr.ocr_text,
                 timestamp: ocr.timestamp,
-                file_path: ocr.file_path.clone(),
+                file_path: query.include_file_paths.then_some(ocr.file_path),
                 offset_index: ocr.offset_index,
-                app_name: ocr.app_name.clone(),
-                window_name: ocr.window_name.clone(),
-                tags: ocr.tags.clone(),
+                app_name: ocr.app_name,
+                window_name: ocr.window_name,
+                tags: ocr.tags,
                 frame: None,
-            }),
-            SearchResult::Audio(audio) => ContentItem::Audio(AudioContent {
-                chunk_id: audio.audio_chunk_id,
-                transcription: audio.transcription.clone(),
-                timestamp: audio.timestamp,
-                file_path: audio.file_path.clone(),
-                offset_index: audio.offset_index,
-                tags: audio.tags.clone(),
-                device_name: audio.device_name.clone(),
-                device_type: audio.device_type.clone(),
-            }),
-            SearchResult::FTS(fts) => ContentItem::FTS(FTSContent {
-                text_id: fts.text_id,
-                matched_text: fts.matched_text.clone(),
-                frame_id: fts.frame_id,
-                timestamp: fts.frame_timestamp,
-                app_name: fts.app_name.clone(),
-                window_name: fts.window_name.clone(),
-                file_path: fts.video_file_path.clone(),
-                original_frame_text: fts.original_frame_text.clone(),
-                tags: fts.tags.clone(),
-            }),
+                media_url: Some(state.media_signer.url(MediaKind::Frame, ocr.frame_id)),
+                match_score: Some(score),
+                rank: None,
+            })
         })
         .collect();
 
     if query.include_frames {
-        debug!("extracting frames for ocr content");
-        let frame_futures: Vec<_> = content_items
-            .iter()
-            .filter_map(|item| {
-                if let ContentItem::OCR(ocr_content) = item {
-                    Some(extract_frame(
-                        &ocr_content.file_path,
-                        ocr_content.offset_index,
-                    ))
-                } else {
-                    None
-                }
-            })
-            .collect();
-
-        let frames = try_join_all(frame_futures).await.unwrap(); // TODO: handle error
-
-        for (item, frame) in content_items.iter_mut().zip(frames.into_iter()) {
-            if let ContentItem::OCR(ref mut ocr_content) = item {
-                ocr_content.frame = Some(frame);
-            }
-        }
+        attach_frames(&mut content_items, &state.db).await;
     }
 
-    info!("search completed: found {} results", total);
+    info!("fuzzy search completed: found {} results", total);
     Ok(JsonResponse(PaginatedResponse {
         data: content_items,
         pagination: PaginationInfo {
             limit: query.pagination.limit,
             offset: query.pagination.offset,
             total: total as i64,
+            next_cursor: None,
         },
     }))
 }
 
+async fn attach_frames(content_items: &mut [ContentItem], db: &DatabaseManager) {
+    debug!("extracting frames for ocr content");
+    // the file path is usually left out of the results, look it up again
+    let frame_futures: Vec<_> = content_items
+        .iter()
+        .filter_map(|item| {
+            if let ContentItem::OCR(ocr_content) = item {
+                Some(async move {
+                    let (file_path, offset_index) =
+                        db.get_frame(ocr_content.frame_id).await?.ok_or_else(|| {
+                            anyhow::anyhow!("frame {} not found", ocr_content.frame_id)
+                        })?;
+                    extract_frame(&file_path, offset_index).await
+                })
+            } else {
+                None
+            }
+        })
+        .collect();
+
+    // a frame deleted since the search or one that can't be extracted is left out
+    let frames = join_all(frame_futures).await;
+
+    let ocr_items = content_items.iter_mut().filter_map(|item| match item {
+        ContentItem::OCR(ocr_content) => Some(ocr_content),
+        _ => None,
+    });
+    for (ocr_content, frame) in ocr_items.zip(frames) {
+        match frame {
+            Ok(frame) => ocr_content.frame = Some(frame),
+            Err(e) => warn!("failed to extract frame {}: {}", ocr_content.frame_id, e),
+        }
+    }
+}
+
+fn next_search_cursor(
+    results: &[SearchResult],
+    previous: SearchCursor,
+    content_type: ContentType,
+    limit: u32,
+) -> SearchCursor {
+    let (mut ocr, mut audio) = (Vec::new(), Vec::new());
+    for result in results {
+        match result {
+            SearchResult::OCR(ocr_result) => ocr.push((ocr_result.timestamp, ocr_result.frame_id)),
+            SearchResult::Audio(audio_result) => {
+                audio.push((audio_result.timestamp, audio_result.transcription_id))
+            }
+            SearchResult::FTS(_) => {}
+        }
+    }
+
+    // a type that was not searched this time is finished
+    let ocr_position = if previous.ocr != CursorPosition::Done
+        && matches!(
+            content_type,
+            ContentType::All | ContentType::OCR | ContentType::Screen(_)
+        ) {
+        CursorPosition::next(ocr.len(), limit, ocr.last().copied())
+    } else {
+        CursorPosition::Done
+    };
+    let audio_position = if previous.audio != CursorPosition::Done
+        && matches!(content_type, ContentType::All | ContentType::Audio)
+    {
+        CursorPosition::next(audio.len(), limit, audio.last().copied())
+    } else {
+        CursorPosition::Done
+    };
+
+    SearchCursor {
+        ocr: ocr_position,
+        audio: audio_position,
+    }
+}
+
 pub(crate) async fn api_list_audio_devices(
     State(_state): State<Arc<AppState>>,
 ) -> Result<JsonResponse<Vec<ListDeviceResponse>>, (StatusCode, JsonResponse<serde_json::Value>)> {
@@ -371,16 +843,34 @@ pub(crate) async fn api_list_audio_devices(
         )
     })?;
 
-    let response: Vec<ListDeviceResponse> = devices
+    let disconnected = audio_status::disconnected_devices();
+    let mut response: Vec<ListDeviceResponse> = devices
         .into_iter()
         .map(|device| {
             let is_default = device == default_input_device || device == default_output_device;
+            let name = device.to_string();
+            let status = if disconnected.contains(&name) {
+                DeviceStatus::Disconnected
+            } else {
+                DeviceStatus::Connected
+            };
             ListDeviceResponse {
-                name: device.to_string(),
+                name,
                 is_default,
+                status,
             }
         })
         .collect();
+    // unplugged devices are gone from the list, still show the ones being waited on
+    for name in disconnected {
+        if !response.iter().any(|device| device.name == name) {
+            response.push(ListDeviceResponse {
+                name,
+                is_default: false,
+                status: DeviceStatus::Disconnected,
+            });
+        }
+    }
 
     if response.is_empty() {
         Err((
@@ -392,6 +882,54 @@ pub(crate) async fn api_list_audio_devices(
     }
 }
 
+/// Enumerates the audio devices again, e.g. after a usb interface was plugged in, and returns
+/// every known one: new devices are added stopped, vanished ones are marked disconnected.
+pub(crate) async fn discover_audio_devices(
+    State(state): State<Arc<AppState>>,
+) -> Result<JsonResponse<Vec<ListDeviceResponse>>, (StatusCode, JsonResponse<serde_json::Value>)> {
+    let list_error = |e: String| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            JsonResponse(json!({"error": format!("Failed to list audio devices: {}", e)})),
+        )
+    };
+    // cpal enumeration blocks, up to seconds with some drivers
+    let discovered =
+        spawn_blocking_in_current_span(|| futures::executor::block_on(list_audio_devices()))
+            .await
+            .map_err(|e| list_error(e.to_string()))?
+            .map_err(|e| list_error(e.to_string()))?;
+    let defaults = [default_input_device().ok(), default_output_device().ok()];
+
+    let mut devices_status = state.devices_status.write().unwrap();
+    let (added, removed) = audio_status::merge_discovered_devices(&mut devices_status, discovered);
+    if !added.is_empty() || !removed.is_empty() {
+        info!(
+            "audio devices discovered: added {:?}, disconnected {:?}",
+            added, removed
+        );
+    }
+    let disconnected = audio_status::disconnected_devices();
+    let mut response: Vec<ListDeviceResponse> = devices_status
+        .keys()
+        .map(|device| {
+            let name = device.to_string();
+            let status = if disconnected.contains(&name) {
+                DeviceStatus::Disconnected
+            } else {
+                DeviceStatus::Connected
+            };
+            ListDeviceResponse {
+                is_default: defaults.iter().flatten().any(|d| d == device),
+                name,
+                status,
+            }
+        })
+        .collect();
+    response.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(JsonResponse(response))
+}
+
 pub async fn api_list_monitors(
 ) -> Result<JsonResponse<Vec<MonitorInfo>>, (StatusCode, JsonResponse<serde_json::Value>)> {
     let monitors = list_monitors().await;
@@ -403,6 +941,7 @@ pub async fn api_list_monitors(
             width: monitor.width(),
             height: monitor.height(),
             is_default: monitor.is_primary(),
+            is_virtual: is_virtual_monitor(&monitor),
         })
         .collect();
 
@@ -487,8 +1026,12 @@ pub async fn health_check(State(state): State<Arc<AppState>>) -> JsonResponse<He
     let threshold = Duration::from_secs(60);
     let app_start_threshold = Duration::from_secs(120); // 2 minutes - ideally should be audio duration chunk
 
+    let idle_for = IDLE_STATUS.idle_for();
     let frame_status = if state.vision_disabled {
         "disabled"
+    } else if idle_for.is_some() {
+        // no new frames is expected while paused
+        "idle"
     } else {
         match last_frame {
             Some(timestamp)
@@ -519,18 +1062,29 @@ pub async fn health_check(State(state): State<Arc<AppState>>) -> JsonResponse<He
         }
     };
 
-    let (overall_status, message, verbose_instructions) = if (frame_status == "ok"
-        || frame_status == "disabled")
+    let disk = DISK_USAGE.snapshot();
+    let frame_ok = matches!(frame_status, "ok" | "disabled" | "idle");
+    let (overall_status, message, verbose_instructions) = if frame_ok
         && (audio_status == "ok" || audio_status == "disabled")
     {
-        (
-            "healthy",
-            "all systems are functioning normally.".to_string(),
-            None,
-        )
+        match disk.filter(DiskUsage::is_degraded) {
+            Some(disk) => (
+                "degraded",
+                format!(
+                    "the disk holding the screenpipe data is {:.0}% full.",
+                    disk.usage_pct
+                ),
+                Some("free up disk space or delete old recordings with DELETE /frames and DELETE /audio, recording stops once the disk is full.".to_string()),
+            ),
+            None => (
+                "healthy",
+                "all systems are functioning normally.".to_string(),
+                None,
+            ),
+        }
     } else {
         let mut unhealthy_systems = Vec::new();
-        if frame_status != "ok" && frame_status != "disabled" {
+        if !frame_ok {
             unhealthy_systems.push("vision");
         }
         if audio_status != "ok" && audio_status != "disabled" {
@@ -557,63 +1111,1441 @@ pub async fn health_check(State(state): State<Arc<AppState>>) -> JsonResponse<He
         audio_status: audio_status.to_string(),
         message,
         verbose_instructions,
+        restarts: HEAL_STATUS.snapshot(),
+        memory_usage_bytes: MEMORY_USAGE_BYTES.load(Ordering::Relaxed),
+        disk,
+        idle_secs: idle_for.map(|idle_for| idle_for.as_secs_f64()),
     })
 }
 
-// Request and response structs
-#[derive(Deserialize)]
-struct DownloadPipeRequest {
-    url: String,
-}
-
-#[derive(Deserialize)]
-struct RunPipeRequest {
-    pipe_id: String,
-}
-
-#[derive(Deserialize)]
-struct UpdatePipeConfigRequest {
-    pipe_id: String,
-    config: serde_json::Value,
+/// 200 when every enabled subsystem works, 503 otherwise, with the `DeepHealthResponse` in both.
+pub(crate) async fn deep_health_check(
+    State(state): State<Arc<AppState>>,
+) -> (StatusCode, JsonResponse<DeepHealthResponse>) {
+    let ocr_engine = state.capture_config.read().unwrap().ocr_engine;
+    let health = check_deep_health(
+        Arc::clone(&state.db),
+        ocr_engine,
+        state.vision_disabled,
+        state.audio_disabled,
+    )
+    .await;
+    let status = if health.healthy {
+        StatusCode::OK
+    } else {
+        error!("deep health check failed: {:?}", health);
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    (status, JsonResponse(health))
 }
 
-// Handler functions
-async fn download_pipe_handler(
+pub(crate) async fn get_stats(
     State(state): State<Arc<AppState>>,
-    JsonResponse(payload): JsonResponse<DownloadPipeRequest>,
-) -> Result<JsonResponse<serde_json::Value>, (StatusCode, JsonResponse<Value>)> {
-    debug!("Downloading pipe: {}", payload.url);
-    match state.pipe_manager.download_pipe(&payload.url).await {
-        Ok(pipe_dir) => Ok(JsonResponse(json!({
-            "message": format!("Pipe {} downloaded successfully", pipe_dir),
-            "pipe_id": pipe_dir
-        }))),
+) -> Result<JsonResponse<RecordingStats>, (StatusCode, JsonResponse<Value>)> {
+    match state.stats_cache.get().await {
+        Ok(stats) => Ok(JsonResponse(stats)),
         Err(e) => {
-            error!("Failed to download pipe: {}", e);
+            error!("failed to get stats: {}", e);
             Err((
                 StatusCode::INTERNAL_SERVER_ERROR,
-                JsonResponse(json!({"error": e.to_string()})),
+                JsonResponse(json!({"error": format!("failed to get stats: {}", e)})),
             ))
         }
     }
 }
 
-async fn run_pipe_handler(
-    State(state): State<Arc<AppState>>,
-    JsonResponse(payload): JsonResponse<RunPipeRequest>,
-) -> Result<JsonResponse<Value>, (StatusCode, JsonResponse<Value>)> {
-    debug!("Starting pipe: {}", payload.pipe_id);
+#[derive(Deserialize)]
+pub(crate) struct FramesQuery {
+    #[serde(default)]
+    from: Option<DateTime<Utc>>,
+    #[serde(default)]
+    to: Option<DateTime<Utc>>,
+    #[serde(default)]
+    thumb: bool,
+    #[serde(default = "default_limit")]
+    limit: u32,
+    #[serde(default)]
+    offset: u32,
+}
 
-    match state
-        .pipe_manager
-        .update_config(
-            &payload.pipe_id,
-            serde_json::json!({
-                "enabled": true,
-            }),
-        )
+#[derive(Serialize, Deserialize, Debug)]
+pub struct FrameItem {
+    pub frame_id: i64,
+    pub timestamp: DateTime<Utc>,
+    pub offset_index: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file_path: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub thumbnail_url: Option<String>,
+    /// Pixel of the frame the mouse pointed at, `None` when it was on another monitor
+    #[serde(default)]
+    pub cursor_x: Option<i64>,
+    #[serde(default)]
+    pub cursor_y: Option<i64>,
+}
+
+pub(crate) async fn list_frames(
+    Query(query): Query<FramesQuery>,
+    State(state): State<Arc<AppState>>,
+) -> Result<JsonResponse<Vec<FrameItem>>, (StatusCode, JsonResponse<Value>)> {
+    let frames = state
+        .db
+        .get_frames(query.from, query.to, query.limit, query.offset)
         .await
-    {
+        .map_err(|e| {
+            error!("failed to list frames: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                JsonResponse(json!({"error": format!("failed to list frames: {}", e)})),
+            )
+        })?;
+
+    let items = frames
+        .into_iter()
+        .map(|frame| FrameItem {
+            frame_id: frame.frame_id,
+            timestamp: frame.timestamp,
+            offset_index: frame.offset_index,
+            thumbnail_url: query
+                .thumb
+                .then(|| format!("/frames/{}/thumbnail", frame.frame_id)),
+            file_path: (!query.thumb).then_some(frame.file_path),
+            cursor_x: frame.cursor_x,
+            cursor_y: frame.cursor_y,
+        })
+        .collect();
+
+    Ok(JsonResponse(items))
+}
+
+/// Most frames one `GET /frames/random` samples
+const MAX_RANDOM_FRAMES: u32 = 100;
+
+#[derive(Deserialize)]
+pub(crate) struct RandomFramesQuery {
+    #[serde(default = "default_random_frames")]
+    n: u32,
+    #[serde(default)]
+    from: Option<DateTime<Utc>>,
+    #[serde(default)]
+    to: Option<DateTime<Utc>>,
+    /// Samples the same frames on every call, for tests
+    #[serde(default)]
+    seed: Option<u64>,
+}
+
+fn default_random_frames() -> u32 {
+    5
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct RandomFrameItem {
+    pub frame_id: i64,
+    pub timestamp: DateTime<Utc>,
+    /// Ocr text of each window of the frame, one per line
+    pub ocr_text: String,
+    pub thumbnail_url: String,
+}
+
+/// Frames sampled at random, to check what is being captured.
+pub(crate) async fn random_frames(
+    Query(query): Query<RandomFramesQuery>,
+    State(state): State<Arc<AppState>>,
+) -> Result<JsonResponse<Vec<RandomFrameItem>>, (StatusCode, JsonResponse<Value>)> {
+    if !(1..=MAX_RANDOM_FRAMES).contains(&query.n) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            JsonResponse(
+                json!({"error": format!("n must be between 1 and {}", MAX_RANDOM_FRAMES)}),
+            ),
+        ));
+    }
+    let frames = state
+        .db
+        .get_random_frames(query.from, query.to, query.n, query.seed)
+        .await
+        .map_err(|e| {
+            error!("failed to sample frames: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                JsonResponse(json!({"error": format!("failed to sample frames: {}", e)})),
+            )
+        })?;
+
+    Ok(JsonResponse(
+        frames
+            .into_iter()
+            .map(|frame| RandomFrameItem {
+                frame_id: frame.frame_id,
+                timestamp: frame.timestamp,
+                ocr_text: frame.text,
+                thumbnail_url: format!("/frames/{}/thumbnail", frame.frame_id),
+            })
+            .collect(),
+    ))
+}
+
+/// Characters of ocr text kept per frame by `GET /frames/search?inline_text=true`.
+const INLINE_TEXT_LIMIT: usize = 500;
+
+#[derive(Deserialize)]
+pub(crate) struct FrameSearchQuery {
+    #[serde(default)]
+    q: String,
+    #[serde(flatten)]
+    pagination: PaginationQuery,
+    #[serde(default)]
+    start_time: Option<DateTime<Utc>>,
+    #[serde(default)]
+    end_time: Option<DateTime<Utc>>,
+    #[serde(default)]
+    app_name: Option<String>,
+    #[serde(default)]
+    window_name: Option<String>,
+    #[serde(default)]
+    inline_text: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct FrameSearchItem {
+    pub frame_id: i64,
+    pub timestamp: DateTime<Utc>,
+    pub file_path: String,
+    pub offset_index: i64,
+    pub app_name: String,
+    pub window_name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ocr_text: Option<String>,
+    /// Set when `ocr_text` was cut to its first `INLINE_TEXT_LIMIT` characters
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub truncated: Option<bool>,
+}
+
+/// Ocr search returning frames, with their text inline when `inline_text` is set.
+pub(crate) async fn search_frames(
+    Query(query): Query<FrameSearchQuery>,
+    State(state): State<Arc<AppState>>,
+) -> Result<JsonResponse<Vec<FrameSearchItem>>, (StatusCode, JsonResponse<Value>)> {
+    let results = state
+        .db
+        .search(
+            &query.q,
+            ContentType::OCR,
+            query.pagination.limit,
+            query.pagination.offset,
+            query.start_time,
+            query.end_time,
+            query.app_name.as_deref(),
+            query.window_name.as_deref(),
+            None,
+            None,
+            None,
+            None,
+            &SearchOrder::default(),
+        )
+        .await
+        .map_err(|e| {
+            error!("failed to search frames: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                JsonResponse(json!({"error": format!("failed to search frames: {}", e)})),
+            )
+        })?;
+
+    let items = results
+        .into_iter()
+        .filter_map(|result| match result {
+            SearchResult::OCR(ocr) => Some(ocr),
+            _ => None,
+        })
+        .map(|ocr| {
+            let (ocr_text, truncated) = if query.inline_text {
+                let truncated = ocr.ocr_text.chars().count() > INLINE_TEXT_LIMIT;
+                let text = if truncated {
+                    ocr.ocr_text.chars().take(INLINE_TEXT_LIMIT).collect()
+                } else {
+                    ocr.ocr_text
+                };
+                (Some(text), Some(truncated))
+            } else {
+                (None, None)
+            };
+            FrameSearchItem {
+                frame_id: ocr.frame_id,
+                timestamp: ocr.timestamp,
+                file_path: ocr.file_path,
+                offset_index: ocr.offset_index,
+                app_name: ocr.app_name,
+                window_name: ocr.window_name,
+                ocr_text,
+                truncated,
+            }
+        })
+        .collect();
+
+    Ok(JsonResponse(items))
+}
+
+/// What changed on screen in a frame, see [`FrameDiff`].
+pub(crate) async fn get_frame_diff(
+    Path(frame_id): Path<i64>,
+    State(state): State<Arc<AppState>>,
+) -> Result<JsonResponse<FrameDiff>, (StatusCode, JsonResponse<Value>)> {
+    match state.db.get_frame_diff(frame_id).await {
+        Ok(Some(diff)) => Ok(JsonResponse(diff)),
+        Ok(None) => Err((
+            StatusCode::NOT_FOUND,
+            JsonResponse(json!({"error": format!("no text diff for frame {}", frame_id)})),
+        )),
+        Err(e) => {
+            error!("failed to get diff of frame {}: {}", frame_id, e);
+            Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                JsonResponse(json!({"error": format!("failed to get frame diff: {}", e)})),
+            ))
+        }
+    }
+}
+
+pub(crate) async fn get_frame_thumbnail(
+    Path(frame_id): Path<i64>,
+    State(state): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, (StatusCode, JsonResponse<Value>)> {
+    let internal_error = |e: String| {
+        error!("failed to get thumbnail for frame {}: {}", frame_id, e);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            JsonResponse(json!({"error": format!("failed to get thumbnail: {}", e)})),
+        )
+    };
+
+    let thumbnails_dir = thumbnails_dir(&state.frames_dir);
+    let path = find_thumbnail(&state.db, &thumbnails_dir, frame_id)
+        .await
+        .map_err(|e| internal_error(e.to_string()))?;
+    let jpeg = match path.map(tokio::fs::read) {
+        Some(read) => read.await.map_err(|e| internal_error(e.to_string()))?,
+        None => {
+            // not generated yet, e.g. frames recorded before thumbnails existed
+            let (file_path, offset_index) = state
+                .db
+                .get_frame(frame_id)
+                .await
+                .map_err(|e| internal_error(e.to_string()))?
+                .ok_or_else(|| {
+                    (
+                        StatusCode::NOT_FOUND,
+                        JsonResponse(json!({"error": "frame not found"})),
+                    )
+                })?;
+            let png = extract_frame_png(&file_path, offset_index)
+                .await
+                .map_err(|e| internal_error(e.to_string()))?;
+            let jpeg = spawn_blocking_in_current_span(move || {
+                encode_thumbnail(&image::load_from_memory(&png)?)
+            })
+            .await
+            .map_err(|e| internal_error(e.to_string()))?
+            .map_err(|e| internal_error(e.to_string()))?;
+            // the read-only server serves it without caching, the main one stores it
+            if !state.db.is_read_only() {
+                store_thumbnail(&state.db, &thumbnails_dir, &[frame_id], &jpeg)
+                    .await
+                    .map_err(|e| internal_error(e.to_string()))?;
+            }
+            jpeg
+        }
+    };
+
+    Ok(([(header::CONTENT_TYPE, "image/jpeg")], jpeg))
+}
+
+#[derive(Deserialize)]
+pub(crate) struct ReprocessOcrQuery {
+    engine: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ReprocessOcrResponse {
+    pub frame_id: i64,
+    pub text: String,
+    pub text_json: String,
+    pub ocr_engine: String,
+    pub confidence: Option<f64>,
+}
+
+/// Runs ocr again on a recorded frame, e.g. after a failed cloud ocr or with a better engine.
+pub(crate) async fn reprocess_frame_ocr(
+    Path(frame_id): Path<i64>,
+    Query(query): Query<ReprocessOcrQuery>,
+    State(state): State<Arc<AppState>>,
+) -> Result<JsonResponse<ReprocessOcrResponse>, (StatusCode, JsonResponse<Value>)> {
+    let internal_error = |e: String| {
+        error!("failed to reprocess ocr of frame {}: {}", frame_id, e);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            JsonResponse(json!({"error": format!("failed to reprocess ocr: {}", e)})),
+        )
+    };
+
+    let ocr_engine = CliOcrEngine::from_str(&query.engine, true)
+        .map(OcrEngine::from)
+        .map_err(|_| {
+            (
+                StatusCode::BAD_REQUEST,
+                JsonResponse(json!({"error": format!("unsupported engine '{}'", query.engine)})),
+            )
+        })?;
+    let (file_path, offset_index) = state
+        .db
+        .get_frame(frame_id)
+        .await
+        .map_err(|e| internal_error(e.to_string()))?
+        .ok_or_else(|| {
+            (
+                StatusCode::NOT_FOUND,
+                JsonResponse(json!({"error": "frame not found"})),
+            )
+        })?;
+    if !tokio::fs::try_exists(&file_path).await.unwrap_or(false) {
+        return Err((
+            StatusCode::NOT_FOUND,
+            JsonResponse(json!({
+                "error": format!("the video file of frame {} was deleted", frame_id)
+            })),
+        ));
+    }
+
+    let png = extract_frame_png(&file_path, offset_index)
+        .await
+        .map_err(|e| internal_error(e.to_string()))?;
+    let mut image = image::load_from_memory(&png).map_err(|e| internal_error(e.to_string()))?;
+    // each window is its own frame, only read the part of the screen it covered
+    let window = state
+        .db
+        .get_frame_window(frame_id)
+        .await
+        .map_err(|e| internal_error(e.to_string()))?;
+    if let Some(bounds) = window.and_then(|window| window.bounds) {
+        let x = bounds.x.min(image.width());
+        let y = bounds.y.min(image.height());
+        let width = bounds.width.min(image.width() - x);
+        let height = bounds.height.min(image.height() - y);
+        if width > 0 && height > 0 {
+            image = image.crop_imm(x, y, width, height);
+        }
+    }
+    let (text, text_json, confidence) = ocr_engine
+        .perform_ocr(&image)
+        .await
+        .map_err(|e| internal_error(e.to_string()))?;
+    let text = if state.normalize_ocr {
+        normalize_ocr_text(&text)
+    } else {
+        text
+    };
+    state
+        .db
+        .replace_ocr_text(frame_id, &text, &text_json, &ocr_engine, confidence)
+        .await
+        .map_err(|e| internal_error(e.to_string()))?;
+    info!(
+        "reprocessed ocr of frame {} with {:?}",
+        frame_id, ocr_engine
+    );
+
+    Ok(JsonResponse(ReprocessOcrResponse {
+        frame_id,
+        text,
+        text_json,
+        ocr_engine: format!("{:?}", ocr_engine),
+        confidence,
+    }))
+}
+
+/// Rows of another instance running with `--remote-sync-url`.
+pub(crate) async fn import_remote_rows(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<JsonResponse<ImportedRows>, (StatusCode, JsonResponse<Value>)> {
+    let Some(secret) = &state.remote_sync_secret else {
+        return Err((
+            StatusCode::FORBIDDEN,
+            JsonResponse(
+                json!({"error": "start screenpipe with --remote-sync-secret to accept imports"}),
+            ),
+        ));
+    };
+    let signature = headers
+        .get(SIGNATURE_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default();
+    if !verify_signature(secret, &body, signature) {
+        return Err((
+            StatusCode::UNAUTHORIZED,
+            JsonResponse(json!({"error": "invalid signature"})),
+        ));
+    }
+    let batch: SyncBatch = serde_json::from_slice(&body).map_err(|e| {
+        (
+            StatusCode::BAD_REQUEST,
+            JsonResponse(json!({"error": format!("invalid batch: {}", e)})),
+        )
+    })?;
+
+    let imported = state.db.import_sync_batch(&batch).await.map_err(|e| {
+        error!("failed to import rows of {}: {}", batch.source, e);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            JsonResponse(json!({"error": format!("failed to import rows: {}", e)})),
+        )
+    })?;
+    debug!(
+        "imported {} frames and {} transcriptions of {}",
+        imported.frames, imported.transcriptions, batch.source
+    );
+    Ok(JsonResponse(imported))
+}
+
+#[derive(Deserialize)]
+pub(crate) struct ImportTranscriptRequest {
+    audio_chunk_id: i64,
+    transcript: Vec<ManualTranscriptSegment>,
+}
+
+#[derive(Serialize)]
+pub(crate) struct ImportTranscriptResponse {
+    audio_chunk_id: i64,
+    segments: usize,
+}
+
+/// Transcript of an audio chunk made outside of screenpipe, replaces the chunk's transcriptions.
+pub(crate) async fn import_transcript(
+    State(state): State<Arc<AppState>>,
+    JsonResponse(request): JsonResponse<ImportTranscriptRequest>,
+) -> Result<JsonResponse<ImportTranscriptResponse>, (StatusCode, JsonResponse<Value>)> {
+    let bad_request = |error: String| {
+        (
+            StatusCode::BAD_REQUEST,
+            JsonResponse(json!({"error": error})),
+        )
+    };
+    if request.transcript.is_empty() {
+        return Err(bad_request("transcript has no segments".to_string()));
+    }
+    for (index, segment) in request.transcript.iter().enumerate() {
+        if segment.end_ms < segment.start_ms {
+            return Err(bad_request(format!(
+                "segment {} ends before it starts",
+                index
+            )));
+        }
+        if segment.text.trim().is_empty() {
+            return Err(bad_request(format!("segment {} has no text", index)));
+        }
+    }
+
+    let segments = state
+        .db
+        .replace_audio_transcript(request.audio_chunk_id, &request.transcript)
+        .await
+        .map_err(|e| {
+            error!(
+                "failed to import transcript of audio chunk {}: {}",
+                request.audio_chunk_id, e
+            );
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                JsonResponse(json!({"error": format!("failed to import transcript: {}", e)})),
+            )
+        })?
+        .ok_or_else(|| {
+            (
+                StatusCode::NOT_FOUND,
+                JsonResponse(
+                    json!({"error": format!("audio chunk {} not found", request.audio_chunk_id)}),
+                ),
+            )
+        })?;
+    info!(
+        "imported {} transcript segments for audio chunk {}",
+        segments, request.audio_chunk_id
+    );
+    Ok(JsonResponse(ImportTranscriptResponse {
+        audio_chunk_id: request.audio_chunk_id,
+        segments,
+    }))
+}
+
+#[derive(Deserialize)]
+pub(crate) struct CorrectTranscriptRequest {
+    transcript: String,
+}
+
+pub(crate) async fn correct_transcript(
+    Path(audio_chunk_id): Path<i64>,
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    JsonResponse(request): JsonResponse<CorrectTranscriptRequest>,
+) -> Result<JsonResponse<TranscriptCorrection>, (StatusCode, JsonResponse<Value>)> {
+    let transcript = request.transcript.trim();
+    if transcript.is_empty() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            JsonResponse(json!({"error": "transcript is empty"})),
+        ));
+    }
+    let user_agent = headers
+        .get(header::USER_AGENT)
+        .and_then(|value| value.to_str().ok());
+
+    let correction = state
+        .db
+        .correct_audio_transcript(audio_chunk_id, transcript, user_agent)
+        .await
+        .map_err(|e| {
+            error!(
+                "failed to correct transcript of audio chunk {}: {}",
+                audio_chunk_id, e
+            );
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                JsonResponse(json!({"error": format!("failed to correct transcript: {}", e)})),
+            )
+        })?
+        .ok_or_else(|| {
+            (
+                StatusCode::NOT_FOUND,
+                JsonResponse(
+                    json!({"error": format!("audio chunk {} has no transcript", audio_chunk_id)}),
+                ),
+            )
+        })?;
+    info!("corrected transcript of audio chunk {}", audio_chunk_id);
+    Ok(JsonResponse(correction))
+}
+
+#[derive(Deserialize)]
+pub(crate) struct CsvExportQuery {
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+    #[serde(default)]
+    columns: Option<String>,
+}
+
+pub(crate) async fn export_frames_csv(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<CsvExportQuery>,
+) -> Result<Response, (StatusCode, JsonResponse<Value>)> {
+    let bad_request = |error: String| {
+        (
+            StatusCode::BAD_REQUEST,
+            JsonResponse(json!({"error": error})),
+        )
+    };
+    let columns = match &query.columns {
+        Some(columns) => parse_csv_columns(columns).map_err(bad_request)?,
+        None => DEFAULT_CSV_COLUMNS.to_vec(),
+    };
+    if query.from > query.to {
+        return Err(bad_request("from must be before to".to_string()));
+    }
+
+    let disposition = format!(
+        "attachment; filename={}",
+        csv_filename(query.from, query.to)
+    );
+    Ok((
+        [
+            (header::CONTENT_TYPE, "text/csv; charset=utf-8".to_string()),
+            (header::CONTENT_DISPOSITION, disposition),
+        ],
+        stream_frames_csv(state.db.clone(), query.from, query.to, columns),
+    )
+        .into_response())
+}
+
+#[derive(Deserialize)]
+pub(crate) struct DeleteContentQuery {
+    #[serde(default)]
+    from: Option<DateTime<Utc>>,
+    #[serde(default)]
+    to: Option<DateTime<Utc>>,
+    #[serde(default)]
+    app_name: Option<String>,
+    #[serde(default)]
+    confirm: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct DeleteContentResponse {
+    pub deleted_rows: usize,
+    pub deleted_files: usize,
+    pub freed_bytes: u64,
+}
+
+/// Deletion needs `?confirm=true` or an `X-Confirm: true` header.
+fn require_confirmation(
+    query: &DeleteContentQuery,
+    headers: &HeaderMap,
+) -> Result<(), (StatusCode, JsonResponse<Value>)> {
+    let header_confirmed = headers
+        .get("x-confirm")
+        .and_then(|v| v.to_str().ok())
+        .map_or(false, |v| v.eq_ignore_ascii_case("true"));
+    if query.confirm || header_confirmed {
+        Ok(())
+    } else {
+        Err((
+            StatusCode::BAD_REQUEST,
+            JsonResponse(
+                json!({"error": "deletion requires ?confirm=true or an X-Confirm: true header"}),
+            ),
+        ))
+    }
+}
+
+/// Removes the files that exist, returning how many were removed and their total size.
+async fn remove_files(paths: impl IntoIterator<Item = PathBuf>) -> (usize, u64) {
+    let (mut count, mut bytes) = (0, 0);
+    for path in paths {
+        let size = match tokio::fs::metadata(&path).await {
+            Ok(metadata) => metadata.len(),
+            Err(_) => continue,
+        };
+        match tokio::fs::remove_file(&path).await {
+            Ok(_) => {
+                count += 1;
+                bytes += size;
+            }
+            Err(e) => error!("failed to remove {}: {}", path.display(), e),
+        }
+    }
+    (count, bytes)
+}
+
+pub(crate) async fn delete_frames_handler(
+    Query(query): Query<DeleteContentQuery>,
+    headers: HeaderMap,
+    State(state): State<Arc<AppState>>,
+) -> Result<JsonResponse<DeleteContentResponse>, (StatusCode, JsonResponse<Value>)> {
+    require_confirmation(&query, &headers)?;
+
+    let deleted = state
+        .db
+        .delete_frames(query.from, query.to, query.app_name.as_deref())
+        .await
+        .map_err(|e| {
+            error!("failed to delete frames: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                JsonResponse(json!({"error": format!("failed to delete frames: {}", e)})),
+            )
+        })?;
+
+    let thumbnails_dir = thumbnails_dir(&state.frames_dir);
+    let mut files: Vec<PathBuf> = deleted.file_paths.iter().map(PathBuf::from).collect();
+    files.extend(
+        deleted
+            .ids
+            .iter()
+            .map(|id| thumbnail_path(&thumbnails_dir, *id)),
+    );
+    let (deleted_files, freed_bytes) = remove_files(files).await;
+
+    info!(
+        "deleted {} frames and {} files ({} bytes)",
+        deleted.ids.len(),
+        deleted_files,
+        freed_bytes
+    );
+    Ok(JsonResponse(DeleteContentResponse {
+        deleted_rows: deleted.ids.len(),
+        deleted_files,
+        freed_bytes,
+    }))
+}
+
+pub(crate) async fn delete_audio_handler(
+    Query(query): Query<DeleteContentQuery>,
+    headers: HeaderMap,
+    State(state): State<Arc<AppState>>,
+) -> Result<JsonResponse<DeleteContentResponse>, (StatusCode, JsonResponse<Value>)> {
+    require_confirmation(&query, &headers)?;
+
+    let deleted = state
+        .db
+        .delete_audio(query.from, query.to)
+        .await
+        .map_err(|e| {
+            error!("failed to delete audio: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                JsonResponse(json!({"error": format!("failed to delete audio: {}", e)})),
+            )
+        })?;
+
+    let (deleted_files, freed_bytes) =
+        remove_files(deleted.file_paths.iter().map(PathBuf::from)).await;
+
+    info!(
+        "deleted {} audio chunks and {} files ({} bytes)",
+        deleted.ids.len(),
+        deleted_files,
+        freed_bytes
+    );
+    Ok(JsonResponse(DeleteContentResponse {
+        deleted_rows: deleted.ids.len(),
+        deleted_files,
+        freed_bytes,
+    }))
+}
+
+pub(crate) async fn generate_thumbnails_handler(
+    State(state): State<Arc<AppState>>,
+) -> (StatusCode, JsonResponse<Value>) {
+    let thumbnails_dir = thumbnails_dir(&state.frames_dir);
+    if spawn_thumbnail_generation(Arc::clone(&state.db), thumbnails_dir) {
+        (
+            StatusCode::ACCEPTED,
+            JsonResponse(json!({"message": "thumbnail generation started"})),
+        )
+    } else {
+        (
+            StatusCode::CONFLICT,
+            JsonResponse(json!({"error": "thumbnail generation already running"})),
+        )
+    }
+}
+
+pub(crate) async fn export_video_handler(
+    State(state): State<Arc<AppState>>,
+    JsonResponse(request): JsonResponse<ExportVideoRequest>,
+) -> Result<Response, (StatusCode, JsonResponse<Value>)> {
+    if request.from > request.to
+        || !(request.fps.is_finite() && request.fps > 0.0)
+        || request.keyframe_interval == Some(0)
+    {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            JsonResponse(json!({"error": "invalid time range, fps or keyframe interval"})),
+        ));
+    }
+
+    let internal_error = |e: String| {
+        error!("failed to export video: {}", e);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            JsonResponse(json!({"error": format!("failed to export video: {}", e)})),
+        )
+    };
+
+    let frames = state
+        .db
+        .get_monitor_frames(request.monitor_id, request.from, request.to)
+        .await
+        .map_err(|e| internal_error(e.to_string()))?;
+
+    if frames.is_empty() {
+        return Err((
+            StatusCode::NOT_FOUND,
+            JsonResponse(json!({"error": "no frames found in the requested range"})),
+        ));
+    }
+
+    let keyframe_interval = keyframe_interval(frames.len(), request.keyframe_interval);
+    info!(
+        "exporting {} frames of monitor {} at {} fps, a keyframe every {} frames",
+        frames.len(),
+        request.monitor_id,
+        request.fps,
+        keyframe_interval
+    );
+
+    if frames.len() > MAX_STREAMED_FRAMES {
+        let job = state
+            .export_jobs
+            .spawn(frames, request.fps, keyframe_interval)
+            .await
+            .map_err(|e| internal_error(e.to_string()))?;
+        return Ok((StatusCode::ACCEPTED, JsonResponse(job)).into_response());
+    }
+
+    let body = stream_export(frames, request.fps, keyframe_interval)
+        .await
+        .map_err(|e| internal_error(e.to_string()))?;
+    Ok(([(header::CONTENT_TYPE, "video/mp4")], body).into_response())
+}
+
+#[derive(Deserialize)]
+pub(crate) struct WaveformQuery {
+    #[serde(default = "default_waveform_samples")]
+    samples: usize,
+}
+
+fn default_waveform_samples() -> usize {
+    200
+}
+
+/// `[min, max]` amplitude pairs of an audio chunk, decoded on demand.
+pub(crate) async fn get_audio_waveform(
+    Path(audio_chunk_id): Path<i64>,
+    Query(query): Query<WaveformQuery>,
+    State(state): State<Arc<AppState>>,
+) -> Result<JsonResponse<Vec<[f32; 2]>>, (StatusCode, JsonResponse<Value>)> {
+    if query.samples == 0 || query.samples > MAX_WAVEFORM_SAMPLES {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            JsonResponse(json!({
+                "error": format!("samples must be between 1 and {}", MAX_WAVEFORM_SAMPLES)
+            })),
+        ));
+    }
+    if let Some(waveform) = cached_waveform(audio_chunk_id, query.samples) {
+        return Ok(JsonResponse(waveform.as_ref().clone()));
+    }
+
+    let internal_error = |e: String| {
+        error!(
+            "failed to compute waveform of audio chunk {}: {}",
+            audio_chunk_id, e
+        );
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            JsonResponse(json!({"error": format!("failed to compute waveform: {}", e)})),
+        )
+    };
+    let file_path = audio_chunk_file(&state.db, audio_chunk_id).await?;
+
+    let samples = query.samples;
+    let waveform = spawn_blocking_in_current_span(move || {
+        let (audio, _) = pcm_decode(&file_path)?;
+        Ok::<_, anyhow::Error>(Arc::new(compute_waveform(&audio, samples)))
+    })
+    .await
+    .map_err(|e| internal_error(e.to_string()))?
+    .map_err(|e| internal_error(e.to_string()))?;
+
+    cache_waveform(audio_chunk_id, samples, waveform.clone());
+    Ok(JsonResponse(waveform.as_ref().clone()))
+}
+
+/// Path of an audio chunk's file, 404 when the chunk does not exist or its file is missing.
+async fn audio_chunk_file(
+    db: &DatabaseManager,
+    audio_chunk_id: i64,
+) -> Result<String, (StatusCode, JsonResponse<Value>)> {
+    let internal_error = |e: sqlx::Error| {
+        error!("failed to get audio chunk {}: {}", audio_chunk_id, e);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            JsonResponse(json!({"error": format!("failed to get audio chunk: {}", e)})),
+        )
+    };
+    let (file_path, file_missing) = db
+        .get_audio_chunk_file(audio_chunk_id)
+        .await
+        .map_err(internal_error)?
+        .ok_or_else(|| {
+            (
+                StatusCode::NOT_FOUND,
+                JsonResponse(json!({"error": "audio chunk not found"})),
+            )
+        })?;
+    // the flag is only what the daily check saw last, the file may have gone or come back since
+    let present = audio_file_present(&file_path).await;
+    if present == file_missing && !db.is_read_only() {
+        db.set_audio_chunk_file_missing(audio_chunk_id, !present)
+            .await
+            .map_err(internal_error)?;
+    }
+    if present {
+        return Ok(file_path);
+    }
+    Err((
+        StatusCode::NOT_FOUND,
+        JsonResponse(json!({
+            "error": format!(
+                "the file of audio chunk {} was deleted or is empty: {}",
+                audio_chunk_id, file_path
+            )
+        })),
+    ))
+}
+
+/// Etag of an audio file, changes when the file is rewritten.
+fn audio_etag(metadata: &std::fs::Metadata) -> String {
+    let mtime = metadata
+        .modified()
+        .ok()
+        .and_then(|modified| modified.duration_since(std::time::UNIX_EPOCH).ok())
+        .unwrap_or_default();
+    format!("\"{:x}-{:x}\"", mtime.as_nanos(), metadata.len())
+}
+
+/// The frame or audio file of a `media_url` of search results, refused once it expired. Audio
+/// answers 304 when `If-None-Match` has its etag.
+pub(crate) async fn get_signed_media(
+    Path(token): Path<String>,
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+) -> Result<Response, (StatusCode, JsonResponse<Value>)> {
+    let token = state.media_signer.verify(&token, Utc::now()).map_err(|e| {
+        (
+            StatusCode::FORBIDDEN,
+            JsonResponse(json!({"error": e.to_string()})),
+        )
+    })?;
+    match token.kind {
+        MediaKind::Frame => serve_frame_png(&state, token.id).await,
+        MediaKind::Audio => serve_audio_chunk(&state, token.id, &headers).await,
+    }
+}
+
+async fn serve_frame_png(
+    state: &AppState,
+    frame_id: i64,
+) -> Result<Response, (StatusCode, JsonResponse<Value>)> {
+    let internal_error = |e: String| {
+        error!("failed to get image of frame {}: {}", frame_id, e);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            JsonResponse(json!({"error": format!("failed to get frame image: {}", e)})),
+        )
+    };
+    let (file_path, offset_index) = state
+        .db
+        .get_frame(frame_id)
+        .await
+        .map_err(|e| internal_error(e.to_string()))?
+        .ok_or_else(|| {
+            (
+                StatusCode::NOT_FOUND,
+                JsonResponse(json!({"error": "frame not found"})),
+            )
+        })?;
+    let png = extract_frame_png(&file_path, offset_index)
+        .await
+        .map_err(|e| internal_error(e.to_string()))?;
+    Ok(([(header::CONTENT_TYPE, "image/png")], png).into_response())
+}
+
+async fn serve_audio_chunk(
+    state: &AppState,
+    audio_chunk_id: i64,
+    headers: &HeaderMap,
+) -> Result<Response, (StatusCode, JsonResponse<Value>)> {
+    let file_path = audio_chunk_file(&state.db, audio_chunk_id).await?;
+    let read_error = |e: std::io::Error| {
+        error!("failed to read audio chunk {}: {}", audio_chunk_id, e);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            JsonResponse(json!({"error": format!("failed to read audio chunk: {}", e)})),
+        )
+    };
+    let file = tokio::fs::File::open(&file_path)
+        .await
+        .map_err(read_error)?;
+    let metadata = file.metadata().await.map_err(read_error)?;
+    let etag = audio_etag(&metadata);
+    if headers
+        .get(header::IF_NONE_MATCH)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.split(',').any(|tag| tag.trim() == etag))
+    {
+        return Ok((StatusCode::NOT_MODIFIED, [(header::ETAG, etag)]).into_response());
+    }
+
+    let format = AudioFormat::from_path(std::path::Path::new(&file_path));
+    let content_type = format.map_or("application/octet-stream", |format| format.content_type());
+    if format.is_some_and(|format| format.is_zstd()) {
+        // served as the wav it holds
+        drop(file);
+        let wav = spawn_blocking_in_current_span(move || {
+            read_audio_file(std::path::Path::new(&file_path))
+        })
+        .await
+        .map_err(|e| read_error(std::io::Error::other(e)))?
+        .map_err(read_error)?;
+        return Ok((
+            [
+                (header::CONTENT_TYPE, content_type.to_string()),
+                (header::ETAG, etag),
+            ],
+            wav,
+        )
+            .into_response());
+    }
+    Ok((
+        [
+            (header::CONTENT_TYPE, content_type.to_string()),
+            (header::CONTENT_LENGTH, metadata.len().to_string()),
+            (header::ETAG, etag),
+        ],
+        Body::from_stream(ReaderStream::new(file)),
+    )
+        .into_response())
+}
+
+/// Markdown written by the daily summary job, `date` is `YYYY-MM-DD`.
+pub(crate) async fn get_summary(
+    Path(date): Path<String>,
+    State(state): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, (StatusCode, JsonResponse<Value>)> {
+    let date = NaiveDate::parse_from_str(&date, "%Y-%m-%d").map_err(|_| {
+        (
+            StatusCode::BAD_REQUEST,
+            JsonResponse(json!({"error": format!("invalid date '{}', expected YYYY-MM-DD", date)})),
+        )
+    })?;
+    match tokio::fs::read_to_string(summary_path(&state.screenpipe_dir, date)).await {
+        Ok(markdown) => Ok((
+            [(header::CONTENT_TYPE, "text/markdown; charset=utf-8")],
+            markdown,
+        )),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Err((
+            StatusCode::NOT_FOUND,
+            JsonResponse(json!({"error": format!("no summary for {}", date)})),
+        )),
+        Err(e) => {
+            error!("failed to read summary of {}: {}", date, e);
+            Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                JsonResponse(json!({"error": format!("failed to read summary: {}", e)})),
+            ))
+        }
+    }
+}
+
+pub(crate) async fn get_export_job(
+    Path(job_id): Path<String>,
+    State(state): State<Arc<AppState>>,
+) -> Result<JsonResponse<ExportJob>, (StatusCode, JsonResponse<Value>)> {
+    state
+        .export_jobs
+        .get(&job_id)
+        .await
+        .map(JsonResponse)
+        .ok_or_else(|| {
+            (
+                StatusCode::NOT_FOUND,
+                JsonResponse(json!({"error": format!("export job {} not found", job_id)})),
+            )
+        })
+}
+
+#[derive(Deserialize)]
+pub(crate) struct SlowQueriesQuery {
+    #[serde(default = "default_limit")]
+    limit: u32,
+}
+
+/// Slowest requests of the last 24 hours.
+pub(crate) async fn get_slow_queries(
+    Query(query): Query<SlowQueriesQuery>,
+    State(state): State<Arc<AppState>>,
+) -> Result<JsonResponse<Vec<RequestLogEntry>>, (StatusCode, JsonResponse<Value>)> {
+    let since = Utc::now() - chrono::Duration::hours(24);
+    state
+        .db
+        .get_slow_requests(since, query.limit)
+        .await
+        .map(JsonResponse)
+        .map_err(|e| {
+            error!("failed to get slow queries: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                JsonResponse(json!({"error": format!("failed to get slow queries: {}", e)})),
+            )
+        })
+}
+
+/// Fires a notification like the resource alerts, to check notifications show up.
+pub(crate) async fn test_alert_handler(
+) -> Result<JsonResponse<Value>, (StatusCode, JsonResponse<Value>)> {
+    spawn_blocking_in_current_span(|| {
+        send_desktop_notification(
+            "screenpipe: test alert",
+            "Desktop notifications are working, resource alerts will show up like this",
+        )
+    })
+    .await
+    .map_err(anyhow::Error::from)
+    .and_then(|result| result)
+    .map(|_| JsonResponse(json!({"success": true})))
+    .map_err(|e| {
+        error!("failed to send test notification: {}", e);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            JsonResponse(json!({"error": format!("failed to send notification: {}", e)})),
+        )
+    })
+}
+
+pub(crate) async fn get_config(
+    State(state): State<Arc<AppState>>,
+) -> JsonResponse<RuntimeConfigResponse> {
+    JsonResponse(RuntimeConfigResponse::new(
+        &state.capture_config.read().unwrap(),
+        &state.rank_weights.read().unwrap(),
+    ))
+}
+
+/// Applies a partial update, the recorder picks it up on its next capture cycle.
+pub(crate) async fn update_config(
+    State(state): State<Arc<AppState>>,
+    JsonResponse(update): JsonResponse<RuntimeConfigUpdate>,
+) -> Result<JsonResponse<RuntimeConfigResponse>, (StatusCode, JsonResponse<Value>)> {
+    let mut config = state.capture_config.write().unwrap();
+    let mut rank_weights = state.rank_weights.write().unwrap();
+    update
+        .apply(&mut config, &mut rank_weights)
+        .map_err(|e| (StatusCode::BAD_REQUEST, JsonResponse(json!({"error": e}))))?;
+    info!(
+        "runtime config updated: {:?}, search rank {:?}",
+        *config, *rank_weights
+    );
+    Ok(JsonResponse(RuntimeConfigResponse::new(
+        &config,
+        &rank_weights,
+    )))
+}
+
+pub(crate) async fn list_sessions(
+    Query(pagination): Query<PaginationQuery>,
+    State(state): State<Arc<AppState>>,
+) -> Result<JsonResponse<Vec<Session>>, (StatusCode, JsonResponse<Value>)> {
+    state
+        .db
+        .get_sessions(pagination.limit, pagination.offset)
+        .await
+        .map(JsonResponse)
+        .map_err(|e| {
+            error!("failed to list sessions: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                JsonResponse(json!({"error": format!("failed to list sessions: {}", e)})),
+            )
+        })
+}
+
+/// Most buckets one `GET /timeline` returns, a wider range needs a coarser resolution.
+const MAX_TIMELINE_BUCKETS: i64 = 10_000;
+
+#[derive(Deserialize)]
+pub(crate) struct TimelineQuery {
+    #[serde(default)]
+    from: Option<DateTime<Utc>>,
+    #[serde(default)]
+    to: Option<DateTime<Utc>>,
+    #[serde(default)]
+    resolution: TimelineResolution,
+}
+
+/// Activity per time bucket, the last 24 hours by default.
+pub(crate) async fn get_timeline(
+    Query(query): Query<TimelineQuery>,
+    State(state): State<Arc<AppState>>,
+) -> Result<JsonResponse<Vec<TimelineBucket>>, (StatusCode, JsonResponse<Value>)> {
+    let to = query.to.unwrap_or_else(Utc::now);
+    let from = query.from.unwrap_or(to - chrono::Duration::days(1));
+    if from > to {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            JsonResponse(json!({"error": "from must be before to"})),
+        ));
+    }
+    let buckets = (to - from).num_seconds() / query.resolution.bucket_secs();
+    if buckets > MAX_TIMELINE_BUCKETS {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            JsonResponse(json!({"error": format!(
+                "range spans {} buckets, at most {} are returned, use a coarser resolution",
+                buckets, MAX_TIMELINE_BUCKETS
+            )})),
+        ));
+    }
+
+    state
+        .db
+        .get_timeline(
+            from,
+            to,
+            query.resolution,
+            state.stats_cache.audio_chunk_duration(),
+        )
+        .await
+        .map(JsonResponse)
+        .map_err(|e| {
+            error!("failed to get timeline: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                JsonResponse(json!({"error": format!("failed to get timeline: {}", e)})),
+            )
+        })
+}
+
+#[derive(Deserialize)]
+pub(crate) struct SubtitlesQuery {
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+    format: SubtitleFormat,
+}
+
+/// Transcripts between `from` and `to` as subtitles timed from `from`.
+pub(crate) async fn export_subtitles(
+    Query(query): Query<SubtitlesQuery>,
+    State(state): State<Arc<AppState>>,
+) -> Result<Response, (StatusCode, JsonResponse<Value>)> {
+    if query.from > query.to {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            JsonResponse(json!({"error": "from must be before to"})),
+        ));
+    }
+    let transcripts = state
+        .db
+        .get_audio_transcripts(query.from, query.to)
+        .await
+        .map_err(|e| {
+            error!("failed to get transcripts: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                JsonResponse(json!({"error": format!("failed to get transcripts: {}", e)})),
+            )
+        })?;
+    let cues = build_cues(
+        query.from,
+        &transcripts,
+        state.stats_cache.audio_chunk_duration(),
+    );
+    Ok((
+        [(header::CONTENT_TYPE, query.format.content_type())],
+        render_subtitles(&cues, query.format),
+    )
+        .into_response())
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct RecordingStatusResponse {
+    pub recording: bool,
+    /// `false` when recording already was in the requested state
+    pub changed: bool,
+}
+
+pub(crate) async fn start_recording(
+    State(state): State<Arc<AppState>>,
+) -> JsonResponse<RecordingStatusResponse> {
+    let changed = state.recording.start();
+    if changed {
+        info!("recording started through the api");
+    }
+    JsonResponse(RecordingStatusResponse {
+        recording: true,
+        changed,
+    })
+}
+
+/// The recorder cuts its current audio chunks short, sends them to whisper and flushes
+/// its pending frames, then stops until the next start.
+pub(crate) async fn stop_recording(
+    State(state): State<Arc<AppState>>,
+) -> JsonResponse<RecordingStatusResponse> {
+    let changed = state.recording.stop();
+    if changed {
+        info!("recording stopped through the api");
+    }
+    JsonResponse(RecordingStatusResponse {
+        recording: false,
+        changed,
+    })
+}
+
+#[derive(Deserialize)]
+pub(crate) struct UpdateSessionRequest {
+    name: Option<String>,
+}
+
+pub(crate) async fn update_session(
+    Path(session_id): Path<String>,
+    State(state): State<Arc<AppState>>,
+    JsonResponse(payload): JsonResponse<UpdateSessionRequest>,
+) -> Result<JsonResponse<Session>, (StatusCode, JsonResponse<Value>)> {
+    match state
+        .db
+        .rename_session(&session_id, payload.name.as_deref())
+        .await
+    {
+        Ok(Some(session)) => Ok(JsonResponse(session)),
+        Ok(None) => Err((
+            StatusCode::NOT_FOUND,
+            JsonResponse(json!({"error": format!("session {} not found", session_id)})),
+        )),
+        Err(e) => {
+            error!("failed to update session {}: {}", session_id, e);
+            Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                JsonResponse(json!({"error": format!("failed to update session: {}", e)})),
+            ))
+        }
+    }
+}
+
+// Request and response structs
+#[derive(Deserialize)]
+struct DownloadPipeRequest {
+    url: String,
+}
+
+#[derive(Deserialize)]
+struct RunPipeRequest {
+    pipe_id: String,
+}
+
+#[derive(Deserialize)]
+struct UpdatePipeConfigRequest {
+    pipe_id: String,
+    config: serde_json::Value,
+}
+
+// Handler functions
+async fn download_pipe_handler(
+    State(state): State<Arc<AppState>>,
+    JsonResponse(payload): JsonResponse<DownloadPipeRequest>,
+) -> Result<JsonResponse<serde_json::Value>, (StatusCode, JsonResponse<Value>)> {
+    debug!("Downloading pipe: {}", payload.url);
+    match state.pipe_manager.download_pipe(&payload.url).await {
+        Ok(pipe_dir) => Ok(JsonResponse(json!({
+            "message": format!("Pipe {} downloaded successfully", pipe_dir),
+            "pipe_id": pipe_dir
+        }))),
+        Err(e) => {
+            error!("Failed to download pipe: {}", e);
+            Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                JsonResponse(json!({"error": e.to_string()})),
+            ))
+        }
+    }
+}
+
+async fn run_pipe_handler(
+    State(state): State<Arc<AppState>>,
+    JsonResponse(payload): JsonResponse<RunPipeRequest>,
+) -> Result<JsonResponse<Value>, (StatusCode, JsonResponse<Value>)> {
+    debug!("Starting pipe: {}", payload.pipe_id);
+
+    match state
+        .pipe_manager
+        .update_config(
+            &payload.pipe_id,
+            serde_json::json!({
+                "enabled": true,
+            }),
+        )
+        .await
+    {
         Ok(_) => Ok(JsonResponse(json!({
             "message": format!("Pipe {} started", payload.pipe_id),
             "pipe_id": payload.pipe_id
@@ -696,10 +2628,22 @@ pub struct Server {
     addr: SocketAddr,
     vision_control: Arc<AtomicBool>,
     audio_devices_control: Arc<SegQueue<(AudioDevice, DeviceControl)>>,
-    screenpipe_dir: PathBuf,
+    storage: StoragePaths,
     pipe_manager: Arc<PipeManager>,
     vision_disabled: bool,
     audio_disabled: bool,
+    audio_chunk_duration: Duration,
+    capture_config: SharedCaptureConfig,
+    recording: Arc<RecordingControl>,
+    security_headers: bool,
+    read_only: bool,
+    remote_sync_secret: Option<String>,
+    rate_limit: Option<RateLimit>,
+    media_signer: Arc<MediaSigner>,
+    live_transcription: Option<Arc<LiveTranscription>>,
+    normalize_ocr: bool,
+    body_limits: BodyLimits,
+    ready: Option<oneshot::Sender<u16>>,
     #[cfg(feature = "llm")]
     enable_llm: bool,
     #[cfg(feature = "llm")]
@@ -713,10 +2657,16 @@ impl Server {
         addr: SocketAddr,
         vision_control: Arc<AtomicBool>,
         audio_devices_control: Arc<SegQueue<(AudioDevice, DeviceControl)>>,
-        screenpipe_dir: PathBuf,
+        storage: StoragePaths,
         pipe_manager: Arc<PipeManager>,
         vision_disabled: bool,
         audio_disabled: bool,
+        audio_chunk_duration: Duration,
+        capture_config: SharedCaptureConfig,
+        recording: Arc<RecordingControl>,
+        security_headers: bool,
+        read_only: bool,
+        remote_sync_secret: Option<String>,
         #[cfg(feature = "llm")] enable_llm: bool,
         #[cfg(feature = "llm")] llm: Option<LLM>,
     ) -> Self {
@@ -725,10 +2675,22 @@ impl Server {
             addr,
             vision_control,
             audio_devices_control,
-            screenpipe_dir,
+            storage,
             pipe_manager,
             vision_disabled,
             audio_disabled,
+            audio_chunk_duration,
+            capture_config,
+            recording,
+            security_headers,
+            read_only,
+            remote_sync_secret,
+            rate_limit: None,
+            media_signer: Arc::new(MediaSigner::random(DEFAULT_SIGNED_URL_TTL)),
+            live_transcription: None,
+            normalize_ocr: true,
+            body_limits: BodyLimits::default(),
+            ready: None,
             #[cfg(feature = "llm")]
             enable_llm,
             #[cfg(feature = "llm")]
@@ -736,6 +2698,44 @@ impl Server {
         }
     }
 
+    /// Limits the requests each client ip may send, see `--rate-limit-rps`.
+    pub fn rate_limit(mut self, rate_limit: Option<RateLimit>) -> Self {
+        self.rate_limit = rate_limit;
+        self
+    }
+
+    /// Signs the media urls of search results, share one between servers so their urls work on
+    /// either.
+    pub fn media_signer(mut self, media_signer: Arc<MediaSigner>) -> Self {
+        self.media_signer = media_signer;
+        self
+    }
+
+    /// Serves `GET /stream/transcription`, whisper is loaded on the first stream.
+    pub fn live_transcription(mut self, engine: Arc<AudioTranscriptionEngine>) -> Self {
+        self.live_transcription = Some(Arc::new(LiveTranscription::new(engine)));
+        self
+    }
+
+    /// Normalizes the text of `POST /ocr/reprocess/:frame_id` like the recorder, see
+    /// `--ocr-no-normalize`.
+    pub fn normalize_ocr(mut self, normalize_ocr: bool) -> Self {
+        self.normalize_ocr = normalize_ocr;
+        self
+    }
+
+    /// Largest request bodies accepted, see `--max-body-size-kb`.
+    pub fn body_limits(mut self, body_limits: BodyLimits) -> Self {
+        self.body_limits = body_limits;
+        self
+    }
+
+    /// `ready` gets the port once the api accepts connections.
+    pub fn notify_ready(mut self, ready: oneshot::Sender<u16>) -> Self {
+        self.ready = Some(ready);
+        self
+    }
+
     pub async fn start<F>(
         self,
         device_status: HashMap<AudioDevice, DeviceControl>,
@@ -744,34 +2744,77 @@ impl Server {
     where
         F: Fn(&axum::http::Request<axum::body::Body>) + Clone + Send + Sync + 'static,
     {
+        let stats_cache = Arc::new(StatsCache::new(
+            self.db.clone(),
+            self.storage.clone(),
+            self.audio_chunk_duration,
+        ));
+        stats_cache.start_refreshing();
+
+        let export_jobs = Arc::new(ExportJobs::new(self.storage.base_dir.join("exports")));
+        export_jobs.start_cleanup();
+
         let app_state = Arc::new(AppState {
             db: self.db,
             vision_control: self.vision_control,
             audio_devices_control: self.audio_devices_control,
-            devices_status: device_status,
+            devices_status: Arc::new(RwLock::new(device_status)),
             app_start_time: Utc::now(),
-            screenpipe_dir: self.screenpipe_dir.clone(),
+            screenpipe_dir: self.storage.base_dir.clone(),
+            frames_dir: self.storage.frames_dir.clone(),
             pipe_manager: self.pipe_manager,
             vision_disabled: self.vision_disabled,
             audio_disabled: self.audio_disabled,
+            stats_cache,
+            export_jobs,
+            capture_config: self.capture_config,
+            rank_weights: Arc::new(RwLock::new(RankWeights::default())),
+            recording: self.recording,
+            remote_sync_secret: self.remote_sync_secret,
+            media_signer: self.media_signer,
+            live_transcription: self.live_transcription,
+            normalize_ocr: self.normalize_ocr,
             #[cfg(feature = "llm")]
             llm_enabled: self.enable_llm,
             #[cfg(feature = "llm")]
             llm: self.llm,
         });
 
-        let app = create_router()
-            .layer(ApiPluginLayer::new(api_plugin))
-            .layer(CorsLayer::permissive())
-            .layer(
-                TraceLayer::new_for_http()
-                    .make_span_with(DefaultMakeSpan::new().include_headers(true)),
-            )
-            .with_state(app_state);
-
-        info!("Server starting on {}", self.addr);
+        let mut router = create_router_with_limits(self.body_limits);
+        if self.security_headers {
+            router = with_security_headers(router);
+        }
+        // the request log is a write, the read-only server leaves it to the main one
+        router = if self.read_only {
+            router.layer(middleware::from_fn(reject_writes))
+        } else {
+            router.layer(middleware::from_fn_with_state(
+                app_state.clone(),
+                log_request_duration,
+            ))
+        };
+        router = router.layer(ApiPluginLayer::new(api_plugin));
+        // preflight requests and 429s still get cors headers
+        if let Some(rate_limit) = self.rate_limit {
+            router = with_rate_limit(router, rate_limit);
+        }
+        router = with_version_headers(router);
+        let app = router.layer(CorsLayer::permissive());
+        let app = with_request_tracing(app).with_state(app_state);
+
+        let listener = TcpListener::from_std(bind_listener(self.addr)?)?;
+        info!("Server listening on {}", listener.local_addr()?);
+        if let Some(ready) = self.ready {
+            let _ = ready.send(listener.local_addr()?.port());
+        }
 
-        match serve(TcpListener::bind(self.addr).await?, app.into_make_service()).await {
+        // the client address is what --rate-limit-rps keys its buckets on
+        match serve(
+            listener,
+            app.into_make_service_with_connect_info::<SocketAddr>(),
+        )
+        .await
+        {
             Ok(_) => {
                 info!("Server stopped gracefully");
                 Ok(())
@@ -784,6 +2827,18 @@ impl Server {
     }
 }
 
+/// Only `GET` requests reach the handlers of the `--read-only-port` server.
+pub async fn reject_writes(request: axum::extract::Request, next: middleware::Next) -> Response {
+    if request.method() == Method::GET || request.method() == Method::HEAD {
+        return next.run(request).await;
+    }
+    (
+        StatusCode::METHOD_NOT_ALLOWED,
+        JsonResponse(json!({"error": "this server is read-only, send writes to the main port"})),
+    )
+        .into_response()
+}
+
 async fn merge_frames_handler(
     State(state): State<Arc<AppState>>,
     JsonResponse(payload): JsonResponse<MergeVideosRequest>,
@@ -857,12 +2912,26 @@ async fn execute_raw_sql(
     }
 }
 
-#[cfg(not(feature = "llm"))]
+/// The api with the default body limits.
 pub fn create_router() -> Router<Arc<AppState>> {
+    create_router_with_limits(BodyLimits::default())
+}
+
+pub fn create_router_with_limits(body_limits: BodyLimits) -> Router<Arc<AppState>> {
+    let import = Router::new().route("/import", post(import_remote_rows));
+    with_body_limit(api_routes(), body_limits.max_body_size)
+        .merge(with_body_limit(import, body_limits.max_import_body_size))
+}
+
+#[cfg(not(feature = "llm"))]
+fn api_routes() -> Router<Arc<AppState>> {
     Router::new()
         .route("/search", get(search))
+        .route("/search/stream", get(stream_search))
         .route("/audio/list", get(api_list_audio_devices))
+        .route("/devices/audio/discover", post(discover_audio_devices))
         .route("/vision/list", post(api_list_monitors))
+        .route("/monitors", get(api_list_monitors))
         .route(
             "/tags/:content_type/:id",
             post(add_tags).delete(remove_tags),
@@ -875,15 +2944,56 @@ pub fn create_router() -> Router<Arc<AppState>> {
         .route("/pipes/update", post(update_pipe_config_handler))
         .route("/experimental/frames/merge", post(merge_frames_handler))
         .route("/health", get(health_check))
+        .route("/version", get(get_version))
+        .route("/health/deep", get(deep_health_check))
+        .route("/stats", get(get_stats))
+        .route("/frames", get(list_frames).delete(delete_frames_handler))
+        .route("/frames/search", get(search_frames))
+        .route("/frames/random", get(random_frames))
+        .route("/frames/export/csv", get(export_frames_csv))
+        .route("/audio", delete(delete_audio_handler))
+        .route("/media/:token", get(get_signed_media))
+        .route("/share/frame/:frame_id", post(create_share))
+        .route("/share/:token", get(get_share))
+        .route("/stream/transcription", get(stream_transcription))
+        .route("/ocr/reprocess/:frame_id", post(reprocess_frame_ocr))
+        .route("/import/transcript", post(import_transcript))
+        .route(
+            "/audio/chunks/:audio_chunk_id/transcript",
+            patch(correct_transcript),
+        )
+        .route("/summaries/:date", get(get_summary))
+        .route("/audio/:audio_chunk_id/waveform", get(get_audio_waveform))
+        .route("/audio/export/subtitles", get(export_subtitles))
+        .route("/frames/:frame_id/thumbnail", get(get_frame_thumbnail))
+        .route("/frames/:frame_id/diff", get(get_frame_diff))
+        .route(
+            "/frames/generate-thumbnails",
+            post(generate_thumbnails_handler),
+        )
+        .route("/export/video", post(export_video_handler))
+        .route("/export/jobs/:job_id", get(get_export_job))
+        .route("/sessions", get(list_sessions))
+        .route("/timeline", get(get_timeline))
+        .route("/graphql", post(graphql_handler))
+        .route("/recording/start", post(start_recording))
+        .route("/recording/stop", post(stop_recording))
+        .route("/sessions/:session_id", put(update_session))
+        .route("/slow-queries", get(get_slow_queries))
+        .route("/alerts/test", post(test_alert_handler))
+        .route("/config", get(get_config).patch(update_config))
         .route("/raw_sql", post(execute_raw_sql))
 }
 
 #[cfg(feature = "llm")]
-pub fn create_router() -> Router<Arc<AppState>> {
+fn api_routes() -> Router<Arc<AppState>> {
     Router::new()
         .route("/search", get(search))
+        .route("/search/stream", get(stream_search))
         .route("/audio/list", get(api_list_audio_devices))
+        .route("/devices/audio/discover", post(discover_audio_devices))
         .route("/vision/list", post(api_list_monitors))
+        .route("/monitors", get(api_list_monitors))
         .route(
             "/tags/:content_type/:id",
             post(add_tags).delete(remove_tags),
@@ -896,6 +3006,44 @@ pub fn create_router() -> Router<Arc<AppState>> {
         .route("/pipes/update", post(update_pipe_config_handler))
         .route("/experimental/frames/merge", post(merge_frames_handler))
         .route("/health", get(health_check))
+        .route("/version", get(get_version))
+        .route("/health/deep", get(deep_health_check))
+        .route("/stats", get(get_stats))
+        .route("/frames", get(list_frames).delete(delete_frames_handler))
+        .route("/frames/search", get(search_frames))
+        .route("/frames/random", get(random_frames))
+        .route("/frames/export/csv", get(export_frames_csv))
+        .route("/audio", delete(delete_audio_handler))
+        .route("/media/:token", get(get_signed_media))
+        .route("/share/frame/:frame_id", post(create_share))
+        .route("/share/:token", get(get_share))
+        .route("/stream/transcription", get(stream_transcription))
+        .route("/ocr/reprocess/:frame_id", post(reprocess_frame_ocr))
+        .route("/import/transcript", post(import_transcript))
+        .route(
+            "/audio/chunks/:audio_chunk_id/transcript",
+            patch(correct_transcript),
+        )
+        .route("/summaries/:date", get(get_summary))
+        .route("/audio/:audio_chunk_id/waveform", get(get_audio_waveform))
+        .route("/audio/export/subtitles", get(export_subtitles))
+        .route("/frames/:frame_id/thumbnail", get(get_frame_thumbnail))
+        .route("/frames/:frame_id/diff", get(get_frame_diff))
+        .route(
+            "/frames/generate-thumbnails",
+            post(generate_thumbnails_handler),
+        )
+        .route("/export/video", post(export_video_handler))
+        .route("/export/jobs/:job_id", get(get_export_job))
+        .route("/sessions", get(list_sessions))
+        .route("/timeline", get(get_timeline))
+        .route("/graphql", post(graphql_handler))
+        .route("/recording/start", post(start_recording))
+        .route("/recording/stop", post(stop_recording))
+        .route("/sessions/:session_id", put(update_session))
+        .route("/slow-queries", get(get_slow_queries))
+        .route("/alerts/test", post(test_alert_handler))
+        .route("/config", get(get_config).patch(update_config))
         .route("/raw_sql", post(execute_raw_sql))
         .route("/llm/chat", post(llm_chat_handler))
 }
@@ -914,9 +3062,16 @@ curl "http://localhost:3030/search?q=test&limit=5&offset=0&content_type=audio" |
 # 4. Search with pagination
 curl "http://localhost:3030/search?q=test&limit=10&offset=20" | jq
 
+# 5. Best matches first, by relevance and recency, or by app name
+curl "http://localhost:3030/search?q=test&limit=10&sort_by=rank" | jq
+curl "http://localhost:3030/search?q=test&limit=10&sort_by=app_name&order=asc" | jq
+
 # 6. Search with no query (should return all results)
 curl "http://localhost:3030/search?limit=5&offset=0"
 
+# 7. Every result, one json object a line, e.g. to export a day of ocr text
+curl -N "http://localhost:3030/search/stream?content_type=ocr&start_time=2024-10-14T00:00:00Z&end_time=2024-10-15T00:00:00Z" > search.ndjson
+
 // list devices
 // # curl "http://localhost:3030/audio/list" | jq
 
@@ -924,6 +3079,12 @@ curl "http://localhost:3030/search?limit=5&offset=0"
 echo "Listing audio devices:"
 curl "http://localhost:3030/audio/list" | jq
 
+# look for audio devices plugged in since startup
+curl -X POST "http://localhost:3030/devices/audio/discover" | jq
+
+# monitors that can be recorded, virtual ones (vnc, rdp, dummy displays) flagged
+curl "http://localhost:3030/monitors" | jq
+
 
 echo "Searching for content:"
 curl "http://localhost:3030/search?q=test&limit=5&offset=0&content_type=all" | jq
@@ -960,6 +3121,18 @@ curl "http://localhost:3030/search?q=libmp3&limit=5&offset=0&content_type=audio&
 curl "http://localhost:3030/search?app_name=cursor"
 curl "http://localhost:3030/search?content_type=audio&min_length=20"
 
+# Screen content classified as code (also browser, spreadsheet, terminal, video)
+curl "http://localhost:3030/search?q=fn&content_type=code&limit=10" | jq
+
+# Waveform of an audio chunk (chunk_id of an audio search result) as 200 [min, max] pairs
+curl "http://localhost:3030/audio/<chunk_id>/waveform?samples=200" | jq
+
+# Fuzzy search of the ocr text, tolerates up to 2 typos, results carry a match_score
+curl "http://localhost:3030/search?q=screnpipe&fuzzy=true&limit=10" | jq
+
+# Audio results with word level timestamps (ms from the start of the audio chunk)
+curl "http://localhost:3030/search?q=meeting&content_type=audio&align=word" | jq
+
 curl 'http://localhost:3030/search?q=Matt&offset=0&limit=50&start_time=2024-08-12T04%3A00%3A00Z&end_time=2024-08-12T05%3A00%3A00Z' | jq .
 
 
@@ -991,6 +3164,127 @@ curl -X POST "http://localhost:3030/tags/vision/626" \
      -d '{"tags": ["debug"]}'
 
 
+# Recording and storage statistics
+curl "http://localhost:3030/stats" | jq
+
+# Actively checks the database, a screen capture, ocr of it and the audio device, 503 on failure
+curl -f "http://localhost:3030/health/deep" | jq
+
+# Frames in a time range with thumbnail urls instead of video paths
+curl "http://localhost:3030/frames?from=$(date -u -v-5M +%Y-%m-%dT%H:%M:%SZ)&thumb=true" | jq
+curl "http://localhost:3030/frames/1/thumbnail" --output /tmp/thumb.jpg && open /tmp/thumb.jpg
+
+# Five frames of the last day picked at random with their text, seed picks the same ones again
+curl "http://localhost:3030/frames/random?n=5&from=$(date -u -v-1d +%Y-%m-%dT%H:%M:%SZ)&seed=42" | jq
+
+# What changed in the text of a frame since the previous frame of its window, as a unified diff
+curl "http://localhost:3030/frames/42/diff" | jq -r '.diff'
+
+# Keyset pagination: pass the previous page's pagination.next_cursor as cursor (offset is then ignored)
+NEXT_CURSOR=$(curl -s "http://localhost:3030/search?q=meeting&limit=20" | jq -r '.pagination.next_cursor')
+curl "http://localhost:3030/search?q=meeting&limit=20&cursor=$NEXT_CURSOR" | jq
+
+# Delete the frames of an app (or a time range) and audio of the last hour, with their files
+curl -X DELETE "http://localhost:3030/frames?app_name=Signal&confirm=true" | jq
+curl -X DELETE -H "X-Confirm: true" "http://localhost:3030/audio?from=$(date -u -v-1H +%Y-%m-%dT%H:%M:%SZ)" | jq
+
+# Generate thumbnails for frames recorded before thumbnails existed
+curl -X POST "http://localhost:3030/frames/generate-thumbnails" | jq
+
+# Export the last 5 minutes of monitor 1 as mp4 (large ranges return a job to poll instead, its
+# mp4 comes with a .json mapping frame ids to timestamps, keyframe_interval trades seeking for size)
+curl -X POST "http://localhost:3030/export/video" \
+  -H "Content-Type: application/json" \
+  -d "{\"from\": \"$(date -u -v-5M +%Y-%m-%dT%H:%M:%SZ)\", \"to\": \"$(date -u +%Y-%m-%dT%H:%M:%SZ)\", \"fps\": 5, \"monitor_id\": 1}" \
+  --output /tmp/export.mp4
+curl "http://localhost:3030/export/jobs/<job_id>" | jq
+curl -X POST "http://localhost:3030/export/video" \
+  -H "Content-Type: application/json" \
+  -d "{\"from\": \"$(date -u -v-1d +%Y-%m-%dT%H:%M:%SZ)\", \"to\": \"$(date -u +%Y-%m-%dT%H:%M:%SZ)\", \"monitor_id\": 1, \"keyframe_interval\": 10}" | jq
+
+# List recording sessions, name one and search within it
+curl "http://localhost:3030/sessions?limit=10" | jq
+curl -X PUT "http://localhost:3030/sessions/<session_id>" \
+  -H "Content-Type: application/json" \
+  -d '{"name": "sprint planning"}' | jq
+curl "http://localhost:3030/search?q=roadmap&session_id=<session_id>" | jq
+
+# Activity per minute over the last 24 hours, e.g. for a heatmap
+curl "http://localhost:3030/timeline?resolution=minute" | jq
+
+# Run ocr of frame 42 again with tesseract, the replaced text is kept in frame_ocr_history
+curl -X POST "http://localhost:3030/ocr/reprocess/42?engine=tesseract" | jq
+
+# Rows pushed by another instance started with --remote-sync-url, signed with --remote-sync-secret
+BODY='{"source":"laptop","frames":[],"transcriptions":[]}'
+curl -X POST http://localhost:3030/import -H "Content-Type: application/json" \
+  -H "x-screenpipe-signature: $(printf '%s' "$BODY" | openssl dgst -sha256 -hmac "$SECRET" | cut -d' ' -f2)" \
+  -d "$BODY" | jq
+
+# Transcript of an audio chunk made with another tool, replacing screenpipe's, search results tell
+# them apart by their "source": "automated" or "manual"
+curl -X POST http://localhost:3030/import/transcript -H "Content-Type: application/json" \
+  -d '{"audio_chunk_id": 42, "transcript": [{"start_ms": 0, "end_ms": 2500, "text": "shall we start?", "speaker": "alice"}, {"start_ms": 2600, "end_ms": 4000, "text": "yes", "speaker": "bob"}]}' | jq
+
+# One-time link to a frame and its text, dead once opened or after ttl_secs (24 hours by default)
+curl -X POST "http://localhost:3030/share/frame/42?ttl_secs=3600" | jq
+curl "http://localhost:3030/share/<token>" | jq -r '.image' | base64 --decode > /tmp/shared.png
+
+# Fix what whisper misheard in an audio chunk, search results of it then have "corrected": true
+curl -X PATCH http://localhost:3030/audio/chunks/42/transcript -H "Content-Type: application/json" \
+  -d '{"transcript": "shall we start?"}' | jq
+
+# Frames of a day as a spreadsheet, columns among frame_id, timestamp, app_name, window_title,
+# ocr_text, focused, file_path and offset_index
+curl -OJ "http://localhost:3030/frames/export/csv?from=2024-10-14T00:00:00Z&to=2024-10-15T00:00:00Z&columns=timestamp,app_name,window_title,ocr_text"
+
+# Server version, api version, commit and build time
+curl "http://localhost:3030/version" | jq
+
+# Markdown summary of today, written every day at --summary-time
+curl "http://localhost:3030/summaries/$(date +%Y-%m-%d)"
+
+# Frames matching "invoice" with their ocr text, cut to 500 characters
+curl "http://localhost:3030/frames/search?q=invoice&inline_text=true&limit=5" | jq
+
+# Open the frame (or audio) of the first search result by its media_url, valid for
+# --signed-url-ttl-secs
+curl "http://localhost:3030$(curl -s "http://localhost:3030/search?q=invoice&limit=1" | jq -r '.data[0].content.media_url')" \
+  --output /tmp/frame.png
+
+# Live captions: stream 16 kHz mono s16le pcm from the microphone, partial and final transcripts
+# come back as json, e.g. {"type":"final","text":"...","start_ms":0,"end_ms":2300}
+ffmpeg -f avfoundation -i ":0" -ac 1 -ar 16000 -f s16le - \
+  | websocat --binary "ws://localhost:3030/stream/transcription?sample_rate=16000&encoding=s16le"
+
+# Start and stop recording, e.g. when running with --manual-start
+curl -X POST "http://localhost:3030/recording/start" | jq
+curl -X POST "http://localhost:3030/recording/stop" | jq
+
+# Transcripts of the last hour as subtitles (srt or vtt)
+curl "http://localhost:3030/audio/export/subtitles?from=$(date -u -v-1H +%Y-%m-%dT%H:%M:%SZ)&to=$(date -u +%Y-%m-%dT%H:%M:%SZ)&format=vtt" \
+  --output /tmp/transcript.vtt
+
+# Same search over graphql, picking only the fields needed
+curl -X POST "http://localhost:3030/graphql" \
+  -H "Content-Type: application/json" \
+  -d '{"query": "{ searchFrames(query: \"roadmap\", limit: 5) { timestamp appName text } }"}' | jq
+
+# Slowest API requests of the last 24 hours
+curl "http://localhost:3030/slow-queries?limit=20" | jq
+
+# Check that desktop notifications (disk / cpu alerts) show up
+curl -X POST "http://localhost:3030/alerts/test" | jq
+
+# Capture settings in effect, and changing them without a restart
+curl "http://localhost:3030/config" | jq
+curl -X PATCH "http://localhost:3030/config" \
+  -H "Content-Type: application/json" \
+  -d '{"fps": 0.5, "dedup_threshold": 0.01, "ocr_engine": "tesseract"}' | jq
+curl -X PATCH "http://localhost:3030/config" \
+  -H "Content-Type: application/json" \
+  -d '{"rank_relevance_weight": 0.8, "rank_recency_weight": 0.2}' | jq
+
 # List all pipes
 curl "http://localhost:3030/pipes/list" | jq
 
@@ -1074,9 +3368,9 @@ curl "http://localhost:3030/search?limit=10&offset=0&min_length=500&content_type
 # Perform the search and store the response
 
 # First, let's search for some recent video content
-SEARCH_RESPONSE1=$(curl -s "http://localhost:3030/search?q=&limit=5&offset=0&content_type=ocr&start_time=$(date -u -v-30M +%Y-%m-%dT%H:%M:%SZ)&end_time=$(date -u -v-25M +%Y-%m-%dT%H:%M:%SZ)")
-SEARCH_RESPONSE2=$(curl -s "http://localhost:3030/search?q=&limit=5&offset=0&content_type=ocr&start_time=$(date -u -v-40M +%Y-%m-%dT%H:%M:%SZ)&end_time=$(date -u -v-35M +%Y-%m-%dT%H:%M:%SZ)")
-SEARCH_RESPONSE3=$(curl -s "http://localhost:3030/search?q=&limit=5&offset=0&content_type=ocr&start_time=$(date -u -v-50M +%Y-%m-%dT%H:%M:%SZ)&end_time=$(date -u -v-45M +%Y-%m-%dT%H:%M:%SZ)")
+SEARCH_RESPONSE1=$(curl -s "http://localhost:3030/search?q=&limit=5&offset=0&content_type=ocr&include_file_paths=true&start_time=$(date -u -v-30M +%Y-%m-%dT%H:%M:%SZ)&end_time=$(date -u -v-25M +%Y-%m-%dT%H:%M:%SZ)")
+SEARCH_RESPONSE2=$(curl -s "http://localhost:3030/search?q=&limit=5&offset=0&content_type=ocr&include_file_paths=true&start_time=$(date -u -v-40M +%Y-%m-%dT%H:%M:%SZ)&end_time=$(date -u -v-35M +%Y-%m-%dT%H:%M:%SZ)")
+SEARCH_RESPONSE3=$(curl -s "http://localhost:3030/search?q=&limit=5&offset=0&content_type=ocr&include_file_paths=true&start_time=$(date -u -v-50M +%Y-%m-%dT%H:%M:%SZ)&end_time=$(date -u -v-45M +%Y-%m-%dT%H:%M:%SZ)")
 
 # Extract the file paths from the search results without creating JSON arrays
 VIDEO_PATHS1=$(echo "$SEARCH_RESPONSE1" | jq -r '.data[].content.file_path' | sort -u)
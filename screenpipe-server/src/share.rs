@@ -0,0 +1,162 @@
+//! One-time links to a frame: `POST /share/frame/:id` makes a token, `GET /share/<token>` serves
+//! the frame and its ocr text to whoever has it, once, so a single result can be shared without
+//! opening up the rest of the api.
+
+use crate::db::ShareClaim;
+use crate::server::AppState;
+use crate::video_utils::extract_frame;
+use axum::extract::{Path, Query, State};
+use axum::http::StatusCode;
+use axum::Json;
+use chrono::{DateTime, Utc};
+use log::{error, info};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::sync::Arc;
+use std::time::Duration;
+
+pub const DEFAULT_SHARE_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// 32 random bytes in hex, unguessable and unrelated to the frame id.
+pub fn new_share_token() -> String {
+    let mut token = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut token);
+    hex::encode(token)
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct CreateShareQuery {
+    ttl_secs: Option<u64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ShareResponse {
+    pub token: String,
+    /// `/share/<token>`
+    pub url: String,
+    pub frame_id: i64,
+    pub expires_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SharedFrame {
+    pub frame_id: i64,
+    pub timestamp: DateTime<Utc>,
+    /// Ocr text of each window of the frame, one per line
+    pub text: String,
+    /// Base64 png
+    pub image: String,
+}
+
+type ApiError = (StatusCode, Json<Value>);
+
+fn internal_error(context: &str, e: impl std::fmt::Display) -> ApiError {
+    error!("{}: {}", context, e);
+    (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        Json(json!({"error": format!("{}: {}", context, e)})),
+    )
+}
+
+pub(crate) async fn create_share(
+    Path(frame_id): Path<i64>,
+    Query(query): Query<CreateShareQuery>,
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<ShareResponse>, ApiError> {
+    let ttl = match query.ttl_secs {
+        Some(0) => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(json!({"error": "ttl_secs must be above 0"})),
+            ))
+        }
+        Some(secs) => Duration::from_secs(secs),
+        None => DEFAULT_SHARE_TTL,
+    };
+    // chrono durations hold at most i64::MAX milliseconds
+    let ttl = chrono::Duration::seconds(ttl.as_secs().min(i64::MAX as u64 / 1000) as i64);
+    let expires_at = Utc::now()
+        .checked_add_signed(ttl)
+        .unwrap_or(DateTime::<Utc>::MAX_UTC);
+
+    let token = new_share_token();
+    let created = state
+        .db
+        .create_share(&token, frame_id, expires_at)
+        .await
+        .map_err(|e| internal_error("failed to create share", e))?;
+    if !created {
+        return Err((
+            StatusCode::NOT_FOUND,
+            Json(json!({"error": format!("frame {} not found", frame_id)})),
+        ));
+    }
+    info!("shared frame {} until {}", frame_id, expires_at);
+    Ok(Json(ShareResponse {
+        url: format!("/share/{}", token),
+        token,
+        frame_id,
+        expires_at,
+    }))
+}
+
+/// Used and expired links answer `410 Gone`, unknown ones `404`. The token is only used up
+/// once the frame was read, so a failed extraction leaves the link working.
+pub(crate) async fn get_share(
+    Path(token): Path<String>,
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<SharedFrame>, ApiError> {
+    // claiming is a write, the query_only pool would fail it after serving nothing
+    if state.db.is_read_only() {
+        return Err((
+            StatusCode::METHOD_NOT_ALLOWED,
+            Json(json!({"error": "this server is read-only, open share links on the main port"})),
+        ));
+    }
+    let gone = |error: &str| (StatusCode::GONE, Json(json!({"error": error})));
+    let not_found = || {
+        (
+            StatusCode::NOT_FOUND,
+            Json(json!({"error": "share not found"})),
+        )
+    };
+    let check = |share: Option<ShareClaim>| match share {
+        Some(ShareClaim::Frame(frame_id)) => Ok(frame_id),
+        Some(ShareClaim::Used) => Err(gone("share link was already used")),
+        Some(ShareClaim::Expired) => Err(gone("share link expired")),
+        None => Err(not_found()),
+    };
+    let frame_id = check(
+        state
+            .db
+            .find_share(&token, Utc::now())
+            .await
+            .map_err(|e| internal_error("failed to get share", e))?,
+    )?;
+
+    let frame = state
+        .db
+        .get_frame_text(frame_id)
+        .await
+        .map_err(|e| internal_error("failed to get shared frame", e))?
+        .ok_or_else(not_found)?;
+    let image = extract_frame(&frame.file_path, frame.offset_index)
+        .await
+        .map_err(|e| internal_error("failed to get shared frame image", e))?;
+    // only one of the requests racing for the token gets the frame
+    check(
+        state
+            .db
+            .claim_share(&token, Utc::now())
+            .await
+            .map_err(|e| internal_error("failed to get share", e))?,
+    )?;
+    info!("served share of frame {}", frame_id);
+    Ok(Json(SharedFrame {
+        frame_id,
+        timestamp: frame.timestamp,
+        text: frame.text,
+        image,
+    }))
+}
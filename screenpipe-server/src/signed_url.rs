@@ -0,0 +1,142 @@
+//! `GET /media/<token>`: frame images and audio files behind urls that expire, so search results
+//! can link to media without handing out where it is stored.
+
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use rand::RngCore;
+use sha2::Sha256;
+use std::fmt;
+use std::time::Duration;
+
+type HmacSha256 = Hmac<Sha256>;
+
+pub const DEFAULT_SIGNED_URL_TTL: Duration = Duration::from_secs(60 * 60);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MediaKind {
+    /// Full size png of a frame, by frame id
+    Frame,
+    /// File of an audio chunk, by audio chunk id
+    Audio,
+}
+
+impl MediaKind {
+    fn as_str(&self) -> &'static str {
+        match self {
+            MediaKind::Frame => "frame",
+            MediaKind::Audio => "audio",
+        }
+    }
+
+    fn parse(kind: &str) -> Option<Self> {
+        match kind {
+            "frame" => Some(MediaKind::Frame),
+            "audio" => Some(MediaKind::Audio),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MediaToken {
+    pub kind: MediaKind,
+    pub id: i64,
+    /// Unix timestamp in seconds
+    pub expires_at: i64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MediaTokenError {
+    Invalid,
+    Expired,
+}
+
+impl fmt::Display for MediaTokenError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MediaTokenError::Invalid => write!(f, "invalid media token"),
+            MediaTokenError::Expired => write!(f, "media url expired"),
+        }
+    }
+}
+
+impl std::error::Error for MediaTokenError {}
+
+/// Signs and checks media tokens `<kind>.<id>.<expires_at>.<hmac-sha256>`, the hmac covering the
+/// three other parts.
+pub struct MediaSigner {
+    key: Vec<u8>,
+    ttl: Duration,
+}
+
+impl MediaSigner {
+    pub fn new(key: impl Into<Vec<u8>>, ttl: Duration) -> Self {
+        MediaSigner {
+            key: key.into(),
+            ttl,
+        }
+    }
+
+    /// A signer with a key of its own, its urls stop working when screenpipe restarts.
+    pub fn random(ttl: Duration) -> Self {
+        let mut key = vec![0u8; 32];
+        rand::thread_rng().fill_bytes(&mut key);
+        Self::new(key, ttl)
+    }
+
+    pub fn ttl(&self) -> Duration {
+        self.ttl
+    }
+
+    fn mac(&self, kind: MediaKind, id: i64, expires_at: i64) -> HmacSha256 {
+        let mut mac = HmacSha256::new_from_slice(&self.key).expect("hmac takes keys of any size");
+        mac.update(format!("{}.{}.{}", kind.as_str(), id, expires_at).as_bytes());
+        mac
+    }
+
+    pub fn sign_until(&self, kind: MediaKind, id: i64, expires_at: DateTime<Utc>) -> String {
+        let expires_at = expires_at.timestamp();
+        let signature = hex::encode(self.mac(kind, id, expires_at).finalize().into_bytes());
+        format!("{}.{}.{}.{}", kind.as_str(), id, expires_at, signature)
+    }
+
+    /// `/media/<token>` valid for the ttl from now.
+    pub fn url(&self, kind: MediaKind, id: i64) -> String {
+        // chrono durations hold at most i64::MAX milliseconds
+        let ttl = chrono::Duration::seconds(self.ttl.as_secs().min(i64::MAX as u64 / 1000) as i64);
+        let expires_at = Utc::now()
+            .checked_add_signed(ttl)
+            .unwrap_or(DateTime::<Utc>::MAX_UTC);
+        format!("/media/{}", self.sign_until(kind, id, expires_at))
+    }
+
+    /// Checks the signature in constant time, then the expiry against `now`.
+    pub fn verify(&self, token: &str, now: DateTime<Utc>) -> Result<MediaToken, MediaTokenError> {
+        let mut parts = token.split('.');
+        let (Some(kind), Some(id), Some(expires_at), Some(signature), None) = (
+            parts.next(),
+            parts.next(),
+            parts.next(),
+            parts.next(),
+            parts.next(),
+        ) else {
+            return Err(MediaTokenError::Invalid);
+        };
+        let kind = MediaKind::parse(kind).ok_or(MediaTokenError::Invalid)?;
+        let id = id.parse().map_err(|_| MediaTokenError::Invalid)?;
+        let expires_at = expires_at.parse().map_err(|_| MediaTokenError::Invalid)?;
+        let signature = hex::decode(signature).map_err(|_| MediaTokenError::Invalid)?;
+        self.mac(kind, id, expires_at)
+            .verify_slice(&signature)
+            .map_err(|_| MediaTokenError::Invalid)?;
+
+        if now.timestamp() >= expires_at {
+            return Err(MediaTokenError::Expired);
+        }
+        Ok(MediaToken {
+            kind,
+            id,
+            expires_at,
+        })
+    }
+}
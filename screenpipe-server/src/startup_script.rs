@@ -0,0 +1,59 @@
+//! `--startup-script`: run once the database is migrated and the api accepts connections, so
+//! test harnesses and container orchestrators know when screenpipe is ready.
+
+use anyhow::Result;
+use log::{debug, info, warn};
+use std::path::{Path, PathBuf};
+use std::process::ExitStatus;
+use tokio::process::Command;
+
+fn script_command(script: &Path) -> Command {
+    // a shell script needs neither a shebang nor the executable bit
+    if script.extension().is_some_and(|ext| ext == "sh") {
+        let mut command = Command::new("sh");
+        command.arg(script);
+        command
+    } else {
+        Command::new(script)
+    }
+}
+
+/// Runs `script` with `SCREENPIPE_PORT` and `SCREENPIPE_DATA_DIR` set and returns its exit
+/// status, its output goes to the debug log.
+pub async fn run_startup_script(script: &Path, port: u16, data_dir: &Path) -> Result<ExitStatus> {
+    let output = script_command(script)
+        .env("SCREENPIPE_PORT", port.to_string())
+        .env("SCREENPIPE_DATA_DIR", data_dir)
+        .kill_on_drop(true)
+        .output()
+        .await?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    if !stdout.trim().is_empty() {
+        debug!("startup script stdout: {}", stdout.trim_end());
+    }
+    if !stderr.trim().is_empty() {
+        debug!("startup script stderr: {}", stderr.trim_end());
+    }
+    Ok(output.status)
+}
+
+/// Waits for the server to be ready then runs the script in the background. A failing script is
+/// only warned about, the server keeps running.
+pub fn spawn_startup_script(
+    script: PathBuf,
+    data_dir: PathBuf,
+    ready: tokio::sync::oneshot::Receiver<u16>,
+) {
+    tokio::spawn(async move {
+        let Ok(port) = ready.await else {
+            return;
+        };
+        info!("server ready, running startup script {}", script.display());
+        match run_startup_script(&script, port, &data_dir).await {
+            Ok(status) if status.success() => debug!("startup script finished"),
+            Ok(status) => warn!("startup script {} exited with {}", script.display(), status),
+            Err(e) => warn!("failed to run startup script {}: {}", script.display(), e),
+        }
+    });
+}
@@ -0,0 +1,213 @@
+use crate::db::ContentAggregates;
+use crate::resource_monitor::MEMORY_USAGE_BYTES;
+use crate::DatabaseManager;
+use chrono::{DateTime, Utc};
+use log::{debug, error};
+use screenpipe_core::StoragePaths;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::RwLock;
+
+const STATS_REFRESH_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Running average of the time between a frame being captured and it being stored in the db.
+pub struct CaptureLatency {
+    total_micros: AtomicU64,
+    count: AtomicU64,
+}
+
+impl CaptureLatency {
+    pub const fn new() -> Self {
+        CaptureLatency {
+            total_micros: AtomicU64::new(0),
+            count: AtomicU64::new(0),
+        }
+    }
+
+    pub fn record(&self, latency: Duration) {
+        self.total_micros
+            .fetch_add(latency.as_micros() as u64, Ordering::Relaxed);
+        self.count.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn average_ms(&self) -> Option<f64> {
+        let count = self.count.load(Ordering::Relaxed);
+        if count == 0 {
+            return None;
+        }
+        let total = self.total_micros.load(Ordering::Relaxed);
+        Some(total as f64 / count as f64 / 1000.0)
+    }
+}
+
+pub static CAPTURE_LATENCY: CaptureLatency = CaptureLatency::new();
+
+/// Frames dropped because their focused window matched an `--ignore-window` pattern.
+pub static DISCARDED_FRAMES: AtomicU64 = AtomicU64::new(0);
+
+/// Frames dropped because their encoded png was over `--max-frame-size-kb`.
+pub static OVERSIZED_FRAMES: AtomicU64 = AtomicU64::new(0);
+
+/// Frames stored without ocr because of `--ocr-skip-frames`.
+pub static OCR_SKIPPED_FRAMES: AtomicU64 = AtomicU64::new(0);
+
+/// Time `--ocr-preprocess-*` steps take per frame, all its windows together.
+pub static OCR_UPSCALE_LATENCY: CaptureLatency = CaptureLatency::new();
+pub static OCR_BINARIZE_LATENCY: CaptureLatency = CaptureLatency::new();
+pub static OCR_DESKEW_LATENCY: CaptureLatency = CaptureLatency::new();
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RecordingStats {
+    pub total_frames: i64,
+    pub total_audio_chunks: i64,
+    pub total_audio_duration_secs: f64,
+    pub total_ocr_entries: i64,
+    pub total_ocr_text_chars: i64,
+    pub database_size_bytes: u64,
+    pub media_size_bytes: u64,
+    pub average_capture_latency_ms: Option<f64>,
+    pub discarded_frames: u64,
+    #[serde(default)]
+    pub oversized_frames: u64,
+    #[serde(default)]
+    pub ocr_skipped_frames: u64,
+    /// `None` while the step is turned off
+    #[serde(default)]
+    pub average_ocr_upscale_ms: Option<f64>,
+    #[serde(default)]
+    pub average_ocr_binarize_ms: Option<f64>,
+    #[serde(default)]
+    pub average_ocr_deskew_ms: Option<f64>,
+    /// Resident memory of screenpipe and its child processes
+    #[serde(default)]
+    pub memory_usage_bytes: u64,
+    pub last_updated: DateTime<Utc>,
+}
+
+/// Caches the expensive aggregates behind `GET /stats`, refreshed by `start_refreshing`.
+pub struct StatsCache {
+    db: Arc<DatabaseManager>,
+    storage: StoragePaths,
+    audio_chunk_duration: Duration,
+    cached: RwLock<Option<RecordingStats>>,
+}
+
+impl StatsCache {
+    pub fn new(
+        db: Arc<DatabaseManager>,
+        storage: StoragePaths,
+        audio_chunk_duration: Duration,
+    ) -> Self {
+        StatsCache {
+            db,
+            storage,
+            audio_chunk_duration,
+            cached: RwLock::new(None),
+        }
+    }
+
+    pub fn audio_chunk_duration(&self) -> Duration {
+        self.audio_chunk_duration
+    }
+
+    pub fn start_refreshing(self: &Arc<Self>) {
+        let cache = Arc::clone(self);
+        tokio::spawn(async move {
+            loop {
+                if let Err(e) = cache.refresh().await {
+                    error!("failed to refresh stats: {}", e);
+                }
+                tokio::time::sleep(STATS_REFRESH_INTERVAL).await;
+            }
+        });
+    }
+
+    pub async fn refresh(&self) -> Result<RecordingStats, sqlx::Error> {
+        let ContentAggregates {
+            total_frames,
+            total_audio_chunks,
+            total_ocr_entries,
+            total_ocr_text_chars,
+        } = self.db.get_content_aggregates().await?;
+
+        let storage = self.storage.clone();
+        let (database_size_bytes, media_size_bytes) = tokio::task::spawn_blocking(move || {
+            let db_size = ["db.sqlite", "db.sqlite-wal", "db.sqlite-shm"]
+                .iter()
+                .filter_map(|name| std::fs::metadata(storage.base_dir.join(name)).ok())
+                .map(|m| m.len())
+                .sum::<u64>();
+            let media_size = storage
+                .media_dirs()
+                .into_iter()
+                .map(|dir| dir_size(dir.as_path()))
+                .sum();
+            (db_size, media_size)
+        })
+        .await
+        .unwrap_or((0, 0));
+
+        let stats = RecordingStats {
+            total_frames,
+            total_audio_chunks,
+            total_audio_duration_secs: total_audio_chunks as f64
+                * self.audio_chunk_duration.as_secs_f64(),
+            total_ocr_entries,
+            total_ocr_text_chars,
+            database_size_bytes,
+            media_size_bytes,
+            average_capture_latency_ms: CAPTURE_LATENCY.average_ms(),
+            discarded_frames: DISCARDED_FRAMES.load(Ordering::Relaxed),
+            oversized_frames: OVERSIZED_FRAMES.load(Ordering::Relaxed),
+            ocr_skipped_frames: OCR_SKIPPED_FRAMES.load(Ordering::Relaxed),
+            average_ocr_upscale_ms: OCR_UPSCALE_LATENCY.average_ms(),
+            average_ocr_binarize_ms: OCR_BINARIZE_LATENCY.average_ms(),
+            average_ocr_deskew_ms: OCR_DESKEW_LATENCY.average_ms(),
+            memory_usage_bytes: MEMORY_USAGE_BYTES.load(Ordering::Relaxed),
+            last_updated: Utc::now(),
+        };
+        debug!("refreshed stats: {:?}", stats);
+
+        *self.cached.write().await = Some(stats.clone());
+        Ok(stats)
+    }
+
+    /// Returns the cached stats, computing them once if the background task has not run yet.
+    pub async fn get(&self) -> Result<RecordingStats, sqlx::Error> {
+        if let Some(stats) = self.cached.read().await.as_ref() {
+            let mut stats = stats.clone();
+            // cheap to read, so always report the live values
+            stats.average_capture_latency_ms = CAPTURE_LATENCY.average_ms();
+            stats.discarded_frames = DISCARDED_FRAMES.load(Ordering::Relaxed);
+            stats.oversized_frames = OVERSIZED_FRAMES.load(Ordering::Relaxed);
+            stats.ocr_skipped_frames = OCR_SKIPPED_FRAMES.load(Ordering::Relaxed);
+            stats.average_ocr_upscale_ms = OCR_UPSCALE_LATENCY.average_ms();
+            stats.average_ocr_binarize_ms = OCR_BINARIZE_LATENCY.average_ms();
+            stats.average_ocr_deskew_ms = OCR_DESKEW_LATENCY.average_ms();
+            stats.memory_usage_bytes = MEMORY_USAGE_BYTES.load(Ordering::Relaxed);
+            return Ok(stats);
+        }
+        self.refresh().await
+    }
+}
+
+fn dir_size(path: &Path) -> u64 {
+    let entries = match std::fs::read_dir(path) {
+        Ok(entries) => entries,
+        Err(_) => return 0,
+    };
+    entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let metadata = entry.metadata().ok()?;
+            if metadata.is_dir() {
+                Some(dir_size(&entry.path()))
+            } else {
+                Some(metadata.len())
+            }
+        })
+        .sum()
+}
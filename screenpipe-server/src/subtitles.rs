@@ -0,0 +1,143 @@
+use chrono::{DateTime, Utc};
+use screenpipe_audio::TranscriptionSegment;
+use serde::Deserialize;
+use std::time::Duration;
+
+/// Segments are grouped into one cue until it would run longer or hold more text than this.
+const MAX_CUE_MS: u64 = 5000;
+const MAX_CUE_CHARS: usize = 84;
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SubtitleFormat {
+    Srt,
+    Vtt,
+}
+
+impl SubtitleFormat {
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            SubtitleFormat::Srt => "application/x-subrip",
+            SubtitleFormat::Vtt => "text/vtt",
+        }
+    }
+}
+
+/// A transcribed audio chunk, `word_timestamps` are relative to `start`.
+#[derive(Debug, Clone)]
+pub struct AudioTranscript {
+    pub start: DateTime<Utc>,
+    pub transcription: String,
+    pub word_timestamps: Vec<TranscriptionSegment>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cue {
+    /// Milliseconds since the start of the export
+    pub start_ms: u64,
+    pub end_ms: u64,
+    pub text: String,
+}
+
+/// Cues of `transcripts` relative to `origin`. A chunk without word timestamps becomes one cue
+/// of `chunk_duration`. Cues that overlap, e.g. input and output devices transcribed at the same
+/// time, are merged into one with a line per speaker.
+pub fn build_cues(
+    origin: DateTime<Utc>,
+    transcripts: &[AudioTranscript],
+    chunk_duration: Duration,
+) -> Vec<Cue> {
+    let mut cues = Vec::new();
+    for transcript in transcripts {
+        let offset_ms = (transcript.start - origin).num_milliseconds().max(0) as u64;
+        if transcript.word_timestamps.is_empty() {
+            let text = transcript.transcription.trim();
+            if !text.is_empty() {
+                cues.push(Cue {
+                    start_ms: offset_ms,
+                    end_ms: offset_ms + chunk_duration.as_millis() as u64,
+                    text: text.to_string(),
+                });
+            }
+            continue;
+        }
+
+        let mut current: Option<Cue> = None;
+        for segment in &transcript.word_timestamps {
+            let text = segment.text.trim();
+            if text.is_empty() {
+                continue;
+            }
+            let start_ms = offset_ms + segment.start_ms;
+            let end_ms = offset_ms + segment.end_ms.max(segment.start_ms);
+            match &mut current {
+                Some(cue)
+                    if end_ms - cue.start_ms <= MAX_CUE_MS
+                        && cue.text.len() + 1 + text.len() <= MAX_CUE_CHARS =>
+                {
+                    cue.text.push(' ');
+                    cue.text.push_str(text);
+                    cue.end_ms = cue.end_ms.max(end_ms);
+                }
+                _ => {
+                    cues.extend(current.take());
+                    current = Some(Cue {
+                        start_ms,
+                        end_ms,
+                        text: text.to_string(),
+                    });
+                }
+            }
+        }
+        cues.extend(current);
+    }
+
+    cues.sort_by_key(|cue| cue.start_ms);
+    let mut merged: Vec<Cue> = Vec::with_capacity(cues.len());
+    for cue in cues {
+        match merged.last_mut() {
+            Some(last) if cue.start_ms < last.end_ms => {
+                last.end_ms = last.end_ms.max(cue.end_ms);
+                last.text.push('\n');
+                last.text.push_str(&cue.text);
+            }
+            _ => merged.push(cue),
+        }
+    }
+    merged
+}
+
+fn format_timestamp(ms: u64, separator: char) -> String {
+    format!(
+        "{:02}:{:02}:{:02}{}{:03}",
+        ms / 3_600_000,
+        ms / 60_000 % 60,
+        ms / 1000 % 60,
+        separator,
+        ms % 1000
+    )
+}
+
+pub fn render_subtitles(cues: &[Cue], format: SubtitleFormat) -> String {
+    let mut output = String::new();
+    if format == SubtitleFormat::Vtt {
+        output.push_str("WEBVTT\n\n");
+    }
+    for (i, cue) in cues.iter().enumerate() {
+        let (start, end) = match format {
+            SubtitleFormat::Srt => {
+                output.push_str(&format!("{}\n", i + 1));
+                (
+                    format_timestamp(cue.start_ms, ','),
+                    format_timestamp(cue.end_ms, ','),
+                )
+            }
+            SubtitleFormat::Vtt => (
+                format_timestamp(cue.start_ms, '.'),
+                format_timestamp(cue.end_ms, '.'),
+            ),
+        };
+        output.push_str(&format!("{} --> {}\n{}\n\n", start, end, cue.text));
+    }
+    output
+}
@@ -0,0 +1,212 @@
+use crate::db::AppScreenTime;
+use crate::DatabaseManager;
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Local, NaiveDate, NaiveTime, TimeZone, Utc};
+use futures::TryStreamExt;
+use log::{error, info};
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Longest a focused frame is counted for, longer gaps are idle time or a paused recorder.
+const MAX_FRAME_GAP: Duration = Duration::from_secs(60);
+
+const TOP_APPS: u32 = 10;
+
+const TOP_TERMS: usize = 20;
+
+const MIN_TERM_LEN: usize = 4;
+
+const STOPWORDS: &[&str] = &[
+    "about", "above", "after", "again", "also", "because", "been", "before", "being", "below",
+    "between", "both", "could", "does", "doing", "down", "during", "each", "from", "further",
+    "have", "having", "here", "into", "just", "more", "most", "only", "other", "over", "same",
+    "should", "some", "such", "than", "that", "their", "them", "then", "there", "these", "they",
+    "this", "those", "through", "under", "until", "very", "were", "what", "when", "where", "which",
+    "while", "will", "with", "would", "your", "yours",
+];
+
+/// Activity of one local day.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DailySummary {
+    pub date: NaiveDate,
+    pub frame_count: i64,
+    pub top_apps: Vec<AppScreenTime>,
+    /// Most frequent terms of the ocr text with the number of texts they appear in
+    pub top_terms: Vec<(String, usize)>,
+}
+
+pub fn summaries_dir(screenpipe_dir: &Path) -> PathBuf {
+    screenpipe_dir.join("summaries")
+}
+
+pub fn summary_path(screenpipe_dir: &Path, date: NaiveDate) -> PathBuf {
+    summaries_dir(screenpipe_dir).join(format!("{}.md", date.format("%Y-%m-%d")))
+}
+
+/// Lowercased words of `text` worth reporting: no stopwords, numbers or short words.
+fn terms(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| word.chars().count() >= MIN_TERM_LEN)
+        .filter(|word| !word.chars().all(|c| c.is_numeric()))
+        .map(|word| word.to_lowercase())
+        .filter(|word| !STOPWORDS.contains(&word.as_str()))
+}
+
+/// Number of texts each term appears in, each text counting a term once so a word repeated on
+/// one screen does not outweigh one seen all day.
+#[derive(Debug, Default)]
+pub struct TermCounts(HashMap<String, usize>);
+
+impl TermCounts {
+    pub fn add(&mut self, text: &str) {
+        for term in terms(text).collect::<HashSet<_>>() {
+            *self.0.entry(term).or_default() += 1;
+        }
+    }
+
+    /// The `limit` terms in the most texts, ties by name.
+    pub fn top(self, limit: usize) -> Vec<(String, usize)> {
+        let mut counts: Vec<_> = self.0.into_iter().collect();
+        counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        counts.truncate(limit);
+        counts
+    }
+}
+
+/// Terms in the most texts, see [`TermCounts`].
+pub fn top_terms<'a>(
+    texts: impl IntoIterator<Item = &'a str>,
+    limit: usize,
+) -> Vec<(String, usize)> {
+    let mut counts = TermCounts::default();
+    for text in texts {
+        counts.add(text);
+    }
+    counts.top(limit)
+}
+
+fn local_midnight(date: NaiveDate) -> Result<DateTime<Utc>> {
+    Local
+        .from_local_datetime(&date.and_time(NaiveTime::MIN))
+        .earliest()
+        .map(|midnight| midnight.with_timezone(&Utc))
+        .ok_or_else(|| anyhow!("no local midnight on {}", date))
+}
+
+pub async fn summarize_day(db: &DatabaseManager, date: NaiveDate) -> Result<DailySummary> {
+    let start = local_midnight(date)?;
+    let next_day = date
+        .succ_opt()
+        .ok_or_else(|| anyhow!("date out of range"))?;
+    let end = local_midnight(next_day)?;
+
+    let frame_count = db.count_frames(start, end).await?;
+    let top_apps = db
+        .get_app_screen_time(start, end, MAX_FRAME_GAP, TOP_APPS)
+        .await?;
+
+    // a day of text is folded into the counts as it is read instead of held in memory
+    let term_counts = db
+        .stream_ocr_texts(start, end)
+        .try_fold(TermCounts::default(), |mut counts, text| async move {
+            counts.add(&text);
+            Ok(counts)
+        })
+        .await?;
+
+    Ok(DailySummary {
+        date,
+        frame_count,
+        top_apps,
+        top_terms: term_counts.top(TOP_TERMS),
+    })
+}
+
+fn format_duration(seconds: f64) -> String {
+    let minutes = (seconds / 60.0).round() as u64;
+    if minutes >= 60 {
+        format!("{}h {:02}m", minutes / 60, minutes % 60)
+    } else {
+        format!("{}m", minutes)
+    }
+}
+
+pub fn render_markdown(summary: &DailySummary) -> String {
+    let mut markdown = format!("# Activity on {}\n\n", summary.date.format("%Y-%m-%d"));
+    let screen_time: f64 = summary.top_apps.iter().map(|app| app.seconds).sum();
+    let _ = writeln!(
+        markdown,
+        "{} frames recorded, {} of screen time in the top apps.\n",
+        summary.frame_count,
+        format_duration(screen_time)
+    );
+
+    markdown.push_str("## Top apps\n\n");
+    if summary.top_apps.is_empty() {
+        markdown.push_str("No app activity recorded.\n");
+    } else {
+        markdown.push_str("| App | Screen time |\n| --- | --- |\n");
+        for app in &summary.top_apps {
+            let _ = writeln!(
+                markdown,
+                "| {} | {} |",
+                app.app_name.replace('|', "\\|"),
+                format_duration(app.seconds)
+            );
+        }
+    }
+
+    markdown.push_str("\n## Notable terms\n\n");
+    if summary.top_terms.is_empty() {
+        markdown.push_str("No text recorded.\n");
+    } else {
+        for (term, count) in &summary.top_terms {
+            let _ = writeln!(markdown, "- {} ({})", term, count);
+        }
+    }
+    markdown
+}
+
+/// Summarizes `date` and writes it to `summaries/YYYY-MM-DD.md`, replacing an earlier one.
+pub async fn write_daily_summary(
+    db: &DatabaseManager,
+    screenpipe_dir: &Path,
+    date: NaiveDate,
+) -> Result<PathBuf> {
+    let summary = summarize_day(db, date).await?;
+    let path = summary_path(screenpipe_dir, date);
+    tokio::fs::create_dir_all(summaries_dir(screenpipe_dir)).await?;
+    tokio::fs::write(&path, render_markdown(&summary)).await?;
+    Ok(path)
+}
+
+/// Next local `time` strictly after `now`.
+fn next_run(now: DateTime<Local>, time: NaiveTime) -> DateTime<Local> {
+    let mut date = now.date_naive();
+    loop {
+        if let Some(run) = Local.from_local_datetime(&date.and_time(time)).earliest() {
+            if run > now {
+                return run;
+            }
+        }
+        date = date.succ_opt().expect("date out of range");
+    }
+}
+
+/// Writes the summary of the current day every day at local `time`.
+pub fn start_daily_summaries(db: Arc<DatabaseManager>, screenpipe_dir: PathBuf, time: NaiveTime) {
+    tokio::spawn(async move {
+        loop {
+            let run = next_run(Local::now(), time);
+            let wait = (run - Local::now()).to_std().unwrap_or_default();
+            tokio::time::sleep(wait).await;
+            match write_daily_summary(&db, &screenpipe_dir, run.date_naive()).await {
+                Ok(path) => info!("wrote daily summary to {}", path.display()),
+                Err(e) => error!("daily summary failed: {}", e),
+            }
+        }
+    });
+}
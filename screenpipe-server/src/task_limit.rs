@@ -0,0 +1,102 @@
+use std::future::Future;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+/// Caps the background work of the recorder: ocr, frame writes and transcription inserts share
+/// `--max-concurrent-tasks` slots. Spawned work waits for a slot behind up to
+/// `--task-queue-depth` others, beyond that it is dropped rather than piling up.
+pub struct TaskLimiter {
+    permits: Arc<Semaphore>,
+    queued: Arc<AtomicUsize>,
+    max_concurrent: usize,
+    queue_depth: usize,
+}
+
+impl TaskLimiter {
+    pub fn new(max_concurrent: usize, queue_depth: usize) -> Self {
+        let max_concurrent = max_concurrent.max(1);
+        TaskLimiter {
+            permits: Arc::new(Semaphore::new(max_concurrent)),
+            queued: Arc::new(AtomicUsize::new(0)),
+            max_concurrent,
+            queue_depth,
+        }
+    }
+
+    /// Spawns `task` once a slot is free, `false` when the queue is full and `task` was dropped.
+    pub fn spawn<F>(&self, task: F) -> bool
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        if let Ok(permit) = Arc::clone(&self.permits).try_acquire_owned() {
+            tokio::spawn(async move {
+                task.await;
+                drop(permit);
+            });
+            return true;
+        }
+        let reserved = self
+            .queued
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |queued| {
+                (queued < self.queue_depth).then_some(queued + 1)
+            });
+        if reserved.is_err() {
+            return false;
+        }
+        let permits = Arc::clone(&self.permits);
+        let queued = Arc::clone(&self.queued);
+        tokio::spawn(async move {
+            let permit = permits.acquire_owned().await;
+            queued.fetch_sub(1, Ordering::SeqCst);
+            // the semaphore is never closed
+            if let Ok(permit) = permit {
+                task.await;
+                drop(permit);
+            }
+        });
+        true
+    }
+
+    /// A slot for work that can't be dropped, e.g. the frame writes of a monitor, which wait
+    /// for it instead.
+    pub async fn acquire(&self) -> OwnedSemaphorePermit {
+        Arc::clone(&self.permits)
+            .acquire_owned()
+            .await
+            .expect("the semaphore is never closed")
+    }
+
+    /// The slots themselves, for the ocr workers of the vision crate.
+    pub fn permits(&self) -> Arc<Semaphore> {
+        Arc::clone(&self.permits)
+    }
+
+    /// Waits for the running and queued tasks to finish, e.g. on shutdown.
+    pub async fn drain(&self) {
+        loop {
+            // the semaphore is fair, tasks already waiting for a slot get theirs first
+            let all = self
+                .permits
+                .acquire_many(self.max_concurrent as u32)
+                .await
+                .expect("the semaphore is never closed");
+            if self.queued() == 0 {
+                return;
+            }
+            // queued tasks that were not waiting on the semaphore yet
+            drop(all);
+            tokio::task::yield_now().await;
+        }
+    }
+
+    /// Tasks waiting for a slot.
+    pub fn queued(&self) -> usize {
+        self.queued.load(Ordering::SeqCst)
+    }
+
+    /// Tasks running right now.
+    pub fn running(&self) -> usize {
+        self.max_concurrent - self.permits.available_permits()
+    }
+}
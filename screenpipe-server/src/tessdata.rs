@@ -0,0 +1,223 @@
+//! `--ocr-hint-language`: language packs tesseract is missing are downloaded on startup from the
+//! official tessdata repository into `~/.screenpipe/tessdata`, which tesseract then reads from.
+//! The tesseract processes only find it through `TESSDATA_PREFIX`, which `main` sets after
+//! [`TesseractLangManager::prepare`] and before the runtime starts any thread.
+
+use anyhow::{anyhow, bail, Result};
+use log::{debug, info, warn};
+use serde::Deserialize;
+use sha1::{Digest, Sha1};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Release of github.com/tesseract-ocr/tessdata the packs are taken from, its models work with
+/// tesseract 4 and 5.
+pub const TESSDATA_RELEASE: &str = "4.1.0";
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(300);
+
+const TRAINEDDATA: &str = "traineddata";
+
+#[derive(Deserialize)]
+struct GithubContent {
+    sha: String,
+}
+
+/// Whether `lang` is a tessdata pack name like `eng` or `chi_sim`, anything else would end up in
+/// a path and a url.
+pub fn is_lang_code(lang: &str) -> bool {
+    !lang.is_empty() && lang.chars().all(|c| c.is_ascii_lowercase() || c == '_')
+}
+
+/// Git blob id of `data`, what the GitHub contents api reports as `sha` for a file.
+pub fn git_blob_sha1(data: &[u8]) -> String {
+    let mut hasher = Sha1::new();
+    hasher.update(format!("blob {}\0", data.len()).as_bytes());
+    hasher.update(data);
+    hex::encode(hasher.finalize())
+}
+
+/// Directory in the first line of `tesseract --list-langs`,
+/// `List of available languages in "/usr/share/tesseract-ocr/5/tessdata/" (3):`.
+pub fn parse_tessdata_dir(list_langs: &str) -> Option<PathBuf> {
+    let line = list_langs.lines().next()?;
+    let start = line.find('"')? + 1;
+    let len = line[start..].find('"')?;
+    Some(PathBuf::from(&line[start..start + len]))
+}
+
+/// Languages with a `.traineddata` file in `dir`.
+pub fn installed_langs(dir: &Path) -> Vec<String> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut langs: Vec<_> = entries
+        .filter_map(|entry| {
+            let path = entry.ok()?.path();
+            if path.extension()? != TRAINEDDATA {
+                return None;
+            }
+            Some(path.file_stem()?.to_string_lossy().into_owned())
+        })
+        .collect();
+    langs.sort();
+    langs
+}
+
+/// Directory tesseract reads its packs from, `TESSDATA_PREFIX` when set.
+fn system_tessdata_dir() -> Option<PathBuf> {
+    if let Some(prefix) = std::env::var_os("TESSDATA_PREFIX") {
+        return Some(PathBuf::from(prefix));
+    }
+    let output = std::process::Command::new("tesseract")
+        .arg("--list-langs")
+        .output()
+        .ok()?;
+    // older versions print the list on stderr
+    parse_tessdata_dir(&String::from_utf8_lossy(&output.stdout))
+        .or_else(|| parse_tessdata_dir(&String::from_utf8_lossy(&output.stderr)))
+}
+
+/// Makes `entry` of the system tessdata dir visible in ours, so packs installed there keep working
+/// once `TESSDATA_PREFIX` points here.
+fn link_entry(entry: &Path, target: &Path) -> std::io::Result<()> {
+    #[cfg(unix)]
+    {
+        std::os::unix::fs::symlink(entry, target)
+    }
+    #[cfg(not(unix))]
+    {
+        if entry.is_dir() {
+            return Ok(());
+        }
+        std::fs::copy(entry, target).map(|_| ())
+    }
+}
+
+pub struct TesseractLangManager {
+    dir: PathBuf,
+    client: reqwest::Client,
+}
+
+impl TesseractLangManager {
+    /// `dir` is where downloaded packs go, `~/.screenpipe/tessdata` by default.
+    pub fn new(dir: PathBuf) -> Result<Self> {
+        let client = reqwest::Client::builder()
+            .timeout(REQUEST_TIMEOUT)
+            .user_agent("screenpipe")
+            .build()?;
+        Ok(TesseractLangManager { dir, client })
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// When tesseract is missing a pack of `langs`, links the packs it has into our directory and
+    /// returns true, `TESSDATA_PREFIX` then has to point at [`Self::dir`] for the packs
+    /// [`Self::ensure_langs`] downloads to be read.
+    pub fn prepare(&self, langs: &[String]) -> Result<bool> {
+        let system_dir = system_tessdata_dir();
+        let system_langs = system_dir
+            .as_deref()
+            .map(installed_langs)
+            .unwrap_or_default();
+        if langs.iter().all(|lang| system_langs.contains(lang)) {
+            debug!("tesseract has every hinted language: {}", langs.join(", "));
+            return Ok(false);
+        }
+
+        std::fs::create_dir_all(&self.dir)?;
+        if let Some(system_dir) = system_dir.filter(|dir| *dir != self.dir) {
+            self.link_system_dir(&system_dir)?;
+        }
+        Ok(true)
+    }
+
+    /// Downloads the packs of `langs` our directory doesn't have. A pack that fails to download is
+    /// skipped with a warning.
+    pub async fn ensure_langs(&self, langs: &[String]) {
+        info!("tesseract reads languages from {}", self.dir.display());
+        let local_langs = installed_langs(&self.dir);
+        for lang in langs {
+            if local_langs.contains(lang) {
+                continue;
+            }
+            if let Err(e) = self.download(lang).await {
+                warn!("failed to download tesseract language {}: {}", lang, e);
+            }
+        }
+    }
+
+    fn link_system_dir(&self, system_dir: &Path) -> Result<()> {
+        for entry in std::fs::read_dir(system_dir)? {
+            let entry = entry?.path();
+            let Some(name) = entry.file_name() else {
+                continue;
+            };
+            let target = self.dir.join(name);
+            if target.symlink_metadata().is_err() {
+                link_entry(&entry, &target)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Fetches `{lang}.traineddata` and checks it against the blob id GitHub lists for it before
+    /// it is moved into place.
+    async fn download(&self, lang: &str) -> Result<()> {
+        if !is_lang_code(lang) {
+            bail!("'{}' is not a tessdata language code", lang);
+        }
+        let file_name = format!("{}.{}", lang, TRAINEDDATA);
+        let expected = self
+            .client
+            .get(format!(
+                "https://api.github.com/repos/tesseract-ocr/tessdata/contents/{}?ref={}",
+                file_name, TESSDATA_RELEASE
+            ))
+            .send()
+            .await?
+            .error_for_status()
+            .map_err(|e| anyhow!("no {} in tessdata {}: {}", lang, TESSDATA_RELEASE, e))?
+            .json::<GithubContent>()
+            .await?
+            .sha;
+
+        let url = format!(
+            "https://github.com/tesseract-ocr/tessdata/raw/{}/{}",
+            TESSDATA_RELEASE, file_name
+        );
+        info!("downloading tesseract language {} from {}", lang, url);
+        let mut response = self.client.get(&url).send().await?.error_for_status()?;
+        let total = response.content_length();
+        let mut data = Vec::with_capacity(total.unwrap_or(0) as usize);
+        let mut logged_percent = 0;
+        while let Some(chunk) = response.chunk().await? {
+            data.extend_from_slice(&chunk);
+            if let Some(total) = total.filter(|total| *total > 0) {
+                let percent = data.len() as u64 * 100 / total;
+                if percent >= logged_percent + 10 {
+                    logged_percent = percent - percent % 10;
+                    info!(
+                        "tesseract language {}: {}% of {} MB",
+                        lang,
+                        logged_percent,
+                        total / 1_000_000
+                    );
+                }
+            }
+        }
+
+        let actual = git_blob_sha1(&data);
+        if actual != expected {
+            bail!("checksum mismatch, expected {} got {}", expected, actual);
+        }
+        info!("tesseract language {} verified, sha1 {}", lang, actual);
+
+        let part = self.dir.join(format!("{}.part", file_name));
+        std::fs::write(&part, &data)?;
+        std::fs::rename(&part, self.dir.join(&file_name))?;
+        Ok(())
+    }
+}
@@ -0,0 +1,130 @@
+use crate::request_id::spawn_in_current_span;
+use crate::video_utils::extract_frame_png;
+use crate::DatabaseManager;
+use anyhow::Result;
+use image::{codecs::jpeg::JpegEncoder, DynamicImage};
+use log::{debug, error, info};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+pub const THUMBNAIL_WIDTH: u32 = 200;
+pub const THUMBNAIL_HEIGHT: u32 = 150;
+const THUMBNAIL_JPEG_QUALITY: u8 = 80;
+const GENERATION_BATCH_SIZE: u32 = 100;
+
+static GENERATION_RUNNING: AtomicBool = AtomicBool::new(false);
+
+pub fn thumbnails_dir(data_dir: &Path) -> PathBuf {
+    data_dir.join("thumbnails")
+}
+
+/// Where thumbnails were written before they were content addressed, one file per frame.
+pub fn thumbnail_path(thumbnails_dir: &Path, frame_id: i64) -> PathBuf {
+    thumbnails_dir.join(format!("{}.jpg", frame_id))
+}
+
+pub fn encode_thumbnail(image: &DynamicImage) -> Result<Vec<u8>> {
+    let thumbnail = image
+        .thumbnail_exact(THUMBNAIL_WIDTH, THUMBNAIL_HEIGHT)
+        .to_rgb8();
+    let mut jpeg = Vec::new();
+    JpegEncoder::new_with_quality(&mut jpeg, THUMBNAIL_JPEG_QUALITY).encode_image(&thumbnail)?;
+    Ok(jpeg)
+}
+
+/// Stores `jpeg` as the thumbnail of `frame_ids` and returns its path. Bit-identical thumbnails
+/// (a static screen) are written once, later frames only point at the existing file by md5.
+pub async fn store_thumbnail(
+    db: &DatabaseManager,
+    thumbnails_dir: &Path,
+    frame_ids: &[i64],
+    jpeg: &[u8],
+) -> Result<PathBuf> {
+    let md5 = format!("{:x}", md5::compute(jpeg));
+    let path = match db.get_frame_hash_path(&md5).await? {
+        Some(path) if Path::new(&path).exists() => PathBuf::from(path),
+        _ => {
+            let path = thumbnails_dir.join(format!("{}.jpg", md5));
+            tokio::fs::create_dir_all(thumbnails_dir).await?;
+            tokio::fs::write(&path, jpeg).await?;
+            db.insert_frame_hash(&md5, &path.to_string_lossy()).await?;
+            path
+        }
+    };
+    db.set_frame_thumbnail_hash(frame_ids, &md5).await?;
+    Ok(path)
+}
+
+/// Path of the thumbnail of a frame, `None` when it has not been generated (or was removed).
+pub async fn find_thumbnail(
+    db: &DatabaseManager,
+    thumbnails_dir: &Path,
+    frame_id: i64,
+) -> Result<Option<PathBuf>> {
+    let path = match db.get_frame_thumbnail_path(frame_id).await? {
+        Some(path) => PathBuf::from(path),
+        None => thumbnail_path(thumbnails_dir, frame_id),
+    };
+    Ok(path.exists().then_some(path))
+}
+
+/// Starts a background job creating thumbnails for frames that don't have one yet.
+/// Returns false if a job is already running.
+pub fn spawn_thumbnail_generation(db: Arc<DatabaseManager>, thumbnails_dir: PathBuf) -> bool {
+    if GENERATION_RUNNING.swap(true, Ordering::SeqCst) {
+        return false;
+    }
+    spawn_in_current_span(async move {
+        match generate_missing_thumbnails(&db, &thumbnails_dir).await {
+            Ok(count) => info!("generated {} missing thumbnails", count),
+            Err(e) => error!("thumbnail generation failed: {}", e),
+        }
+        GENERATION_RUNNING.store(false, Ordering::SeqCst);
+    });
+    true
+}
+
+async fn generate_missing_thumbnails(db: &DatabaseManager, thumbnails_dir: &Path) -> Result<usize> {
+    let mut offset = 0;
+    let mut generated = 0;
+    loop {
+        let frames = db
+            .get_frames(None, None, GENERATION_BATCH_SIZE, offset)
+            .await?;
+        if frames.is_empty() {
+            break;
+        }
+        offset += frames.len() as u32;
+
+        for frame in frames {
+            let frame_id = frame.frame_id;
+            if find_thumbnail(db, thumbnails_dir, frame_id)
+                .await?
+                .is_some()
+            {
+                continue;
+            }
+            let png = match extract_frame_png(&frame.file_path, frame.offset_index).await {
+                Ok(png) => png,
+                Err(e) => {
+                    debug!("skipping thumbnail for frame {}: {}", frame_id, e);
+                    continue;
+                }
+            };
+            let jpeg = tokio::task::spawn_blocking(move || {
+                encode_thumbnail(&image::load_from_memory(&png)?)
+            })
+            .await?;
+            let result = match jpeg {
+                Ok(jpeg) => store_thumbnail(db, thumbnails_dir, &[frame_id], &jpeg).await,
+                Err(e) => Err(e),
+            };
+            match result {
+                Ok(_) => generated += 1,
+                Err(e) => error!("failed to write thumbnail for frame {}: {}", frame_id, e),
+            }
+        }
+    }
+    Ok(generated)
+}
@@ -0,0 +1,60 @@
+//! Version of the server and of its api, in headers of every response and at `GET /version`, so
+//! clients can tell what they talk to.
+
+use axum::http::{HeaderName, HeaderValue};
+use axum::Json;
+use axum::Router;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tower_http::set_header::SetResponseHeaderLayer;
+
+pub const SERVER_VERSION: &str = env!("CARGO_PKG_VERSION");
+/// Bumped on breaking changes to the http api
+pub const API_VERSION: u32 = 1;
+
+pub const X_SCREENPIPE_VERSION: &str = "x-screenpipe-version";
+pub const X_SCREENPIPE_API_VERSION: &str = "x-screenpipe-api-version";
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct VersionResponse {
+    pub server_version: String,
+    pub api_version: u32,
+    /// Rfc 3339, `None` if the build script couldn't tell
+    pub build_timestamp: Option<String>,
+    pub git_sha: String,
+}
+
+impl VersionResponse {
+    pub fn current() -> Self {
+        VersionResponse {
+            server_version: SERVER_VERSION.to_string(),
+            api_version: API_VERSION,
+            build_timestamp: env!("SCREENPIPE_BUILD_TIMESTAMP")
+                .parse()
+                .ok()
+                .and_then(|secs| DateTime::<Utc>::from_timestamp(secs, 0))
+                .map(|built_at| built_at.to_rfc3339()),
+            git_sha: env!("SCREENPIPE_GIT_SHA").to_string(),
+        }
+    }
+}
+
+pub async fn get_version() -> Json<VersionResponse> {
+    Json(VersionResponse::current())
+}
+
+/// Adds `X-Screenpipe-Version` and `X-Screenpipe-Api-Version` to the responses of `router`.
+pub fn with_version_headers<S>(router: Router<S>) -> Router<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    router
+        .layer(SetResponseHeaderLayer::overriding(
+            HeaderName::from_static(X_SCREENPIPE_VERSION),
+            HeaderValue::from_static(SERVER_VERSION),
+        ))
+        .layer(SetResponseHeaderLayer::overriding(
+            HeaderName::from_static(X_SCREENPIPE_API_VERSION),
+            HeaderValue::from(API_VERSION),
+        ))
+}
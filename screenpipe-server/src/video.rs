@@ -1,65 +1,87 @@
+use crate::filtering::window_title_matches;
+use crate::frame_buffer::FrameBuffer;
+use crate::frame_format::CaptureFormat;
+use crate::stats::{DISCARDED_FRAMES, OVERSIZED_FRAMES};
 use chrono::Utc;
 use crossbeam::queue::ArrayQueue;
-use image::ImageFormat::{self};
+use futures::future::BoxFuture;
 use log::{debug, error};
 use log::{info, warn};
 use screenpipe_core::find_ffmpeg_path;
-use screenpipe_vision::{continuous_capture, CaptureResult, OcrEngine};
-use std::path::PathBuf;
+use screenpipe_vision::{
+    continuous_capture_with_config, CaptureResult, OcrFallback, SharedCaptureConfig,
+};
+use std::path::{Path, PathBuf};
 use std::process::Stdio;
+use std::sync::atomic::Ordering;
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use tokio::io::AsyncWriteExt;
 use tokio::io::{AsyncBufReadExt, BufReader};
 use tokio::process::{Child, ChildStdin, Command};
 use tokio::sync::mpsc::{channel, Receiver, Sender};
+use tokio::sync::Semaphore;
+use tokio::task::JoinHandle;
 use tokio::time::{sleep, timeout};
+use tokio_util::sync::CancellationToken;
 
-const MAX_FPS: f64 = 30.0; // Adjust based on your needs
+pub(crate) const MAX_FPS: f64 = 30.0; // Adjust based on your needs
 const MAX_QUEUE_SIZE: usize = 10;
 
 pub struct VideoCapture {
+    /// Frames encoded in the `--capture-format`, ready for ffmpeg
     #[allow(unused)]
-    video_frame_queue: Arc<ArrayQueue<Arc<CaptureResult>>>,
+    video_frame_queue: Arc<ArrayQueue<Arc<Vec<u8>>>>,
     pub ocr_frame_queue: Arc<ArrayQueue<Arc<CaptureResult>>>,
+    capture_thread: JoinHandle<()>,
+    video_thread: JoinHandle<()>,
+    shutdown: CancellationToken,
 }
 
 impl VideoCapture {
+    /// With a `frame_buffer` chunks are recorded in its buffer dir, `chunk_moved_callback` gets
+    /// the old and new path of each once it is moved to `output_path`, the move is done once its
+    /// future is.
     pub fn new(
         output_path: &str,
-        fps: f64,
+        frame_buffer: Option<FrameBuffer>,
+        capture_config: SharedCaptureConfig,
         video_chunk_duration: Duration,
         new_chunk_callback: impl Fn(&str) + Send + Sync + 'static,
+        chunk_moved_callback: impl Fn(&str, &str) -> BoxFuture<'static, ()> + Send + Sync + 'static,
         save_text_files: bool,
-        ocr_engine: Arc<OcrEngine>,
+        ocr_fallback: Option<OcrFallback>,
+        ocr_workers: usize,
+        max_frame_size_kb: u64,
+        capture_format: CaptureFormat,
         monitor_id: u32,
         ignore_list: &[String],
         include_list: &[String],
+        ignore_window_patterns: &[String],
+        task_permits: Option<Arc<Semaphore>>,
     ) -> Self {
         info!("Starting new video capture");
-        let fps = if fps.is_finite() && fps > 0.0 {
-            fps
-        } else {
-            warn!("Invalid FPS value: {}. Using default of 1.0", fps);
-            1.0
-        };
-        let interval = Duration::from_secs_f64(1.0 / fps);
         let video_frame_queue = Arc::new(ArrayQueue::new(MAX_QUEUE_SIZE));
         let ocr_frame_queue = Arc::new(ArrayQueue::new(MAX_QUEUE_SIZE));
         let new_chunk_callback = Arc::new(new_chunk_callback);
         let new_chunk_callback_clone = Arc::clone(&new_chunk_callback);
+        let chunk_moved_callback = Arc::new(chunk_moved_callback);
 
         let capture_video_frame_queue = video_frame_queue.clone();
         let capture_ocr_frame_queue = ocr_frame_queue.clone();
         let (result_sender, mut result_receiver) = channel(512);
         let ignore_list_clone = ignore_list.to_vec();
         let include_list_clone = include_list.to_vec();
-        let _capture_thread = tokio::spawn(async move {
-            continuous_capture(
+        let ignore_window_patterns = ignore_window_patterns.to_vec();
+        let capture_thread_config = Arc::clone(&capture_config);
+        let capture_thread = tokio::spawn(async move {
+            continuous_capture_with_config(
                 result_sender,
-                interval,
+                capture_thread_config,
                 save_text_files,
-                *ocr_engine,
+                ocr_fallback,
+                ocr_workers,
+                task_permits,
                 monitor_id,
                 &ignore_list_clone,
                 &include_list_clone,
@@ -72,9 +94,9 @@ impl VideoCapture {
         // In the _queue_thread
         let _queue_thread = tokio::spawn(async move {
             // Helper function to push to queue and handle errors
-            fn push_to_queue(
-                queue: &ArrayQueue<Arc<CaptureResult>>,
-                result: &Arc<CaptureResult>,
+            fn push_to_queue<T>(
+                queue: &ArrayQueue<Arc<T>>,
+                result: &Arc<T>,
                 queue_name: &str,
             ) -> bool {
                 if queue.push(Arc::clone(result)).is_err() {
@@ -97,9 +119,51 @@ impl VideoCapture {
                 let frame_number = result.frame_number;
                 debug!("Received frame {} for queueing", frame_number);
 
+                if let Some(window) = result.window_ocr_results.iter().find(|window| {
+                    window.focused
+                        && ignore_window_patterns
+                            .iter()
+                            .any(|pattern| window_title_matches(&window.window_name, pattern))
+                }) {
+                    debug!(
+                        "Discarding frame {}, focused window '{}' is ignored",
+                        frame_number, window.window_name
+                    );
+                    DISCARDED_FRAMES.fetch_add(1, Ordering::Relaxed);
+                    continue;
+                }
+
                 let result = Arc::new(result);
 
-                let video_pushed = push_to_queue(&capture_video_frame_queue, &result, "Video");
+                // encoded here rather than by the video thread so a corrupt frame is never stored
+                let frame = Arc::clone(&result);
+                let encoded =
+                    match tokio::task::spawn_blocking(move || capture_format.encode(&frame.image))
+                        .await
+                    {
+                        Ok(Ok(encoded)) => encoded,
+                        Ok(Err(e)) => {
+                            error!("Failed to encode frame {}: {}", frame_number, e);
+                            continue;
+                        }
+                        Err(e) => {
+                            error!("Failed to encode frame {}: {}", frame_number, e);
+                            continue;
+                        }
+                    };
+                if encoded.len() as u64 > max_frame_size_kb * 1024 {
+                    warn!(
+                        "Skipping frame {}, its {} KB encoding is over --max-frame-size-kb {} which usually means a capture error",
+                        frame_number,
+                        encoded.len() / 1024,
+                        max_frame_size_kb
+                    );
+                    OVERSIZED_FRAMES.fetch_add(1, Ordering::Relaxed);
+                    continue;
+                }
+
+                let video_pushed =
+                    push_to_queue(&capture_video_frame_queue, &Arc::new(encoded), "Video");
                 let ocr_pushed = push_to_queue(&capture_ocr_frame_queue, &result, "OCR");
 
                 if !video_pushed || !ocr_pushed {
@@ -122,14 +186,20 @@ impl VideoCapture {
         let video_frame_queue_clone = video_frame_queue.clone();
 
         let output_path = output_path.to_string();
-        let _video_thread = tokio::spawn(async move {
+        let shutdown = CancellationToken::new();
+        let video_shutdown = shutdown.clone();
+        let video_thread = tokio::spawn(async move {
             save_frames_as_video(
                 &video_frame_queue_clone,
                 &output_path,
-                fps,
+                frame_buffer,
+                &capture_config,
                 new_chunk_callback_clone,
+                chunk_moved_callback,
+                capture_format,
                 monitor_id,
                 video_chunk_duration,
+                video_shutdown,
             )
             .await;
         });
@@ -137,42 +207,133 @@ impl VideoCapture {
         VideoCapture {
             video_frame_queue,
             ocr_frame_queue,
+            capture_thread,
+            video_thread,
+            shutdown,
         }
     }
+
+    /// Stops capturing and waits for ffmpeg to finalize the current video chunk.
+    pub async fn stop(self) {
+        self.capture_thread.abort();
+        self.shutdown.cancel();
+        if let Err(e) = self.video_thread.await {
+            error!("video thread failed while stopping: {:?}", e);
+        }
+    }
+}
+
+async fn finish_ffmpeg(child: Child, stdin: Option<ChildStdin>) {
+    drop(stdin); // Ensure stdin is closed
+    match child.wait_with_output().await {
+        Ok(output) => {
+            debug!("FFmpeg process exited with status: {}", output.status);
+            if !output.status.success() {
+                error!("FFmpeg stderr: {}", String::from_utf8_lossy(&output.stderr));
+            }
+        }
+        Err(e) => error!("ffmpeg process failed: {}", e),
+    }
 }
+
+/// Finalizes a chunk and, when it was recorded in the frame buffer, moves it out in the
+/// background. The handle finishes once `chunk_moved_callback` updated the path of the chunk.
+async fn finish_chunk(
+    child: Child,
+    stdin: Option<ChildStdin>,
+    chunk: Option<String>,
+    frame_buffer: Option<&FrameBuffer>,
+    chunk_moved_callback: &Arc<dyn Fn(&str, &str) -> BoxFuture<'static, ()> + Send + Sync>,
+) -> Option<JoinHandle<()>> {
+    let start = Instant::now();
+    finish_ffmpeg(child, stdin).await;
+    let finalized_in = start.elapsed();
+    let (frame_buffer, chunk) = (frame_buffer?.clone(), chunk?);
+    let chunk_moved_callback = Arc::clone(chunk_moved_callback);
+    Some(tokio::spawn(async move {
+        let start = Instant::now();
+        match frame_buffer.flush(Path::new(&chunk)).await {
+            Ok(moved) => {
+                debug!(
+                    "finalized {} in {:?} in the frame buffer, moved it to {} in {:?}",
+                    chunk,
+                    finalized_in,
+                    moved.display(),
+                    start.elapsed()
+                );
+                chunk_moved_callback(&chunk, &moved.to_string_lossy()).await;
+            }
+            Err(e) => error!(
+                "failed to move {} out of the frame buffer, it stays there: {}",
+                chunk, e
+            ),
+        }
+    }))
+}
+
 async fn save_frames_as_video(
-    frame_queue: &Arc<ArrayQueue<Arc<CaptureResult>>>,
+    frame_queue: &Arc<ArrayQueue<Arc<Vec<u8>>>>,
     output_path: &str,
-    fps: f64,
+    frame_buffer: Option<FrameBuffer>,
+    capture_config: &SharedCaptureConfig,
     new_chunk_callback: Arc<dyn Fn(&str) + Send + Sync>,
+    chunk_moved_callback: Arc<dyn Fn(&str, &str) -> BoxFuture<'static, ()> + Send + Sync>,
+    capture_format: CaptureFormat,
     monitor_id: u32,
     video_chunk_duration: Duration,
+    shutdown: CancellationToken,
 ) {
     debug!("Starting save_frames_as_video function");
-    let frames_per_video = (fps * video_chunk_duration.as_secs_f64()).ceil() as usize;
+    // fps changes at runtime apply from the next chunk, ffmpeg encodes each at a fixed rate
+    let mut fps = capture_config.read().unwrap().fps;
+    let mut frames_per_video = (fps * video_chunk_duration.as_secs_f64()).ceil() as usize;
     let mut frame_count = 0;
-    let (sender, mut receiver): (Sender<Vec<u8>>, Receiver<Vec<u8>>) = channel(512);
-    let sender = Arc::new(sender);
+    let (sender, mut receiver): (Sender<Arc<Vec<u8>>>, Receiver<Arc<Vec<u8>>>) = channel(512);
     let mut current_ffmpeg: Option<Child> = None;
     let mut current_stdin: Option<ChildStdin> = None;
+    let mut current_chunk: Option<String> = None;
+    let chunk_dir = frame_buffer.as_ref().map_or_else(
+        || PathBuf::from(output_path),
+        |buffer| buffer.buffer_dir.clone(),
+    );
 
     loop {
+        if shutdown.is_cancelled() {
+            if let Some(child) = current_ffmpeg.take() {
+                info!("finalizing video chunk for monitor {}", monitor_id);
+                let moving = finish_chunk(
+                    child,
+                    current_stdin.take(),
+                    current_chunk.take(),
+                    frame_buffer.as_ref(),
+                    &chunk_moved_callback,
+                )
+                .await;
+                // the runtime may stop right after, the chunk must not stay in the buffer
+                if let Some(moving) = moving {
+                    let _ = moving.await;
+                }
+            }
+            return;
+        }
+
         if frame_count >= frames_per_video || current_ffmpeg.is_none() {
             debug!("Starting new FFmpeg process");
             // Close previous FFmpeg process if exists
             if let Some(child) = current_ffmpeg.take() {
-                drop(current_stdin.take()); // Ensure stdin is closed
-                let output = child
-                    .wait_with_output()
-                    .await
-                    .expect("ffmpeg process failed");
-                debug!("FFmpeg process exited with status: {}", output.status);
-                if !output.status.success() {
-                    error!("FFmpeg stderr: {}", String::from_utf8_lossy(&output.stderr));
-                }
+                finish_chunk(
+                    child,
+                    current_stdin.take(),
+                    current_chunk.take(),
+                    frame_buffer.as_ref(),
+                    &chunk_moved_callback,
+                )
+                .await;
             }
             // Reset frame count
             frame_count = 0;
+            fps = capture_config.read().unwrap().fps;
+            frames_per_video = (fps * video_chunk_duration.as_secs_f64()).ceil() as usize;
 
             // Wait for at least one frame before starting a new FFmpeg process
             let first_frame = loop {
@@ -180,20 +341,18 @@ async fn save_frames_as_video(
                     debug!("Got first frame for new chunk");
                     break result;
                 }
+                if shutdown.is_cancelled() {
+                    return;
+                }
                 tokio::time::sleep(Duration::from_millis(10)).await;
             };
 
-            // Encode the first frame
-            let mut buffer = Vec::new();
-            first_frame
-                .image
-                .write_to(&mut std::io::Cursor::new(&mut buffer), ImageFormat::Png)
-                .expect("Failed to encode first frame");
+            let buffer = first_frame;
 
             let time = Utc::now();
             let formatted_time = time.format("%Y-%m-%d_%H-%M-%S").to_string();
             // Start new FFmpeg process with a new output file
-            let output_file = PathBuf::from(output_path)
+            let output_file = chunk_dir
                 .join(format!("monitor_{}_{}.mp4", monitor_id, formatted_time))
                 .to_str()
                 .expect("Failed to create valid path")
@@ -202,7 +361,7 @@ async fn save_frames_as_video(
             // Call the callback with the new video chunk file path
             new_chunk_callback(&output_file);
 
-            match start_ffmpeg_process(&output_file, fps).await {
+            match start_ffmpeg_process(&output_file, fps, capture_format, None).await {
                 Ok(mut child) => {
                     let mut stdin = child.stdin.take().expect("Failed to open stdin");
                     let stderr = child.stderr.take().expect("Failed to open stderr");
@@ -235,6 +394,7 @@ async fn save_frames_as_video(
 
                     current_ffmpeg = Some(child);
                     current_stdin = Some(stdin);
+                    current_chunk = Some(output_file.clone());
                     debug!("New FFmpeg process started for file: {}", output_file);
                 }
                 Err(e) => {
@@ -244,25 +404,12 @@ async fn save_frames_as_video(
             }
         }
 
-        if let Some(result) = frame_queue.pop() {
+        if let Some(png) = frame_queue.pop() {
             debug!("Processing frame in video.rs"); // {}", frame_count + 1
-            let sender = Arc::clone(&sender);
-
-            tokio::spawn(async move {
-                let mut buffer = Vec::new();
-                match result
-                    .image
-                    .write_to(&mut std::io::Cursor::new(&mut buffer), ImageFormat::Png)
-                {
-                    Ok(_) => {
-                        sender
-                            .send(buffer)
-                            .await
-                            .expect("Failed to send encoded frame");
-                    }
-                    Err(e) => error!("Failed to encode image as PNG: {}", e),
-                }
-            });
+            sender
+                .send(png)
+                .await
+                .expect("Failed to send encoded frame");
         } else {
             // debug!("No frames in queue, waiting...");
             tokio::time::sleep(Duration::from_millis(10)).await;
@@ -330,7 +477,12 @@ async fn save_frames_as_video(
 
 use std::env;
 
-async fn start_ffmpeg_process(output_file: &str, fps: f64) -> Result<Child, anyhow::Error> {
+pub(crate) async fn start_ffmpeg_process(
+    output_file: &str,
+    fps: f64,
+    input_format: CaptureFormat,
+    keyframe_interval: Option<u32>,
+) -> Result<Child, anyhow::Error> {
     // Overriding fps with max fps if over the max and warning user
     let fps = if fps > MAX_FPS {
         warn!("Overriding FPS from {} to {}", fps, MAX_FPS);
@@ -346,7 +498,7 @@ async fn start_ffmpeg_process(output_file: &str, fps: f64) -> Result<Child, anyh
         "-f",
         "image2pipe",
         "-vcodec",
-        "png",
+        input_format.ffmpeg_codec(),
         "-r",
         &fps_str,
         "-i",
@@ -371,6 +523,16 @@ async fn start_ffmpeg_process(output_file: &str, fps: f64) -> Result<Child, anyh
         args.extend_from_slice(&["-vcodec", "libx264", "-preset", "ultrafast", "-crf", "23"]);
     }
 
+    let keyframe_interval = keyframe_interval.map(|interval| interval.max(1).to_string());
+    if let Some(interval) = &keyframe_interval {
+        args.extend_from_slice(&["-g", interval]);
+    }
+
+    if output_file == "-" {
+        // mp4 can only be written to a pipe as fragments
+        args.extend_from_slice(&["-movflags", "frag_keyframe+empty_moov", "-f", "mp4"]);
+    }
+
     args.extend_from_slice(&["-pix_fmt", "yuv420p", output_file]);
 
     command
@@ -9,6 +9,11 @@ use tracing::{debug, info};
 use uuid::Uuid;
 
 pub async fn extract_frame(file_path: &str, offset_index: i64) -> Result<String> {
+    let frame_data = extract_frame_png(file_path, offset_index).await?;
+    Ok(general_purpose::STANDARD.encode(frame_data))
+}
+
+pub async fn extract_frame_png(file_path: &str, offset_index: i64) -> Result<Vec<u8>> {
     let ffmpeg_path = find_ffmpeg_path().expect("failed to find ffmpeg path");
 
     let offset_seconds = offset_index as f64 / 1000.0;
@@ -58,7 +63,7 @@ pub async fn extract_frame(file_path: &str, offset_index: i64) -> Result<String>
         return Err(anyhow::anyhow!("failed to extract frame: no data received"));
     }
 
-    Ok(general_purpose::STANDARD.encode(frame_data))
+    Ok(frame_data)
 }
 
 #[derive(Deserialize)]
@@ -0,0 +1,48 @@
+use lru::LruCache;
+use std::num::NonZeroUsize;
+use std::sync::{Arc, Mutex, OnceLock};
+
+/// Waveforms of recently viewed audio chunks, keyed by (audio chunk id, samples).
+const WAVEFORM_CACHE_SIZE: usize = 64;
+
+pub const MAX_WAVEFORM_SAMPLES: usize = 10_000;
+
+pub type Waveform = Arc<Vec<[f32; 2]>>;
+
+fn cache() -> &'static Mutex<LruCache<(i64, usize), Waveform>> {
+    static CACHE: OnceLock<Mutex<LruCache<(i64, usize), Waveform>>> = OnceLock::new();
+    CACHE.get_or_init(|| {
+        Mutex::new(LruCache::new(
+            NonZeroUsize::new(WAVEFORM_CACHE_SIZE).unwrap(),
+        ))
+    })
+}
+
+pub fn cached_waveform(audio_chunk_id: i64, samples: usize) -> Option<Waveform> {
+    cache()
+        .lock()
+        .unwrap()
+        .get(&(audio_chunk_id, samples))
+        .cloned()
+}
+
+pub fn cache_waveform(audio_chunk_id: i64, samples: usize, waveform: Waveform) {
+    cache()
+        .lock()
+        .unwrap()
+        .put((audio_chunk_id, samples), waveform);
+}
+
+/// Peak-hold downsampling: the `[min, max]` amplitude of each of `samples` equal buckets, fewer
+/// points when the audio is shorter than that.
+pub fn compute_waveform(audio: &[f32], samples: usize) -> Vec<[f32; 2]> {
+    let samples = samples.min(audio.len());
+    (0..samples)
+        .map(|i| {
+            let bucket = &audio[i * audio.len() / samples..(i + 1) * audio.len() / samples];
+            bucket.iter().fold([f32::MAX, f32::MIN], |[min, max], &s| {
+                [min.min(s), max.max(s)]
+            })
+        })
+        .collect()
+}
@@ -0,0 +1,46 @@
+use screenpipe_server::{check_audio_chunks, AudioIntegrityReport, DatabaseManager};
+
+#[tokio::test]
+async fn test_check_audio_chunks_flags_missing_files() {
+    let dir = tempfile::tempdir().unwrap();
+    let present = dir.path().join("present.mp4");
+    let empty = dir.path().join("empty.mp4");
+    std::fs::write(&present, b"audio").unwrap();
+    std::fs::write(&empty, b"").unwrap();
+    let deleted = dir.path().join("deleted.mp4");
+
+    let db = DatabaseManager::new("sqlite::memory:").await.unwrap();
+    let present_id = db
+        .insert_audio_chunk(&present.to_string_lossy())
+        .await
+        .unwrap();
+    let empty_id = db
+        .insert_audio_chunk(&empty.to_string_lossy())
+        .await
+        .unwrap();
+    let deleted_id = db
+        .insert_audio_chunk(&deleted.to_string_lossy())
+        .await
+        .unwrap();
+
+    let report = check_audio_chunks(&db).await.unwrap();
+    assert_eq!(
+        report,
+        AudioIntegrityReport {
+            checked: 3,
+            missing: 2
+        }
+    );
+    let file_missing = |id| {
+        let db = &db;
+        async move { db.get_audio_chunk_file(id).await.unwrap().unwrap().1 }
+    };
+    assert!(!file_missing(present_id).await);
+    assert!(file_missing(empty_id).await);
+    assert!(file_missing(deleted_id).await);
+
+    // a file put back is no longer flagged
+    std::fs::write(&deleted, b"audio").unwrap();
+    check_audio_chunks(&db).await.unwrap();
+    assert!(!file_missing(deleted_id).await);
+}
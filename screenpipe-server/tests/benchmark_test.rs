@@ -0,0 +1,31 @@
+use std::time::Duration;
+
+use screenpipe_server::benchmark::{percentile, EngineBenchmark};
+
+#[test]
+fn test_percentile() {
+    let latencies: Vec<Duration> = (1..=10).map(Duration::from_millis).collect();
+    assert_eq!(percentile(&latencies, 50.0), Duration::from_millis(5));
+    assert_eq!(percentile(&latencies, 99.0), Duration::from_millis(10));
+    assert_eq!(percentile(&latencies, 0.0), Duration::from_millis(1));
+    assert_eq!(percentile(&[], 50.0), Duration::ZERO);
+}
+
+#[test]
+fn test_benchmark_json() {
+    let result = EngineBenchmark {
+        engine: "tesseract".to_string(),
+        frames: 10,
+        median_ms: 310.5,
+        p99_ms: 420.0,
+        characters: 1200,
+        error: None,
+    };
+    let json = serde_json::to_value(&result).unwrap();
+    assert_eq!(json["engine"], "tesseract");
+    assert_eq!(json["median_ms"], 310.5);
+    assert_eq!(
+        serde_json::from_value::<EngineBenchmark>(json).unwrap(),
+        result
+    );
+}
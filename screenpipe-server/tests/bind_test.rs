@@ -0,0 +1,36 @@
+use screenpipe_server::{bind_listener, ipv6_available, listen_addr};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, TcpStream};
+
+#[test]
+fn test_listen_addr() {
+    let localhost = IpAddr::V4(Ipv4Addr::LOCALHOST);
+    assert_eq!(
+        listen_addr(localhost, 3030, false),
+        SocketAddr::new(localhost, 3030)
+    );
+    let all_ipv4 = IpAddr::V4(Ipv4Addr::UNSPECIFIED);
+    assert_eq!(
+        listen_addr(IpAddr::V6(Ipv6Addr::UNSPECIFIED), 3030, true),
+        SocketAddr::new(all_ipv4, 3030)
+    );
+    let expected = if ipv6_available() {
+        IpAddr::V6(Ipv6Addr::UNSPECIFIED)
+    } else {
+        all_ipv4
+    };
+    assert_eq!(
+        listen_addr(all_ipv4, 3030, false),
+        SocketAddr::new(expected, 3030)
+    );
+}
+
+#[test]
+fn test_dual_stack_listener_accepts_ipv4() {
+    if !ipv6_available() {
+        return;
+    }
+    let listener = bind_listener(SocketAddr::new(IpAddr::V6(Ipv6Addr::UNSPECIFIED), 0)).unwrap();
+    let port = listener.local_addr().unwrap().port();
+    TcpStream::connect((Ipv4Addr::LOCALHOST, port)).unwrap();
+    TcpStream::connect((Ipv6Addr::LOCALHOST, port)).unwrap();
+}
@@ -0,0 +1,114 @@
+use std::time::Duration;
+
+use clap::Parser;
+use screenpipe_server::{parse_dedup_threshold, parse_fps, Cli};
+use screenpipe_vision::{capture_interval, default_ocr_workers};
+
+#[test]
+fn test_parse_fps_range() {
+    assert_eq!(parse_fps("0.0167"), Ok(0.0167));
+    assert_eq!(parse_fps("0.001"), Ok(0.001));
+    assert_eq!(parse_fps("60"), Ok(60.0));
+
+    for value in ["0", "0.0009", "60.5", "-1", "NaN", "inf", "fast"] {
+        assert!(parse_fps(value).is_err(), "{} was accepted", value);
+    }
+}
+
+#[test]
+fn test_parse_dedup_threshold_range() {
+    assert_eq!(parse_dedup_threshold("0"), Ok(0.0));
+    assert_eq!(parse_dedup_threshold("0.006"), Ok(0.006));
+    assert_eq!(parse_dedup_threshold("1"), Ok(1.0));
+
+    for value in ["-0.1", "1.5", "NaN", "inf", "low"] {
+        assert!(
+            parse_dedup_threshold(value).is_err(),
+            "{} was accepted",
+            value
+        );
+    }
+    assert!(Cli::try_parse_from(["screenpipe", "--dedup-threshold", "2"]).is_err());
+}
+
+#[test]
+fn test_capture_interval() {
+    assert_eq!(capture_interval(0.5), Duration::from_secs(2));
+    assert_eq!(capture_interval(0.001), Duration::from_secs(1000));
+    // out of range rates are clamped instead of panicking
+    assert_eq!(capture_interval(0.0), Duration::from_secs(1000));
+    assert_eq!(capture_interval(f64::NAN), Duration::from_secs(1000));
+    assert_eq!(
+        capture_interval(f64::INFINITY),
+        Duration::from_secs_f64(1.0 / 60.0)
+    );
+}
+
+#[test]
+fn test_ocr_workers() {
+    let cli = Cli::try_parse_from(["screenpipe"]).unwrap();
+    assert_eq!(cli.ocr_workers as usize, default_ocr_workers());
+    assert!(default_ocr_workers() >= 1);
+
+    let cli = Cli::try_parse_from(["screenpipe", "--ocr-workers", "4"]).unwrap();
+    assert_eq!(cli.ocr_workers, 4);
+    assert!(Cli::try_parse_from(["screenpipe", "--ocr-workers", "0"]).is_err());
+}
+
+#[test]
+fn test_cloud_ocr_timeout() {
+    let cli = Cli::try_parse_from(["screenpipe"]).unwrap();
+    assert_eq!(cli.cloud_ocr_timeout_ms, 10_000);
+
+    let cli = Cli::try_parse_from(["screenpipe", "--cloud-ocr-timeout-ms", "2500"]).unwrap();
+    assert_eq!(cli.cloud_ocr_timeout_ms, 2500);
+    assert!(Cli::try_parse_from(["screenpipe", "--cloud-ocr-timeout-ms", "0"]).is_err());
+}
+
+#[test]
+fn test_cli_env_vars() {
+    use screenpipe_server::{env_var_name, ConfigSource};
+
+    assert_eq!(
+        env_var_name("audio-chunk-duration"),
+        "SCREENPIPE_AUDIO_CHUNK_DURATION"
+    );
+    std::env::set_var("SCREENPIPE_FPS", "0.5");
+    std::env::set_var("SCREENPIPE_PORT", "4040");
+    std::env::set_var("SCREENPIPE_DISABLE_AUDIO", "true");
+    let (cli, sources) = Cli::try_parse_with_env(["screenpipe", "--port", "5050"]).unwrap();
+    std::env::remove_var("SCREENPIPE_FPS");
+    std::env::remove_var("SCREENPIPE_PORT");
+    std::env::remove_var("SCREENPIPE_DISABLE_AUDIO");
+
+    assert_eq!(cli.fps, 0.5);
+    assert_eq!(cli.port, 5050);
+    assert!(cli.disable_audio);
+    assert_eq!(sources["fps"], ConfigSource::Env);
+    assert_eq!(sources["port"], ConfigSource::Cli);
+    assert_eq!(sources["disable_audio"], ConfigSource::Env);
+    assert_eq!(sources["audio_chunk_duration"], ConfigSource::Default);
+    assert!(!sources.contains_key("help"));
+}
+
+#[test]
+fn test_disable_ocr() {
+    let cli = Cli::try_parse_from(["screenpipe", "--disable-ocr"]).unwrap();
+    assert!(cli.disable_ocr);
+    assert!(!cli.disable_vision);
+    assert!(!cli.records_nothing());
+
+    let cli = Cli::try_parse_from(["screenpipe", "--disable-frames"]).unwrap();
+    assert!(cli.disable_vision);
+
+    for flags in [
+        ["--disable-audio", "--disable-ocr"],
+        ["--disable-audio", "--disable-frames"],
+    ] {
+        let cli = Cli::try_parse_from(["screenpipe", flags[0], flags[1]]).unwrap();
+        assert!(cli.records_nothing());
+        assert!(!cli.force);
+    }
+    let cli = Cli::try_parse_from(["screenpipe", "--disable-audio"]).unwrap();
+    assert!(!cli.records_nothing());
+}
@@ -0,0 +1,116 @@
+mod common;
+
+use common::test_app_state;
+use screenpipe_server::client::{Client, SearchParams};
+use screenpipe_server::{
+    create_router, AppState, ContentItem, ContentType, DatabaseManager, RuntimeConfigUpdate,
+};
+use screenpipe_vision::OcrEngine;
+use std::sync::Arc;
+
+/// Serves the api on a free port and returns a client for it.
+async fn setup_client() -> (Client, Arc<AppState>) {
+    let db = Arc::new(DatabaseManager::new("sqlite::memory:").await.unwrap());
+    let app_state = Arc::new(test_app_state(db));
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let app = create_router().with_state(app_state.clone());
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+    let client = Client::new(format!("http://{}/", addr)).unwrap();
+    (client, app_state)
+}
+
+#[tokio::test]
+async fn test_client_health_and_search() {
+    let (client, app_state) = setup_client().await;
+
+    let _video_chunk_id = app_state
+        .db
+        .insert_video_chunk("test_video_file.mp4")
+        .await
+        .unwrap();
+    let frame_id = app_state.db.insert_frame().await.unwrap();
+    app_state
+        .db
+        .insert_ocr_text(
+            frame_id,
+            "quarterly invoice",
+            "",
+            "billing",
+            "invoices",
+            Arc::new(OcrEngine::Tesseract),
+            true,
+        )
+        .await
+        .unwrap();
+
+    let health = client.get_health().await.unwrap();
+    assert!(!health.status.is_empty());
+
+    let results = client
+        .search(&SearchParams {
+            q: Some("invoice".to_string()),
+            content_type: Some(ContentType::OCR),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    assert_eq!(results.data.len(), 1);
+    match &results.data[0] {
+        ContentItem::OCR(ocr) => {
+            assert_eq!(ocr.frame_id, frame_id);
+            assert_eq!(ocr.app_name, "billing");
+        }
+        other => panic!("expected an ocr result, got {:?}", other),
+    }
+
+    let none = client
+        .search(&SearchParams {
+            q: Some("invoice".to_string()),
+            app_name: Some("terminal".to_string()),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    assert!(none.data.is_empty());
+}
+
+#[tokio::test]
+async fn test_client_recording_and_config() {
+    let (client, app_state) = setup_client().await;
+
+    let paused = client.pause_recording().await.unwrap();
+    assert!(!paused.recording);
+    assert!(paused.changed);
+    assert!(!app_state.recording.is_running());
+
+    let resumed = client.resume_recording().await.unwrap();
+    assert!(resumed.recording);
+    assert!(resumed.changed);
+
+    let config = client
+        .update_config(&RuntimeConfigUpdate {
+            fps: Some(0.5),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    assert_eq!(config.fps, 0.5);
+    assert_eq!(client.get_config().await.unwrap(), config);
+}
+
+#[tokio::test]
+async fn test_client_error_status() {
+    let (client, _app_state) = setup_client().await;
+
+    let err = client
+        .update_config(&RuntimeConfigUpdate {
+            fps: Some(-1.0),
+            ..Default::default()
+        })
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("400"), "{}", err);
+}
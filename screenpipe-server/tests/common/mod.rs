@@ -0,0 +1,51 @@
+use chrono::Utc;
+use crossbeam::queue::SegQueue;
+use screenpipe_core::StoragePaths;
+use screenpipe_server::{
+    AppState, DatabaseManager, ExportJobs, MediaSigner, PipeManager, RankWeights, RecordingControl,
+    StatsCache, DEFAULT_SIGNED_URL_TTL,
+};
+use screenpipe_vision::{CaptureConfig, OcrEngine, OcrPreprocess, DEFAULT_DEDUP_THRESHOLD};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::atomic::AtomicBool;
+use std::sync::{Arc, RwLock};
+
+/// State of a server recording nothing on top of `db`, a field a test needs set differently is
+/// overridden with `AppState { .., ..test_app_state(db) }`.
+pub fn test_app_state(db: Arc<DatabaseManager>) -> AppState {
+    AppState {
+        db: db.clone(),
+        vision_disabled: false,
+        audio_disabled: false,
+        vision_control: Arc::new(AtomicBool::new(false)),
+        audio_devices_control: Arc::new(SegQueue::new()),
+        devices_status: Arc::new(RwLock::new(HashMap::new())),
+        app_start_time: Utc::now(),
+        screenpipe_dir: PathBuf::from(""),
+        frames_dir: PathBuf::from("data"),
+        pipe_manager: Arc::new(PipeManager::new(PathBuf::from(""))),
+        stats_cache: Arc::new(StatsCache::new(
+            db,
+            StoragePaths::new(PathBuf::from("")),
+            std::time::Duration::from_secs(30),
+        )),
+        export_jobs: Arc::new(ExportJobs::new(PathBuf::from(""))),
+        capture_config: Arc::new(RwLock::new(CaptureConfig {
+            fps: 1.0,
+            ocr_engine: OcrEngine::Tesseract,
+            dedup_threshold: DEFAULT_DEDUP_THRESHOLD,
+            idle_pause: None,
+            capture_cursor: false,
+            ocr_skip_frames: 1,
+            disable_ocr: false,
+            ocr_preprocess: OcrPreprocess::default(),
+        })),
+        rank_weights: Arc::new(RwLock::new(RankWeights::default())),
+        recording: Arc::new(RecordingControl::new(true)),
+        remote_sync_secret: None,
+        media_signer: Arc::new(MediaSigner::random(DEFAULT_SIGNED_URL_TTL)),
+        live_transcription: None,
+        normalize_ocr: true,
+    }
+}
@@ -0,0 +1,40 @@
+use screenpipe_server::content_classifier::{classify_screen_content, ScreenContentType};
+
+#[test]
+fn test_classify_by_window_and_app() {
+    assert_eq!(
+        classify_screen_content("", "Google Chrome", "Rust tutorial - YouTube"),
+        Some(ScreenContentType::Video)
+    );
+    assert_eq!(
+        classify_screen_content("", "Code", "db.rs — screenpipe"),
+        Some(ScreenContentType::Code)
+    );
+    assert_eq!(
+        classify_screen_content("", "Microsoft Excel", "budget"),
+        Some(ScreenContentType::Spreadsheet)
+    );
+    assert_eq!(
+        classify_screen_content("", "iTerm2", "~/projects"),
+        Some(ScreenContentType::Terminal)
+    );
+    assert_eq!(
+        classify_screen_content("", "Arc", "Hacker News"),
+        Some(ScreenContentType::Browser)
+    );
+    // "arc" inside another word is not the browser
+    assert_eq!(classify_screen_content("", "Research", "notes"), None);
+}
+
+#[test]
+fn test_classify_by_text_keywords() {
+    let code = "pub fn main() {\n    let x = 1;\n    return x;\n}";
+    assert_eq!(
+        classify_screen_content(code, "Unknown", "untitled"),
+        Some(ScreenContentType::Code)
+    );
+    assert_eq!(
+        classify_screen_content("meeting notes for tomorrow", "Notes", "todo"),
+        None
+    );
+}
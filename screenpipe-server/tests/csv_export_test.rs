@@ -0,0 +1,31 @@
+use chrono::{TimeZone, Utc};
+use screenpipe_server::{csv_filename, escape_csv_field, parse_csv_columns, CsvColumn};
+
+#[test]
+fn test_parse_csv_columns() {
+    assert_eq!(
+        parse_csv_columns("timestamp, ocr_text,frame_id").unwrap(),
+        vec![CsvColumn::Timestamp, CsvColumn::OcrText, CsvColumn::FrameId]
+    );
+    let error = parse_csv_columns("timestamp,secrets").unwrap_err();
+    assert!(error.contains("'secrets'") && error.contains("window_title"));
+    assert!(parse_csv_columns("").is_err());
+}
+
+#[test]
+fn test_escape_csv_field() {
+    assert_eq!(escape_csv_field("plain text"), "plain text");
+    assert_eq!(escape_csv_field("a,b"), "\"a,b\"");
+    assert_eq!(escape_csv_field("line\r\nbreak"), "\"line\r\nbreak\"");
+    assert_eq!(escape_csv_field("6\" screen"), "\"6\"\" screen\"");
+}
+
+#[test]
+fn test_csv_filename() {
+    let from = Utc.with_ymd_and_hms(2024, 10, 14, 0, 0, 0).unwrap();
+    let to = Utc.with_ymd_and_hms(2024, 10, 14, 18, 30, 0).unwrap();
+    assert_eq!(
+        csv_filename(from, to),
+        "frames_20241014T000000Z_20241014T183000Z.csv"
+    );
+}
@@ -2,10 +2,13 @@
 mod tests {
     use std::sync::Arc;
 
-    use chrono::Utc;
-    use screenpipe_audio::{AudioDevice, DeviceType};
-    use screenpipe_server::{ContentType, DatabaseManager, SearchResult};
-    use screenpipe_vision::OcrEngine;
+    use chrono::{DateTime, Duration, Utc};
+    use screenpipe_audio::{AudioDevice, DeviceType, TranscriptionSegment};
+    use screenpipe_server::{
+        ContentType, DatabaseManager, FrameData, FrameWindow, ScreenContentType, SearchOrder,
+        SearchResult, ShareClaim, TimelineResolution,
+    };
+    use screenpipe_vision::{MousePosition, OcrEngine, WindowBounds};
 
     async fn setup_test_db() -> DatabaseManager {
         DatabaseManager::new("sqlite::memory:").await.unwrap()
@@ -40,6 +43,9 @@ mod tests {
                 None,
                 None,
                 None,
+                None,
+                None,
+                &SearchOrder::default(),
             )
             .await
             .unwrap();
@@ -78,6 +84,9 @@ mod tests {
                 None,
                 None,
                 None,
+                None,
+                None,
+                &SearchOrder::default(),
             )
             .await
             .unwrap();
@@ -85,11 +94,100 @@ mod tests {
         if let SearchResult::Audio(audio_result) = &results[0] {
             assert_eq!(audio_result.transcription, "Hello from audio");
             assert_eq!(audio_result.file_path, "test_audio.mp4");
+            assert_eq!(audio_result.format, "mp4");
+        } else {
+            panic!("Expected Audio result");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_audio_chunk_format_from_extension() {
+        let db = setup_test_db().await;
+        let audio_chunk_id = db.insert_audio_chunk("test_audio.flac").await.unwrap();
+        db.insert_audio_transcription(
+            audio_chunk_id,
+            "Lossless audio",
+            0,
+            "",
+            &AudioDevice::new("test".to_string(), DeviceType::Input),
+        )
+        .await
+        .unwrap();
+
+        let results = db
+            .search(
+                "Lossless",
+                ContentType::Audio,
+                100,
+                0,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                &SearchOrder::default(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(results.len(), 1);
+        if let SearchResult::Audio(audio_result) = &results[0] {
+            assert_eq!(audio_result.format, "flac");
         } else {
             panic!("Expected Audio result");
         }
     }
 
+    #[tokio::test]
+    async fn test_audio_word_timestamps() {
+        let db = setup_test_db().await;
+        let audio_chunk_id = db.insert_audio_chunk("test_audio.mp4").await.unwrap();
+        db.insert_audio_transcription(
+            audio_chunk_id,
+            "hello world",
+            0,
+            "",
+            &AudioDevice::new("test".to_string(), DeviceType::Input),
+        )
+        .await
+        .unwrap();
+        let words = vec![
+            TranscriptionSegment {
+                text: "hello".to_string(),
+                start_ms: 1200,
+                end_ms: 1500,
+            },
+            TranscriptionSegment {
+                text: "world".to_string(),
+                start_ms: 1600,
+                end_ms: 2100,
+            },
+        ];
+        db.set_audio_chunk_word_timestamps(audio_chunk_id, &words)
+            .await
+            .unwrap();
+
+        let results = db
+            .search_audio(
+                "hello",
+                10,
+                0,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                &SearchOrder::default(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].word_timestamps, words);
+    }
+
     #[tokio::test]
     async fn test_search_all() {
         let db = setup_test_db().await;
@@ -133,6 +231,9 @@ mod tests {
                 None,
                 None,
                 None,
+                None,
+                None,
+                &SearchOrder::default(),
             )
             .await
             .unwrap();
@@ -218,7 +319,18 @@ mod tests {
         tokio::time::sleep(tokio::time::Duration::from_millis(10)).await;
         // Add this check
         let audio_results = db
-            .search_audio("Hello from audio 2", 100, 0, None, None, None, None)
+            .search_audio(
+                "Hello from audio 2",
+                100,
+                0,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                &SearchOrder::default(),
+            )
             .await
             .unwrap();
         println!("Audio results after insertion: {:?}", audio_results);
@@ -237,6 +349,9 @@ mod tests {
                 None,
                 None,
                 None,
+                None,
+                None,
+                &SearchOrder::default(),
             )
             .await
             .unwrap();
@@ -256,6 +371,9 @@ mod tests {
                 None,
                 None,
                 None,
+                None,
+                None,
+                &SearchOrder::default(),
             )
             .await
             .unwrap();
@@ -279,6 +397,9 @@ mod tests {
                 None,
                 None,
                 None,
+                None,
+                None,
+                &SearchOrder::default(),
             )
             .await
             .unwrap();
@@ -297,6 +418,9 @@ mod tests {
                 None,
                 None,
                 None,
+                None,
+                None,
+                &SearchOrder::default(),
             )
             .await
             .unwrap();
@@ -381,6 +505,9 @@ mod tests {
                 None,
                 None,
                 None,
+                None,
+                None,
+                &SearchOrder::default(),
             )
             .await
             .unwrap();
@@ -403,9 +530,757 @@ mod tests {
                 None,
                 None,
                 None,
+                None,
             )
             .await
             .unwrap();
         assert_eq!(count, 2);
     }
+
+    #[tokio::test]
+    async fn test_pool_uses_wal_on_every_connection() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("db.sqlite");
+        let db = DatabaseManager::new_with_pool_size(path.to_str().unwrap(), 2)
+            .await
+            .unwrap();
+
+        assert_eq!(db.pool.options().get_max_connections(), 2);
+
+        // hold two connections at once so both are checked, not just the first one
+        let mut conn1 = db.pool.acquire().await.unwrap();
+        let mut conn2 = db.pool.acquire().await.unwrap();
+        for conn in [&mut conn1, &mut conn2] {
+            let journal_mode: String = sqlx::query_scalar("PRAGMA journal_mode")
+                .fetch_one(&mut **conn)
+                .await
+                .unwrap();
+            assert_eq!(journal_mode, "wal");
+
+            // 1 = NORMAL
+            let synchronous: i64 = sqlx::query_scalar("PRAGMA synchronous")
+                .fetch_one(&mut **conn)
+                .await
+                .unwrap();
+            assert_eq!(synchronous, 1);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_search_filters_by_session() {
+        let db = setup_test_db().await;
+        let _ = db.insert_video_chunk("test_video.mp4").await.unwrap();
+
+        let first_session = db.start_session().await.unwrap();
+        let frame_id = db.insert_frame().await.unwrap();
+        db.insert_ocr_text(
+            frame_id,
+            "Hello from the first session",
+            "",
+            "",
+            "",
+            Arc::new(OcrEngine::Tesseract),
+            false,
+        )
+        .await
+        .unwrap();
+
+        // starting a new session closes the previous one
+        let second_session = db.start_session().await.unwrap();
+        let frame_id = db.insert_frame().await.unwrap();
+        db.insert_ocr_text(
+            frame_id,
+            "Hello from the second session",
+            "",
+            "",
+            "",
+            Arc::new(OcrEngine::Tesseract),
+            false,
+        )
+        .await
+        .unwrap();
+
+        let results = db
+            .search(
+                "Hello",
+                ContentType::OCR,
+                100,
+                0,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                Some(&first_session),
+                None,
+                &SearchOrder::default(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(results.len(), 1);
+        if let SearchResult::OCR(ocr_result) = &results[0] {
+            assert_eq!(ocr_result.ocr_text, "Hello from the first session");
+        } else {
+            panic!("Expected OCR result");
+        }
+
+        let renamed = db
+            .rename_session(&first_session, Some("standup"))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(renamed.name.as_deref(), Some("standup"));
+        assert!(renamed.end_time.is_some());
+        assert!(db.rename_session("missing", None).await.unwrap().is_none());
+
+        let sessions = db.get_sessions(10, 0).await.unwrap();
+        assert_eq!(sessions.len(), 2);
+        assert_eq!(sessions[0].id, second_session);
+        assert!(sessions[0].end_time.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_bulk_insert_frames() {
+        let db = setup_test_db().await;
+        let frames: Vec<FrameData> = ["first", "second", "third"]
+            .iter()
+            .map(|text| FrameData {
+                timestamp: Utc::now(),
+                text: format!("Batched {} frame", text),
+                text_json: "".to_string(),
+                app_name: "".to_string(),
+                window_name: "".to_string(),
+                ocr_engine: Arc::new(OcrEngine::Tesseract),
+                focused: false,
+                ocr_failed: false,
+                cursor: None,
+                window_bounds: None,
+            })
+            .collect();
+
+        // nothing to attach the frames to without a video chunk
+        assert!(db
+            .bulk_insert_frames(frames.clone())
+            .await
+            .unwrap()
+            .is_empty());
+
+        let _ = db.insert_video_chunk("test_video.mp4").await.unwrap();
+        let frame_ids = db.bulk_insert_frames(frames).await.unwrap();
+        assert_eq!(frame_ids.len(), 3);
+
+        let results = db
+            .search(
+                "Batched",
+                ContentType::OCR,
+                100,
+                0,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                &SearchOrder::default(),
+            )
+            .await
+            .unwrap();
+        let mut offsets: Vec<i64> = results
+            .iter()
+            .map(|result| match result {
+                SearchResult::OCR(ocr_result) => ocr_result.offset_index,
+                _ => panic!("Expected OCR result"),
+            })
+            .collect();
+        offsets.sort();
+        assert_eq!(offsets, vec![0, 1, 2]);
+
+        // a frame cloud ocr gave up on gets an id but no text
+        let failed = FrameData {
+            timestamp: Utc::now(),
+            text: String::new(),
+            text_json: String::new(),
+            app_name: "".to_string(),
+            window_name: "".to_string(),
+            ocr_engine: Arc::new(OcrEngine::Unstructured),
+            focused: false,
+            ocr_failed: true,
+            cursor: None,
+            window_bounds: None,
+        };
+        let frame_ids = db.bulk_insert_frames(vec![failed]).await.unwrap();
+        assert_eq!(frame_ids.len(), 1);
+        assert_eq!(db.get_frames(None, None, 10, 0).await.unwrap().len(), 4);
+        let (ocr_rows,): (i64,) =
+            sqlx::query_as("SELECT COUNT(*) FROM ocr_text WHERE frame_id = ?1")
+                .bind(frame_ids[0])
+                .fetch_one(&db.pool)
+                .await
+                .unwrap();
+        assert_eq!(ocr_rows, 0);
+    }
+
+    #[tokio::test]
+    async fn test_search_filters_by_screen_content_type() {
+        let db = setup_test_db().await;
+        let _ = db.insert_video_chunk("test_video.mp4").await.unwrap();
+        for (text, app_name, window_name) in [
+            ("fn main() {}", "Code", "main.rs"),
+            ("Top stories", "Firefox", "News"),
+        ] {
+            let frame_id = db.insert_frame().await.unwrap();
+            db.insert_ocr_text(
+                frame_id,
+                text,
+                "",
+                app_name,
+                window_name,
+                Arc::new(OcrEngine::Tesseract),
+                false,
+            )
+            .await
+            .unwrap();
+        }
+
+        let content_type = ContentType::Screen(ScreenContentType::Code);
+        let results = db
+            .search(
+                "",
+                content_type,
+                100,
+                0,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                &SearchOrder::default(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(results.len(), 1);
+        if let SearchResult::OCR(ocr_result) = &results[0] {
+            assert_eq!(ocr_result.app_name, "Code");
+        } else {
+            panic!("Expected OCR result");
+        }
+
+        let count = db
+            .count_search_results("", content_type, None, None, None, None, None, None, None)
+            .await
+            .unwrap();
+        assert_eq!(count, 1);
+    }
+
+    #[tokio::test]
+    async fn test_fuzzy_search_ocr() {
+        let db = setup_test_db().await;
+        let _ = db.insert_video_chunk("test_video.mp4").await.unwrap();
+        for text in ["screenpipe records your screen", "unrelated window text"] {
+            let frame_id = db.insert_frame().await.unwrap();
+            db.insert_ocr_text(
+                frame_id,
+                text,
+                "",
+                "",
+                "",
+                Arc::new(OcrEngine::Tesseract),
+                false,
+            )
+            .await
+            .unwrap();
+        }
+
+        // one letter missing
+        let (results, total) = db
+            .fuzzy_search_ocr("screnpipe", 10, 0, None, None, None, None, None)
+            .await
+            .unwrap();
+        assert_eq!(total, 1);
+        assert_eq!(results[0].0.ocr_text, "screenpipe records your screen");
+        assert!(results[0].1 < 1.0 && results[0].1 > 0.8);
+
+        let (results, _) = db
+            .fuzzy_search_ocr("SCREENPIPE", 10, 0, None, None, None, None, None)
+            .await
+            .unwrap();
+        assert_eq!(results[0].1, 1.0);
+
+        let (results, total) = db
+            .fuzzy_search_ocr("scrxxxpipe", 10, 0, None, None, None, None, None)
+            .await
+            .unwrap();
+        assert!(results.is_empty());
+        assert_eq!(total, 0);
+    }
+
+    #[tokio::test]
+    async fn test_fuzzy_search_ocr_after_vacuum() {
+        let db = setup_test_db().await;
+        let _ = db.insert_video_chunk("test_video.mp4").await.unwrap();
+        let mut frame_ids = Vec::new();
+        for text in [
+            "first window",
+            "screenpipe records your screen",
+            "last window",
+        ] {
+            let frame_id = db.insert_frame().await.unwrap();
+            db.insert_ocr_text(
+                frame_id,
+                text,
+                "",
+                "",
+                "",
+                Arc::new(OcrEngine::Tesseract),
+                false,
+            )
+            .await
+            .unwrap();
+            frame_ids.push(frame_id);
+        }
+
+        // a vacuum renumbers implicit rowids, the trigram index is keyed on ocr_text.id
+        sqlx::query("DELETE FROM ocr_text WHERE frame_id = ?1")
+            .bind(frame_ids[0])
+            .execute(&db.pool)
+            .await
+            .unwrap();
+        sqlx::query("VACUUM").execute(&db.pool).await.unwrap();
+
+        let (results, total) = db
+            .fuzzy_search_ocr("screnpipe", 10, 0, None, None, None, None, None)
+            .await
+            .unwrap();
+        assert_eq!(total, 1);
+        assert_eq!(results[0].0.frame_id, frame_ids[1]);
+        assert_eq!(results[0].0.ocr_text, "screenpipe records your screen");
+    }
+
+    #[tokio::test]
+    async fn test_frame_hashes_shared_thumbnail() {
+        let db = setup_test_db().await;
+        let _ = db.insert_video_chunk("test_video.mp4").await.unwrap();
+        let first = db.insert_frame().await.unwrap();
+        let second = db.insert_frame().await.unwrap();
+
+        assert_eq!(db.get_frame_hash_path("abc").await.unwrap(), None);
+        db.insert_frame_hash("abc", "/thumbnails/abc.jpg")
+            .await
+            .unwrap();
+        db.set_frame_thumbnail_hash(&[first, second], "abc")
+            .await
+            .unwrap();
+        for frame_id in [first, second] {
+            assert_eq!(
+                db.get_frame_thumbnail_path(frame_id).await.unwrap(),
+                Some("/thumbnails/abc.jpg".to_string())
+            );
+        }
+
+        // the shared file is handed back for removal once no frame points at it
+        let deleted = db.delete_frames(None, None, None).await.unwrap();
+        assert_eq!(deleted.ids.len(), 2);
+        assert_eq!(deleted.file_paths, vec!["/thumbnails/abc.jpg".to_string()]);
+        assert_eq!(db.get_frame_hash_path("abc").await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_timeline_buckets() {
+        let db = setup_test_db().await;
+        let _ = db.insert_video_chunk("test_video.mp4").await.unwrap();
+        let start = "2024-10-14T10:00:00Z".parse::<DateTime<Utc>>().unwrap();
+        let frames: Vec<FrameData> = [
+            (0, "Code", "fn main"),
+            (20, "Code", "fn main() { println!() }"),
+            (40, "Slack", "hi"),
+            (70, "Slack", "standup"),
+        ]
+        .iter()
+        .map(|(secs, app, text)| FrameData {
+            timestamp: start + Duration::seconds(*secs),
+            text: text.to_string(),
+            text_json: "".to_string(),
+            app_name: app.to_string(),
+            window_name: "".to_string(),
+            ocr_engine: Arc::new(OcrEngine::Tesseract),
+            focused: true,
+            ocr_failed: false,
+            cursor: None,
+            window_bounds: None,
+        })
+        .collect();
+        db.bulk_insert_frames(frames).await.unwrap();
+
+        let buckets = db
+            .get_timeline(
+                start,
+                start + Duration::hours(1),
+                TimelineResolution::Minute,
+                std::time::Duration::from_secs(30),
+            )
+            .await
+            .unwrap();
+        assert_eq!(buckets.len(), 2);
+        assert_eq!(buckets[0].bucket_ts, start);
+        assert_eq!(buckets[0].frame_count, 3);
+        assert_eq!(buckets[0].audio_duration_sec, 0.0);
+        assert_eq!(buckets[0].dominant_app.as_deref(), Some("Code"));
+        assert_eq!(
+            buckets[0].text_snippet.as_deref(),
+            Some("fn main() { println!() }")
+        );
+        assert_eq!(buckets[1].bucket_ts, start + Duration::minutes(1));
+        assert_eq!(buckets[1].frame_count, 1);
+        assert_eq!(buckets[1].dominant_app.as_deref(), Some("Slack"));
+
+        let buckets = db
+            .get_timeline(
+                start,
+                start + Duration::hours(1),
+                TimelineResolution::Hour,
+                std::time::Duration::from_secs(30),
+            )
+            .await
+            .unwrap();
+        assert_eq!(buckets.len(), 1);
+        assert_eq!(buckets[0].frame_count, 4);
+    }
+
+    #[tokio::test]
+    async fn test_search_audio_fts() {
+        let db = setup_test_db().await;
+        let device = AudioDevice::new("test".to_string(), DeviceType::Input);
+        for transcription in ["Quarterly budget review", "she said \"hi\" twice", "ok"] {
+            let audio_chunk_id = db.insert_audio_chunk("test_audio.mp4").await.unwrap();
+            db.insert_audio_transcription(audio_chunk_id, transcription, 0, "", &device)
+                .await
+                .unwrap();
+        }
+        let search = |query: &'static str| {
+            let db = &db;
+            async move {
+                db.search_audio(
+                    query,
+                    10,
+                    0,
+                    None,
+                    None,
+                    None,
+                    None,
+                    None,
+                    None,
+                    &SearchOrder::default(),
+                )
+                .await
+                .unwrap()
+                .into_iter()
+                .map(|result| result.transcription)
+                .collect::<Vec<_>>()
+            }
+        };
+
+        assert_eq!(search("BUDGET").await, vec!["Quarterly budget review"]);
+        // trigrams match inside words too
+        assert_eq!(search("rly bud").await, vec!["Quarterly budget review"]);
+        // too short for the index
+        assert_eq!(search("ok").await, vec!["ok"]);
+        assert_eq!(search("\"hi\"").await, vec!["she said \"hi\" twice"]);
+        assert!(search("OR").await.is_empty());
+        assert_eq!(search("").await.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_read_only_database() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("db.sqlite").to_string_lossy().into_owned();
+        let db = DatabaseManager::new(&path).await.unwrap();
+        let _ = db.insert_video_chunk("test_video.mp4").await.unwrap();
+        db.insert_frame().await.unwrap();
+
+        let read_only = DatabaseManager::new_read_only(&path, 2).await.unwrap();
+        assert_eq!(
+            read_only.get_frames(None, None, 10, 0).await.unwrap().len(),
+            1
+        );
+        assert!(read_only.insert_video_chunk("other.mp4").await.is_err());
+
+        // the read-only pool sees what the recorder writes afterwards
+        db.insert_frame().await.unwrap();
+        assert_eq!(
+            read_only.get_frames(None, None, 10, 0).await.unwrap().len(),
+            2
+        );
+    }
+
+    #[tokio::test]
+    async fn test_replace_ocr_text() {
+        let db = setup_test_db().await;
+        let _ = db.insert_video_chunk("test_video.mp4").await.unwrap();
+        let frame_id = db.insert_frame().await.unwrap();
+        db.insert_ocr_text(
+            frame_id,
+            "Helo wrld",
+            "",
+            "Notes",
+            "",
+            Arc::new(OcrEngine::Unstructured),
+            true,
+        )
+        .await
+        .unwrap();
+
+        db.replace_ocr_text(
+            frame_id,
+            "Hello world",
+            "[]",
+            &OcrEngine::Tesseract,
+            Some(0.9),
+        )
+        .await
+        .unwrap();
+        let results = db
+            .search(
+                "Hello",
+                ContentType::OCR,
+                100,
+                0,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                &SearchOrder::default(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(results.len(), 1);
+        if let SearchResult::OCR(ocr_result) = &results[0] {
+            assert_eq!(ocr_result.ocr_engine, "Tesseract");
+            assert_eq!(ocr_result.app_name, "Notes");
+        } else {
+            panic!("Expected OCR result");
+        }
+        let history = db.get_ocr_history(frame_id).await.unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].text, "Helo wrld");
+        assert_eq!(history[0].ocr_engine, "Unstructured");
+        assert_eq!(history[0].confidence, None);
+
+        // a frame stored without text gets its first row, nothing to archive
+        let failed_id = db.insert_frame().await.unwrap();
+        db.replace_ocr_text(failed_id, "retried", "[]", &OcrEngine::Tesseract, None)
+            .await
+            .unwrap();
+        assert!(db.get_ocr_history(failed_id).await.unwrap().is_empty());
+        assert_eq!(db.get_ocr_history(frame_id).await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_replace_ocr_text_of_failed_window() {
+        let db = setup_test_db().await;
+        db.insert_video_chunk("test_video.mp4").await.unwrap();
+        let bounds = WindowBounds {
+            x: 10,
+            y: 20,
+            width: 300,
+            height: 200,
+        };
+        let frame_ids = db
+            .bulk_insert_frames(vec![FrameData {
+                timestamp: Utc::now(),
+                text: String::new(),
+                text_json: String::new(),
+                app_name: "Code".to_string(),
+                window_name: "main.rs".to_string(),
+                ocr_engine: Arc::new(OcrEngine::Tesseract),
+                focused: true,
+                ocr_failed: true,
+                cursor: None,
+                window_bounds: Some(bounds),
+            }])
+            .await
+            .unwrap();
+        assert_eq!(
+            db.get_frame_window(frame_ids[0]).await.unwrap(),
+            Some(FrameWindow {
+                app_name: "Code".to_string(),
+                window_name: "main.rs".to_string(),
+                focused: true,
+                bounds: Some(bounds),
+            })
+        );
+
+        db.replace_ocr_text(
+            frame_ids[0],
+            "fn main() {\n    println!(\"hi\");\n}",
+            "[]",
+            &OcrEngine::Tesseract,
+            None,
+        )
+        .await
+        .unwrap();
+        // classified like a recorded frame of the same window
+        let results = db
+            .search(
+                "println",
+                ContentType::Screen(ScreenContentType::Code),
+                100,
+                0,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                &SearchOrder::default(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(results.len(), 1);
+        if let SearchResult::OCR(ocr_result) = &results[0] {
+            assert_eq!(ocr_result.app_name, "Code");
+            assert_eq!(ocr_result.window_name, "main.rs");
+        } else {
+            panic!("Expected OCR result");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_remote_sync_rows() {
+        let db = setup_test_db().await;
+        let _ = db.insert_video_chunk("test_video.mp4").await.unwrap();
+        let frame = |text: &str, ocr_failed: bool| FrameData {
+            timestamp: Utc::now(),
+            text: text.to_string(),
+            text_json: String::new(),
+            app_name: "Code".to_string(),
+            window_name: "main.rs".to_string(),
+            ocr_engine: Arc::new(OcrEngine::Tesseract),
+            focused: true,
+            ocr_failed,
+            cursor: None,
+            window_bounds: None,
+        };
+        let ids = db
+            .bulk_insert_frames(vec![frame("fn main", false), frame("", true)])
+            .await
+            .unwrap();
+
+        let frames = db.get_frames_to_sync(0, 10).await.unwrap();
+        assert_eq!(frames.len(), 2);
+        assert_eq!(frames[0].text.as_deref(), Some("fn main"));
+        assert_eq!(frames[0].app_name, "Code");
+        assert_eq!(frames[1].text, None);
+        assert!(db.get_frames_to_sync(ids[1], 10).await.unwrap().is_empty());
+
+        let url = "http://desktop:3030";
+        assert_eq!(db.get_remote_sync_state(url).await.unwrap(), (0, 0));
+        db.set_remote_sync_state(url, ids[1], 0).await.unwrap();
+        db.set_remote_sync_state(url, ids[1], 5).await.unwrap();
+        assert_eq!(db.get_remote_sync_state(url).await.unwrap(), (ids[1], 5));
+    }
+
+    #[tokio::test]
+    async fn test_bulk_insert_frames_stores_cursor() {
+        let db = setup_test_db().await;
+        db.insert_video_chunk("test_video.mp4").await.unwrap();
+        let frame = |cursor| FrameData {
+            timestamp: Utc::now(),
+            text: "text".to_string(),
+            text_json: String::new(),
+            app_name: "app".to_string(),
+            window_name: "window".to_string(),
+            ocr_engine: Arc::new(OcrEngine::Tesseract),
+            focused: true,
+            ocr_failed: false,
+            cursor,
+            window_bounds: None,
+        };
+        db.bulk_insert_frames(vec![
+            frame(Some(MousePosition { x: 120, y: 48 })),
+            frame(None),
+        ])
+        .await
+        .unwrap();
+
+        let frames = db.get_frames(None, None, 10, 0).await.unwrap();
+        assert_eq!(
+            (frames[0].cursor_x, frames[0].cursor_y),
+            (Some(120), Some(48))
+        );
+        assert_eq!((frames[1].cursor_x, frames[1].cursor_y), (None, None));
+    }
+
+    #[tokio::test]
+    async fn test_share_claimed_once() {
+        let db = setup_test_db().await;
+        let _ = db.insert_video_chunk("test_video.mp4").await.unwrap();
+        let frame_id = db.insert_frame().await.unwrap();
+        db.insert_ocr_text(
+            frame_id,
+            "quarterly numbers",
+            "",
+            "",
+            "",
+            Arc::new(OcrEngine::Tesseract),
+            false,
+        )
+        .await
+        .unwrap();
+        let now = Utc::now();
+
+        assert!(!db
+            .create_share("missing", frame_id + 1, now + Duration::hours(1))
+            .await
+            .unwrap());
+        assert!(db
+            .create_share("once", frame_id, now + Duration::hours(1))
+            .await
+            .unwrap());
+        assert!(db
+            .create_share("stale", frame_id, now - Duration::seconds(1))
+            .await
+            .unwrap());
+
+        assert_eq!(
+            db.find_share("once", now).await.unwrap(),
+            Some(ShareClaim::Frame(frame_id))
+        );
+        assert_eq!(
+            db.claim_share("once", now).await.unwrap(),
+            Some(ShareClaim::Frame(frame_id))
+        );
+        assert_eq!(
+            db.claim_share("once", now).await.unwrap(),
+            Some(ShareClaim::Used)
+        );
+        assert_eq!(
+            db.find_share("once", now).await.unwrap(),
+            Some(ShareClaim::Used)
+        );
+        assert_eq!(
+            db.find_share("stale", now).await.unwrap(),
+            Some(ShareClaim::Expired)
+        );
+        assert_eq!(
+            db.claim_share("stale", now).await.unwrap(),
+            Some(ShareClaim::Expired)
+        );
+        assert_eq!(db.claim_share("missing", now).await.unwrap(), None);
+
+        let frame = db.get_frame_text(frame_id).await.unwrap().unwrap();
+        assert_eq!(frame.text, "quarterly numbers");
+        assert_eq!(frame.file_path, "test_video.mp4");
+    }
 }
@@ -0,0 +1,42 @@
+use screenpipe_server::{check_deep_health, DatabaseManager};
+use screenpipe_vision::OcrEngine;
+use std::sync::Arc;
+
+#[tokio::test]
+async fn test_deep_health_disabled_subsystems() {
+    let db = Arc::new(DatabaseManager::new("sqlite::memory:").await.unwrap());
+    let health = check_deep_health(Arc::clone(&db), OcrEngine::Tesseract, true, true).await;
+
+    assert!(health.healthy);
+    assert!(health.database.ok);
+    assert!(health.database.error.is_none());
+    assert!(health.capture.is_none());
+    assert!(health.ocr.is_none());
+    assert!(health.audio.is_none());
+
+    // the row written by the check is rolled back
+    let chunks: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM video_chunks")
+        .fetch_one(&db.pool)
+        .await
+        .unwrap();
+    assert_eq!(chunks, 0);
+
+    let json = serde_json::to_value(&health).unwrap();
+    assert!(json["database"]["latency_ms"].is_u64());
+    assert!(json["capture"].is_null());
+    assert!(json["database"]["skipped"].is_null());
+}
+
+#[tokio::test]
+async fn test_deep_health_read_only_skips_write_check() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("db.sqlite");
+    let path = path.to_string_lossy();
+    DatabaseManager::new(&path).await.unwrap();
+    let db = Arc::new(DatabaseManager::new_read_only(&path, 1).await.unwrap());
+
+    let health = check_deep_health(db, OcrEngine::Tesseract, true, true).await;
+    assert!(health.healthy);
+    assert!(health.database.ok, "{:?}", health.database.error);
+    assert!(health.database.skipped.is_some());
+}
@@ -0,0 +1,35 @@
+use screenpipe_audio::{AudioDevice, DeviceControl, DeviceType};
+use screenpipe_server::merge_discovered_devices;
+use std::collections::HashMap;
+
+#[test]
+fn test_merge_discovered_devices() {
+    let mic = AudioDevice::new("Built-in Microphone".to_string(), DeviceType::Input);
+    let usb = AudioDevice::new("USB Audio Interface".to_string(), DeviceType::Input);
+    let mut devices = HashMap::from([(
+        mic.clone(),
+        DeviceControl {
+            is_running: true,
+            is_paused: false,
+        },
+    )]);
+
+    // plugged in after startup
+    let (added, removed) = merge_discovered_devices(&mut devices, vec![mic.clone(), usb.clone()]);
+    assert_eq!(added, vec![usb.to_string()]);
+    assert!(removed.is_empty());
+    assert!(!devices[&usb].is_running);
+    assert!(devices[&mic].is_running);
+
+    // unplugged again, kept but reported once
+    let (added, removed) = merge_discovered_devices(&mut devices, vec![mic.clone()]);
+    assert!(added.is_empty());
+    assert_eq!(removed, vec![usb.to_string()]);
+    assert_eq!(devices.len(), 2);
+    let (_, removed) = merge_discovered_devices(&mut devices, vec![mic.clone()]);
+    assert!(removed.is_empty());
+
+    // back, neither added nor removed
+    let (added, removed) = merge_discovered_devices(&mut devices, vec![mic, usb]);
+    assert!(added.is_empty() && removed.is_empty());
+}
@@ -1,24 +1,38 @@
+mod common;
+
 #[cfg(test)]
 mod tests {
+    use crate::common::test_app_state;
     use axum::body::to_bytes;
     use axum::body::Body;
+    use axum::http::header::CONTENT_TYPE;
     use axum::http::{Request, StatusCode};
     use axum::Router;
     use chrono::DateTime;
     use chrono::{Duration, Utc};
-    use crossbeam::queue::SegQueue;
-    use screenpipe_audio::{AudioDevice, DeviceType};
+    use screenpipe_audio::{
+        encode_single_audio, read_audio_file, AudioDevice, AudioFormat, DeviceType,
+    };
     use screenpipe_server::ContentType;
+    use screenpipe_server::RandomFrameItem;
+    use screenpipe_server::RuntimeConfigResponse;
     use screenpipe_server::SearchResult;
     use screenpipe_server::{
-        create_router, AppState, ContentItem, DatabaseManager, PaginatedResponse,
+        create_router, reject_writes, with_request_tracing, with_security_headers, AppState,
+        ContentItem, DatabaseManager, PaginatedResponse, REQUEST_ID_HEADER,
+    };
+    use screenpipe_server::{create_router_with_limits, is_logged_path, BodyLimits};
+    use screenpipe_server::{new_share_token, ShareResponse};
+    use screenpipe_server::{
+        sign_payload, ImportedRows, SyncBatch, SyncedFrame, SyncedTranscription, SIGNATURE_HEADER,
     };
-    use screenpipe_server::{HealthCheckResponse, PipeManager};
+    use screenpipe_server::{with_version_headers, VersionResponse, API_VERSION, SERVER_VERSION};
+    use screenpipe_server::{HealthCheckResponse, RecordingStats};
+    use screenpipe_server::{MediaKind, MediaSigner, DEFAULT_SIGNED_URL_TTL};
+    use screenpipe_server::{RankWeights, SearchOrder, TranscriptCorrection, TranscriptSource};
     use screenpipe_vision::OcrEngine; // Adjust this import based on your actual module structure
     use serde::Deserialize;
-    use std::collections::HashMap;
-    use std::path::PathBuf;
-    use std::sync::atomic::AtomicBool;
+    use std::sync::atomic::Ordering;
     use std::sync::Arc;
     use tower::ServiceExt; // for `oneshot` and `ready`
 
@@ -27,21 +41,16 @@ mod tests {
     struct TestErrorResponse {
         error: String,
     }
+    const TEST_SYNC_SECRET: &str = "test-secret";
+
     async fn setup_test_app() -> (Router, Arc<AppState>) {
         // env_logger::builder()
         //     .filter_level(LevelFilter::Debug)
         //     .init();
         let db = Arc::new(DatabaseManager::new("sqlite::memory:").await.unwrap());
         let app_state = Arc::new(AppState {
-            db: db.clone(),
-            vision_control: Arc::new(AtomicBool::new(false)),
-            audio_devices_control: Arc::new(SegQueue::new()),
-            devices_status: HashMap::new(),
-            app_start_time: Utc::now(),
-            screenpipe_dir: PathBuf::from(""),
-            pipe_manager: Arc::new(PipeManager::new(PathBuf::from(""))),
-            vision_disabled: false,
-            audio_disabled: false,
+            remote_sync_secret: Some(TEST_SYNC_SECRET.to_string()),
+            ..test_app_state(db)
         });
 
         let router = create_router();
@@ -404,14 +413,34 @@ mod tests {
 
         // Test counting all results
         let count = db
-            .count_search_results("test", ContentType::All, None, None, None, None, None, None)
+            .count_search_results(
+                "test",
+                ContentType::All,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+            )
             .await
             .unwrap();
         assert_eq!(count, 4);
 
         // Test counting only OCR results
         let count = db
-            .count_search_results("OCR", ContentType::OCR, None, None, None, None, None, None)
+            .count_search_results(
+                "OCR",
+                ContentType::OCR,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+            )
             .await
             .unwrap();
         assert_eq!(count, 2);
@@ -427,6 +456,7 @@ mod tests {
                 None,
                 None,
                 None,
+                None,
             )
             .await
             .unwrap();
@@ -443,6 +473,7 @@ mod tests {
                 None,
                 None,
                 None,
+                None,
             )
             .await
             .unwrap();
@@ -459,6 +490,7 @@ mod tests {
                 Some("TestWindow2"),
                 None,
                 None,
+                None,
             )
             .await
             .unwrap();
@@ -475,6 +507,7 @@ mod tests {
                 None,
                 Some(30),
                 None,
+                None,
             )
             .await
             .unwrap();
@@ -491,6 +524,7 @@ mod tests {
                 None,
                 None,
                 Some(25),
+                None,
             )
             .await
             .unwrap();
@@ -564,6 +598,9 @@ mod tests {
                 None,
                 None,
                 None,
+                None,
+                None,
+                &SearchOrder::default(),
             )
             .await
             .unwrap();
@@ -581,6 +618,9 @@ mod tests {
                 None,
                 None,
                 None,
+                None,
+                None,
+                &SearchOrder::default(),
             )
             .await
             .unwrap();
@@ -599,6 +639,9 @@ mod tests {
                 None,
                 None,
                 None,
+                None,
+                None,
+                &SearchOrder::default(),
             )
             .await
             .unwrap();
@@ -621,6 +664,9 @@ mod tests {
                 None,
                 None,
                 None,
+                None,
+                None,
+                &SearchOrder::default(),
             )
             .await
             .unwrap();
@@ -642,6 +688,7 @@ mod tests {
                 None,
                 None,
                 None,
+                None,
             )
             .await
             .unwrap();
@@ -657,9 +704,1650 @@ mod tests {
                 None,
                 None,
                 None,
+                None,
             )
             .await
             .unwrap();
         assert_eq!(audio_count, 1);
     }
+
+    #[tokio::test]
+    async fn test_stats_endpoint() {
+        let (app, state) = setup_test_app().await;
+        let db = &state.db;
+
+        let _ = db.insert_video_chunk("test_video.mp4").await.unwrap();
+        let frame_id = db.insert_frame().await.unwrap();
+        db.insert_ocr_text(
+            frame_id,
+            "hello stats",
+            "",
+            "",
+            "",
+            Arc::new(OcrEngine::Tesseract),
+            false,
+        )
+        .await
+        .unwrap();
+        let _ = db.insert_audio_chunk("test_audio.mp4").await.unwrap();
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/stats")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let stats: RecordingStats = serde_json::from_slice(&body).unwrap();
+
+        assert_eq!(stats.total_frames, 1);
+        assert_eq!(stats.total_audio_chunks, 1);
+        assert_eq!(stats.total_audio_duration_secs, 30.0);
+        assert_eq!(stats.total_ocr_entries, 1);
+        assert_eq!(stats.total_ocr_text_chars, "hello stats".len() as i64);
+    }
+
+    #[tokio::test]
+    async fn test_frames_endpoint_thumbnails() {
+        let (app, state) = setup_test_app().await;
+        let db = &state.db;
+
+        let _ = db.insert_video_chunk("test_video.mp4").await.unwrap();
+        let frame_id1 = db.insert_frame().await.unwrap();
+        let frame_id2 = db.insert_frame().await.unwrap();
+
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri("/frames?thumb=true")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let frames: Vec<serde_json::Value> = serde_json::from_slice(&body).unwrap();
+
+        assert_eq!(frames.len(), 2);
+        assert_eq!(frames[0]["frame_id"], frame_id1);
+        assert_eq!(
+            frames[1]["thumbnail_url"],
+            format!("/frames/{}/thumbnail", frame_id2)
+        );
+        assert!(frames[0].get("file_path").is_none());
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/frames?limit=1")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let frames: Vec<serde_json::Value> = serde_json::from_slice(&body).unwrap();
+
+        assert_eq!(frames.len(), 1);
+        assert_eq!(frames[0]["file_path"], "test_video.mp4");
+        assert!(frames[0].get("thumbnail_url").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_export_video_without_frames() {
+        let (app, _) = setup_test_app().await;
+
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/export/video")
+                    .header("Content-Type", "application/json")
+                    .body(Body::from(
+                        serde_json::json!({
+                            "from": "2024-01-01T00:00:00Z",
+                            "to": "2024-01-01T01:00:00Z",
+                            "fps": 1.0,
+                            "monitor_id": 1
+                        })
+                        .to_string(),
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/export/jobs/does-not-exist")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[test]
+    fn test_health_polls_are_not_logged() {
+        for path in ["/health", "/health/deep", "/stats"] {
+            assert!(!is_logged_path(path), "{} is logged", path);
+        }
+        for path in ["/search", "/frames/:frame_id/thumbnail", "/slow-queries"] {
+            assert!(is_logged_path(path), "{} is not logged", path);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_slow_queries_endpoint() {
+        let (app, state) = setup_test_app().await;
+        let db = &state.db;
+
+        db.insert_request_log("/search", "GET", 200, 750.0)
+            .await
+            .unwrap();
+        db.insert_request_log("/health", "GET", 200, 2.0)
+            .await
+            .unwrap();
+        db.insert_request_log("/frames", "GET", 500, 120.0)
+            .await
+            .unwrap();
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/slow-queries?limit=2")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let entries: Vec<serde_json::Value> = serde_json::from_slice(&body).unwrap();
+
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0]["path"], "/search");
+        assert_eq!(entries[0]["duration_ms"], 750.0);
+        assert_eq!(entries[1]["path"], "/frames");
+        assert_eq!(entries[1]["status"], 500);
+    }
+
+    #[tokio::test]
+    async fn test_delete_frames_by_app() {
+        let (app, state) = setup_test_app().await;
+        let db = &state.db;
+
+        let _ = db.insert_video_chunk("test_video.mp4").await.unwrap();
+        for app_name in ["Signal", "Terminal"] {
+            let frame_id = db.insert_frame().await.unwrap();
+            db.insert_ocr_text(
+                frame_id,
+                "secret",
+                "",
+                app_name,
+                "",
+                Arc::new(OcrEngine::Tesseract),
+                false,
+            )
+            .await
+            .unwrap();
+        }
+
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method("DELETE")
+                    .uri("/frames?app_name=signal")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method("DELETE")
+                    .uri("/frames?app_name=signal&confirm=true")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let deleted: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(deleted["deleted_rows"], 1);
+        // the video chunk is still in use by the remaining frame
+        assert_eq!(deleted["deleted_files"], 0);
+
+        let frames = db.get_frames(None, None, 10, 0).await.unwrap();
+        assert_eq!(frames.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_delete_audio_removes_files() {
+        let (app, state) = setup_test_app().await;
+        let db = &state.db;
+
+        let dir = tempfile::tempdir().unwrap();
+        let audio_path = dir.path().join("test_audio.mp4");
+        std::fs::write(&audio_path, vec![0u8; 1024]).unwrap();
+        let audio_chunk_id = db
+            .insert_audio_chunk(audio_path.to_str().unwrap())
+            .await
+            .unwrap();
+        db.insert_audio_transcription(
+            audio_chunk_id,
+            "delete me",
+            0,
+            "",
+            &AudioDevice::new("test".to_string(), DeviceType::Input),
+        )
+        .await
+        .unwrap();
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method("DELETE")
+                    .uri("/audio")
+                    .header("X-Confirm", "true")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let deleted: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(deleted["deleted_rows"], 1);
+        assert_eq!(deleted["deleted_files"], 1);
+        assert_eq!(deleted["freed_bytes"], 1024);
+        assert!(!audio_path.exists());
+
+        let count = db
+            .count_search_results(
+                "",
+                ContentType::Audio,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+        assert_eq!(count, 0);
+    }
+
+    #[tokio::test]
+    async fn test_search_cursor_pagination() {
+        let (app, state) = setup_test_app().await;
+        let db = &state.db;
+
+        let _ = db.insert_video_chunk("test_video.mp4").await.unwrap();
+        let mut frame_ids = Vec::new();
+        for i in 0..3 {
+            let frame_id = db.insert_frame().await.unwrap();
+            db.insert_ocr_text(
+                frame_id,
+                &format!("cursor page {}", i),
+                "",
+                "",
+                "",
+                Arc::new(OcrEngine::Tesseract),
+                false,
+            )
+            .await
+            .unwrap();
+            frame_ids.push(frame_id);
+        }
+
+        let search = |uri: String| {
+            let app = app.clone();
+            async move {
+                let response = app
+                    .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+                    .await
+                    .unwrap();
+                let status = response.status();
+                let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+                (status, body)
+            }
+        };
+
+        let (status, body) = search("/search?q=cursor&content_type=ocr&limit=2".to_string()).await;
+        assert_eq!(status, StatusCode::OK);
+        let page: PaginatedResponse<ContentItem> = serde_json::from_slice(&body).unwrap();
+        assert_eq!(page.data.len(), 2);
+        let cursor = page.pagination.next_cursor.expect("expected a next cursor");
+
+        let (status, body) = search(format!(
+            "/search?q=cursor&content_type=ocr&limit=2&cursor={}",
+            cursor
+        ))
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        let page: PaginatedResponse<ContentItem> = serde_json::from_slice(&body).unwrap();
+        assert_eq!(page.data.len(), 1);
+        if let ContentItem::OCR(ocr) = &page.data[0] {
+            // newest first, so the oldest frame is last
+            assert_eq!(ocr.frame_id, frame_ids[0]);
+        } else {
+            panic!("Expected OCR content");
+        }
+        assert!(page.pagination.next_cursor.is_none());
+
+        let (status, _) = search("/search?q=cursor&cursor=not-a-cursor".to_string()).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_search_sort_by_rank_and_app_name() {
+        let (app, state) = setup_test_app().await;
+        let db = &state.db;
+        // relevance only, the frames are all recorded now
+        *state.rank_weights.write().unwrap() = RankWeights {
+            relevance: 1.0,
+            recency: 0.0,
+        };
+
+        let _ = db.insert_video_chunk("test_video.mp4").await.unwrap();
+        let texts = [
+            ("b", "ranked apple"),
+            ("a", "ranked apple apple apple"),
+            ("c", "ranked melon"),
+            ("d", "noise"),
+            ("d", "more noise"),
+            ("d", "still noise"),
+        ];
+        let mut frame_ids = Vec::new();
+        for (app_name, text) in texts {
+            let frame_id = db.insert_frame().await.unwrap();
+            db.insert_ocr_text(
+                frame_id,
+                text,
+                "",
+                app_name,
+                "",
+                Arc::new(OcrEngine::Tesseract),
+                false,
+            )
+            .await
+            .unwrap();
+            frame_ids.push(frame_id);
+        }
+
+        let search = |uri: String| {
+            let app = app.clone();
+            async move {
+                let response = app
+                    .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+                    .await
+                    .unwrap();
+                let status = response.status();
+                let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+                (status, body)
+            }
+        };
+        let ocr = |item: &ContentItem| match item {
+            ContentItem::OCR(ocr) => (ocr.frame_id, ocr.app_name.clone(), ocr.rank.unwrap()),
+            _ => panic!("Expected OCR content"),
+        };
+
+        let (status, body) =
+            search("/search?q=apple&content_type=ocr&sort_by=rank".to_string()).await;
+        assert_eq!(status, StatusCode::OK);
+        let page: PaginatedResponse<ContentItem> = serde_json::from_slice(&body).unwrap();
+        let ranked: Vec<_> = page.data.iter().map(ocr).collect();
+        assert_eq!(ranked.len(), 2);
+        assert_eq!(ranked[0].0, frame_ids[1]);
+        assert!(ranked[0].2 > ranked[1].2);
+        assert!(ranked.iter().all(|(_, _, rank)| (0.0..=1.0).contains(rank)));
+        // other orders than the default are paged with offset
+        assert!(page.pagination.next_cursor.is_none());
+
+        let (_, body) =
+            search("/search?q=ranked&content_type=ocr&sort_by=app_name&order=asc".to_string())
+                .await;
+        let page: PaginatedResponse<ContentItem> = serde_json::from_slice(&body).unwrap();
+        let apps: Vec<_> = page.data.iter().map(|item| ocr(item).1).collect();
+        assert_eq!(apps, vec!["a", "b", "c"]);
+
+        let (_, body) = search("/search?q=ranked&content_type=ocr&limit=1".to_string()).await;
+        let page: PaginatedResponse<ContentItem> = serde_json::from_slice(&body).unwrap();
+        let cursor = page.pagination.next_cursor.expect("expected a next cursor");
+        let (status, _) = search(format!(
+            "/search?q=ranked&content_type=ocr&limit=1&order=asc&cursor={}",
+            cursor
+        ))
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        let (status, _) = search("/search?q=ranked&sort_by=relevance".to_string()).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_audio_waveform_endpoint() {
+        let (app, state) = setup_test_app().await;
+
+        // 1000 mono 16 bit samples ramping from -0.5 to 0.5
+        let samples: Vec<i16> = (0..1000)
+            .map(|i| ((i as f32 / 999.0 - 0.5) * i16::MAX as f32) as i16)
+            .collect();
+        let data_len = (samples.len() * 2) as u32;
+        let mut wav = Vec::new();
+        wav.extend_from_slice(b"RIFF");
+        wav.extend_from_slice(&(36 + data_len).to_le_bytes());
+        wav.extend_from_slice(b"WAVEfmt ");
+        wav.extend_from_slice(&16u32.to_le_bytes());
+        wav.extend_from_slice(&1u16.to_le_bytes()); // pcm
+        wav.extend_from_slice(&1u16.to_le_bytes()); // channels
+        wav.extend_from_slice(&16000u32.to_le_bytes());
+        wav.extend_from_slice(&32000u32.to_le_bytes());
+        wav.extend_from_slice(&2u16.to_le_bytes());
+        wav.extend_from_slice(&16u16.to_le_bytes());
+        wav.extend_from_slice(b"data");
+        wav.extend_from_slice(&data_len.to_le_bytes());
+        for sample in samples {
+            wav.extend_from_slice(&sample.to_le_bytes());
+        }
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("audio.wav");
+        std::fs::write(&path, wav).unwrap();
+        let audio_chunk_id = state
+            .db
+            .insert_audio_chunk(&path.to_string_lossy())
+            .await
+            .unwrap();
+
+        let get = |uri: String| {
+            let app = app.clone();
+            async move {
+                let response = app
+                    .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+                    .await
+                    .unwrap();
+                let status = response.status();
+                let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+                (status, body)
+            }
+        };
+
+        let (status, body) = get(format!("/audio/{}/waveform?samples=4", audio_chunk_id)).await;
+        assert_eq!(status, StatusCode::OK);
+        let waveform: Vec<[f32; 2]> = serde_json::from_slice(&body).unwrap();
+        assert_eq!(waveform.len(), 4);
+        assert!((waveform[0][0] + 0.5).abs() < 0.01);
+        assert!((waveform[3][1] - 0.5).abs() < 0.01);
+        assert!(waveform.iter().all(|[min, max]| min <= max));
+
+        let (status, _) = get(format!("/audio/{}/waveform?samples=0", audio_chunk_id)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        let (status, _) = get("/audio/424242/waveform".to_string()).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_runtime_config_endpoints() {
+        let (app, state) = setup_test_app().await;
+
+        let patch = |body: &'static str| {
+            let app = app.clone();
+            async move {
+                app.oneshot(
+                    Request::builder()
+                        .method("PATCH")
+                        .uri("/config")
+                        .header("content-type", "application/json")
+                        .body(Body::from(body))
+                        .unwrap(),
+                )
+                .await
+                .unwrap()
+            }
+        };
+
+        let response = patch(r#"{"fps": 0.5, "dedup_threshold": 0.02}"#).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(state.capture_config.read().unwrap().fps, 0.5);
+        assert_eq!(state.capture_config.read().unwrap().dedup_threshold, 0.02);
+
+        // nothing is applied when one field is invalid
+        let response = patch(r#"{"fps": 2.0, "dedup_threshold": 3.0}"#).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(state.capture_config.read().unwrap().fps, 0.5);
+        let response = patch(r#"{"ocr_engine": "nonexistent"}"#).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let response = patch(r#"{"rank_relevance_weight": -1.0}"#).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let response = patch(r#"{"rank_relevance_weight": 0.8, "rank_recency_weight": 0.2}"#).await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/config")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let config: RuntimeConfigResponse = serde_json::from_slice(&body).unwrap();
+        assert_eq!(
+            config,
+            RuntimeConfigResponse {
+                fps: 0.5,
+                dedup_threshold: 0.02,
+                ocr_engine: "tesseract".to_string(),
+                rank_relevance_weight: 0.8,
+                rank_recency_weight: 0.2,
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_security_headers() {
+        let (_, state) = setup_test_app().await;
+        let app = with_security_headers(create_router()).with_state(state);
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/config")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let headers = response.headers();
+        assert_eq!(headers["x-content-type-options"], "nosniff");
+        assert_eq!(headers["x-frame-options"], "DENY");
+        assert_eq!(headers["content-security-policy"], "default-src 'none'");
+        assert_eq!(headers["referrer-policy"], "no-referrer");
+
+        // images, videos and audio are served as is
+        let app: Router = with_security_headers(Router::new().route(
+            "/frame.png",
+            axum::routing::get(|| async { ([(CONTENT_TYPE, "image/png")], vec![0u8; 4]) }),
+        ));
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/frame.png")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(response.headers().get("content-security-policy").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_version() {
+        let (_, state) = setup_test_app().await;
+        let app = with_version_headers(create_router()).with_state(state);
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/version")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()["x-screenpipe-version"], SERVER_VERSION);
+        assert_eq!(response.headers()["x-screenpipe-api-version"], "1");
+
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let version: VersionResponse = serde_json::from_slice(&body).unwrap();
+        assert_eq!(version.server_version, env!("CARGO_PKG_VERSION"));
+        assert_eq!(version.api_version, API_VERSION);
+        assert!(!version.git_sha.is_empty());
+        assert!(version.build_timestamp.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_request_id_header() {
+        let (_, state) = setup_test_app().await;
+        let app = with_request_tracing(create_router()).with_state(state);
+
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri("/config")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let generated = response.headers()[REQUEST_ID_HEADER].to_str().unwrap();
+        assert!(uuid::Uuid::parse_str(generated).is_ok());
+
+        // an id sent by the client is kept
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/config")
+                    .header(REQUEST_ID_HEADER, "trace-42")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.headers()[REQUEST_ID_HEADER], "trace-42");
+    }
+
+    #[tokio::test]
+    async fn test_graphql_search_and_pause() {
+        let (app, state) = setup_test_app().await;
+        let db = &state.db;
+        state.vision_control.store(true, Ordering::SeqCst);
+
+        let _ = db.insert_video_chunk("test_video.mp4").await.unwrap();
+        let frame_id = db.insert_frame().await.unwrap();
+        db.insert_ocr_text(
+            frame_id,
+            "graphql roadmap",
+            "",
+            "Notes",
+            "",
+            Arc::new(OcrEngine::Tesseract),
+            false,
+        )
+        .await
+        .unwrap();
+
+        let graphql = |query: &str| {
+            Request::builder()
+                .method("POST")
+                .uri("/graphql")
+                .header("Content-Type", "application/json")
+                .body(Body::from(
+                    serde_json::json!({ "query": query }).to_string(),
+                ))
+                .unwrap()
+        };
+
+        let response = app
+            .clone()
+            .oneshot(graphql(
+                r#"{ searchFrames(query: "roadmap", limit: 5) { frameId text appName } searchAudio(query: "roadmap") { transcription } }"#,
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert!(json.get("errors").is_none(), "{}", json);
+        assert_eq!(
+            json["data"]["searchFrames"],
+            serde_json::json!([{ "frameId": frame_id, "text": "graphql roadmap", "appName": "Notes" }])
+        );
+        assert_eq!(json["data"]["searchAudio"], serde_json::json!([]));
+
+        let response = app
+            .oneshot(graphql("mutation { pauseRecording }"))
+            .await
+            .unwrap();
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["data"]["pauseRecording"], true);
+        assert!(!state.vision_control.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test]
+    async fn test_read_only_rejects_writes() {
+        let (_, state) = setup_test_app().await;
+        let app = create_router()
+            .layer(axum::middleware::from_fn(reject_writes))
+            .with_state(state);
+
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri("/config")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        for (method, uri) in [
+            ("PATCH", "/config"),
+            ("DELETE", "/frames"),
+            ("POST", "/graphql"),
+        ] {
+            let response = app
+                .clone()
+                .oneshot(
+                    Request::builder()
+                        .method(method)
+                        .uri(uri)
+                        .body(Body::empty())
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+            assert_eq!(
+                response.status(),
+                StatusCode::METHOD_NOT_ALLOWED,
+                "{} {}",
+                method,
+                uri
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_recording_start_stop() {
+        let (app, state) = setup_test_app().await;
+        let post = |uri: &str| {
+            Request::builder()
+                .method("POST")
+                .uri(uri)
+                .body(Body::empty())
+                .unwrap()
+        };
+
+        let stopped = {
+            let recording = Arc::clone(&state.recording);
+            tokio::spawn(async move { recording.stopped().await })
+        };
+        let response = app.clone().oneshot(post("/recording/stop")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({"recording": false, "changed": true})
+        );
+        tokio::time::timeout(std::time::Duration::from_secs(1), stopped)
+            .await
+            .unwrap()
+            .unwrap();
+
+        // stopping twice changes nothing
+        let response = app.clone().oneshot(post("/recording/stop")).await.unwrap();
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["changed"], false);
+
+        let response = app.oneshot(post("/recording/start")).await.unwrap();
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({"recording": true, "changed": true})
+        );
+        assert!(state.recording.is_running());
+    }
+
+    #[tokio::test]
+    async fn test_search_frames_inline_text() {
+        let (app, state) = setup_test_app().await;
+        let _ = state.db.insert_video_chunk("test_video.mp4").await.unwrap();
+        let long_text = format!("invoice {}", "é".repeat(600));
+        for text in ["invoice 42", long_text.as_str()] {
+            let frame_id = state.db.insert_frame().await.unwrap();
+            state
+                .db
+                .insert_ocr_text(
+                    frame_id,
+                    text,
+                    "",
+                    "Mail",
+                    "inbox",
+                    Arc::new(OcrEngine::Tesseract),
+                    false,
+                )
+                .await
+                .unwrap();
+        }
+        let search = |uri: &str| {
+            let app = app.clone();
+            let request = Request::builder().uri(uri).body(Body::empty()).unwrap();
+            async move {
+                let response = app.oneshot(request).await.unwrap();
+                assert_eq!(response.status(), StatusCode::OK);
+                let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+                serde_json::from_slice::<Vec<serde_json::Value>>(&body).unwrap()
+            }
+        };
+
+        let frames = search("/frames/search?q=invoice").await;
+        assert_eq!(frames.len(), 2);
+        assert!(frames.iter().all(|frame| frame.get("ocr_text").is_none()));
+        assert_eq!(frames[0]["app_name"], "Mail");
+
+        let frames = search("/frames/search?q=invoice&inline_text=true").await;
+        let short = frames.iter().find(|f| f["truncated"] == false).unwrap();
+        assert_eq!(short["ocr_text"], "invoice 42");
+        let long = frames.iter().find(|f| f["truncated"] == true).unwrap();
+        assert_eq!(long["ocr_text"].as_str().unwrap().chars().count(), 500);
+    }
+
+    #[tokio::test]
+    async fn test_get_summary_errors() {
+        let (app, _) = setup_test_app().await;
+        for (uri, status) in [
+            ("/summaries/yesterday", StatusCode::BAD_REQUEST),
+            ("/summaries/1999-01-01", StatusCode::NOT_FOUND),
+        ] {
+            let response = app
+                .clone()
+                .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+                .await
+                .unwrap();
+            assert_eq!(response.status(), status);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_export_frames_csv() {
+        let (app, state) = setup_test_app().await;
+        let _ = state.db.insert_video_chunk("test_video.mp4").await.unwrap();
+        for text in ["total: 42", "say \"hi\"\nbye"] {
+            let frame_id = state.db.insert_frame().await.unwrap();
+            state
+                .db
+                .insert_ocr_text(
+                    frame_id,
+                    text,
+                    "",
+                    "Excel",
+                    "Budget, 2024",
+                    Arc::new(OcrEngine::Tesseract),
+                    true,
+                )
+                .await
+                .unwrap();
+        }
+        let from = (Utc::now() - Duration::hours(1)).format("%Y-%m-%dT%H:%M:%SZ");
+        let to = (Utc::now() + Duration::hours(1)).format("%Y-%m-%dT%H:%M:%SZ");
+        let get = |columns: &str| {
+            Request::builder()
+                .uri(format!(
+                    "/frames/export/csv?from={}&to={}&columns={}",
+                    from, to, columns
+                ))
+                .body(Body::empty())
+                .unwrap()
+        };
+
+        let response = app
+            .clone()
+            .oneshot(get("app_name,window_title,ocr_text"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[CONTENT_TYPE], "text/csv; charset=utf-8");
+        let disposition = response.headers()["content-disposition"].to_str().unwrap();
+        assert!(disposition.starts_with("attachment; filename=frames_"));
+        assert!(disposition.ends_with(".csv"));
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        assert_eq!(
+            String::from_utf8(body.to_vec()).unwrap(),
+            "app_name,window_title,ocr_text\r\n\
+             Excel,\"Budget, 2024\",total: 42\r\n\
+             Excel,\"Budget, 2024\",\"say \"\"hi\"\"\nbye\"\r\n"
+        );
+
+        let response = app.clone().oneshot(get("app_name,password")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_import_remote_rows() {
+        let (app, state) = setup_test_app().await;
+        let batch = SyncBatch {
+            source: "laptop".to_string(),
+            frames: vec![SyncedFrame {
+                id: 7,
+                timestamp: Utc::now(),
+                text: Some("remote invoice".to_string()),
+                text_json: String::new(),
+                app_name: "Mail".to_string(),
+                window_name: "Inbox".to_string(),
+                ocr_engine: "Tesseract".to_string(),
+                focused: true,
+            }],
+            transcriptions: vec![SyncedTranscription {
+                id: 3,
+                timestamp: Utc::now(),
+                transcription: "remote meeting".to_string(),
+                transcription_engine: "WhisperLargeV3Turbo".to_string(),
+                device: "Microphone".to_string(),
+                is_input_device: true,
+            }],
+        };
+        let body = serde_json::to_vec(&batch).unwrap();
+        let import = |signature: String| {
+            Request::builder()
+                .method("POST")
+                .uri("/import")
+                .header(CONTENT_TYPE, "application/json")
+                .header(SIGNATURE_HEADER, signature)
+                .body(Body::from(body.clone()))
+                .unwrap()
+        };
+
+        let forged = import(sign_payload("other-secret", &body));
+        let response = app.clone().oneshot(forged).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        let signature = sign_payload(TEST_SYNC_SECRET, &body);
+        for expected in [1, 0] {
+            // sent again after a lost response, nothing is imported twice
+            let response = app
+                .clone()
+                .oneshot(import(signature.clone()))
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+            let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+            let imported: ImportedRows = serde_json::from_slice(&body).unwrap();
+            assert_eq!(imported.frames, expected);
+            assert_eq!(imported.transcriptions, expected);
+        }
+
+        let results = state
+            .db
+            .search(
+                "remote",
+                ContentType::All,
+                10,
+                0,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                &SearchOrder::default(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(results.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_reprocess_frame_ocr_errors() {
+        let (app, state) = setup_test_app().await;
+        let _ = state.db.insert_video_chunk("deleted.mp4").await.unwrap();
+        let frame_id = state.db.insert_frame().await.unwrap();
+        for (uri, status) in [
+            (
+                format!("/ocr/reprocess/{}?engine=paper", frame_id),
+                StatusCode::BAD_REQUEST,
+            ),
+            (
+                "/ocr/reprocess/9999?engine=unstructured".to_string(),
+                StatusCode::NOT_FOUND,
+            ),
+            (
+                format!("/ocr/reprocess/{}?engine=unstructured", frame_id),
+                StatusCode::NOT_FOUND,
+            ),
+        ] {
+            let response = app
+                .clone()
+                .oneshot(
+                    Request::builder()
+                        .method("POST")
+                        .uri(uri)
+                        .body(Body::empty())
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+            assert_eq!(response.status(), status);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_get_audio_chunk_missing_file() {
+        let (app, state) = setup_test_app().await;
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("chunk.wav");
+        std::fs::write(&path, b"RIFF").unwrap();
+        let audio_chunk_id = state
+            .db
+            .insert_audio_chunk(&path.to_string_lossy())
+            .await
+            .unwrap();
+        let url = state.media_signer.url(MediaKind::Audio, audio_chunk_id);
+        let get = || Request::builder().uri(&url).body(Body::empty()).unwrap();
+
+        let response = app.clone().oneshot(get()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()["content-type"], "audio/wav");
+        assert_eq!(response.headers()["content-length"], "4");
+        let etag = response.headers()["etag"].clone();
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        assert_eq!(&body[..], b"RIFF");
+
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri(&url)
+                    .header("if-none-match", etag)
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+
+        std::fs::remove_file(&path).unwrap();
+        let response = app.clone().oneshot(get()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert!(json["error"].as_str().unwrap().contains("was deleted"));
+        assert_eq!(
+            state.db.get_audio_chunk_file(audio_chunk_id).await.unwrap(),
+            Some((path.to_string_lossy().into_owned(), true))
+        );
+
+        // e.g. a backup restored, the flag is cleared on the next request
+        std::fs::write(&path, b"RIFF").unwrap();
+        let response = app.oneshot(get()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            state.db.get_audio_chunk_file(audio_chunk_id).await.unwrap(),
+            Some((path.to_string_lossy().into_owned(), false))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_signed_media_urls() {
+        let (app, state) = setup_test_app().await;
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("chunk.wav");
+        std::fs::write(&path, b"RIFF").unwrap();
+        let audio_chunk_id = state
+            .db
+            .insert_audio_chunk(&path.to_string_lossy())
+            .await
+            .unwrap();
+        let get = |uri: String| {
+            let app = app.clone();
+            async move {
+                let response = app
+                    .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+                    .await
+                    .unwrap();
+                let status = response.status();
+                let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+                (status, body)
+            }
+        };
+
+        let url = state.media_signer.url(MediaKind::Audio, audio_chunk_id);
+        assert!(url.starts_with("/media/audio."));
+        assert!(!url.contains("chunk.wav"));
+        let (status, body) = get(url.clone()).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(&body[..], b"RIFF");
+        // no unsigned way to the file
+        let (status, _) = get(format!("/audio/{}", audio_chunk_id)).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+
+        // another chunk id under the same signature
+        let tampered = url.replacen(
+            &format!("audio.{}.", audio_chunk_id),
+            &format!("audio.{}.", audio_chunk_id + 1),
+            1,
+        );
+        let (status, _) = get(tampered).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+
+        let (status, _) = get("/media/not-a-token".to_string()).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+
+        let other_key = MediaSigner::random(DEFAULT_SIGNED_URL_TTL);
+        let (status, _) = get(other_key.url(MediaKind::Audio, audio_chunk_id)).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+
+        let expired = state.media_signer.sign_until(
+            MediaKind::Audio,
+            audio_chunk_id,
+            Utc::now() - chrono::Duration::minutes(1),
+        );
+        let (status, body) = get(format!("/media/{}", expired)).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert!(json["error"].as_str().unwrap().contains("expired"));
+
+        let (status, _) = get(state.media_signer.url(MediaKind::Frame, 12345)).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_get_compressed_audio_chunk() {
+        let (app, state) = setup_test_app().await;
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("chunk.wav.zst");
+        let samples: Vec<f32> = (0..16000).map(|i| (i as f32 / 100.0).sin() * 0.5).collect();
+        let data: Vec<u8> = samples.iter().flat_map(|s| s.to_le_bytes()).collect();
+        encode_single_audio(&data, 16000, 1, &path, AudioFormat::WavZstd).unwrap();
+        let audio_chunk_id = state
+            .db
+            .insert_audio_chunk(&path.to_string_lossy())
+            .await
+            .unwrap();
+        let format: String = sqlx::query_scalar("SELECT format FROM audio_chunks WHERE id = ?1")
+            .bind(audio_chunk_id)
+            .fetch_one(&state.db.pool)
+            .await
+            .unwrap();
+        assert_eq!(format, "wav+zstd");
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri(state.media_signer.url(MediaKind::Audio, audio_chunk_id))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()["content-type"], "audio/wav");
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        assert_eq!(&body[..4], b"RIFF");
+        assert_eq!(body.len(), 44 + samples.len() * 2);
+        assert_eq!(&body[..], &read_audio_file(&path).unwrap()[..]);
+    }
+
+    #[tokio::test]
+    async fn test_body_limits() {
+        let (_, state) = setup_test_app().await;
+        let app = create_router_with_limits(BodyLimits {
+            max_body_size: 1024,
+            max_import_body_size: 4096,
+        })
+        .with_state(state);
+        let post = |uri: &str, body: String, content_length: bool| {
+            let mut request = Request::builder()
+                .method("POST")
+                .uri(uri)
+                .header(CONTENT_TYPE, "application/json");
+            if content_length {
+                request = request.header("content-length", body.len());
+            }
+            app.clone().oneshot(request.body(Body::from(body)).unwrap())
+        };
+        let large = format!("\"{}\"", "x".repeat(2048));
+
+        // refused from the content-length, or once the streamed body goes over
+        for content_length in [true, false] {
+            let response = post("/import/transcript", large.clone(), content_length)
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+            let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+            let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+            assert_eq!(json["error"], "request body is larger than 1 KB");
+        }
+
+        // /import has a limit of its own
+        let response = post("/import", large, true).await.unwrap();
+        assert_ne!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+        let response = post("/import", "x".repeat(8192), true).await.unwrap();
+        assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    }
+
+    #[tokio::test]
+    async fn test_import_transcript() {
+        let (app, state) = setup_test_app().await;
+        let audio_chunk_id = state.db.insert_audio_chunk("meeting.wav").await.unwrap();
+        state
+            .db
+            .insert_audio_transcription(
+                audio_chunk_id,
+                "shell we stat",
+                0,
+                "Whisper",
+                &AudioDevice::new("mic".to_string(), DeviceType::Input),
+            )
+            .await
+            .unwrap();
+
+        let post = |body: serde_json::Value| {
+            let app = app.clone();
+            async move {
+                let response = app
+                    .oneshot(
+                        Request::builder()
+                            .method("POST")
+                            .uri("/import/transcript")
+                            .header(CONTENT_TYPE, "application/json")
+                            .body(Body::from(body.to_string()))
+                            .unwrap(),
+                    )
+                    .await
+                    .unwrap();
+                let status = response.status();
+                let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+                (
+                    status,
+                    serde_json::from_slice::<serde_json::Value>(&body).unwrap(),
+                )
+            }
+        };
+
+        let (status, json) = post(serde_json::json!({
+            "audio_chunk_id": audio_chunk_id,
+            "transcript": [
+                {"start_ms": 0, "end_ms": 2500, "text": "shall we start", "speaker": "alice"},
+                {"start_ms": 2600, "end_ms": 4000, "text": "yes"}
+            ]
+        }))
+        .await;
+        assert_eq!(status, StatusCode::OK, "{}", json);
+        assert_eq!(json["segments"], 2);
+
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri("/search?content_type=audio&limit=10")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let results: PaginatedResponse<ContentItem> = serde_json::from_slice(&body).unwrap();
+        let mut segments: Vec<_> = results
+            .data
+            .iter()
+            .map(|item| match item {
+                ContentItem::Audio(audio) => audio,
+                other => panic!("expected audio, got {:?}", other),
+            })
+            .collect();
+        segments.sort_by_key(|audio| audio.offset_index);
+        assert_eq!(segments.len(), 2);
+        assert_eq!(segments[0].transcription, "shall we start");
+        assert_eq!(segments[0].source, TranscriptSource::Manual);
+        assert_eq!(segments[0].speaker.as_deref(), Some("alice"));
+        assert_eq!(segments[0].device_name, "mic");
+        assert_eq!(segments[1].speaker, None);
+        assert!(segments[1].timestamp >= segments[0].timestamp);
+
+        let (status, _) = post(serde_json::json!({
+            "audio_chunk_id": audio_chunk_id + 1,
+            "transcript": [{"start_ms": 0, "end_ms": 10, "text": "hi"}]
+        }))
+        .await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        let (status, _) = post(serde_json::json!({
+            "audio_chunk_id": audio_chunk_id,
+            "transcript": [{"start_ms": 10, "end_ms": 0, "text": "hi"}]
+        }))
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_correct_transcript() {
+        let (app, state) = setup_test_app().await;
+        let audio_chunk_id = state.db.insert_audio_chunk("standup.wav").await.unwrap();
+        let device = AudioDevice::new("mic".to_string(), DeviceType::Input);
+        for (index, text) in ["the deploy is", "on fryday"].into_iter().enumerate() {
+            state
+                .db
+                .insert_audio_transcription(audio_chunk_id, text, index as i64, "Whisper", &device)
+                .await
+                .unwrap();
+        }
+
+        let patch = |audio_chunk_id: i64, body: serde_json::Value| {
+            let app = app.clone();
+            async move {
+                let response = app
+                    .oneshot(
+                        Request::builder()
+                            .method("PATCH")
+                            .uri(format!("/audio/chunks/{}/transcript", audio_chunk_id))
+                            .header(CONTENT_TYPE, "application/json")
+                            .header("user-agent", "review-tool/1.0")
+                            .body(Body::from(body.to_string()))
+                            .unwrap(),
+                    )
+                    .await
+                    .unwrap();
+                let status = response.status();
+                let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+                (status, body)
+            }
+        };
+        let search = |q: &'static str| {
+            let app = app.clone();
+            async move {
+                let response = app
+                    .oneshot(
+                        Request::builder()
+                            .uri(format!("/search?content_type=audio&q={}", q))
+                            .body(Body::empty())
+                            .unwrap(),
+                    )
+                    .await
+                    .unwrap();
+                let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+                let results: PaginatedResponse<ContentItem> =
+                    serde_json::from_slice(&body).unwrap();
+                results
+                    .data
+                    .into_iter()
+                    .map(|item| match item {
+                        ContentItem::Audio(audio) => audio,
+                        other => panic!("expected audio, got {:?}", other),
+                    })
+                    .collect::<Vec<_>>()
+            }
+        };
+        let before = search("fryday").await;
+        assert_eq!(before.len(), 1);
+        assert!(!before[0].corrected);
+
+        let (status, body) = patch(
+            audio_chunk_id,
+            serde_json::json!({"transcript": "the deploy is on friday"}),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        let correction: TranscriptCorrection = serde_json::from_slice(&body).unwrap();
+        assert_eq!(correction.audio_chunk_id, audio_chunk_id);
+        assert_eq!(correction.original_transcript, "the deploy is on fryday");
+        assert_eq!(correction.corrected_transcript, "the deploy is on friday");
+        assert_eq!(correction.user_agent.as_deref(), Some("review-tool/1.0"));
+
+        // the fts index has the new text only
+        assert!(search("fryday").await.is_empty());
+        let after = search("friday").await;
+        assert_eq!(after.len(), 1);
+        assert_eq!(after[0].transcription, "the deploy is on friday");
+        assert!(after[0].corrected);
+        assert_eq!(after[0].source, TranscriptSource::Corrected);
+
+        // the transcribed rows are kept under the correction
+        let rows: Vec<(String, String)> = sqlx::query_as(
+            "SELECT transcription, source FROM audio_transcriptions WHERE audio_chunk_id = ?1 ORDER BY id",
+        )
+        .bind(audio_chunk_id)
+        .fetch_all(&state.db.pool)
+        .await
+        .unwrap();
+        assert_eq!(
+            rows,
+            vec![
+                ("the deploy is".to_string(), "automated".to_string()),
+                ("on fryday".to_string(), "automated".to_string()),
+                (
+                    "the deploy is on friday".to_string(),
+                    "corrected".to_string()
+                ),
+            ]
+        );
+
+        // a second correction replaces the first one
+        let (status, body) = patch(
+            audio_chunk_id,
+            serde_json::json!({"transcript": "the deploy is on friday at noon"}),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        let correction: TranscriptCorrection = serde_json::from_slice(&body).unwrap();
+        assert_eq!(correction.original_transcript, "the deploy is on friday");
+        let after = search("noon").await;
+        assert_eq!(after.len(), 1);
+        assert_eq!(after[0].transcription, "the deploy is on friday at noon");
+
+        let (status, _) = patch(audio_chunk_id, serde_json::json!({"transcript": "  "})).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        let (status, _) = patch(
+            audio_chunk_id + 1,
+            serde_json::json!({"transcript": "hello"}),
+        )
+        .await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_share_frame() {
+        let (app, state) = setup_test_app().await;
+        let _ = state.db.insert_video_chunk("shared.mp4").await.unwrap();
+        let frame_id = state.db.insert_frame().await.unwrap();
+
+        let send = |method: &'static str, uri: String| {
+            let app = app.clone();
+            async move {
+                let response = app
+                    .oneshot(
+                        Request::builder()
+                            .method(method)
+                            .uri(uri)
+                            .body(Body::empty())
+                            .unwrap(),
+                    )
+                    .await
+                    .unwrap();
+                let status = response.status();
+                let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+                (status, body)
+            }
+        };
+
+        let before = Utc::now();
+        let (status, body) = send("POST", format!("/share/frame/{}", frame_id)).await;
+        assert_eq!(status, StatusCode::OK);
+        let share: ShareResponse = serde_json::from_slice(&body).unwrap();
+        assert_eq!(share.frame_id, frame_id);
+        assert_eq!(share.url, format!("/share/{}", share.token));
+        assert_eq!(share.token.len(), 64);
+        assert!(share.expires_at >= before + Duration::hours(24));
+
+        let (status, body) = send("POST", format!("/share/frame/{}?ttl_secs=60", frame_id)).await;
+        assert_eq!(status, StatusCode::OK);
+        let short: ShareResponse = serde_json::from_slice(&body).unwrap();
+        assert_ne!(short.token, share.token);
+        assert!(short.expires_at <= Utc::now() + Duration::seconds(60));
+
+        let (status, _) = send("POST", format!("/share/frame/{}?ttl_secs=0", frame_id)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        let (status, _) = send("POST", format!("/share/frame/{}", frame_id + 1)).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        let (status, _) = send("GET", format!("/share/{}", new_share_token())).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_random_frames() {
+        let (app, state) = setup_test_app().await;
+        let _ = state.db.insert_video_chunk("sampled.mp4").await.unwrap();
+        let mut frame_ids = Vec::new();
+        for index in 0..10 {
+            let frame_id = state.db.insert_frame().await.unwrap();
+            state
+                .db
+                .insert_ocr_text(
+                    frame_id,
+                    &format!("window text {}", index),
+                    "",
+                    "editor",
+                    "notes",
+                    Arc::new(OcrEngine::Tesseract),
+                    true,
+                )
+                .await
+                .unwrap();
+            frame_ids.push(frame_id);
+        }
+
+        let sample = |uri: &'static str| {
+            let app = app.clone();
+            async move {
+                let response = app
+                    .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+                    .await
+                    .unwrap();
+                let status = response.status();
+                let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+                let frames = serde_json::from_slice::<Vec<RandomFrameItem>>(&body).ok();
+                (status, frames)
+            }
+        };
+
+        let (status, frames) = sample("/frames/random").await;
+        assert_eq!(status, StatusCode::OK);
+        let frames = frames.unwrap();
+        assert_eq!(frames.len(), 5);
+        for frame in &frames {
+            assert!(frame_ids.contains(&frame.frame_id));
+            assert!(frame.ocr_text.starts_with("window text "));
+            assert_eq!(
+                frame.thumbnail_url,
+                format!("/frames/{}/thumbnail", frame.frame_id)
+            );
+        }
+
+        let (_, first) = sample("/frames/random?n=4&seed=7").await;
+        let (_, second) = sample("/frames/random?n=4&seed=7").await;
+        let ids = |frames: Option<Vec<RandomFrameItem>>| -> Vec<i64> {
+            frames.unwrap().iter().map(|frame| frame.frame_id).collect()
+        };
+        let first = ids(first);
+        assert_eq!(first.len(), 4);
+        assert_eq!(first, ids(second));
+
+        let (_, all) = sample("/frames/random?n=50&seed=1").await;
+        let mut all = ids(all);
+        all.sort();
+        assert_eq!(all, frame_ids);
+        let (_, none) = sample("/frames/random?from=2100-01-01T00:00:00Z").await;
+        assert!(none.unwrap().is_empty());
+
+        let (status, _) = sample("/frames/random?n=0").await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        let (status, _) = sample("/frames/random?n=101").await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_search_stream() {
+        let (app, state) = setup_test_app().await;
+        let _ = state.db.insert_video_chunk("streamed.mp4").await.unwrap();
+        for index in 0..3 {
+            let frame_id = state.db.insert_frame().await.unwrap();
+            state
+                .db
+                .insert_ocr_text(
+                    frame_id,
+                    &format!("needle on screen {}", index),
+                    "",
+                    "editor",
+                    "notes",
+                    Arc::new(OcrEngine::Tesseract),
+                    true,
+                )
+                .await
+                .unwrap();
+        }
+        let audio_chunk_id = state.db.insert_audio_chunk("streamed.wav").await.unwrap();
+        state
+            .db
+            .insert_audio_transcription(
+                audio_chunk_id,
+                "needle in a call",
+                0,
+                "",
+                &AudioDevice::new("mic".to_string(), DeviceType::Input),
+            )
+            .await
+            .unwrap();
+
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri("/search/stream?q=needle")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers().get(CONTENT_TYPE).unwrap(),
+            "application/x-ndjson"
+        );
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let items: Vec<ContentItem> = std::str::from_utf8(&body)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(items.len(), 4);
+        assert!(items[..3]
+            .iter()
+            .all(|item| matches!(item, ContentItem::OCR(_))));
+        match &items[3] {
+            ContentItem::Audio(audio) => assert_eq!(audio.transcription, "needle in a call"),
+            _ => panic!("expected an audio result last"),
+        }
+
+        // app filters leave the transcriptions out
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/search/stream?q=needle&app_name=editor")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        assert_eq!(std::str::from_utf8(&body).unwrap().lines().count(), 3);
+    }
 }
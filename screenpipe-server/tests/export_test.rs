@@ -0,0 +1,56 @@
+use std::path::{Path, PathBuf};
+
+use screenpipe_server::{
+    index_path, keyframe_interval, ExportIndex, FrameTimestamp, DEFAULT_KEYFRAME_INTERVAL,
+};
+
+#[test]
+fn test_keyframe_interval() {
+    // short clips seek to every frame whatever was asked
+    assert_eq!(keyframe_interval(1, None), 1);
+    assert_eq!(keyframe_interval(300, Some(60)), 1);
+
+    assert_eq!(keyframe_interval(301, None), DEFAULT_KEYFRAME_INTERVAL);
+    assert_eq!(keyframe_interval(5000, Some(10)), 10);
+    assert_eq!(keyframe_interval(5000, Some(0)), 1);
+}
+
+#[test]
+fn test_export_index() {
+    let index = ExportIndex::new(&[7, 9, 12], 2.0, 1);
+    assert_eq!(index.fps, 2.0);
+    assert_eq!(
+        index.frames,
+        vec![
+            FrameTimestamp {
+                frame_id: 7,
+                pts: 0.0
+            },
+            FrameTimestamp {
+                frame_id: 9,
+                pts: 0.5
+            },
+            FrameTimestamp {
+                frame_id: 12,
+                pts: 1.0
+            },
+        ]
+    );
+
+    // ffmpeg encodes at most 30 fps
+    let index = ExportIndex::new(&[1, 2], 60.0, 30);
+    assert_eq!(index.fps, 30.0);
+    assert_eq!(index.frames[1].pts, 1.0 / 30.0);
+
+    let json: serde_json::Value = serde_json::to_value(&index).unwrap();
+    assert_eq!(json["keyframe_interval"], 30);
+    assert_eq!(json["frames"][0]["frame_id"], 1);
+}
+
+#[test]
+fn test_index_path() {
+    assert_eq!(
+        index_path(Path::new("/data/exports/export_abc.mp4")),
+        PathBuf::from("/data/exports/export_abc.json")
+    );
+}
@@ -0,0 +1,43 @@
+use screenpipe_audio::{AudioDevice, DeviceType};
+use screenpipe_server::filtering::{select_prioritized_device, window_title_matches};
+
+#[test]
+fn test_window_title_matches_glob() {
+    assert!(window_title_matches(
+        "New Private Window - Firefox",
+        "*private*"
+    ));
+    assert!(window_title_matches(
+        "Google Chrome - Incognito",
+        "*- INCOGNITO"
+    ));
+    assert!(window_title_matches("Slack", "sl?ck"));
+    assert!(window_title_matches("anything", "*"));
+
+    assert!(!window_title_matches("Private notes", "*window*"));
+    assert!(!window_title_matches("Slack", "sl?"));
+    assert!(!window_title_matches("Terminal", ""));
+}
+
+#[test]
+fn test_select_prioritized_device() {
+    let devices = vec![
+        AudioDevice::new("MacBook Pro Microphone".to_string(), DeviceType::Input),
+        AudioDevice::new("USB Headset".to_string(), DeviceType::Input),
+        AudioDevice::new("USB Headset".to_string(), DeviceType::Output),
+    ];
+    let priorities = vec!["*headset*".to_string(), "*microphone*".to_string()];
+
+    let (device, pattern) =
+        select_prioritized_device(&devices, &DeviceType::Input, &priorities).unwrap();
+    assert_eq!(device, &devices[1]);
+    assert_eq!(pattern, "*headset*");
+
+    // rules are tried in order, not devices
+    let priorities = vec!["*microphone*".to_string(), "*headset*".to_string()];
+    let (device, _) = select_prioritized_device(&devices, &DeviceType::Input, &priorities).unwrap();
+    assert_eq!(device, &devices[0]);
+
+    assert!(select_prioritized_device(&devices, &DeviceType::Output, &priorities[..1]).is_none());
+    assert!(select_prioritized_device(&devices, &DeviceType::Input, &[]).is_none());
+}
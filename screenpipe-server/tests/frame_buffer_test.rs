@@ -0,0 +1,49 @@
+use screenpipe_server::{move_file, DatabaseManager, FrameBuffer};
+
+#[tokio::test]
+async fn test_move_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let from = dir.path().join("a.mp4");
+    let to = dir.path().join("b.mp4");
+    std::fs::write(&from, b"video").unwrap();
+
+    move_file(&from, &to).await.unwrap();
+    assert!(!from.exists());
+    assert_eq!(std::fs::read(&to).unwrap(), b"video");
+
+    assert!(move_file(&from, &to).await.is_err());
+}
+
+#[tokio::test]
+async fn test_flush_leftovers() {
+    let buffer_dir = tempfile::tempdir().unwrap();
+    let frames_dir = tempfile::tempdir().unwrap();
+    let frame_buffer = FrameBuffer {
+        buffer_dir: buffer_dir.path().to_path_buf(),
+        frames_dir: frames_dir.path().to_path_buf(),
+    };
+    let db = DatabaseManager::new("sqlite::memory:").await.unwrap();
+
+    let chunk = buffer_dir.path().join("monitor_1_2024-10-14_10-00-00.mp4");
+    std::fs::write(&chunk, b"video").unwrap();
+    std::fs::write(buffer_dir.path().join("notes.txt"), b"not a chunk").unwrap();
+    let chunk_id = db
+        .insert_video_chunk(&chunk.to_string_lossy())
+        .await
+        .unwrap();
+
+    assert_eq!(frame_buffer.flush_leftovers(&db).await.unwrap(), 1);
+
+    let moved = frames_dir.path().join("monitor_1_2024-10-14_10-00-00.mp4");
+    assert!(!chunk.exists());
+    assert_eq!(std::fs::read(&moved).unwrap(), b"video");
+    assert!(buffer_dir.path().join("notes.txt").exists());
+    let file_path: String = sqlx::query_scalar("SELECT file_path FROM video_chunks WHERE id = ?1")
+        .bind(chunk_id)
+        .fetch_one(&db.pool)
+        .await
+        .unwrap();
+    assert_eq!(file_path, moved.to_string_lossy());
+
+    assert_eq!(frame_buffer.flush_leftovers(&db).await.unwrap(), 0);
+}
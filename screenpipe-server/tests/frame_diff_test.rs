@@ -0,0 +1,65 @@
+use screenpipe_server::{diff_text, DatabaseManager};
+use screenpipe_vision::OcrEngine;
+use std::sync::Arc;
+
+#[test]
+fn test_diff_text() {
+    let (diff, added, removed) = diff_text("inbox\nmeeting at 3\n", "inbox\nmeeting at 4\nlunch\n");
+    assert_eq!((added, removed), (2, 1));
+    assert!(diff.starts_with("--- previous\n+++ current\n"));
+    assert!(diff.contains("\n-meeting at 3\n"));
+    assert!(diff.contains("\n+meeting at 4\n+lunch\n"));
+
+    assert_eq!(diff_text("same\n", "same\n"), (String::new(), 0, 0));
+    let (_, added, removed) = diff_text("", "first\nsecond");
+    assert_eq!((added, removed), (2, 0));
+}
+
+async fn insert_window_frame(db: &DatabaseManager, app: &str, window: &str, text: &str) -> i64 {
+    let frame_id = db.insert_frame().await.unwrap();
+    db.insert_ocr_text(
+        frame_id,
+        text,
+        "",
+        app,
+        window,
+        Arc::new(OcrEngine::Tesseract),
+        true,
+    )
+    .await
+    .unwrap();
+    frame_id
+}
+
+#[tokio::test]
+async fn test_frame_diffs_follow_their_window() {
+    let db = DatabaseManager::new("sqlite::memory:").await.unwrap();
+    db.insert_video_chunk("chunk.mp4").await.unwrap();
+
+    let first = insert_window_frame(&db, "mail", "inbox", "hello\n").await;
+    let other = insert_window_frame(&db, "editor", "main.rs", "fn main() {}\n").await;
+    let second = insert_window_frame(&db, "mail", "inbox", "hello\nnew mail\n").await;
+
+    let diff = db.get_frame_diff(first).await.unwrap().unwrap();
+    assert_eq!(diff.previous_frame_id, None);
+    assert_eq!((diff.lines_added, diff.lines_removed), (1, 0));
+
+    let diff = db.get_frame_diff(second).await.unwrap().unwrap();
+    assert_eq!(diff.previous_frame_id, Some(first));
+    assert_eq!((diff.lines_added, diff.lines_removed), (1, 0));
+    assert!(diff.diff.contains("+new mail"));
+    assert!(!diff.diff.contains("fn main"));
+
+    let diff = db.get_frame_diff(other).await.unwrap().unwrap();
+    assert_eq!(diff.previous_frame_id, None);
+
+    // re-ocr'd text is diffed again
+    db.replace_ocr_text(second, "hello\n", "", &OcrEngine::Tesseract, None)
+        .await
+        .unwrap();
+    let diff = db.get_frame_diff(second).await.unwrap().unwrap();
+    assert_eq!(diff.diff, "");
+    assert_eq!((diff.lines_added, diff.lines_removed), (0, 0));
+
+    assert!(db.get_frame_diff(second + 1).await.unwrap().is_none());
+}
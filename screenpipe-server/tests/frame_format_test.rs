@@ -0,0 +1,92 @@
+use image::{DynamicImage, GenericImageView, Rgb, RgbImage, Rgba, RgbaImage};
+use screenpipe_server::CaptureFormat;
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+#[test]
+fn test_capture_formats_decode_losslessly() {
+    let rgb = DynamicImage::ImageRgb8(RgbImage::from_fn(64, 48, |x, y| {
+        Rgb([(x * 4) as u8, (y * 5) as u8, ((x + y) % 7 * 30) as u8])
+    }));
+    let rgba = DynamicImage::ImageRgba8(RgbaImage::from_fn(40, 30, |x, y| {
+        Rgba([(x * 6) as u8, (y * 8) as u8, 90, 255])
+    }));
+    // neither rgb8 nor rgba8, converted before webp encoding
+    let luma = DynamicImage::ImageLuma8(rgb.to_luma8());
+
+    for format in [CaptureFormat::Png, CaptureFormat::WebpLossless] {
+        for image in [&rgb, &rgba, &luma] {
+            let encoded = format.encode(image).unwrap();
+            // what re-running ocr on a stored frame does
+            let decoded = image::load_from_memory(&encoded).unwrap();
+            assert_eq!(decoded.dimensions(), image.dimensions(), "{:?}", format);
+            assert_eq!(decoded.to_rgba8(), image.to_rgba8(), "{:?}", format);
+        }
+    }
+}
+
+#[test]
+fn test_webp_lossless_is_webp() {
+    let image = DynamicImage::ImageRgb8(RgbImage::new(16, 16));
+    let encoded = CaptureFormat::WebpLossless.encode(&image).unwrap();
+    assert_eq!(
+        image::guess_format(&encoded).unwrap(),
+        image::ImageFormat::WebP
+    );
+    assert_eq!(CaptureFormat::WebpLossless.ffmpeg_codec(), "webp");
+    assert_eq!(CaptureFormat::default(), CaptureFormat::Png);
+}
+
+#[test]
+fn test_webp_frames_reach_reocr_through_ffmpeg() {
+    let Some(ffmpeg) = screenpipe_core::find_ffmpeg_path() else {
+        eprintln!("ffmpeg not found, skipping");
+        return;
+    };
+    let dir = tempfile::tempdir().unwrap();
+    let video = dir.path().join("frames.mp4");
+    let frame = DynamicImage::ImageRgb8(RgbImage::from_fn(64, 48, |x, _| {
+        if x < 32 {
+            Rgb([250, 250, 250])
+        } else {
+            Rgb([20, 20, 20])
+        }
+    }));
+
+    // the input side of the recorder's ffmpeg, see `start_ffmpeg_process`
+    let mut encoder = Command::new(&ffmpeg)
+        .args(["-f", "image2pipe", "-vcodec"])
+        .arg(CaptureFormat::WebpLossless.ffmpeg_codec())
+        .args([
+            "-r", "1", "-i", "-", "-vcodec", "libx264", "-pix_fmt", "yuv420p",
+        ])
+        .arg(&video)
+        .stdin(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .unwrap();
+    let mut stdin = encoder.stdin.take().unwrap();
+    for _ in 0..3 {
+        stdin
+            .write_all(&CaptureFormat::WebpLossless.encode(&frame).unwrap())
+            .unwrap();
+    }
+    drop(stdin);
+    assert!(encoder.wait().unwrap().success());
+
+    // what `POST /ocr/reprocess/:frame_id` decodes
+    let extracted = Command::new(&ffmpeg)
+        .args(["-ss", "0.000", "-i"])
+        .arg(&video)
+        .args(["-vframes", "1", "-f", "image2pipe", "-vcodec", "png", "-"])
+        .stderr(Stdio::null())
+        .output()
+        .unwrap();
+    assert!(extracted.status.success());
+    let decoded = image::load_from_memory(&extracted.stdout)
+        .unwrap()
+        .to_luma8();
+    assert_eq!(decoded.dimensions(), (64, 48));
+    assert!(decoded.get_pixel(8, 24)[0] > 200);
+    assert!(decoded.get_pixel(56, 24)[0] < 60);
+}
@@ -0,0 +1,21 @@
+use std::time::Duration;
+
+use screenpipe_server::HealBackoff;
+
+#[test]
+fn test_heal_backoff_doubles_up_to_max() {
+    let mut backoff = HealBackoff::new(Duration::from_secs(5), Duration::from_secs(30));
+    let delays: Vec<u64> = (0..5).map(|_| backoff.next_delay().as_secs()).collect();
+    assert_eq!(delays, vec![5, 10, 20, 30, 30]);
+
+    backoff.reset();
+    assert_eq!(backoff.next_delay(), Duration::from_secs(5));
+}
+
+#[test]
+fn test_heal_backoff_does_not_overflow() {
+    let mut backoff = HealBackoff::new(Duration::from_secs(1), Duration::from_secs(300));
+    for _ in 0..100 {
+        assert!(backoff.next_delay() <= Duration::from_secs(300));
+    }
+}
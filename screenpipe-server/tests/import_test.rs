@@ -0,0 +1,89 @@
+use std::sync::Arc;
+
+use chrono::{TimeZone, Utc};
+use screenpipe_server::{parse_media_info, DatabaseManager, FrameData, MediaInfo};
+use screenpipe_vision::OcrEngine;
+
+#[test]
+fn test_parse_media_info() {
+    let output = r#"Input #0, mov,mp4,m4a,3gp,3g2,mj2, from 'meeting.mp4':
+  Duration: 01:02:03.50, start: 0.000000, bitrate: 1205 kb/s
+  Stream #0:0[0x1](und): Video: h264 (High) (avc1 / 0x31637661), yuv420p, 1920x1080, 30 fps
+  Stream #0:1[0x2](und): Audio: aac (LC) (mp4a / 0x6134706D), 48000 Hz, stereo, fltp
+At least one output file must be specified"#;
+    assert_eq!(
+        parse_media_info(output),
+        Some(MediaInfo {
+            duration_secs: 3723.5,
+            has_audio: true,
+        })
+    );
+
+    let video_only =
+        "  Duration: 00:00:10.00, start: 0.000000, bitrate: 100 kb/s\n  Stream #0:0: Video: h264";
+    assert_eq!(
+        parse_media_info(video_only).map(|i| i.has_audio),
+        Some(false)
+    );
+
+    assert_eq!(parse_media_info("  Duration: N/A, bitrate: N/A"), None);
+    assert_eq!(parse_media_info("no such file or directory"), None);
+}
+
+fn frame(text: &str) -> FrameData {
+    FrameData {
+        timestamp: Utc.with_ymd_and_hms(2024, 1, 1, 10, 0, 1).unwrap(),
+        text: text.to_string(),
+        text_json: String::new(),
+        app_name: "screenpipe import".to_string(),
+        window_name: "meeting.mp4".to_string(),
+        ocr_engine: Arc::new(OcrEngine::Tesseract),
+        focused: true,
+        ocr_failed: false,
+        cursor: None,
+        window_bounds: None,
+    }
+}
+
+#[tokio::test]
+async fn test_import_progress_is_tracked() {
+    let db = DatabaseManager::new("sqlite::memory:").await.unwrap();
+    let _ = db.insert_video_chunk("live.mp4").await.unwrap();
+    assert_eq!(db.get_import("/videos/meeting.mp4").await.unwrap(), None);
+
+    let start_time = Utc.with_ymd_and_hms(2024, 1, 1, 10, 0, 0).unwrap();
+    let state = db
+        .insert_import("/videos/meeting.mp4", start_time)
+        .await
+        .unwrap();
+    assert_eq!(state.last_frame_ms, None);
+
+    db.insert_imported_frame("/videos/meeting.mp4", 1000, &frame("agenda"))
+        .await
+        .unwrap();
+    db.set_import_audio_progress("/videos/meeting.mp4", 30000)
+        .await
+        .unwrap();
+    let resumed = db.get_import("/videos/meeting.mp4").await.unwrap().unwrap();
+    assert_eq!(resumed.video_chunk_id, state.video_chunk_id);
+    assert_eq!(resumed.start_time, start_time);
+    assert_eq!(resumed.last_frame_ms, Some(1000));
+    assert_eq!(resumed.last_audio_ms, Some(30000));
+
+    let frames = db.get_frames(None, None, 10, 0).await.unwrap();
+    assert_eq!(frames.len(), 1);
+    assert_eq!(frames[0].file_path, "/videos/meeting.mp4");
+    assert_eq!(frames[0].offset_index, 1000);
+
+    // live frames keep going to the recorded chunk, not the imported file
+    db.bulk_insert_frames(vec![frame("live")]).await.unwrap();
+    assert_eq!(
+        db.get_frames(None, None, 10, 0)
+            .await
+            .unwrap()
+            .iter()
+            .filter(|f| f.file_path == "live.mp4")
+            .count(),
+        1
+    );
+}
@@ -0,0 +1,18 @@
+use screenpipe_server::{decode_pcm, PcmEncoding};
+
+#[test]
+fn test_decode_pcm() {
+    let s16: Vec<u8> = [0i16, 16384, -32768]
+        .iter()
+        .flat_map(|sample| sample.to_le_bytes())
+        .chain([0x7f])
+        .collect();
+    assert_eq!(decode_pcm(&s16, PcmEncoding::S16le), vec![0.0, 0.5, -1.0]);
+
+    let f32le: Vec<u8> = [0.25f32, -0.75]
+        .iter()
+        .flat_map(|sample| sample.to_le_bytes())
+        .collect();
+    assert_eq!(decode_pcm(&f32le, PcmEncoding::F32le), vec![0.25, -0.75]);
+    assert!(decode_pcm(&[1, 2, 3], PcmEncoding::F32le).is_empty());
+}
@@ -0,0 +1,58 @@
+use screenpipe_server::JsonLogFormat;
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+use tracing_subscriber::fmt::format::JsonFields;
+use tracing_subscriber::fmt::MakeWriter;
+use tracing_subscriber::prelude::*;
+
+#[derive(Clone, Default)]
+struct Captured(Arc<Mutex<Vec<u8>>>);
+
+impl Write for Captured {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl<'a> MakeWriter<'a> for Captured {
+    type Writer = Self;
+
+    fn make_writer(&self) -> Self::Writer {
+        self.clone()
+    }
+}
+
+#[test]
+fn test_json_log_format() {
+    let captured = Captured::default();
+    let subscriber = tracing_subscriber::registry().with(
+        tracing_subscriber::fmt::layer()
+            .event_format(JsonLogFormat)
+            .fmt_fields(JsonFields::new())
+            .with_writer(captured.clone()),
+    );
+    tracing::subscriber::with_default(subscriber, || {
+        let session = tracing::info_span!("recording", session_id = "abc");
+        let _session = session.enter();
+        let device = tracing::info_span!("audio", device = "MacBook Pro Microphone (input)");
+        let _device = device.enter();
+        tracing::warn!(chunk = 3, "audio chunk \"{}\" dropped", 3);
+    });
+
+    let output = String::from_utf8(captured.0.lock().unwrap().clone()).unwrap();
+    let lines: Vec<&str> = output.lines().collect();
+    assert_eq!(lines.len(), 1);
+    let line: serde_json::Value = serde_json::from_str(lines[0]).unwrap();
+    assert_eq!(line["level"], "WARN");
+    assert_eq!(line["msg"], "audio chunk \"3\" dropped");
+    assert_eq!(line["module"], "logs_test");
+    assert_eq!(line["chunk"], 3);
+    assert_eq!(line["session_id"], "abc");
+    assert_eq!(line["device"], "MacBook Pro Microphone (input)");
+    assert!(chrono::DateTime::parse_from_rfc3339(line["ts"].as_str().unwrap()).is_ok());
+}
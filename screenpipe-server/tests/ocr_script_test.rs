@@ -0,0 +1,33 @@
+use screenpipe_server::OcrScript;
+use std::time::{Duration, Instant};
+
+#[test]
+fn test_ocr_script() {
+    let script = OcrScript::from_source(
+        r#"
+        text.replace("CONFIDENTIAL", "");
+        if confidence < 0.5 { "" } else { text.trim(); text }
+        "#,
+    )
+    .unwrap();
+    assert_eq!(
+        script.run("CONFIDENTIAL quarterly report ", 0.9),
+        "quarterly report"
+    );
+    assert_eq!(script.run("blurry", 0.2), "");
+
+    // runtime errors keep the text of the engine
+    let failing = OcrScript::from_source("text + undefined_variable").unwrap();
+    assert_eq!(failing.run("kept", 0.9), "kept");
+    assert!(OcrScript::from_source("fn (").is_err());
+}
+
+#[test]
+fn test_ocr_script_timeout() {
+    let script = OcrScript::from_source("loop { }")
+        .unwrap()
+        .with_timeout(Duration::from_millis(20));
+    let started = Instant::now();
+    assert_eq!(script.run("kept", 0.9), "kept");
+    assert!(started.elapsed() < Duration::from_secs(2));
+}
@@ -0,0 +1,123 @@
+#![cfg(unix)]
+
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::Utc;
+use screenpipe_server::{
+    run_pipe_cmd, ContentType, DatabaseManager, PipeCmd, PipeCmdInput, PipeCmdOutput, SearchOrder,
+    SearchResult,
+};
+use screenpipe_vision::OcrEngine;
+
+fn write_script(dir: &Path, body: &str) -> String {
+    let path = dir.join("pipe.sh");
+    std::fs::write(&path, format!("#!/bin/sh\n{}\n", body)).unwrap();
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+    path.to_string_lossy().into_owned()
+}
+
+fn input() -> PipeCmdInput {
+    PipeCmdInput {
+        timestamp: Utc::now(),
+        ocr_text: "quarterly report".to_string(),
+        app_name: "Excel".to_string(),
+        frame_path: PathBuf::from("/tmp/1.jpg"),
+    }
+}
+
+#[tokio::test]
+async fn test_run_pipe_cmd_reads_json_reply() {
+    let dir = tempfile::tempdir().unwrap();
+    // answers with the app name it was given as a tag
+    let cmd = write_script(
+        dir.path(),
+        r#"app=$(sed 's/.*"app_name":"\([^"]*\)".*/\1/')
+echo "{\"tags\": [\"$app\"], \"notes\": \"seen\"}""#,
+    );
+
+    let output = run_pipe_cmd(&cmd, &input()).await.unwrap();
+    assert_eq!(
+        output,
+        PipeCmdOutput {
+            tags: vec!["Excel".to_string()],
+            notes: "seen".to_string(),
+        }
+    );
+}
+
+#[tokio::test]
+async fn test_run_pipe_cmd_failures() {
+    let dir = tempfile::tempdir().unwrap();
+
+    let cmd = write_script(dir.path(), "cat > /dev/null; echo boom >&2; exit 3");
+    let err = run_pipe_cmd(&cmd, &input()).await.unwrap_err();
+    assert!(err.to_string().contains("boom"));
+
+    let cmd = write_script(dir.path(), "cat > /dev/null; echo not json");
+    assert!(run_pipe_cmd(&cmd, &input()).await.is_err());
+
+    assert!(run_pipe_cmd("/nonexistent/pipe", &input()).await.is_err());
+}
+
+#[tokio::test]
+async fn test_pipe_cmd_stores_tags_and_notes() {
+    let dir = tempfile::tempdir().unwrap();
+    let cmd = write_script(
+        dir.path(),
+        r#"cat > /dev/null; echo '{"tags": ["finance"], "notes": "q3 numbers"}'"#,
+    );
+
+    let db = Arc::new(DatabaseManager::new("sqlite::memory:").await.unwrap());
+    let _ = db.insert_video_chunk("test_video.mp4").await.unwrap();
+    let frame_id = db.insert_frame().await.unwrap();
+    db.insert_ocr_text(
+        frame_id,
+        "quarterly report",
+        "",
+        "Excel",
+        "",
+        Arc::new(OcrEngine::Tesseract),
+        false,
+    )
+    .await
+    .unwrap();
+
+    let pipe_cmd = Arc::new(PipeCmd::new(cmd, 1, Arc::clone(&db)));
+    pipe_cmd.submit(frame_id, input());
+
+    let mut notes = None;
+    for _ in 0..50 {
+        notes = db.get_frame_notes(frame_id).await.unwrap();
+        if notes.is_some() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    assert_eq!(notes.as_deref(), Some("q3 numbers"));
+
+    let results = db
+        .search(
+            "quarterly",
+            ContentType::OCR,
+            10,
+            0,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            &SearchOrder::default(),
+        )
+        .await
+        .unwrap();
+    match &results[0] {
+        SearchResult::OCR(ocr) => assert_eq!(ocr.tags, vec!["finance".to_string()]),
+        _ => panic!("Expected OCR result"),
+    }
+}
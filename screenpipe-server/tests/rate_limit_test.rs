@@ -0,0 +1,59 @@
+use axum::body::Body;
+use axum::http::header::{AUTHORIZATION, RETRY_AFTER};
+use axum::http::{Request, StatusCode};
+use axum::routing::get;
+use axum::Router;
+use screenpipe_server::{with_rate_limit, RateLimit};
+use std::num::NonZeroU32;
+use tower::ServiceExt;
+
+fn app() -> Router {
+    let router = Router::new().route("/health", get(|| async { "ok" }));
+    with_rate_limit(
+        router,
+        RateLimit {
+            requests_per_second: NonZeroU32::new(1).unwrap(),
+            burst: NonZeroU32::new(2).unwrap(),
+            exempt_key: Some("secret".to_string()),
+        },
+    )
+}
+
+async fn status(app: &Router, authorization: Option<&str>) -> (StatusCode, Option<String>) {
+    let mut request = Request::builder().uri("/health");
+    if let Some(authorization) = authorization {
+        request = request.header(AUTHORIZATION, authorization);
+    }
+    let response = app
+        .clone()
+        .oneshot(request.body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    let retry_after = response
+        .headers()
+        .get(RETRY_AFTER)
+        .map(|value| value.to_str().unwrap().to_string());
+    (response.status(), retry_after)
+}
+
+#[tokio::test]
+async fn test_rate_limit_burst_then_429() {
+    let app = app();
+    assert_eq!(status(&app, None).await.0, StatusCode::OK);
+    assert_eq!(status(&app, None).await.0, StatusCode::OK);
+
+    let (code, retry_after) = status(&app, None).await;
+    assert_eq!(code, StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(retry_after.as_deref(), Some("1"));
+
+    // the exempt key goes through, a wrong one is limited like any other request
+    assert_eq!(status(&app, Some("Bearer secret")).await.0, StatusCode::OK);
+    for guess in ["Bearer guess", "Bearer secre", "Bearer secret2", "secret"] {
+        assert_eq!(
+            status(&app, Some(guess)).await.0,
+            StatusCode::TOO_MANY_REQUESTS,
+            "{} was exempt",
+            guess
+        );
+    }
+}
@@ -0,0 +1,56 @@
+#![cfg(unix)]
+
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
+
+use screenpipe_server::{secrets_path, Secrets};
+
+fn write_secrets(dir: &Path, content: &str, mode: u32) -> std::path::PathBuf {
+    let path = secrets_path(dir);
+    std::fs::write(&path, content).unwrap();
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(mode)).unwrap();
+    path
+}
+
+#[test]
+fn test_load_secrets() {
+    let dir = tempfile::tempdir().unwrap();
+    assert_eq!(
+        Secrets::load(&secrets_path(dir.path())).unwrap(),
+        Secrets::default()
+    );
+
+    let path = write_secrets(
+        dir.path(),
+        "unstructured_api_key = \"uns-123\"\nopenai_api_key = \"sk-456\"\n",
+        0o600,
+    );
+    let secrets = Secrets::load(&path).unwrap();
+    assert_eq!(secrets.unstructured_api_key.as_deref(), Some("uns-123"));
+    assert_eq!(secrets.openai_api_key.as_deref(), Some("sk-456"));
+    assert_eq!(secrets.deepgram_api_key, None);
+    assert_eq!(
+        secrets.names(),
+        vec!["unstructured_api_key", "openai_api_key"]
+    );
+
+    let debug = format!("{:?}", secrets);
+    assert!(!debug.contains("uns-123") && !debug.contains("sk-456"));
+    assert!(debug.contains("***"));
+}
+
+#[test]
+fn test_load_secrets_rejected() {
+    let dir = tempfile::tempdir().unwrap();
+
+    let path = write_secrets(dir.path(), "openai_api_key = \"sk-456\"\n", 0o644);
+    assert!(Secrets::load(&path).is_err());
+
+    // errors must not quote the file
+    let path = write_secrets(dir.path(), "openai_api_key = sk-456\n", 0o600);
+    let err = Secrets::load(&path).unwrap_err();
+    assert!(!err.to_string().contains("sk-456"));
+
+    let path = write_secrets(dir.path(), "github_token = \"ghp\"\n", 0o600);
+    assert!(Secrets::load(&path).is_err());
+}
@@ -0,0 +1,40 @@
+#![cfg(unix)]
+
+use screenpipe_server::run_startup_script;
+
+#[tokio::test]
+async fn test_startup_script_gets_port_and_data_dir() {
+    let dir = tempfile::tempdir().unwrap();
+    let out = dir.path().join("ready.txt");
+    let script = dir.path().join("ready.sh");
+    std::fs::write(
+        &script,
+        format!(
+            "echo \"$SCREENPIPE_PORT $SCREENPIPE_DATA_DIR\" > {}\n",
+            out.display()
+        ),
+    )
+    .unwrap();
+
+    let status = run_startup_script(&script, 3030, dir.path()).await.unwrap();
+    assert!(status.success());
+    assert_eq!(
+        std::fs::read_to_string(&out).unwrap().trim(),
+        format!("3030 {}", dir.path().display())
+    );
+}
+
+#[tokio::test]
+async fn test_startup_script_failure_is_reported() {
+    let dir = tempfile::tempdir().unwrap();
+    let script = dir.path().join("fail.sh");
+    std::fs::write(&script, "exit 3\n").unwrap();
+
+    let status = run_startup_script(&script, 3030, dir.path()).await.unwrap();
+    assert_eq!(status.code(), Some(3));
+
+    let missing = dir.path().join("missing");
+    assert!(run_startup_script(&missing, 3030, dir.path())
+        .await
+        .is_err());
+}
@@ -0,0 +1,91 @@
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use screenpipe_audio::TranscriptionSegment;
+use screenpipe_server::{build_cues, render_subtitles, AudioTranscript, Cue, SubtitleFormat};
+
+fn segment(text: &str, start_ms: u64, end_ms: u64) -> TranscriptionSegment {
+    TranscriptionSegment {
+        text: text.to_string(),
+        start_ms,
+        end_ms,
+    }
+}
+
+fn origin() -> DateTime<Utc> {
+    "2024-10-14T10:00:00Z".parse().unwrap()
+}
+
+#[test]
+fn test_build_cues() {
+    let transcripts = vec![
+        AudioTranscript {
+            start: origin(),
+            transcription: "hello there how are you".to_string(),
+            word_timestamps: vec![
+                segment(" hello there", 0, 1500),
+                segment(" how are you", 1500, 3000),
+                // past the longest cue, starts a new one
+                segment(" fine", 6000, 7000),
+            ],
+        },
+        // the output device heard someone talking over the first cue
+        AudioTranscript {
+            start: origin() + chrono::Duration::seconds(2),
+            transcription: "sorry".to_string(),
+            word_timestamps: vec![segment("sorry", 0, 500)],
+        },
+        // no word timestamps, spans the whole chunk
+        AudioTranscript {
+            start: origin() + chrono::Duration::seconds(30),
+            transcription: " bye ".to_string(),
+            word_timestamps: vec![],
+        },
+    ];
+
+    let cues = build_cues(origin(), &transcripts, Duration::from_secs(30));
+    assert_eq!(
+        cues,
+        vec![
+            Cue {
+                start_ms: 0,
+                end_ms: 3000,
+                text: "hello there how are you\nsorry".to_string(),
+            },
+            Cue {
+                start_ms: 6000,
+                end_ms: 7000,
+                text: "fine".to_string(),
+            },
+            Cue {
+                start_ms: 30000,
+                end_ms: 60000,
+                text: "bye".to_string(),
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_render_subtitles() {
+    let cues = vec![
+        Cue {
+            start_ms: 1500,
+            end_ms: 4000,
+            text: "hello".to_string(),
+        },
+        Cue {
+            start_ms: 3_725_001,
+            end_ms: 3_726_000,
+            text: "an hour later".to_string(),
+        },
+    ];
+    assert_eq!(
+        render_subtitles(&cues, SubtitleFormat::Srt),
+        "1\n00:00:01,500 --> 00:00:04,000\nhello\n\n2\n01:02:05,001 --> 01:02:06,000\nan hour later\n\n"
+    );
+    assert_eq!(
+        render_subtitles(&cues, SubtitleFormat::Vtt),
+        "WEBVTT\n\n00:00:01.500 --> 00:00:04.000\nhello\n\n01:02:05.001 --> 01:02:06.000\nan hour later\n\n"
+    );
+}
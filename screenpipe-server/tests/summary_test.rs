@@ -0,0 +1,84 @@
+use chrono::{Duration, Local, NaiveTime, TimeZone, Utc};
+use screenpipe_server::{
+    render_markdown, summarize_day, summary_path, top_terms, write_daily_summary, DatabaseManager,
+    FrameData,
+};
+use screenpipe_vision::OcrEngine;
+use std::sync::Arc;
+
+#[test]
+fn test_top_terms() {
+    let terms = top_terms(
+        [
+            "Invoice invoice INVOICE total",
+            "invoice draft from accounting",
+            "the 2024 total",
+        ],
+        2,
+    );
+    // counted once per text, stopwords and numbers left out
+    assert_eq!(
+        terms,
+        vec![("invoice".to_string(), 2), ("total".to_string(), 2)]
+    );
+}
+
+#[tokio::test]
+async fn test_daily_summary() {
+    let db = DatabaseManager::new("sqlite::memory:").await.unwrap();
+    let _ = db.insert_video_chunk("test_video.mp4").await.unwrap();
+
+    let date = Local::now().date_naive();
+    let start = Local
+        .from_local_datetime(&date.and_time(NaiveTime::from_hms_opt(10, 0, 0).unwrap()))
+        .earliest()
+        .unwrap()
+        .with_timezone(&Utc);
+    let frame = |secs: i64, app: &str, text: &str, focused: bool| FrameData {
+        timestamp: start + Duration::seconds(secs),
+        text: text.to_string(),
+        text_json: String::new(),
+        app_name: app.to_string(),
+        window_name: String::new(),
+        ocr_engine: Arc::new(OcrEngine::Tesseract),
+        focused,
+        ocr_failed: false,
+        cursor: None,
+        window_bounds: None,
+    };
+    db.bulk_insert_frames(vec![
+        frame(0, "Code", "quarterly invoice script", true),
+        frame(0, "Firefox", "news of the day", false),
+        frame(60, "Code", "invoice totals", true),
+        frame(120, "Slack", "sent the invoice", true),
+        // long after, the gap before it is capped
+        frame(20 * 60, "Code", "lunch", true),
+    ])
+    .await
+    .unwrap();
+
+    let summary = summarize_day(&db, date).await.unwrap();
+    assert_eq!(summary.frame_count, 5);
+    let apps: Vec<_> = summary
+        .top_apps
+        .iter()
+        .map(|app| app.app_name.as_str())
+        .collect();
+    assert_eq!(apps, vec!["Code", "Slack"]);
+    assert!((summary.top_apps[0].seconds - 120.0).abs() < 0.01);
+    assert!((summary.top_apps[1].seconds - 60.0).abs() < 0.01);
+    assert_eq!(summary.top_terms[0], ("invoice".to_string(), 3));
+
+    let markdown = render_markdown(&summary);
+    assert!(markdown.contains("| Code | 2m |"));
+    assert!(markdown.contains("- invoice (3)"));
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = write_daily_summary(&db, dir.path(), date).await.unwrap();
+    assert_eq!(path, summary_path(dir.path(), date));
+    assert_eq!(std::fs::read_to_string(path).unwrap(), markdown);
+
+    let empty = summarize_day(&db, date - Duration::days(1)).await.unwrap();
+    assert_eq!(empty.frame_count, 0);
+    assert!(render_markdown(&empty).contains("No app activity recorded."));
+}
@@ -4,18 +4,17 @@ use axum::{
     Router,
 };
 use chrono::Utc;
-use crossbeam::queue::SegQueue;
 use screenpipe_audio::{AudioDevice, DeviceType};
 use screenpipe_vision::OcrEngine;
 use serde_json::json;
-use std::sync::atomic::AtomicBool;
 use std::sync::Arc;
-use std::{collections::HashMap, path::PathBuf};
 use tower::ServiceExt;
 
+mod common;
+
+use common::test_app_state;
 use screenpipe_server::{
     create_router, AppState, ContentItem, ContentSource, DatabaseManager, PaginatedResponse,
-    PipeManager,
 };
 
 // Add this function to initialize the logger
@@ -25,17 +24,7 @@ fn init() {
 
 async fn setup_test_app() -> (Router, Arc<AppState>) {
     let db = Arc::new(DatabaseManager::new("sqlite::memory:").await.unwrap());
-    let app_state = Arc::new(AppState {
-        db: db.clone(),
-        vision_disabled: false,
-        audio_disabled: false,
-        vision_control: Arc::new(AtomicBool::new(false)),
-        audio_devices_control: Arc::new(SegQueue::new()),
-        devices_status: HashMap::new(),
-        app_start_time: Utc::now(),
-        screenpipe_dir: PathBuf::from(""),
-        pipe_manager: Arc::new(PipeManager::new(PathBuf::from(""))),
-    });
+    let app_state = Arc::new(test_app_state(db));
 
     let app = create_router().with_state(app_state.clone());
     init();
@@ -400,4 +389,4 @@ async fn insert_test_data(db: &Arc<DatabaseManager>) {
     )
     .await
     .unwrap();
-}
\ No newline at end of file
+}
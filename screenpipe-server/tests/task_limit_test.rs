@@ -0,0 +1,53 @@
+use screenpipe_server::TaskLimiter;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Notify;
+
+#[tokio::test]
+async fn test_task_limiter_queues_then_drops() {
+    let limiter = TaskLimiter::new(2, 1);
+    let release = Arc::new(Notify::new());
+    let done = Arc::new(AtomicUsize::new(0));
+    let task = || {
+        let release = Arc::clone(&release);
+        let done = Arc::clone(&done);
+        async move {
+            release.notified().await;
+            done.fetch_add(1, Ordering::SeqCst);
+        }
+    };
+
+    assert!(limiter.spawn(task()));
+    assert!(limiter.spawn(task()));
+    // both slots taken, the third waits and the fourth is dropped
+    assert!(limiter.spawn(task()));
+    assert!(!limiter.spawn(task()));
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert_eq!(limiter.running(), 2);
+    assert_eq!(limiter.queued(), 1);
+
+    while done.load(Ordering::SeqCst) < 3 {
+        release.notify_waiters();
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    assert_eq!(done.load(Ordering::SeqCst), 3);
+    assert_eq!(limiter.running(), 0);
+    assert_eq!(limiter.queued(), 0);
+}
+
+#[tokio::test]
+async fn test_task_limiter_drain_waits_for_queued_tasks() {
+    let limiter = TaskLimiter::new(1, 4);
+    let done = Arc::new(AtomicUsize::new(0));
+    for _ in 0..3 {
+        let done = Arc::clone(&done);
+        assert!(limiter.spawn(async move {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            done.fetch_add(1, Ordering::SeqCst);
+        }));
+    }
+    limiter.drain().await;
+    assert_eq!(done.load(Ordering::SeqCst), 3);
+    assert_eq!(limiter.running(), 0);
+}
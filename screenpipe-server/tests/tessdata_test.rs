@@ -0,0 +1,71 @@
+use clap::Parser;
+use screenpipe_server::{
+    git_blob_sha1, installed_langs, is_lang_code, parse_tessdata_dir, Cli, TesseractLangManager,
+};
+use std::path::PathBuf;
+
+#[test]
+fn test_git_blob_sha1_matches_git() {
+    // `git hash-object` of an empty file and of "hello world\n"
+    assert_eq!(
+        git_blob_sha1(b""),
+        "e69de29bb2d1d6434b8b29ae775ad8c2e48c5391"
+    );
+    assert_eq!(
+        git_blob_sha1(b"hello world\n"),
+        "3b18e512dba79e4c8300dd08aeb37f8e728b8dad"
+    );
+}
+
+#[test]
+fn test_parse_tessdata_dir() {
+    let output = "List of available languages in \"/usr/share/tesseract-ocr/5/tessdata/\" (2):\n\
+                  eng\nosd\n";
+    assert_eq!(
+        parse_tessdata_dir(output),
+        Some(PathBuf::from("/usr/share/tesseract-ocr/5/tessdata/"))
+    );
+    assert_eq!(parse_tessdata_dir("tesseract: command not found"), None);
+    assert_eq!(parse_tessdata_dir(""), None);
+}
+
+#[test]
+fn test_installed_langs_lists_traineddata_files() {
+    let dir = tempfile::tempdir().unwrap();
+    for name in [
+        "eng.traineddata",
+        "deu.traineddata",
+        "deu.traineddata.part",
+        "pdf.ttf",
+    ] {
+        std::fs::write(dir.path().join(name), b"").unwrap();
+    }
+    std::fs::create_dir(dir.path().join("configs")).unwrap();
+
+    assert_eq!(installed_langs(dir.path()), vec!["deu", "eng"]);
+    assert!(installed_langs(&dir.path().join("missing")).is_empty());
+}
+
+#[test]
+fn test_lang_codes_are_validated() {
+    for lang in ["eng", "chi_sim", "osd"] {
+        assert!(is_lang_code(lang), "{} was rejected", lang);
+    }
+    for lang in ["", "../eng", "ENG", "deu?ref=main", "eng+deu"] {
+        assert!(!is_lang_code(lang), "{} was accepted", lang);
+    }
+
+    let cli = Cli::try_parse_from(["screenpipe", "--ocr-hint-language", "chi_sim"]).unwrap();
+    assert_eq!(cli.ocr_hint_language, vec!["chi_sim"]);
+    assert!(Cli::try_parse_from(["screenpipe", "--ocr-hint-language", "../../etc/x"]).is_err());
+}
+
+#[tokio::test]
+async fn test_invalid_lang_is_never_written() {
+    let dir = tempfile::tempdir().unwrap();
+    let tessdata = TesseractLangManager::new(dir.path().to_path_buf()).unwrap();
+    // rejected before any request, so nothing is downloaded or written outside the directory
+    tessdata.ensure_langs(&["../escape".to_string()]).await;
+
+    assert!(std::fs::read_dir(dir.path()).unwrap().next().is_none());
+}
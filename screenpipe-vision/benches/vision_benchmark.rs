@@ -18,6 +18,7 @@ async fn benchmark_continuous_capture(duration_secs: u64) -> f64 {
             Duration::from_millis(100),
             false,
             OcrEngine::Tesseract,
+            None,
             get_default_monitor().await.id(),
             &[],
             &[],
@@ -95,6 +95,7 @@ async fn main() -> Result<()> {
             } else {
                 OcrEngine::Tesseract
             },
+            None,
             id,
             &cli.ignored_windows,
             &cli.included_windows,
@@ -49,6 +49,7 @@ async fn main() -> Result<()> {
             } else {
                 OcrEngine::Tesseract
             },
+            None,
             id,
             &cli.ignore,
             &cli.include,
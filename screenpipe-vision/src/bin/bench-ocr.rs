@@ -0,0 +1,176 @@
+use anyhow::{bail, Context, Result};
+use clap::{Parser, ValueEnum};
+use screenpipe_vision::OcrEngine;
+use std::fs::File;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::time::Instant;
+
+/// Runs ocr engines on a directory of png files and prints one csv row per engine and image,
+/// e.g. to compare engines or catch latency regressions in ci.
+#[derive(Parser)]
+#[command(author, version)]
+struct Cli {
+    /// Directory of the png files to read
+    images: PathBuf,
+
+    /// Engines to run, defaults to the ones available on this platform
+    #[arg(long = "engine", value_enum)]
+    engines: Vec<BenchEngine>,
+
+    /// Times each image is read, latency_ms is the mean of the runs
+    #[arg(long, default_value_t = 1, value_parser = clap::value_parser!(u32).range(1..))]
+    runs: u32,
+
+    /// Writes the csv to this file instead of stdout
+    #[arg(long)]
+    output: Option<PathBuf>,
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum BenchEngine {
+    Tesseract,
+    Unstructured,
+    WindowsNative,
+    AppleNative,
+}
+
+impl BenchEngine {
+    fn available() -> Vec<BenchEngine> {
+        let mut engines = vec![BenchEngine::Tesseract];
+        if cfg!(target_os = "windows") {
+            engines.push(BenchEngine::WindowsNative);
+        }
+        if cfg!(target_os = "macos") {
+            engines.push(BenchEngine::AppleNative);
+        }
+        engines
+    }
+
+    fn name(&self) -> String {
+        self.to_possible_value()
+            .map(|value| value.get_name().to_string())
+            .unwrap_or_default()
+    }
+}
+
+impl From<BenchEngine> for OcrEngine {
+    fn from(engine: BenchEngine) -> Self {
+        match engine {
+            BenchEngine::Tesseract => OcrEngine::Tesseract,
+            BenchEngine::Unstructured => OcrEngine::Unstructured,
+            BenchEngine::WindowsNative => OcrEngine::WindowsNative,
+            BenchEngine::AppleNative => OcrEngine::AppleNative,
+        }
+    }
+}
+
+fn png_files(dir: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut files: Vec<PathBuf> = std::fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|ext| ext.eq_ignore_ascii_case("png"))
+        })
+        .collect();
+    files.sort();
+    Ok(files)
+}
+
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// Returns whether every engine read every image.
+async fn run(cli: Cli) -> Result<bool> {
+    let engines = if cli.engines.is_empty() {
+        BenchEngine::available()
+    } else {
+        cli.engines
+    };
+
+    let files = png_files(&cli.images)
+        .with_context(|| format!("failed to read {}", cli.images.display()))?;
+    if files.is_empty() {
+        bail!("no png files in {}", cli.images.display());
+    }
+
+    let mut out: Box<dyn Write> = match &cli.output {
+        Some(path) => Box::new(
+            File::create(path).with_context(|| format!("failed to create {}", path.display()))?,
+        ),
+        None => Box::new(io::stdout()),
+    };
+    writeln!(out, "engine,filename,latency_ms,char_count,confidence_mean")?;
+
+    let mut failed = false;
+    for file in &files {
+        let filename = file
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let image = match image::open(file) {
+            Ok(image) => image,
+            Err(e) => {
+                eprintln!("skipping {}: {}", filename, e);
+                failed = true;
+                continue;
+            }
+        };
+
+        for engine in &engines {
+            let ocr_engine = OcrEngine::from(*engine);
+            let mut total_ms = 0.0;
+            let mut result = None;
+            for _ in 0..cli.runs {
+                let start = Instant::now();
+                let output = ocr_engine.perform_ocr(&image).await;
+                total_ms += start.elapsed().as_secs_f64() * 1000.0;
+                match output {
+                    Ok(output) => result = Some(output),
+                    Err(e) => {
+                        eprintln!("{} failed on {}: {}", engine.name(), filename, e);
+                        result = None;
+                        break;
+                    }
+                }
+            }
+            let Some((text, _, confidence)) = result else {
+                failed = true;
+                continue;
+            };
+
+            writeln!(
+                out,
+                "{},{},{:.1},{},{}",
+                engine.name(),
+                csv_field(&filename),
+                total_ms / cli.runs as f64,
+                text.chars().count(),
+                confidence.map_or(String::new(), |confidence| format!(
+                    "{:.3}",
+                    ocr_engine.normalize_confidence(confidence)
+                )),
+            )?;
+        }
+    }
+    out.flush()?;
+    Ok(!failed)
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    match run(Cli::parse()).await {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        Err(e) => {
+            eprintln!("{:#}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -41,6 +41,7 @@ async fn main() {
             Duration::from_secs_f32(1.0 / cli.fps),
             save_text_files,
             OcrEngine::AppleNative,
+            None,
             id,
             &[],
             &[],
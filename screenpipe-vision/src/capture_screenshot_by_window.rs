@@ -23,6 +23,45 @@ impl fmt::Display for CaptureError {
 
 impl Error for CaptureError {}
 
+/// Part of a monitor frame a window covers, in pixels of the frame, clipped to it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WindowBounds {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Maps a window at `window_origin` of `window_size`, in the desktop coordinates of the monitor's
+/// origin and size, onto a frame of `image_size` pixels like
+/// [`crate::cursor::to_image_position`] does the cursor. `None` when none of it is on the
+/// monitor.
+pub fn to_image_bounds(
+    window_origin: (i32, i32),
+    window_size: (u32, u32),
+    monitor_origin: (i32, i32),
+    monitor_size: (u32, u32),
+    image_size: (u32, u32),
+) -> Option<WindowBounds> {
+    let (monitor_width, monitor_height) = (monitor_size.0 as i64, monitor_size.1 as i64);
+    let left = (window_origin.0 as i64 - monitor_origin.0 as i64).clamp(0, monitor_width);
+    let top = (window_origin.1 as i64 - monitor_origin.1 as i64).clamp(0, monitor_height);
+    let right = (window_origin.0 as i64 - monitor_origin.0 as i64 + window_size.0 as i64)
+        .clamp(0, monitor_width);
+    let bottom = (window_origin.1 as i64 - monitor_origin.1 as i64 + window_size.1 as i64)
+        .clamp(0, monitor_height);
+    let scale_x = |x: i64| (x * image_size.0 as i64 / monitor_width.max(1)) as u32;
+    let scale_y = |y: i64| (y * image_size.1 as i64 / monitor_height.max(1)) as u32;
+    let (x, y) = (scale_x(left), scale_y(top));
+    let (width, height) = (scale_x(right) - x, scale_y(bottom) - y);
+    (width > 0 && height > 0).then_some(WindowBounds {
+        x,
+        y,
+        width,
+        height,
+    })
+}
+
 impl From<XCapError> for CaptureError {
     fn from(error: XCapError) -> Self {
         error!("XCap error occurred: {}", error);
@@ -30,11 +69,13 @@ impl From<XCapError> for CaptureError {
     }
 }
 
+/// Each window with where it is on the frame of `image_size` pixels just captured from `monitor`.
 pub async fn capture_all_visible_windows(
     monitor: &Monitor,
+    image_size: (u32, u32),
     ignore_list: &[String],
     include_list: &[String],
-) -> Result<Vec<(DynamicImage, String, String, bool)>, Box<dyn Error>> {
+) -> Result<Vec<(DynamicImage, String, String, bool, Option<WindowBounds>)>, Box<dyn Error>> {
     let mut all_captured_images = Vec::new();
 
     let windows = retry_with_backoff(
@@ -74,11 +115,19 @@ pub async fn capture_all_visible_windows(
                         .unwrap(),
                     );
 
+                    let bounds = to_image_bounds(
+                        (window.x(), window.y()),
+                        (window.width(), window.height()),
+                        (monitor.x(), monitor.y()),
+                        (monitor.width(), monitor.height()),
+                        image_size,
+                    );
                     all_captured_images.push((
                         image,
                         app_name.to_string(),
                         window_name.to_string(),
                         is_focused,
+                        bounds,
                     ));
                 }
                 Err(e) => error!(
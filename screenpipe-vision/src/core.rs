@@ -1,54 +1,270 @@
 use image::DynamicImage;
-use log::{debug, error};
-use screenpipe_integrations::unstructured_ocr::perform_ocr_cloud;
+use log::{debug, error, info, warn};
 use serde_json;
 use std::{
     collections::HashMap,
+    sync::{Arc, RwLock},
     time::{Duration, Instant},
 };
-use tokio::sync::mpsc::Sender;
-
-#[cfg(target_os = "macos")]
-use crate::apple::parse_apple_ocr_result;
-#[cfg(target_os = "macos")]
-use crate::apple::perform_ocr_apple;
-#[cfg(target_os = "windows")]
-use crate::microsoft::perform_ocr_windows;
+use tokio::runtime::Handle;
+use tokio::sync::mpsc::{self, Sender};
+use tokio::sync::Semaphore;
+use tokio::task::JoinHandle;
+
+use crate::capture_screenshot_by_window::WindowBounds;
+use crate::cursor::{draw_cursor, mouse_position, MousePosition};
+use crate::idle::IDLE_STATUS;
 use crate::monitor::get_monitor_by_id;
-use crate::tesseract::perform_ocr_tesseract;
-use crate::utils::OcrEngine;
+use crate::preprocess::{OcrPreprocess, PreprocessTimings};
 use crate::utils::{capture_screenshot, compare_with_previous_image, save_text_files};
+use crate::utils::{OcrEngine, OcrFallback};
 
 pub struct CaptureResult {
     pub image: DynamicImage,
     pub frame_number: u64,
     pub timestamp: Instant,
     pub window_ocr_results: Vec<WindowOcrResult>,
+    /// Where the mouse was when the frame was captured, drawn into `image` with `capture_cursor`
+    pub cursor: Option<MousePosition>,
+    /// Left out by `ocr_skip_frames` or `disable_ocr`, the windows have no text
+    pub ocr_skipped: bool,
+    /// Summed over the windows of the frame
+    pub preprocess_timings: PreprocessTimings,
 }
 
 pub struct WindowOcrResult {
     pub image: DynamicImage,
+    /// Where the window is on the frame, `None` when it could not be placed on it
+    pub bounds: Option<WindowBounds>,
     pub window_name: String,
     pub app_name: String,
     pub text: String,
     pub text_json: Vec<HashMap<String, String>>, // Change this line
     pub focused: bool,
     pub confidence: f64,
+    /// Cloud ocr gave up on this window, `text` is empty rather than what it shows
+    pub ocr_failed: bool,
 }
 
 pub struct OcrTaskData {
     pub image: DynamicImage,
-    pub window_images: Vec<(DynamicImage, String, String, bool)>,
+    pub window_images: Vec<(DynamicImage, String, String, bool, Option<WindowBounds>)>,
     pub frame_number: u64,
     pub timestamp: Instant,
     pub result_tx: Sender<CaptureResult>,
 }
 
+/// Capture rates accepted from the cli, `MIN_FPS` is one frame about every 17 minutes.
+pub const MIN_FPS: f64 = 0.001;
+pub const MAX_FPS: f64 = 60.0;
+
+/// Time between two captures at `fps`, clamped to `MIN_FPS..=MAX_FPS` so a zero or non-finite
+/// rate can't make `Duration::from_secs_f64` panic.
+pub fn capture_interval(fps: f64) -> Duration {
+    let fps = if fps.is_nan() {
+        MIN_FPS
+    } else {
+        fps.clamp(MIN_FPS, MAX_FPS)
+    };
+    Duration::from_secs_f64(1.0 / fps)
+}
+
+/// Longest time between two captures of an idle monitor, also how long it may take to notice the
+/// screen changed again.
+pub const MAX_IDLE_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Time between two captures of a monitor that has been idle for `idle_captures` captures: the
+/// interval doubles with each one up to [`MAX_IDLE_INTERVAL`], a slower `fps` keeps its own.
+pub fn idle_capture_interval(interval: Duration, idle_captures: u32) -> Duration {
+    let backed_off = interval.saturating_mul(1 << idle_captures.min(16));
+    backed_off.min(MAX_IDLE_INTERVAL.max(interval))
+}
+
+/// Sleeps out what is left of the interval started at `cycle_start`. A capture that took longer
+/// than the interval is followed by the next one right away, the missed ones are skipped rather
+/// than caught up on.
+async fn wait_for_next_capture(cycle_start: Instant, interval: Duration) {
+    let elapsed = cycle_start.elapsed();
+    match interval.checked_sub(elapsed) {
+        Some(remaining) => tokio::time::sleep(remaining).await,
+        None => {
+            debug!(
+                "capture took {:?}, longer than the {:?} interval, skipping missed frames",
+                elapsed, interval
+            );
+            tokio::task::yield_now().await;
+        }
+    }
+}
+
+/// Whether the frame after `frames_submitted` others gets ocr, one in `ocr_skip_frames` does.
+pub fn should_run_ocr(frames_submitted: u64, ocr_skip_frames: u32) -> bool {
+    frames_submitted % u64::from(ocr_skip_frames.max(1)) == 0
+}
+
+/// Physical cores minus one, leaving a core for capture and encoding.
+pub fn default_ocr_workers() -> usize {
+    num_cpus::get_physical().saturating_sub(1).max(1)
+}
+
+/// Runs ocr on up to `workers` frames at once on the blocking pool and hands the results to
+/// `result_tx` in capture order, whatever order they finish in. With `task_permits` each job
+/// also holds one of them, shared with the other work of the recorder.
+pub struct OcrWorkers {
+    permits: Arc<Semaphore>,
+    task_permits: Option<Arc<Semaphore>>,
+    jobs: mpsc::Sender<JoinHandle<Result<CaptureResult, std::io::Error>>>,
+}
+
+impl OcrWorkers {
+    pub fn new(
+        workers: usize,
+        task_permits: Option<Arc<Semaphore>>,
+        result_tx: Sender<CaptureResult>,
+    ) -> Self {
+        let workers = workers.max(1);
+        let (jobs, mut pending) =
+            mpsc::channel::<JoinHandle<Result<CaptureResult, std::io::Error>>>(workers);
+        tokio::spawn(async move {
+            while let Some(job) = pending.recv().await {
+                match job.await {
+                    Ok(Ok(capture_result)) => {
+                        if let Err(e) = result_tx.send(capture_result).await {
+                            error!("Failed to send OCR result: {}", e);
+                            break;
+                        }
+                    }
+                    Ok(Err(e)) => error!("Error processing OCR task: {}", e),
+                    Err(e) => error!("OCR worker failed: {}", e),
+                }
+            }
+        });
+        OcrWorkers {
+            permits: Arc::new(Semaphore::new(workers)),
+            task_permits,
+            jobs,
+        }
+    }
+
+    /// Waits for a free worker, so capture slows down rather than queueing frames without bound.
+    /// A frame without `run_ocr` skips the workers but is still handed over in order.
+    pub async fn submit(
+        &self,
+        frame: MaxAverageFrame,
+        run_ocr: bool,
+        save_text_files_flag: bool,
+        ocr_engine: OcrEngine,
+        ocr_fallback: Option<OcrFallback>,
+        ocr_preprocess: OcrPreprocess,
+    ) {
+        let draw_cursor = frame.draw_cursor;
+        if !run_ocr {
+            let result = with_cursor(skipped_ocr_result(frame), draw_cursor);
+            let job = tokio::spawn(async move { Ok(result) });
+            if self.jobs.send(job).await.is_err() {
+                error!("OCR results are no longer received, dropping frame");
+            }
+            return;
+        }
+        let Ok(permit) = Arc::clone(&self.permits).acquire_owned().await else {
+            return;
+        };
+        let task_permit = match &self.task_permits {
+            Some(task_permits) => match Arc::clone(task_permits).acquire_owned().await {
+                Ok(task_permit) => Some(task_permit),
+                Err(_) => return,
+            },
+            None => None,
+        };
+        let handle = Handle::current();
+        let job = tokio::task::spawn_blocking(move || {
+            let _permits = (permit, task_permit);
+            let cursor = frame.cursor;
+            handle
+                .block_on(run_ocr(
+                    frame.image,
+                    frame.window_images,
+                    frame.frame_number,
+                    frame.timestamp,
+                    save_text_files_flag,
+                    &ocr_engine,
+                    ocr_fallback.as_ref(),
+                    ocr_preprocess,
+                ))
+                .map(|result| with_cursor(CaptureResult { cursor, ..result }, draw_cursor))
+        });
+        if self.jobs.send(job).await.is_err() {
+            error!("OCR results are no longer received, dropping frame");
+        }
+    }
+}
+
+/// Frames whose average difference with the previous one is below this are skipped.
+pub const DEFAULT_DEDUP_THRESHOLD: f64 = 0.006;
+
+/// Capture settings that can change while recording, read again on every capture cycle.
+#[derive(Clone, Debug)]
+pub struct CaptureConfig {
+    pub fps: f64,
+    pub ocr_engine: OcrEngine,
+    pub dedup_threshold: f64,
+    /// Time without a frame above `dedup_threshold` after which the monitor counts as idle
+    pub idle_pause: Option<Duration>,
+    /// Draws the mouse cursor into captured frames
+    pub capture_cursor: bool,
+    /// Runs ocr on one in this many frames kept after dedup, 1 is every frame
+    pub ocr_skip_frames: u32,
+    /// Frames are kept without ocr, as if every one was left out by `ocr_skip_frames`
+    pub disable_ocr: bool,
+    /// Applied to window images before ocr, the stored images are left as captured
+    pub ocr_preprocess: OcrPreprocess,
+}
+
+pub type SharedCaptureConfig = Arc<RwLock<CaptureConfig>>;
+
 pub async fn continuous_capture(
     result_tx: Sender<CaptureResult>,
     interval: Duration,
     save_text_files_flag: bool,
     ocr_engine: OcrEngine,
+    ocr_fallback: Option<OcrFallback>,
+    monitor_id: u32,
+    ignore_list: &[String],
+    include_list: &[String],
+) {
+    let config = Arc::new(RwLock::new(CaptureConfig {
+        fps: 1.0 / interval.as_secs_f64(),
+        ocr_engine,
+        dedup_threshold: DEFAULT_DEDUP_THRESHOLD,
+        idle_pause: None,
+        capture_cursor: false,
+        ocr_skip_frames: 1,
+        disable_ocr: false,
+        ocr_preprocess: OcrPreprocess::default(),
+    }));
+    continuous_capture_with_config(
+        result_tx,
+        config,
+        save_text_files_flag,
+        ocr_fallback,
+        default_ocr_workers(),
+        None,
+        monitor_id,
+        ignore_list,
+        include_list,
+    )
+    .await
+}
+
+/// [`continuous_capture`] following a config that may be updated while it runs, with ocr spread
+/// over `ocr_workers` threads, each taking one of `task_permits` too when given.
+pub async fn continuous_capture_with_config(
+    result_tx: Sender<CaptureResult>,
+    config: SharedCaptureConfig,
+    save_text_files_flag: bool,
+    ocr_fallback: Option<OcrFallback>,
+    ocr_workers: usize,
+    task_permits: Option<Arc<Semaphore>>,
     monitor_id: u32,
     ignore_list: &[String],
     include_list: &[String],
@@ -58,9 +274,15 @@ pub async fn continuous_capture(
         monitor_id
     );
     let mut frame_counter: u64 = 0;
+    let mut frames_submitted: u64 = 0;
     let mut previous_image: Option<DynamicImage> = None;
     let mut max_average: Option<MaxAverageFrame> = None;
     let mut max_avg_value = 0.0;
+    let workers = OcrWorkers::new(ocr_workers, task_permits, result_tx);
+    let mut last_change = Instant::now();
+    // captures since the monitor went idle, 0 while it is active
+    let mut idle_captures: u32 = 0;
+    IDLE_STATUS.set_active(monitor_id);
 
     let monitor = match get_monitor_by_id(monitor_id).await {
         Some(m) => m,
@@ -74,13 +296,27 @@ pub async fn continuous_capture(
     };
 
     loop {
+        let cycle_start = Instant::now();
+        let CaptureConfig {
+            fps,
+            ocr_engine,
+            dedup_threshold,
+            idle_pause,
+            capture_cursor,
+            ocr_skip_frames,
+            disable_ocr,
+            ocr_preprocess,
+        } = config.read().unwrap().clone();
+        let interval = capture_interval(fps);
+
         let capture_result = match capture_screenshot(&monitor, &ignore_list, &include_list).await {
             Ok((image, window_images, image_hash, _capture_duration)) => {
                 debug!(
                     "Captured screenshot on monitor {} with hash: {}",
                     monitor_id, image_hash
                 );
-                Some((image, window_images, image_hash))
+                let cursor = mouse_position(&monitor, &image);
+                Some((image, window_images, image_hash, cursor))
             }
             Err(e) => {
                 error!("Failed to capture screenshot: {}", e);
@@ -88,7 +324,7 @@ pub async fn continuous_capture(
             }
         };
 
-        if let Some((image, window_images, image_hash)) = capture_result {
+        if let Some((image, window_images, image_hash, cursor)) = capture_result {
             let current_average = match compare_with_previous_image(
                 previous_image.as_ref(),
                 &image,
@@ -111,16 +347,28 @@ pub async fn continuous_capture(
                 current_average
             };
 
-            if current_average < 0.006 {
+            if current_average < dedup_threshold {
                 debug!(
                     "Skipping frame {} due to low average difference: {:.3}",
                     frame_counter, current_average
                 );
+                idle_captures =
+                    next_idle_captures(idle_captures, monitor_id, last_change, idle_pause);
                 frame_counter += 1;
-                tokio::time::sleep(interval).await;
+                wait_for_next_capture(cycle_start, idle_capture_interval(interval, idle_captures))
+                    .await;
                 continue;
             }
 
+            last_change = Instant::now();
+            idle_captures = 0;
+            if let Some(idle_for) = IDLE_STATUS.set_active(monitor_id) {
+                info!(
+                    "idle pause ended on monitor {} after {:.0?}",
+                    monitor_id, idle_for
+                );
+            }
+
             if current_average > max_avg_value {
                 max_average = Some(MaxAverageFrame {
                     image: image.clone(),
@@ -128,8 +376,9 @@ pub async fn continuous_capture(
                     image_hash,
                     frame_number: frame_counter,
                     timestamp: Instant::now(),
-                    result_tx: result_tx.clone(),
                     average: current_average,
+                    cursor,
+                    draw_cursor: capture_cursor,
                 });
                 max_avg_value = current_average;
             }
@@ -137,46 +386,112 @@ pub async fn continuous_capture(
             previous_image = Some(image);
 
             if let Some(max_avg_frame) = max_average.take() {
-                let ocr_task_data = OcrTaskData {
-                    image: max_avg_frame.image,
-                    window_images: max_avg_frame.window_images,
-                    frame_number: max_avg_frame.frame_number,
-                    timestamp: max_avg_frame.timestamp,
-                    result_tx: max_avg_frame.result_tx,
-                };
-
-                if let Err(e) =
-                    process_ocr_task(ocr_task_data, save_text_files_flag, &ocr_engine).await
-                {
-                    error!("Error processing OCR task: {}", e);
-                }
+                let run_ocr = !disable_ocr && should_run_ocr(frames_submitted, ocr_skip_frames);
+                frames_submitted += 1;
+                workers
+                    .submit(
+                        max_avg_frame,
+                        run_ocr,
+                        save_text_files_flag,
+                        ocr_engine,
+                        ocr_fallback,
+                        ocr_preprocess,
+                    )
+                    .await;
 
                 frame_counter = 0;
                 max_avg_value = 0.0;
             }
         } else {
             debug!("Skipping frame {} due to capture failure", frame_counter);
+            // e.g. a locked screen on some platforms
+            idle_captures = next_idle_captures(idle_captures, monitor_id, last_change, idle_pause);
         }
 
         frame_counter += 1;
-        tokio::time::sleep(interval).await;
+        wait_for_next_capture(cycle_start, idle_capture_interval(interval, idle_captures)).await;
+    }
+}
+
+/// Marks the monitor idle once nothing changed for `idle_pause`, and counts the captures since
+/// it went idle, which slow down capture.
+fn next_idle_captures(
+    idle_captures: u32,
+    monitor_id: u32,
+    last_change: Instant,
+    idle_pause: Option<Duration>,
+) -> u32 {
+    if !idle_pause.is_some_and(|idle_pause| last_change.elapsed() >= idle_pause) {
+        return 0;
+    }
+    if IDLE_STATUS.set_idle(monitor_id) {
+        info!(
+            "idle pause activated on monitor {}, no screen change for {:.0?}",
+            monitor_id,
+            last_change.elapsed()
+        );
     }
+    idle_captures.saturating_add(1)
 }
 
 pub struct MaxAverageFrame {
     pub image: DynamicImage,
-    pub window_images: Vec<(DynamicImage, String, String, bool)>,
+    pub window_images: Vec<(DynamicImage, String, String, bool, Option<WindowBounds>)>,
     pub image_hash: u64,
     pub frame_number: u64,
     pub timestamp: Instant,
-    pub result_tx: Sender<CaptureResult>,
     pub average: f64,
+    pub cursor: Option<MousePosition>,
+    /// Draw `cursor` into the stored image, see [`with_cursor`]
+    pub draw_cursor: bool,
+}
+
+/// Draws the cursor into the image that is stored, once dedup and ocr are done with the frame,
+/// so a moving mouse alone does not count as a screen change.
+fn with_cursor(mut result: CaptureResult, draw: bool) -> CaptureResult {
+    if let Some(cursor) = result.cursor.filter(|_| draw) {
+        draw_cursor(&mut result.image, cursor);
+    }
+    result
+}
+
+/// The frame with its windows and no text, for frames left out by `ocr_skip_frames` or
+/// `disable_ocr`.
+fn skipped_ocr_result(frame: MaxAverageFrame) -> CaptureResult {
+    debug!("Skipping OCR for frame {}", frame.frame_number);
+    let window_ocr_results = frame
+        .window_images
+        .into_iter()
+        .map(
+            |(image, app_name, window_name, focused, bounds)| WindowOcrResult {
+                image,
+                bounds,
+                window_name,
+                app_name,
+                text: String::new(),
+                text_json: Vec::new(),
+                focused,
+                confidence: 0.0,
+                ocr_failed: false,
+            },
+        )
+        .collect();
+    CaptureResult {
+        image: frame.image,
+        frame_number: frame.frame_number,
+        timestamp: frame.timestamp,
+        window_ocr_results,
+        cursor: frame.cursor,
+        ocr_skipped: true,
+        preprocess_timings: PreprocessTimings::default(),
+    }
 }
 
 pub async fn process_ocr_task(
     ocr_task_data: OcrTaskData,
     save_text_files_flag: bool,
     ocr_engine: &OcrEngine,
+    ocr_fallback: Option<&OcrFallback>,
 ) -> Result<(), std::io::Error> {
     let OcrTaskData {
         image,
@@ -186,6 +501,37 @@ pub async fn process_ocr_task(
         result_tx,
     } = ocr_task_data;
 
+    let capture_result = run_ocr(
+        image,
+        window_images,
+        frame_number,
+        timestamp,
+        save_text_files_flag,
+        ocr_engine,
+        ocr_fallback,
+        OcrPreprocess::default(),
+    )
+    .await?;
+    if let Err(e) = result_tx.send(capture_result).await {
+        error!("Failed to send OCR result: {}", e);
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::Other,
+            "Failed to send OCR result",
+        ));
+    }
+    Ok(())
+}
+
+async fn run_ocr(
+    image: DynamicImage,
+    window_images: Vec<(DynamicImage, String, String, bool, Option<WindowBounds>)>,
+    frame_number: u64,
+    timestamp: Instant,
+    save_text_files_flag: bool,
+    ocr_engine: &OcrEngine,
+    ocr_fallback: Option<&OcrFallback>,
+    ocr_preprocess: OcrPreprocess,
+) -> Result<CaptureResult, std::io::Error> {
     let start_time = Instant::now();
     debug!(
         "Performing OCR for frame number since beginning of program {}",
@@ -195,26 +541,44 @@ pub async fn process_ocr_task(
     let mut window_ocr_results = Vec::new();
     let mut total_confidence = 0.0;
     let mut window_count = 0;
+    let mut preprocess_timings = PreprocessTimings::default();
 
-    for (window_image, window_app_name, window_name, focused) in window_images {
-        let (window_text, window_json_output, confidence) = match ocr_engine {
-            OcrEngine::Unstructured => perform_ocr_cloud(&window_image)
-                .await
-                .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e))?,
-            OcrEngine::Tesseract => perform_ocr_tesseract(&window_image),
-            #[cfg(target_os = "windows")]
-            OcrEngine::WindowsNative => perform_ocr_windows(&window_image)
-                .await
-                .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e))?,
-            #[cfg(target_os = "macos")]
-            OcrEngine::AppleNative => parse_apple_ocr_result(&perform_ocr_apple(&window_image)),
-            _ => {
-                return Err(std::io::Error::new(
-                    std::io::ErrorKind::Other,
-                    "Unsupported OCR engine",
-                ))
-            }
-        };
+    for (window_image, window_app_name, window_name, focused, bounds) in window_images {
+        let preprocessed = ocr_preprocess.is_enabled().then(|| {
+            let (image, timings, transform) = ocr_preprocess.apply(&window_image);
+            preprocess_timings.add(timings);
+            (image, transform)
+        });
+        let ocr_image = preprocessed
+            .as_ref()
+            .map_or(&window_image, |(image, _)| image);
+        let (window_text, window_json_output, confidence, ocr_failed) =
+            match perform_ocr_with_fallback(ocr_image, ocr_engine, ocr_fallback).await {
+                Ok(((text, json, confidence), used_engine)) => {
+                    let json = match &preprocessed {
+                        // the boxes are on the preprocessed image, stored ones are on the window
+                        Some((_, transform)) if !transform.is_identity() => {
+                            let mut boxes = parse_json_output(&json);
+                            transform.restore_boxes(
+                                &mut boxes,
+                                matches!(used_engine, OcrEngine::AppleNative),
+                            );
+                            serde_json::to_string(&boxes).unwrap_or(json)
+                        }
+                        _ => json,
+                    };
+                    (text, json, confidence, false)
+                }
+                // the frame is kept without text instead of stalling on an unreachable api
+                Err(e) if matches!(ocr_engine, OcrEngine::Unstructured) => {
+                    warn!(
+                        "cloud ocr failed for frame {} ({}): {}",
+                        frame_number, window_app_name, e
+                    );
+                    (String::new(), String::new(), None, true)
+                }
+                Err(e) => return Err(e),
+            };
 
         if let Some(conf) = confidence {
             total_confidence += conf;
@@ -223,12 +587,14 @@ pub async fn process_ocr_task(
 
         window_ocr_results.push(WindowOcrResult {
             image: window_image,
+            bounds,
             window_name,
             app_name: window_app_name,
             text: window_text,
             text_json: parse_json_output(&window_json_output),
             focused,
             confidence: confidence.unwrap_or(0.0),
+            ocr_failed,
         });
     }
 
@@ -249,16 +615,11 @@ pub async fn process_ocr_task(
         frame_number,
         timestamp,
         window_ocr_results,
+        cursor: None,
+        ocr_skipped: false,
+        preprocess_timings,
     };
 
-    if let Err(e) = result_tx.send(capture_result).await {
-        error!("Failed to send OCR result: {}", e);
-        return Err(std::io::Error::new(
-            std::io::ErrorKind::Other,
-            "Failed to send OCR result",
-        ));
-    }
-
     let duration = start_time.elapsed();
     let avg_confidence = if window_count > 0 {
         total_confidence / window_count as f64
@@ -269,7 +630,60 @@ pub async fn process_ocr_task(
         "OCR task processed frame {} with {} windows in {:?}, average confidence: {:.2}",
         frame_number, window_count, duration, avg_confidence
     );
-    Ok(())
+    Ok(capture_result)
+}
+
+async fn perform_ocr_with_fallback<'a>(
+    image: &DynamicImage,
+    ocr_engine: &'a OcrEngine,
+    ocr_fallback: Option<&'a OcrFallback>,
+) -> Result<((String, String, Option<f64>), &'a OcrEngine), std::io::Error> {
+    let fallback = match ocr_fallback {
+        Some(fallback) => fallback,
+        None => return Ok((ocr_engine.perform_ocr(image).await?, ocr_engine)),
+    };
+
+    let primary = match ocr_engine.perform_ocr(image).await {
+        Ok(result) => result,
+        Err(e) => {
+            warn!(
+                "{:?} OCR failed: {}, using fallback {:?}",
+                ocr_engine, e, fallback.engine
+            );
+            return Ok((fallback.engine.perform_ocr(image).await?, &fallback.engine));
+        }
+    };
+
+    // engines without a confidence score are trusted as is
+    let primary_confidence = match primary.2 {
+        Some(conf) => ocr_engine.normalize_confidence(conf),
+        None => return Ok((primary, ocr_engine)),
+    };
+    if primary_confidence >= fallback.confidence_threshold {
+        return Ok((primary, ocr_engine));
+    }
+
+    debug!(
+        "{:?} OCR confidence {:.2} below threshold {:.2}, retrying with {:?}",
+        ocr_engine, primary_confidence, fallback.confidence_threshold, fallback.engine
+    );
+    match fallback.engine.perform_ocr(image).await {
+        Ok(secondary) => {
+            let secondary_confidence = secondary
+                .2
+                .map(|conf| fallback.engine.normalize_confidence(conf))
+                .unwrap_or(0.0);
+            if secondary_confidence > primary_confidence {
+                Ok((secondary, &fallback.engine))
+            } else {
+                Ok((primary, ocr_engine))
+            }
+        }
+        Err(e) => {
+            warn!("fallback {:?} OCR failed: {}", fallback.engine, e);
+            Ok((primary, ocr_engine))
+        }
+    }
 }
 
 fn parse_json_output(json_output: &str) -> Vec<HashMap<String, String>> {
@@ -0,0 +1,87 @@
+//! Mouse cursor of a captured frame. Its position is kept with every frame, and with
+//! `--capture-cursor` an arrow is drawn into the image, the capture apis leaving the cursor out.
+
+use device_query::{DeviceQuery, DeviceState};
+use image::{DynamicImage, GenericImage, GenericImageView, Rgba};
+use xcap::Monitor;
+
+/// Pixel of the frame the cursor points at, from its top left corner.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MousePosition {
+    pub x: i32,
+    pub y: i32,
+}
+
+// 'B' outline, 'W' fill, the hot spot is the top left pixel
+const ARROW: [&str; 19] = [
+    "B",
+    "BB",
+    "BWB",
+    "BWWB",
+    "BWWWB",
+    "BWWWWB",
+    "BWWWWWB",
+    "BWWWWWWB",
+    "BWWWWWWWB",
+    "BWWWWWWWWB",
+    "BWWWWWWWWWB",
+    "BWWWWWWBBBBB",
+    "BWWWBWWB",
+    "BWWBBWWB",
+    "BWB  BWWB",
+    "BB   BWWB",
+    "B     BWWB",
+    "      BWWB",
+    "       BB",
+];
+
+/// Maps `global`, in the desktop coordinates the monitor's origin and size are in, onto a frame of
+/// `image_size` pixels, which differs from the monitor size on scaled displays. `None` when the
+/// cursor is on another monitor.
+pub fn to_image_position(
+    global: (i32, i32),
+    monitor_origin: (i32, i32),
+    monitor_size: (u32, u32),
+    image_size: (u32, u32),
+) -> Option<MousePosition> {
+    let x = global.0 - monitor_origin.0;
+    let y = global.1 - monitor_origin.1;
+    if x < 0 || y < 0 || x >= monitor_size.0 as i32 || y >= monitor_size.1 as i32 {
+        return None;
+    }
+    Some(MousePosition {
+        x: (x as i64 * image_size.0 as i64 / monitor_size.0.max(1) as i64) as i32,
+        y: (y as i64 * image_size.1 as i64 / monitor_size.1.max(1) as i64) as i32,
+    })
+}
+
+/// Where the cursor is on the frame just captured from `monitor`, `None` when it is on another
+/// monitor or the platform doesn't tell (no X display, missing accessibility permission).
+pub fn mouse_position(monitor: &Monitor, image: &DynamicImage) -> Option<MousePosition> {
+    let coords = DeviceState::checked_new()?.get_mouse().coords;
+    to_image_position(
+        coords,
+        (monitor.x(), monitor.y()),
+        (monitor.width(), monitor.height()),
+        image.dimensions(),
+    )
+}
+
+/// Draws an arrow with its tip at `position`, clipped to the image.
+pub fn draw_cursor(image: &mut DynamicImage, position: MousePosition) {
+    let (width, height) = image.dimensions();
+    for (dy, row) in ARROW.iter().enumerate() {
+        for (dx, pixel) in row.chars().enumerate() {
+            let color = match pixel {
+                'B' => Rgba([0, 0, 0, 255]),
+                'W' => Rgba([255, 255, 255, 255]),
+                _ => continue,
+            };
+            let x = position.x as i64 + dx as i64;
+            let y = position.y as i64 + dy as i64;
+            if x >= 0 && y >= 0 && x < width as i64 && y < height as i64 {
+                image.put_pixel(x as u32, y as u32, color);
+            }
+        }
+    }
+}
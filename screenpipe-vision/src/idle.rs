@@ -0,0 +1,56 @@
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Which monitors paused capture for lack of screen changes, see `--idle-pause-secs`.
+pub struct IdleStatus {
+    /// Since when each capturing monitor is idle, `None` while it is active
+    monitors: Mutex<BTreeMap<u32, Option<Instant>>>,
+}
+
+impl IdleStatus {
+    pub const fn new() -> Self {
+        IdleStatus {
+            monitors: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    /// Returns `false` when the monitor was already idle.
+    pub fn set_idle(&self, monitor_id: u32) -> bool {
+        let mut monitors = self.monitors.lock().unwrap();
+        let since = monitors.entry(monitor_id).or_default();
+        if since.is_some() {
+            return false;
+        }
+        *since = Some(Instant::now());
+        true
+    }
+
+    /// Returns how long the monitor was idle, `None` when it was not.
+    pub fn set_active(&self, monitor_id: u32) -> Option<Duration> {
+        let mut monitors = self.monitors.lock().unwrap();
+        monitors
+            .insert(monitor_id, None)
+            .flatten()
+            .map(|since| since.elapsed())
+    }
+
+    /// How long every capturing monitor has been idle, `None` while any of them records.
+    pub fn idle_for(&self) -> Option<Duration> {
+        let monitors = self.monitors.lock().unwrap();
+        // an active monitor sorts first
+        monitors
+            .values()
+            .map(|since| since.map(|since| since.elapsed()))
+            .min()
+            .flatten()
+    }
+}
+
+impl Default for IdleStatus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub static IDLE_STATUS: IdleStatus = IdleStatus::new();
@@ -1,16 +1,38 @@
 #[cfg(target_os = "macos")]
 pub mod apple;
 pub mod core;
+pub mod cursor;
+pub mod idle;
 #[cfg(target_os = "windows")]
 pub mod microsoft;
 pub mod monitor;
+pub mod normalize;
+pub mod ocr_provider;
+pub mod preprocess;
 pub mod tesseract;
 pub mod utils;
 #[cfg(target_os = "macos")]
 pub use apple::{parse_apple_ocr_result, perform_ocr_apple};
-pub use core::{continuous_capture, process_ocr_task, CaptureResult};
-pub use utils::OcrEngine;
+pub use core::{
+    capture_interval, continuous_capture, continuous_capture_with_config, default_ocr_workers,
+    idle_capture_interval, process_ocr_task, should_run_ocr, CaptureConfig, CaptureResult,
+    MaxAverageFrame, OcrWorkers, SharedCaptureConfig, DEFAULT_DEDUP_THRESHOLD, MAX_FPS,
+    MAX_IDLE_INTERVAL, MIN_FPS,
+};
+pub use cursor::MousePosition;
+pub use idle::{IdleStatus, IDLE_STATUS};
+pub use normalize::normalize_ocr_text;
+#[cfg(target_os = "macos")]
+pub use ocr_provider::AppleOcr;
+#[cfg(target_os = "windows")]
+pub use ocr_provider::WindowsOcr;
+pub use ocr_provider::{OcrProvider, TesseractOcr, UnstructuredOcr};
+pub use preprocess::{OcrPreprocess, PreprocessTimings, PreprocessTransform};
+pub use utils::{OcrEngine, OcrFallback};
 pub mod capture_screenshot_by_window;
+pub use capture_screenshot_by_window::{to_image_bounds, WindowBounds};
 #[cfg(target_os = "windows")]
 pub use microsoft::perform_ocr_windows;
-pub use tesseract::perform_ocr_tesseract;
+pub use tesseract::{
+    detect_script, parse_osd_script, perform_ocr_tesseract, script_lang, set_tesseract_langs,
+};
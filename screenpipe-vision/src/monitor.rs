@@ -40,6 +40,24 @@ pub async fn get_windows_for_monitor(monitor: Monitor) -> Vec<Window> {
         .collect()
 }
 
+/// Words in the names capture backends give to displays of remote sessions, virtual display
+/// drivers and headless servers. Sidecar and AirPlay displays are screens someone looks at, they
+/// count as physical.
+const VIRTUAL_DISPLAY_NAMES: [&str; 6] = ["virtual", "vnc", "rdp", "dummy", "headless", "xvfb"];
+
+/// Whether a display named `name` is a virtual one (a VNC or RDP session, a dummy display
+/// driver, ...) rather than a physical screen. The capture apis don't tell, so this goes by name.
+pub fn is_virtual_display_name(name: &str) -> bool {
+    let name = name.to_lowercase();
+    VIRTUAL_DISPLAY_NAMES
+        .iter()
+        .any(|virtual_name| name.contains(virtual_name))
+}
+
+pub fn is_virtual_monitor(monitor: &Monitor) -> bool {
+    is_virtual_display_name(monitor.name())
+}
+
 pub async fn list_monitors() -> Vec<Monitor> {
     let monitors = Monitor::all().unwrap();
     return monitors.iter().map(|m| m.clone()).collect();
@@ -0,0 +1,46 @@
+use unicode_normalization::UnicodeNormalization;
+
+/// Typographic ligatures tesseract reads off rendered text, letters such as `æ` are left alone.
+const LIGATURES: [(char, &str); 7] = [
+    ('\u{FB00}', "ff"),
+    ('\u{FB01}', "fi"),
+    ('\u{FB02}', "fl"),
+    ('\u{FB03}', "ffi"),
+    ('\u{FB04}', "ffl"),
+    ('\u{FB05}', "st"),
+    ('\u{FB06}', "st"),
+];
+
+/// Cleans up raw ocr output before it is stored: expands ligatures, drops control characters,
+/// applies NFC and collapses whitespace within lines and runs of blank lines, keeping the line
+/// layout otherwise.
+pub fn normalize_ocr_text(input: &str) -> String {
+    let mut expanded = String::with_capacity(input.len());
+    for c in input.chars() {
+        match LIGATURES.iter().find(|(ligature, _)| *ligature == c) {
+            Some((_, letters)) => expanded.push_str(letters),
+            None if c.is_control() && c != '\n' && c != '\t' => {}
+            None => expanded.push(c),
+        }
+    }
+    let expanded: String = expanded.nfc().collect();
+
+    let mut output = String::with_capacity(expanded.len());
+    let mut blank_line = false;
+    for line in expanded.lines() {
+        let line = line.split_whitespace().collect::<Vec<_>>().join(" ");
+        if line.is_empty() {
+            blank_line = !output.is_empty();
+            continue;
+        }
+        if !output.is_empty() {
+            output.push('\n');
+            if blank_line {
+                output.push('\n');
+            }
+        }
+        output.push_str(&line);
+        blank_line = false;
+    }
+    output
+}
@@ -0,0 +1,122 @@
+use async_trait::async_trait;
+use image::DynamicImage;
+use screenpipe_integrations::unstructured_ocr::perform_ocr_cloud;
+
+#[cfg(target_os = "macos")]
+use crate::apple::{parse_apple_ocr_result, perform_ocr_apple};
+#[cfg(target_os = "windows")]
+use crate::microsoft::perform_ocr_windows;
+use crate::tesseract::perform_ocr_tesseract;
+use crate::utils::OcrEngine;
+
+/// What every OCR engine implements, [`OcrEngine::provider`] gives the one an engine runs with.
+/// Callers like the fallback only go through this, so an engine is added by implementing it.
+#[async_trait]
+pub trait OcrProvider: Send + Sync {
+    /// Runs on `image`, returning `(text, json, confidence)` with the engine's own confidence.
+    async fn perform_ocr(
+        &self,
+        image: &DynamicImage,
+    ) -> Result<(String, String, Option<f64>), std::io::Error>;
+
+    /// Maps a confidence of [`Self::perform_ocr`] onto 0.0-1.0 so engines can be compared.
+    fn normalize_confidence(&self, confidence: f64) -> f64 {
+        confidence.clamp(0.0, 1.0)
+    }
+}
+
+fn other_error(e: impl std::fmt::Display) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::Other, e.to_string())
+}
+
+/// The Unstructured cloud api, needs `UNSTRUCTURED_API_KEY`.
+pub struct UnstructuredOcr;
+
+#[async_trait]
+impl OcrProvider for UnstructuredOcr {
+    async fn perform_ocr(
+        &self,
+        image: &DynamicImage,
+    ) -> Result<(String, String, Option<f64>), std::io::Error> {
+        perform_ocr_cloud(image).await.map_err(other_error)
+    }
+}
+
+pub struct TesseractOcr;
+
+#[async_trait]
+impl OcrProvider for TesseractOcr {
+    async fn perform_ocr(
+        &self,
+        image: &DynamicImage,
+    ) -> Result<(String, String, Option<f64>), std::io::Error> {
+        perform_ocr_tesseract(image)
+    }
+
+    // tesseract reports confidence as a percentage
+    fn normalize_confidence(&self, confidence: f64) -> f64 {
+        (confidence / 100.0).clamp(0.0, 1.0)
+    }
+}
+
+#[cfg(target_os = "windows")]
+pub struct WindowsOcr;
+
+#[cfg(target_os = "windows")]
+#[async_trait]
+impl OcrProvider for WindowsOcr {
+    async fn perform_ocr(
+        &self,
+        image: &DynamicImage,
+    ) -> Result<(String, String, Option<f64>), std::io::Error> {
+        perform_ocr_windows(image).await.map_err(other_error)
+    }
+}
+
+#[cfg(target_os = "macos")]
+pub struct AppleOcr;
+
+#[cfg(target_os = "macos")]
+#[async_trait]
+impl OcrProvider for AppleOcr {
+    async fn perform_ocr(
+        &self,
+        image: &DynamicImage,
+    ) -> Result<(String, String, Option<f64>), std::io::Error> {
+        Ok(parse_apple_ocr_result(&perform_ocr_apple(image)))
+    }
+}
+
+impl OcrEngine {
+    /// The implementation of this engine, `None` for a native engine of another platform.
+    pub fn provider(&self) -> Option<&'static dyn OcrProvider> {
+        match self {
+            OcrEngine::Unstructured => Some(&UnstructuredOcr),
+            OcrEngine::Tesseract => Some(&TesseractOcr),
+            #[cfg(target_os = "windows")]
+            OcrEngine::WindowsNative => Some(&WindowsOcr),
+            #[cfg(target_os = "macos")]
+            OcrEngine::AppleNative => Some(&AppleOcr),
+            _ => None,
+        }
+    }
+
+    /// Runs this engine on `image`, returning `(text, json, confidence)`.
+    pub async fn perform_ocr(
+        &self,
+        image: &DynamicImage,
+    ) -> Result<(String, String, Option<f64>), std::io::Error> {
+        match self.provider() {
+            Some(provider) => provider.perform_ocr(image).await,
+            None => Err(other_error(format!("{:?} OCR is not supported here", self))),
+        }
+    }
+
+    /// Maps an engine-specific confidence onto 0.0-1.0 so engines can be compared.
+    pub fn normalize_confidence(&self, confidence: f64) -> f64 {
+        match self.provider() {
+            Some(provider) => provider.normalize_confidence(confidence),
+            None => confidence.clamp(0.0, 1.0),
+        }
+    }
+}
@@ -0,0 +1,337 @@
+//! Optional cleanup of window images before ocr, `--ocr-preprocess-*`: screenshots hold small,
+//! antialiased text that tesseract reads better once enlarged, straightened and thresholded.
+
+use image::imageops::FilterType;
+use image::{DynamicImage, GrayImage, Luma};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// Most a text line is looked for off horizontal, in degrees
+const MAX_SKEW_DEGREES: f32 = 15.0;
+const SKEW_STEP_DEGREES: f32 = 0.25;
+/// Smaller skews are left alone, rotating blurs the text more than it helps
+const MIN_SKEW_DEGREES: f32 = 0.2;
+/// Foreground pixels the skew is estimated from, more are sampled evenly
+const MAX_SKEW_SAMPLES: usize = 20_000;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct OcrPreprocess {
+    /// Bicubic upscale by this factor, `None` or 1 leaves the size as is
+    pub upscale: Option<u32>,
+    /// Black and white at the otsu threshold
+    pub binarize: bool,
+    /// Rotates text lines back to horizontal
+    pub deskew: bool,
+}
+
+/// Time each step took, `None` for steps turned off.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PreprocessTimings {
+    pub upscale: Option<Duration>,
+    pub binarize: Option<Duration>,
+    pub deskew: Option<Duration>,
+}
+
+impl PreprocessTimings {
+    /// Adds up the timings of the windows of a frame.
+    pub fn add(&mut self, other: PreprocessTimings) {
+        fn sum(a: Option<Duration>, b: Option<Duration>) -> Option<Duration> {
+            match (a, b) {
+                (Some(a), Some(b)) => Some(a + b),
+                (a, b) => a.or(b),
+            }
+        }
+        self.upscale = sum(self.upscale, other.upscale);
+        self.binarize = sum(self.binarize, other.binarize);
+        self.deskew = sum(self.deskew, other.deskew);
+    }
+}
+
+/// How a preprocessed image was resized and rotated, to place what ocr found on it back on
+/// the window image.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PreprocessTransform {
+    /// The window image was upscaled by this factor, 1 when it was not
+    pub factor: u32,
+    /// The upscaled image was rotated back by this many degrees, 0 when it was not
+    pub skew_degrees: f32,
+    /// Size of the preprocessed image
+    pub size: (u32, u32),
+}
+
+impl PreprocessTransform {
+    pub fn is_identity(&self) -> bool {
+        self.factor <= 1 && self.skew_degrees == 0.0
+    }
+
+    /// Box of the window image covering the box at `left`, `top` of the preprocessed image,
+    /// undoing the deskew rotation then the upscale.
+    pub fn to_original(&self, left: f64, top: f64, width: f64, height: f64) -> [f64; 4] {
+        let (size_x, size_y) = (self.size.0 as f64, self.size.1 as f64);
+        let (cx, cy) = (size_x / 2.0, size_y / 2.0);
+        // the preprocessed image was sampled at this rotation of the upscaled one, see `rotate`
+        let (sin, cos) = (-self.skew_degrees as f64).to_radians().sin_cos();
+        let corners = [
+            (left, top),
+            (left + width, top),
+            (left, top + height),
+            (left + width, top + height),
+        ]
+        .map(|(x, y)| {
+            let (dx, dy) = (x - cx, y - cy);
+            (cos * dx - sin * dy + cx, sin * dx + cos * dy + cy)
+        });
+        let clamp_x = |x: f64| x.clamp(0.0, size_x);
+        let clamp_y = |y: f64| y.clamp(0.0, size_y);
+        let min_x = clamp_x(corners.iter().map(|c| c.0).fold(f64::MAX, f64::min));
+        let max_x = clamp_x(corners.iter().map(|c| c.0).fold(f64::MIN, f64::max));
+        let min_y = clamp_y(corners.iter().map(|c| c.1).fold(f64::MAX, f64::min));
+        let max_y = clamp_y(corners.iter().map(|c| c.1).fold(f64::MIN, f64::max));
+        let factor = self.factor.max(1) as f64;
+        [
+            min_x / factor,
+            min_y / factor,
+            (max_x - min_x) / factor,
+            (max_y - min_y) / factor,
+        ]
+    }
+
+    /// Moves the `left`, `top`, `width` and `height` of each ocr box back onto the window image.
+    /// `normalized` boxes are fractions of the image with y up, as apple vision gives them,
+    /// the others are pixels.
+    pub fn restore_boxes(&self, boxes: &mut [HashMap<String, String>], normalized: bool) {
+        if self.is_identity() {
+            return;
+        }
+        let (size_x, size_y) = (self.size.0 as f64, self.size.1 as f64);
+        let factor = self.factor.max(1) as f64;
+        let (original_x, original_y) = (size_x / factor, size_y / factor);
+        for ocr_box in boxes.iter_mut() {
+            let field = |name: &str| {
+                ocr_box
+                    .get(name)
+                    .and_then(|value| value.parse::<f64>().ok())
+            };
+            let (Some(left), Some(top), Some(width), Some(height)) =
+                (field("left"), field("top"), field("width"), field("height"))
+            else {
+                continue;
+            };
+            let restored = if normalized {
+                let [left, top, width, height] = self.to_original(
+                    left * size_x,
+                    (1.0 - top - height) * size_y,
+                    width * size_x,
+                    height * size_y,
+                );
+                [
+                    left / original_x,
+                    1.0 - (top + height) / original_y,
+                    width / original_x,
+                    height / original_y,
+                ]
+                .map(|value| value.to_string())
+            } else {
+                self.to_original(left, top, width, height)
+                    .map(|value| (value.round() as i64).to_string())
+            };
+            for (name, value) in ["left", "top", "width", "height"].into_iter().zip(restored) {
+                ocr_box.insert(name.to_string(), value);
+            }
+        }
+    }
+}
+
+impl OcrPreprocess {
+    pub fn is_enabled(&self) -> bool {
+        self.upscale.is_some_and(|factor| factor > 1) || self.binarize || self.deskew
+    }
+
+    /// Upscales, then deskews, then binarizes `image`. Deskewed and binarized images are
+    /// grayscale.
+    pub fn apply(
+        &self,
+        image: &DynamicImage,
+    ) -> (DynamicImage, PreprocessTimings, PreprocessTransform) {
+        let mut timings = PreprocessTimings::default();
+        let mut image = image.clone();
+        let mut factor = 1;
+        let mut skew_degrees = 0.0;
+        if let Some(upscale_factor) = self.upscale.filter(|&factor| factor > 1) {
+            let start = Instant::now();
+            image = upscale(&image, upscale_factor);
+            factor = upscale_factor;
+            timings.upscale = Some(start.elapsed());
+        }
+        if self.deskew {
+            let start = Instant::now();
+            (image, skew_degrees) = straighten(&image);
+            timings.deskew = Some(start.elapsed());
+        }
+        if self.binarize {
+            let start = Instant::now();
+            image = binarize(&image);
+            timings.binarize = Some(start.elapsed());
+        }
+        let transform = PreprocessTransform {
+            factor,
+            skew_degrees,
+            size: (image.width(), image.height()),
+        };
+        (image, timings, transform)
+    }
+}
+
+pub fn upscale(image: &DynamicImage, factor: u32) -> DynamicImage {
+    image.resize_exact(
+        image.width().saturating_mul(factor),
+        image.height().saturating_mul(factor),
+        FilterType::CatmullRom,
+    )
+}
+
+/// Gray level that best splits the histogram of `image` in two classes (otsu's method).
+/// Pixels at or below it are the dark class.
+pub fn otsu_threshold(image: &GrayImage) -> u8 {
+    let mut histogram = [0u64; 256];
+    for pixel in image.pixels() {
+        histogram[pixel[0] as usize] += 1;
+    }
+    let total = image.pixels().len() as f64;
+    let total_sum: f64 = histogram
+        .iter()
+        .enumerate()
+        .map(|(level, &count)| level as f64 * count as f64)
+        .sum();
+
+    let (mut dark_count, mut dark_sum) = (0.0, 0.0);
+    let (mut best_threshold, mut best_variance) = (0u8, -1.0);
+    for (level, &count) in histogram.iter().enumerate() {
+        dark_count += count as f64;
+        dark_sum += level as f64 * count as f64;
+        let light_count = total - dark_count;
+        if dark_count == 0.0 || light_count == 0.0 {
+            continue;
+        }
+        let dark_mean = dark_sum / dark_count;
+        let light_mean = (total_sum - dark_sum) / light_count;
+        let variance = dark_count * light_count * (dark_mean - light_mean).powi(2);
+        if variance > best_variance {
+            best_variance = variance;
+            best_threshold = level as u8;
+        }
+    }
+    best_threshold
+}
+
+pub fn binarize(image: &DynamicImage) -> DynamicImage {
+    let mut gray = image.to_luma8();
+    let threshold = otsu_threshold(&gray);
+    for pixel in gray.pixels_mut() {
+        pixel[0] = if pixel[0] > threshold { 255 } else { 0 };
+    }
+    DynamicImage::ImageLuma8(gray)
+}
+
+/// Angle in degrees the text lines of `image` rise to the right by. Each angle is a hough
+/// transform restricted to near horizontal lines: the text pixels vote for the distance from
+/// the origin of the line through them at that angle, and the angle whose votes pile up on the
+/// fewest lines wins.
+pub fn estimate_skew(image: &GrayImage) -> f32 {
+    let threshold = otsu_threshold(image);
+    let dark = image.pixels().filter(|pixel| pixel[0] <= threshold).count();
+    // text is the smaller class, dark on light or light on dark
+    let text_is_dark = dark * 2 <= image.pixels().len();
+    let points: Vec<(f32, f32)> = image
+        .enumerate_pixels()
+        .filter(|(_, _, pixel)| (pixel[0] <= threshold) == text_is_dark)
+        .map(|(x, y, _)| (x as f32, y as f32))
+        .collect();
+    if points.is_empty() {
+        return 0.0;
+    }
+    let stride = points.len().div_ceil(MAX_SKEW_SAMPLES);
+    let points: Vec<(f32, f32)> = points.into_iter().step_by(stride).collect();
+
+    let diagonal = (image.width() as f32).hypot(image.height() as f32);
+    let offset = diagonal as usize + 1;
+    let mut accumulator = vec![0u32; 2 * offset + 1];
+    let steps = (MAX_SKEW_DEGREES / SKEW_STEP_DEGREES) as i32;
+    let (mut best_angle, mut best_score) = (0.0f32, 0u64);
+    for step in -steps..=steps {
+        let angle = step as f32 * SKEW_STEP_DEGREES;
+        let (sin, cos) = angle.to_radians().sin_cos();
+        accumulator.iter_mut().for_each(|votes| *votes = 0);
+        // image y grows downwards, a line rising to the right has y + x * tan(angle) constant
+        for &(x, y) in &points {
+            let rho = (y * cos + x * sin).round() as isize + offset as isize;
+            accumulator[rho as usize] += 1;
+        }
+        let score = accumulator
+            .iter()
+            .map(|&votes| votes as u64 * votes as u64)
+            .sum::<u64>();
+        if score > best_score || (score == best_score && angle.abs() < best_angle.abs()) {
+            best_score = score;
+            best_angle = angle;
+        }
+    }
+    best_angle
+}
+
+/// Rotates `image` by `degrees` counterclockwise around its center, corners that come from
+/// outside the image are filled with the background.
+pub fn rotate(image: &GrayImage, degrees: f32, background: u8) -> GrayImage {
+    let (width, height) = image.dimensions();
+    let (cx, cy) = (width as f32 / 2.0, height as f32 / 2.0);
+    let (sin, cos) = degrees.to_radians().sin_cos();
+    GrayImage::from_fn(width, height, |x, y| {
+        let (dx, dy) = (x as f32 + 0.5 - cx, y as f32 + 0.5 - cy);
+        // inverse of a counterclockwise rotation, with y pointing down
+        let sx = cos * dx - sin * dy + cx - 0.5;
+        let sy = sin * dx + cos * dy + cy - 0.5;
+        Luma([bilinear(image, sx, sy, background)])
+    })
+}
+
+fn bilinear(image: &GrayImage, x: f32, y: f32, background: u8) -> u8 {
+    let (width, height) = image.dimensions();
+    if x < 0.0 || y < 0.0 || x > (width - 1) as f32 || y > (height - 1) as f32 {
+        return background;
+    }
+    let (x0, y0) = (x.floor() as u32, y.floor() as u32);
+    let (x1, y1) = ((x0 + 1).min(width - 1), (y0 + 1).min(height - 1));
+    let (fx, fy) = (x - x0 as f32, y - y0 as f32);
+    let at = |x, y| image.get_pixel(x, y)[0] as f32;
+    let top = at(x0, y0) * (1.0 - fx) + at(x1, y0) * fx;
+    let bottom = at(x0, y1) * (1.0 - fx) + at(x1, y1) * fx;
+    (top * (1.0 - fy) + bottom * fy).round() as u8
+}
+
+/// Rotates the text lines of `image` back to horizontal.
+pub fn deskew(image: &DynamicImage) -> DynamicImage {
+    straighten(image).0
+}
+
+/// Deskewed `image` and the skew it was rotated back by, 0 when it was left as is.
+fn straighten(image: &DynamicImage) -> (DynamicImage, f32) {
+    let gray = image.to_luma8();
+    if gray.width() < 2 || gray.height() < 2 {
+        return (DynamicImage::ImageLuma8(gray), 0.0);
+    }
+    let skew = estimate_skew(&gray);
+    if skew.abs() < MIN_SKEW_DEGREES {
+        return (DynamicImage::ImageLuma8(gray), 0.0);
+    }
+    let threshold = otsu_threshold(&gray);
+    let dark = gray.pixels().filter(|pixel| pixel[0] <= threshold).count();
+    let background = if dark * 2 <= gray.pixels().len() {
+        255
+    } else {
+        0
+    };
+    (
+        DynamicImage::ImageLuma8(rotate(&gray, -skew, background)),
+        skew,
+    )
+}
@@ -1,21 +1,218 @@
 use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
 
 use image::DynamicImage;
+use log::{debug, warn};
 use rusty_tesseract::{Args, DataOutput, Image};
+use unicode_script::{Script, UnicodeScript};
 
-pub fn perform_ocr_tesseract(image: &DynamicImage) -> (String, String, Option<f64>) {
+const DEFAULT_LANG: &str = "eng";
+
+/// Tesseract language pack reading each script, all of them ship an LSTM model.
+pub fn script_lang(script: Script) -> Option<&'static str> {
+    match script {
+        Script::Latin => Some("eng"),
+        Script::Han => Some("chi_sim"),
+        Script::Hiragana | Script::Katakana => Some("jpn"),
+        Script::Hangul => Some("kor"),
+        Script::Cyrillic => Some("rus"),
+        Script::Greek => Some("ell"),
+        Script::Arabic => Some("ara"),
+        Script::Hebrew => Some("heb"),
+        Script::Devanagari => Some("hin"),
+        Script::Thai => Some("tha"),
+        _ => None,
+    }
+}
+
+/// Script of a `Script:` line of tesseract's orientation and script detection (`--psm 0`).
+pub fn parse_osd_script(osd: &str) -> Option<Script> {
+    let name = osd
+        .lines()
+        .find_map(|line| line.trim().strip_prefix("Script:"))?
+        .trim();
+    match name {
+        "Latin" => Some(Script::Latin),
+        "Han" => Some(Script::Han),
+        "Japanese" | "Hiragana" | "Katakana" => Some(Script::Hiragana),
+        "Korean" | "Hangul" => Some(Script::Hangul),
+        "Cyrillic" => Some(Script::Cyrillic),
+        "Greek" => Some(Script::Greek),
+        "Arabic" => Some(Script::Arabic),
+        "Hebrew" => Some(Script::Hebrew),
+        "Devanagari" => Some(Script::Devanagari),
+        "Thai" => Some(Script::Thai),
+        _ => None,
+    }
+}
+
+/// Script most letters of `text` are written in, punctuation and digits don't count.
+pub fn detect_script(text: &str) -> Option<Script> {
+    let mut counts: HashMap<Script, usize> = HashMap::new();
+    for c in text.chars() {
+        let script = c.script();
+        if !matches!(script, Script::Common | Script::Inherited | Script::Unknown) {
+            *counts.entry(script).or_default() += 1;
+        }
+    }
+    counts
+        .into_iter()
+        .max_by_key(|(_, count)| *count)
+        .map(|(script, _)| script)
+}
+
+/// Tesseract runs as a new process on every call, so what is kept between calls is the language
+/// the last frame was read with, the args per language and which scripts have a pack installed.
+struct LangCache {
+    current: String,
+    args: HashMap<String, Args>,
+    installed: Option<Vec<String>>,
+    script_langs: HashMap<Script, Option<String>>,
+}
+
+fn lang_cache() -> &'static Mutex<LangCache> {
+    static CACHE: OnceLock<Mutex<LangCache>> = OnceLock::new();
+    CACHE.get_or_init(|| {
+        Mutex::new(LangCache {
+            current: DEFAULT_LANG.to_string(),
+            args: HashMap::new(),
+            installed: None,
+            script_langs: HashMap::new(),
+        })
+    })
+}
+
+/// `--ocr-hint-language`: the languages frames are read with until a script another pack reads
+/// is detected. Several are used together, `eng` and `deu` as `eng+deu` so tesseract picks per
+/// word, a single `deu` reads English text with the German pack only.
+pub fn set_tesseract_langs(langs: &[String]) {
+    if !langs.is_empty() {
+        lang_cache().lock().unwrap().current = langs.join("+");
+    }
+}
+
+impl LangCache {
+    fn args(&mut self, lang: &str) -> Args {
+        self.args
+            .entry(lang.to_string())
+            .or_insert_with(|| Args {
+                lang: lang.to_string(),
+                config_variables: HashMap::from([("tessedit_create_tsv".into(), "1".into())]),
+                dpi: Some(600), // 150 is a balanced option, 600 seems faster surprisingly, the bigger the number the more granualar result
+                psm: Some(1), // PSM 1: Automatic page segmentation with OSD. PSM 3: Automatic page segmentation with OSD
+                oem: Some(1), //1: Neural nets LSTM engine only,    3: Default, based on what is available. (Default)
+            })
+            .clone()
+    }
+
+    fn installed(&mut self) -> &[String] {
+        self.installed.get_or_insert_with(|| {
+            rusty_tesseract::get_tesseract_langs().unwrap_or_else(|e| {
+                warn!("failed to list tesseract languages: {}", e);
+                Vec::new()
+            })
+        })
+    }
+
+    /// The installed language pack for `script`, `None` when it has none or it isn't installed.
+    fn script_lang(&mut self, script: Script) -> Option<String> {
+        if let Some(lang) = self.script_langs.get(&script) {
+            return lang.clone();
+        }
+        let installed = self.installed();
+        let lang = script_lang(script)
+            .filter(|lang| installed.iter().any(|installed| installed == lang))
+            .map(str::to_string);
+        self.script_langs.insert(script, lang.clone());
+        lang
+    }
+}
+
+/// Script of `image` by tesseract's orientation and script detection, which looks at the shapes
+/// of the letters whatever language pack reads them. `None` without the `osd` pack or when there
+/// is too little text to tell.
+fn detect_image_script(image: &Image) -> Option<Script> {
+    if !lang_cache()
+        .lock()
+        .unwrap()
+        .installed()
+        .iter()
+        .any(|lang| lang == "osd")
+    {
+        return None;
+    }
     let args = Args {
-        lang: "eng".to_string(),
-        config_variables: HashMap::from([("tessedit_create_tsv".into(), "1".into())]),
-        dpi: Some(600), // 150 is a balanced option, 600 seems faster surprisingly, the bigger the number the more granualar result
-        psm: Some(1), // PSM 1: Automatic page segmentation with OSD. PSM 3: Automatic page segmentation with OSD
-        oem: Some(1), //1: Neural nets LSTM engine only,    3: Default, based on what is available. (Default)
+        lang: "osd".to_string(),
+        config_variables: HashMap::new(),
+        dpi: Some(600),
+        psm: Some(0),
+        oem: None,
+    };
+    match rusty_tesseract::image_to_string(image, &args) {
+        Ok(osd) => parse_osd_script(&osd),
+        Err(e) => {
+            debug!("tesseract script detection failed: {}", e);
+            None
+        }
+    }
+}
+
+/// Reads `image` with the language of the previous frame, and when its letters are in a script
+/// another installed language pack reads, again with that one. The script comes from tesseract's
+/// script detection, or without the `osd` pack from the text of the first pass, which only tells
+/// scripts the current pack can read. The result with the higher confidence wins and its language
+/// is used first for the next frame.
+pub fn perform_ocr_tesseract(
+    image: &DynamicImage,
+) -> Result<(String, String, Option<f64>), std::io::Error> {
+    let (lang, args) = {
+        let mut cache = lang_cache().lock().unwrap();
+        let lang = cache.current.clone();
+        let args = cache.args(&lang);
+        (lang, args)
     };
+    let ocr_image = Image::from_dynamic_image(image).map_err(tesseract_error)?;
+    let first = ocr_with_args(&ocr_image, &args)?;
+
+    let script = detect_image_script(&ocr_image).or_else(|| detect_script(&first.0));
+    let switch = script.and_then(|script| {
+        let mut cache = lang_cache().lock().unwrap();
+        let detected = cache
+            .script_lang(script)
+            .filter(|detected| !lang.split('+').any(|lang| lang == detected))?;
+        let args = cache.args(&detected);
+        Some((script, detected, args))
+    });
+    let Some((script, detected, args)) = switch else {
+        return Ok(first);
+    };
+
+    debug!(
+        "tesseract read {:?} text with {}, re-running with {}",
+        script, lang, detected
+    );
+    let second = ocr_with_args(&ocr_image, &args)?;
+    if second.2.unwrap_or(0.0) >= first.2.unwrap_or(0.0) {
+        lang_cache().lock().unwrap().current = detected;
+        Ok(second)
+    } else {
+        Ok(first)
+    }
+}
 
-    let ocr_image = Image::from_dynamic_image(image).unwrap();
+fn tesseract_error(e: impl std::fmt::Display) -> std::io::Error {
+    std::io::Error::new(
+        std::io::ErrorKind::Other,
+        format!("tesseract failed: {}", e),
+    )
+}
 
+fn ocr_with_args(
+    ocr_image: &Image,
+    args: &Args,
+) -> Result<(String, String, Option<f64>), std::io::Error> {
     // Extract data output
-    let data_output = rusty_tesseract::image_to_data(&ocr_image, &args).unwrap();
+    let data_output = rusty_tesseract::image_to_data(ocr_image, args).map_err(tesseract_error)?;
     // let tsv_output = data_output_to_tsv(&data_output);
 
     // Extract text from data output
@@ -24,7 +221,7 @@ pub fn perform_ocr_tesseract(image: &DynamicImage) -> (String, String, Option<f6
 
     let overall_confidence = calculate_overall_confidence(&data_output);
 
-    (text, json_output, Some(overall_confidence))
+    Ok((text, json_output, Some(overall_confidence)))
 }
 
 fn data_output_to_text(data_output: &DataOutput) -> String {
@@ -100,4 +297,4 @@ fn calculate_overall_confidence(data_output: &DataOutput) -> f64 {
     } else {
         0.0
     }
-}
\ No newline at end of file
+}
@@ -1,6 +1,6 @@
-use crate::capture_screenshot_by_window::capture_all_visible_windows;
+use crate::capture_screenshot_by_window::{capture_all_visible_windows, WindowBounds};
 use crate::core::MaxAverageFrame;
-use image::DynamicImage;
+use image::{DynamicImage, GenericImageView};
 use image_compare::{Algorithm, Metric, Similarity};
 use log::{debug, error, warn};
 use std::collections::HashMap;
@@ -24,6 +24,15 @@ impl Default for OcrEngine {
         OcrEngine::Tesseract
     }
 }
+
+/// Secondary engine that re-runs OCR on a window when the primary engine's confidence
+/// (normalized to 0.0-1.0) is below `confidence_threshold`.
+#[derive(Clone, Debug, Copy)]
+pub struct OcrFallback {
+    pub engine: OcrEngine,
+    pub confidence_threshold: f64,
+}
+
 pub fn calculate_hash(image: &DynamicImage) -> u64 {
     let mut hasher = DefaultHasher::new();
     image.as_bytes().hash(&mut hasher);
@@ -56,7 +65,7 @@ pub async fn capture_screenshot(
 ) -> Result<
     (
         DynamicImage,
-        Vec<(DynamicImage, String, String, bool)>,
+        Vec<(DynamicImage, String, String, bool, Option<WindowBounds>)>,
         u64,
         Duration,
     ),
@@ -72,17 +81,19 @@ pub async fn capture_screenshot(
     let image_hash = calculate_hash(&image);
     let capture_duration = capture_start.elapsed();
 
-    let window_images = match capture_all_visible_windows(monitor, ignore_list, include_list).await
-    {
-        Ok(images) => images,
-        Err(e) => {
-            warn!(
-                "Failed to capture window images: {}. Continuing with empty result.",
-                e
-            );
-            Vec::new()
-        }
-    };
+    let window_images =
+        match capture_all_visible_windows(monitor, image.dimensions(), ignore_list, include_list)
+            .await
+        {
+            Ok(images) => images,
+            Err(e) => {
+                warn!(
+                    "Failed to capture window images: {}. Continuing with empty result.",
+                    e
+                );
+                Vec::new()
+            }
+        };
 
     Ok((image, window_images, image_hash, capture_duration))
 }
@@ -0,0 +1,34 @@
+use image::{DynamicImage, GenericImageView, Rgba};
+use screenpipe_vision::cursor::{draw_cursor, to_image_position, MousePosition};
+
+#[test]
+fn test_to_image_position() {
+    // second monitor right of the first, captured at twice its size
+    let position = to_image_position((2020, 300), (1920, 0), (1280, 800), (2560, 1600));
+    assert_eq!(position, Some(MousePosition { x: 200, y: 600 }));
+
+    assert_eq!(
+        to_image_position((100, 300), (1920, 0), (1280, 800), (2560, 1600)),
+        None
+    );
+    assert_eq!(
+        to_image_position((3200, 300), (1920, 0), (1280, 800), (1280, 800)),
+        None
+    );
+}
+
+#[test]
+fn test_draw_cursor() {
+    let mut image = DynamicImage::new_rgba8(20, 20);
+    draw_cursor(&mut image, MousePosition { x: 10, y: 5 });
+
+    assert_eq!(image.get_pixel(10, 5), Rgba([0, 0, 0, 255]));
+    assert_eq!(image.get_pixel(11, 7), Rgba([255, 255, 255, 255]));
+    assert_eq!(image.get_pixel(9, 5), Rgba([0, 0, 0, 0]));
+
+    // clipped at the edges instead of panicking
+    draw_cursor(&mut image, MousePosition { x: 15, y: 15 });
+    draw_cursor(&mut image, MousePosition { x: -5, y: -5 });
+    assert_eq!(image.get_pixel(19, 19), Rgba([0, 0, 0, 255]));
+    assert_eq!(image.get_pixel(0, 0), Rgba([0, 0, 0, 255]));
+}
@@ -0,0 +1,36 @@
+use screenpipe_vision::{idle_capture_interval, IdleStatus, MAX_IDLE_INTERVAL};
+use std::time::Duration;
+
+#[test]
+fn test_idle_status() {
+    let status = IdleStatus::new();
+    assert_eq!(status.idle_for(), None);
+
+    status.set_active(1);
+    status.set_active(2);
+    assert!(status.set_idle(1));
+    assert!(!status.set_idle(1));
+    // monitor 2 still records
+    assert_eq!(status.idle_for(), None);
+
+    assert!(status.set_idle(2));
+    assert!(status.idle_for().is_some());
+
+    assert!(status.set_active(1).is_some());
+    assert_eq!(status.set_active(1), None);
+    assert_eq!(status.idle_for(), None);
+}
+
+#[test]
+fn test_idle_capture_interval() {
+    let interval = Duration::from_millis(500);
+    assert_eq!(idle_capture_interval(interval, 0), interval);
+    assert_eq!(idle_capture_interval(interval, 1), Duration::from_secs(1));
+    assert_eq!(idle_capture_interval(interval, 2), Duration::from_secs(2));
+    assert_eq!(idle_capture_interval(interval, 10), MAX_IDLE_INTERVAL);
+    assert_eq!(idle_capture_interval(interval, u32::MAX), MAX_IDLE_INTERVAL);
+
+    // a rate already slower than the backoff is left alone
+    let slow = Duration::from_secs(60);
+    assert_eq!(idle_capture_interval(slow, 5), slow);
+}
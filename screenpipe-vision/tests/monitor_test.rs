@@ -0,0 +1,22 @@
+use screenpipe_vision::monitor::is_virtual_display_name;
+
+#[test]
+fn test_virtual_display_names() {
+    for name in ["VNC-0", "VIRTUAL1", "rdp0", "Dummy 1920x1080"] {
+        assert!(is_virtual_display_name(name), "{} should be virtual", name);
+    }
+    for name in [
+        "eDP-1",
+        "HDMI-A-1",
+        "DELL U2720Q",
+        "Built-in Retina Display",
+        "Sidecar Display",
+        "AirPlay Display",
+    ] {
+        assert!(
+            !is_virtual_display_name(name),
+            "{} should be physical",
+            name
+        );
+    }
+}
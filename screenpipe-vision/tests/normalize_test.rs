@@ -0,0 +1,19 @@
+use screenpipe_vision::normalize_ocr_text;
+
+#[test]
+fn test_normalize_ocr_text() {
+    assert_eq!(
+        normalize_ocr_text("\u{FB01}le e\u{FB03}cient"),
+        "file efficient"
+    );
+    // decomposed e + combining acute becomes the single precomposed character
+    assert_eq!(normalize_ocr_text("caf\u{0065}\u{0301}"), "caf\u{00E9}");
+    assert_eq!(normalize_ocr_text("bell\u{0007}\u{0000}o"), "bello");
+    assert_eq!(
+        normalize_ocr_text("  first   line\t \r\nsecond\n\n\n\nthird  \n\n"),
+        "first line\nsecond\n\nthird"
+    );
+    // letters that look like ligatures are left alone
+    assert_eq!(normalize_ocr_text("Æsir"), "Æsir");
+    assert_eq!(normalize_ocr_text(" \n\t "), "");
+}
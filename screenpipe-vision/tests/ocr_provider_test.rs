@@ -0,0 +1,52 @@
+use async_trait::async_trait;
+use image::DynamicImage;
+use screenpipe_vision::{OcrEngine, OcrProvider};
+
+/// Reads the same text from every image with a confidence of 0-10.
+struct FixedOcr;
+
+#[async_trait]
+impl OcrProvider for FixedOcr {
+    async fn perform_ocr(
+        &self,
+        _image: &DynamicImage,
+    ) -> Result<(String, String, Option<f64>), std::io::Error> {
+        Ok(("hello".to_string(), "[]".to_string(), Some(7.0)))
+    }
+
+    fn normalize_confidence(&self, confidence: f64) -> f64 {
+        confidence / 10.0
+    }
+}
+
+#[tokio::test]
+async fn test_providers_are_used_through_the_trait() {
+    let providers: [&dyn OcrProvider; 2] = [&FixedOcr, OcrEngine::Tesseract.provider().unwrap()];
+    let (text, _, confidence) = providers[0]
+        .perform_ocr(&DynamicImage::new_rgb8(4, 4))
+        .await
+        .unwrap();
+    assert_eq!(text, "hello");
+    assert_eq!(providers[0].normalize_confidence(confidence.unwrap()), 0.7);
+    assert_eq!(providers[1].normalize_confidence(85.0), 0.85);
+}
+
+#[test]
+fn test_engines_normalize_confidence_through_their_provider() {
+    assert_eq!(OcrEngine::Tesseract.normalize_confidence(85.0), 0.85);
+    assert_eq!(OcrEngine::Tesseract.normalize_confidence(150.0), 1.0);
+    assert_eq!(OcrEngine::Unstructured.normalize_confidence(0.9), 0.9);
+    assert_eq!(OcrEngine::Unstructured.normalize_confidence(-1.0), 0.0);
+}
+
+#[cfg(target_os = "linux")]
+#[tokio::test]
+async fn test_native_engines_of_other_platforms_have_no_provider() {
+    for engine in [OcrEngine::AppleNative, OcrEngine::WindowsNative] {
+        assert!(engine.provider().is_none());
+        assert!(engine
+            .perform_ocr(&DynamicImage::new_rgb8(4, 4))
+            .await
+            .is_err());
+    }
+}
@@ -0,0 +1,11 @@
+use screenpipe_vision::should_run_ocr;
+
+#[test]
+fn test_should_run_ocr() {
+    let every_third: Vec<bool> = (0..6).map(|n| should_run_ocr(n, 3)).collect();
+    assert_eq!(every_third, vec![true, false, false, true, false, false]);
+
+    // 0 and 1 both mean every frame
+    assert!((0..4).all(|n| should_run_ocr(n, 1)));
+    assert!((0..4).all(|n| should_run_ocr(n, 0)));
+}
@@ -0,0 +1,44 @@
+use std::time::Instant;
+
+use image::DynamicImage;
+use screenpipe_vision::{MaxAverageFrame, OcrEngine, OcrPreprocess, OcrWorkers};
+use tokio::sync::mpsc;
+
+fn frame(frame_number: u64) -> MaxAverageFrame {
+    MaxAverageFrame {
+        image: DynamicImage::new_rgb8(8, 8),
+        window_images: Vec::new(),
+        image_hash: 0,
+        frame_number,
+        timestamp: Instant::now(),
+        average: 1.0,
+        cursor: None,
+        draw_cursor: false,
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_ocr_results_arrive_in_capture_order() {
+    let (result_tx, mut result_rx) = mpsc::channel(16);
+    let workers = OcrWorkers::new(4, None, result_tx);
+    // frames without ocr are done at once, before the ones submitted earlier
+    let run_ocr = |frame_number: u64| frame_number % 3 != 1;
+    for frame_number in 0..9 {
+        workers
+            .submit(
+                frame(frame_number),
+                run_ocr(frame_number),
+                false,
+                OcrEngine::Tesseract,
+                None,
+                OcrPreprocess::default(),
+            )
+            .await;
+    }
+
+    for frame_number in 0..9 {
+        let result = result_rx.recv().await.unwrap();
+        assert_eq!(result.frame_number, frame_number);
+        assert_eq!(result.ocr_skipped, !run_ocr(frame_number));
+    }
+}
@@ -0,0 +1,130 @@
+use image::{DynamicImage, GenericImageView, GrayImage, Luma};
+use screenpipe_vision::preprocess::{
+    binarize, deskew, estimate_skew, otsu_threshold, rotate, upscale,
+};
+use screenpipe_vision::{OcrPreprocess, PreprocessTransform};
+use std::collections::HashMap;
+
+/// Dark lines of "text" on a light page.
+fn page() -> GrayImage {
+    GrayImage::from_fn(400, 300, |_, y| {
+        if y % 20 < 4 && (40..260).contains(&y) {
+            Luma([30])
+        } else {
+            Luma([220])
+        }
+    })
+}
+
+#[test]
+fn test_otsu_threshold_splits_classes() {
+    let threshold = otsu_threshold(&page());
+    assert!((30..220).contains(&threshold), "threshold {}", threshold);
+}
+
+#[test]
+fn test_binarize() {
+    let binary = binarize(&DynamicImage::ImageLuma8(page())).to_luma8();
+    assert!(binary
+        .pixels()
+        .all(|pixel| pixel[0] == 0 || pixel[0] == 255));
+    assert_eq!(binary.get_pixel(0, 40)[0], 0);
+    assert_eq!(binary.get_pixel(0, 10)[0], 255);
+}
+
+#[test]
+fn test_upscale() {
+    let image = DynamicImage::ImageLuma8(page());
+    assert_eq!(upscale(&image, 2).dimensions(), (800, 600));
+}
+
+#[test]
+fn test_estimate_skew_and_deskew() {
+    assert!(estimate_skew(&page()).abs() < 0.3);
+
+    let skewed = rotate(&page(), 4.0, 220);
+    let skew = estimate_skew(&skewed);
+    assert!((skew - 4.0).abs() <= 0.5, "estimated {} degrees", skew);
+
+    let straightened = deskew(&DynamicImage::ImageLuma8(skewed)).to_luma8();
+    let skew = estimate_skew(&straightened);
+    assert!(skew.abs() <= 0.5, "{} degrees left after deskew", skew);
+}
+
+#[test]
+fn test_apply_times_enabled_steps() {
+    let image = DynamicImage::ImageLuma8(page());
+    assert!(!OcrPreprocess::default().is_enabled());
+    assert!(!OcrPreprocess {
+        upscale: Some(1),
+        ..Default::default()
+    }
+    .is_enabled());
+
+    let preprocess = OcrPreprocess {
+        upscale: Some(2),
+        binarize: true,
+        deskew: false,
+    };
+    let (processed, timings, transform) = preprocess.apply(&image);
+    assert_eq!(processed.dimensions(), (800, 600));
+    assert!(timings.upscale.is_some());
+    assert!(timings.binarize.is_some());
+    assert!(timings.deskew.is_none());
+    assert_eq!(
+        transform,
+        PreprocessTransform {
+            factor: 2,
+            skew_degrees: 0.0,
+            size: (800, 600),
+        }
+    );
+}
+
+#[test]
+fn test_restore_boxes() {
+    let ocr_box = |left: &str, top: &str, width: &str, height: &str| {
+        HashMap::from([
+            ("left".to_string(), left.to_string()),
+            ("top".to_string(), top.to_string()),
+            ("width".to_string(), width.to_string()),
+            ("height".to_string(), height.to_string()),
+            ("text".to_string(), "word".to_string()),
+        ])
+    };
+
+    // upscaled only, boxes shrink back by the factor
+    let upscaled = PreprocessTransform {
+        factor: 2,
+        skew_degrees: 0.0,
+        size: (800, 600),
+    };
+    let mut boxes = vec![ocr_box("100", "40", "60", "20")];
+    upscaled.restore_boxes(&mut boxes, false);
+    assert_eq!(boxes[0], ocr_box("50", "20", "30", "10"));
+    let mut boxes = vec![ocr_box("0.25", "0.5", "0.125", "0.25")];
+    upscaled.restore_boxes(&mut boxes, true);
+    assert_eq!(boxes[0], ocr_box("0.25", "0.5", "0.125", "0.25"));
+
+    // a box on a text line of the deskewed image lands on the skewed line it was read from
+    let skewed = rotate(&page(), 4.0, 220);
+    let (deskewed, _, transform) = OcrPreprocess {
+        upscale: None,
+        binarize: false,
+        deskew: true,
+    }
+    .apply(&DynamicImage::ImageLuma8(skewed.clone()));
+    let deskewed = deskewed.to_luma8();
+    let line_y = (0..deskewed.height())
+        .find(|&y| deskewed.get_pixel(200, y)[0] < 100)
+        .unwrap();
+    let mut boxes = vec![ocr_box("190", &line_y.to_string(), "20", "4")];
+    transform.restore_boxes(&mut boxes, false);
+    let field = |name: &str| boxes[0][name].parse::<u32>().unwrap();
+    let (left, top, width, height) = (field("left"), field("top"), field("width"), field("height"));
+    let center = (left + width / 2, top + height / 2);
+    assert!((180..=220).contains(&center.0), "center {:?}", center);
+    let dark =
+        (center.1.saturating_sub(3)..center.1 + 4).any(|y| skewed.get_pixel(center.0, y)[0] < 100);
+    assert!(dark, "no text line near {:?}", center);
+}
@@ -0,0 +1,36 @@
+use screenpipe_vision::{detect_script, parse_osd_script, script_lang};
+use unicode_script::Script;
+
+#[test]
+fn test_detect_script() {
+    assert_eq!(detect_script("Quarterly report 2024"), Some(Script::Latin));
+    assert_eq!(detect_script("会议记录 Q3 2024"), Some(Script::Han));
+    assert_eq!(detect_script("Привет, мир!"), Some(Script::Cyrillic));
+    // digits and punctuation alone say nothing about the language
+    assert_eq!(detect_script("12:30 - 14:00"), None);
+    assert_eq!(detect_script(""), None);
+}
+
+#[test]
+fn test_script_lang() {
+    assert_eq!(script_lang(Script::Latin), Some("eng"));
+    assert_eq!(script_lang(Script::Han), Some("chi_sim"));
+    assert_eq!(script_lang(Script::Katakana), Some("jpn"));
+    assert_eq!(script_lang(Script::Hangul), Some("kor"));
+    assert_eq!(script_lang(Script::Ogham), None);
+}
+
+#[test]
+fn test_parse_osd_script() {
+    let osd = "Page number: 0\nOrientation in degrees: 0\nRotate: 0\nOrientation confidence: 2.15\nScript: Han\nScript confidence: 1.67\n";
+    assert_eq!(parse_osd_script(osd), Some(Script::Han));
+    assert_eq!(
+        parse_osd_script("Script: Cyrillic\n"),
+        Some(Script::Cyrillic)
+    );
+    assert_eq!(parse_osd_script("Script: Fraktur\n"), None);
+    assert_eq!(
+        parse_osd_script("Too few characters. Skipping this page"),
+        None
+    );
+}
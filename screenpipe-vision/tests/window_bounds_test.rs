@@ -0,0 +1,45 @@
+use screenpipe_vision::{to_image_bounds, WindowBounds};
+
+#[test]
+fn test_to_image_bounds() {
+    // second monitor right of the first, frames captured at twice its size
+    let monitor_origin = (1920, 0);
+    let monitor_size = (1440, 900);
+    let image_size = (2880, 1800);
+    assert_eq!(
+        to_image_bounds(
+            (2020, 50),
+            (400, 300),
+            monitor_origin,
+            monitor_size,
+            image_size
+        ),
+        Some(WindowBounds {
+            x: 200,
+            y: 100,
+            width: 800,
+            height: 600,
+        })
+    );
+    // hanging over the left edge of the monitor
+    assert_eq!(
+        to_image_bounds(
+            (1820, 0),
+            (200, 100),
+            monitor_origin,
+            monitor_size,
+            image_size
+        ),
+        Some(WindowBounds {
+            x: 0,
+            y: 0,
+            width: 200,
+            height: 200,
+        })
+    );
+    // on the first monitor
+    assert_eq!(
+        to_image_bounds((0, 0), (800, 600), monitor_origin, monitor_size, image_size),
+        None
+    );
+}
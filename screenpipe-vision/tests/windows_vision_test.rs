@@ -33,6 +33,7 @@ mod tests {
             "test_app".to_string(),
             "test_window".to_string(),
             true,
+            None,
         )];
 
         let result = process_ocr_task(
@@ -45,6 +46,7 @@ mod tests {
             },
             false,
             &ocr_engine,
+            None,
         )
         .await;
 
@@ -72,6 +74,7 @@ mod tests {
             interval,
             save_text_files_flag,
             ocr_engine,
+            None,
             monitor,
             &[],
             &[],